@@ -1,11 +1,81 @@
 // connexion BD
 
-use sea_orm::{Database, DatabaseConnection, DbErr};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
 use std::env;
+use std::time::Duration;
+use tracing::info;
+
+// Valeurs par défaut du pool (le batch 2000+ symboles ouvre beaucoup de petites transactions)
+const DEFAULT_MAX_CONNECTIONS: u32 = 20;
+const DEFAULT_MIN_CONNECTIONS: u32 = 2;
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 30;
+
+/// Configuration effective du pool de connexions
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub connect_timeout_secs: u64,
+    pub acquire_timeout_secs: u64,
+    pub sqlx_logging: bool,
+    pub sqlx_log_level: log::LevelFilter,
+}
+
+impl PoolConfig {
+    /// Lit la config depuis DB_MAX_CONNECTIONS, DB_MIN_CONNECTIONS, DB_CONNECT_TIMEOUT,
+    /// DB_ACQUIRE_TIMEOUT (secondes) et DB_SQLX_LOG_LEVEL (off|error|warn|info|debug|trace)
+    pub fn from_env() -> Self {
+        let max_connections = env_or("DB_MAX_CONNECTIONS", DEFAULT_MAX_CONNECTIONS);
+        // min ne peut pas dépasser max
+        let min_connections = env_or("DB_MIN_CONNECTIONS", DEFAULT_MIN_CONNECTIONS).min(max_connections);
+
+        let sqlx_log_level = env::var("DB_SQLX_LOG_LEVEL")
+            .ok()
+            .and_then(|v| v.parse::<log::LevelFilter>().ok())
+            .unwrap_or(log::LevelFilter::Off);
+
+        Self {
+            max_connections,
+            min_connections,
+            connect_timeout_secs: env_or("DB_CONNECT_TIMEOUT", DEFAULT_CONNECT_TIMEOUT_SECS),
+            acquire_timeout_secs: env_or("DB_ACQUIRE_TIMEOUT", DEFAULT_ACQUIRE_TIMEOUT_SECS),
+            sqlx_logging: sqlx_log_level != log::LevelFilter::Off,
+            sqlx_log_level,
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse::<T>().ok())
+        .unwrap_or(default)
+}
 
 pub async fn establish_connection() -> Result<DatabaseConnection, DbErr> {
     let database_url = env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set in .env file");
 
-    Database::connect(&database_url).await
-}
\ No newline at end of file
+    let config = PoolConfig::from_env();
+
+    info!(
+        "DB pool: max={}, min={}, connect_timeout={}s, acquire_timeout={}s, sqlx_log={}",
+        config.max_connections,
+        config.min_connections,
+        config.connect_timeout_secs,
+        config.acquire_timeout_secs,
+        config.sqlx_log_level
+    );
+
+    let mut options = ConnectOptions::new(database_url);
+    options
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .sqlx_logging(config.sqlx_logging)
+        .sqlx_logging_level(config.sqlx_log_level);
+
+    Database::connect(options).await
+}
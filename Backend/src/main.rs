@@ -41,26 +41,121 @@ mod db;
 mod services;
 mod utils;
 mod middleware;
-use actix_web::{App, HttpServer, web};
+use actix_web::{App, HttpServer, middleware::from_fn, web};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
 
-    println!("🔌 Connecting to database...");
+    // Logs filtrés par RUST_LOG (défaut: info)
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    info!("Connecting to database...");
     let db = db::establish_connection()
         .await
         .expect("Failed to connect to database");
-    println!("✅ Database connected!");
+    info!("Database connected!");
+
+    // Mode benchmark : --bench-indicators [--symbols N] [--days M] (pas de serveur HTTP)
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(config) = services::indicator_bench::BenchConfig::from_args(&args) {
+        services::indicator_bench::run(&config, &db)
+            .await
+            .map_err(std::io::Error::other)?;
+        return Ok(());
+    }
+
+    // Purge périodique des verrouillages de login expirés (login_attempts_rust)
+    let purge_db = db.clone();
+    actix_web::rt::spawn(async move {
+        let attempts = services::login_attempt_service::LoginAttemptService::from_env();
+        let mut interval = actix_web::rt::time::interval(attempts.lockout_window().to_std().unwrap_or_default());
+        loop {
+            interval.tick().await;
+            match attempts.purge_stale(&purge_db).await {
+                Ok(purged) if purged > 0 => info!("Purged {} stale login attempts", purged),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to purge login attempts: {}", e),
+            }
+        }
+    });
+
+    // Purge périodique des tokens de reset / vérification expirés
+    if let Some(period) = services::token_cleanup_service::purge_interval_from_env() {
+        let purge_db = db.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(period);
+            loop {
+                interval.tick().await;
+                match services::token_cleanup_service::purge_tokens(&purge_db).await {
+                    Ok(report) => info!(
+                        "Purged expired tokens: {} password reset, {} email verification",
+                        report.password_reset_tokens, report.email_verification_tokens
+                    ),
+                    Err(e) => warn!("Failed to purge expired tokens: {}", e),
+                }
+            }
+        });
+    }
+
+    // HTTPS si TLS_CERT_PATH et TLS_KEY_PATH sont définis, HTTP en clair sinon (dev local)
+    let tls_config = match utils::tls::tls_paths_from_env()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?
+    {
+        Some(paths) => {
+            let config = utils::tls::load_server_config(&paths)?;
+            info!("TLS enabled (certificate: {})", paths.cert_path);
+            Some(config)
+        }
+        None => {
+            warn!("TLS disabled: TLS_CERT_PATH / TLS_KEY_PATH not set, serving plain HTTP");
+            None
+        }
+    };
+
+    let scheme = if tls_config.is_some() { "https" } else { "http" };
+    info!("Starting server on {}://127.0.0.1:8080", scheme);
 
-    println!("🚀 Starting server on http://127.0.0.1:8080");
+    let shutdown_timeout = services::shutdown::timeout_from_env();
 
-    HttpServer::new(move || {
+    // Compression gzip/br des réponses (HTTP_COMPRESSION=false pour la désactiver en debug)
+    let compression = middleware::compression::compression_enabled_from_env();
+    if !compression {
+        warn!("Response compression disabled (HTTP_COMPRESSION)");
+    }
+
+    // Signaux gérés par services::shutdown (drapeau pour les jobs longs + arrêt gracieux)
+    let server = HttpServer::new(move || {
         App::new()
+            .wrap(middleware::compression::compress(compression))
+            .wrap(from_fn(middleware::request_id::request_id_middleware))
             .app_data(web::Data::new(db.clone()))
             .configure(routes::configure_routes)
     })
-        .bind(("127.0.0.1", 8080))?
-        .run()
-        .await
+        .disable_signals()
+        .shutdown_timeout(shutdown_timeout);
+
+    let server = match tls_config {
+        Some(config) => server.bind_rustls_0_23(("127.0.0.1", 8080), config)?,
+        None => server.bind(("127.0.0.1", 8080))?,
+    }
+    .run();
+
+    let handle = server.handle();
+    actix_web::rt::spawn(async move {
+        services::shutdown::wait_for_signal().await;
+        info!("Shutting down: draining in-flight requests (timeout {}s)", shutdown_timeout);
+        services::shutdown::request();
+        handle.stop(true).await;
+    });
+
+    server.await?;
+    info!("Server stopped cleanly");
+    Ok(())
 }
\ No newline at end of file
@@ -1,83 +1,287 @@
-use actix_web::{dev::Payload, Error, FromRequest, HttpRequest, HttpResponse};
-use futures::future::{ready, Ready};
+use actix_web::{dev::Payload, web, Error, FromRequest, HttpRequest, HttpResponse};
+use futures::future::{ready, LocalBoxFuture};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait};
 use serde::{Deserialize, Serialize};
 
+use crate::models::users;
+use crate::services::api_key_service;
 use crate::utils::jwt;
 
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
 /// Structure qui contient les infos de l'utilisateur authentifié
 /// Utilisée comme extracteur dans les routes protégées
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthUser {
     pub user_id: i32,
     pub username: String,
+    /// Clé d'API utilisée (None = JWT)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<i32>,
+}
+
+impl AuthUser {
+    /// 403 {"code": "jwt_required"} si la requête est authentifiée par une clé d'API
+    /// (routes sensibles : gestion des clés, arrêt d'urgence)
+    pub fn jwt_required(&self, error: &str) -> Option<HttpResponse> {
+        self.api_key_id.map(|_| {
+            HttpResponse::Forbidden().json(serde_json::json!({
+                "error": error,
+                "code": "jwt_required"
+            }))
+        })
+    }
+}
+
+/// Utilisateur authentifié dont le compte a le rôle admin (users_rust.is_admin)
+/// Extracteur des routes admin : 401 sans authentification, 403 si le compte n'est pas admin
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+/// Réponse 401 / 403 de l'extracteur
+fn reject(response: HttpResponse) -> Error {
+    actix_web::error::InternalError::from_response("", response).into()
+}
+
+fn unauthorized(message: String) -> Error {
+    reject(HttpResponse::Unauthorized().json(serde_json::json!({ "error": message })))
 }
 
 /// Implémentation de FromRequest pour AuthUser
 /// Cela permet à Actix-Web d'extraire automatiquement AuthUser des requêtes
+/// Deux modes : Authorization: Bearer <jwt>, ou X-API-Key: <clé> (résolue en BD)
 impl FromRequest for AuthUser {
     type Error = Error;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        // 1. Extraire le header Authorization
-        let auth_header = match req.headers().get("Authorization") {
-            Some(header) => header,
-            None => {
-                let response = HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Missing Authorization header"
-                }));
-                return ready(Err(actix_web::error::InternalError::from_response(
-                    "",
-                    response,
-                ).into()));
+        match req.headers().get(API_KEY_HEADER) {
+            Some(key) => from_api_key(req, key.to_str().map(str::to_string)),
+            None => Box::pin(ready(from_bearer(req))),
+        }
+    }
+}
+
+impl FromRequest for AdminUser {
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let auth_user = AuthUser::from_request(req, payload);
+        let db = req.app_data::<web::Data<DatabaseConnection>>().cloned();
+
+        Box::pin(async move {
+            let auth_user = auth_user.await?;
+            let db = db.ok_or_else(|| {
+                reject(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Database not configured"
+                })))
+            })?;
+
+            match is_admin(db.get_ref(), auth_user.user_id).await {
+                Ok(true) => Ok(AdminUser(auth_user)),
+                Ok(false) => Err(reject(HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Admin role required",
+                    "code": "admin_required"
+                })))),
+                Err(e) => Err(reject(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": format!("Database error: {}", e)
+                })))),
             }
-        };
+        })
+    }
+}
 
-        // 2. Convertir le header en string
-        let auth_str = match auth_header.to_str() {
-            Ok(s) => s,
-            Err(_) => {
-                let response = HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": "Invalid Authorization header"
-                }));
-                return ready(Err(actix_web::error::InternalError::from_response(
-                    "",
-                    response,
-                ).into()));
+/// Rôle relu en BD à chaque requête (un retrait du rôle est immédiat, sans attendre l'expiration du JWT)
+async fn is_admin(db: &DatabaseConnection, user_id: i32) -> Result<bool, DbErr> {
+    Ok(users::Entity::find_by_id(user_id).one(db).await?.is_some_and(|u| u.is_admin))
+}
+
+/// Clé d'API : utilisateur propriétaire, méthode autorisée par le scope de la clé
+fn from_api_key(
+    req: &HttpRequest,
+    key: Result<String, actix_web::http::header::ToStrError>,
+) -> LocalBoxFuture<'static, Result<AuthUser, Error>> {
+    let db = req.app_data::<web::Data<DatabaseConnection>>().cloned();
+    let method = req.method().clone();
+    let path = req.path().to_string();
+
+    Box::pin(async move {
+        let key = key.map_err(|_| unauthorized("Invalid X-API-Key header".to_string()))?;
+        let db = db.ok_or_else(|| {
+            reject(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Database not configured"
+            })))
+        })?;
+
+        let owner = match api_key_service::authenticate(key.trim(), db.get_ref()).await {
+            Ok(Some(owner)) => owner,
+            Ok(None) => return Err(unauthorized("Invalid API key".to_string())),
+            Err(e) => {
+                return Err(reject(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": format!("Database error: {}", e)
+                }))));
             }
         };
 
-        // 3. Extraire le token (format: "Bearer <token>")
-        let token = if auth_str.starts_with("Bearer ") {
-            &auth_str[7..]
-        } else {
-            let response = HttpResponse::Unauthorized().json(serde_json::json!({
-                "error": "Invalid Authorization format (expected: Bearer <token>)"
-            }));
-            return ready(Err(actix_web::error::InternalError::from_response(
-                "",
-                response,
-            ).into()));
+        if !owner.scope.allows(&method, &path) {
+            return Err(reject(HttpResponse::Forbidden().json(serde_json::json!({
+                "error": "This API key is read-only",
+                "code": "api_key_read_only"
+            }))));
+        }
+
+        Ok(AuthUser {
+            user_id: owner.user_id,
+            username: owner.username,
+            api_key_id: Some(owner.key_id),
+        })
+    })
+}
+
+/// JWT : Authorization: Bearer <token>
+fn from_bearer(req: &HttpRequest) -> Result<AuthUser, Error> {
+    // 1. Extraire le header Authorization
+    let auth_header = req
+        .headers()
+        .get("Authorization")
+        .ok_or_else(|| unauthorized("Missing Authorization header".to_string()))?;
+
+    // 2. Convertir le header en string
+    let auth_str = auth_header
+        .to_str()
+        .map_err(|_| unauthorized("Invalid Authorization header".to_string()))?;
+
+    // 3. Extraire le token (format: "Bearer <token>")
+    let token = auth_str
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| unauthorized("Invalid Authorization format (expected: Bearer <token>)".to_string()))?;
+
+    // 4. Vérifier le token JWT
+    let claims = jwt::verify_token(token).map_err(|e| unauthorized(format!("Invalid token: {}", e)))?;
+
+    // 5. Créer et retourner AuthUser
+    Ok(AuthUser {
+        user_id: claims.sub,
+        username: claims.username,
+        api_key_id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::api_keys;
+    use crate::services::api_key_service::ApiKeyScope;
+    use actix_web::{http::Method, test, App};
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+    async fn whoami(auth_user: AuthUser) -> HttpResponse {
+        HttpResponse::Ok().json(auth_user)
+    }
+
+    async fn key_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table abonnement ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [schema.create_table_from_entity(users::Entity), schema.create_table_from_entity(api_keys::Entity)] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        users::ActiveModel {
+            id: Set(7),
+            username: Set("alice".to_string()),
+            email: Set("alice@example.com".to_string()),
+            email_verified: Set(true),
+            auto_post_realized_pnl: Set(false),
+            block_duplicate_trades: Set(false),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        db
+    }
+
+    #[actix_web::test]
+    async fn api_key_authenticates_its_owner_within_scope() {
+        let db = key_db().await;
+        let (_, read_key) = api_key_service::create(7, "reader", ApiKeyScope::Read, &db).await.unwrap();
+        let (_, write_key) = api_key_service::create(7, "writer", ApiKeyScope::ReadWrite, &db).await.unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .route("/whoami", web::get().to(whoami))
+                .route("/whoami", web::post().to(whoami))
+                .route("/api/trades/validate", web::post().to(whoami)),
+        )
+        .await;
+        let call = |method: Method, key: Option<&str>| {
+            let mut req = test::TestRequest::default().method(method).uri("/whoami");
+            if let Some(key) = key {
+                req = req.insert_header((API_KEY_HEADER, key.to_string()));
+            }
+            req.to_request()
         };
 
-        // 4. Vérifier le token JWT
-        let claims = match jwt::verify_token(token) {
-            Ok(claims) => claims,
-            Err(e) => {
-                let response = HttpResponse::Unauthorized().json(serde_json::json!({
-                    "error": format!("Invalid token: {}", e)
-                }));
-                return ready(Err(actix_web::error::InternalError::from_response(
-                    "",
-                    response,
-                ).into()));
+        let resp = test::call_service(&app, call(Method::GET, Some(&read_key))).await;
+        assert_eq!(resp.status(), 200);
+        let json: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!((json["user_id"].as_i64(), json["username"].as_str()), (Some(7), Some("alice")));
+        assert!(json["api_key_id"].is_number());
+
+        // Clé read-only : pas d'écriture ; clé read_write : OK
+        let resp = test::call_service(&app, call(Method::POST, Some(&read_key))).await;
+        assert_eq!(resp.status(), 403);
+        let resp = test::call_service(&app, call(Method::POST, Some(&write_key))).await;
+        assert_eq!(resp.status(), 200);
+
+        // POST sans écriture : permis à une clé read-only
+        let validate = test::TestRequest::post()
+            .uri("/api/trades/validate")
+            .insert_header((API_KEY_HEADER, read_key.clone()))
+            .to_request();
+        assert_eq!(test::call_service(&app, validate).await.status(), 200);
+
+        // Clé inconnue ou absence d'authentification : 401
+        let unknown = api_key_service::generate_key();
+        assert_eq!(test::call_service(&app, call(Method::GET, Some(&unknown))).await.status(), 401);
+        assert_eq!(test::call_service(&app, call(Method::GET, None)).await.status(), 401);
+    }
+
+    #[actix_web::test]
+    async fn admin_routes_require_the_admin_role() {
+        async fn admin_only(admin: AdminUser) -> HttpResponse {
+            HttpResponse::Ok().json(admin.0)
+        }
+
+        let db = key_db().await;
+        let (_, key) = api_key_service::create(7, "ops", ApiKeyScope::ReadWrite, &db).await.unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .route("/admin", web::post().to(admin_only)),
+        )
+        .await;
+        let call = |key: Option<&str>| {
+            let mut req = test::TestRequest::post().uri("/admin");
+            if let Some(key) = key {
+                req = req.insert_header((API_KEY_HEADER, key.to_string()));
             }
+            req.to_request()
         };
 
-        // 5. Créer et retourner AuthUser
-        ready(Ok(AuthUser {
-            user_id: claims.sub,
-            username: claims.username,
-        }))
+        assert_eq!(test::call_service(&app, call(None)).await.status(), 401);
+
+        let resp = test::call_service(&app, call(Some(&key))).await;
+        assert_eq!(resp.status(), 403);
+        let json: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(json["code"], "admin_required");
+
+        users::ActiveModel { id: Set(7), is_admin: Set(true), ..Default::default() }
+            .update(&db)
+            .await
+            .unwrap();
+        assert_eq!(test::call_service(&app, call(Some(&key))).await.status(), 200);
     }
-}
\ No newline at end of file
+}
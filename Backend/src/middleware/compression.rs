@@ -0,0 +1,93 @@
+use actix_web::middleware::{Compress, Condition};
+
+/// Variable d'environnement pour désactiver la compression (debug : réponses lisibles au proxy)
+pub const COMPRESSION_ENV: &str = "HTTP_COMPRESSION";
+
+/// Compression activée sauf si HTTP_COMPRESSION=false / 0 / off (défaut: activée)
+pub fn compression_enabled_from_env() -> bool {
+    std::env::var(COMPRESSION_ENV)
+        .map(|v| !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "off"))
+        .unwrap_or(true)
+}
+
+/// Middleware Compress d'actix : encodage négocié via l'Accept-Encoding du client
+/// (br, gzip, zstd, deflate), réponse inchangée si le client n'en annonce aucun.
+/// Utile surtout pour /stocks et /stocks/strategies (plusieurs milliers de symboles).
+pub fn compress(enabled: bool) -> Condition<Compress> {
+    Condition::new(enabled, Compress::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, test, web, App, HttpResponse};
+    use serde_json::{json, Value};
+    use std::io::Read;
+
+    /// Réponse du même ordre que GET /stocks : une entrée par symbole
+    async fn big_stock_list() -> HttpResponse {
+        let stocks: Vec<Value> = (0..2000)
+            .map(|i| json!({
+                "symbol": format!("SYM{}", i),
+                "name": format!("Company {}", i),
+                "currency": "USD",
+                "is_alive": "1",
+            }))
+            .collect();
+        HttpResponse::Ok().json(stocks)
+    }
+
+    #[actix_web::test]
+    async fn test_gzip_response_decodes_to_same_json() {
+        let app = test::init_service(
+            App::new()
+                .wrap(compress(true))
+                .route("/stocks", web::get().to(big_stock_list)),
+        )
+        .await;
+
+        let plain = test::call_service(&app, test::TestRequest::get().uri("/stocks").to_request()).await;
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        let plain_body = test::read_body(plain).await;
+
+        let gzipped = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/stocks")
+                .insert_header((header::ACCEPT_ENCODING, "gzip"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(gzipped.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        let gzipped_body = test::read_body(gzipped).await;
+        assert!(gzipped_body.len() * 5 < plain_body.len());
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&gzipped_body[..])
+            .read_to_end(&mut decoded)
+            .unwrap();
+        let decoded: Value = serde_json::from_slice(&decoded).unwrap();
+        let expected: Value = serde_json::from_slice(&plain_body).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[actix_web::test]
+    async fn test_disabled_compression_sends_identity() {
+        let app = test::init_service(
+            App::new()
+                .wrap(compress(false))
+                .route("/stocks", web::get().to(big_stock_list)),
+        )
+        .await;
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/stocks")
+                .insert_header((header::ACCEPT_ENCODING, "gzip, br"))
+                .to_request(),
+        )
+        .await;
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod request_id;
+pub mod compression;
 
-pub use auth::AuthUser;
\ No newline at end of file
+pub use auth::{AdminUser, AuthUser};
\ No newline at end of file
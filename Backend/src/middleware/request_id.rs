@@ -0,0 +1,83 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    Error, HttpMessage,
+};
+use std::time::Instant;
+use tracing::{info, info_span, Instrument};
+use uuid::Uuid;
+
+/// Header renvoyé au client pour corréler une réponse avec les logs serveur
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Identifiant unique de la requête, disponible dans les extensions
+/// (`req.extensions().get::<RequestId>()`) pour les handlers qui en ont besoin
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct RequestId(pub String);
+
+/// Middleware qui assigne un UUID à chaque requête et l'injecte dans un span tracing.
+/// Tous les logs émis pendant le traitement (routes, services, indicateurs) portent
+/// donc le request_id. Loggue method, path, status et latence à la fin de la requête.
+///
+/// Les logs restent filtrés par RUST_LOG (ex: RUST_LOG=info,Backend=debug).
+pub async fn request_id_middleware(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let request_id = Uuid::new_v4().to_string();
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let span = info_span!("request", request_id = %request_id, method = %method, path = %path);
+    let start = Instant::now();
+
+    let result = next.call(req).instrument(span.clone()).await;
+    let latency_ms = start.elapsed().as_millis();
+
+    let _enter = span.enter();
+    match result {
+        Ok(mut res) => {
+            info!(status = res.status().as_u16(), latency_ms, "{} {}", method, path);
+
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(res)
+        }
+        Err(e) => {
+            let status = e.as_response_error().status_code().as_u16();
+            info!(status, latency_ms, "{} {}", method, path);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{middleware::from_fn, test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_response_carries_request_id_header() {
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(request_id_middleware))
+                .route("/ping", web::get().to(|| async { HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let first = test::call_service(&app, test::TestRequest::get().uri("/ping").to_request()).await;
+        let second = test::call_service(&app, test::TestRequest::get().uri("/ping").to_request()).await;
+
+        let first_id = first.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+        let second_id = second.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+
+        assert!(Uuid::parse_str(&first_id).is_ok());
+        assert_ne!(first_id, second_id);
+    }
+}
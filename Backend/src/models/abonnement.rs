@@ -9,6 +9,8 @@ pub struct Model {
     pub name: String,
     pub price: Decimal,
     pub caracteristiques: Option<Json>,
+    pub max_strategies: Option<i32>, // NULL = illimité
+    pub max_symbols: Option<i32>,    // Symboles par stratégie, NULL = illimité
     pub created_at: Option<DateTime>,
 }
 
@@ -0,0 +1,69 @@
+// ============================================================================
+// MODÈLE : API KEYS
+// ============================================================================
+//
+// Description:
+//   Modèle de la table api_keys_rust : clés d'API personnelles pour scripter
+//   contre son propre compte sans JWT. Gérées par /api/auth/api-keys
+//   (api_key_service) et acceptées par l'extracteur AuthUser via X-API-Key.
+//
+// Colonnes de la table api_keys_rust:
+//   - id (INTEGER, PRIMARY KEY, SERIAL)
+//   - user_id (INTEGER, NOT NULL, FK vers users_rust ON DELETE CASCADE)
+//   - name (VARCHAR(100), NOT NULL) - libellé choisi par l'utilisateur
+//   - key_prefix (VARCHAR(12), NOT NULL) - début de la clé, pour la reconnaître
+//   - key_hash (VARCHAR(64), UNIQUE, NOT NULL) - SHA-256 hex de la clé
+//   - scope (VARCHAR(16), NOT NULL) - 'read' | 'read_write'
+//   - created_at (TIMESTAMP, NOT NULL)
+//   - last_used_at (TIMESTAMP, NULL)
+//
+// Points d'attention:
+//   - La clé en clair n'est renvoyée qu'une fois, à la création ; seul son hash est stocké
+//   - Une clé 'read' n'accepte que GET / HEAD / OPTIONS
+//   - Révocation = suppression de la ligne
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "api_keys_rust")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+
+    pub user_id: i32,
+
+    pub name: String,
+
+    pub key_prefix: String,
+
+    #[sea_orm(unique)]
+    pub key_hash: String,
+
+    pub scope: String,
+
+    pub created_at: DateTime,
+
+    pub last_used_at: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::users::Entity",
+        from = "Column::UserId",
+        to = "super::users::Column::Id",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::users::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,48 @@
+// ============================================================================
+// MODÈLE : AUDIT LOG
+// ============================================================================
+//
+// Description:
+//   Modèle de la table audit_log_rust : journal des actions sensibles
+//   (qui a fait quoi, sur quelle entité, quand).
+//
+// Colonnes de la table audit_log_rust:
+//   - id (SERIAL, PRIMARY KEY)
+//   - user_id (INTEGER, NOT NULL) - auteur de l'action
+//   - action (VARCHAR, NOT NULL) - ex: "trade.delete"
+//   - entity (VARCHAR, NOT NULL) - ex: "trade"
+//   - entity_id (VARCHAR, NOT NULL) - id de l'entité concernée
+//   - details (JSONB, NULL) - snapshot / contexte de l'action
+//   - created_at (TIMESTAMP, NOT NULL)
+//
+// Points d'attention:
+//   - Table en ajout seul : jamais de UPDATE ni de DELETE
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "audit_log_rust")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+
+    pub user_id: i32,
+
+    pub action: String,
+
+    pub entity: String,
+
+    pub entity_id: String,
+
+    pub details: Option<Json>,
+
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
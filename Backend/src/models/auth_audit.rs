@@ -0,0 +1,50 @@
+// ============================================================================
+// MODÈLE : AUTH AUDIT
+// ============================================================================
+//
+// Description:
+//   Modèle de la table auth_audit_rust : journal des événements d'authentification
+//   (inscription, login réussi ou échoué, changement / reset de mot de passe,
+//   vérification d'email, connexion Google).
+//
+// Colonnes de la table auth_audit_rust:
+//   - id (SERIAL, PRIMARY KEY)
+//   - user_id (INTEGER, NULL) - NULL si le compte est inconnu (ex: login d'un username inexistant)
+//   - event_type (VARCHAR, NOT NULL) - ex: "login", "password_change", "google_sign_in"
+//   - success (BOOLEAN, NOT NULL)
+//   - ip (VARCHAR, NULL) - adresse de la connexion TCP
+//   - user_agent (VARCHAR, NULL) - tronqué à 512 caractères
+//   - created_at (TIMESTAMP, NOT NULL)
+//
+// Points d'attention:
+//   - Table en ajout seul : jamais de UPDATE ni de DELETE
+//   - Index conseillé : (user_id, created_at DESC)
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "auth_audit_rust")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+
+    pub user_id: Option<i32>,
+
+    pub event_type: String,
+
+    pub success: bool,
+
+    pub ip: Option<String>,
+
+    pub user_agent: Option<String>,
+
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
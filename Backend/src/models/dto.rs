@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use validator::Validate;
 use rust_decimal::Decimal;
 
+use crate::services::risk_service::PivotLevel;
+
 // ============================================
 // DTOs pour Stocks et Stratégies
 // ============================================
@@ -19,12 +21,114 @@ pub struct StockInfo {
     pub currency: Option<String>,
 }
 
+/// Vue détaillée d'un symbole : métadonnées, dernier OHLCV, derniers indicateurs,
+/// dernière recommandation de chaque stratégie
+#[derive(Debug, Serialize)]
+pub struct StockDetail {
+    pub stock: StockInfo,
+    pub is_alive: bool,
+    pub latest_price: Option<crate::models::historic_data::Model>,
+    pub indicators: Option<crate::models::indicator::Model>, // point_pivot déjà en JSON
+    pub strategies: Vec<StrategyWithResult>,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StockSearchResult {
+    pub symbol: String,
+    pub company_name: String,
+    pub currency: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct StrategyWithResult {
     pub strategy_id: i32,
     pub strategy_name: Option<String>,
     pub date: Option<String>,
-    pub recommendation: Option<String>,
+    pub recommendation: Option<String>, // Signal : "BUY" | "SELL" | "HOLD"
+    pub confidence: Option<f64>,        // 0.0-1.0, None pour les anciens résultats sans confidence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<i64>,          // Âge du résultat en jours (positions ouvertes uniquement)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale: Option<bool>,            // true si le résultat dépasse STALE_PRICE_MAX_DAYS
+}
+
+impl StrategyWithResult {
+    /// Construit la réponse à partir d'un résultat stocké (forme {signal, confidence} ou ancienne forme)
+    pub fn from_result(result: crate::models::strategy_result::Model, strategy_name: Option<String>) -> Self {
+        let signal = result
+            .recommendation
+            .as_ref()
+            .and_then(crate::services::strategies::strategy_trait::read_signal);
+
+        Self {
+            strategy_id: result.strategy_id,
+            strategy_name,
+            date: result.date,
+            recommendation: signal.as_ref().map(|(s, _)| s.clone()),
+            confidence: signal.and_then(|(_, c)| c),
+            age_days: None,
+            stale: None,
+        }
+    }
+
+    /// Renseigne l'âge du résultat et le drapeau stale (voir data_quality::freshness)
+    pub fn with_freshness(mut self, today: chrono::NaiveDate, max_age_days: i64) -> Self {
+        let (age_days, stale) = crate::services::data_quality::freshness(self.date.as_deref(), today, max_age_days);
+        self.age_days = age_days;
+        self.stale = Some(stale);
+        self
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BatchRecommendationRequest {
+    #[validate(length(min = 1, max = 200))]
+    pub symbols: Vec<String>,
+    #[serde(default)]
+    pub strategy_ids: Vec<i32>, // vide = toutes les stratégies
+}
+
+/// Simulation d'une stratégie par défaut avec des seuils proposés (rien n'est sauvegardé)
+#[derive(Debug, Deserialize, Validate)]
+pub struct SimulateStrategyRequest {
+    pub strategy_type: String, // Voir strategy_service::SIMULATION_TYPES
+    #[serde(default)]
+    pub config: serde_json::Value, // Ex. RSI : {"buy_below": 40, "sell_above": 65}
+    #[serde(default)]
+    #[validate(length(max = 200))]
+    pub symbols: Vec<String>, // vide = tous les symboles actifs
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateStrategyRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub symbols: Vec<String>,
+    #[serde(default)]
+    pub strategy_config: serde_json::Value, // DSL JSON (V2)
+}
+
+/// Version du format d'export des stratégies (StrategyExport.format_version)
+pub const STRATEGY_EXPORT_VERSION: u32 = 1;
+
+/// Stratégie portable (GET /api/strategies/{id}/export, POST /api/strategies/import) :
+/// aucun id serveur, propriétaire ni date ; les seuils restent dans strategy_config
+#[derive(Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct StrategyExport {
+    pub format_version: u32,
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub symbols: Vec<String>,
+    #[serde(default)]
+    pub strategy_config: serde_json::Value, // DSL JSON sans la liste de symboles
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolRecommendations {
+    pub symbol: String,
+    pub strategies: Vec<StrategyWithResult>,
 }
 
 // ============================================
@@ -39,10 +143,23 @@ pub struct CreateTradeRequest {
     #[validate(custom(function = "validate_trade_type"))]
     pub trade_type: String,
 
-    #[validate(custom(function = "validate_positive_decimal"))]
+    #[validate(custom(function = "validate_trade_quantity"))]
     pub quantite: Decimal,
 
-    #[validate(custom(function = "validate_positive_decimal"))]
+    #[validate(custom(function = "validate_trade_price"))]
+    pub prix_unitaire: Decimal,
+
+    pub date: String,
+
+    // Optionnel : remplace la devise de la table stock (stock absent ou erroné)
+    #[serde(default)]
+    #[validate(custom(function = "validate_currency"))]
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ClosePositionRequest {
+    #[validate(custom(function = "validate_trade_price"))]
     pub prix_unitaire: Decimal,
 
     pub date: String,
@@ -58,6 +175,29 @@ pub struct TradeResponse {
     pub prix_unitaire: Decimal,
     pub prix_total: Decimal,
     pub date: String,
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<i32>, // Trade identique créé juste avant (POST /api/trades, mode avertissement)
+}
+
+/// Page de GET /api/trades : `total` = nombre de trades correspondant aux filtres (toutes pages)
+#[derive(Debug, Serialize)]
+pub struct TradePageResponse {
+    pub items: Vec<TradeResponse>,
+    pub total: u64,
+    pub limit: u64,
+    pub offset: u64,
+}
+
+/// Détail d'un trade : TradeResponse + état FIFO
+#[derive(Debug, Serialize)]
+pub struct TradeDetailResponse {
+    #[serde(flatten)]
+    pub trade: TradeResponse,
+    pub quantite_restante: Decimal,
+    // Achats uniquement : ids des trades fermés qui ont consommé ce lot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_trade_ids: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -73,12 +213,29 @@ pub struct OpenPositionWithRecommendationsResponse {
     pub quantite_totale: Decimal,
     pub prix_moyen: Decimal,
     pub current_price: Option<Decimal>,
+    pub price_date: Option<String>,     // Date du dernier close (None : current_price = prix moyen)
+    pub price_age_days: Option<i64>,    // Jours calendaires écoulés depuis price_date
+    pub stale: bool,                    // true si price_age_days > STALE_PRICE_MAX_DAYS (ou aucun close)
     pub pnl_dollars: Option<Decimal>,
     pub pnl_percentage: Option<f64>,
-    pub entry_date: Option<String>,
+    pub entry_date: Option<String>,       // Déprécié : identique à first_entry_date, gardé pour les anciens clients
+    pub first_entry_date: Option<String>, // Date du premier achat
+    pub avg_entry_date: Option<String>,   // Date moyenne pondérée par la quantité encore ouverte
+    pub trailing_stop: Option<Decimal>,   // Niveau du trailing stop (TRAILING_STOP_PCT sous le plus haut close)
+    pub trailing_stop_breached: Option<bool>, // true si le dernier close est sous le stop
+    pub nearest_support: Option<PivotLevel>,    // Niveau pivot le plus proche sous le prix actuel
+    pub nearest_resistance: Option<PivotLevel>, // Niveau pivot le plus proche au-dessus
     pub strategies: Vec<StrategyWithResult>,
 }
 
+/// Position ouverte à revoir (GET /api/trades/attention) et pourquoi
+#[derive(Serialize)]
+pub struct PositionAttentionResponse {
+    #[serde(flatten)]
+    pub position: OpenPositionWithRecommendationsResponse,
+    pub reasons: Vec<String>, // Ex. "SELL from RSI", "P&L -12.50% below -10%"
+}
+
 #[derive(Debug, Serialize)]
 pub struct ClosedTradeResponse {
     pub symbol: String,
@@ -86,18 +243,143 @@ pub struct ClosedTradeResponse {
     pub prix_achat: String,
     pub date_vente: String,
     pub prix_vente: String,
-    pub pourcentage_gain: i32,
+    pub pourcentage_gain: Decimal, // 2 décimales (ex: 6.40)
     pub gain_dollars: Decimal,
-    pub temps_jours: i32,
+    pub temps_jours: i32, // Séances de bourse entre achat et vente
     pub trade_achat_id: i32,
     pub trade_vente_id: i32,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ClosePositionResponse {
+    pub trade: TradeResponse,
+    pub closed_trades: Vec<ClosedTradeResponse>,
+}
+
+/// Vente hypothétique à simuler en FIFO (POST /api/trades/preview-sale)
+#[derive(Debug, Deserialize, Validate)]
+pub struct PreviewSaleRequest {
+    #[validate(length(min = 1))]
+    pub symbol: String,
+
+    #[validate(custom(function = "validate_trade_quantity"))]
+    pub quantite: Decimal,
+
+    #[validate(custom(function = "validate_trade_price"))]
+    pub prix_unitaire: Decimal,
+
+    pub date: String,
+}
+
+/// Lot d'achat que consommerait la vente simulée
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SalePreviewLot {
+    pub trade_achat_id: i32,
+    pub date_achat: String,
+    pub quantite: Decimal,         // Quantité fermée sur ce lot
+    pub prix_achat: Decimal,
+    pub gain_dollars: Decimal,
+    pub pourcentage_gain: Decimal, // 2 décimales, comme trades_fermes
+    pub temps_jours: i32,          // Séances de bourse entre achat et vente
+}
+
+/// Aperçu FIFO d'une vente : lots consommés du plus ancien au plus récent, et totaux
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SalePreviewResponse {
+    pub symbol: String,
+    pub quantite: Decimal,
+    pub prix_vente: Decimal,
+    pub date_vente: String,
+    pub lots: Vec<SalePreviewLot>,
+    pub cost_basis: Decimal,       // Σ prix_achat × quantite des lots consommés
+    pub gain_dollars: Decimal,
+    pub pourcentage_gain: Decimal, // gain_dollars / cost_basis, 2 décimales
+}
+
+/// Résultat d'un dry-run de création de trade (rien n'est écrit)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TradeValidationResponse {
+    pub valid: bool,
+    pub reasons: Vec<String>,              // Vide si valid = true
+    pub estimated_fees: Decimal,
+    pub resulting_treasury: Option<Decimal>, // Trésorerie de la devise après le trade, None si devise inconnue
+    pub currency: Option<String>,
+}
+
+/// État FIFO d'un utilisateur (avant/après recalcul)
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FifoStateSummary {
+    pub closed_trades: usize,
+    pub total_gain: Decimal,
+    pub open_quantity: Decimal, // Somme des quantite_restante des achats
+}
+
+#[derive(Debug, Serialize)]
+pub struct FifoRecalculationResponse {
+    pub before: FifoStateSummary,
+    pub after: FifoStateSummary,
+    pub buy_lots_updated: usize,
+}
+
+// ============================================
+// DTOs pour l'arrêt d'urgence
+// ============================================
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetEmergencyPinRequest {
+    #[validate(length(min = 4, max = 12), custom(function = "validate_pin_digits"))]
+    pub pin: String,
+    pub current_pin: Option<String>, // Requis si un PIN existe déjà
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmergencyStopRequest {
+    pub pin: String,
+    pub armed: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmergencyStopStatus {
+    pub armed: bool,
+    pub pin_set: bool,
+    pub updated_at: Option<chrono::NaiveDateTime>,
+}
+
+/// Préférences de trading de l'utilisateur (GET / POST /api/trading/preferences)
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct TradingPreferences {
+    #[validate(range(min = 1, max = 1000))]
+    pub max_open_positions: Option<i32>, // None = pas de limite
+
+    #[serde(default)]
+    pub auto_post_realized_pnl: bool, // Reporter le P&L réalisé de chaque vente en gain / perte du wallet
+
+    #[serde(default)]
+    pub block_duplicate_trades: bool, // Refuser (409) un trade identique récent au lieu d'avertir
+}
+
+fn validate_pin_digits(value: &str) -> Result<(), validator::ValidationError> {
+    if value.chars().all(|c| c.is_ascii_digit()) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("pin_must_be_digits"))
+    }
+}
+
 fn validate_trade_type(value: &str) -> Result<(), validator::ValidationError> {
     if value == "achat" || value == "vente" {
         Ok(())
     } else {
-        Err(validator::ValidationError::new("invalid_trade_type"))
+        Err(validator::ValidationError::new("invalid_trade_type")
+            .with_message("must be 'achat' or 'vente'".into()))
+    }
+}
+
+fn validate_currency(value: &str) -> Result<(), validator::ValidationError> {
+    if crate::utils::currency::is_supported(&value.trim().to_uppercase()) {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("unsupported_currency"))
     }
 }
 
@@ -107,4 +389,85 @@ fn validate_positive_decimal(value: &Decimal) -> Result<(), validator::Validatio
     } else {
         Err(validator::ValidationError::new("must_be_positive"))
     }
-}
\ No newline at end of file
+}
+
+/// Bornes de saisie d'un trade : un ordre géant par faute de frappe est refusé avant même le contrôle des fonds
+const MAX_TRADE_QUANTITY: i64 = 1_000_000_000;
+const MAX_TRADE_PRICE: i64 = 10_000_000;
+const MAX_TRADE_DECIMALS: u32 = 8;
+
+/// > 0, ≤ max et au plus MAX_TRADE_DECIMALS décimales significatives (les zéros de fin ne comptent pas)
+fn validate_bounded_decimal(value: &Decimal, max: i64) -> Result<(), validator::ValidationError> {
+    validate_positive_decimal(value)?;
+
+    if *value > Decimal::from(max) {
+        return Err(validator::ValidationError::new("too_large")
+            .with_message(format!("must be at most {}", max).into()));
+    }
+    if value.normalize().scale() > MAX_TRADE_DECIMALS {
+        return Err(validator::ValidationError::new("too_many_decimals")
+            .with_message(format!("must have at most {} decimal places", MAX_TRADE_DECIMALS).into()));
+    }
+    Ok(())
+}
+
+fn validate_trade_quantity(value: &Decimal) -> Result<(), validator::ValidationError> {
+    validate_bounded_decimal(value, MAX_TRADE_QUANTITY)
+}
+
+fn validate_trade_price(value: &Decimal) -> Result<(), validator::ValidationError> {
+    validate_bounded_decimal(value, MAX_TRADE_PRICE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn request(quantite: &str, prix_unitaire: &str) -> CreateTradeRequest {
+        CreateTradeRequest {
+            symbol: "AAPL".to_string(),
+            trade_type: "achat".to_string(),
+            quantite: Decimal::from_str(quantite).unwrap(),
+            prix_unitaire: Decimal::from_str(prix_unitaire).unwrap(),
+            date: "2025-06-30".to_string(),
+            currency: None,
+        }
+    }
+
+    /// Champs en erreur → code de la première erreur
+    fn field_errors(req: &CreateTradeRequest) -> Vec<(String, String)> {
+        let mut errors: Vec<(String, String)> = match req.validate() {
+            Ok(()) => vec![],
+            Err(e) => e
+                .field_errors()
+                .into_iter()
+                .map(|(field, errs)| (field.to_string(), errs[0].code.to_string()))
+                .collect(),
+        };
+        errors.sort();
+        errors
+    }
+
+    #[test]
+    fn test_trade_bounds_are_inclusive() {
+        assert!(field_errors(&request("1000000000", "10000000")).is_empty());
+        assert!(field_errors(&request("0.00000001", "0.00000001")).is_empty());
+        // Zéros de fin ignorés
+        assert!(field_errors(&request("1.500000000000", "2.0000000000")).is_empty());
+    }
+
+    #[test]
+    fn test_trade_bounds_reject_giant_or_overprecise_values() {
+        assert_eq!(field_errors(&request("1000000000.00000001", "10")), vec![("quantite".to_string(), "too_large".to_string())]);
+        assert_eq!(field_errors(&request("10", "10000000.01")), vec![("prix_unitaire".to_string(), "too_large".to_string())]);
+        assert_eq!(
+            field_errors(&request("0.000000001", "1.123456789")),
+            vec![
+                ("prix_unitaire".to_string(), "too_many_decimals".to_string()),
+                ("quantite".to_string(), "too_many_decimals".to_string()),
+            ]
+        );
+        assert_eq!(field_errors(&request("0", "10")), vec![("quantite".to_string(), "must_be_positive".to_string())]);
+    }
+}
@@ -0,0 +1,49 @@
+// ============================================================================
+// MODÈLE : ARRÊT D'URGENCE (EMERGENCY STOP)
+// ============================================================================
+//
+// Description:
+//   Modèle de la table emergency_stop_rust : interrupteur d'arrêt d'urgence par
+//   utilisateur. Tant qu'il est armé, aucun ordre (trade manuel, broker, auto-trading)
+//   ne doit être passé.
+//
+// Colonnes de la table emergency_stop_rust:
+//   - user_id (INTEGER, PRIMARY KEY, FK users_rust.id)
+//   - pin_hash (VARCHAR, NOT NULL) - hash Argon2id du PIN (distinct du mot de passe)
+//   - armed (BOOLEAN, NOT NULL, DEFAULT false)
+//   - updated_at (TIMESTAMP, NOT NULL)
+//   - failed_pin_attempts (INTEGER, NOT NULL, DEFAULT 0) - échecs de PIN consécutifs
+//   - pin_locked_until (TIMESTAMP, NULL) - PIN refusé jusqu'à cette date après trop d'échecs
+//
+// Points d'attention:
+//   - Pas de ligne = PIN jamais défini = arrêt d'urgence désarmé
+//   - Le PIN n'est jamais stocké en clair
+//   - Le compteur d'échecs est remis à zéro à chaque PIN correct
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "emergency_stop_rust")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i32,
+
+    #[serde(skip_serializing)]
+    pub pin_hash: String,
+
+    pub armed: bool,
+
+    pub updated_at: DateTime,
+
+    pub failed_pin_attempts: i32,
+
+    pub pin_locked_until: Option<DateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -12,6 +12,7 @@ pub struct Model {
     pub high: Option<String>,
     pub low: Option<String>,
     pub close: Option<String>,
+    pub adjusted_close: Option<String>, // Close ajusté splits/dividendes (NULL si non fourni par la source)
     pub volume: Option<String>,
 }
 
@@ -1,8 +1,19 @@
 use serde::Serialize;
 use sea_orm::entity::prelude::*;
 
+use super::tables;
+
+/// Nom de table résolu par models::tables (indicators_rust, ou indicators_test sans prod-tables)
+#[derive(Copy, Clone, Default, Debug, DeriveEntity)]
+pub struct Entity;
+
+impl EntityName for Entity {
+    fn table_name(&self) -> &str {
+        tables::INDICATORS
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
-#[sea_orm(table_name = "indicators_rust")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub date: String,
@@ -14,6 +25,16 @@ pub struct Model {
     pub rsi25: Option<String>,
     pub stochastic14_7_7: Option<String>,
     pub point_pivot: Option<serde_json::Value>,
+    pub kc_upper: Option<String>,
+    pub kc_middle: Option<String>,
+    pub kc_lower: Option<String>,
+    pub roc12: Option<String>,
+    pub donchian_upper: Option<String>,
+    pub donchian_lower: Option<String>,
+    pub mfi14: Option<String>,
+    pub psar: Option<String>,
+    pub psar_trend: Option<String>, // "up" | "down"
+    pub trix15: Option<String>,     // % (4 décimales)
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,41 @@
+// ============================================================================
+// MODÈLE : LOGIN ATTEMPTS
+// ============================================================================
+//
+// Description:
+//   Modèle de la table login_attempts_rust qui persiste les échecs de login
+//   et le verrouillage des comptes (survit aux redémarrages du serveur).
+//
+// Colonnes de la table login_attempts_rust:
+//   - username (VARCHAR, PRIMARY KEY) - username tel que saisi au login
+//   - failed_attempts (INTEGER, NOT NULL, DEFAULT 0)
+//   - locked_until (TIMESTAMP, NULL) - NULL si pas verrouillé
+//   - last_failed_at (TIMESTAMP, NOT NULL)
+//
+// Points d'attention:
+//   - Clé = username saisi, même s'il n'existe pas (pas d'énumération de comptes)
+//   - Ligne supprimée au login réussi
+//   - Lignes plus vieilles que la fenêtre de verrouillage purgées périodiquement
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "login_attempts_rust")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub username: String,
+
+    pub failed_attempts: i32,
+
+    pub locked_until: Option<DateTime>,
+
+    pub last_failed_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -13,6 +13,7 @@
 //   - strategy_result : Résultats des stratégies calculées
 //   - historic_data : Données historiques OHLCV
 //   - indicator : Indicateurs techniques (RSI, EMA, etc.)
+//   - tables : Noms des tables indicators / strategy_results (feature prod-tables)
 //   - dto : Data Transfer Objects pour les réponses API
 //   - users : Utilisateurs (auth classique + OAuth Google)
 //   - password_reset_tokens : Tokens de reset password (expire 1h)
@@ -21,6 +22,12 @@
 //   - trade : Trades (achats/ventes)
 //   - trades_fermes : Historique trades fermés (FIFO)
 //   - abonnement : Plans d'abonnement (Free, Pro, etc.)
+//   - login_attempts : Échecs de login et verrouillage des comptes
+//   - emergency_stop : Arrêt d'urgence du trading par utilisateur (PIN hashé)
+//   - audit_log : Journal des actions sensibles (suppression de trade, etc.)
+//   - strategy_run : Historique d'exécution des stratégies par défaut (statut, erreurs)
+//   - auth_audit : Journal des événements d'authentification (logins, resets, OAuth)
+//   - watchlist : Symboles suivis par utilisateur (digest quotidien)
 //
 // Points d'attention:
 //   - Tous les modèles utilisent SeaORM (pas de SQL brut)
@@ -35,6 +42,7 @@ pub mod strategy;
 pub mod strategy_result;
 pub mod historic_data;
 pub mod indicator;
+pub mod tables;
 pub mod dto;
 pub mod users;
 pub mod password_reset_tokens;
@@ -42,4 +50,12 @@ pub mod email_verification_tokens;
 pub mod wallet;
 pub mod trade;
 pub mod trades_fermes;
-pub mod abonnement;
\ No newline at end of file
+pub mod abonnement;
+pub mod login_attempts;
+pub mod emergency_stop;
+pub mod audit_log;
+pub mod strategy_run;
+pub mod auth_audit;
+pub mod watchlist;
+pub mod api_keys;
+pub mod strategy_result_history;
\ No newline at end of file
@@ -1,6 +1,9 @@
 use serde::Serialize;
 use sea_orm::entity::prelude::*;
 
+// lot_size / min_lot : contraintes de la place de cotation, NULL = aucune contrainte
+//   ALTER TABLE stock ADD COLUMN lot_size NUMERIC, ADD COLUMN min_lot NUMERIC;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "stock")]
 pub struct Model {
@@ -10,6 +13,8 @@ pub struct Model {
     pub low_data: Option<String>,
     pub symbol_alphavantage: Option<String>,
     pub currency: Option<String>,
+    pub lot_size: Option<Decimal>, // Quantité d'un trade = multiple de lot_size (ex: 1 = actions entières)
+    pub min_lot: Option<Decimal>,  // Montant minimal d'un trade (quantité × prix unitaire)
 }
 
 //QUOI: definir les relations
@@ -30,4 +35,63 @@ impl Related<super::strategy_result::Entity> for Entity {
 }
 
 //définit des hooks (actions automatiques) avant/après insert/update/delete.
-impl ActiveModelBehavior for ActiveModel {}
\ No newline at end of file
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Indique si le symbole est encore coté (colonne is_alive)
+    /// NULL = jamais renseigné → considéré vivant pour ne rien exclure par défaut
+    pub fn is_tradable(&self) -> bool {
+        is_alive_value(self.is_alive.as_deref())
+    }
+}
+
+/// Interprète la valeur texte de is_alive ("false", "0", "no", "" = symbole mort)
+pub fn is_alive_value(value: Option<&str>) -> bool {
+    match value {
+        None => true,
+        Some(v) => !matches!(
+            v.trim().to_lowercase().as_str(),
+            "" | "false" | "f" | "0" | "no" | "n"
+        ),
+    }
+}
+
+/// Garde seulement les symboles vivants (skip les tickers délistés)
+pub fn alive_symbols(stocks: Vec<Model>) -> Vec<String> {
+    stocks
+        .into_iter()
+        .filter(|s| s.is_tradable())
+        .filter_map(|s| s.symbol_alphavantage)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock(symbol: &str, is_alive: Option<&str>) -> Model {
+        Model {
+            compagny_name: format!("{} Inc", symbol),
+            is_alive: is_alive.map(|s| s.to_string()),
+            low_data: None,
+            symbol_alphavantage: Some(symbol.to_string()),
+            currency: Some("USD".to_string()),
+            lot_size: None,
+            min_lot: None,
+        }
+    }
+
+    #[test]
+    fn test_dead_symbol_is_excluded() {
+        let stocks = vec![
+            stock("AAPL", Some("true")),
+            stock("DEAD", Some("false")),
+            stock("GONE", Some("0")),
+            stock("MSFT", None),
+        ];
+
+        let symbols = alive_symbols(stocks);
+
+        assert_eq!(symbols, vec!["AAPL".to_string(), "MSFT".to_string()]);
+    }
+}
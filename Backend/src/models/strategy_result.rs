@@ -1,11 +1,21 @@
 use serde::Serialize;
 use sea_orm::entity::prelude::*;
 
+use super::tables;
+
+/// Nom de table résolu par models::tables (strategy_results_rust, ou strategy_results_test sans prod-tables)
+#[derive(Copy, Clone, Default, Debug, DeriveEntity)]
+pub struct Entity;
+
+impl EntityName for Entity {
+    fn table_name(&self) -> &str {
+        tables::STRATEGY_RESULTS
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
-//#[sea_orm(table_name = "strategy_results")]
-#[sea_orm(table_name = "strategy_results_rust")]
 pub struct Model {
-    #[sea_orm(primary_key)]
+    #[sea_orm(primary_key, auto_increment = false)] // Id de strategies_rust, une ligne par (strategy_id, symbol)
     pub strategy_id: i32,
     pub symbol: Option<String>,
     pub date: Option<String>,
@@ -0,0 +1,62 @@
+// ============================================================================
+// MODÈLE : HISTORIQUE DES RÉSULTATS DE STRATÉGIES
+// ============================================================================
+//
+// Description:
+//   Modèle de la table strategy_result_history_rust : une ligne par (stratégie,
+//   symbole, jour de bourse). strategy_results_rust ne garde que le dernier
+//   résultat de chaque couple ; cette table conserve les jours précédents pour
+//   GET /api/stocks/with-strategies?date=.
+//
+// Colonnes de la table strategy_result_history_rust:
+//   - strategy_id (INTEGER, PRIMARY KEY)
+//   - symbol (VARCHAR, PRIMARY KEY)
+//   - date (VARCHAR, PRIMARY KEY) - YYYY-MM-DD (date de bourse, MARKET_TZ)
+//   - recommendation (JSONB, NULL)
+//   - metadata (JSONB, NULL)
+//
+// Points d'attention:
+//   - Écrite par save_result en même temps que strategy_results_rust
+//   - Un recalcul le même jour remplace la ligne du jour
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+
+use super::strategy_result;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
+#[sea_orm(table_name = "strategy_result_history_rust")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub strategy_id: i32,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub symbol: String,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub date: String,
+
+    pub recommendation: Option<serde_json::Value>,
+
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Même forme que strategy_results_rust, pour réutiliser StrategyWithResult::from_result
+impl From<Model> for strategy_result::Model {
+    fn from(row: Model) -> Self {
+        Self {
+            strategy_id: row.strategy_id,
+            symbol: Some(row.symbol),
+            date: Some(row.date),
+            recommendation: row.recommendation,
+            metadata: row.metadata,
+        }
+    }
+}
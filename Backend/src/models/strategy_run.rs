@@ -0,0 +1,52 @@
+// ============================================================================
+// MODÈLE : STRATEGY RUNS
+// ============================================================================
+//
+// Description:
+//   Modèle de la table strategy_runs_rust : une ligne par exécution d'une
+//   stratégie par défaut dans execute_default_strategies.
+//
+// Colonnes de la table strategy_runs_rust:
+//   - id (SERIAL, PRIMARY KEY)
+//   - strategy_id (INTEGER, NOT NULL) - id de strategies_rust (1-7)
+//   - started_at (TIMESTAMP, NOT NULL)
+//   - finished_at (TIMESTAMP, NULL) - NULL tant que l'exécution est en cours
+//   - symbols_processed (INTEGER, NOT NULL)
+//   - recommendations_generated (INTEGER, NULL)
+//   - status (VARCHAR, NOT NULL) - "running" | "success" | "empty" | "failed" | "cancelled"
+//   - error (TEXT, NULL) - message d'erreur si status = "failed", raison de l'arrêt si "cancelled"
+//
+// Points d'attention:
+//   - "empty" = exécution terminée sans aucune recommandation (à surveiller)
+//   - Une ligne restée "running" = calcul interrompu (crash)
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "strategy_runs_rust")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+
+    pub strategy_id: i32,
+
+    pub started_at: DateTime,
+
+    pub finished_at: Option<DateTime>,
+
+    pub symbols_processed: i32,
+
+    pub recommendations_generated: Option<i32>,
+
+    pub status: String,
+
+    pub error: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,56 @@
+// ============================================================================
+// NOMS DES TABLES INDICATEURS / RÉSULTATS DE STRATÉGIES
+// ============================================================================
+//
+// Description:
+//   Seul endroit où sont nommées indicators_* et strategy_results_*. Les
+//   entités SeaORM (indicator, strategy_result) et le batch INSERT sqlx de
+//   IndicatorService lisent ces constantes.
+//
+// Feature Cargo prod-tables (activée par défaut):
+//   - activée : indicators_rust / strategy_results_rust (PROD)
+//   - désactivée : indicators_test / strategy_results_test (DEV)
+//       cargo run --no-default-features
+//
+// ============================================================================
+
+/// true si le binaire écrit dans les tables de PROD
+pub const PROD_TABLES: bool = cfg!(feature = "prod-tables");
+
+pub const fn indicators(prod: bool) -> &'static str {
+    if prod { "indicators_rust" } else { "indicators_test" }
+}
+
+pub const fn strategy_results(prod: bool) -> &'static str {
+    if prod { "strategy_results_rust" } else { "strategy_results_test" }
+}
+
+/// Table des indicateurs de ce build
+pub const INDICATORS: &str = indicators(PROD_TABLES);
+
+/// Table des derniers résultats de stratégies de ce build
+pub const STRATEGY_RESULTS: &str = strategy_results(PROD_TABLES);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{indicator, strategy_result};
+    use sea_orm::EntityName;
+
+    #[test]
+    fn test_each_configuration_resolves_its_tables() {
+        assert_eq!((indicators(true), strategy_results(true)), ("indicators_rust", "strategy_results_rust"));
+        assert_eq!((indicators(false), strategy_results(false)), ("indicators_test", "strategy_results_test"));
+    }
+
+    #[test]
+    fn test_entities_use_the_resolved_tables() {
+        assert_eq!(indicator::Entity.table_name(), INDICATORS);
+        assert_eq!(strategy_result::Entity.table_name(), STRATEGY_RESULTS);
+
+        #[cfg(feature = "prod-tables")]
+        assert_eq!(INDICATORS, "indicators_rust");
+        #[cfg(not(feature = "prod-tables"))]
+        assert_eq!(INDICATORS, "indicators_test");
+    }
+}
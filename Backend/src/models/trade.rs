@@ -25,6 +25,18 @@ pub struct Model {
     // - Vente 30 AAPL  → Le trade d'achat devient: quantite=100, quantite_restante=70
     // - Vente 70 AAPL  → Le trade d'achat devient: quantite=100, quantite_restante=0
     pub quantite_restante: Decimal,
+
+    // Devise forcée à la saisie (positions étrangères suivies manuellement)
+    // NULL → devise déduite de la table stock
+    pub currency: Option<String>,
+
+    // Soft-delete : NULL = trade actif. Un trade supprimé est ignoré partout
+    // (positions, FIFO, wallet) mais conservé pour l'historique
+    pub deleted_at: Option<DateTime>,
+
+    // Horodatage de création (détection des doublons soumis deux fois), NULL pour les anciens trades
+    //   ALTER TABLE trade ADD COLUMN created_at TIMESTAMP;
+    pub created_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -43,4 +55,11 @@ impl Related<super::users::Entity> for Entity {
     }
 }
 
+impl Entity {
+    /// Trades non supprimés (à utiliser pour toute lecture de la table trade)
+    pub fn find_active() -> Select<Entity> {
+        Self::find().filter(Column::DeletedAt.is_null())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
\ No newline at end of file
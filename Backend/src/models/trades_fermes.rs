@@ -1,6 +1,11 @@
 use serde::{Serialize, Deserialize};
 use sea_orm::entity::prelude::*;
 
+// pourcentage_gain : NUMERIC(10, 2), 2 décimales (anciennement INTEGER arrondi à l'unité : 6.4 % → 6)
+// Migration de la colonne existante (les anciennes valeurs entières sont conservées telles quelles) :
+//   ALTER TABLE trades_fermes_rust
+//     ALTER COLUMN pourcentage_gain TYPE NUMERIC(10, 2) USING pourcentage_gain::NUMERIC(10, 2);
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "trades_fermes_rust")]
 pub struct Model {
@@ -12,9 +17,9 @@ pub struct Model {
     pub prix_achat: Option<String>,
     pub date_vente: Option<String>,
     pub prix_vente: Option<String>,
-    pub pourcentage_gain: Option<i32>,
+    pub pourcentage_gain: Option<Decimal>,
     pub gain_dollars: Option<Decimal>,
-    pub temps_jours: Option<i32>,
+    pub temps_jours: Option<i32>, // Séances de bourse (utils::trading_calendar), pas jours calendaires
     pub trade_achat_id: Option<i32>,
     pub trade_vente_id: Option<i32>,
 }
@@ -14,6 +14,13 @@
 //   - google_id (VARCHAR, UNIQUE, NULL)
 //   - email_verified (BOOLEAN, DEFAULT FALSE, NOT NULL)
 //   - abonnement_id (INTEGER, NULL, FK vers abonnements_rust)
+//   - max_open_positions (INTEGER, NULL) - nombre max de symboles détenus, NULL = illimité
+//   - auto_post_realized_pnl (BOOLEAN, DEFAULT FALSE, NOT NULL) - reporter le P&L des ventes dans le wallet
+//       ALTER TABLE users_rust ADD COLUMN auto_post_realized_pnl BOOLEAN NOT NULL DEFAULT FALSE;
+//   - block_duplicate_trades (BOOLEAN, DEFAULT FALSE, NOT NULL) - refuser (409) un trade identique récent
+//       ALTER TABLE users_rust ADD COLUMN block_duplicate_trades BOOLEAN NOT NULL DEFAULT FALSE;
+//   - is_admin (BOOLEAN, DEFAULT FALSE, NOT NULL) - accès aux routes /api/admin protégées par AdminUser
+//       ALTER TABLE users_rust ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
 //   - created_at (TIMESTAMP, DEFAULT CURRENT_TIMESTAMP)
 //   - updated_at (TIMESTAMP, DEFAULT CURRENT_TIMESTAMP)
 //
@@ -55,6 +62,21 @@ pub struct Model {
 
     pub abonnement_id: Option<i32>,
 
+    // Préférence de risque : au plus N symboles en position ouverte (NULL = pas de limite)
+    pub max_open_positions: Option<i32>,
+
+    // Préférence : chaque vente poste son P&L réalisé (FIFO) en gain / perte dans le wallet
+    #[sea_orm(default_value = false)]
+    pub auto_post_realized_pnl: bool,
+
+    // Préférence : un trade identique soumis dans la fenêtre de doublon est refusé (sinon simple avertissement)
+    #[sea_orm(default_value = false)]
+    pub block_duplicate_trades: bool,
+
+    // Rôle admin : attribué en BD uniquement (aucune route ne le modifie)
+    #[sea_orm(default_value = false)]
+    pub is_admin: bool,
+
     pub created_at: Option<DateTime>,
 
     pub updated_at: Option<DateTime>,
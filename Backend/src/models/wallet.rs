@@ -1,6 +1,14 @@
 use serde::{Serialize, Deserialize};
 use sea_orm::entity::prelude::*;
 
+// trade_vente_id : vente dont le P&L réalisé a été reporté automatiquement (NULL = saisie manuelle)
+//   ALTER TABLE wallet_rust ADD COLUMN trade_vente_id INTEGER UNIQUE;
+// account_type : compte réel ou compte papier (simulation), jamais mélangés dans les soldes
+//   ALTER TABLE wallet_rust ADD COLUMN account_type VARCHAR NOT NULL DEFAULT 'real';
+
+pub const REAL_ACCOUNT: &str = "real";
+pub const PAPER_ACCOUNT: &str = "paper";
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "wallet_rust")]
 pub struct Model {
@@ -12,6 +20,10 @@ pub struct Model {
     pub symbol: Option<String>, // NULL si ajout/retrait
     pub amount: Decimal,
     pub currency: String,    // 'CAD', 'USD', 'EUR'
+    #[sea_orm(unique)]
+    pub trade_vente_id: Option<i32>, // Une seule entrée gain / perte par vente
+    #[sea_orm(default_value = "real")]
+    pub account_type: String, // REAL_ACCOUNT | PAPER_ACCOUNT
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
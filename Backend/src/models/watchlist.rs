@@ -0,0 +1,40 @@
+// ============================================================================
+// MODÈLE : WATCHLIST
+// ============================================================================
+//
+// Description:
+//   Modèle de la table watchlist_rust : symboles suivis par un utilisateur sans
+//   position ouverte. Gérée par /api/watchlist (watchlist_service) et lue par le
+//   digest quotidien (GET /api/me/digest) avec les symboles détenus.
+//
+// Colonnes de la table watchlist_rust:
+//   - user_id (INTEGER, PRIMARY KEY, FK users_rust.id)
+//   - symbol (VARCHAR, PRIMARY KEY) - symbole en majuscules
+//   - added_at (TIMESTAMP, NOT NULL)
+//
+// Points d'attention:
+//   - Un symbole à la fois détenu et suivi n'apparaît qu'une fois dans le digest
+//   - Symbole vérifié dans stock à l'ajout (pas de FK : stock est indexé par compagny_name)
+//   - Au plus MAX_WATCHLIST_SYMBOLS (100) symboles par utilisateur
+//
+// ============================================================================
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "watchlist_rust")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub user_id: i32,
+
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub symbol: String,
+
+    pub added_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
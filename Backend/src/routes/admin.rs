@@ -5,33 +5,111 @@ StrategyService::execute_default_strategies()
 ↓
 ├─ 1. Récupère tous les symboles depuis table stock
 │
-├─ 2. IndicatorService::calculate_all_indicators()
+├─ 2. IndicatorService::calculate_all_indicators()  (sauté si skip_indicators)
 │      ↓
-│      ├─ Trouve last_date dans indicators_test
+│      ├─ Trouve last_date dans indicators_rust
 │      ├─ Récupère 365 jours historicdata
 │      ├─ Crée df_full (365 jours)
 │      ├─ Crée df_new_dates (dates > last_date)
 │      ├─ RSICalculator::calculate(df_new_dates, df_full)
 │      │    ├─ Calcule RSI sur df_full
 │      │    └─ Filtre pour retourner seulement df_new_dates avec rsi25
-│      └─ Sauvegarde dans indicators_test
+│      └─ Sauvegarde dans indicators_rust
 │
 └─ 3. MinMaxLastYear::calculate_batch()
-├─ Appelle stored procedure get_min_max_prices_last_year
-└─ Sauvegarde dans strategy_results_test
+├─ Appelle stored procedure get_min_max_prices_last_year (fallback SeaORM si absente)
+└─ Sauvegarde dans strategy_results_rust
 */
 
-use actix_web::{post, web, HttpResponse};
-use sea_orm::{DatabaseConnection, EntityTrait};
-use crate::services::strategy_service::StrategyService;
-use crate::models::stock::Entity as Stock;
-use crate::middleware::AuthUser;  // ← AJOUTE CETTE LIGNE
+use actix_web::{post, get, web, HttpResponse};
+use chrono::{Local, Duration};
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, Set, ActiveModelTrait};
+use serde::Deserialize;
+use tracing::info;
+use crate::services::strategy_service::{self, StrategyService, StrategySelection, DEFAULT_STRATEGIES};
+use crate::services::strategy_run_service;
+use crate::services::data_quality::{self, DataQualityService, SymbolDataQuality};
+use crate::services::data_import::{self, ImportBar, ImportError};
+use crate::services::token_cleanup_service;
+use crate::services::calculation_lock;
+use crate::services::calculation_jobs::{self, CancelError};
+use crate::services::auth_audit_service::{self, AuthAuditService};
+use crate::services::stock_onboarding::{self, NewStock, OnboardingError};
+use crate::models::stock::{self, Entity as Stock};
+use crate::middleware::AdminUser;
+
+#[derive(Deserialize)]
+pub struct SetAliveRequest {
+    pub is_alive: bool,
+}
+
+#[derive(Deserialize)]
+pub struct MissingIndicatorsQuery {
+    pub stale_days: Option<i64>, // défaut: 7 jours calendaires
+}
+
+#[derive(Deserialize)]
+pub struct ReplaceHistoryRequest {
+    pub bars: Vec<ImportBar>,
+}
+
+#[derive(Deserialize)]
+pub struct AuthAuditQuery {
+    pub user_id: Option<i32>, // absent = tous les utilisateurs
+    pub limit: Option<u64>,   // défaut 50, max 500
+}
+
+#[derive(Deserialize)]
+pub struct AddStocksRequest {
+    pub stocks: Vec<NewStock>,
+}
+
+#[derive(Deserialize)]
+pub struct DataGapsQuery {
+    pub max_gap_days: Option<i64>,
+    pub since: Option<String>, // YYYY-MM-DD, défaut: il y a 365 jours
+    pub symbol: Option<String>, // un seul symbole, tout son historique (since ignoré)
+}
+
+/// Body optionnel de POST /api/admin/strategies/calculate (absent = toutes les stratégies, indicateurs recalculés)
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CalculateStrategiesRequest {
+    pub strategies: Option<Vec<String>>, // types de SIMULATION_TYPES, exécutés dans cet ordre
+    #[serde(default)]
+    pub skip_indicators: bool,
+}
 
 #[post("/calculate")]
 pub async fn calculate_strategies(
-    _auth_user: AuthUser,  // ← AJOUTE CE PARAMÈTRE (protège la route)
+    _admin: AdminUser,
     db: web::Data<DatabaseConnection>,
+    body: web::Bytes,
 ) -> HttpResponse {
+    // 0. Sélection des stratégies (body vide = tout)
+    let request: CalculateStrategiesRequest = if body.iter().all(u8::is_ascii_whitespace) {
+        CalculateStrategiesRequest::default()
+    } else {
+        match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "success": false,
+                    "error": format!("Invalid body: {}", e)
+                }));
+            }
+        }
+    };
+    let selection = match StrategySelection::parse(request.strategies.as_deref(), request.skip_indicators) {
+        Ok(selection) => selection,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+
     // 1. Récupérer tous les symboles depuis la table stock
     let stocks = match Stock::find().all(db.get_ref()).await {
         Ok(stocks) => stocks,
@@ -43,11 +121,8 @@ pub async fn calculate_strategies(
         }
     };
 
-    // 2. Extraire les symboles (symbol_alphavantage)
-    let symbols: Vec<String> = stocks
-        .into_iter()
-        .filter_map(|stock| stock.symbol_alphavantage)
-        .collect();
+    // 2. Extraire les symboles (symbol_alphavantage) des stocks encore cotés
+    let symbols: Vec<String> = stock::alive_symbols(stocks);
 
     if symbols.is_empty() {
         return HttpResponse::BadRequest().json(serde_json::json!({
@@ -59,15 +134,50 @@ pub async fn calculate_strategies(
     // ⚠️ VERSION TEST : Un seul symbole hardcodé
     //let symbols = vec!["AAPL.TO".to_string()];
 
-    // 3. Exécuter les stratégies
+    // 3. Un seul calcul à la fois (409 si un autre est en cours, sur cette instance ou une autre)
+    let lock = match calculation_lock::try_acquire(db.get_ref()).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            return HttpResponse::Conflict().json(serde_json::json!({
+                "success": false,
+                "error": "calculation already in progress",
+                "job_id": calculation_jobs::current()
+            }));
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+
+    // 4. Exécuter les stratégies (job annulable via POST /api/admin/jobs/{id}/cancel)
+    let job_id = calculation_jobs::start();
     let service = StrategyService::new();
+    let outcome = service.execute_default_strategies(None, &selection, db.get_ref()).await;
+    let job = calculation_jobs::finish(job_id, outcome.as_ref().map(Vec::len).map_err(Clone::clone));
+    lock.release().await;
 
-    match service.execute_default_strategies(db.get_ref()).await {
+    if let Some(job) = job.filter(|j| j.status == calculation_jobs::JobStatus::Cancelled) {
+        return HttpResponse::Ok().json(serde_json::json!({
+            "success": false,
+            "status": "cancelled",
+            "job_id": job.id,
+            "processed": job.processed,
+            "error": job.error
+        }));
+    }
+
+    match outcome {
         Ok(results) => {
             HttpResponse::Ok().json(serde_json::json!({
                 "success": true,
+                "job_id": job_id,
                 "message": format!("Calculated strategies for {} symbols", symbols.len()),
                 "total_results": results.len(),
+                "strategies": selection.strategies,
+                "indicators_recomputed": !selection.skip_indicators,
                 "symbols_processed": symbols
             }))
         }
@@ -80,9 +190,479 @@ pub async fn calculate_strategies(
     }
 }
 
+/// POST /api/admin/strategies/seed - Crée / renomme les stratégies par défaut (ids utilisés par save_result)
+/// Idempotent : un second appel ne modifie rien
+#[post("/seed")]
+pub async fn seed_strategies(
+    _admin: AdminUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match strategy_service::seed_default_strategies(db.get_ref()).await {
+        Ok(report) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "inserted": report.inserted,
+            "updated": report.updated,
+            "unchanged": report.unchanged,
+            "conflicts": report.conflicts
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// GET /api/admin/strategies/status - Dernière exécution de chaque stratégie par défaut
+/// (status "empty" = aucune recommandation produite, "running" sans finished_at = interrompue)
+#[get("/status")]
+pub async fn get_strategies_status(
+    _admin: AdminUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let mut strategies = Vec::with_capacity(DEFAULT_STRATEGIES.len());
+
+    for (strategy_id, _, name) in DEFAULT_STRATEGIES {
+        match strategy_run_service::latest_run(strategy_id, db.get_ref()).await {
+            Ok(last_run) => strategies.push(serde_json::json!({
+                "strategy_id": strategy_id,
+                "name": name,
+                "last_run": last_run
+            })),
+            Err(e) => {
+                return HttpResponse::InternalServerError().json(serde_json::json!({
+                    "success": false,
+                    "error": format!("Failed to fetch strategy runs: {}", e)
+                }));
+            }
+        }
+    }
+
+    HttpResponse::Ok().json(serde_json::json!({ "strategies": strategies }))
+}
+
+/// POST /api/admin/stocks - Ajouter des symboles et lancer le calcul initial de leurs indicateurs (FLUX B)
+/// Tout ou rien : un doublon refuse toute la liste
+#[post("")]
+pub async fn add_stocks(
+    _admin: AdminUser,
+    body: web::Json<AddStocksRequest>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match stock_onboarding::onboard(&body.stocks, db.get_ref()).await {
+        Ok(summary) => HttpResponse::Created().json(serde_json::json!({
+            "success": true,
+            "added": summary.added,
+            "job_id": summary.job_id,
+            "scheduled": summary.job_id.is_some()
+        })),
+        Err(OnboardingError::Invalid(errors)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": "Invalid stocks",
+            "errors": errors
+        })),
+        Err(OnboardingError::Database(e)) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// POST /api/admin/stocks/{symbol}/alive - Marquer un symbole comme vivant ou délisté
+/// Les symboles morts sont ignorés par le calcul des indicateurs et des stratégies
+#[post("/{symbol}/alive")]
+pub async fn set_stock_alive(
+    _admin: AdminUser,
+    path: web::Path<String>,
+    body: web::Json<SetAliveRequest>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let symbol = path.into_inner();
+
+    let stock = match Stock::find()
+        .filter(stock::Column::SymbolAlphavantage.eq(&symbol))
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(stock)) => stock,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "success": false,
+                "error": format!("Stock not found: {}", symbol)
+            }));
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to fetch stock: {}", e)
+            }));
+        }
+    };
+
+    let mut active_model: stock::ActiveModel = stock.into();
+    active_model.is_alive = Set(Some(body.is_alive.to_string()));
+
+    match active_model.update(db.get_ref()).await {
+        Ok(updated) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "symbol": symbol,
+            "is_alive": updated.is_tradable()
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": format!("Failed to update stock: {}", e)
+        })),
+    }
+}
+
+/// GET /api/admin/stocks/currency-audit - Stocks à la devise NULL / non standard et symboles tradés absents de stock
+#[get("/currency-audit")]
+pub async fn get_currency_audit(
+    _admin: AdminUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match DataQualityService::currency_audit(db.get_ref()).await {
+        Ok(audit) => HttpResponse::Ok().json(audit),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// GET /api/admin/data/gaps - Lister les symboles avec des trous dans historicdata
+#[get("/gaps")]
+pub async fn get_data_gaps(
+    _admin: AdminUser,
+    query: web::Query<DataGapsQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let max_gap_days = query.max_gap_days.unwrap_or_else(data_quality::max_gap_days_from_env);
+
+    // Un seul symbole : tout son historique
+    if let Some(symbol) = query.symbol.as_deref() {
+        return match DataQualityService::find_gaps(symbol, max_gap_days, db.get_ref()).await {
+            Ok(gaps) => {
+                let report: Vec<_> = SymbolDataQuality::from_gaps(symbol.to_string(), gaps).into_iter().collect();
+                HttpResponse::Ok().json(serde_json::json!({
+                    "max_gap_days": max_gap_days,
+                    "since": null,
+                    "symbols_checked": 1,
+                    "symbols_with_gaps": report.len(),
+                    "symbols": report
+                }))
+            }
+            Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": e
+            })),
+        };
+    }
+
+    let since = query.since.clone().unwrap_or_else(|| {
+        (Local::now().naive_local().date() - Duration::days(365))
+            .format("%Y-%m-%d")
+            .to_string()
+    });
+
+    let stocks = match Stock::find().all(db.get_ref()).await {
+        Ok(stocks) => stocks,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to fetch stocks: {}", e)
+            }));
+        }
+    };
+    let symbols = stock::alive_symbols(stocks);
+
+    match DataQualityService::find_gaps_for_symbols(&symbols, &since, max_gap_days, db.get_ref()).await {
+        Ok(report) => HttpResponse::Ok().json(serde_json::json!({
+            "max_gap_days": max_gap_days,
+            "since": since,
+            "symbols_checked": symbols.len(),
+            "symbols_with_gaps": report.len(),
+            "symbols": report
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// POST /api/admin/data/{symbol}/replace - Remplacer toute la série historicdata d'un symbole (une transaction)
+#[post("/{symbol}/replace")]
+pub async fn replace_history(
+    admin: AdminUser,
+    path: web::Path<String>,
+    body: web::Json<ReplaceHistoryRequest>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let symbol = path.into_inner();
+    info!("{} is replacing the history of {} ({} bars)", admin.0.username, symbol, body.bars.len());
+
+    match data_import::replace_symbol_history(&symbol, &body.bars, db.get_ref()).await {
+        Ok(summary) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "summary": summary
+        })),
+        Err(ImportError::Invalid(e)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+        Err(ImportError::Database(e)) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": e
+        })),
+    }
+}
+
+/// GET /api/admin/indicators/missing - Symboles sans indicateurs ou aux indicateurs périmés
+#[get("/missing")]
+pub async fn get_missing_indicators(
+    _admin: AdminUser,
+    query: web::Query<MissingIndicatorsQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let stale_days = query
+        .stale_days
+        .filter(|d| *d >= 0)
+        .unwrap_or(data_quality::DEFAULT_STALE_INDICATOR_DAYS);
+
+    let stocks = match Stock::find().all(db.get_ref()).await {
+        Ok(stocks) => stocks,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to fetch stocks: {}", e)
+            }));
+        }
+    };
+    let symbols = stock::alive_symbols(stocks);
+
+    let latest = match DataQualityService::latest_indicator_dates(db.get_ref()).await {
+        Ok(latest) => latest,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+
+    let today = Local::now().naive_local().date();
+    let coverage = data_quality::classify_indicator_coverage(&symbols, &latest, today, stale_days);
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "stale_days": stale_days,
+        "symbols_checked": symbols.len(),
+        "missing_count": coverage.missing.len(),
+        "stale_count": coverage.stale.len(),
+        "missing": coverage.missing,
+        "stale": coverage.stale
+    }))
+}
+
+/// POST /api/admin/maintenance/purge-tokens - Supprimer les tokens de reset / vérification expirés
+#[post("/purge-tokens")]
+pub async fn purge_tokens(
+    _admin: AdminUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match token_cleanup_service::purge_tokens(db.get_ref()).await {
+        Ok(report) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "deleted": report
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": format!("Failed to purge tokens: {}", e)
+        })),
+    }
+}
+
+/// GET /api/admin/jobs/{id} - État d'un calcul (running / completed / failed / cancelled)
+#[get("/{id}")]
+pub async fn get_job(
+    _admin: AdminUser,
+    path: web::Path<u64>,
+) -> HttpResponse {
+    match calculation_jobs::get(path.into_inner()) {
+        Some(job) => HttpResponse::Ok().json(job),
+        None => HttpResponse::NotFound().json(serde_json::json!({
+            "success": false,
+            "error": "Job not found"
+        })),
+    }
+}
+
+/// POST /api/admin/jobs/{id}/cancel - Demander l'arrêt d'un calcul en cours
+/// Le calcul s'arrête au prochain point de contrôle (entre deux symboles) ; rien n'est annulé en BD
+#[post("/{id}/cancel")]
+pub async fn cancel_job(
+    _admin: AdminUser,
+    path: web::Path<u64>,
+) -> HttpResponse {
+    match calculation_jobs::cancel(path.into_inner()) {
+        Ok(job) => HttpResponse::Accepted().json(serde_json::json!({
+            "success": true,
+            "job": job
+        })),
+        Err(CancelError::NotFound) => HttpResponse::NotFound().json(serde_json::json!({
+            "success": false,
+            "error": "Job not found"
+        })),
+        Err(CancelError::NotRunning(status)) => HttpResponse::Conflict().json(serde_json::json!({
+            "success": false,
+            "error": "Job is not running",
+            "status": status
+        })),
+    }
+}
+
+/// GET /api/admin/auth/audit - Événements d'authentification récents, tous utilisateurs ou un seul
+#[get("/audit")]
+pub async fn get_auth_audit(
+    _admin: AdminUser,
+    query: web::Query<AuthAuditQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let limit = auth_audit_service::clamp_limit(query.limit);
+
+    match AuthAuditService::recent(db.get_ref(), query.user_id, limit).await {
+        Ok(events) => HttpResponse::Ok().json(serde_json::json!({
+            "count": events.len(),
+            "failed_count": events.iter().filter(|e| !e.success).count(),
+            "events": events
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "success": false,
+            "error": format!("Failed to fetch auth audit: {}", e)
+        })),
+    }
+}
+
 pub fn admin_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/admin/strategies")
             .service(calculate_strategies)
+            .service(seed_strategies)
+            .service(get_strategies_status)
+    );
+    cfg.service(
+        web::scope("/admin/stocks")
+            .service(add_stocks)
+            .service(get_currency_audit)
+            .service(set_stock_alive)
+    );
+    cfg.service(
+        web::scope("/admin/data")
+            .service(get_data_gaps)
+            .service(replace_history)
+    );
+    cfg.service(
+        web::scope("/admin/indicators")
+            .service(get_missing_indicators)
+    );
+    cfg.service(
+        web::scope("/admin/maintenance")
+            .service(purge_tokens)
+    );
+    cfg.service(
+        web::scope("/admin/auth")
+            .service(get_auth_audit)
+    );
+    cfg.service(
+        web::scope("/admin/jobs")
+            .service(get_job)
+            .service(cancel_job)
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::auth::API_KEY_HEADER;
+    use crate::models::{api_keys, users};
+    use crate::services::api_key_service::{self, ApiKeyScope};
+    use actix_web::{test, App};
+    use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema};
+
+    // Utilisateur 1 : compte normal, utilisateur 2 : admin ; une clé read_write chacun
+    async fn admin_db() -> (DatabaseConnection, String, String) {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(users::Entity),
+            schema.create_table_from_entity(api_keys::Entity),
+            schema.create_table_from_entity(Stock),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        for (id, username, is_admin) in [(1, "alice", false), (2, "root", true)] {
+            users::ActiveModel {
+                id: Set(id),
+                username: Set(username.to_string()),
+                email: Set(format!("{}@example.com", username)),
+                email_verified: Set(true),
+                auto_post_realized_pnl: Set(false),
+                block_duplicate_trades: Set(false),
+                is_admin: Set(is_admin),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        stock::ActiveModel {
+            compagny_name: Set("Apple".to_string()),
+            is_alive: Set(Some("true".to_string())),
+            symbol_alphavantage: Set(Some("AAPL".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let (_, user_key) = api_key_service::create(1, "alice", ApiKeyScope::ReadWrite, &db).await.unwrap();
+        let (_, admin_key) = api_key_service::create(2, "root", ApiKeyScope::ReadWrite, &db).await.unwrap();
+        (db, user_key, admin_key)
+    }
+
+    async fn is_alive(symbol: &str, db: &DatabaseConnection) -> Option<String> {
+        Stock::find()
+            .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+            .one(db)
+            .await
+            .unwrap()
+            .and_then(|stock| stock.is_alive)
+    }
+
+    #[actix_web::test]
+    async fn test_set_stock_alive_requires_admin() {
+        let (db, user_key, admin_key) = admin_db().await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(db.clone()))
+                .configure(admin_routes),
+        )
+        .await;
+        let call = |key: &str| {
+            test::TestRequest::post()
+                .uri("/admin/stocks/AAPL/alive")
+                .insert_header((API_KEY_HEADER, key.to_string()))
+                .set_json(serde_json::json!({"is_alive": false}))
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, call(&user_key)).await;
+        assert_eq!(resp.status(), 403);
+        let json: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(json["code"], "admin_required");
+        assert_eq!(is_alive("AAPL", &db).await.as_deref(), Some("true"));
+
+        let resp = test::call_service(&app, call(&admin_key)).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(is_alive("AAPL", &db).await.as_deref(), Some("false"));
+    }
+}
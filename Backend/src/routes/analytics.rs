@@ -0,0 +1,82 @@
+use actix_web::{get, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+use crate::middleware::AuthUser;
+use crate::services::analytics_service::{
+    self, DEFAULT_CORRELATION_DAYS, MAX_CORRELATION_DAYS, MAX_CORRELATION_SYMBOLS, MIN_CORRELATION_DAYS,
+};
+
+#[derive(Deserialize)]
+pub struct CorrelationQuery {
+    pub symbols: String,   // "AAPL,MSFT" (2 à MAX_CORRELATION_SYMBOLS, doublons ignorés)
+    pub days: Option<u32>, // Jours calendaires, défaut DEFAULT_CORRELATION_DAYS
+}
+
+impl CorrelationQuery {
+    /// Symboles (majuscules, dédoublonnés, ordre conservé) et fenêtre validés
+    fn parse(&self) -> Result<(Vec<String>, u32), String> {
+        let mut symbols: Vec<String> = Vec::new();
+        for symbol in self.symbols.split(',').map(|s| s.trim().to_uppercase()).filter(|s| !s.is_empty()) {
+            if !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+        if symbols.len() < 2 || symbols.len() > MAX_CORRELATION_SYMBOLS {
+            return Err(format!("symbols must list between 2 and {} distinct symbols", MAX_CORRELATION_SYMBOLS));
+        }
+
+        let days = self.days.unwrap_or(DEFAULT_CORRELATION_DAYS);
+        if !(MIN_CORRELATION_DAYS..=MAX_CORRELATION_DAYS).contains(&days) {
+            return Err(format!("days must be between {} and {}", MIN_CORRELATION_DAYS, MAX_CORRELATION_DAYS));
+        }
+
+        Ok((symbols, days))
+    }
+}
+
+/// GET /api/analytics/correlation - Corrélation des rendements journaliers entre symboles
+#[get("/correlation")]
+pub async fn get_correlation(
+    _auth_user: AuthUser,
+    query: web::Query<CorrelationQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let (symbols, days) = match query.parse() {
+        Ok(parsed) => parsed,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+
+    match analytics_service::correlation(&symbols, days, db.get_ref()).await {
+        Ok(matrix) => HttpResponse::Ok().json(matrix),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to compute correlation: {}", e)
+        })),
+    }
+}
+
+pub fn analytics_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/analytics")
+            .service(get_correlation)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(symbols: &str, days: Option<u32>) -> CorrelationQuery {
+        CorrelationQuery { symbols: symbols.to_string(), days }
+    }
+
+    #[test]
+    fn correlation_query_is_capped() {
+        assert_eq!(query(" aapl, MSFT,AAPL,", None).parse(), Ok((vec!["AAPL".to_string(), "MSFT".to_string()], 90)));
+        assert!(query("AAPL", None).parse().is_err());
+        assert!(query("AAPL,MSFT", Some(5000)).parse().is_err());
+
+        let too_many: Vec<String> = (0..=MAX_CORRELATION_SYMBOLS).map(|i| format!("S{}", i)).collect();
+        assert!(query(&too_many.join(","), None).parse().is_err());
+    }
+}
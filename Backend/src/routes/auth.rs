@@ -7,13 +7,19 @@
 //
 // Routes disponibles:
 //   - POST /api/auth/register : Créer un compte (1-1)
-//   - POST /api/auth/login : Se connecter
+//   - POST /api/auth/login : Se connecter (username ou email)
 //   - GET /api/auth/me : Vérifier son token JWT (protégée)
 //   - POST /api/auth/change-password : Changer mot de passe (protégée)
 //   - POST /api/auth/forgot-password : Demander reset password (2-1)
 //   - POST /api/auth/reset-password : Réinitialiser mot de passe avec token (2-2)
 //   - GET /api/auth/verify-email : Vérifier l'email avec token (apres register 1-2)
 //   - POST /api/auth/google : Authentification Google OAuth
+//   - GET /api/auth/audit : Activité d'authentification récente de l'utilisateur (protégée)
+//   - POST /api/auth/api-keys : Créer une clé d'API (protégée, JWT uniquement)
+//   - GET /api/auth/api-keys : Lister ses clés d'API (protégée, JWT uniquement)
+//   - DELETE /api/auth/api-keys/{id} : Révoquer une clé d'API (protégée, JWT uniquement)
+//
+// Chaque route trace ses succès / échecs dans auth_audit_rust (services::auth_audit_service)
 //
 // Dépendances:
 //   - actix_web : Framework web
@@ -25,7 +31,7 @@
 //
 // ============================================================================
 
-use actix_web::{post, get, web, HttpResponse};
+use actix_web::{post, get, delete, web, http::header, HttpRequest, HttpResponse};
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
 use chrono::{Utc, Duration};
@@ -35,7 +41,14 @@ use crate::models::users::{self, Entity as User};
 use crate::models::password_reset_tokens::{self, Entity as PasswordResetToken};
 use crate::models::email_verification_tokens::{self, Entity as EmailVerificationToken};
 use crate::utils::{jwt, password};
+use crate::utils::google::{self, GoogleVerifyError};
 use crate::middleware::auth::AuthUser;
+use crate::services::login_attempt_service::LoginAttemptService;
+use crate::services::auth_audit_service::{self, AuthAuditService, AuthEventType, AuthRequestInfo};
+use crate::services::subscription_service::{self, PlanUsage, SubscriptionService};
+use crate::services::wallet_service::WalletService;
+use crate::services::api_key_service::{self, ApiKeyError, ApiKeyScope, MAX_API_KEYS_PER_USER};
+use tracing::warn;
 
 #[derive(Deserialize)]
 pub struct RegisterRequest {
@@ -46,10 +59,22 @@ pub struct RegisterRequest {
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
-    pub username: String,
+    pub identifier: Option<String>, // Username ou email
+    pub username: Option<String>,   // Déprécié : ancien nom de identifier, gardé pour les anciens clients
     pub password: String,
 }
 
+impl LoginRequest {
+    /// identifier, sinon l'ancien champ username (None si aucun des deux n'est renseigné)
+    pub fn identifier(&self) -> Option<&str> {
+        self.identifier
+            .as_deref()
+            .or(self.username.as_deref())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+}
+
 #[derive(Serialize)]
 pub struct AuthResponse {
     pub token: String,
@@ -92,11 +117,43 @@ pub struct GoogleAuthRequest {
 }
 
 #[derive(Deserialize)]
-pub struct GoogleTokenInfo {
-    pub sub: String,        // Google ID unique
-    pub email: String,
-    pub name: Option<String>,
-    pub email_verified: Option<String>,
+pub struct AuthAuditQuery {
+    pub limit: Option<u64>, // défaut 50, max 500
+}
+
+#[derive(Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scope: Option<String>, // "read" (défaut) | "read_write"
+}
+
+/// IP de la connexion et User-Agent de la requête
+fn request_info(req: &HttpRequest) -> AuthRequestInfo {
+    AuthRequestInfo::new(
+        req.peer_addr().map(|addr| addr.ip().to_string()),
+        req.headers().get(header::USER_AGENT).and_then(|v| v.to_str().ok()),
+    )
+}
+
+/// Trace un événement d'authentification (best effort : un échec d'écriture ne bloque pas la route)
+async fn audit(
+    db: &DatabaseConnection,
+    info: &AuthRequestInfo,
+    event: AuthEventType,
+    user_id: Option<i32>,
+    success: bool,
+) {
+    if let Err(e) = AuthAuditService::record(db, event, user_id, success, info).await {
+        warn!("Failed to record {} auth event: {}", event.as_str(), e);
+    }
+}
+
+/// Amorce le compte papier d'un nouvel utilisateur : un échec n'empêche pas l'inscription,
+/// l'amorçage sera refait au premier accès au compte papier
+async fn seed_paper_wallet(db: &DatabaseConnection, user_id: i32) {
+    if let Err(e) = WalletService::seed_paper_wallet(db, user_id).await {
+        warn!("Failed to seed paper wallet for user {}: {}", user_id, e);
+    }
 }
 
 // ============================================================================
@@ -105,8 +162,11 @@ pub struct GoogleTokenInfo {
 #[post("/register")]
 pub async fn register(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     body: web::Json<RegisterRequest>,
 ) -> HttpResponse {
+    let info = request_info(&req);
+
     // Vérifier si username existe déjà
     let existing_user = User::find()
         .filter(users::Column::Username.eq(&body.username))
@@ -115,6 +175,7 @@ pub async fn register(
 
     match existing_user {
         Ok(Some(_)) => {
+            audit(db.get_ref(), &info, AuthEventType::Register, None, false).await;
             return HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "Username already exists"
             }));
@@ -135,6 +196,7 @@ pub async fn register(
 
     match existing_email {
         Ok(Some(_)) => {
+            audit(db.get_ref(), &info, AuthEventType::Register, None, false).await;
             return HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "Email already exists"
             }));
@@ -164,7 +226,7 @@ pub async fn register(
         email: Set(body.email.clone()),
         google_id: Set(None),
         email_verified: Set(false),
-        abonnement_id: Set(Some(1)),
+        abonnement_id: Set(Some(subscription_service::default_abonnement_id())),
         ..Default::default()
     };
 
@@ -199,6 +261,9 @@ pub async fn register(
     // TODO: Envoyer l'email de vérification avec le lien
     // https://votreapp.com/verify-email?token={verification_token}
 
+    seed_paper_wallet(db.get_ref(), user.id).await;
+    audit(db.get_ref(), &info, AuthEventType::Register, Some(user.id), true).await;
+
     // Générer JWT
     let token = match jwt::generate_token(user.id, &user.username) {
         Ok(token) => token,
@@ -227,20 +292,20 @@ pub async fn register(
 #[post("/login")]
 pub async fn login(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     body: web::Json<LoginRequest>,
 ) -> HttpResponse {
-    // Trouver le user
-    let user = match User::find()
-        .filter(users::Column::Username.eq(&body.username))
-        .one(db.get_ref())
-        .await
-    {
-        Ok(Some(user)) => user,
-        Ok(None) => {
-            return HttpResponse::Unauthorized().json(serde_json::json!({
-                "error": "Invalid credentials"
-            }));
-        }
+    let info = request_info(&req);
+
+    let Some(identifier) = body.identifier() else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "identifier (username or email) is required"
+        }));
+    };
+
+    // Trouver le user (par username ou email)
+    let user = match find_login_user(db.get_ref(), identifier).await {
+        Ok(user) => user,
         Err(e) => {
             return HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": format!("Database error: {}", e)
@@ -248,30 +313,70 @@ pub async fn login(
         }
     };
 
-    // Vérifier que le user a un password_hash (pas OAuth Google)
-    let password_hash = match &user.password_hash {
-        Some(hash) => hash,
-        None => {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "This account uses Google OAuth. Please login with Google."
-            }));
-        }
-    };
+    // Compteur d'échecs sur le username du compte, que l'on se connecte par username ou par email
+    // (identifiant tel quel si le compte n'existe pas)
+    let attempt_key = user.as_ref().map_or(identifier, |u| u.username.as_str()).to_string();
 
-    // Vérifier le mot de passe
-    let is_valid = match password::verify_password(&body.password, password_hash) {
-        Ok(valid) => valid,
+    // Compte verrouillé après trop d'échecs (état persisté dans login_attempts_rust)
+    let attempts = LoginAttemptService::from_env();
+    match attempts.check_locked(db.get_ref(), &attempt_key).await {
+        Ok(Some(locked_until)) => {
+            audit(db.get_ref(), &info, AuthEventType::Login, None, false).await;
+            return account_locked(locked_until);
+        }
+        Ok(None) => {}
         Err(e) => {
             return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Password verification error: {}", e)
+                "error": format!("Database error: {}", e)
             }));
         }
+    }
+
+    // Vérifier le mot de passe (réponse et temps identiques si le user n'existe pas)
+    let known_user_id = user.as_ref().map(|u| u.id);
+    let user = match authenticate(user, &body.password) {
+        Ok(user) => user,
+        Err(response) => {
+            audit(db.get_ref(), &info, AuthEventType::Login, known_user_id, false).await;
+            return match attempts.record_failure(db.get_ref(), &attempt_key).await {
+                Ok(attempt) => match attempt.locked_until {
+                    Some(locked_until) => account_locked(locked_until),
+                    None => *response,
+                },
+                Err(e) => {
+                    warn!("Failed to record login attempt for {}: {}", attempt_key, e);
+                    *response
+                }
+            };
+        }
     };
 
-    if !is_valid {
-        return HttpResponse::Unauthorized().json(serde_json::json!({
-            "error": "Invalid credentials"
-        }));
+    if let Err(e) = LoginAttemptService::record_success(db.get_ref(), &attempt_key).await {
+        warn!("Failed to clear login attempts for {}: {}", attempt_key, e);
+    }
+
+    let password_hash = user.password_hash.as_deref().unwrap_or_default();
+
+    // Email non vérifié → 403 sans token (si REQUIRE_EMAIL_VERIFICATION=true)
+    if let Err(response) = check_email_verified(&user, require_email_verification()) {
+        audit(db.get_ref(), &info, AuthEventType::Login, Some(user.id), false).await;
+        return *response;
+    }
+
+    // Migrer le hash si ancien schéma / moins d'itérations (PASSWORD_SCHEME, PBKDF2_ITERATIONS)
+    // Un échec ici ne bloque pas le login: le hash actuel reste valide
+    if password::needs_rehash(password_hash) {
+        match password::hash_password(&body.password) {
+            Ok(new_hash) => {
+                let mut active_model: users::ActiveModel = user.clone().into();
+                active_model.password_hash = Set(Some(new_hash));
+
+                if let Err(e) = active_model.update(db.get_ref()).await {
+                    warn!("Failed to upgrade password hash for user {}: {}", user.id, e);
+                }
+            }
+            Err(e) => warn!("Failed to rehash password for user {}: {}", user.id, e),
+        }
     }
 
     // Générer JWT
@@ -284,6 +389,8 @@ pub async fn login(
         }
     };
 
+    audit(db.get_ref(), &info, AuthEventType::Login, Some(user.id), true).await;
+
     HttpResponse::Ok().json(AuthResponse {
         token,
         user: UserInfo {
@@ -295,6 +402,90 @@ pub async fn login(
     })
 }
 
+/// User correspondant à l'identifiant de login : correspondance exacte sur le username en priorité,
+/// sinon sur l'email (un username qui serait aussi l'email d'un autre compte désigne son propriétaire)
+async fn find_login_user(db: &DatabaseConnection, identifier: &str) -> Result<Option<users::Model>, DbErr> {
+    let mut candidates = User::find()
+        .filter(
+            Condition::any()
+                .add(users::Column::Username.eq(identifier))
+                .add(users::Column::Email.eq(identifier)),
+        )
+        .limit(2)
+        .all(db)
+        .await?;
+
+    candidates.sort_by_key(|u| u.username != identifier);
+    Ok(candidates.into_iter().next())
+}
+
+/// Vérifie les identifiants d'un login
+/// User inconnu, compte Google OAuth (sans password_hash) ou mauvais mot de passe
+/// → même réponse "Invalid credentials", avec une vérification factice pour égaliser le temps
+fn authenticate(user: Option<users::Model>, password: &str) -> Result<users::Model, Box<HttpResponse>> {
+    let (user, password_hash) = match user {
+        Some(user) => match user.password_hash.clone() {
+            Some(hash) => (user, hash),
+            None => {
+                password::dummy_verify(password);
+                return Err(Box::new(invalid_credentials()));
+            }
+        },
+        None => {
+            password::dummy_verify(password);
+            return Err(Box::new(invalid_credentials()));
+        }
+    };
+
+    match password::verify_password(password, &password_hash) {
+        Ok(true) => Ok(user),
+        Ok(false) => Err(Box::new(invalid_credentials())),
+        Err(e) => {
+            warn!("Password verification error for user {}: {}", user.id, e);
+            Err(Box::new(invalid_credentials()))
+        }
+    }
+}
+
+/// 429 avec le délai avant déverrouillage (même réponse que le username existe ou non)
+fn account_locked(locked_until: chrono::NaiveDateTime) -> HttpResponse {
+    let retry_after = (locked_until - Utc::now().naive_utc()).num_seconds().max(1);
+
+    HttpResponse::TooManyRequests()
+        .insert_header(("Retry-After", retry_after.to_string()))
+        .json(serde_json::json!({
+            "error": "Too many failed login attempts, account temporarily locked",
+            "code": "account_locked",
+            "retry_after_seconds": retry_after
+        }))
+}
+
+/// REQUIRE_EMAIL_VERIFICATION=true : le login est refusé tant que l'email n'est pas vérifié
+fn require_email_verification() -> bool {
+    std::env::var("REQUIRE_EMAIL_VERIFICATION")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Refuse le login (403, code email_not_verified) si la vérification est exigée et pas faite
+/// Les comptes liés à Google sont exemptés (email déjà vérifié par Google)
+fn check_email_verified(user: &users::Model, required: bool) -> Result<(), Box<HttpResponse>> {
+    if !required || user.email_verified || user.google_id.is_some() {
+        return Ok(());
+    }
+
+    Err(Box::new(HttpResponse::Forbidden().json(serde_json::json!({
+        "error": "Email not verified",
+        "code": "email_not_verified"
+    }))))
+}
+
+fn invalid_credentials() -> HttpResponse {
+    HttpResponse::Unauthorized().json(serde_json::json!({
+        "error": "Invalid credentials"
+    }))
+}
+
 // ============================================================================
 // ME
 // ============================================================================
@@ -320,12 +511,31 @@ pub async fn get_current_user(
         }
     };
 
-    HttpResponse::Ok().json(serde_json::json!({
+    let usage = match SubscriptionService::usage_for_user(db.get_ref(), user.id, &user.username).await {
+        Ok(usage) => usage,
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Database error: {}", e)
+            }));
+        }
+    };
+
+    HttpResponse::Ok().json(me_response(&user, &usage))
+}
+
+/// Réponse de /me : champs historiques + plan et quotas
+fn me_response(user: &users::Model, usage: &PlanUsage) -> serde_json::Value {
+    serde_json::json!({
         "id": user.id,
         "username": user.username,
         "email": user.email,
         "email_verified": user.email_verified,
-    }))
+        "abonnement": usage.plan,
+        "limits": {
+            "strategies": usage.strategies,
+            "symbols_per_strategy": usage.symbols_per_strategy,
+        },
+    })
 }
 
 // ============================================================================
@@ -334,9 +544,12 @@ pub async fn get_current_user(
 #[post("/change-password")]
 pub async fn change_password(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     auth_user: AuthUser,
     body: web::Json<ChangePasswordRequest>,
 ) -> HttpResponse {
+    let info = request_info(&req);
+
     // Trouver le user
     let user = match User::find_by_id(auth_user.user_id)
         .one(db.get_ref())
@@ -376,6 +589,7 @@ pub async fn change_password(
     };
 
     if !is_valid {
+        audit(db.get_ref(), &info, AuthEventType::PasswordChange, Some(auth_user.user_id), false).await;
         return HttpResponse::Unauthorized().json(serde_json::json!({
             "error": "Current password is incorrect"
         }));
@@ -397,6 +611,7 @@ pub async fn change_password(
 
     match active_model.update(db.get_ref()).await {
         Ok(_) => {
+            audit(db.get_ref(), &info, AuthEventType::PasswordChange, Some(auth_user.user_id), true).await;
             HttpResponse::Ok().json(serde_json::json!({
                 "message": "Password changed successfully"
             }))
@@ -415,20 +630,19 @@ pub async fn change_password(
 #[post("/forgot-password")]
 pub async fn forgot_password(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     body: web::Json<ForgotPasswordRequest>,
 ) -> HttpResponse {
-    // Vérifier que l'email existe
+    let info = request_info(&req);
+
+    // Chercher le user par email
+    // La réponse est toujours la même, que l'email existe ou non (anti-énumération)
     let user = match User::find()
         .filter(users::Column::Email.eq(&body.email))
         .one(db.get_ref())
         .await
     {
-        Ok(Some(user)) => user,
-        Ok(None) => {
-            return HttpResponse::NotFound().json(serde_json::json!({
-                "error": "Email not found"
-            }));
-        }
+        Ok(user) => user,
         Err(e) => {
             return HttpResponse::InternalServerError().json(serde_json::json!({
                 "error": format!("Database error: {}", e)
@@ -436,6 +650,14 @@ pub async fn forgot_password(
         }
     };
 
+    let user = match user {
+        Some(user) => user,
+        None => {
+            audit(db.get_ref(), &info, AuthEventType::PasswordResetRequest, None, false).await;
+            return forgot_password_response();
+        }
+    };
+
     // Générer un token UUID v4
     let token = Uuid::new_v4().to_string();
 
@@ -452,21 +674,25 @@ pub async fn forgot_password(
     };
 
     // Insérer en BD
-    match new_token.insert(db.get_ref()).await {
-        Ok(_) => {
-            // TODO: Envoyer l'email ici avec le lien
-            // EN PRODUCTION: Ne pas renvoyer le token dans la réponse !
-            HttpResponse::Ok().json(serde_json::json!({
-                "message": "Password reset email sent. Check your inbox.",
-                "token": token  // ← À SUPPRIMER EN PRODUCTION
-            }))
-        }
-        Err(e) => {
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to create reset token: {}", e)
-            }))
-        }
+    if let Err(e) = new_token.insert(db.get_ref()).await {
+        warn!("Failed to create reset token for user {}: {}", user.id, e);
+        audit(db.get_ref(), &info, AuthEventType::PasswordResetRequest, Some(user.id), false).await;
+        return forgot_password_response();
     }
+
+    audit(db.get_ref(), &info, AuthEventType::PasswordResetRequest, Some(user.id), true).await;
+
+    // TODO: Envoyer l'email ici avec le lien contenant le token
+    // Le token n'est jamais renvoyé dans la réponse (sinon on révèle que l'email existe)
+
+    forgot_password_response()
+}
+
+/// Réponse unique de forgot-password, que l'email existe ou non
+fn forgot_password_response() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({
+        "message": "If an account exists for this email, a password reset link has been sent."
+    }))
 }
 
 // ============================================================================
@@ -475,8 +701,11 @@ pub async fn forgot_password(
 #[post("/reset-password")]
 pub async fn reset_password(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     body: web::Json<ResetPasswordRequest>,
 ) -> HttpResponse {
+    let info = request_info(&req);
+
     // Trouver le token dans la BD
     let reset_token = match PasswordResetToken::find()
         .filter(password_reset_tokens::Column::Token.eq(&body.token))
@@ -485,6 +714,7 @@ pub async fn reset_password(
     {
         Ok(Some(token)) => token,
         Ok(None) => {
+            audit(db.get_ref(), &info, AuthEventType::PasswordReset, None, false).await;
             return HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "Invalid or expired token"
             }));
@@ -498,6 +728,7 @@ pub async fn reset_password(
 
     // Vérifier que le token n'a pas déjà été utilisé
     if reset_token.used {
+        audit(db.get_ref(), &info, AuthEventType::PasswordReset, Some(reset_token.user_id), false).await;
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Token has already been used"
         }));
@@ -506,6 +737,7 @@ pub async fn reset_password(
     // Vérifier que le token n'est pas expiré
     let now = Utc::now().naive_utc();
     if reset_token.expires_at < now {
+        audit(db.get_ref(), &info, AuthEventType::PasswordReset, Some(reset_token.user_id), false).await;
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Token has expired"
         }));
@@ -549,6 +781,8 @@ pub async fn reset_password(
         }));
     }
 
+    audit(db.get_ref(), &info, AuthEventType::PasswordReset, Some(reset_token.user_id), true).await;
+
     // Marquer le token comme utilisé
     let mut token_active_model: password_reset_tokens::ActiveModel = reset_token.into();
     token_active_model.used = Set(true);
@@ -570,8 +804,11 @@ pub async fn reset_password(
 #[get("/verify-email")]
 pub async fn verify_email(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     query: web::Query<VerifyEmailQuery>,
 ) -> HttpResponse {
+    let info = request_info(&req);
+
     // Trouver le token dans la BD
     let verification_token = match EmailVerificationToken::find()
         .filter(email_verification_tokens::Column::Token.eq(&query.token))
@@ -580,6 +817,7 @@ pub async fn verify_email(
     {
         Ok(Some(token)) => token,
         Ok(None) => {
+            audit(db.get_ref(), &info, AuthEventType::EmailVerification, None, false).await;
             return HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "Invalid or expired verification token"
             }));
@@ -593,6 +831,7 @@ pub async fn verify_email(
 
     // Vérifier que le token n'a pas déjà été utilisé
     if verification_token.used {
+        audit(db.get_ref(), &info, AuthEventType::EmailVerification, Some(verification_token.user_id), false).await;
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Token has already been used"
         }));
@@ -601,6 +840,7 @@ pub async fn verify_email(
     // Vérifier que le token n'est pas expiré
     let now = Utc::now().naive_utc();
     if verification_token.expires_at < now {
+        audit(db.get_ref(), &info, AuthEventType::EmailVerification, Some(verification_token.user_id), false).await;
         return HttpResponse::BadRequest().json(serde_json::json!({
             "error": "Token has expired"
         }));
@@ -634,6 +874,8 @@ pub async fn verify_email(
         }));
     }
 
+    audit(db.get_ref(), &info, AuthEventType::EmailVerification, Some(verification_token.user_id), true).await;
+
     // Marquer le token comme utilisé
     let mut token_active_model: email_verification_tokens::ActiveModel = verification_token.into();
     token_active_model.used = Set(true);
@@ -655,37 +897,24 @@ pub async fn verify_email(
 #[post("/google")]
 pub async fn google_auth(
     db: web::Data<DatabaseConnection>,
+    req: HttpRequest,
     body: web::Json<GoogleAuthRequest>,
 ) -> HttpResponse {
-    // Vérifier le token Google auprès de l'API Google
-    let google_token_url = format!(
-        "https://oauth2.googleapis.com/tokeninfo?id_token={}",
-        body.id_token
-    );
+    let info = request_info(&req);
 
-    let client = reqwest::Client::new();
-    let google_response = match client.get(&google_token_url).send().await {
-        Ok(resp) => resp,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to verify Google token: {}", e)
+    // Vérifier le token Google auprès de l'API Google (retry sur erreurs transitoires)
+    let google_info = match google::verify_id_token(&body.id_token).await {
+        Ok(info) => info,
+        Err(GoogleVerifyError::InvalidToken | GoogleVerifyError::WrongAudience) => {
+            audit(db.get_ref(), &info, AuthEventType::GoogleSignIn, None, false).await;
+            return HttpResponse::Unauthorized().json(serde_json::json!({
+                "error": "Invalid Google token"
             }));
         }
-    };
-
-    // Vérifier que la réponse de Google est OK
-    if !google_response.status().is_success() {
-        return HttpResponse::Unauthorized().json(serde_json::json!({
-            "error": "Invalid Google token"
-        }));
-    }
-
-    // Parser les infos du user depuis Google
-    let google_info: GoogleTokenInfo = match google_response.json().await {
-        Ok(info) => info,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to parse Google response: {}", e)
+        Err(GoogleVerifyError::Unavailable(e)) => {
+            warn!("Google token verification unavailable: {}", e);
+            return HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": "Google authentication is temporarily unavailable, please retry"
             }));
         }
     };
@@ -708,6 +937,8 @@ pub async fn google_auth(
                 }
             };
 
+            audit(db.get_ref(), &info, AuthEventType::GoogleSignIn, Some(user.id), true).await;
+
             HttpResponse::Ok().json(serde_json::json!({
                 "token": token,
                 "user": UserInfo {
@@ -722,6 +953,15 @@ pub async fn google_auth(
         Ok(None) => {
             // CAS B: User n'existe pas → Créer le compte automatiquement
 
+            // Pas de création de compte sur un email que Google n'a pas vérifié
+            if !google_info.is_email_verified() {
+                audit(db.get_ref(), &info, AuthEventType::GoogleSignIn, None, false).await;
+                return HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Google account email is not verified",
+                    "code": "email_not_verified"
+                }));
+            }
+
             // Vérifier si l'email existe déjà (avec un autre compte)
             let existing_email = User::find()
                 .filter(users::Column::Email.eq(&google_info.email))
@@ -729,7 +969,8 @@ pub async fn google_auth(
                 .await;
 
             match existing_email {
-                Ok(Some(_)) => {
+                Ok(Some(existing)) => {
+                    audit(db.get_ref(), &info, AuthEventType::GoogleSignIn, Some(existing.id), false).await;
                     return HttpResponse::BadRequest().json(serde_json::json!({
                         "error": "Email already exists with a password account. Please login with your password."
                     }));
@@ -767,7 +1008,7 @@ pub async fn google_auth(
                 email: Set(google_info.email.clone()),
                 google_id: Set(Some(google_info.sub.clone())),
                 email_verified: Set(true),  // Google a déjà vérifié l'email
-                abonnement_id: Set(Some(1)),  // Free par défaut
+                abonnement_id: Set(Some(subscription_service::default_abonnement_id())),  // Free par défaut
                 ..Default::default()
             };
 
@@ -790,6 +1031,9 @@ pub async fn google_auth(
                 }
             };
 
+            seed_paper_wallet(db.get_ref(), user.id).await;
+            audit(db.get_ref(), &info, AuthEventType::GoogleSignIn, Some(user.id), true).await;
+
             HttpResponse::Ok().json(serde_json::json!({
                 "token": token,
                 "user": UserInfo {
@@ -809,6 +1053,98 @@ pub async fn google_auth(
     }
 }
 
+// ============================================================================
+// AUDIT
+// ============================================================================
+#[get("/audit")]
+pub async fn get_auth_audit(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    query: web::Query<AuthAuditQuery>,
+) -> HttpResponse {
+    let limit = auth_audit_service::clamp_limit(query.limit);
+
+    match AuthAuditService::recent(db.get_ref(), Some(auth_user.user_id), limit).await {
+        Ok(events) => HttpResponse::Ok().json(serde_json::json!({ "events": events })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+// ============================================================================
+// API KEYS
+// ============================================================================
+
+/// Les clés se gèrent avec un JWT : une clé (même read_write) ne peut pas en créer ni en révoquer
+fn jwt_required(auth_user: &AuthUser) -> Option<HttpResponse> {
+    auth_user.jwt_required("API keys must be managed with a JWT, not an API key")
+}
+
+#[post("/api-keys")]
+pub async fn create_api_key(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    body: web::Json<CreateApiKeyRequest>,
+) -> HttpResponse {
+    if let Some(response) = jwt_required(&auth_user) {
+        return response;
+    }
+
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > api_key_service::MAX_NAME_LENGTH {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("name must be 1 to {} characters", api_key_service::MAX_NAME_LENGTH)
+        }));
+    }
+    let Some(scope) = ApiKeyScope::parse(body.scope.as_deref().unwrap_or("read")) else {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "scope must be 'read' or 'read_write'"
+        }));
+    };
+
+    match api_key_service::create(auth_user.user_id, name, scope, db.get_ref()).await {
+        // La clé en clair n'est renvoyée qu'ici
+        Ok((api_key, key)) => HttpResponse::Created().json(serde_json::json!({ "api_key": api_key, "key": key })),
+        Err(ApiKeyError::LimitReached) => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": format!("At most {} API keys per user", MAX_API_KEYS_PER_USER),
+            "code": "api_key_limit_reached"
+        })),
+        Err(ApiKeyError::Database(e)) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Database error: {}", e)
+        })),
+    }
+}
+
+#[get("/api-keys")]
+pub async fn list_api_keys(db: web::Data<DatabaseConnection>, auth_user: AuthUser) -> HttpResponse {
+    if let Some(response) = jwt_required(&auth_user) {
+        return response;
+    }
+
+    match api_key_service::list(auth_user.user_id, db.get_ref()).await {
+        Ok(api_keys) => HttpResponse::Ok().json(serde_json::json!({ "api_keys": api_keys })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+#[delete("/api-keys/{id}")]
+pub async fn delete_api_key(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    if let Some(response) = jwt_required(&auth_user) {
+        return response;
+    }
+
+    match api_key_service::revoke(auth_user.user_id, path.into_inner(), db.get_ref()).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({ "error": "API key not found" })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
 // ============================================================================
 // CONFIGURATION DES ROUTES
 // ============================================================================
@@ -823,5 +1159,221 @@ pub fn auth_routes(cfg: &mut web::ServiceConfig) {
             .service(reset_password)
             .service(verify_email)
             .service(google_auth)
+            .service(get_auth_audit)
+            .service(create_api_key)
+            .service(list_api_keys)
+            .service(delete_api_key)
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    fn user_with_password(password: &str) -> users::Model {
+        users::Model {
+            id: 1,
+            username: "alice".to_string(),
+            password_hash: Some(
+                password::hash_password_with(password, password::PasswordScheme::Pbkdf2, 1000).unwrap()
+            ),
+            email: "alice@example.com".to_string(),
+            google_id: None,
+            email_verified: true,
+            abonnement_id: Some(1),
+            max_open_positions: None,
+            auto_post_realized_pnl: false,
+            block_duplicate_trades: false,
+            is_admin: false,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    async fn into_parts(response: HttpResponse) -> (u16, Vec<u8>) {
+        let status = response.status().as_u16();
+        let body = to_bytes(response.into_body()).await.unwrap().to_vec();
+        (status, body)
+    }
+
+    #[actix_web::test]
+    async fn test_login_identical_response_for_unknown_user_and_bad_password() {
+        let existing = authenticate(Some(user_with_password("secret")), "wrong").unwrap_err();
+        let unknown = authenticate(None, "wrong").unwrap_err();
+
+        let mut oauth_user = user_with_password("secret");
+        oauth_user.password_hash = None;
+        let oauth = authenticate(Some(oauth_user), "wrong").unwrap_err();
+
+        let existing = into_parts(*existing).await;
+        assert_eq!(existing.0, 401);
+        assert_eq!(existing, into_parts(*unknown).await);
+        assert_eq!(existing, into_parts(*oauth).await);
+    }
+
+    #[actix_web::test]
+    async fn test_login_succeeds_with_good_password() {
+        let user = authenticate(Some(user_with_password("secret")), "secret").unwrap();
+        assert_eq!(user.username, "alice");
+    }
+
+    #[actix_web::test]
+    async fn test_email_verification_not_required() {
+        let mut unverified = user_with_password("secret");
+        unverified.email_verified = false;
+
+        assert!(check_email_verified(&user_with_password("secret"), false).is_ok());
+        assert!(check_email_verified(&unverified, false).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_email_verification_required() {
+        assert!(check_email_verified(&user_with_password("secret"), true).is_ok());
+
+        let mut unverified = user_with_password("secret");
+        unverified.email_verified = false;
+
+        let (status, body) = into_parts(*check_email_verified(&unverified, true).unwrap_err()).await;
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status, 403);
+        assert_eq!(json["code"], "email_not_verified");
+        assert!(json.get("token").is_none());
+
+        // Compte lié à Google : exempté
+        unverified.google_id = Some("google-123".to_string());
+        assert!(check_email_verified(&unverified, true).is_ok());
+    }
+
+    #[test]
+    fn test_me_includes_plan_and_quotas_for_free_user() {
+        let free = subscription_service::PlanLimits {
+            plan_name: "Free".to_string(),
+            max_strategies: Some(10),
+            max_symbols: Some(15),
+        };
+
+        let json = me_response(&user_with_password("secret"), &free.usage(2, 8, 5));
+
+        // Champs existants conservés
+        assert_eq!(json["username"], "alice");
+        assert_eq!(json["email_verified"], true);
+        // Nouveaux champs
+        assert_eq!(json["abonnement"], "Free");
+        assert_eq!(json["limits"]["strategies"]["used"], 2);
+        assert_eq!(json["limits"]["strategies"]["limit"], 10);
+        assert_eq!(json["limits"]["strategies"]["remaining"], 8);
+        assert_eq!(json["limits"]["symbols_per_strategy"]["limit"], 15);
+    }
+
+    /// SQLite en mémoire avec les tables touchées par /login, et le compte alice
+    async fn login_test_db() -> DatabaseConnection {
+        use crate::models::{abonnement, auth_audit, login_attempts};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let schema = Schema::new(DbBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(abonnement::Entity),
+            schema.create_table_from_entity(User),
+            schema.create_table_from_entity(login_attempts::Entity),
+            schema.create_table_from_entity(auth_audit::Entity),
+        ] {
+            db.execute(DbBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        let mut alice = user_with_password("secret");
+        alice.abonnement_id = None;
+        User::insert(alice.into_active_model()).exec(&db).await.unwrap();
+        db
+    }
+
+    #[actix_web::test]
+    async fn test_failed_login_records_unsuccessful_audit_row() {
+        use actix_web::{test, App};
+
+        let db = login_test_db().await;
+
+        let app = test::init_service(App::new().app_data(web::Data::new(db.clone())).configure(auth_routes)).await;
+        let login_request = |username: &str| {
+            test::TestRequest::post()
+                .uri("/auth/login")
+                .peer_addr("203.0.113.7:52000".parse().unwrap())
+                .insert_header((header::USER_AGENT, "test-agent"))
+                .set_json(serde_json::json!({ "username": username, "password": "wrong" }))
+                .to_request()
+        };
+
+        assert_eq!(test::call_service(&app, login_request("alice")).await.status(), 401);
+        assert_eq!(test::call_service(&app, login_request("nobody")).await.status(), 401);
+
+        let mut events = AuthAuditService::recent(&db, None, 10).await.unwrap();
+        events.sort_by_key(|e| e.id);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.event_type == "login" && !e.success));
+        assert_eq!(events[0].user_id, Some(1));
+        assert_eq!(events[0].ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(events[0].user_agent.as_deref(), Some("test-agent"));
+        // Username inconnu : tracé sans user_id
+        assert_eq!(events[1].user_id, None);
+
+        // La vue utilisateur ne montre que ses propres événements
+        assert_eq!(AuthAuditService::recent(&db, Some(1), 10).await.unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_forgot_password_response_does_not_leak_token() {
+        let (status, body) = into_parts(forgot_password_response()).await;
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(status, 200);
+        assert!(json.get("token").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_login_identifier_matches_username_or_email() {
+        let db = login_test_db().await;
+
+        let by_username = find_login_user(&db, "alice").await.unwrap().unwrap();
+        let by_email = find_login_user(&db, "alice@example.com").await.unwrap().unwrap();
+        assert_eq!(by_username, by_email);
+        assert_eq!(authenticate(Some(by_username), "secret").unwrap().id, 1);
+        assert_eq!(authenticate(Some(by_email), "secret").unwrap().id, 1);
+        assert!(find_login_user(&db, "nobody@example.com").await.unwrap().is_none());
+
+        // Ancien champ username toujours accepté ; identifier prioritaire si les deux sont envoyés
+        let legacy: LoginRequest = serde_json::from_value(serde_json::json!({"username": " alice ", "password": "x"})).unwrap();
+        assert_eq!(legacy.identifier(), Some("alice"));
+        let both: LoginRequest = serde_json::from_value(
+            serde_json::json!({"identifier": "alice@example.com", "username": "alice", "password": "x"}),
+        )
+        .unwrap();
+        assert_eq!(both.identifier(), Some("alice@example.com"));
+    }
+
+    #[actix_web::test]
+    async fn test_login_by_email_fails_like_unknown_identifier() {
+        use crate::models::login_attempts;
+        use actix_web::{test, App};
+
+        let db = login_test_db().await;
+        let app = test::init_service(App::new().app_data(web::Data::new(db.clone())).configure(auth_routes)).await;
+        let failed_login = |identifier: &str| {
+            test::TestRequest::post()
+                .uri("/auth/login")
+                .set_json(serde_json::json!({ "identifier": identifier, "password": "wrong" }))
+                .to_request()
+        };
+
+        let by_email = test::call_service(&app, failed_login("alice@example.com")).await;
+        let by_username = test::call_service(&app, failed_login("alice")).await;
+        let unknown = test::call_service(&app, failed_login("nobody@example.com")).await;
+
+        assert_eq!(by_email.status(), 401);
+        let by_email = test::read_body(by_email).await;
+        assert_eq!(by_email, test::read_body(by_username).await);
+        assert_eq!(by_email, test::read_body(unknown).await);
+
+        // Les échecs par email et par username verrouillent le même compte
+        let alice = login_attempts::Entity::find_by_id("alice").one(&db).await.unwrap().unwrap();
+        assert_eq!(alice.failed_attempts, 2);
+    }
+}
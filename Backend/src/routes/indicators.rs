@@ -0,0 +1,18 @@
+use actix_web::{get, web, HttpResponse};
+
+use crate::middleware::AuthUser;
+use crate::services::indicators::registry::INDICATOR_CATALOG;
+
+/// Indicateurs disponibles : colonnes produites, paramètres par défaut et plage de valeurs
+/// (base de la génération de stratégies personnalisées)
+#[get("/catalog")]
+pub async fn get_indicator_catalog(_auth_user: AuthUser) -> HttpResponse {
+    HttpResponse::Ok().json(INDICATOR_CATALOG)
+}
+
+pub fn indicators_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/indicators")
+            .service(get_indicator_catalog)
+    );
+}
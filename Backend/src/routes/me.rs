@@ -0,0 +1,23 @@
+use actix_web::{get, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::AuthUser;
+use crate::services::digest_service;
+
+/// GET /api/me/digest - Dernières recommandations et P&L des symboles détenus ou suivis
+#[get("/digest")]
+pub async fn get_digest(auth_user: AuthUser, db: web::Data<DatabaseConnection>) -> HttpResponse {
+    match digest_service::digest(auth_user.user_id, db.get_ref()).await {
+        Ok(digest) => HttpResponse::Ok().json(digest),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to build digest: {}", e)
+        })),
+    }
+}
+
+pub fn me_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/me")
+            .service(get_digest)
+    );
+}
@@ -3,16 +3,178 @@
 ROUTES DISPONIBLES
 ========================================
 
+Erreurs de validation (routes /api/trades, /api/trading, /api/strategies) : 400 avec l'enveloppe commune
+  {"error": "Validation failed", "code": "validation_failed",
+   "validation": [{"field": "trade_type", "code": "invalid_trade_type", "message": "must be 'achat' or 'vente'"}]}
+
 HEALTH:
   GET  /api/health                          - Vérifier que l'API fonctionne
 
 STOCKS:
   GET  /api/stocks                          - Récupérer tous les stocks
-  GET  /api/stocks/with-strategies          - Récupérer les stocks avec leurs stratégies (dernière date)
+  GET  /api/stocks/search                   - Rechercher un symbole (autocomplete)
+                                              Query: ?q=app&limit=10 (limit plafonné à 50)
+                                              Response: [{"symbol": "AAPL", "company_name": "Apple Inc", "currency": "USD"}]
+  GET  /api/stocks/with-strategies          - Récupérer les stocks avec leurs stratégies (dernière date, ou ?date=YYYY-MM-DD)
+                                              ?date= : dernier résultat <= date par stratégie, lu dans strategy_result_history_rust
+                                              (un résultat par jour de calcul) ; 400 si la date n'est pas au format YYYY-MM-DD
+  GET  /api/stocks/{symbol}                 - Vue détaillée : métadonnées, dernier OHLCV, derniers indicateurs
+                                              (point_pivot en JSON), dernière recommandation par stratégie (404 si inconnu)
+  Note: /api/stocks et /api/stocks/with-strategies renvoient ETag (faible) + Cache-Control: private, max-age
+        (HTTP_CACHE_MAX_AGE, défaut 300s) ; If-None-Match identique → 304 Not Modified
+
+STRATEGIES:
+  POST /api/strategies                      - Créer une stratégie personnalisée (protégée)
+                                              Body: {"name": "...", "symbols": ["AAPL"], "strategy_config": {...}}
+                                              403 {"code": "plan_limit_reached"} si max_strategies / max_symbols du plan dépassé
+
+  GET  /api/strategies/{id}/export          - Exporter une stratégie (la sienne ou publique) en JSON portable (protégée)
+                                              Response: {"format_version": 1, "name": "...", "symbols": ["AAPL"],
+                                                         "strategy_config": {...seuils, sans "symbols"}}
+                                              Sans id / created_by / shared_with / is_public / created_at ; 404 sinon
+
+  POST /api/strategies/import               - Créer une stratégie depuis un export (nouvel id, propriétaire = appelant) (protégée)
+                                              Body: le JSON de GET /api/strategies/{id}/export
+                                              Mêmes validations et 403 {"code": "plan_limit_reached"} que POST /api/strategies ;
+                                              400 si format_version inconnue ou strategy_config n'est pas un objet
+
+  POST /api/strategies/recommendations      - Dernières recommandations pour une liste de symboles (protégée)
+                                              Body: {"symbols": ["AAPL", "SHOP.TO"], "strategy_ids": [1, 3]} (max 200 symboles, strategy_ids optionnel)
+                                              Response: [{"symbol": "AAPL", "strategies": [{"strategy_id": 1, "strategy_name": "...", "date": "...", "recommendation": "BUY", "confidence": 0.58}]}]
+                                              Les symboles inconnus sont ignorés
+
+  GET  /api/strategies/definitions          - Stratégies par défaut (protégée)
+                                              Response: [{"id": 1, "name": "MinMaxLastYear", "seeded": true}, ...]
+                                              seeded = false tant que POST /api/admin/strategies/seed n'a pas été appelé
+
+  POST /api/strategies/simulate             - Simuler une stratégie par défaut avec des seuils proposés (protégée)
+                                              Body: {"strategy_type": "rsi", "config": {"buy_below": 40, "sell_above": 65},
+                                                     "symbols": ["AAPL"] (optionnel, vide = tous les symboles actifs)}
+                                              Response: {"strategy_type": "rsi", "symbols": 1, "recommendations": [...]}
+                                              Calcul en mémoire depuis les derniers indicateurs : rien n'est sauvegardé
+                                              config.min_history_days s'applique comme pour le run réel (HOLD si historique trop court)
+                                              400 si strategy_type inconnu (liste dans "supported")
+
+  GET  /api/strategies/{id}/explain/{symbol} - Dernière recommandation d'une stratégie pour un symbole, expliquée (protégée)
+                                              Response: {"symbol": "AAPL", "strategy_id": 3, "strategy_name": "RSI", "date": "...",
+                                                         "recommendation": "BUY", "confidence": 0.39,
+                                                         "explanation": "RSI 18.4 ≤ 30 → BUY", "metadata": {...}}
+                                              explanation = null pour une stratégie personnalisée ou un metadata incomplet
+                                              404 si aucune recommandation pour ce couple stratégie / symbole
+
+INDICATEURS:
+  GET  /api/indicators/catalog              - Indicateurs calculés, colonnes produites et paramètres par défaut (protégée)
+                                              Response: [{"name": "RSI", "description": "...",
+                                                          "columns": [{"name": "rsi25", "range": {"kind": "bounded", "min": 0.0, "max": 100.0}}],
+                                                          "params": [{"name": "period", "default": 25.0}]}, ...]
+                                              range.kind : bounded | price | percent | categories (values) | json
 
 ADMIN:
   POST /api/admin/strategies/calculate      - Calculer les indicateurs et stratégies pour tous les symboles
                                               (RSI, Stochastic, EMA, Point Pivot, MinMaxLastYear)
+                                              Les stocks avec is_alive = false sont ignorés
+                                              Body optionnel: {"strategies": ["rsi", "ema"], "skip_indicators": true}
+                                                strategies : min_max_last_year | ema | rsi | stochastic | point_pivot | squeeze |
+                                                             ema_cross | donchian_breakout, exécutées dans l'ordre donné (absent = toutes)
+                                                skip_indicators : pas de recalcul des indicateurs (défaut false)
+                                                400 si une stratégie est inconnue ou si la liste est vide
+                                              Response: {"success": true, "job_id": 7, "total_results": 14000,
+                                                         "strategies": ["rsi"], "indicators_recomputed": false, ...}
+                                              409 {"error": "calculation already in progress", "job_id": 7} si un calcul tourne déjà
+                                              Annulé : {"success": false, "status": "cancelled", "job_id": 7, "processed": 4000}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+                                              Lissage : strategy_config {"confirmation_days": 2} (1 = aucun, max 30) → un changement
+                                              de signal n'est enregistré qu'après N jours consécutifs du même signal brut ;
+                                              sinon le signal précédent est conservé (metadata.smoothing : raw, raw_streak, suppressed)
+
+                                              EMA (id 2) : strategy_config {"hold_band_pct": 0.5} (0 = aucune, max 10) → un close à moins
+                                              de 0.5 % d'une EMA vote HOLD pour cette EMA ; HOLD l'emporte à égalité (metadata.signals)
+
+                                              Historique minimum : strategy_config {"min_history_days": 120} (0 = aucun, max 2000) ;
+                                              défaut selon la stratégie (MinMax 252, EMA / EMA Cross 200, RSI 26, Stochastic 28,
+                                              Pivot 30, Squeeze 20, Donchian 21). En dessous → HOLD, confiance 0,
+                                              metadata {"reason": "insufficient_history", "history_bars": 10, "min_history_days": 200}
+
+  POST /api/admin/strategies/seed           - Créer / renommer les 8 stratégies par défaut (ids 1-8), idempotent
+                                              Response: {"success": true, "inserted": [1, 2], "updated": [3], "unchanged": [...], "conflicts": []}
+                                              Seules les lignes created_by = 'system' sont renommées ; conflicts = ids occupés par une autre
+                                              ligne (created_by utilisateur ou NULL), non écrasés
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/strategies/status         - Dernière exécution de chaque stratégie par défaut (strategy_runs_rust)
+                                              Response: {"strategies": [{"strategy_id": 1, "name": "MinMaxLastYear", "last_run": {
+                                                "started_at": "...", "finished_at": "...", "symbols_processed": 2000,
+                                                "recommendations_generated": 0, "status": "empty", "error": null}}]}
+                                              status: running | success | empty (aucune recommandation) | failed (voir error)
+                                                      | cancelled (arrêt entre deux symboles, symbols_processed = symboles traités)
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  POST /api/admin/stocks                    - Ajouter des symboles à suivre et lancer leur calcul initial d'indicateurs (FLUX B)
+                                              Body: {"stocks": [{"compagny_name": "New Co", "symbol_alphavantage": "NEWCO",
+                                                                 "currency": "USD"}]}   // currency optionnelle, défaut CAD
+                                              Response 201: {"success": true, "added": ["NEWCO"], "job_id": 8, "scheduled": true}
+                                              job_id = null (scheduled: false) si un calcul tourne déjà : le suivant les traitera
+                                              400 {"error": "Invalid stocks", "errors": ["stocks[0]: symbol already exists: NEWCO"]}
+                                              (doublon en base ou dans la liste, max 200 symboles) : rien n'est inséré
+                                              Le calcul utilise historicdata déjà importé (aucun téléchargement côté backend)
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  POST /api/admin/stocks/{symbol}/alive     - Marquer un symbole comme vivant ou délisté
+                                              Body: {"is_alive": false}
+                                              Response: {"success": true, "symbol": "XYZ", "is_alive": false}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/stocks/currency-audit     - Audit des devises : stocks à la devise NULL / hors SUPPORTED_CURRENCIES,
+                                              symboles présents dans trade mais absents de stock ("Stock not found")
+                                              Response: {"stocks_checked": 2000, "trades_checked": 350, "missing_currency_count": 3,
+                                                         "unsupported_currency_count": 1, "unknown_trade_symbol_count": 2,
+                                                         "stocks": [{"symbol": "RY.TO", "compagny_name": "...", "currency": "cad",
+                                                                     "issue": "missing|unsupported", "normalized": "CAD", "trade_count": 12}],
+                                                         "unknown_trade_symbols": [{"symbol": "GHOST", "trade_count": 4, "defaulted_trade_count": 4}]}
+                                              Triés par nombre de trades impactés décroissant
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/data/gaps                 - Lister les symboles avec des trous dans historicdata
+                                              Query: ?max_gap_days=10&since=2025-01-01 (optionnels)
+                                                     ?symbol=XYZ → ce symbole seul, sur tout son historique (since ignoré)
+                                              Response: {"max_gap_days": 10, "symbols": [{"symbol": "XYZ", "largest_gap_days": 38, "gaps": [...]}]}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  POST /api/admin/data/{symbol}/replace     - Remplacer toute la série historicdata d'un symbole (delete + insert, une transaction)
+                                              Body: {"bars": [{"date": "2025-01-02", "open": 10.0, "high": 11.0, "low": 9.5,
+                                                               "close": 10.5, "adjusted_close": 10.4, "volume": 120000}]}
+                                              Response: {"success": true, "summary": {"symbol": "XYZ", "deleted": 2500,
+                                                         "inserted": 2510, "indicators_cleared": 2300}}
+                                              400 barre invalide (rien n'est écrit) ; 500 erreur BD (rollback, ancienne série intacte)
+                                              Les indicateurs du symbole sont recalculés en entier au prochain calcul
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/indicators/missing        - Symboles (is_alive) sans indicateurs ou aux indicateurs périmés
+                                              Query: ?stale_days=7 (optionnel)
+                                              Response: {"missing": ["NEWCO"], "stale": [{"symbol": "MSFT", "latest_date": "2025-05-01", "days_behind": 60}], ...}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  POST /api/admin/maintenance/purge-tokens  - Supprimer les tokens expirés (reset password + vérification email)
+                                              Vérification : aussi les tokens utilisés créés il y a plus de 30 jours
+                                              Response: {"success": true, "deleted": {"password_reset_tokens": 12, "email_verification_tokens": 40}}
+                                              Exécuté aussi toutes les TOKEN_PURGE_INTERVAL_HOURS heures (défaut 24, 0 = désactivé)
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/jobs/{id}                 - État d'un calcul (registre en mémoire de l'instance)
+                                              Response: {"id": 7, "status": "running|completed|failed|cancelled", "cancel_requested": false,
+                                                         "processed": 4000, "started_at": "...", "finished_at": null, "error": null}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  POST /api/admin/jobs/{id}/cancel          - Arrêter un calcul en cours au prochain point de contrôle (entre deux symboles)
+                                              Response 202: {"success": true, "job": {...}} ; 404 inconnu ; 409 déjà terminé
+                                              Les transactions déjà commitées (par symbole) sont conservées, pas de rollback
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
+
+  GET  /api/admin/auth/audit                - Événements d'authentification récents (auth_audit_rust), tous utilisateurs
+                                              Query: ?user_id=123&limit=50 (optionnels, limit max 500)
+                                              Response: {"count": 50, "failed_count": 7, "events": [...même forme que /api/auth/audit...]}
+                                              Réservée aux comptes admin (users_rust.is_admin) : 403 {"code": "admin_required"} sinon
 
 AUTH:
   POST /api/auth/register                   - Créer un compte utilisateur
@@ -20,18 +182,57 @@ AUTH:
                                               Response: {"token": "...", "user_id": 123, "username": "..."}
 
   POST /api/auth/login                      - Se connecter
-                                              Body: {"username": "...", "password": "..."}
+                                              Body: {"identifier": "alice" ou "alice@example.com", "password": "..."}
+                                              ("username" accepté comme alias déprécié de "identifier")
                                               Response: {"token": "...", "user_id": 123, "username": "..."}
+                                              Même 401 "Invalid credentials" que l'identifiant soit un username, un email ou inconnu
+                                              403 {"code": "email_not_verified"} si REQUIRE_EMAIL_VERIFICATION=true et email non vérifié
+                                              429 {"code": "account_locked"} après LOGIN_MAX_ATTEMPTS échecs (LOGIN_LOCKOUT_MINUTES)
 
   GET  /api/auth/me                         - Vérifier son token JWT (route protégée)
                                               Header: Authorization: Bearer <token>
-                                              Response: {"user_id": 123, "username": "..."}
+                                              Response: {"id": 123, "username": "...", "email": "...", "email_verified": true,
+                                                         "abonnement": "Free",
+                                                         "limits": {"strategies": {"used": 2, "limit": 10, "remaining": 8},
+                                                                    "symbols_per_strategy": {"used": 8, "limit": 15, "remaining": 10}}}
 
   POST /api/auth/change-password            - Changer son mot de passe (route protégée)
                                               Header: Authorization: Bearer <token>
                                               Body: {"current_password": "...", "new_password": "..."}
                                               Response: {"success": true, "message": "Password changed successfully"}
 
+  POST /api/auth/google                     - Connexion / inscription Google OAuth
+                                              Body: {"id_token": "..."}
+                                              Response: {"token": "...", "user": {...}, "is_new_user": true}
+                                              401 si le token est invalide ou émis pour un autre client (aud ≠ GOOGLE_CLIENT_ID)
+                                              403 {"code": "email_not_verified"} si Google n'a pas vérifié l'email (nouveau compte)
+                                              503 si Google est injoignable ou GOOGLE_CLIENT_ID absent
+
+  GET  /api/auth/audit                      - Activité d'authentification récente de l'utilisateur connecté (route protégée)
+                                              Query: ?limit=50 (optionnel, max 500)
+                                              Response: {"events": [{"id": 1, "user_id": 123, "event_type": "login", "success": false,
+                                                                     "ip": "203.0.113.7", "user_agent": "...", "created_at": "..."}]}
+                                              event_type: register | login | password_change | password_reset_request |
+                                                          password_reset | email_verification | google_sign_in
+
+  POST /api/auth/api-keys                   - Créer une clé d'API personnelle (protégée, JWT uniquement)
+                                              Body: {"name": "backtest script", "scope": "read" | "read_write" (défaut "read")}
+                                              Response 201: {"api_key": {"id": 1, "name": "...", "prefix": "tk_1a2b3c4d", "scope": "read",
+                                                                         "created_at": "...", "last_used_at": null},
+                                                             "key": "tk_..."}   ← clé en clair, renvoyée UNE seule fois
+                                              403 {"code": "api_key_limit_reached"} au-delà de 10 clés
+
+  GET  /api/auth/api-keys                   - Lister ses clés d'API, sans le clair (protégée, JWT uniquement)
+                                              Response: {"api_keys": [{"id": 1, "name": "...", "prefix": "tk_1a2b3c4d", ...}]}
+
+  DELETE /api/auth/api-keys/{id}            - Révoquer une clé d'API (protégée, JWT uniquement) → 204, 404 si inconnue
+
+  Note: toute route protégée accepte aussi le header X-API-Key: tk_... à la place de Authorization: Bearer.
+        Clé inconnue / révoquée → 401 ; clé "read" sur une méthode autre que GET/HEAD/OPTIONS → 403 {"code": "api_key_read_only"},
+        sauf les POST sans écriture : /api/strategies/recommendations, /api/strategies/simulate,
+        /api/trades/validate, /api/trades/preview-sale ;
+        les routes /api/auth/api-keys et POST /api/trading/emergency-stop(/pin) appelées avec une clé → 403 {"code": "jwt_required"}
+
 WALLET:
   POST /api/wallet/transaction              - Ajouter une transaction au wallet (protégée)
                                               Header: Authorization: Bearer <token>
@@ -40,7 +241,7 @@ WALLET:
                                                 "action": "ajout|retrait|gain|perte",
                                                 "symbol": "AAPL" (optionnel, null pour ajout/retrait),
                                                 "amount": 100.50,
-                                                "currency": "CAD|USD|EUR"
+                                                "currency": "CAD|USD|EUR|GBP|JPY|CHF|AUD|NZD|HKD"
                                               }
                                               Response: {"success": true, "message": "Transaction added successfully", "transaction": {...}}
 
@@ -52,8 +253,9 @@ WALLET:
                                                   "date": "2025-12-20",
                                                   "action": "ajout",
                                                   "symbol": null,
-                                                  "amount": 1000.0,
-                                                  "currency": "CAD"
+                                                  "amount": "1000.00",   // Decimal sérialisé en chaîne (précision exacte)
+                                                  "currency": "CAD",
+                                                  "trade_vente_id": null // id de la vente si gain / perte reporté automatiquement
                                                 }
                                               ]
 
@@ -62,11 +264,100 @@ WALLET:
                                               Response: [
                                                 {
                                                   "currency": "CAD",
-                                                  "total": 2500.50,      // Total wallet (ajouts + gains - retraits - pertes)
-                                                  "invested": 1800.00,   // Montant investi dans les trades en cours
-                                                  "treasury": 700.50     // Trésorerie disponible (total - invested)
+                                                  "total": "2500.50",    // Total wallet (ajouts + gains - retraits - pertes)
+                                                  "invested": "1800.00", // Montant investi dans les trades en cours
+                                                  "treasury": "700.50"   // Trésorerie disponible (total - invested)
                                                 }
                                               ]
+                                              Compte réel uniquement : les entrées du compte papier (account_type = 'paper') sont exclues
+                                              ici, de /history et des rendements du portfolio.
+
+  GET  /api/wallet/paper/balance            - Soldes du compte papier (simulation) par devise (protégée)
+                                              Header: Authorization: Bearer <token>
+                                              Amorcé à l'inscription (ou au premier accès) par un "ajout" par devise
+                                              selon PAPER_STARTING_CASH (défaut "CAD:10000,USD:10000", vide = aucun)
+                                              Response: même forme que /api/wallet/balance ("invested" = 0)
+
+PORTFOLIO:
+  GET  /api/portfolio/irr                   - Rendement pondéré par l'argent (IRR annualisé) d'une devise (protégée)
+                                              Query: ?currency=CAD (optionnel, défaut CAD)
+                                              Flux : ajouts / retraits du wallet + valeur actuelle (trésorerie + positions au dernier cours)
+                                              Response: {"currency": "CAD", "irr": 0.0842, "current_value": "12500.00",
+                                                         "cash_flows": 3, "as_of": "2025-06-30", "note": null}
+                                              irr = null avec note "no_cash_flows" ou "irr_undefined" si le taux n'est pas défini
+
+  GET  /api/portfolio/concentration         - Concentration des positions ouvertes d'une devise (protégée)
+                                              Query: ?currency=CAD&top=3 (optionnels)
+                                              Valeur = lots ouverts × dernier close (prix d'achat si aucun cours)
+                                              Response: {"currency": "CAD", "total_value": "4000",
+                                                         "positions": [{"symbol": "MSFT", "quantity": "20", "market_value": "3000",
+                                                                        "share_pct": 75.0, "above_threshold": true}, ...],
+                                                         "top_n": 3, "top_n_share_pct": 100.0, "hhi": 6250.0,
+                                                         "threshold_pct": 25.0, "concentrated": ["MSFT"], "as_of": "2025-06-30"}
+                                              hhi = Σ (part en %)² (10000 = une seule ligne);
+                                              seuil via CONCENTRATION_THRESHOLD_PCT (défaut 25)
+
+  GET  /api/portfolio/twr                   - Rendement pondéré par le temps (TWR) d'une devise (protégée)
+                                              Query: ?currency=CAD&from=2025-01-01&to=2025-06-30 (optionnels;
+                                                     défaut from = premier ajout/retrait, to = date de bourse du jour)
+                                              Valeur = wallet (ajouts + gains - pertes - retraits) + plus-values latentes
+                                              des lots ouverts au close du jour; sous-période découpée à chaque ajout / retrait
+                                              Response: {"currency": "CAD", "from": "2025-01-02", "to": "2025-01-31",
+                                                         "twr": 0.1030, "start_equity": "1000", "end_equity": "1389",
+                                                         "cash_flows": 2,
+                                                         "sub_periods": [{"start": "2025-01-02", "end": "2025-01-10",
+                                                                          "start_equity": 1000.0, "end_equity": 1600.0,
+                                                                          "flow": 500.0, "rate": 0.10}, ...],
+                                                         "note": null}
+                                              rate = null si la sous-période part d'une valeur nulle (avant le premier dépôt);
+                                              twr = null avec note "no_equity" si aucune sous-période n'est mesurable
+
+  GET  /api/portfolio/performance           - Performance réalisée glissante 7d / 30d / 90d / 1y, par devise (protégée)
+                                              Trades fermés dont date_vente ∈ [from, as_of] ; devise = celle du trade d'achat
+                                              Response: {"as_of": "2025-06-30",
+                                                         "currencies": [{"currency": "CAD",
+                                                                         "windows": [{"window": "7d", "from": "2025-06-24", "to": "2025-06-30",
+                                                                                      "closed_trades": 2, "realized_gain": "60",
+                                                                                      "winning_trades": 1, "win_rate_pct": 50.0}, ...]}]}
+                                              win_rate_pct = null si aucun trade fermé dans la fenêtre
+
+ANALYTICS:
+  GET  /api/analytics/correlation           - Corrélation des rendements journaliers entre symboles (protégée)
+                                              Query: ?symbols=AAPL,MSFT&days=90 (2 à 20 symboles; days 10 à 730, défaut 90)
+                                              Closes de historicdata (ajustés si disponibles) sur [aujourd'hui - days, aujourd'hui];
+                                              chaque paire n'utilise que les dates communes aux deux symboles
+                                              Response: {"symbols": ["AAPL", "MSFT"], "from": "2025-04-01", "to": "2025-06-30",
+                                                         "matrix": [[1.0, 0.8312], [0.8312, 1.0]],
+                                                         "observations": [[61, 60], [60, 62]], "missing": []}
+                                              matrix[i][j] = null si moins de 5 rendements communs ou série constante
+
+ME (DIGEST):
+  GET  /api/me/digest                       - Digest du matin : symboles détenus ou suivis, dernière recommandation
+                                              de chaque stratégie et P&L latent des positions (protégée)
+                                              Response: {"date": "2025-06-03", "pnl_by_currency": {"USD": 84.00},
+                                                         "symbols": [{"symbol": "AAPL", "held": true, "watchlist": false,
+                                                                      "price_date": "2025-06-02", "stale": false,
+                                                                      "position": {"quantity": 9, "avg_price": 116.67, "current_price": 126.00,
+                                                                                   "currency": "USD", "pnl_dollars": 84.00, "pnl_percentage": 8.0},
+                                                                      "strategies": [{"strategy_id": 3, "strategy_name": "RSI",
+                                                                                      "recommendation": "SELL", "confidence": 0.75, ...}]},
+                                                                     {"symbol": "MSFT", "held": false, "watchlist": true,
+                                                                      "position": null, ...}]}
+                                              Prix moyen = coût des lots encore ouverts ; requêtes groupées quel que soit le nombre de symboles
+
+WATCHLIST:
+  GET  /api/watchlist                       - Symboles suivis avec dernier cours et dernières recommandations (protégée)
+                                              Response: [{"symbol": "MSFT", "added_at": "...", "price": 401.2,
+                                                          "price_date": "2025-06-02", "stale": false,
+                                                          "strategies": [{"strategy_id": 2, "strategy_name": "EMA",
+                                                                          "recommendation": "BUY", "confidence": 0.75, ...}]}]
+
+  POST /api/watchlist                       - Suivre un symbole de la table stock (protégée)
+                                              Body: {"symbol": "MSFT"} → 201 {"symbol": "MSFT", "added": true}
+                                              (200 {"added": false} s'il est déjà suivi)
+                                              404 {"code": "unknown_symbol"}, 403 {"code": "watchlist_limit_reached"} (max 100)
+
+  DELETE /api/watchlist/{symbol}            - Ne plus suivre un symbole (protégée) : 204, 404 s'il n'était pas suivi
 
 TRADES:
   POST /api/trades                          - Créer un trade (achat ou vente) (protégée)
@@ -76,7 +367,8 @@ TRADES:
                                                 "trade_type": "achat|vente",
                                                 "quantite": 10,
                                                 "prix_unitaire": 150.50,
-                                                "date": "2025-12-20"
+                                                "date": "2025-12-20",
+                                                "currency": "USD" (optionnel, remplace la devise du stock)
                                               }
                                               Response: {
                                                 "id": 1,
@@ -86,24 +378,82 @@ TRADES:
                                                 "quantite": 10,
                                                 "prix_unitaire": 150.50,
                                                 "prix_total": 1505.00,
-                                                "date": "2025-12-20"
+                                                "date": "2025-12-20",
+                                                "currency": null
                                               }
-                                              Note: Si type="vente", calcule automatiquement les trades fermés (FIFO)
+                                              Note: Si type="vente", calcule automatiquement les trades fermés (FIFO) ;
+                                              vente, FIFO et P&L reporté dans le wallet sont écrits dans une seule transaction
+                                              Bornes: 0 < quantite ≤ 1e9, 0 < prix_unitaire ≤ 1e7, 8 décimales max
+                                              → 400 {"quantite": [{"code": "too_large|too_many_decimals|must_be_positive", ...}]}
+                                              Contraintes de lot du stock (stock.lot_size / stock.min_lot, NULL = aucune) :
+                                              → 400 {"error": "Quantity 150 of RY.TO is not a multiple of the lot size 100 ..."}
+                                              → 400 {"error": "Trade amount 750 for AAPL is below the minimum of 1000"}
+                                              Achat d'un nouveau symbole au-delà de max_open_positions (GET /api/trading/preferences)
+                                              → 403 {"code": "max_open_positions_reached"}
+                                              Doublon : trade identique (symbole, type, quantité, prix, date) créé il y a moins de
+                                              DUPLICATE_TRADE_WINDOW_SECS secondes (défaut 10, 0 = désactivé, max 3600)
+                                              → 201 avec "duplicate_of": 41 (avertissement, le trade est créé)
+                                              → 409 {"code": "duplicate_trade", "existing_trade_id": 41} si block_duplicate_trades
+
+  POST /api/trades/validate                 - Dry-run d'un trade : mêmes vérifications que POST /api/trades, rien n'est créé (protégée)
+                                              Body: identique à POST /api/trades
+                                              Response: {"valid": false, "reasons": ["Insufficient funds: ..."],
+                                                         "estimated_fees": "0", "resulting_treasury": "-500", "currency": "USD"}
+                                              resulting_treasury = null si la devise est inconnue (stock introuvable)
+
+  POST /api/trades/preview-sale             - Aperçu FIFO d'une vente hypothétique, rien n'est écrit (protégée)
+                                              Body: {"symbol": "AAPL", "quantite": 8, "prix_unitaire": 120.00, "date": "2025-03-03"}
+                                              Response: {"symbol": "AAPL", "quantite": "8", "prix_vente": "120", "date_vente": "2025-03-03",
+                                                         "lots": [{"trade_achat_id": 1, "date_achat": "2025-01-10", "quantite": "5",
+                                                                   "prix_achat": "100", "gain_dollars": "100", "pourcentage_gain": "20.00",
+                                                                   "temps_jours": 34}, ...],
+                                                         "cost_basis": "860", "gain_dollars": "100", "pourcentage_gain": "11.63"}
+                                              Lots du plus ancien au plus récent, comme les trades fermés de POST /api/trades
+                                              400 si la date précède le plus ancien lot ouvert ou si la quantité dépasse la position
 
-  GET  /api/trades                          - Voir tous les trades (achats et ventes) (protégée)
+  POST /api/trades/close/{symbol}           - Vendre toute la position ouverte d'un symbole (protégée)
                                               Header: Authorization: Bearer <token>
-                                              Response: [
-                                                {
-                                                  "id": 1,
-                                                  "user_id": 123,
-                                                  "symbol": "AAPL",
-                                                  "trade_type": "achat",
-                                                  "quantite": 10,
-                                                  "prix_unitaire": 150.50,
-                                                  "prix_total": 1505.00,
-                                                  "date": "2025-12-20"
-                                                }
-                                              ]
+                                              Body: {"prix_unitaire": 160.00, "date": "2025-12-21"}
+                                              Response: {"trade": {...}, "closed_trades": [...]}
+                                              Note: 400 si aucune position ouverte pour ce symbole
+
+  GET  /api/trades/{id}                     - Détail d'un trade (protégée)
+                                              Header: Authorization: Bearer <token>
+                                              Response: {...TradeResponse, "quantite_restante": 30, "closed_trade_ids": ["1_1_2_1734..."]}
+                                              Note: closed_trade_ids seulement pour les achats; 404 si trade d'un autre utilisateur
+
+  DELETE /api/trades/{id}                   - Supprimer un trade (soft-delete, tracé dans audit_log_rust) (protégée)
+                                              Header: Authorization: Bearer <token>
+                                              Response: 204
+                                              409 {"code": "trade_consumed"} si le lot a déjà été vendu (ou la vente a fermé des lots)
+
+  POST /api/trades/recalculate              - Reconstruire l'état FIFO (quantite_restante + trades fermés) (protégée)
+                                              Header: Authorization: Bearer <token>
+                                              Response: {"before": {...}, "after": {...}, "buy_lots_updated": 2}
+                                              Note: transactionnel; 400 si une vente dépasse la quantité détenue ou si un trade a une date illisible
+
+  GET  /api/trades?limit=100&offset=0&symbol=AAPL&trade_type=achat&from=2025-01-01&to=2025-12-31
+                                            - Voir les trades (achats et ventes), paginés et filtrés (protégée)
+                                              Header: Authorization: Bearer <token>
+                                              Response: {
+                                                "items": [
+                                                  {
+                                                    "id": 1,
+                                                    "user_id": 123,
+                                                    "symbol": "AAPL",
+                                                    "trade_type": "achat",
+                                                    "quantite": 10,
+                                                    "prix_unitaire": 150.50,
+                                                    "prix_total": 1505.00,
+                                                    "date": "2025-12-20"
+                                                  }
+                                                ],
+                                                "total": 1,
+                                                "limit": 100,
+                                                "offset": 0
+                                              }
+                                              Note: tous les paramètres sont optionnels; tri date desc, id desc;
+                                              limit 1-500 (défaut 100); from/to inclus (YYYY-MM-DD); 400 si invalide
 
   GET  /api/trades/open                     - Voir les positions ouvertes (calculées FIFO) (protégée)
                                               Header: Authorization: Bearer <token>
@@ -115,25 +465,43 @@ TRADES:
                                                 }
                                               ]
 
-  GET  /api/trades/open-with-recommendations - Voir les positions ouvertes avec recommandations de stratégies (protégée)
+  GET  /api/trades/open-with-recommendations - Voir les positions ouvertes avec recommandations de stratégies et trailing stop (protégée)
+                                              nearest_support / nearest_resistance : niveau pivot (toutes périodes) le plus proche
+                                              sous / au-dessus du prix actuel, {"period": "month", "level": "S1", "price": 148.2,
+                                              "distance_pct": 1.53} ; null si aucun point_pivot pour le symbole
+                                              price_date / price_age_days : date et âge (jours calendaires) du dernier close ;
+                                              stale = true au-delà de STALE_PRICE_MAX_DAYS (défaut 4) ou sans close
+                                              (current_price = prix moyen). Même age_days / stale sur chaque stratégie
+                                              first_entry_date : premier achat ; avg_entry_date : date moyenne pondérée par la quantité ouverte ;
+                                              entry_date : déprécié, identique à first_entry_date
                                               Header: Authorization: Bearer <token>
                                               Response: [
                                                 {
                                                   "symbol": "AAPL",
                                                   "quantite_totale": 10,
                                                   "prix_moyen": 150.50,
+                                                  "current_price": 158.20,
+                                                  "price_date": "2025-12-19",
+                                                  "price_age_days": 1,
+                                                  "stale": false,
                                                   "strategies": [
                                                     {
                                                       "strategy_id": 1,
                                                       "strategy_name": "RSI",
-                                                      "date": "2025-12-20",
-                                                      "recommendation": "SELL"
+                                                      "date": "2025-12-19",
+                                                      "recommendation": "SELL",
+                                                      "confidence": 0.75,
+                                                      "age_days": 1,
+                                                      "stale": false
                                                     },
                                                     {
                                                       "strategy_id": 2,
                                                       "strategy_name": "Stochastic",
-                                                      "date": "2025-12-20",
-                                                      "recommendation": "HOLD"
+                                                      "date": "2025-12-12",
+                                                      "recommendation": "HOLD",
+                                                      "confidence": 0.4,
+                                                      "age_days": 8,
+                                                      "stale": true
                                                     }
                                                   ]
                                                 }
@@ -141,32 +509,78 @@ TRADES:
                                               Note: Combine les positions ouvertes avec les dernières recommandations de stratégies
                                                     pour aider à décider si vendre, garder ou racheter
 
+  GET  /api/trades/attention                - Positions ouvertes à revoir : SELL d'au moins une stratégie ou P&L latent hors seuils (protégée)
+                                              Query: ?loss_pct=-10&take_pct=20 (optionnels, défauts -10 / 20, loss_pct < take_pct)
+                                              Response: [{...mêmes champs que /open-with-recommendations...,
+                                                          "reasons": ["SELL from RSI", "P&L -12.50% below -10%"]}]
+                                              Positions sans raison omises
+
   GET  /api/trades/closed                   - Voir les trades fermés avec gains/pertes (protégée)
                                               Header: Authorization: Bearer <token>
                                               Response: [
                                                 {
                                                   "symbol": "AAPL",
-                                                  "date_achat": "2025-12-20",
+                                                  "date_achat": "2025-12-18",
                                                   "prix_achat": "150.50",
-                                                  "date_vente": "2025-12-21",
+                                                  "date_vente": "2025-12-19",
                                                   "prix_vente": "160.00",
-                                                  "pourcentage_gain": 6,
+                                                  "pourcentage_gain": "6.31",
                                                   "gain_dollars": 47.50,
-                                                  "temps_jours": 1,
+                                                  "temps_jours": 1,       // Séances de bourse (week-ends/fériés exclus)
                                                   "trade_achat_id": 1,
                                                   "trade_vente_id": 2
                                                 }
                                               ]
 
+  Note: POST /api/trades et POST /api/trades/close/{symbol} renvoient 423 {"code": "emergency_stop_active"}
+        tant que l'arrêt d'urgence de l'utilisateur est armé
+
+TRADING (ARRÊT D'URGENCE, PRÉFÉRENCES):
+  GET  /api/trading/emergency-stop          - État de l'arrêt d'urgence (protégée)
+                                              Response: {"armed": false, "pin_set": true, "updated_at": "..."}
+
+  POST /api/trading/emergency-stop/pin      - Définir / changer le PIN (4 à 12 chiffres, ≠ mot de passe) (protégée, JWT uniquement)
+                                              Body: {"pin": "4821", "current_pin": "1234"} (current_pin requis si un PIN existe)
+
+  POST /api/trading/emergency-stop          - Armer / désarmer l'arrêt d'urgence (protégée, JWT uniquement)
+                                              Body: {"pin": "4821", "armed": true}
+                                              403 {"code": "invalid_pin"}, 400 {"code": "pin_not_set"}
+                                              429 {"code": "pin_locked"} + Retry-After après 5 PIN faux consécutifs
+                                              (15 min ; compté aussi sur current_pin de /emergency-stop/pin)
+                                              Appelées avec une clé d'API (X-API-Key) → 403 {"code": "jwt_required"}
+
+  GET  /api/trading/preferences             - Préférences de trading (protégée)
+                                              Response: {"max_open_positions": 5, "auto_post_realized_pnl": false,
+                                                         "block_duplicate_trades": false} (null = pas de limite)
+
+  POST /api/trading/preferences             - Modifier les préférences (protégée)
+                                              Body: {"max_open_positions": 5, "auto_post_realized_pnl": true, "block_duplicate_trades": true}
+                                              (1 à 1000, null = pas de limite ; booléens absents = false)
+                                              Un achat qui ouvrirait un symbole de plus que la limite est refusé :
+                                              403 {"code": "max_open_positions_reached"} (renforcer une position existante reste permis)
+                                              auto_post_realized_pnl : chaque vente poste son P&L réalisé (FIFO) en "gain" / "perte"
+                                              dans le wallet, devise du trade sinon du stock (une entrée par vente, trade_vente_id).
+                                              POST /api/trades/recalculate met ces entrées à jour au lieu d'en créer de nouvelles
+                                              et supprime celles des ventes qui ne ferment plus aucun lot (vente supprimée)
+                                              block_duplicate_trades : un doublon récent de POST /api/trades est refusé (409)
+                                              au lieu d'être créé avec "duplicate_of"
+
 ========================================
 */
 
 pub mod health;
 pub mod stocks;
+pub mod strategies;
 pub mod admin;
 pub mod auth;
 pub mod wallet;
 pub mod trade;
+pub mod trading;
+pub mod portfolio;
+pub mod indicators;
+pub mod analytics;
+pub mod me;
+pub mod watchlist;
 
 use actix_web::web;
 
@@ -175,9 +589,16 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/api")
             .service(health::health_check)
             .configure(stocks::stocks_routes)
+            .configure(strategies::strategies_routes)
             .configure(admin::admin_routes)
             .configure(auth::auth_routes)
             .configure(wallet::wallet_routes)
             .configure(trade::configure)
+            .configure(trading::trading_routes)
+            .configure(portfolio::portfolio_routes)
+            .configure(indicators::indicators_routes)
+            .configure(analytics::analytics_routes)
+            .configure(me::me_routes)
+            .configure(watchlist::watchlist_routes)
     );
 }
\ No newline at end of file
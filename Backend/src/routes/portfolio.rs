@@ -0,0 +1,159 @@
+use actix_web::{get, web, HttpResponse};
+use chrono::NaiveDate;
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+use crate::middleware::AuthUser;
+use crate::services::analytics_service;
+use crate::utils::currency;
+
+#[derive(Deserialize)]
+pub struct IrrQuery {
+    pub currency: Option<String>, // défaut: devise par défaut (CAD)
+}
+
+/// GET /api/portfolio/irr - Rendement pondéré par l'argent (IRR annualisé) d'une devise
+#[get("/irr")]
+pub async fn get_irr(
+    auth_user: AuthUser,
+    query: web::Query<IrrQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let currency = query
+        .currency
+        .as_deref()
+        .map(|c| c.trim().to_uppercase())
+        .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+
+    if !currency::is_supported(&currency) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Invalid currency. Must be one of: {}", currency::supported_list())
+        }));
+    }
+
+    match analytics_service::money_weighted_return(auth_user.user_id, &currency, db.get_ref()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to compute IRR: {}", e)
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConcentrationQuery {
+    pub currency: Option<String>, // défaut: devise par défaut (CAD)
+    pub top: Option<usize>,       // défaut 3
+}
+
+/// GET /api/portfolio/concentration - Poids des positions ouvertes, concentration top N et HHI
+#[get("/concentration")]
+pub async fn get_concentration(
+    auth_user: AuthUser,
+    query: web::Query<ConcentrationQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let currency = query
+        .currency
+        .as_deref()
+        .map(|c| c.trim().to_uppercase())
+        .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+
+    if !currency::is_supported(&currency) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Invalid currency. Must be one of: {}", currency::supported_list())
+        }));
+    }
+
+    let top_n = query.top.unwrap_or(analytics_service::DEFAULT_CONCENTRATION_TOP_N).max(1);
+
+    match analytics_service::concentration(auth_user.user_id, &currency, top_n, db.get_ref()).await {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to compute concentration: {}", e)
+        })),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TwrQuery {
+    pub currency: Option<String>, // défaut: devise par défaut (CAD)
+    pub from: Option<String>,     // YYYY-MM-DD, défaut: premier ajout / retrait
+    pub to: Option<String>,       // YYYY-MM-DD, défaut: date de bourse du jour
+}
+
+impl TwrQuery {
+    /// Bornes from / to validées
+    fn period(&self) -> Result<(Option<NaiveDate>, Option<NaiveDate>), String> {
+        let bound = |name: &str, value: &Option<String>| -> Result<Option<NaiveDate>, String> {
+            match value {
+                Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map(Some)
+                    .map_err(|_| format!("{} must be a YYYY-MM-DD date", name)),
+                None => Ok(None),
+            }
+        };
+        let (from, to) = (bound("from", &self.from)?, bound("to", &self.to)?);
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err("from must be before to".to_string());
+        }
+        Ok((from, to))
+    }
+}
+
+/// GET /api/portfolio/twr - Rendement pondéré par le temps (sous-périodes chaînées à chaque ajout / retrait)
+#[get("/twr")]
+pub async fn get_twr(
+    auth_user: AuthUser,
+    query: web::Query<TwrQuery>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let currency = query
+        .currency
+        .as_deref()
+        .map(|c| c.trim().to_uppercase())
+        .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+
+    if !currency::is_supported(&currency) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Invalid currency. Must be one of: {}", currency::supported_list())
+        }));
+    }
+
+    let (from, to) = match query.period() {
+        Ok(period) => period,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+
+    match analytics_service::time_weighted_return(auth_user.user_id, &currency, from, to, db.get_ref()).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to compute TWR: {}", e)
+        })),
+    }
+}
+
+/// GET /api/portfolio/performance - Gain réalisé, trades fermés et taux de réussite sur 7j / 30j / 90j / 1 an, par devise
+#[get("/performance")]
+pub async fn get_performance(
+    auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    match analytics_service::performance(auth_user.user_id, db.get_ref()).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to compute performance: {}", e)
+        })),
+    }
+}
+
+pub fn portfolio_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/portfolio")
+            .service(get_irr)
+            .service(get_concentration)
+            .service(get_twr)
+            .service(get_performance)
+    );
+}
@@ -1,55 +1,184 @@
-use actix_web::{get, web, HttpResponse};
+use actix_web::{get, web, HttpRequest, HttpResponse};
 use crate::models::{
     stock::Entity as Stock,
     strategy_result::{self, Entity as StrategyResult},
+    strategy_result_history::{self, Entity as StrategyResultHistory},
     strategy::{self, Entity as Strategy},
-    dto::{StockWithStrategies, StockInfo, StrategyWithResult},
+    stock,
+    historic_data::{self, Entity as HistoricData},
+    indicator::{self, Entity as Indicator},
+    dto::{StockWithStrategies, StockInfo, StrategyWithResult, StockSearchResult, StockDetail},
 };
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect, Condition, PaginatorTrait};
+use sea_orm::sea_query::{Expr, Func, LikeExpr, Order, SimpleExpr};
+use serde::Deserialize;
 use std::collections::{HashSet, HashMap};
 use crate::middleware::AuthUser;
+use crate::utils::http_cache;
+
+const DEFAULT_SEARCH_LIMIT: u64 = 10;
+const MAX_SEARCH_LIMIT: u64 = 50;
+/// Couples (stratégie, symbole, date) par requête (3 paramètres chacun)
+const AS_OF_CHUNK_SIZE: usize = 1000;
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    pub limit: Option<u64>, // défaut 10, plafonné à 50
+}
+
+#[derive(Deserialize)]
+pub struct WithStrategiesQuery {
+    pub date: Option<String>, // YYYY-MM-DD, défaut: dernière date disponible
+}
+
+/// Version des données servies : dernière date de strategy_results + nombre de stocks
+/// (les deux ne changent qu'au calcul quotidien ou à l'ajout d'un symbole)
+struct DataVersion {
+    latest_date: Option<String>,
+    stock_count: u64,
+}
+
+impl DataVersion {
+    async fn fetch(db: &DatabaseConnection) -> Result<Self, DbErr> {
+        let latest_date = StrategyResult::find()
+            .select_only()
+            .column_as(strategy_result::Column::Date.max(), "latest_date")
+            .into_tuple::<Option<String>>()
+            .one(db)
+            .await?
+            .flatten();
+
+        let stock_count = Stock::find().count(db).await?;
+
+        Ok(Self { latest_date, stock_count })
+    }
+
+    fn etag(&self, endpoint: &str, extra: Option<&str>) -> String {
+        let count = self.stock_count.to_string();
+        let mut parts = vec![endpoint, self.latest_date.as_deref().unwrap_or("none"), count.as_str()];
+        parts.extend(extra);
+        http_cache::weak_etag(&parts)
+    }
+}
 
 #[get("")]
 pub async fn get_stocks(
     _auth_user: AuthUser,
+    req: HttpRequest,
     db_connection: web::Data<DatabaseConnection>
 ) -> HttpResponse {
+    let etag = match DataVersion::fetch(db_connection.get_ref()).await {
+        Ok(version) => version.etag("stocks", None),
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    if let Some(response) = http_cache::not_modified(&req, &etag) {
+        return response;
+    }
+
     let stocks = Stock::find()
         .all(db_connection.get_ref())
         .await;
 
     match stocks {
-        Ok(stocks) => HttpResponse::Ok().json(stocks),
+        Ok(stocks) => http_cache::with_cache_headers(&mut HttpResponse::Ok(), &etag).json(stocks),
         Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
     }
 }
 
+#[get("/search")]
+pub async fn search_stocks(
+    _auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    query: web::Query<SearchQuery>,
+) -> HttpResponse {
+    let term = query.q.as_deref().unwrap_or_default().trim().to_lowercase();
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT) as usize;
+
+    if term.is_empty() {
+        return HttpResponse::Ok().json(Vec::<StockSearchResult>::new());
+    }
+
+    match search_candidates(&term, limit as u64, db.get_ref()).await {
+        Ok(stocks) => HttpResponse::Ok().json(rank_search_results(stocks, &term, limit)),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// LIKE insensible à la casse sur symbole et nom (les jokers % et _ saisis sont échappés),
+/// triés en SQL comme match_rank pour que LIMIT garde les meilleurs résultats
+async fn search_candidates(term: &str, limit: u64, db: &DatabaseConnection) -> Result<Vec<stock::Model>, DbErr> {
+    let escaped = escape_like(term);
+    let lower = |column: stock::Column| Expr::expr(Func::lower(Expr::col(column)));
+    let like = |column: stock::Column, pattern: String| lower(column).like(LikeExpr::new(pattern).escape('\\'));
+    let (prefix, contains) = (format!("{}%", escaped), format!("%{}%", escaped));
+
+    let rank: SimpleExpr = Expr::case(lower(stock::Column::SymbolAlphavantage).eq(term), 0)
+        .case(like(stock::Column::SymbolAlphavantage, prefix.clone()), 1)
+        .case(like(stock::Column::CompagnyName, prefix), 2)
+        .case(like(stock::Column::SymbolAlphavantage, contains.clone()), 3)
+        .finally(4)
+        .into();
+
+    Stock::find()
+        .filter(stock::Column::SymbolAlphavantage.is_not_null())
+        .filter(
+            Condition::any()
+                .add(like(stock::Column::SymbolAlphavantage, contains.clone()))
+                .add(like(stock::Column::CompagnyName, contains)),
+        )
+        .order_by(rank, Order::Asc)
+        .order_by(Expr::expr(Func::char_length(Expr::col(stock::Column::SymbolAlphavantage))), Order::Asc)
+        .order_by_asc(stock::Column::SymbolAlphavantage)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
 #[get("/with-strategies")]
 pub async fn get_stocks_with_strategies(
     _auth_user: AuthUser,
-    db: web::Data<DatabaseConnection>
+    req: HttpRequest,
+    db: web::Data<DatabaseConnection>,
+    query: web::Query<WithStrategiesQuery>,
 ) -> HttpResponse {
-    // 1. Trouver la date la plus récente
-    let latest_date = StrategyResult::find()
-        .order_by_desc(strategy_result::Column::Date)
-        .one(db.get_ref())
-        .await
-        .ok()
-        .flatten()
-        .and_then(|r| r.date);
+    let version = match DataVersion::fetch(db.get_ref()).await {
+        Ok(version) => version,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
 
-    if latest_date.is_none() {
-        return HttpResponse::Ok().json(Vec::<StockWithStrategies>::new());
+    let etag = version.etag("with-strategies", query.date.as_deref());
+    if let Some(response) = http_cache::not_modified(&req, &etag) {
+        return response;
     }
 
-    let latest_date = latest_date.unwrap();
+    let stocks_with_results = match &query.date {
+        // Résultats "as of" : le plus récent résultat <= date pour chaque (stock, stratégie)
+        Some(date) => {
+            if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                return HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Invalid date format (expected YYYY-MM-DD)"
+                }));
+            }
 
-    // 2. Récupérer stocks avec résultats filtrés sur cette date
-    let stocks_with_results = Stock::find()
-        .find_with_related(StrategyResult)
-        .filter(strategy_result::Column::Date.eq(latest_date))
-        .all(db.get_ref())
-        .await;
+            results_as_of(date, db.get_ref()).await
+        }
+        None => {
+            // 1. Date la plus récente (déjà lue pour l'ETag)
+            let Some(latest_date) = version.latest_date else {
+                return http_cache::with_cache_headers(&mut HttpResponse::Ok(), &etag)
+                    .json(Vec::<StockWithStrategies>::new());
+            };
+
+            // 2. Récupérer stocks avec résultats filtrés sur cette date
+            Stock::find()
+                .find_with_related(StrategyResult)
+                .filter(strategy_result::Column::Date.eq(latest_date))
+                .all(db.get_ref())
+                .await
+        }
+    };
 
     match stocks_with_results {
         Ok(stocks_with_results) => {
@@ -80,11 +209,9 @@ pub async fn get_stocks_with_strategies(
                 .map(|(stock, strategy_results)| {
                     let strategies = strategy_results
                         .into_iter()
-                        .map(|result| StrategyWithResult {
-                            strategy_id: result.strategy_id,
-                            strategy_name: strategies_map.get(&result.strategy_id).cloned(),
-                            date: result.date,
-                            recommendation: result.recommendation.map(|v| v.to_string()),
+                        .map(|result| {
+                            let name = strategies_map.get(&result.strategy_id).cloned();
+                            StrategyWithResult::from_result(result, name)
                         })
                         .collect();
 
@@ -99,16 +226,433 @@ pub async fn get_stocks_with_strategies(
                 })
                 .collect();
 
-            HttpResponse::Ok().json(response)
+            http_cache::with_cache_headers(&mut HttpResponse::Ok(), &etag).json(response)
         }
         Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
     }
 }
 
+/// GET /api/stocks/{symbol} - Vue détaillée d'un symbole (404 si absent de la table stock)
+#[get("/{symbol}")]
+pub async fn get_stock_detail(
+    _auth_user: AuthUser,
+    path: web::Path<String>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let symbol = path.into_inner();
+
+    match fetch_stock_detail(&symbol, db.get_ref()).await {
+        Ok(Some(detail)) => HttpResponse::Ok().json(detail),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Stock not found: {}", symbol)
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+async fn fetch_stock_detail(symbol: &str, db: &DatabaseConnection) -> Result<Option<StockDetail>, DbErr> {
+    let Some(stock) = Stock::find()
+        .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+        .one(db)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let latest_price = HistoricData::find()
+        .filter(historic_data::Column::Symbol.eq(symbol))
+        .order_by_desc(historic_data::Column::Date)
+        .one(db)
+        .await?;
+
+    let indicators = Indicator::find()
+        .filter(indicator::Column::Symbol.eq(symbol))
+        .order_by_desc(indicator::Column::Date)
+        .one(db)
+        .await?;
+
+    let results = StrategyResult::find()
+        .filter(strategy_result::Column::Symbol.eq(symbol))
+        .all(db)
+        .await?;
+    let results = latest_results(results);
+
+    let strategies_map: HashMap<i32, String> = Strategy::find()
+        .filter(strategy::Column::Id.is_in(results.iter().map(|r| r.strategy_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .filter_map(|s| s.name.map(|name| (s.id, name)))
+        .collect();
+
+    let strategies = results
+        .into_iter()
+        .map(|result| {
+            let name = strategies_map.get(&result.strategy_id).cloned();
+            StrategyWithResult::from_result(result, name)
+        })
+        .collect();
+
+    Ok(Some(StockDetail {
+        is_alive: stock.is_tradable(),
+        stock: StockInfo {
+            company_name: stock.compagny_name,
+            symbol_alphavantage: stock.symbol_alphavantage,
+            currency: stock.currency,
+        },
+        latest_price,
+        indicators,
+        strategies,
+    }))
+}
+
+/// Échappe les caractères spéciaux de LIKE (\\, %, _)
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Score de pertinence (plus petit = meilleur), None si aucune correspondance
+fn match_rank(symbol: &str, company_name: &str, term: &str) -> Option<u8> {
+    let symbol = symbol.to_lowercase();
+    let company_name = company_name.to_lowercase();
+
+    if symbol == term {
+        Some(0)
+    } else if symbol.starts_with(term) {
+        Some(1)
+    } else if company_name.starts_with(term) {
+        Some(2)
+    } else if symbol.contains(term) {
+        Some(3)
+    } else if company_name.contains(term) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// Trie par pertinence (exact > préfixe symbole > préfixe nom > sous-chaîne), puis symbole le plus court
+fn rank_search_results(stocks: Vec<stock::Model>, term: &str, limit: usize) -> Vec<StockSearchResult> {
+    let mut ranked: Vec<(u8, StockSearchResult)> = stocks
+        .into_iter()
+        .filter_map(|stock| {
+            let symbol = stock.symbol_alphavantage?;
+            let rank = match_rank(&symbol, &stock.compagny_name, term)?;
+            Some((rank, StockSearchResult {
+                symbol,
+                company_name: stock.compagny_name,
+                currency: stock.currency,
+            }))
+        })
+        .collect();
+
+    ranked.sort_by(|(rank_a, a), (rank_b, b)| {
+        rank_a
+            .cmp(rank_b)
+            .then(a.symbol.len().cmp(&b.symbol.len()))
+            .then_with(|| a.symbol.cmp(&b.symbol))
+    });
+
+    ranked.into_iter().take(limit).map(|(_, result)| result).collect()
+}
+
+/// Résultats "as of" de chaque stock : pour chaque (stratégie, symbole), le plus récent résultat <= `as_of`.
+/// Lu dans strategy_result_history_rust (dernière date <= as_of en SQL, puis les lignes correspondantes),
+/// complété par strategy_results_rust pour les résultats antérieurs à l'historique.
+async fn results_as_of(
+    as_of: &str,
+    db: &DatabaseConnection,
+) -> Result<Vec<(stock::Model, Vec<strategy_result::Model>)>, DbErr> {
+    let keys: Vec<(i32, String, String)> = StrategyResultHistory::find()
+        .select_only()
+        .column(strategy_result_history::Column::StrategyId)
+        .column(strategy_result_history::Column::Symbol)
+        .column_as(Expr::col(strategy_result_history::Column::Date).max(), "max_date")
+        .filter(strategy_result_history::Column::Date.lte(as_of))
+        .group_by(strategy_result_history::Column::StrategyId)
+        .group_by(strategy_result_history::Column::Symbol)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut results: Vec<strategy_result::Model> = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(AS_OF_CHUNK_SIZE) {
+        let rows = StrategyResultHistory::find()
+            .filter(
+                Expr::tuple([
+                    Expr::col(strategy_result_history::Column::StrategyId).into(),
+                    Expr::col(strategy_result_history::Column::Symbol).into(),
+                    Expr::col(strategy_result_history::Column::Date).into(),
+                ])
+                .in_tuples(chunk.iter().cloned()),
+            )
+            .all(db)
+            .await?;
+        results.extend(rows.into_iter().map(strategy_result::Model::from));
+    }
+
+    // Résultat courant déjà <= as_of : c'est le plus récent de son couple (même s'il précède l'historique)
+    results.extend(
+        StrategyResult::find()
+            .filter(strategy_result::Column::Date.lte(as_of))
+            .all(db)
+            .await?,
+    );
+
+    let mut by_symbol: HashMap<String, Vec<strategy_result::Model>> = HashMap::new();
+    for result in results {
+        if let Some(symbol) = result.symbol.clone() {
+            by_symbol.entry(symbol).or_default().push(result);
+        }
+    }
+
+    let stocks = Stock::find()
+        .filter(stock::Column::SymbolAlphavantage.is_in(by_symbol.keys().cloned()))
+        .order_by_asc(stock::Column::CompagnyName)
+        .all(db)
+        .await?;
+
+    Ok(stocks
+        .into_iter()
+        .filter_map(|stock| {
+            let results = by_symbol.remove(stock.symbol_alphavantage.as_deref()?)?;
+            Some((stock, latest_results_as_of(results, as_of)))
+        })
+        .collect())
+}
+
+/// Garde, pour chaque stratégie, le résultat le plus récent dont la date est <= `as_of`.
+/// Les dates sont au format YYYY-MM-DD, donc la comparaison de chaînes suit l'ordre chronologique.
+fn latest_results_as_of(
+    results: Vec<strategy_result::Model>,
+    as_of: &str,
+) -> Vec<strategy_result::Model> {
+    let mut latest: HashMap<i32, strategy_result::Model> = HashMap::new();
+
+    for result in results {
+        let Some(date) = result.date.as_deref() else { continue };
+        if date > as_of {
+            continue;
+        }
+
+        let is_newer = latest
+            .get(&result.strategy_id)
+            .and_then(|current| current.date.as_deref())
+            .is_none_or(|current_date| date > current_date);
+
+        if is_newer {
+            latest.insert(result.strategy_id, result);
+        }
+    }
+
+    let mut latest: Vec<strategy_result::Model> = latest.into_values().collect();
+    latest.sort_by_key(|r| r.strategy_id);
+    latest
+}
+
+/// Dernier résultat de chaque stratégie, quelle que soit sa date
+fn latest_results(results: Vec<strategy_result::Model>) -> Vec<strategy_result::Model> {
+    let Some(latest_date) = results.iter().filter_map(|r| r.date.clone()).max() else {
+        return Vec::new();
+    };
+    latest_results_as_of(results, &latest_date)
+}
+
 pub fn stocks_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/stocks")
             .service(get_stocks)
+            .service(search_stocks)
             .service(get_stocks_with_strategies)
+            .service(get_stock_detail) // en dernier : /{symbol} capturerait /search
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(strategy_id: i32, date: &str, recommendation: &str) -> strategy_result::Model {
+        strategy_result::Model {
+            strategy_id,
+            symbol: Some("AAPL".to_string()),
+            date: Some(date.to_string()),
+            recommendation: Some(serde_json::json!(recommendation)),
+            metadata: None,
+        }
+    }
+
+    fn history() -> Vec<strategy_result::Model> {
+        vec![
+            result(1, "2025-05-20", "BUY"),
+            result(1, "2025-06-01", "HOLD"),
+            result(1, "2025-06-10", "SELL"),
+            result(2, "2025-05-28", "BUY"),
+            result(2, "2025-06-05", "SELL"),
+        ]
+    }
+
+    /// SQLite en mémoire : historique d'AAPL (2 stratégies) et un résultat MSFT antérieur à l'historique
+    async fn as_of_db() -> DatabaseConnection {
+        use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(Stock),
+            schema.create_table_from_entity(StrategyResult),
+            schema.create_table_from_entity(StrategyResultHistory),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        for (name, symbol) in [("Apple Inc", "AAPL"), ("Microsoft", "MSFT")] {
+            stock::ActiveModel::from(stock(name, symbol)).insert(&db).await.unwrap();
+        }
+
+        // Ce qu'écrit save_result : une ligne d'historique par jour, le dernier résultat dans strategy_results_rust
+        for (strategy_id, date, recommendation) in [
+            (1, "2025-05-20", "BUY"),
+            (1, "2025-06-01", "HOLD"),
+            (1, "2025-06-10", "SELL"),
+            (2, "2025-05-28", "BUY"),
+            (2, "2025-06-05", "SELL"),
+        ] {
+            strategy_result_history::ActiveModel {
+                strategy_id: Set(strategy_id),
+                symbol: Set("AAPL".to_string()),
+                date: Set(date.to_string()),
+                recommendation: Set(Some(serde_json::json!(recommendation))),
+                metadata: Set(None),
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        for latest in [result(1, "2025-06-10", "SELL"), result(2, "2025-06-05", "SELL")] {
+            strategy_result::ActiveModel::from(latest).insert(&db).await.unwrap();
+        }
+        // Calculé avant l'historique : seulement dans strategy_results_rust
+        let mut legacy = result(3, "2025-05-15", "BUY");
+        legacy.symbol = Some("MSFT".to_string());
+        strategy_result::ActiveModel::from(legacy).insert(&db).await.unwrap();
+
+        db
+    }
+
+    /// (symbole, [(strategy_id, date, recommandation)])
+    async fn as_of(date: &str, db: &DatabaseConnection) -> Vec<(String, Vec<(i32, String, serde_json::Value)>)> {
+        results_as_of(date, db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(stock, results)| {
+                let results = results
+                    .into_iter()
+                    .map(|r| (r.strategy_id, r.date.unwrap(), r.recommendation.unwrap()))
+                    .collect();
+                (stock.symbol_alphavantage.unwrap(), results)
+            })
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn test_as_of_exact_date() {
+        let db = as_of_db().await;
+        let latest = as_of("2025-06-01", &db).await;
+
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].0, "AAPL");
+        assert_eq!(latest[0].1, vec![
+            (1, "2025-06-01".to_string(), serde_json::json!("HOLD")),
+            (2, "2025-05-28".to_string(), serde_json::json!("BUY")),
+        ]);
+        assert_eq!(latest[1], ("MSFT".to_string(), vec![(3, "2025-05-15".to_string(), serde_json::json!("BUY"))]));
+    }
+
+    #[actix_web::test]
+    async fn test_as_of_in_between_date() {
+        let db = as_of_db().await;
+        let latest = as_of("2025-06-07", &db).await;
+
+        assert_eq!(latest[0].1, vec![
+            (1, "2025-06-01".to_string(), serde_json::json!("HOLD")),
+            (2, "2025-06-05".to_string(), serde_json::json!("SELL")),
+        ]);
+        assert_eq!(as_of("2025-06-30", &db).await[0].1[0].1, "2025-06-10");
+    }
+
+    #[actix_web::test]
+    async fn test_as_of_before_any_result() {
+        let db = as_of_db().await;
+        assert!(as_of("2025-01-01", &db).await.is_empty());
+    }
+
+    fn stock(name: &str, symbol: &str) -> stock::Model {
+        stock::Model {
+            compagny_name: name.to_string(),
+            is_alive: None,
+            low_data: None,
+            symbol_alphavantage: Some(symbol.to_string()),
+            currency: Some("USD".to_string()),
+            lot_size: None,
+            min_lot: None,
+        }
+    }
+
+    #[test]
+    fn test_search_ranks_best_match_first() {
+        let stocks = vec![
+            stock("Snapple Group", "SNAP"),
+            stock("Applied Materials", "AMAT"),
+            stock("Apple Inc", "AAPL"),
+            stock("Apptio", "APP"),
+        ];
+
+        let symbols: Vec<String> = rank_search_results(stocks, "app", 10)
+            .into_iter()
+            .map(|r| r.symbol)
+            .collect();
+
+        // exact (APP) > préfixe nom (Apple, Applied : départagés par symbole) > sous-chaîne nom (Snapple)
+        assert_eq!(symbols, vec!["APP", "AAPL", "AMAT", "SNAP"]);
+    }
+
+    #[actix_web::test]
+    async fn test_search_limit_keeps_best_matches() {
+        use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let table = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(Stock);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        for (name, symbol) in [("Snapple Group", "SNAP"), ("Applied Materials", "AMAT"), ("Apple Inc", "AAPL"), ("Apptio", "APP")] {
+            stock::ActiveModel::from(stock(name, symbol)).insert(&db).await.unwrap();
+        }
+
+        // LIMIT en SQL : les 2 meilleurs, pas les 2 premiers lus
+        let symbols: Vec<String> = search_candidates("app", 2, &db)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|s| s.symbol_alphavantage)
+            .collect();
+        assert_eq!(symbols, vec!["APP", "AAPL"]);
+    }
+
+    #[test]
+    fn test_search_respects_limit_and_escapes_like() {
+        let stocks = vec![stock("Apple Inc", "AAPL"), stock("Apptio", "APP")];
+        assert_eq!(rank_search_results(stocks, "ap", 1).len(), 1);
+        assert_eq!(escape_like("50%_off"), "50\\%\\_off");
+    }
+
+    #[test]
+    fn test_latest_results_keeps_most_recent_per_strategy() {
+        let latest = latest_results(history());
+
+        let dates: Vec<(i32, Option<&str>)> = latest.iter().map(|r| (r.strategy_id, r.date.as_deref())).collect();
+        assert_eq!(dates, vec![(1, Some("2025-06-10")), (2, Some("2025-06-05"))]);
+        assert!(latest_results(Vec::new()).is_empty());
+    }
+}
@@ -0,0 +1,560 @@
+use actix_web::{get, post, web, HttpResponse};
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect, PaginatorTrait, Set, ActiveModelTrait, TransactionTrait};
+use std::collections::{HashMap, HashSet};
+use validator::Validate;
+
+use crate::models::{
+    strategy_result::{self, Entity as StrategyResult},
+    strategy::{self, Entity as Strategy},
+    dto::{BatchRecommendationRequest, CreateStrategyRequest, SimulateStrategyRequest, StrategyExport, StrategyWithResult, SymbolRecommendations, STRATEGY_EXPORT_VERSION},
+    stock::{self, Entity as Stock},
+    users::Entity as User,
+};
+use crate::services::strategy_service::{StrategyService, DEFAULT_STRATEGIES, SIMULATION_TYPES};
+use crate::services::strategies::explain::explain;
+use crate::services::subscription_service::{PlanLimits, SubscriptionService};
+use crate::middleware::AuthUser;
+use crate::utils::api_error::{validation_error_response, ApiError};
+
+/// Crée une stratégie personnalisée, dans les limites du plan de l'utilisateur
+/// (nombre de stratégies et de symboles par stratégie, lus depuis abonnements_rust)
+#[post("")]
+pub async fn create_strategy(
+    auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    body: web::Json<CreateStrategyRequest>,
+) -> HttpResponse {
+    if let Err(errors) = body.validate() {
+        return validation_error_response(&errors);
+    }
+
+    create_for_user(&auth_user, db.get_ref(), &body).await
+}
+
+/// Symboles normalisés (trim + majuscules), sans doublons, ordre conservé
+fn normalize_symbols(symbols: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    symbols
+        .iter()
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| seen.insert(s.clone()))
+        .collect()
+}
+
+/// strategy_config stocké : le DSL de la requête (objet, sinon vide) avec la liste de symboles
+fn stored_config(strategy_config: &serde_json::Value, symbols: &[String]) -> serde_json::Value {
+    let mut config = match strategy_config {
+        serde_json::Value::Object(map) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+    config.insert("symbols".to_string(), serde_json::json!(symbols));
+    serde_json::Value::Object(config)
+}
+
+/// Insère une stratégie validée pour l'utilisateur, dans les limites de son plan
+async fn create_for_user(
+    auth_user: &AuthUser,
+    db: &DatabaseConnection,
+    request: &CreateStrategyRequest,
+) -> HttpResponse {
+    let limits = match SubscriptionService::limits_for_user(db, auth_user.user_id).await {
+        Ok(limits) => limits,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    let symbols = normalize_symbols(&request.symbols);
+
+    // Les symboles sont stockés dans strategy_config avec le DSL
+    let new_strategy = strategy::ActiveModel {
+        name: Set(Some(request.name.clone())),
+        created_by: Set(Some(auth_user.username.clone())),
+        is_public: Set(Some(false)),
+        strategy_config: Set(Some(stored_config(&request.strategy_config, &symbols))),
+        created_at: Set(Some(chrono::Utc::now().naive_utc())),
+        ..Default::default()
+    };
+
+    match insert_within_plan(auth_user, &limits, new_strategy, symbols.len(), db).await {
+        Ok(Ok(created)) => HttpResponse::Created().json(created),
+        Ok(Err(message)) => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": message,
+            "code": "plan_limit_reached"
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// Compte les stratégies de l'utilisateur et insère la nouvelle dans une même transaction,
+/// sa ligne users_rust verrouillée (FOR UPDATE) : deux créations simultanées ne peuvent pas dépasser max_strategies.
+/// Ok(Err(message)) si le plan est dépassé (rien n'est inséré)
+async fn insert_within_plan(
+    auth_user: &AuthUser,
+    limits: &PlanLimits,
+    new_strategy: strategy::ActiveModel,
+    symbol_count: usize,
+    db: &DatabaseConnection,
+) -> Result<Result<strategy::Model, String>, DbErr> {
+    let txn = db.begin().await?;
+
+    User::find_by_id(auth_user.user_id).lock_exclusive().one(&txn).await?;
+    let existing = Strategy::find()
+        .filter(strategy::Column::CreatedBy.eq(&auth_user.username))
+        .count(&txn)
+        .await?;
+
+    if let Err(message) = limits.check_new_strategy(existing, symbol_count) {
+        return Ok(Err(message));
+    }
+
+    let created = new_strategy.insert(&txn).await?;
+    txn.commit().await?;
+    Ok(Ok(created))
+}
+
+/// Export portable d'une stratégie : les symboles sont sortis de strategy_config,
+/// id / created_by / shared_with / is_public / created_at ne sont pas exportés
+pub fn export_strategy(model: &strategy::Model) -> StrategyExport {
+    let mut config = match &model.strategy_config {
+        Some(serde_json::Value::Object(map)) => map.clone(),
+        _ => serde_json::Map::new(),
+    };
+
+    let symbols = match config.remove("symbols") {
+        Some(serde_json::Value::Array(values)) => values
+            .into_iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    StrategyExport {
+        format_version: STRATEGY_EXPORT_VERSION,
+        name: model.name.clone().unwrap_or_default(),
+        symbols,
+        strategy_config: serde_json::Value::Object(config),
+    }
+}
+
+/// Valide un export avant import (version, nom, symboles, DSL objet ou absent)
+/// et le convertit en requête de création
+fn import_request(export: StrategyExport) -> Result<CreateStrategyRequest, ApiError> {
+    if export.format_version != STRATEGY_EXPORT_VERSION {
+        return Err(ApiError::new(format!(
+            "Unsupported strategy export version {} (expected {})",
+            export.format_version, STRATEGY_EXPORT_VERSION
+        )));
+    }
+
+    if let Err(errors) = export.validate() {
+        return Err(ApiError::validation(&errors));
+    }
+
+    if !matches!(export.strategy_config, serde_json::Value::Object(_) | serde_json::Value::Null) {
+        return Err(ApiError::new("strategy_config must be a JSON object"));
+    }
+
+    let request = CreateStrategyRequest {
+        name: export.name,
+        symbols: export.symbols,
+        strategy_config: export.strategy_config,
+    };
+    request.validate().map_err(|errors| ApiError::validation(&errors))?;
+    Ok(request)
+}
+
+/// GET /api/strategies/{id}/export - Stratégie de l'utilisateur (ou publique) en JSON portable
+#[get("/{id}/export")]
+pub async fn export_strategy_config(
+    auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    path: web::Path<i32>,
+) -> HttpResponse {
+    let strategy_id = path.into_inner();
+
+    let model = match Strategy::find_by_id(strategy_id).one(db.get_ref()).await {
+        Ok(Some(model))
+            if model.created_by.as_deref() == Some(auth_user.username.as_str())
+                || model.is_public == Some(true) =>
+        {
+            model
+        }
+        Ok(_) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": format!("Strategy {} not found", strategy_id)
+            }))
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    HttpResponse::Ok().json(export_strategy(&model))
+}
+
+/// POST /api/strategies/import - Crée une nouvelle stratégie (nouvel id) depuis un export,
+/// avec les mêmes validations et limites de plan que POST /api/strategies
+#[post("/import")]
+pub async fn import_strategy(
+    auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    body: web::Json<StrategyExport>,
+) -> HttpResponse {
+    match import_request(body.into_inner()) {
+        Ok(request) => create_for_user(&auth_user, db.get_ref(), &request).await,
+        Err(errors) => HttpResponse::BadRequest().json(errors),
+    }
+}
+
+/// Dernière recommandation de chaque stratégie demandée, pour une liste de symboles
+/// Une seule requête sur strategy_results ; les symboles inconnus sont ignorés
+#[post("/recommendations")]
+pub async fn get_batch_recommendations(
+    _auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    body: web::Json<BatchRecommendationRequest>,
+) -> HttpResponse {
+    if let Err(errors) = body.validate() {
+        return validation_error_response(&errors);
+    }
+
+    let mut query = StrategyResult::find()
+        .filter(strategy_result::Column::Symbol.is_in(body.symbols.iter().map(|s| s.as_str())));
+
+    if !body.strategy_ids.is_empty() {
+        query = query.filter(strategy_result::Column::StrategyId.is_in(body.strategy_ids.clone()));
+    }
+
+    let results = match query
+        .order_by_desc(strategy_result::Column::Date)
+        .all(db.get_ref())
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    // Noms des stratégies en une seule query
+    let strategy_names: HashMap<i32, String> = Strategy::find()
+        .all(db.get_ref())
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|s| s.name.map(|name| (s.id, name)))
+        .collect();
+
+    HttpResponse::Ok().json(group_latest_by_symbol(&body.symbols, results, &strategy_names))
+}
+
+/// Regroupe par symbole (dans l'ordre de la requête) en gardant le résultat le plus récent
+/// de chaque stratégie. Les symboles sans aucun résultat ne sont pas retournés.
+fn group_latest_by_symbol(
+    symbols: &[String],
+    results: Vec<strategy_result::Model>,
+    strategy_names: &HashMap<i32, String>,
+) -> Vec<SymbolRecommendations> {
+    let mut by_symbol: HashMap<String, HashMap<i32, strategy_result::Model>> = HashMap::new();
+
+    for result in results {
+        let Some(symbol) = result.symbol.clone() else { continue };
+        let latest = by_symbol.entry(symbol).or_default();
+
+        let is_newer = latest
+            .get(&result.strategy_id)
+            .is_none_or(|current| result.date > current.date);
+
+        if is_newer {
+            latest.insert(result.strategy_id, result);
+        }
+    }
+
+    let mut seen = HashSet::new();
+
+    symbols
+        .iter()
+        .filter(|symbol| seen.insert(symbol.as_str()))
+        .filter_map(|symbol| {
+            let mut latest: Vec<strategy_result::Model> = by_symbol.remove(symbol)?.into_values().collect();
+            latest.sort_by_key(|r| r.strategy_id);
+
+            Some(SymbolRecommendations {
+                symbol: symbol.clone(),
+                strategies: latest
+                    .into_iter()
+                    .map(|result| {
+                        let name = strategy_names.get(&result.strategy_id).cloned();
+                        StrategyWithResult::from_result(result, name)
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Stratégies par défaut (id → nom) et présence de la ligne correspondante dans strategies_rust
+#[get("/definitions")]
+pub async fn get_strategy_definitions(
+    _auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let ids: Vec<i32> = DEFAULT_STRATEGIES.iter().map(|(id, _, _)| *id).collect();
+    let rows = match Strategy::find()
+        .filter(strategy::Column::Id.is_in(ids))
+        .all(db.get_ref())
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    let stored: HashMap<i32, Option<String>> = rows.into_iter().map(|s| (s.id, s.name)).collect();
+
+    let definitions: Vec<serde_json::Value> = DEFAULT_STRATEGIES
+        .iter()
+        .map(|&(id, _, name)| {
+            serde_json::json!({
+                "id": id,
+                "name": name,
+                "seeded": stored.get(&id).and_then(|n| n.as_deref()) == Some(name)
+            })
+        })
+        .collect();
+
+    HttpResponse::Ok().json(definitions)
+}
+
+/// Ce qu'une stratégie par défaut recommanderait aujourd'hui avec les seuils proposés,
+/// calculé en mémoire depuis les derniers indicateurs (rien n'est sauvegardé)
+#[post("/simulate")]
+pub async fn simulate_strategy(
+    _auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    body: web::Json<SimulateStrategyRequest>,
+) -> HttpResponse {
+    if let Err(errors) = body.validate() {
+        return validation_error_response(&errors);
+    }
+
+    let strategy_type = body.strategy_type.trim().to_lowercase();
+    if !SIMULATION_TYPES.contains(&strategy_type.as_str()) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Unknown strategy type: {}", body.strategy_type),
+            "supported": SIMULATION_TYPES
+        }));
+    }
+
+    let symbols: Vec<String> = if body.symbols.is_empty() {
+        match Stock::find().all(db.get_ref()).await {
+            Ok(stocks) => stock::alive_symbols(stocks),
+            Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        }
+    } else {
+        body.symbols.iter().map(|s| s.trim().to_uppercase()).collect()
+    };
+
+    match StrategyService::new()
+        .simulate_default_strategy(&strategy_type, &body.config, &symbols, db.get_ref())
+        .await
+    {
+        Ok(recommendations) => HttpResponse::Ok().json(serde_json::json!({
+            "strategy_type": strategy_type,
+            "symbols": symbols.len(),
+            "recommendations": recommendations
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// Dernière recommandation d'une stratégie pour un symbole, avec l'explication
+/// reconstruite depuis le metadata stocké (ex: "RSI 18.4 ≤ 30 → BUY")
+#[get("/{id}/explain/{symbol}")]
+pub async fn explain_recommendation(
+    _auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+    path: web::Path<(i32, String)>,
+) -> HttpResponse {
+    let (strategy_id, symbol) = path.into_inner();
+    let symbol = symbol.trim().to_uppercase();
+
+    let latest = match StrategyResult::find()
+        .filter(strategy_result::Column::StrategyId.eq(strategy_id))
+        .filter(strategy_result::Column::Symbol.eq(&symbol))
+        .order_by_desc(strategy_result::Column::Date)
+        .one(db.get_ref())
+        .await
+    {
+        Ok(Some(result)) => result,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(serde_json::json!({
+                "error": format!("No recommendation for strategy {} and symbol {}", strategy_id, symbol)
+            }))
+        }
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    let strategy_name = match Strategy::find_by_id(strategy_id).one(db.get_ref()).await {
+        Ok(strategy) => strategy.and_then(|s| s.name),
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    let metadata = latest.metadata.clone().unwrap_or(serde_json::Value::Null);
+    let result = StrategyWithResult::from_result(latest, strategy_name);
+    let explanation = result
+        .recommendation
+        .as_deref()
+        .and_then(|signal| explain(strategy_id, signal, &metadata));
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "symbol": symbol,
+        "strategy_id": result.strategy_id,
+        "strategy_name": result.strategy_name,
+        "date": result.date,
+        "recommendation": result.recommendation,
+        "confidence": result.confidence,
+        "explanation": explanation,
+        "metadata": metadata
+    }))
+}
+
+pub fn strategies_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/strategies")
+            .service(create_strategy)
+            .service(import_strategy)
+            .service(export_strategy_config)
+            .service(get_batch_recommendations)
+            .service(get_strategy_definitions)
+            .service(simulate_strategy)
+            .service(explain_recommendation)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(strategy_id: i32, symbol: &str, date: &str, recommendation: &str) -> strategy_result::Model {
+        strategy_result::Model {
+            strategy_id,
+            symbol: Some(symbol.to_string()),
+            date: Some(date.to_string()),
+            recommendation: Some(serde_json::json!(recommendation)),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_latest_result_per_strategy_in_request_order() {
+        let symbols = vec!["SHOP".to_string(), "UNKNOWN".to_string(), "AAPL".to_string()];
+        let results = vec![
+            result(1, "AAPL", "2025-06-02", "BUY"),
+            result(1, "AAPL", "2025-06-01", "SELL"),
+            result(3, "AAPL", "2025-06-02", "HOLD"),
+            result(1, "SHOP", "2025-06-02", "SELL"),
+        ];
+        let names = HashMap::from([(1, "MinMaxLastYear".to_string())]);
+
+        let grouped = group_latest_by_symbol(&symbols, results, &names);
+
+        // UNKNOWN est ignoré, l'ordre de la requête est conservé
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[0].symbol, "SHOP");
+        assert_eq!(grouped[1].symbol, "AAPL");
+
+        let aapl = &grouped[1].strategies;
+        assert_eq!(aapl.len(), 2);
+        assert_eq!(aapl[0].strategy_name.as_deref(), Some("MinMaxLastYear"));
+        assert_eq!(aapl[0].recommendation.as_deref(), Some("BUY"));
+        assert_eq!(aapl[1].strategy_id, 3);
+    }
+
+    #[test]
+    fn test_export_then_import_round_trip() {
+        let original = strategy::Model {
+            id: 42,
+            name: Some("Momentum".to_string()),
+            created_by: Some("alice".to_string()),
+            shared_with: Some("bob".to_string()),
+            is_public: Some(false),
+            strategy_config: Some(serde_json::json!({
+                "rules": [{"indicator": "rsi14", "buy_below": 30, "sell_above": 70}],
+                "symbols": ["AAPL", "SHOP.TO"]
+            })),
+            created_at: None,
+        };
+
+        let json = serde_json::to_value(export_strategy(&original)).unwrap();
+
+        // Aucun identifiant serveur dans l'export
+        for internal in ["id", "created_by", "shared_with", "is_public", "created_at"] {
+            assert!(json.get(internal).is_none(), "{} leaked", internal);
+        }
+        assert_eq!(json["symbols"], serde_json::json!(["AAPL", "SHOP.TO"]));
+        assert!(json["strategy_config"].get("symbols").is_none());
+
+        let imported = import_request(serde_json::from_value(json).unwrap()).unwrap();
+        let symbols = normalize_symbols(&imported.symbols);
+
+        assert_eq!(imported.name, "Momentum");
+        assert_eq!(stored_config(&imported.strategy_config, &symbols), original.strategy_config.unwrap());
+    }
+
+    #[test]
+    fn test_import_rejects_bad_version_and_config() {
+        let export = |format_version: u32, strategy_config: serde_json::Value| StrategyExport {
+            format_version,
+            name: "Momentum".to_string(),
+            symbols: vec!["AAPL".to_string()],
+            strategy_config,
+        };
+
+        assert!(import_request(export(1, serde_json::Value::Null)).is_ok());
+        assert!(import_request(export(2, serde_json::json!({}))).is_err());
+        assert!(import_request(export(1, serde_json::json!([1, 2]))).is_err());
+        assert!(import_request(StrategyExport { symbols: vec![], ..export(1, serde_json::json!({})) }).is_err());
+    }
+
+    #[test]
+    fn test_symbol_list_is_capped_at_200() {
+        let request = BatchRecommendationRequest {
+            symbols: (0..201).map(|i| format!("SYM{}", i)).collect(),
+            strategy_ids: vec![],
+        };
+        assert!(request.validate().is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_plan_limit_is_checked_inside_the_insert_transaction() {
+        use crate::models::users;
+        use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table abonnement ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [schema.create_table_from_entity(User), schema.create_table_from_entity(Strategy)] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        users::ActiveModel {
+            id: Set(1),
+            username: Set("alice".to_string()),
+            email: Set("alice@example.com".to_string()),
+            email_verified: Set(true),
+            auto_post_realized_pnl: Set(false),
+            block_duplicate_trades: Set(false),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let alice = AuthUser { user_id: 1, username: "alice".to_string(), api_key_id: None };
+        let one_strategy = PlanLimits { plan_name: "Tiny".to_string(), max_strategies: Some(1), max_symbols: None };
+        let new_strategy = || strategy::ActiveModel {
+            name: Set(Some("mine".to_string())),
+            created_by: Set(Some("alice".to_string())),
+            ..Default::default()
+        };
+
+        assert!(insert_within_plan(&alice, &one_strategy, new_strategy(), 3, &db).await.unwrap().is_ok());
+        let rejected = insert_within_plan(&alice, &one_strategy, new_strategy(), 3, &db).await.unwrap();
+        assert_eq!(rejected.unwrap_err(), "Strategy limit reached for plan Tiny (1 max)");
+        assert_eq!(Strategy::find().count(&db).await.unwrap(), 1);
+    }
+}
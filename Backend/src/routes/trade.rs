@@ -1,13 +1,19 @@
-use actix_web::{web, HttpResponse, Responder, get};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
+use actix_web::{web, HttpResponse, Responder, delete, get, post};
+use sea_orm::{Condition, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
 use validator::Validate;
 use rust_decimal::Decimal;
+use serde::Deserialize;
 use std::collections::HashMap;
 use crate::middleware::AuthUser;
-use crate::models::dto::{CreateTradeRequest, TradeResponse, OpenPositionResponse, ClosedTradeResponse, OpenPositionWithRecommendationsResponse, StrategyWithResult};
-use crate::models::{trade, strategy, strategy_result};
-use crate::services::trade_service::TradeService;
-use rust_decimal::prelude::ToPrimitive;
+use crate::models::dto::{CreateTradeRequest, TradeResponse, TradePageResponse, TradeDetailResponse, OpenPositionResponse, ClosedTradeResponse, OpenPositionWithRecommendationsResponse, PositionAttentionResponse, StrategyWithResult, ClosePositionRequest, ClosePositionResponse, PreviewSaleRequest};
+use crate::models::{trade, strategy, strategy_result, trades_fermes};
+use crate::services::trade_service::{CreateTradeError, DeleteTradeError, TradeService};
+use crate::services::risk_service::{self, RiskService};
+use crate::services::{data_quality, historic_bars};
+use crate::routes::trading::ensure_trading_allowed;
+use crate::utils::api_error::validation_error_response;
+use crate::utils::dates::{market_today, parse_trade_date, weighted_average_date};
+use crate::utils::money;
 
 pub async fn create_trade(
     db: web::Data<DatabaseConnection>,
@@ -15,56 +21,177 @@ pub async fn create_trade(
     request: web::Json<CreateTradeRequest>,
 ) -> impl Responder {
     if let Err(errors) = request.validate() {
-        return HttpResponse::BadRequest().json(errors);
+        return validation_error_response(&errors);
+    }
+
+    if let Err(response) = ensure_trading_allowed(&db, auth_user.user_id).await {
+        return *response;
     }
 
     match TradeService::create_trade(&db, auth_user.user_id, request.into_inner()).await {
-        Ok(trade_model) => {
-            let response = TradeResponse {
-                id: trade_model.id,
-                user_id: trade_model.user_id,
-                symbol: trade_model.symbol.unwrap_or_default(),
-                trade_type: trade_model.trade_type.unwrap_or_default(),
-                quantite: trade_model.quantite.unwrap_or_default(),
-                prix_unitaire: trade_model.prix_unitaire.unwrap_or_default(),
-                prix_total: trade_model.prix_total.unwrap_or_default(),
-                date: trade_model.date.unwrap_or_default(),
-            };
-            HttpResponse::Created().json(response)
-        }
+        Ok(created) => HttpResponse::Created().json(TradeResponse {
+            duplicate_of: created.duplicate_of,
+            ..to_trade_response(created.trade)
+        }),
+        Err(CreateTradeError::Rejected(message)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": message
+        })),
+        Err(CreateTradeError::OpenPositionsLimit(message)) => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": message,
+            "code": "max_open_positions_reached"
+        })),
+        Err(CreateTradeError::Duplicate { existing_trade_id }) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": "An identical trade was submitted moments ago",
+            "code": "duplicate_trade",
+            "existing_trade_id": existing_trade_id
+        })),
+        Err(CreateTradeError::Db(e)) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// POST /api/trades/validate - Dry-run de POST /api/trades : mêmes vérifications, rien n'est créé
+#[post("/validate")]
+pub async fn validate_trade(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    request: web::Json<CreateTradeRequest>,
+) -> impl Responder {
+    if let Err(errors) = request.validate() {
+        return validation_error_response(&errors);
+    }
+
+    match TradeService::validate_trade(&db, auth_user.user_id, &request).await {
+        Ok(validation) => HttpResponse::Ok().json(validation),
         Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
     }
 }
 
-#[get("")]
-pub async fn get_all_trades(
+/// POST /api/trades/preview-sale - Lots FIFO que consommerait une vente, avec gains et temps_jours par lot.
+/// Rien n'est écrit ; 400 si la vente précède le plus ancien lot ouvert ou dépasse la position
+#[post("/preview-sale")]
+pub async fn preview_sale(
     db: web::Data<DatabaseConnection>,
     auth_user: AuthUser,
+    request: web::Json<PreviewSaleRequest>,
 ) -> impl Responder {
-    let trades = trade::Entity::find()
-        .filter(trade::Column::UserId.eq(auth_user.user_id))
+    if let Err(errors) = request.validate() {
+        return validation_error_response(&errors);
+    }
+
+    match TradeService::preview_sale(&db, auth_user.user_id, &request).await {
+        Ok(Ok(preview)) => HttpResponse::Ok().json(preview),
+        Ok(Err(message)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": message
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// Trades par page par défaut / maximum (GET /api/trades)
+const DEFAULT_TRADES_LIMIT: u64 = 100;
+const MAX_TRADES_LIMIT: u64 = 500;
+
+/// Filtres et pagination de GET /api/trades
+#[derive(Debug, Default, Deserialize)]
+pub struct TradesQuery {
+    pub limit: Option<u64>,  // 1..=500, défaut 100
+    pub offset: Option<u64>, // défaut 0
+    pub symbol: Option<String>,
+    pub trade_type: Option<String>, // "achat" | "vente"
+    pub from: Option<String>,       // YYYY-MM-DD, inclus
+    pub to: Option<String>,         // YYYY-MM-DD, inclus
+}
+
+impl TradesQuery {
+    /// (limit, offset) validés
+    fn page(&self) -> Result<(u64, u64), String> {
+        let limit = self.limit.unwrap_or(DEFAULT_TRADES_LIMIT);
+        if !(1..=MAX_TRADES_LIMIT).contains(&limit) {
+            return Err(format!("limit must be between 1 and {}", MAX_TRADES_LIMIT));
+        }
+        Ok((limit, self.offset.unwrap_or(0)))
+    }
+
+    /// Conditions SeaORM des filtres (en plus de l'utilisateur)
+    /// Les bornes de date comparent la chaîne : seules les dates YYYY-MM-DD sont filtrées correctement
+    fn condition(&self, user_id: i32) -> Result<Condition, String> {
+        let mut condition = Condition::all().add(trade::Column::UserId.eq(user_id));
+
+        if let Some(symbol) = self.symbol.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            condition = condition.add(trade::Column::Symbol.eq(symbol.to_uppercase()));
+        }
+        if let Some(trade_type) = self.trade_type.as_deref() {
+            if trade_type != "achat" && trade_type != "vente" {
+                return Err("trade_type must be 'achat' or 'vente'".to_string());
+            }
+            condition = condition.add(trade::Column::TradeType.eq(trade_type));
+        }
+
+        let bound = |name: &str, value: &Option<String>| -> Result<Option<String>, String> {
+            match value {
+                Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map(|d| Some(d.format("%Y-%m-%d").to_string()))
+                    .map_err(|_| format!("{} must be a YYYY-MM-DD date", name)),
+                None => Ok(None),
+            }
+        };
+        let (from, to) = (bound("from", &self.from)?, bound("to", &self.to)?);
+        if let (Some(from), Some(to)) = (&from, &to)
+            && from > to
+        {
+            return Err("from must be before to".to_string());
+        }
+        if let Some(from) = from {
+            condition = condition.add(trade::Column::Date.gte(from));
+        }
+        if let Some(to) = to {
+            condition = condition.add(trade::Column::Date.lte(to));
+        }
+
+        Ok(condition)
+    }
+}
+
+/// Une page de trades (date desc, id desc) et le total correspondant aux filtres
+async fn fetch_trade_page(
+    db: &DatabaseConnection,
+    condition: Condition,
+    limit: u64,
+    offset: u64,
+) -> Result<TradePageResponse, DbErr> {
+    let query = trade::Entity::find_active().filter(condition);
+    let total = query.clone().count(db).await?;
+
+    let trades = query
         .order_by_desc(trade::Column::Date)
         .order_by_desc(trade::Column::Id)
-        .all(db.get_ref())
-        .await;
+        .limit(limit)
+        .offset(offset)
+        .all(db)
+        .await?;
+
+    Ok(TradePageResponse {
+        items: trades.into_iter().map(to_trade_response).collect(),
+        total,
+        limit,
+        offset,
+    })
+}
 
-    match trades {
-        Ok(trades) => {
-            let response: Vec<TradeResponse> = trades
-                .into_iter()
-                .map(|t| TradeResponse {
-                    id: t.id,
-                    user_id: t.user_id,
-                    symbol: t.symbol.unwrap_or_default(),
-                    trade_type: t.trade_type.unwrap_or_default(),
-                    quantite: t.quantite.unwrap_or_default(),
-                    prix_unitaire: t.prix_unitaire.unwrap_or_default(),
-                    prix_total: t.prix_total.unwrap_or_default(),
-                    date: t.date.unwrap_or_default(),
-                })
-                .collect();
-            HttpResponse::Ok().json(response)
-        }
+#[get("")]
+pub async fn get_all_trades(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    query: web::Query<TradesQuery>,
+) -> impl Responder {
+    let filters = query.page().and_then(|page| Ok((page, query.condition(auth_user.user_id)?)));
+    let ((limit, offset), condition) = match filters {
+        Ok(filters) => filters,
+        Err(message) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": message })),
+    };
+
+    match fetch_trade_page(db.get_ref(), condition, limit, offset).await {
+        Ok(page) => HttpResponse::Ok().json(page),
         Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
     }
 }
@@ -74,7 +201,7 @@ pub async fn get_open_positions(
     db: web::Data<DatabaseConnection>,
     auth_user: AuthUser,
 ) -> impl Responder {
-    let trades = trade::Entity::find()
+    let trades = trade::Entity::find_active()
         .filter(trade::Column::UserId.eq(auth_user.user_id))
         .order_by_asc(trade::Column::Date)
         .all(db.get_ref())
@@ -127,26 +254,96 @@ pub async fn get_open_positions_with_recommendations(
     db: web::Data<DatabaseConnection>,
     auth_user: AuthUser,
 ) -> impl Responder {
+    match open_positions_with_recommendations(db.get_ref(), auth_user.user_id).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error fetching trades: {}", e)),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AttentionQuery {
+    pub loss_pct: Option<f64>, // défaut: -10 (P&L % en dessous → à surveiller)
+    pub take_pct: Option<f64>, // défaut: 20 (P&L % au-dessus → prise de profit)
+}
+
+const DEFAULT_ATTENTION_LOSS_PCT: f64 = -10.0;
+const DEFAULT_ATTENTION_TAKE_PCT: f64 = 20.0;
+
+/// Raisons pour lesquelles une position ouverte demande attention (vide = rien à signaler) :
+/// un SELL d'au moins une stratégie, ou un P&L latent sous `loss_pct` / au-dessus de `take_pct`
+pub fn attention_reasons(position: &OpenPositionWithRecommendationsResponse, loss_pct: f64, take_pct: f64) -> Vec<String> {
+    let mut reasons: Vec<String> = position
+        .strategies
+        .iter()
+        .filter(|s| s.recommendation.as_deref() == Some("SELL"))
+        .map(|s| match &s.strategy_name {
+            Some(name) => format!("SELL from {}", name),
+            None => format!("SELL from strategy {}", s.strategy_id),
+        })
+        .collect();
+
+    if let Some(pnl) = position.pnl_percentage {
+        if pnl < loss_pct {
+            reasons.push(format!("P&L {:.2}% below {}%", pnl, loss_pct));
+        } else if pnl > take_pct {
+            reasons.push(format!("P&L {:.2}% above {}%", pnl, take_pct));
+        }
+    }
+
+    reasons
+}
+
+/// GET /api/trades/attention - Positions ouvertes avec un SELL ou un P&L hors des seuils, avec les raisons
+#[get("/attention")]
+pub async fn get_positions_needing_attention(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    query: web::Query<AttentionQuery>,
+) -> impl Responder {
+    let loss_pct = query.loss_pct.unwrap_or(DEFAULT_ATTENTION_LOSS_PCT);
+    let take_pct = query.take_pct.unwrap_or(DEFAULT_ATTENTION_TAKE_PCT);
+
+    if !loss_pct.is_finite() || !take_pct.is_finite() || loss_pct >= take_pct {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "loss_pct must be lower than take_pct"
+        }));
+    }
+
+    let positions = match open_positions_with_recommendations(db.get_ref(), auth_user.user_id).await {
+        Ok(positions) => positions,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error fetching trades: {}", e)),
+    };
+
+    let response: Vec<PositionAttentionResponse> = positions
+        .into_iter()
+        .filter_map(|position| {
+            let reasons = attention_reasons(&position, loss_pct, take_pct);
+            (!reasons.is_empty()).then_some(PositionAttentionResponse { position, reasons })
+        })
+        .collect();
+
+    HttpResponse::Ok().json(response)
+}
+
+/// Positions ouvertes (FIFO) avec P&L, trailing stop et dernière recommandation de chaque stratégie
+async fn open_positions_with_recommendations(
+    db: &DatabaseConnection,
+    user_id: i32,
+) -> Result<Vec<OpenPositionWithRecommendationsResponse>, DbErr> {
     use chrono::NaiveDate;
-    use crate::models::historic_data;
     use rust_decimal::prelude::ToPrimitive;
 
     // Récupérer tous les trades de l'utilisateur
-    let trades = trade::Entity::find()
-        .filter(trade::Column::UserId.eq(auth_user.user_id))
+    let trades = trade::Entity::find_active()
+        .filter(trade::Column::UserId.eq(user_id))
         .order_by_asc(trade::Column::Date)
-        .all(db.get_ref())
-        .await;
-
-    let trades = match trades {
-        Ok(t) => t,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(format!("Error fetching trades: {}", e));
-        }
-    };
+        .all(db)
+        .await?;
 
     // Calculer les positions ouvertes (FIFO) avec date d'entrée
     let mut positions: HashMap<String, (Decimal, Decimal, NaiveDate)> = HashMap::new();
+    // Lots d'achat encore ouverts (date, quantite_restante) pour la date moyenne pondérée
+    let mut open_lots: HashMap<String, Vec<(NaiveDate, Decimal)>> = HashMap::new();
 
     for t in &trades {
         let symbol = t.symbol.clone().unwrap_or_default();
@@ -154,16 +351,19 @@ pub async fn get_open_positions_with_recommendations(
         let prix_unitaire = t.prix_unitaire.unwrap_or_default();
         let trade_type = t.trade_type.clone().unwrap_or_default();
 
-        // Parser la date String en NaiveDate (format DD/MM/YYYY)
-        let date = match t.date.clone().unwrap_or_default().as_str() {
-            date_str => {
-                match NaiveDate::parse_from_str(date_str, "%d/%m/%Y") {
-                    Ok(d) => d,
-                    Err(_) => continue,
-                }
-            }
+        // Parser la date String en NaiveDate (YYYY-MM-DD ou ancien format DD/MM/YYYY)
+        let date = match parse_trade_date(t.date.as_deref().unwrap_or_default()) {
+            Some(d) => d,
+            None => continue,
         };
 
+        if trade_type == "achat" && t.quantite_restante > Decimal::ZERO {
+            open_lots
+                .entry(symbol.clone())
+                .or_default()
+                .push((date, t.quantite_restante));
+        }
+
         let entry = positions
             .entry(symbol.clone())
             .or_insert((Decimal::ZERO, Decimal::ZERO, date));
@@ -185,47 +385,31 @@ pub async fn get_open_positions_with_recommendations(
         }
     }
 
-    // Pour chaque position ouverte, récupérer les recommandations + P&L
+    // Pour chaque position ouverte, récupérer les recommandations + P&L + trailing stop
+    let trail_pct = risk_service::trail_pct_from_env();
+    let (today, stale_days) = (market_today(), data_quality::stale_price_days_from_env());
     let mut response: Vec<OpenPositionWithRecommendationsResponse> = Vec::new();
 
-    for (symbol, (quantite_totale, prix_moyen, entry_date)) in positions {
+    for (symbol, (quantite_totale, prix_moyen, first_entry_date)) in positions {
         // Ignorer les positions fermées
         if quantite_totale <= Decimal::ZERO {
             continue;
         }
 
         // Récupérer le prix actuel depuis historic_data_rust (dernière clôture)
-        let latest_price = historic_data::Entity::find()
-            .filter(historic_data::Column::Symbol.eq(&symbol))
-            .order_by_desc(historic_data::Column::Date)
-            .limit(1)
-            .one(db.get_ref())
-            .await;
-
-        let current_price = match latest_price {
-            Ok(Some(data)) => {
-                data.close
-                    .and_then(|close_str| close_str.parse::<f64>().ok())
-                    .and_then(|p| Decimal::from_f64_retain(p))
-                    .unwrap_or(prix_moyen)
-            }
-            Ok(None) => prix_moyen,
-            Err(_) => prix_moyen,
-        };
+        let latest_bar = historic_bars::fetch_latest_bar(&symbol, db).await.ok().flatten();
+        let latest_close = latest_bar.as_ref().and_then(|bar| Decimal::from_f64_retain(bar.close));
+        let current_price = latest_close.unwrap_or(prix_moyen);
+        let price_date = latest_bar.filter(|_| latest_close.is_some()).map(|bar| bar.date);
+        let (price_age_days, stale) = data_quality::freshness(price_date.as_deref(), today, stale_days);
 
         // Calcul du P&L
         let pnl_dollars = (current_price - prix_moyen) * quantite_totale;
-        let pnl_percentage = if prix_moyen > Decimal::ZERO {
-            ((current_price - prix_moyen) / prix_moyen * Decimal::from(100))
-                .to_f64()
-                .unwrap_or(0.0)
-        } else {
-            0.0
-        };
+        let pnl_percentage = money::percentage_change(prix_moyen, current_price);
 
         // Récupérer les stratégies
         let all_strategies = strategy::Entity::find()
-            .all(db.get_ref())
+            .all(db)
             .await;
 
         let strategies = match all_strategies {
@@ -237,40 +421,15 @@ pub async fn get_open_positions_with_recommendations(
                         .filter(strategy_result::Column::StrategyId.eq(strat.id))
                         .filter(strategy_result::Column::Symbol.eq(&symbol))
                         .order_by_desc(strategy_result::Column::Date)
-                        .all(db.get_ref())
+                        .all(db)
                         .await;
 
-                    if let Ok(results) = all_results {
-                        if !results.is_empty() {
-                            let sr = &results[0];
-
-                            let recommendation_str = sr.recommendation.clone().and_then(|v| {
-                                if let Some(s) = v.as_str() {
-                                    return Some(s.to_string());
-                                }
-                                if let Some(arr) = v.as_array() {
-                                    let items: Vec<String> = arr
-                                        .iter()
-                                        .filter_map(|item| {
-                                            if let Some(s) = item.as_str() {
-                                                Some(s.to_string())
-                                            } else {
-                                                Some(item.to_string())
-                                            }
-                                        })
-                                        .collect();
-                                    return Some(format!("[{}]", items.join(", ")));
-                                }
-                                Some(v.to_string())
-                            });
-
-                            strategy_list.push(StrategyWithResult {
-                                strategy_id: strat.id,
-                                strategy_name: strat.name.clone(),
-                                date: sr.date.clone(),
-                                recommendation: recommendation_str,
-                            });
-                        }
+                    if let Ok(results) = all_results
+                        && let Some(sr) = results.into_iter().next()
+                    {
+                        strategy_list.push(
+                            StrategyWithResult::from_result(sr, strat.name.clone()).with_freshness(today, stale_days),
+                        );
                     }
                 }
 
@@ -279,25 +438,57 @@ pub async fn get_open_positions_with_recommendations(
             Err(_) => vec![],
         };
 
+        // Date d'entrée moyenne pondérée par la quantité encore détenue
+        let avg_entry_date = open_lots
+            .get(&symbol)
+            .and_then(|lots| weighted_average_date(lots));
+
+        // Trailing stop depuis l'entrée du plus ancien lot encore ouvert
+        let stop_since = open_lots
+            .get(&symbol)
+            .and_then(|lots| lots.iter().map(|(date, _)| *date).min())
+            .unwrap_or(first_entry_date);
+
+        let highest_close = RiskService::highest_close_since(db, &symbol, stop_since)
+            .await
+            .unwrap_or(None);
+        let trailing_stop = risk_service::trailing_stop(prix_moyen, highest_close, trail_pct);
+        let trailing_stop_breached = latest_close.map(|close| risk_service::is_stop_breached(trailing_stop, close));
+
+        // Support / résistance pivot les plus proches du prix actuel (aucun si pas de point_pivot)
+        let (nearest_support, nearest_resistance) = match RiskService::latest_point_pivot(db, &symbol).await.unwrap_or(None) {
+            Some(point_pivot) => risk_service::nearest_pivot_levels(current_price.to_f64().unwrap_or(0.0), &point_pivot),
+            None => (None, None),
+        };
+
         // Arrondir à 2 décimales
-        let prix_moyen_rounded = prix_moyen.round_dp(2);
-        let current_price_rounded = current_price.round_dp(2);
-        let pnl_dollars_rounded = pnl_dollars.round_dp(2);
-        let pnl_percentage_rounded = (pnl_percentage * 100.0).round() / 100.0;
+        let prix_moyen_rounded = money::round_money(prix_moyen);
+        let current_price_rounded = money::round_money(current_price);
+        let pnl_dollars_rounded = money::round_money(pnl_dollars);
+        let pnl_percentage_rounded = money::percent_f64(pnl_percentage);
 
         response.push(OpenPositionWithRecommendationsResponse {
             symbol,
             quantite_totale,
             prix_moyen: prix_moyen_rounded,
             current_price: Some(current_price_rounded),
+            price_date,
+            price_age_days,
+            stale,
             pnl_dollars: Some(pnl_dollars_rounded),
             pnl_percentage: Some(pnl_percentage_rounded),
-            entry_date: Some(entry_date.to_string()),
+            entry_date: Some(first_entry_date.to_string()),
+            first_entry_date: Some(first_entry_date.to_string()),
+            avg_entry_date: avg_entry_date.map(|d| d.to_string()),
+            trailing_stop: Some(trailing_stop),
+            trailing_stop_breached,
+            nearest_support,
+            nearest_resistance,
             strategies,
         });
     }
 
-    HttpResponse::Ok().json(response)
+    Ok(response)
 }
 
 #[get("/closed")]
@@ -305,8 +496,6 @@ pub async fn get_closed_trades(
     db: web::Data<DatabaseConnection>,
     auth_user: AuthUser,
 ) -> impl Responder {
-    use crate::models::trades_fermes;
-
     let closed_trades = trades_fermes::Entity::find()
         .filter(trades_fermes::Column::UserId.eq(auth_user.user_id))
         .order_by_desc(trades_fermes::Column::DateVente)
@@ -317,18 +506,7 @@ pub async fn get_closed_trades(
         Ok(trades) => {
             let response: Vec<ClosedTradeResponse> = trades
                 .into_iter()
-                .map(|t| ClosedTradeResponse {
-                    symbol: t.symbol.unwrap_or_default(),
-                    date_achat: t.date_achat.unwrap_or_default(),
-                    prix_achat: t.prix_achat.unwrap_or_default(),
-                    date_vente: t.date_vente.unwrap_or_default(),
-                    prix_vente: t.prix_vente.unwrap_or_default(),
-                    pourcentage_gain: t.pourcentage_gain.unwrap_or(0),
-                    gain_dollars: t.gain_dollars.unwrap_or_default(),
-                    temps_jours: t.temps_jours.unwrap_or(0),
-                    trade_achat_id: t.trade_achat_id.unwrap_or(0),
-                    trade_vente_id: t.trade_vente_id.unwrap_or(0),
-                })
+                .map(to_closed_trade_response)
                 .collect();
             HttpResponse::Ok().json(response)
         }
@@ -336,13 +514,476 @@ pub async fn get_closed_trades(
     }
 }
 
+/// POST /api/trades/close/{symbol} - Vendre toute la position ouverte d'un symbole
+#[post("/close/{symbol}")]
+pub async fn close_position(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    path: web::Path<String>,
+    request: web::Json<ClosePositionRequest>,
+) -> impl Responder {
+    if let Err(errors) = request.validate() {
+        return validation_error_response(&errors);
+    }
+
+    if let Err(response) = ensure_trading_allowed(&db, auth_user.user_id).await {
+        return *response;
+    }
+
+    let symbol = path.into_inner();
+    let request = request.into_inner();
+
+    // Quantité encore ouverte (somme des quantite_restante FIFO)
+    let quantite = match TradeService::get_available_quantity(&db, auth_user.user_id, &symbol).await {
+        Ok(q) => q,
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    if quantite <= Decimal::ZERO {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("No open position for {}", symbol)
+        }));
+    }
+
+    match TradeService::close_position(
+        &db,
+        auth_user.user_id,
+        &symbol,
+        quantite,
+        request.prix_unitaire,
+        request.date,
+    ).await {
+        Ok((sale_trade, closed_trades)) => HttpResponse::Created().json(ClosePositionResponse {
+            trade: to_trade_response(sale_trade),
+            closed_trades: closed_trades.into_iter().map(to_closed_trade_response).collect(),
+        }),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// GET /api/trades/{id} - Détail d'un trade (404 si inexistant ou appartenant à un autre utilisateur)
+#[get("/{id:\\d+}")]
+pub async fn get_trade(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let trade_id = path.into_inner();
+
+    let found = trade::Entity::find_active()
+        .filter(trade::Column::Id.eq(trade_id))
+        .filter(trade::Column::UserId.eq(auth_user.user_id))
+        .one(db.get_ref())
+        .await;
+
+    let t = match found {
+        Ok(Some(t)) => t,
+        Ok(None) => return HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Trade {} not found", trade_id)
+        })),
+        Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    };
+
+    // Pour un achat : trades fermés ayant consommé ce lot (FIFO)
+    let closed_trade_ids = if t.trade_type.as_deref() == Some("achat") {
+        let closed = trades_fermes::Entity::find()
+            .filter(trades_fermes::Column::UserId.eq(auth_user.user_id))
+            .filter(trades_fermes::Column::TradeAchatId.eq(t.id))
+            .order_by_asc(trades_fermes::Column::DateVente)
+            .all(db.get_ref())
+            .await;
+
+        match closed {
+            Ok(closed) => Some(closed.into_iter().map(|c| c.id).collect()),
+            Err(e) => return HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        }
+    } else {
+        None
+    };
+
+    let quantite_restante = t.quantite_restante;
+
+    HttpResponse::Ok().json(TradeDetailResponse {
+        trade: to_trade_response(t),
+        quantite_restante,
+        closed_trade_ids,
+    })
+}
+
+/// DELETE /api/trades/{id} - Soft-delete d'un trade encore hors historique FIFO
+#[delete("/{id:\\d+}")]
+pub async fn delete_trade(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let trade_id = path.into_inner();
+
+    match TradeService::soft_delete_trade(&db, auth_user.user_id, trade_id).await {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(DeleteTradeError::NotFound) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Trade {} not found", trade_id)
+        })),
+        Err(DeleteTradeError::Consumed(reason)) => HttpResponse::Conflict().json(serde_json::json!({
+            "error": reason,
+            "code": "trade_consumed"
+        })),
+        Err(DeleteTradeError::Db(e)) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// POST /api/trades/recalculate - Reconstruire quantite_restante et trades fermés (FIFO)
+#[post("/recalculate")]
+pub async fn recalculate_fifo(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+) -> impl Responder {
+    match TradeService::recalculate_fifo(&db, auth_user.user_id).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(DbErr::Custom(message)) => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": message
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+fn to_trade_response(t: trade::Model) -> TradeResponse {
+    TradeResponse {
+        id: t.id,
+        user_id: t.user_id,
+        symbol: t.symbol.unwrap_or_default(),
+        trade_type: t.trade_type.unwrap_or_default(),
+        quantite: t.quantite.unwrap_or_default(),
+        prix_unitaire: t.prix_unitaire.unwrap_or_default(),
+        prix_total: t.prix_total.unwrap_or_default(),
+        date: t.date.unwrap_or_default(),
+        currency: t.currency,
+        duplicate_of: None,
+    }
+}
+
+fn to_closed_trade_response(t: trades_fermes::Model) -> ClosedTradeResponse {
+    ClosedTradeResponse {
+        symbol: t.symbol.unwrap_or_default(),
+        date_achat: t.date_achat.unwrap_or_default(),
+        prix_achat: t.prix_achat.unwrap_or_default(),
+        date_vente: t.date_vente.unwrap_or_default(),
+        prix_vente: t.prix_vente.unwrap_or_default(),
+        pourcentage_gain: t.pourcentage_gain.unwrap_or_default(),
+        gain_dollars: t.gain_dollars.unwrap_or_default(),
+        temps_jours: t.temps_jours.unwrap_or(0),
+        trade_achat_id: t.trade_achat_id.unwrap_or(0),
+        trade_vente_id: t.trade_vente_id.unwrap_or(0),
+    }
+}
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/trades")
             .route("", web::post().to(create_trade))
+            .service(validate_trade)
+            .service(preview_sale)
             .service(get_all_trades)
             .service(get_open_positions)
             .service(get_open_positions_with_recommendations)
+            .service(get_positions_needing_attention)
             .service(get_closed_trades)
+            .service(close_position)
+            .service(recalculate_fifo)
+            .service(get_trade)
+            .service(delete_trade)
     );
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+    fn position(pnl_percentage: Option<f64>, signals: &[(&str, &str)]) -> OpenPositionWithRecommendationsResponse {
+        OpenPositionWithRecommendationsResponse {
+            symbol: "AAPL".to_string(),
+            quantite_totale: Decimal::from(10),
+            prix_moyen: Decimal::from(100),
+            current_price: None,
+            price_date: None,
+            price_age_days: None,
+            stale: false,
+            pnl_dollars: None,
+            pnl_percentage,
+            entry_date: None,
+            first_entry_date: None,
+            avg_entry_date: None,
+            trailing_stop: None,
+            trailing_stop_breached: None,
+            nearest_support: None,
+            nearest_resistance: None,
+            strategies: signals
+                .iter()
+                .enumerate()
+                .map(|(i, (name, signal))| StrategyWithResult {
+                    strategy_id: i as i32 + 1,
+                    strategy_name: Some(name.to_string()),
+                    date: Some("2025-06-30".to_string()),
+                    recommendation: Some(signal.to_string()),
+                    confidence: None,
+                    age_days: None,
+                    stale: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_sell_from_any_strategy_needs_attention() {
+        let reasons = attention_reasons(&position(Some(2.0), &[("RSI", "HOLD"), ("EMA", "SELL")]), -10.0, 20.0);
+        assert_eq!(reasons, vec!["SELL from EMA".to_string()]);
+    }
+
+    #[test]
+    fn test_loss_below_threshold_needs_attention() {
+        let reasons = attention_reasons(&position(Some(-12.5), &[("RSI", "BUY")]), -10.0, 20.0);
+        assert_eq!(reasons, vec!["P&L -12.50% below -10%".to_string()]);
+    }
+
+    #[test]
+    fn test_gain_above_take_profit_needs_attention() {
+        let reasons = attention_reasons(&position(Some(25.0), &[("RSI", "SELL")]), -10.0, 20.0);
+        assert_eq!(reasons, vec!["SELL from RSI".to_string(), "P&L 25.00% above 20%".to_string()]);
+    }
+
+    #[test]
+    fn test_quiet_position_is_not_flagged() {
+        // Seuils stricts : -10% / +20% pile ne déclenchent rien
+        assert!(attention_reasons(&position(Some(-10.0), &[("RSI", "HOLD")]), -10.0, 20.0).is_empty());
+        assert!(attention_reasons(&position(Some(20.0), &[]), -10.0, 20.0).is_empty());
+        assert!(attention_reasons(&position(None, &[("RSI", "BUY")]), -10.0, 20.0).is_empty());
+    }
+
+    async fn trades_db(rows: &[(&str, &str, &str)]) -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table users ici
+        let table = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(trade::Entity);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+
+        for (symbol, trade_type, date) in rows {
+            trade::ActiveModel {
+                user_id: Set(1),
+                symbol: Set(Some(symbol.to_string())),
+                trade_type: Set(Some(trade_type.to_string())),
+                date: Set(Some(date.to_string())),
+                quantite: Set(Some(Decimal::from(1))),
+                prix_unitaire: Set(Some(Decimal::from(10))),
+                prix_total: Set(Some(Decimal::from(10))),
+                quantite_restante: Set(Decimal::ZERO),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    async fn page(db: &DatabaseConnection, query: TradesQuery) -> TradePageResponse {
+        let (limit, offset) = query.page().unwrap();
+        fetch_trade_page(db, query.condition(1).unwrap(), limit, offset).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_trades_symbol_and_date_filters() {
+        let db = trades_db(&[
+            ("AAPL", "achat", "2025-01-10"),
+            ("MSFT", "achat", "2025-01-11"),
+            ("AAPL", "vente", "2025-02-01"),
+            ("AAPL", "achat", "2025-03-01"),
+        ])
+        .await;
+
+        let aapl = page(&db, TradesQuery { symbol: Some("aapl".into()), ..Default::default() }).await;
+        assert_eq!(aapl.total, 3);
+        let dates: Vec<&str> = aapl.items.iter().map(|t| t.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-03-01", "2025-02-01", "2025-01-10"]);
+
+        let query = TradesQuery {
+            symbol: Some("AAPL".into()),
+            trade_type: Some("achat".into()),
+            from: Some("2025-01-01".into()),
+            to: Some("2025-01-31".into()),
+            ..Default::default()
+        };
+        let filtered = page(&db, query).await;
+        assert_eq!((filtered.total, filtered.items.len()), (1, 1));
+        assert_eq!(filtered.items[0].date, "2025-01-10");
+
+        // Un autre utilisateur ne voit rien
+        let other = fetch_trade_page(&db, TradesQuery::default().condition(2).unwrap(), 10, 0).await.unwrap();
+        assert_eq!(other.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_trades_pagination_math() {
+        let days: Vec<String> = (1..=7).map(|day| format!("2025-01-{:02}", day)).collect();
+        let rows: Vec<(&str, &str, &str)> = days.iter().map(|d| ("AAPL", "achat", d.as_str())).collect();
+        let db = trades_db(&rows).await;
+
+        let second = page(&db, TradesQuery { limit: Some(3), offset: Some(3), ..Default::default() }).await;
+        assert_eq!((second.total, second.limit, second.offset), (7, 3, 3));
+        let dates: Vec<&str> = second.items.iter().map(|t| t.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-01-04", "2025-01-03", "2025-01-02"]);
+
+        let last = page(&db, TradesQuery { limit: Some(3), offset: Some(6), ..Default::default() }).await;
+        assert_eq!(last.items.len(), 1);
+        let beyond = page(&db, TradesQuery { limit: Some(3), offset: Some(9), ..Default::default() }).await;
+        assert_eq!((beyond.total, beyond.items.len()), (7, 0));
+
+        assert_eq!(TradesQuery::default().page(), Ok((DEFAULT_TRADES_LIMIT, 0)));
+        assert!(TradesQuery { limit: Some(0), ..Default::default() }.page().is_err());
+        assert!(TradesQuery { limit: Some(501), ..Default::default() }.page().is_err());
+        assert!(TradesQuery { trade_type: Some("buy".into()), ..Default::default() }.condition(1).is_err());
+        assert!(TradesQuery { from: Some("01/02/2025".into()), ..Default::default() }.condition(1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_positions_flag_stale_prices_and_recommendations() {
+        use crate::models::historic_data;
+
+        let today = market_today();
+        let fresh_date = today.format("%Y-%m-%d").to_string();
+        let stale_date = (today - chrono::Duration::days(30)).format("%Y-%m-%d").to_string();
+        let db = trades_db(&[("FRESH", "achat", "2025-01-10"), ("STALE", "achat", "2025-01-10")]).await;
+
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(historic_data::Entity),
+            schema.create_table_from_entity(strategy::Entity),
+            schema.create_table_from_entity(strategy_result::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        for (id, (symbol, date)) in [("FRESH", &fresh_date), ("STALE", &stale_date)].into_iter().enumerate() {
+            historic_data::ActiveModel {
+                symbol: Set(symbol.to_string()),
+                date: Set(date.clone()),
+                open: Set(Some("12".to_string())),
+                high: Set(Some("12".to_string())),
+                low: Set(Some("12".to_string())),
+                close: Set(Some("12".to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            strategy::ActiveModel { id: Set(id as i32 + 1), name: Set(Some(format!("S{}", id + 1))), ..Default::default() }
+                .insert(&db)
+                .await
+                .unwrap();
+            strategy_result::ActiveModel {
+                strategy_id: Set(id as i32 + 1),
+                symbol: Set(Some(symbol.to_string())),
+                date: Set(Some(date.clone())),
+                recommendation: Set(Some(serde_json::json!({"signal": "HOLD", "confidence": 0.5}))),
+                metadata: Set(None),
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let positions = open_positions_with_recommendations(&db, 1).await.unwrap();
+        let by_symbol = |symbol: &str| positions.iter().find(|p| p.symbol == symbol).unwrap();
+
+        let fresh = by_symbol("FRESH");
+        assert_eq!((fresh.price_date.as_deref(), fresh.price_age_days, fresh.stale), (Some(fresh_date.as_str()), Some(0), false));
+        assert_eq!((fresh.strategies[0].age_days, fresh.strategies[0].stale), (Some(0), Some(false)));
+
+        let stale = by_symbol("STALE");
+        assert_eq!((stale.price_date.as_deref(), stale.price_age_days, stale.stale), (Some(stale_date.as_str()), Some(30), true));
+        assert_eq!((stale.strategies[0].age_days, stale.strategies[0].stale), (Some(30), Some(true)));
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_trade_type_uses_api_error_envelope() {
+        use actix_web::body::to_bytes;
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let request = CreateTradeRequest {
+            symbol: "AAPL".to_string(),
+            trade_type: "buy".to_string(),
+            quantite: Decimal::from(10),
+            prix_unitaire: Decimal::from(150),
+            date: "2025-03-03".to_string(),
+            currency: None,
+        };
+        let auth_user = AuthUser { user_id: 1, username: "alice".to_string(), api_key_id: None };
+
+        let response = create_trade(web::Data::new(db), auth_user, web::Json(request))
+            .await
+            .respond_to(&actix_web::test::TestRequest::default().to_http_request())
+            .map_into_boxed_body();
+        assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({
+            "error": "Validation failed",
+            "code": "validation_failed",
+            "validation": [
+                {"field": "trade_type", "code": "invalid_trade_type", "message": "must be 'achat' or 'vente'"}
+            ]
+        }));
+    }
+
+    #[actix_web::test]
+    async fn test_trade_routes_return_423_once_armed() {
+        use crate::middleware::auth::API_KEY_HEADER;
+        use crate::models::{api_keys, emergency_stop, users};
+        use crate::services::api_key_service::{self, ApiKeyScope};
+        use crate::services::emergency_stop_service::EmergencyStopService;
+        use actix_web::{test, App};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(users::Entity),
+            schema.create_table_from_entity(api_keys::Entity),
+            schema.create_table_from_entity(emergency_stop::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        users::ActiveModel {
+            id: Set(1),
+            username: Set("alice".to_string()),
+            email: Set("alice@example.com".to_string()),
+            email_verified: Set(true),
+            auto_post_realized_pnl: Set(false),
+            block_duplicate_trades: Set(false),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let (_, key) = api_key_service::create(1, "bot", ApiKeyScope::ReadWrite, &db).await.unwrap();
+        EmergencyStopService::set_pin(&db, 1, "4821", None).await.unwrap();
+        EmergencyStopService::set_armed(&db, 1, "4821", true).await.unwrap();
+
+        let app = test::init_service(App::new().app_data(web::Data::new(db.clone())).configure(configure)).await;
+        let requests = [
+            ("/trades", serde_json::json!({
+                "symbol": "AAPL", "trade_type": "achat", "quantite": 10, "prix_unitaire": 150, "date": "2025-03-03"
+            })),
+            ("/trades/close/AAPL", serde_json::json!({"prix_unitaire": 150, "date": "2025-03-03"})),
+        ];
+        for (uri, body) in requests {
+            let req = test::TestRequest::post()
+                .uri(uri)
+                .insert_header((API_KEY_HEADER, key.clone()))
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), actix_web::http::StatusCode::LOCKED, "{}", uri);
+            let json: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(json["code"], "emergency_stop_active");
+        }
+    }
+}
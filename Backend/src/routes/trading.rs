@@ -0,0 +1,245 @@
+use actix_web::{get, post, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use validator::Validate;
+
+use crate::middleware::AuthUser;
+use crate::models::dto::{EmergencyStopRequest, EmergencyStopStatus, SetEmergencyPinRequest, TradingPreferences};
+use crate::services::emergency_stop_service::{EmergencyStopError, EmergencyStopService};
+use crate::services::trade_service::TradeService;
+use crate::utils::api_error::validation_error_response;
+
+/// Le PIN et l'arrêt d'urgence se gèrent avec un JWT : une clé d'API (même read_write) est refusée
+fn jwt_required(auth_user: &AuthUser) -> Option<HttpResponse> {
+    auth_user.jwt_required("The emergency stop must be managed with a JWT, not an API key")
+}
+
+/// GET /api/trading/emergency-stop - État de l'arrêt d'urgence
+#[get("/emergency-stop")]
+pub async fn get_emergency_stop(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+) -> HttpResponse {
+    match EmergencyStopService::find(&db, auth_user.user_id).await {
+        Ok(record) => HttpResponse::Ok().json(EmergencyStopStatus {
+            armed: record.as_ref().map(|r| r.armed).unwrap_or(false),
+            pin_set: record.is_some(),
+            updated_at: record.map(|r| r.updated_at),
+        }),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// POST /api/trading/emergency-stop/pin - Définir ou changer le PIN d'arrêt d'urgence
+#[post("/emergency-stop/pin")]
+pub async fn set_emergency_pin(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    body: web::Json<SetEmergencyPinRequest>,
+) -> HttpResponse {
+    if let Some(response) = jwt_required(&auth_user) {
+        return response;
+    }
+
+    if let Err(errors) = body.validate() {
+        return validation_error_response(&errors);
+    }
+
+    match EmergencyStopService::set_pin(&db, auth_user.user_id, &body.pin, body.current_pin.as_deref()).await {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "success": true,
+            "message": "Emergency stop PIN saved"
+        })),
+        Err(e) => error_response(e),
+    }
+}
+
+/// POST /api/trading/emergency-stop - Armer / désarmer l'arrêt d'urgence (PIN requis)
+#[post("/emergency-stop")]
+pub async fn toggle_emergency_stop(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    body: web::Json<EmergencyStopRequest>,
+) -> HttpResponse {
+    if let Some(response) = jwt_required(&auth_user) {
+        return response;
+    }
+
+    match EmergencyStopService::set_armed(&db, auth_user.user_id, &body.pin, body.armed).await {
+        Ok(record) => HttpResponse::Ok().json(EmergencyStopStatus {
+            armed: record.armed,
+            pin_set: true,
+            updated_at: Some(record.updated_at),
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GET /api/trading/preferences - Préférences de trading (limite de positions ouvertes)
+#[get("/preferences")]
+pub async fn get_trading_preferences(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+) -> HttpResponse {
+    match TradeService::trading_preferences(&db, auth_user.user_id).await {
+        Ok(preferences) => HttpResponse::Ok().json(preferences),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// POST /api/trading/preferences - Modifier les préférences (max_open_positions null = pas de limite,
+/// auto_post_realized_pnl absent = désactivé)
+#[post("/preferences")]
+pub async fn set_trading_preferences(
+    db: web::Data<DatabaseConnection>,
+    auth_user: AuthUser,
+    body: web::Json<TradingPreferences>,
+) -> HttpResponse {
+    if let Err(errors) = body.validate() {
+        return validation_error_response(&errors);
+    }
+
+    match TradeService::set_trading_preferences(&db, auth_user.user_id, &body).await {
+        Ok(()) => HttpResponse::Ok().json(body.into_inner()),
+        Err(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+/// Refuse tout ordre (423 Locked) si l'arrêt d'urgence de l'utilisateur est armé
+/// À appeler en tête de chaque route qui passe un ordre
+pub async fn ensure_trading_allowed(db: &DatabaseConnection, user_id: i32) -> Result<(), Box<HttpResponse>> {
+    match EmergencyStopService::is_armed(db, user_id).await {
+        Ok(armed) => trading_gate(armed),
+        Err(e) => Err(Box::new(HttpResponse::InternalServerError().json(format!("Error: {}", e)))),
+    }
+}
+
+fn trading_gate(armed: bool) -> Result<(), Box<HttpResponse>> {
+    if !armed {
+        return Ok(());
+    }
+
+    Err(Box::new(HttpResponse::build(actix_web::http::StatusCode::LOCKED).json(serde_json::json!({
+        "error": "Emergency stop is armed, trading is halted",
+        "code": "emergency_stop_active"
+    }))))
+}
+
+fn error_response(error: EmergencyStopError) -> HttpResponse {
+    match error {
+        EmergencyStopError::PinNotSet => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Emergency stop PIN is not set",
+            "code": "pin_not_set"
+        })),
+        EmergencyStopError::InvalidPin => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": "Invalid PIN",
+            "code": "invalid_pin"
+        })),
+        EmergencyStopError::PinIsPassword => HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "The PIN must be different from the login password",
+            "code": "pin_is_password"
+        })),
+        EmergencyStopError::Locked(until) => {
+            let retry_after = (until - chrono::Utc::now().naive_utc()).num_seconds().max(1);
+
+            HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(serde_json::json!({
+                    "error": "Too many invalid PIN attempts, PIN temporarily locked",
+                    "code": "pin_locked",
+                    "retry_after_seconds": retry_after
+                }))
+        }
+        EmergencyStopError::Hash(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+        EmergencyStopError::Db(e) => HttpResponse::InternalServerError().json(format!("Error: {}", e)),
+    }
+}
+
+pub fn trading_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/trading")
+            .service(get_emergency_stop)
+            .service(set_emergency_pin)
+            .service(toggle_emergency_stop)
+            .service(get_trading_preferences)
+            .service(set_trading_preferences)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+
+    #[test]
+    fn test_trades_rejected_with_423_while_armed() {
+        assert!(trading_gate(false).is_ok());
+
+        let response = trading_gate(true).unwrap_err();
+        assert_eq!(response.status(), StatusCode::LOCKED);
+    }
+
+    #[test]
+    fn test_pin_errors_are_distinct() {
+        assert_eq!(error_response(EmergencyStopError::InvalidPin).status(), StatusCode::FORBIDDEN);
+        assert_eq!(error_response(EmergencyStopError::PinNotSet).status(), StatusCode::BAD_REQUEST);
+
+        let locked = error_response(EmergencyStopError::Locked(chrono::Utc::now().naive_utc() + chrono::Duration::minutes(5)));
+        assert_eq!(locked.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(locked.headers().contains_key("Retry-After"));
+    }
+
+    #[actix_web::test]
+    async fn test_api_keys_cannot_manage_the_emergency_stop() {
+        use crate::middleware::auth::API_KEY_HEADER;
+        use crate::models::{api_keys, emergency_stop, users};
+        use crate::services::api_key_service::{self, ApiKeyScope};
+        use actix_web::{test, App};
+        use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(users::Entity),
+            schema.create_table_from_entity(api_keys::Entity),
+            schema.create_table_from_entity(emergency_stop::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        users::ActiveModel {
+            id: Set(1),
+            username: Set("alice".to_string()),
+            email: Set("alice@example.com".to_string()),
+            email_verified: Set(true),
+            auto_post_realized_pnl: Set(false),
+            block_duplicate_trades: Set(false),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        EmergencyStopService::set_pin(&db, 1, "4821", None).await.unwrap();
+        let (_, key) = api_key_service::create(1, "bot", ApiKeyScope::ReadWrite, &db).await.unwrap();
+
+        let app = test::init_service(App::new().app_data(web::Data::new(db.clone())).configure(trading_routes)).await;
+        let requests = [
+            ("/trading/emergency-stop/pin", serde_json::json!({"pin": "9999", "current_pin": "4821"})),
+            ("/trading/emergency-stop", serde_json::json!({"pin": "4821", "armed": true})),
+        ];
+        for (uri, body) in requests {
+            let req = test::TestRequest::post()
+                .uri(uri)
+                .insert_header((API_KEY_HEADER, key.clone()))
+                .set_json(body)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", uri);
+            let json: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(json["code"], "jwt_required");
+        }
+
+        // Ni le PIN ni l'état n'ont changé
+        assert!(!EmergencyStopService::is_armed(&db, 1).await.unwrap());
+        assert!(EmergencyStopService::set_armed(&db, 1, "4821", true).await.is_ok());
+    }
+}
@@ -2,10 +2,12 @@ use actix_web::{post, get, web, HttpResponse};
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, Set, ActiveModelTrait};
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
+use std::str::FromStr;
 
-use crate::models::wallet::{Entity as Wallet, Column as WalletColumn, ActiveModel as WalletActiveModel};
-use crate::models::trade::{Entity as Trade, Column as TradeColumn};
+use crate::models::wallet::{Entity as Wallet, Column as WalletColumn, ActiveModel as WalletActiveModel, REAL_ACCOUNT};
 use crate::middleware::AuthUser;
+use crate::services::wallet_service::{CurrencyBalance, WalletService};
+use crate::utils::currency;
 
 // DTO pour ajouter une transaction
 #[derive(Deserialize)]
@@ -14,27 +16,54 @@ pub struct AddTransactionRequest {
     pub action: String,         // "gain", "perte", "ajout", "retrait"
     pub symbol: Option<String>, // Optionnel, NULL pour ajout/retrait
     pub amount: f64,
-    pub currency: String,       // "CAD", "USD", "EUR"
+    pub currency: String,       // Voir utils::currency::SUPPORTED_CURRENCIES
 }
 
 // DTO pour une transaction dans la réponse
+// Les montants sont sérialisés en Decimal (chaîne) pour ne pas perdre de précision via f64
 #[derive(Serialize)]
 pub struct TransactionResponse {
     pub id: i32,
     pub date: String,
     pub action: String,
     pub symbol: Option<String>,
-    pub amount: f64,
+    pub amount: Decimal,
     pub currency: String,
+    pub trade_vente_id: Option<i32>, // Renseigné si l'entrée vient d'une vente (P&L reporté automatiquement)
+}
+
+impl From<crate::models::wallet::Model> for TransactionResponse {
+    fn from(t: crate::models::wallet::Model) -> Self {
+        Self {
+            id: t.id,
+            date: t.date,
+            action: t.action,
+            symbol: t.symbol,
+            amount: t.amount,
+            currency: t.currency,
+            trade_vente_id: t.trade_vente_id,
+        }
+    }
 }
 
 // DTO pour le solde par devise
 #[derive(Serialize)]
 pub struct BalanceResponse {
     pub currency: String,
-    pub total: f64,        // Total du wallet (ajouts + gains - pertes - retraits)
-    pub invested: f64,     // Montant investi dans les trades en cours
-    pub treasury: f64,     // Trésorerie disponible (total - invested)
+    pub total: Decimal,        // Total du wallet (ajouts + gains - pertes - retraits)
+    pub invested: Decimal,     // Montant investi dans les trades en cours
+    pub treasury: Decimal,     // Trésorerie disponible (total - invested)
+}
+
+impl From<CurrencyBalance> for BalanceResponse {
+    fn from(b: CurrencyBalance) -> Self {
+        Self {
+            currency: b.currency,
+            total: b.total,
+            invested: b.invested,
+            treasury: b.treasury,
+        }
+    }
 }
 
 /// POST /api/wallet/transaction - Ajouter une transaction au wallet
@@ -53,10 +82,9 @@ pub async fn add_transaction(
     }
 
     // Valider la devise
-    let valid_currencies = ["CAD", "USD", "EUR"];
-    if !valid_currencies.contains(&body.currency.as_str()) {
+    if !currency::is_supported(&body.currency) {
         return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Invalid currency. Must be one of: CAD, USD, EUR"
+            "error": format!("Invalid currency. Must be one of: {}", currency::supported_list())
         }));
     }
 
@@ -67,16 +95,23 @@ pub async fn add_transaction(
         }));
     }
 
-    // Convertir f64 en Decimal
-    let amount_decimal = match Decimal::from_f64_retain(body.amount) {
-        Some(d) => d,
-        None => {
+    // Convertir f64 en Decimal (via la représentation texte pour garder 100.1 et non 100.0999...)
+    let amount_decimal = match Decimal::from_str(&body.amount.to_string()) {
+        Ok(d) => d,
+        Err(_) => {
             return HttpResponse::BadRequest().json(serde_json::json!({
                 "error": "Invalid amount format"
             }));
         }
     };
 
+    // Valider les décimales selon la devise (JPY: 0, CAD/USD/EUR: 2)
+    if let Err(e) = currency::validate_amount(&body.currency, amount_decimal) {
+        return HttpResponse::BadRequest().json(serde_json::json!({
+            "error": e
+        }));
+    }
+
     // Créer la transaction
     let new_transaction = WalletActiveModel {
         user_id: Set(auth_user.user_id),
@@ -85,6 +120,7 @@ pub async fn add_transaction(
         symbol: Set(body.symbol.clone()),
         amount: Set(amount_decimal),
         currency: Set(body.currency.clone()),
+        account_type: Set(REAL_ACCOUNT.to_string()),
         ..Default::default()
     };
 
@@ -93,14 +129,7 @@ pub async fn add_transaction(
             HttpResponse::Created().json(serde_json::json!({
                 "success": true,
                 "message": "Transaction added successfully",
-                "transaction": {
-                    "id": transaction.id,
-                    "date": transaction.date,
-                    "action": transaction.action,
-                    "symbol": transaction.symbol,
-                    "amount": decimal_to_f64(transaction.amount),
-                    "currency": transaction.currency
-                }
+                "transaction": TransactionResponse::from(transaction)
             }))
         }
         Err(e) => {
@@ -119,6 +148,7 @@ pub async fn get_history(
 ) -> HttpResponse {
     let transactions = Wallet::find()
         .filter(WalletColumn::UserId.eq(auth_user.user_id))
+        .filter(WalletColumn::AccountType.eq(REAL_ACCOUNT))
         .order_by_desc(WalletColumn::Date)
         .order_by_desc(WalletColumn::Id)
         .all(db.get_ref())
@@ -128,14 +158,7 @@ pub async fn get_history(
         Ok(transactions) => {
             let response: Vec<TransactionResponse> = transactions
                 .into_iter()
-                .map(|t| TransactionResponse {
-                    id: t.id,
-                    date: t.date,
-                    action: t.action,
-                    symbol: t.symbol,
-                    amount: decimal_to_f64(t.amount),
-                    currency: t.currency,
-                })
+                .map(TransactionResponse::from)
                 .collect();
 
             HttpResponse::Ok().json(response)
@@ -154,140 +177,109 @@ pub async fn get_balance(
     auth_user: AuthUser,
     db: web::Data<DatabaseConnection>,
 ) -> HttpResponse {
-    // 1. Récupérer toutes les transactions wallet
-    let transactions_result = Wallet::find()
-        .filter(WalletColumn::UserId.eq(auth_user.user_id))
-        .all(db.get_ref())
-        .await;
+    // Tout le calcul reste en Decimal (WalletService), aucune conversion f64
+    match WalletService::calculate_balances(db.get_ref(), auth_user.user_id).await {
+        Ok(balances) => {
+            let response: Vec<BalanceResponse> = balances
+                .into_iter()
+                .map(BalanceResponse::from)
+                .collect();
 
-    let transactions = match transactions_result {
-        Ok(t) => t,
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to fetch wallet: {}", e)
-            }));
+            HttpResponse::Ok().json(response)
         }
-    };
-
-    // 2. Récupérer tous les trades (achats et ventes) pour calculer la position nette
-    let trades_result = Trade::find()
-        .filter(TradeColumn::UserId.eq(auth_user.user_id))
-        .all(db.get_ref())
-        .await;
-
-    let trades = match trades_result {
-        Ok(t) => t,
         Err(e) => {
-            return HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": format!("Failed to fetch trades: {}", e)
-            }));
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to compute balance: {}", e)
+            }))
         }
-    };
+    }
+}
 
-    // 3. Calculer le solde total par devise (wallet)
-    let mut balances: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+/// GET /api/wallet/paper/balance - Solde du compte papier (simulation), séparé du compte réel
+/// Au premier accès, un compte sans entrée papier reçoit PAPER_STARTING_CASH
+#[get("/paper/balance")]
+pub async fn get_paper_balance(
+    auth_user: AuthUser,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    if let Err(e) = WalletService::seed_paper_wallet(db.get_ref(), auth_user.user_id).await {
+        return HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to seed paper wallet: {}", e)
+        }));
+    }
 
-    for transaction in transactions {
-        let balance = balances.entry(transaction.currency.clone()).or_insert(0.0);
-        let amount = decimal_to_f64(transaction.amount);
+    match WalletService::calculate_paper_balances(db.get_ref(), auth_user.user_id).await {
+        Ok(balances) => {
+            let response: Vec<BalanceResponse> = balances
+                .into_iter()
+                .map(BalanceResponse::from)
+                .collect();
 
-        match transaction.action.as_str() {
-            "gain" | "ajout" => *balance += amount,
-            "perte" | "retrait" => *balance -= amount,
-            _ => {}
+            HttpResponse::Ok().json(response)
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": format!("Failed to compute balance: {}", e)
+            }))
         }
     }
+}
 
-    // 4. Calculer le montant investi par devise
-    // On doit joindre avec la table stock pour récupérer la currency de chaque symbole
-    use crate::models::stock::{Entity as Stock, Column as StockColumn};
-
-    let mut invested: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
-
-    for trade in trades {
-        // Récupérer le symbole du trade
-        let symbol = match &trade.symbol {
-            Some(s) => s,
-            None => continue, // Skip si pas de symbole
-        };
-
-        // Trouver le stock correspondant pour récupérer la currency
-        let stock = match Stock::find()
-            .filter(StockColumn::SymbolAlphavantage.eq(symbol))
-            .one(db.get_ref())
-            .await
-        {
-            Ok(Some(s)) => s,
-            Ok(None) => {
-                // Stock non trouvé, on utilise CAD par défaut
-                eprintln!("⚠️  Stock not found for symbol: {}", symbol);
-                continue;
-            }
-            Err(e) => {
-                eprintln!("⚠️  Error fetching stock for symbol {}: {}", symbol, e);
-                continue;
-            }
-        };
-
-        // Récupérer la currency du stock (CAD, USD, EUR)
-        let currency = stock.currency.unwrap_or_else(|| "CAD".to_string());
-
-        let inv = invested.entry(currency).or_insert(0.0);
+pub fn wallet_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/wallet")
+            .service(add_transaction)
+            .service(get_history)
+            .service(get_balance)
+            .service(get_paper_balance)
+    );
+}
 
-        // Calculer le montant investi selon le type de trade
-        let quantite = parse_decimal_field(&trade.quantite).unwrap_or(0.0);
-        let prix_unitaire = parse_decimal_field(&trade.prix_unitaire).unwrap_or(0.0);
-        let montant = quantite * prix_unitaire;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Achat: augmente l'investissement, Vente: diminue l'investissement
-        match trade.trade_type.as_deref() {
-            Some("achat") => *inv += montant,
-            Some("vente") => *inv -= montant,
-            _ => {} // Type inconnu, on ignore
-        }
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
     }
 
-    // 5. Construire la réponse avec total, invested, treasury
-    let mut response: Vec<BalanceResponse> = Vec::new();
-
-    // Récupérer toutes les devises (union des devises du wallet et des trades)
-    let mut all_currencies: std::collections::HashSet<String> = balances.keys().cloned().collect();
-    all_currencies.extend(invested.keys().cloned());
-
-    for currency in all_currencies {
-        let total = *balances.get(&currency).unwrap_or(&0.0);
-        let inv = *invested.get(&currency).unwrap_or(&0.0);
-        let treasury = total - inv;
+    #[test]
+    fn f64_sum_drifts_but_decimal_does_not() {
+        // Reproduit l'ancien écart : 0.1 + 0.2 en f64 != 0.3
+        assert_ne!(0.1_f64 + 0.2_f64, 0.3_f64);
+        assert_eq!(dec("0.1") + dec("0.2"), dec("0.3"));
+    }
 
-        response.push(BalanceResponse {
-            currency,
+    #[test]
+    fn balance_serializes_exact_decimal_amounts() {
+        let total = dec("0.1") + dec("0.2");
+        let invested = dec("0.05");
+        let response = BalanceResponse::from(CurrencyBalance {
+            currency: "CAD".to_string(),
             total,
-            invested: inv,
-            treasury,
+            invested,
+            treasury: total - invested,
         });
-    }
 
-    // Trier par devise
-    response.sort_by(|a, b| a.currency.cmp(&b.currency));
-
-    HttpResponse::Ok().json(response)
-}
-
-// Fonction helper pour convertir Decimal en f64
-fn decimal_to_f64(decimal: Decimal) -> f64 {
-    decimal.to_string().parse::<f64>().unwrap_or(0.0)
-}
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["total"], "0.3");
+        assert_eq!(json["invested"], "0.05");
+        assert_eq!(json["treasury"], "0.25");
+    }
 
-// Fonction helper pour convertir Option<Decimal> en Option<f64>
-fn parse_decimal_field(field: &Option<Decimal>) -> Option<f64> {
-    field.as_ref().map(|d| decimal_to_f64(*d))
-}
+    #[test]
+    fn transaction_serializes_amount_without_f64_round_trip() {
+        let response = TransactionResponse {
+            id: 1,
+            date: "2025-12-20".to_string(),
+            action: "ajout".to_string(),
+            symbol: None,
+            amount: dec("100.10"),
+            currency: "CAD".to_string(),
+            trade_vente_id: None,
+        };
 
-pub fn wallet_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/wallet")
-            .service(add_transaction)
-            .service(get_history)
-            .service(get_balance)
-    );
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["amount"], "100.10");
+    }
 }
\ No newline at end of file
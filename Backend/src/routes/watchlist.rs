@@ -0,0 +1,102 @@
+use actix_web::{delete, get, post, web, HttpResponse};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+use crate::middleware::AuthUser;
+use crate::services::watchlist_service::{self, WatchlistError, MAX_WATCHLIST_SYMBOLS};
+
+pub const MAX_SYMBOL_LENGTH: usize = 20;
+
+#[derive(Deserialize)]
+pub struct WatchlistRequest {
+    pub symbol: String,
+}
+
+/// Symbole normalisé (majuscules, sans espaces) ou message d'erreur
+fn normalize_symbol(symbol: &str) -> Result<String, String> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LENGTH || symbol.chars().any(char::is_whitespace) {
+        return Err(format!("symbol must be 1 to {} characters without spaces", MAX_SYMBOL_LENGTH));
+    }
+    Ok(symbol)
+}
+
+/// GET /api/watchlist - Symboles suivis avec dernier cours et dernières recommandations
+#[get("")]
+pub async fn get_watchlist(auth_user: AuthUser, db: web::Data<DatabaseConnection>) -> HttpResponse {
+    match watchlist_service::list(auth_user.user_id, db.get_ref()).await {
+        Ok(items) => HttpResponse::Ok().json(items),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to fetch watchlist: {}", e)
+        })),
+    }
+}
+
+/// POST /api/watchlist - Suivre un symbole de la table stock (idempotent)
+#[post("")]
+pub async fn add_to_watchlist(
+    auth_user: AuthUser,
+    body: web::Json<WatchlistRequest>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let symbol = match normalize_symbol(&body.symbol) {
+        Ok(symbol) => symbol,
+        Err(e) => return HttpResponse::BadRequest().json(serde_json::json!({ "error": e })),
+    };
+
+    match watchlist_service::add(auth_user.user_id, &symbol, db.get_ref()).await {
+        Ok(true) => HttpResponse::Created().json(serde_json::json!({ "symbol": symbol, "added": true })),
+        Ok(false) => HttpResponse::Ok().json(serde_json::json!({ "symbol": symbol, "added": false })),
+        Err(WatchlistError::UnknownSymbol(symbol)) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("Unknown symbol: {}", symbol),
+            "code": "unknown_symbol"
+        })),
+        Err(WatchlistError::LimitReached) => HttpResponse::Forbidden().json(serde_json::json!({
+            "error": format!("Watchlist is limited to {} symbols", MAX_WATCHLIST_SYMBOLS),
+            "code": "watchlist_limit_reached"
+        })),
+        Err(WatchlistError::Database(e)) => HttpResponse::InternalServerError().json(serde_json::json!({
+            "error": format!("Failed to add to watchlist: {}", e)
+        })),
+    }
+}
+
+/// DELETE /api/watchlist/{symbol} - Ne plus suivre un symbole
+#[delete("/{symbol}")]
+pub async fn remove_from_watchlist(
+    auth_user: AuthUser,
+    path: web::Path<String>,
+    db: web::Data<DatabaseConnection>,
+) -> HttpResponse {
+    let symbol = path.into_inner().trim().to_uppercase();
+
+    match watchlist_service::remove(auth_user.user_id, &symbol, db.get_ref()).await {
+        Ok(true) => HttpResponse::NoContent().finish(),
+        Ok(false) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("{} is not in the watchlist", symbol)
+        })),
+        Err(e) => HttpResponse::InternalServerError().json(serde_json::json!({ "error": e })),
+    }
+}
+
+pub fn watchlist_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/watchlist")
+            .service(get_watchlist)
+            .service(add_to_watchlist)
+            .service(remove_from_watchlist)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchlist_symbol_is_normalized() {
+        assert_eq!(normalize_symbol(" shop.to "), Ok("SHOP.TO".to_string()));
+        assert!(normalize_symbol("  ").is_err());
+        assert!(normalize_symbol("BRK B").is_err());
+        assert!(normalize_symbol(&"X".repeat(MAX_SYMBOL_LENGTH + 1)).is_err());
+    }
+}
@@ -0,0 +1,1118 @@
+// Rendement pondéré par l'argent (money-weighted return / IRR annualisé).
+// Flux externes du wallet : ajout = argent investi (négatif), retrait = argent récupéré (positif).
+// La valeur actuelle du portefeuille (trésorerie + positions ouvertes au dernier cours)
+// est ajoutée comme flux final positif à la date de bourse du jour.
+// Concentration : poids de chaque position ouverte dans la valeur de marché investie
+// d'une devise, part des N plus grosses lignes et indice de Herfindahl-Hirschman.
+// Rendement pondéré par le temps (TWR) : la courbe de valeur (wallet + plus-values latentes
+// des lots ouverts au close du jour) est découpée à chaque ajout / retrait, et les rendements
+// des sous-périodes sont chaînés — le calendrier des dépôts ne pèse plus sur la performance.
+// Performance glissante : gain réalisé, nombre de trades fermés et taux de réussite des trades
+// fermés (date_vente) sur 7 / 30 / 90 / 365 jours, par devise.
+// Corrélation : coefficient de Pearson des rendements journaliers (close ajusté si disponible)
+// de chaque paire de symboles, calculés sur les seules dates communes aux deux séries.
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::warn;
+
+use crate::models::{historic_data, trade, trades_fermes, wallet};
+use crate::services::historic_bars::{self, PriceSource};
+use crate::services::trade_service::replay_fifo;
+use crate::services::wallet_service::WalletService;
+use crate::utils::currency;
+use crate::utils::dates::{market_today, parse_trade_date};
+
+const DAYS_PER_YEAR: f64 = 365.0;
+const NEWTON_MAX_ITERATIONS: usize = 50;
+const BISECTION_MAX_ITERATIONS: usize = 200;
+const TOLERANCE: f64 = 1e-9;
+// Borne basse de la recherche : -99.99 % (un taux ≤ -100 % n'a pas de sens)
+const MIN_RATE: f64 = -0.9999;
+const MAX_RATE: f64 = 1e6;
+
+/// Flux de trésorerie daté, du point de vue de l'investisseur
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CashFlow {
+    pub date: NaiveDate,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoneyWeightedReturn {
+    pub currency: String,
+    pub irr: Option<f64>,          // Taux annualisé (0.12 = 12 %/an), None si indéfini
+    pub current_value: Decimal,    // Trésorerie + valeur de marché des positions ouvertes
+    pub cash_flows: usize,         // Nombre d'ajouts / retraits pris en compte
+    pub as_of: NaiveDate,
+    pub note: Option<&'static str>, // "no_cash_flows" | "irr_undefined"
+}
+
+/// Seuil de concentration par défaut : une ligne au-delà de 25 % de la valeur investie est signalée
+pub const DEFAULT_CONCENTRATION_THRESHOLD_PCT: f64 = 25.0;
+
+/// Nombre de lignes cumulées par défaut pour la concentration "top N"
+pub const DEFAULT_CONCENTRATION_TOP_N: usize = 3;
+
+/// Seuil configuré via CONCENTRATION_THRESHOLD_PCT (défaut: 25)
+pub fn concentration_threshold_from_env() -> f64 {
+    std::env::var("CONCENTRATION_THRESHOLD_PCT")
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|v| *v > 0.0 && *v <= 100.0)
+        .unwrap_or(DEFAULT_CONCENTRATION_THRESHOLD_PCT)
+}
+
+/// Valeur de marché d'une position ouverte (tous lots du symbole confondus)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionValue {
+    pub symbol: String,
+    pub quantity: Decimal,
+    pub market_value: Decimal,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PositionWeight {
+    pub symbol: String,
+    pub quantity: Decimal,
+    pub market_value: Decimal,
+    pub share_pct: f64, // Part de la valeur investie totale, en %
+    pub above_threshold: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConcentrationReport {
+    pub currency: String,
+    pub total_value: Decimal,
+    pub positions: Vec<PositionWeight>, // Du plus gros poids au plus petit
+    pub top_n: usize,
+    pub top_n_share_pct: f64,
+    pub hhi: f64, // Σ (part en %)² : 10000 = une seule ligne, 10000 / n = n lignes égales
+    pub threshold_pct: f64,
+    pub concentrated: Vec<String>, // Symboles au-delà du seuil
+    pub as_of: NaiveDate,
+}
+
+/// Valeur du portefeuille d'une devise en fin de journée
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub date: NaiveDate,
+    pub equity: Decimal, // Total wallet + plus-values latentes des lots ouverts
+}
+
+/// Rendement d'une sous-période entre deux flux externes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubPeriodReturn {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub start_equity: f64,
+    pub end_equity: f64,      // Après le flux du jour de fin
+    pub flow: f64,            // Flux entrant le jour de fin (ajout > 0, retrait < 0)
+    pub rate: Option<f64>,    // None si la valeur de départ est nulle (sous-période ignorée)
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeWeightedReturn {
+    pub currency: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub twr: Option<f64>,           // Rendement chaîné sur la période (0.10 = 10 %), non annualisé
+    pub start_equity: Decimal,
+    pub end_equity: Decimal,
+    pub cash_flows: usize,          // Ajouts / retraits dans ]from, to]
+    pub sub_periods: Vec<SubPeriodReturn>,
+    pub note: Option<&'static str>, // "no_equity"
+}
+
+/// Fenêtres glissantes de GET /api/portfolio/performance (libellé, jours)
+pub const PERFORMANCE_WINDOWS: [(&str, i64); 4] = [("7d", 7), ("30d", 30), ("90d", 90), ("1y", 365)];
+
+/// P&L réalisé d'un ensemble de trades fermés
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RealizedPnl {
+    pub closed_trades: usize,
+    pub realized_gain: Decimal,   // Somme des gain_dollars
+    pub winning_trades: usize,    // gain_dollars > 0
+    pub win_rate_pct: Option<f64>, // None si aucun trade fermé
+}
+
+/// Performance réalisée d'une fenêtre glissante
+#[derive(Debug, Serialize)]
+pub struct PerformanceWindow {
+    pub window: &'static str,
+    pub from: NaiveDate, // Premier jour inclus
+    pub to: NaiveDate,
+    #[serde(flatten)]
+    pub pnl: RealizedPnl,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurrencyPerformance {
+    pub currency: String,
+    pub windows: Vec<PerformanceWindow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PerformanceSummary {
+    pub as_of: NaiveDate,
+    pub currencies: Vec<CurrencyPerformance>,
+}
+
+/// Fenêtre par défaut de GET /api/analytics/correlation, en jours calendaires
+pub const DEFAULT_CORRELATION_DAYS: u32 = 90;
+pub const MIN_CORRELATION_DAYS: u32 = 10;
+pub const MAX_CORRELATION_DAYS: u32 = 730;
+pub const MAX_CORRELATION_SYMBOLS: usize = 20;
+/// En dessous de ce nombre de rendements communs, la corrélation d'une paire n'est pas publiée
+pub const MIN_CORRELATION_OBSERVATIONS: usize = 5;
+
+/// Matrice de corrélation : matrix[i][j] et observations[i][j] suivent l'ordre de `symbols`
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CorrelationMatrix {
+    pub symbols: Vec<String>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub matrix: Vec<Vec<Option<f64>>>,  // None : trop peu de dates communes ou série constante
+    pub observations: Vec<Vec<usize>>,  // Rendements journaliers communs à la paire
+    pub missing: Vec<String>,           // Symboles sans aucun close sur la fenêtre
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Poids de chaque position, concentration des N premières lignes et HHI (calcul pur)
+pub fn concentration_report(
+    currency: &str,
+    mut positions: Vec<PositionValue>,
+    top_n: usize,
+    threshold_pct: f64,
+    as_of: NaiveDate,
+) -> ConcentrationReport {
+    positions.sort_by(|a, b| b.market_value.cmp(&a.market_value).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    let total_value: Decimal = positions.iter().map(|p| p.market_value).sum();
+    let total = total_value.to_f64().unwrap_or(0.0);
+    let share = |value: Decimal| if total > 0.0 { 100.0 * value.to_f64().unwrap_or(0.0) / total } else { 0.0 };
+
+    let shares: Vec<f64> = positions.iter().map(|p| share(p.market_value)).collect();
+    let hhi = shares.iter().map(|s| s * s).sum::<f64>();
+    let top_n_share_pct = shares.iter().take(top_n).sum::<f64>();
+
+    let weights: Vec<PositionWeight> = positions
+        .into_iter()
+        .zip(shares)
+        .map(|(p, share_pct)| PositionWeight {
+            symbol: p.symbol,
+            quantity: p.quantity,
+            market_value: p.market_value,
+            share_pct: round2(share_pct),
+            above_threshold: share_pct > threshold_pct,
+        })
+        .collect();
+
+    ConcentrationReport {
+        currency: currency.to_string(),
+        total_value,
+        concentrated: weights.iter().filter(|w| w.above_threshold).map(|w| w.symbol.clone()).collect(),
+        positions: weights,
+        top_n,
+        top_n_share_pct: round2(top_n_share_pct),
+        hhi: round2(hhi),
+        threshold_pct,
+        as_of,
+    }
+}
+
+/// Agrège le P&L réalisé des trades fermés dont date_vente ∈ [from, to] (None = tous, y compris
+/// les dates illisibles)
+pub fn realized_pnl(closed: &[trades_fermes::Model], range: Option<(NaiveDate, NaiveDate)>) -> RealizedPnl {
+    let in_range: Vec<&trades_fermes::Model> = closed
+        .iter()
+        .filter(|c| match range {
+            Some((from, to)) => c
+                .date_vente
+                .as_deref()
+                .and_then(parse_trade_date)
+                .is_some_and(|d| d >= from && d <= to),
+            None => true,
+        })
+        .collect();
+
+    let winning_trades = in_range.iter().filter(|c| c.gain_dollars.is_some_and(|g| g > Decimal::ZERO)).count();
+    let closed_trades = in_range.len();
+
+    RealizedPnl {
+        closed_trades,
+        realized_gain: in_range.iter().filter_map(|c| c.gain_dollars).sum(),
+        winning_trades,
+        win_rate_pct: (closed_trades > 0).then(|| round2(100.0 * winning_trades as f64 / closed_trades as f64)),
+    }
+}
+
+/// P&L réalisé de chaque fenêtre de PERFORMANCE_WINDOWS se terminant à `as_of` (calcul pur)
+pub fn performance_windows(closed: &[trades_fermes::Model], as_of: NaiveDate) -> Vec<PerformanceWindow> {
+    PERFORMANCE_WINDOWS
+        .iter()
+        .map(|(window, days)| {
+            let from = as_of - chrono::Duration::days(days - 1);
+            PerformanceWindow { window, from, to: as_of, pnl: realized_pnl(closed, Some((from, as_of))) }
+        })
+        .collect()
+}
+
+/// Dernier close connu à une date (closes triés par date)
+fn close_on_or_before(closes: &[(NaiveDate, Decimal)], date: NaiveDate) -> Option<Decimal> {
+    let idx = closes.partition_point(|(d, _)| *d <= date);
+    idx.checked_sub(1).map(|i| closes[i].1)
+}
+
+/// Courbe de valeur d'une devise aux dates demandées (calcul pur).
+/// Valeur = ajouts + gains - pertes - retraits datés ≤ jour, + (close du jour - prix d'achat) × quantité
+/// restante des lots ouverts à ce jour (rejeu FIFO des trades datés ≤ jour ; prix d'achat si aucun close).
+/// `trades` : trades déjà filtrés sur la devise
+pub fn equity_curve(
+    dates: &[NaiveDate],
+    transactions: &[wallet::Model],
+    trades: &[trade::Model],
+    closes: &HashMap<String, Vec<(NaiveDate, Decimal)>>,
+    currency: &str,
+) -> Vec<EquityPoint> {
+    let dated_transactions: Vec<(NaiveDate, &wallet::Model)> = transactions
+        .iter()
+        .filter(|t| t.currency == currency)
+        .filter_map(|t| parse_trade_date(&t.date).map(|d| (d, t)))
+        .collect();
+
+    dates
+        .iter()
+        .map(|&date| {
+            let total: Decimal = dated_transactions
+                .iter()
+                .filter(|(d, _)| *d <= date)
+                .map(|(_, t)| match t.action.as_str() {
+                    "gain" | "ajout" => t.amount,
+                    "perte" | "retrait" => -t.amount,
+                    _ => Decimal::ZERO,
+                })
+                .sum();
+
+            let to_date: Vec<trade::Model> = trades
+                .iter()
+                .filter(|t| t.date.as_deref().and_then(parse_trade_date).is_some_and(|d| d <= date))
+                .cloned()
+                .collect();
+            let remaining = match replay_fifo(&to_date) {
+                Ok(replay) => replay.remaining,
+                Err(e) => {
+                    warn!("Equity curve on {}: FIFO replay failed ({}), open lots ignored", date, e);
+                    HashMap::new()
+                }
+            };
+
+            let unrealized: Decimal = to_date
+                .iter()
+                .filter_map(|t| {
+                    let quantity = *remaining.get(&t.id)?;
+                    let buy_price = t.prix_unitaire?;
+                    let close = t
+                        .symbol
+                        .as_ref()
+                        .and_then(|s| closes.get(s))
+                        .and_then(|series| close_on_or_before(series, date))
+                        .unwrap_or(buy_price);
+                    Some(quantity * (close - buy_price))
+                })
+                .sum();
+
+            EquityPoint { date, equity: total + unrealized }
+        })
+        .collect()
+}
+
+/// Chaîne les rendements des sous-périodes (calcul pur).
+/// `points[0]` : valeur de départ ; chaque point suivant : valeur en fin de journée (flux inclus) et flux
+/// entrant de ce jour. r = (valeur - flux) / valeur précédente - 1 ; une sous-période partant d'une valeur
+/// nulle ou négative (avant le premier dépôt) est ignorée. None si aucune sous-période n'est mesurable.
+pub fn chain_sub_period_returns(points: &[(EquityPoint, f64)]) -> (Option<f64>, Vec<SubPeriodReturn>) {
+    let mut growth = 1.0;
+    let mut measured = false;
+
+    let sub_periods: Vec<SubPeriodReturn> = points
+        .windows(2)
+        .map(|pair| {
+            let (start, _) = pair[0];
+            let (end, flow) = pair[1];
+            let start_equity = start.equity.to_f64().unwrap_or(0.0);
+            let end_equity = end.equity.to_f64().unwrap_or(0.0);
+
+            let rate = (start_equity > 0.0).then(|| (end_equity - flow) / start_equity - 1.0);
+            if let Some(rate) = rate {
+                growth *= 1.0 + rate;
+                measured = true;
+            }
+
+            SubPeriodReturn { start: start.date, end: end.date, start_equity, end_equity, flow, rate }
+        })
+        .collect();
+
+    (measured.then_some(growth - 1.0), sub_periods)
+}
+
+fn year_fraction(origin: NaiveDate, date: NaiveDate) -> f64 {
+    (date - origin).num_days() as f64 / DAYS_PER_YEAR
+}
+
+fn npv(flows: &[CashFlow], origin: NaiveDate, rate: f64) -> f64 {
+    flows
+        .iter()
+        .map(|f| f.amount / (1.0 + rate).powf(year_fraction(origin, f.date)))
+        .sum()
+}
+
+fn npv_derivative(flows: &[CashFlow], origin: NaiveDate, rate: f64) -> f64 {
+    flows
+        .iter()
+        .map(|f| {
+            let t = year_fraction(origin, f.date);
+            -t * f.amount / (1.0 + rate).powf(t + 1.0)
+        })
+        .sum()
+}
+
+/// IRR annualisé (convention XIRR, base 365 jours) : Newton, puis bisection si Newton diverge.
+/// None s'il n'y a pas au moins un flux positif et un flux négatif, ou si aucun taux n'annule la VAN.
+pub fn xirr(flows: &[CashFlow]) -> Option<f64> {
+    let has_positive = flows.iter().any(|f| f.amount > 0.0);
+    let has_negative = flows.iter().any(|f| f.amount < 0.0);
+    if !has_positive || !has_negative {
+        return None;
+    }
+
+    let origin = flows.iter().map(|f| f.date).min()?;
+    // Tous les flux le même jour : la VAN ne dépend pas du taux
+    if flows.iter().all(|f| f.date == origin) {
+        return None;
+    }
+
+    // 1. Newton-Raphson depuis 10 %
+    let mut rate = 0.1;
+    for _ in 0..NEWTON_MAX_ITERATIONS {
+        let value = npv(flows, origin, rate);
+        let derivative = npv_derivative(flows, origin, rate);
+        if derivative == 0.0 || !derivative.is_finite() {
+            break;
+        }
+
+        let next = rate - value / derivative;
+        if !next.is_finite() || next <= MIN_RATE {
+            break;
+        }
+        if (next - rate).abs() < TOLERANCE {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    // 2. Bisection : élargir la borne haute jusqu'au changement de signe
+    let mut low = MIN_RATE;
+    let mut high = 1.0;
+    let low_value = npv(flows, origin, low);
+    while low_value.signum() == npv(flows, origin, high).signum() {
+        high *= 2.0;
+        if high > MAX_RATE {
+            return None;
+        }
+    }
+
+    for _ in 0..BISECTION_MAX_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        let mid_value = npv(flows, origin, mid);
+        if mid_value.abs() < TOLERANCE || (high - low) / 2.0 < TOLERANCE {
+            return Some(mid);
+        }
+        if mid_value.signum() == low_value.signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some((low + high) / 2.0)
+}
+
+/// Ajouts (négatifs) et retraits (positifs) d'une devise ; gains / pertes sont internes au portefeuille
+pub fn external_cash_flows(transactions: &[wallet::Model], currency: &str) -> Vec<CashFlow> {
+    transactions
+        .iter()
+        .filter(|t| t.currency == currency)
+        .filter_map(|t| {
+            let sign = match t.action.as_str() {
+                "ajout" => -1.0,
+                "retrait" => 1.0,
+                _ => return None,
+            };
+            let Some(date) = parse_trade_date(&t.date) else {
+                warn!("Skipping wallet transaction {} with unparseable date {:?}", t.id, t.date);
+                return None;
+            };
+            Some(CashFlow { date, amount: sign * t.amount.to_f64().unwrap_or(0.0) })
+        })
+        .collect()
+}
+
+/// IRR des flux + valeur actuelle, avec la raison quand le taux n'est pas défini
+pub fn irr_with_terminal_value(
+    flows: &[CashFlow],
+    current_value: f64,
+    as_of: NaiveDate,
+) -> (Option<f64>, Option<&'static str>) {
+    if flows.is_empty() {
+        return (None, Some("no_cash_flows"));
+    }
+
+    let mut all_flows = flows.to_vec();
+    if current_value != 0.0 {
+        all_flows.push(CashFlow { date: as_of, amount: current_value });
+    }
+
+    match xirr(&all_flows) {
+        Some(rate) => (Some(rate), None),
+        None => (None, Some("irr_undefined")),
+    }
+}
+
+/// Coefficient de Pearson, None si moins de MIN_CORRELATION_OBSERVATIONS points ou variance nulle
+pub fn pearson(x: &[f64], y: &[f64]) -> Option<f64> {
+    let n = x.len().min(y.len());
+    if n < MIN_CORRELATION_OBSERVATIONS {
+        return None;
+    }
+
+    let mean_x = x[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = y[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x[..n].iter().zip(&y[..n]) {
+        cov += (a - mean_x) * (b - mean_y);
+        var_x += (a - mean_x).powi(2);
+        var_y += (b - mean_y).powi(2);
+    }
+
+    let denominator = (var_x * var_y).sqrt();
+    (denominator > 0.0).then(|| (cov / denominator).clamp(-1.0, 1.0))
+}
+
+/// Rendements journaliers des deux séries sur leurs dates communes : chaque rendement relie
+/// deux dates communes consécutives, une date présente d'un seul côté est ignorée
+pub fn paired_returns(a: &BTreeMap<String, f64>, b: &BTreeMap<String, f64>) -> (Vec<f64>, Vec<f64>) {
+    let common: Vec<(f64, f64)> = a
+        .iter()
+        .filter_map(|(date, close_a)| b.get(date).map(|close_b| (*close_a, *close_b)))
+        .collect();
+
+    common
+        .windows(2)
+        .map(|w| (w[1].0 / w[0].0 - 1.0, w[1].1 / w[0].1 - 1.0))
+        .unzip()
+}
+
+/// Matrice de corrélation des rendements journaliers (calcul pur), closes par date YYYY-MM-DD
+pub fn correlation_matrix(
+    symbols: &[String],
+    closes: &HashMap<String, BTreeMap<String, f64>>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> CorrelationMatrix {
+    let empty = BTreeMap::new();
+    let series: Vec<&BTreeMap<String, f64>> = symbols.iter().map(|s| closes.get(s).unwrap_or(&empty)).collect();
+
+    let size = symbols.len();
+    let mut matrix = vec![vec![None; size]; size];
+    let mut observations = vec![vec![0; size]; size];
+    for i in 0..size {
+        for j in i..size {
+            let (x, y) = paired_returns(series[i], series[j]);
+            let rho = pearson(&x, &y).map(|r| (r * 10_000.0).round() / 10_000.0);
+            matrix[i][j] = rho;
+            matrix[j][i] = rho;
+            observations[i][j] = x.len();
+            observations[j][i] = x.len();
+        }
+    }
+
+    CorrelationMatrix {
+        symbols: symbols.to_vec(),
+        from,
+        to,
+        matrix,
+        observations,
+        missing: symbols.iter().zip(&series).filter(|(_, s)| s.is_empty()).map(|(sym, _)| sym.clone()).collect(),
+    }
+}
+
+/// Dernier cours de clôture connu d'un symbole
+async fn latest_close(symbol: &str, db: &DatabaseConnection) -> Result<Option<Decimal>, DbErr> {
+    let row = historic_data::Entity::find()
+        .filter(historic_data::Column::Symbol.eq(symbol))
+        .order_by_desc(historic_data::Column::Date)
+        .one(db)
+        .await?;
+
+    Ok(row.and_then(|r| r.close).and_then(|c| c.trim().parse::<Decimal>().ok()))
+}
+
+/// Valeur de marché des positions ouvertes d'une devise (prix d'achat si aucun cours connu)
+async fn open_positions_value(user_id: i32, currency: &str, db: &DatabaseConnection) -> Result<Decimal, DbErr> {
+    Ok(open_position_values(user_id, currency, db).await?.iter().map(|p| p.market_value).sum())
+}
+
+/// Valeur de marché de chaque position ouverte d'une devise, par symbole
+/// (lots ouverts × dernier close, prix d'achat du lot si aucun cours connu)
+async fn open_position_values(user_id: i32, currency: &str, db: &DatabaseConnection) -> Result<Vec<PositionValue>, DbErr> {
+    let trades = trade::Entity::find_active()
+        .filter(trade::Column::UserId.eq(user_id))
+        .filter(trade::Column::TradeType.eq("achat"))
+        .filter(trade::Column::QuantiteRestante.gt(Decimal::ZERO))
+        .all(db)
+        .await?;
+
+    let mut closes: HashMap<String, Option<Decimal>> = HashMap::new();
+    let mut positions: HashMap<String, PositionValue> = HashMap::new();
+
+    for t in trades {
+        if WalletService::trade_currency(db, &t).await?.as_deref() != Some(currency) {
+            continue;
+        }
+        let Some(symbol) = t.symbol.clone() else { continue };
+
+        let close = match closes.get(&symbol) {
+            Some(close) => *close,
+            None => {
+                let close = latest_close(&symbol, db).await?;
+                closes.insert(symbol.clone(), close);
+                close
+            }
+        };
+
+        let price = close.or(t.prix_unitaire).unwrap_or(Decimal::ZERO);
+        let position = positions.entry(symbol.clone()).or_insert_with(|| PositionValue {
+            symbol,
+            quantity: Decimal::ZERO,
+            market_value: Decimal::ZERO,
+        });
+        position.quantity += t.quantite_restante;
+        position.market_value += t.quantite_restante * price;
+    }
+
+    Ok(positions.into_values().collect())
+}
+
+/// Performance réalisée glissante d'un utilisateur, par devise (devise du trade d'achat)
+pub async fn performance(user_id: i32, db: &DatabaseConnection) -> Result<PerformanceSummary, DbErr> {
+    let closed = trades_fermes::Entity::find()
+        .filter(trades_fermes::Column::UserId.eq(user_id))
+        .all(db)
+        .await?;
+
+    let buy_trades: HashMap<i32, trade::Model> = trade::Entity::find()
+        .filter(trade::Column::UserId.eq(user_id))
+        .filter(trade::Column::TradeType.eq("achat"))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| (t.id, t))
+        .collect();
+
+    let mut by_currency: HashMap<String, Vec<trades_fermes::Model>> = HashMap::new();
+    for c in closed {
+        let currency = match c.trade_achat_id.and_then(|id| buy_trades.get(&id)) {
+            Some(buy) => WalletService::trade_currency(db, buy).await?,
+            None => None,
+        }
+        .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+        by_currency.entry(currency).or_default().push(c);
+    }
+
+    let as_of = market_today();
+    let mut currencies: Vec<CurrencyPerformance> = by_currency
+        .into_iter()
+        .map(|(currency, closed)| CurrencyPerformance { windows: performance_windows(&closed, as_of), currency })
+        .collect();
+    currencies.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+    Ok(PerformanceSummary { as_of, currencies })
+}
+
+/// Rapport de concentration des positions ouvertes d'une devise
+pub async fn concentration(
+    user_id: i32,
+    currency: &str,
+    top_n: usize,
+    db: &DatabaseConnection,
+) -> Result<ConcentrationReport, DbErr> {
+    let positions = open_position_values(user_id, currency, db).await?;
+    Ok(concentration_report(currency, positions, top_n, concentration_threshold_from_env(), market_today()))
+}
+
+/// Rendement pondéré par le temps d'un utilisateur dans une devise, de `from` (défaut: premier
+/// ajout / retrait) à `to` (défaut: date de bourse du jour)
+pub async fn time_weighted_return(
+    user_id: i32,
+    currency: &str,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    db: &DatabaseConnection,
+) -> Result<TimeWeightedReturn, DbErr> {
+    let transactions = wallet::Entity::find()
+        .filter(wallet::Column::UserId.eq(user_id))
+        .filter(wallet::Column::Currency.eq(currency))
+        .filter(wallet::Column::AccountType.eq(wallet::REAL_ACCOUNT))
+        .all(db)
+        .await?;
+    let flows = external_cash_flows(&transactions, currency);
+
+    let to = to.unwrap_or_else(market_today);
+    let from = from.or_else(|| flows.iter().map(|f| f.date).min()).unwrap_or(to).min(to);
+
+    // Flux entrants du point de vue du portefeuille (ajout > 0), regroupés par jour
+    let mut flows_by_day: HashMap<NaiveDate, f64> = HashMap::new();
+    for f in flows.iter().filter(|f| f.date > from && f.date <= to) {
+        *flows_by_day.entry(f.date).or_insert(0.0) -= f.amount;
+    }
+    let mut dates: Vec<NaiveDate> = std::iter::once(from).chain(flows_by_day.keys().copied()).collect();
+    dates.sort();
+    if *dates.last().unwrap() != to {
+        dates.push(to);
+    }
+
+    let mut trades = Vec::new();
+    for t in trade::Entity::find_active().filter(trade::Column::UserId.eq(user_id)).all(db).await? {
+        if WalletService::trade_currency(db, &t).await?.as_deref() == Some(currency) {
+            trades.push(t);
+        }
+    }
+
+    let symbols: HashSet<String> = trades.iter().filter_map(|t| t.symbol.clone()).collect();
+    let mut closes: HashMap<String, Vec<(NaiveDate, Decimal)>> = HashMap::new();
+    if !symbols.is_empty() {
+        let rows = historic_data::Entity::find()
+            .filter(historic_data::Column::Symbol.is_in(symbols))
+            .filter(historic_data::Column::Date.lte(to.format("%Y-%m-%d").to_string()))
+            .order_by_asc(historic_data::Column::Date)
+            .all(db)
+            .await?;
+        for row in rows {
+            let (Some(date), Some(close)) = (
+                parse_trade_date(&row.date),
+                row.close.as_deref().and_then(|c| c.trim().parse::<Decimal>().ok()),
+            ) else {
+                continue;
+            };
+            closes.entry(row.symbol).or_default().push((date, close));
+        }
+    }
+
+    let curve = equity_curve(&dates, &transactions, &trades, &closes, currency);
+    let points: Vec<(EquityPoint, f64)> = curve
+        .iter()
+        .map(|p| (*p, flows_by_day.get(&p.date).copied().unwrap_or(0.0)))
+        .collect();
+    let (twr, sub_periods) = chain_sub_period_returns(&points);
+
+    Ok(TimeWeightedReturn {
+        currency: currency.to_string(),
+        from,
+        to,
+        twr,
+        start_equity: curve.first().map(|p| p.equity).unwrap_or(Decimal::ZERO),
+        end_equity: curve.last().map(|p| p.equity).unwrap_or(Decimal::ZERO),
+        cash_flows: flows.iter().filter(|f| f.date > from && f.date <= to).count(),
+        sub_periods,
+        note: twr.is_none().then_some("no_equity"),
+    })
+}
+
+/// Rendement pondéré par l'argent d'un utilisateur dans une devise
+pub async fn money_weighted_return(
+    user_id: i32,
+    currency: &str,
+    db: &DatabaseConnection,
+) -> Result<MoneyWeightedReturn, DbErr> {
+    let transactions = wallet::Entity::find()
+        .filter(wallet::Column::UserId.eq(user_id))
+        .filter(wallet::Column::Currency.eq(currency))
+        .filter(wallet::Column::AccountType.eq(wallet::REAL_ACCOUNT))
+        .all(db)
+        .await?;
+    let flows = external_cash_flows(&transactions, currency);
+
+    let treasury = WalletService::get_treasury_for_currency(db, user_id, currency).await?;
+    let current_value = treasury + open_positions_value(user_id, currency, db).await?;
+
+    let as_of = market_today();
+    let (irr, note) = irr_with_terminal_value(&flows, current_value.to_f64().unwrap_or(0.0), as_of);
+
+    Ok(MoneyWeightedReturn {
+        currency: currency.to_string(),
+        irr,
+        current_value,
+        cash_flows: flows.len(),
+        as_of,
+        note,
+    })
+}
+
+/// Corrélation des rendements journaliers de `symbols` sur les `days` derniers jours calendaires
+pub async fn correlation(symbols: &[String], days: u32, db: &DatabaseConnection) -> Result<CorrelationMatrix, String> {
+    let to = market_today();
+    let from = to - chrono::Duration::days(days as i64);
+
+    let bars = historic_bars::fetch_bars_since(symbols, &from.format("%Y-%m-%d").to_string(), db).await?;
+    let mut closes: HashMap<String, BTreeMap<String, f64>> = HashMap::new();
+    for bar in bars {
+        let bar = bar.priced(PriceSource::AdjustedClose);
+        closes.entry(bar.symbol).or_default().insert(bar.date, bar.close);
+    }
+
+    Ok(correlation_matrix(symbols, &closes, from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn flow(d: &str, amount: f64) -> CashFlow {
+        CashFlow { date: date(d), amount }
+    }
+
+    #[test]
+    fn xirr_matches_reference_series() {
+        // Série de référence de la documentation Excel XIRR : 37.34 %
+        let flows = [
+            flow("2008-01-01", -10000.0),
+            flow("2008-03-01", 2750.0),
+            flow("2008-10-30", 4250.0),
+            flow("2009-02-15", 3250.0),
+            flow("2009-04-01", 2750.0),
+        ];
+
+        let rate = xirr(&flows).unwrap();
+        assert!((rate - 0.373362535).abs() < 1e-6, "got {}", rate);
+    }
+
+    #[test]
+    fn single_deposit_has_closed_form_rate() {
+        // 1000 → 1100 sur 366 jours : (1.1)^(365/366) - 1
+        let (irr, note) = irr_with_terminal_value(&[flow("2024-01-01", -1000.0)], 1100.0, date("2025-01-01"));
+
+        let expected = 1.1f64.powf(365.0 / 366.0) - 1.0;
+        assert!((irr.unwrap() - expected).abs() < 1e-9);
+        assert_eq!(note, None);
+    }
+
+    #[test]
+    fn losses_give_negative_rate() {
+        let rate = xirr(&[flow("2024-01-01", -1000.0), flow("2024-12-31", 500.0)]).unwrap();
+        assert!(rate < -0.49 && rate > -0.51, "got {}", rate);
+    }
+
+    #[test]
+    fn undefined_cases_are_reported() {
+        assert_eq!(irr_with_terminal_value(&[], 500.0, date("2025-01-01")), (None, Some("no_cash_flows")));
+
+        // Dépôt le jour même : aucun horizon de temps
+        let same_day = irr_with_terminal_value(&[flow("2025-01-01", -1000.0)], 1000.0, date("2025-01-01"));
+        assert_eq!(same_day, (None, Some("irr_undefined")));
+
+        // Portefeuille vide sans retrait : pas de flux positif
+        let all_out = irr_with_terminal_value(&[flow("2024-01-01", -1000.0)], 0.0, date("2025-01-01"));
+        assert_eq!(all_out, (None, Some("irr_undefined")));
+    }
+
+    #[test]
+    fn only_deposits_and_withdrawals_count_as_external_flows() {
+        let tx = |id: i32, action: &str, amount: &str, currency: &str| wallet::Model {
+            id,
+            user_id: 1,
+            date: "2025-01-02".to_string(),
+            action: action.to_string(),
+            symbol: None,
+            amount: amount.parse().unwrap(),
+            currency: currency.to_string(),
+            trade_vente_id: None,
+            account_type: wallet::REAL_ACCOUNT.to_string(),
+        };
+        let transactions = vec![
+            tx(1, "ajout", "1000", "CAD"),
+            tx(2, "gain", "50", "CAD"),
+            tx(3, "retrait", "200", "CAD"),
+            tx(4, "ajout", "999", "USD"),
+        ];
+
+        let flows = external_cash_flows(&transactions, "CAD");
+        assert_eq!(flows, vec![flow("2025-01-02", -1000.0), flow("2025-01-02", 200.0)]);
+    }
+
+    fn position(symbol: &str, quantity: i64, market_value: i64) -> PositionValue {
+        PositionValue { symbol: symbol.to_string(), quantity: Decimal::from(quantity), market_value: Decimal::from(market_value) }
+    }
+
+    #[test]
+    fn two_position_portfolio_shares_and_hhi() {
+        // 10 × 100 et 20 × 150 : 1000 + 3000
+        let report = concentration_report(
+            "CAD",
+            vec![position("AAPL", 10, 1000), position("MSFT", 20, 3000)],
+            1,
+            50.0,
+            date("2025-06-30"),
+        );
+
+        assert_eq!(report.total_value, Decimal::from(4000));
+        let shares: Vec<(&str, f64)> = report.positions.iter().map(|p| (p.symbol.as_str(), p.share_pct)).collect();
+        assert_eq!(shares, vec![("MSFT", 75.0), ("AAPL", 25.0)]);
+        assert_eq!(report.top_n_share_pct, 75.0);
+        // 75² + 25²
+        assert_eq!(report.hhi, 6250.0);
+        assert_eq!(report.concentrated, vec!["MSFT".to_string()]);
+        assert!(!report.positions[1].above_threshold);
+    }
+
+    #[test]
+    fn empty_portfolio_has_no_concentration() {
+        let report = concentration_report("CAD", vec![], 3, 25.0, date("2025-06-30"));
+
+        assert_eq!(report.total_value, Decimal::ZERO);
+        assert_eq!((report.hhi, report.top_n_share_pct), (0.0, 0.0));
+        assert!(report.positions.is_empty() && report.concentrated.is_empty());
+    }
+
+    fn point(d: &str, equity: i64, flow: f64) -> (EquityPoint, f64) {
+        (EquityPoint { date: date(d), equity: Decimal::from(equity) }, flow)
+    }
+
+    #[test]
+    fn period_without_flows_is_a_single_sub_period() {
+        let (twr, sub_periods) = chain_sub_period_returns(&[point("2025-01-02", 1000, 0.0), point("2025-01-31", 1100, 0.0)]);
+
+        assert!((twr.unwrap() - 0.10).abs() < 1e-12);
+        assert_eq!(sub_periods.len(), 1);
+    }
+
+    #[test]
+    fn zero_starting_equity_skips_first_sub_period() {
+        // Période ouverte avant le premier dépôt : 0 → 1000 n'est pas un rendement
+        let (twr, sub_periods) = chain_sub_period_returns(&[
+            point("2025-01-01", 0, 0.0),
+            point("2025-01-02", 1000, 1000.0),
+            point("2025-01-31", 1050, 0.0),
+        ]);
+
+        assert_eq!(sub_periods[0].rate, None);
+        assert!((twr.unwrap() - 0.05).abs() < 1e-12);
+
+        let (empty, _) = chain_sub_period_returns(&[point("2025-01-01", 0, 0.0), point("2025-01-31", 0, 0.0)]);
+        assert_eq!(empty, None);
+    }
+
+    async fn twr_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table users ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(wallet::Entity),
+            schema.create_table_from_entity(trade::Entity),
+            schema.create_table_from_entity(historic_data::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        db
+    }
+
+    #[tokio::test]
+    async fn two_flow_scenario_matches_hand_computation() {
+        let db = twr_db().await;
+
+        for (d, action, amount) in [("2025-01-02", "ajout", 1000), ("2025-01-10", "ajout", 500), ("2025-01-20", "retrait", 200)] {
+            wallet::ActiveModel {
+                user_id: Set(1),
+                date: Set(d.to_string()),
+                action: Set(action.to_string()),
+                symbol: Set(None),
+                amount: Set(Decimal::from(amount)),
+                currency: Set("CAD".to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        trade::ActiveModel {
+            user_id: Set(1),
+            symbol: Set(Some("AAPL".to_string())),
+            trade_type: Set(Some("achat".to_string())),
+            date: Set(Some("2025-01-02".to_string())),
+            quantite: Set(Some(Decimal::from(10))),
+            prix_unitaire: Set(Some(Decimal::from(100))),
+            prix_total: Set(Some(Decimal::from(1000))),
+            quantite_restante: Set(Decimal::from(10)),
+            currency: Set(Some("CAD".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        for (d, close) in [("2025-01-02", "100"), ("2025-01-10", "110"), ("2025-01-20", "99"), ("2025-01-31", "108.9")] {
+            historic_data::ActiveModel {
+                symbol: Set("AAPL".to_string()),
+                date: Set(d.to_string()),
+                close: Set(Some(close.to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let result = time_weighted_return(1, "CAD", None, Some(date("2025-01-31")), &db).await.unwrap();
+
+        // 1000 → 1100 avant l'ajout de 500 : +10 %
+        // 1600 → 1490 avant le retrait de 200 : -6.875 %
+        // 1290 → 1389 : +7.6744 %
+        let rates: Vec<f64> = result.sub_periods.iter().map(|p| p.rate.unwrap()).collect();
+        assert!((rates[0] - 0.10).abs() < 1e-12);
+        assert!((rates[1] + 0.06875).abs() < 1e-12);
+        assert!((rates[2] - (1389.0 / 1290.0 - 1.0)).abs() < 1e-12);
+
+        let expected = 1.10 * 0.93125 * (1389.0 / 1290.0) - 1.0;
+        assert!((result.twr.unwrap() - expected).abs() < 1e-12, "got {:?}", result.twr);
+        assert_eq!((result.from, result.cash_flows), (date("2025-01-02"), 2));
+        assert_eq!((result.start_equity, result.end_equity), (Decimal::from(1000), Decimal::from(1389)));
+    }
+
+    fn closed_trade(id: &str, date_vente: &str, gain: i64) -> trades_fermes::Model {
+        trades_fermes::Model {
+            id: id.to_string(),
+            user_id: 1,
+            symbol: Some("AAPL".to_string()),
+            date_achat: Some("2024-01-02".to_string()),
+            prix_achat: Some("100".to_string()),
+            date_vente: Some(date_vente.to_string()),
+            prix_vente: None,
+            pourcentage_gain: None,
+            gain_dollars: Some(Decimal::from(gain)),
+            temps_jours: None,
+            trade_achat_id: None,
+            trade_vente_id: None,
+        }
+    }
+
+    #[test]
+    fn trades_outside_a_window_are_excluded() {
+        let closed = vec![
+            closed_trade("a", "2025-06-30", 100), // Jour même
+            closed_trade("b", "2025-06-24", -40), // 7e jour de la fenêtre 7d
+            closed_trade("c", "2025-06-23", 60),  // Hors 7d, dans 30d
+            closed_trade("d", "2025-03-01", 25),  // Hors 90d (121 jours avant), dans 1y
+            closed_trade("e", "2024-06-30", 500), // 365 jours avant : hors de toutes les fenêtres
+            closed_trade("f", "2025-07-01", 999), // Après as_of
+        ];
+
+        let windows = performance_windows(&closed, date("2025-06-30"));
+        let summary: Vec<(&str, usize, Decimal, Option<f64>)> = windows
+            .iter()
+            .map(|w| (w.window, w.pnl.closed_trades, w.pnl.realized_gain, w.pnl.win_rate_pct))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("7d", 2, Decimal::from(60), Some(50.0)),
+                ("30d", 3, Decimal::from(120), Some(66.67)),
+                ("90d", 3, Decimal::from(120), Some(66.67)),
+                ("1y", 4, Decimal::from(145), Some(75.0)),
+            ]
+        );
+        assert_eq!(windows[0].from, date("2025-06-24"));
+    }
+
+    #[test]
+    fn empty_window_has_no_win_rate() {
+        let pnl = realized_pnl(&[closed_trade("a", "2025-01-02", 10)], Some((date("2025-06-01"), date("2025-06-30"))));
+        assert_eq!(pnl, RealizedPnl { closed_trades: 0, realized_gain: Decimal::ZERO, winning_trades: 0, win_rate_pct: None });
+
+        // Sans bornes : tous les trades, même à date illisible
+        let all = realized_pnl(&[closed_trade("a", "n/a", 10), closed_trade("b", "2025-06-02", -5)], None);
+        assert_eq!((all.closed_trades, all.realized_gain), (2, Decimal::from(5)));
+    }
+    fn series(closes: &[f64]) -> BTreeMap<String, f64> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| (format!("2025-03-{:02}", i + 1), *close))
+            .collect()
+    }
+
+    fn correlation_of(a: BTreeMap<String, f64>, b: BTreeMap<String, f64>) -> CorrelationMatrix {
+        let symbols = vec!["AAA".to_string(), "BBB".to_string()];
+        let closes = HashMap::from([(symbols[0].clone(), a), (symbols[1].clone(), b)]);
+        correlation_matrix(&symbols, &closes, date("2025-03-01"), date("2025-03-31"))
+    }
+
+    #[test]
+    fn proportional_series_are_perfectly_correlated() {
+        let a = [100.0, 102.0, 101.0, 105.0, 104.0, 108.0, 107.0];
+        // Même rendements à une autre échelle de prix
+        let b: Vec<f64> = a.iter().map(|c| c * 0.5).collect();
+
+        let result = correlation_of(series(&a), series(&b));
+        assert_eq!(result.matrix, vec![vec![Some(1.0), Some(1.0)], vec![Some(1.0), Some(1.0)]]);
+        assert_eq!(result.observations[0][1], 6);
+        assert!(result.missing.is_empty());
+    }
+
+    #[test]
+    fn orthogonal_returns_are_uncorrelated() {
+        // Rendements +1 / -1 % en alternance d'un côté, par paires de l'autre : covariance nulle
+        let path = |moves: &[f64]| {
+            let mut close = 100.0;
+            let mut closes = vec![close];
+            for m in moves {
+                close *= 1.0 + m;
+                closes.push(close);
+            }
+            closes
+        };
+        let a = path(&[0.01, -0.01, 0.01, -0.01, 0.01, -0.01, 0.01, -0.01]);
+        let b = path(&[0.01, 0.01, -0.01, -0.01, 0.01, 0.01, -0.01, -0.01]);
+
+        let rho = correlation_of(series(&a), series(&b)).matrix[0][1].unwrap();
+        assert!(rho.abs() < 1e-3, "got {}", rho);
+    }
+
+    #[test]
+    fn correlation_uses_common_dates_only() {
+        let a = series(&[100.0, 101.0, 103.0, 102.0, 104.0, 106.0, 105.0]);
+        let mut b: BTreeMap<String, f64> = a.iter().map(|(d, c)| (d.clone(), c * 2.0)).collect();
+        // Jour manquant d'un côté, jour en trop de l'autre : la paire reste parfaitement corrélée
+        b.remove("2025-03-03");
+        b.insert("2025-03-20".to_string(), 999.0);
+
+        let result = correlation_of(a, b);
+        assert_eq!(result.matrix[0][1], Some(1.0));
+        assert_eq!(result.observations[0][1], 5);
+
+        let lonely = correlation_of(series(&[100.0, 101.0]), BTreeMap::new());
+        assert_eq!(lonely.matrix[0][1], None);
+        assert_eq!(lonely.missing, vec!["BBB".to_string()]);
+    }
+}
@@ -0,0 +1,323 @@
+// ============================================================================
+// SERVICE : API KEYS
+// ============================================================================
+//
+// Description:
+//   Clés d'API personnelles (api_keys_rust) : accès programmatique à son propre
+//   compte via le header X-API-Key, sans JWT de 24h. Création / liste /
+//   révocation via /api/auth/api-keys ; résolution de la clé vers son
+//   utilisateur par l'extracteur AuthUser.
+//
+// Points d'attention:
+//   - Clé = "tk_" + 64 caractères hex aléatoires, stockée en SHA-256 (la clé a
+//     assez d'entropie : pas besoin d'un hash lent comme pour les mots de passe)
+//   - Le clair n'est renvoyé qu'à la création
+//   - Scope 'read' : méthodes GET / HEAD / OPTIONS, plus les POST sans écriture
+//     de READ_ONLY_POSTS (calculs à la demande dont le corps est une requête)
+//   - Au plus MAX_API_KEYS_PER_USER clés par utilisateur
+//   - last_used_at mis à jour au plus une fois par LAST_USED_RESOLUTION_SECS
+//
+// ============================================================================
+
+use actix_web::http::Method;
+use chrono::{Duration, NaiveDateTime, Utc};
+use rand::RngCore;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, PaginatorTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::models::{api_keys, users};
+
+pub const KEY_PREFIX: &str = "tk_";
+pub const MAX_API_KEYS_PER_USER: u64 = 10;
+pub const MAX_NAME_LENGTH: usize = 100;
+const KEY_BYTES: usize = 32;
+const DISPLAY_PREFIX_LENGTH: usize = 11; // "tk_" + 8 caractères
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// POST qui n'écrivent rien, permis à une clé 'read'
+pub const READ_ONLY_POSTS: [&str; 4] = [
+    "/api/strategies/recommendations",
+    "/api/strategies/simulate",
+    "/api/trades/validate",
+    "/api/trades/preview-sale",
+];
+
+/// Portée d'une clé : lecture seule ou lecture / écriture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    Read,
+    ReadWrite,
+}
+
+impl ApiKeyScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "read" => Some(Self::Read),
+            "read_write" => Some(Self::ReadWrite),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::ReadWrite => "read_write",
+        }
+    }
+
+    /// Une clé 'read' ne peut rien modifier
+    pub fn allows(&self, method: &Method, path: &str) -> bool {
+        match self {
+            Self::Read => {
+                matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+                    || (*method == Method::POST && READ_ONLY_POSTS.contains(&path.trim_end_matches('/')))
+            }
+            Self::ReadWrite => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiKeyError {
+    LimitReached,
+    Database(String),
+}
+
+/// Clé telle que listée (jamais le clair ni le hash)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiKeyInfo {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scope: String,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl From<api_keys::Model> for ApiKeyInfo {
+    fn from(key: api_keys::Model) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            prefix: key.key_prefix,
+            scope: key.scope,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+        }
+    }
+}
+
+/// Utilisateur résolu depuis une clé
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKeyOwner {
+    pub key_id: i32,
+    pub user_id: i32,
+    pub username: String,
+    pub scope: ApiKeyScope,
+}
+
+/// Nouvelle clé en clair
+pub fn generate_key() -> String {
+    let mut bytes = [0u8; KEY_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", KEY_PREFIX, hex::encode(bytes))
+}
+
+/// SHA-256 hex d'une clé (valeur stockée dans key_hash)
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Crée une clé ; renvoie ses métadonnées et le clair (à ne montrer qu'une fois)
+pub async fn create(
+    user_id: i32,
+    name: &str,
+    scope: ApiKeyScope,
+    db: &DatabaseConnection,
+) -> Result<(ApiKeyInfo, String), ApiKeyError> {
+    let db_err = |e: sea_orm::DbErr| ApiKeyError::Database(e.to_string());
+
+    let existing = api_keys::Entity::find()
+        .filter(api_keys::Column::UserId.eq(user_id))
+        .count(db)
+        .await
+        .map_err(db_err)?;
+    if existing >= MAX_API_KEYS_PER_USER {
+        return Err(ApiKeyError::LimitReached);
+    }
+
+    let key = generate_key();
+    let row = api_keys::ActiveModel {
+        user_id: Set(user_id),
+        name: Set(name.to_string()),
+        key_prefix: Set(key[..DISPLAY_PREFIX_LENGTH].to_string()),
+        key_hash: Set(hash_key(&key)),
+        scope: Set(scope.as_str().to_string()),
+        created_at: Set(Utc::now().naive_utc()),
+        last_used_at: Set(None),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(db_err)?;
+
+    Ok((row.into(), key))
+}
+
+/// Clés d'un utilisateur, les plus récentes d'abord
+pub async fn list(user_id: i32, db: &DatabaseConnection) -> Result<Vec<ApiKeyInfo>, String> {
+    api_keys::Entity::find()
+        .filter(api_keys::Column::UserId.eq(user_id))
+        .order_by_desc(api_keys::Column::CreatedAt)
+        .order_by_desc(api_keys::Column::Id)
+        .all(db)
+        .await
+        .map(|keys| keys.into_iter().map(ApiKeyInfo::from).collect())
+        .map_err(|e| format!("Failed to fetch API keys: {}", e))
+}
+
+/// Révoque une clé de l'utilisateur ; false si elle n'existe pas (ou appartient à un autre)
+pub async fn revoke(user_id: i32, key_id: i32, db: &DatabaseConnection) -> Result<bool, String> {
+    api_keys::Entity::delete_many()
+        .filter(api_keys::Column::Id.eq(key_id))
+        .filter(api_keys::Column::UserId.eq(user_id))
+        .exec(db)
+        .await
+        .map(|res| res.rows_affected > 0)
+        .map_err(|e| format!("Failed to revoke API key: {}", e))
+}
+
+/// Résout une clé en clair vers son utilisateur (None si inconnue ou révoquée)
+pub async fn authenticate(key: &str, db: &DatabaseConnection) -> Result<Option<ApiKeyOwner>, String> {
+    if !key.starts_with(KEY_PREFIX) {
+        return Ok(None);
+    }
+
+    let found = api_keys::Entity::find()
+        .filter(api_keys::Column::KeyHash.eq(hash_key(key)))
+        .find_also_related(users::Entity)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to look up API key: {}", e))?;
+
+    let Some((key_row, Some(user))) = found else {
+        return Ok(None);
+    };
+    let Some(scope) = ApiKeyScope::parse(&key_row.scope) else {
+        warn!("API key {} has an unknown scope '{}'", key_row.id, key_row.scope);
+        return Ok(None);
+    };
+
+    let owner = ApiKeyOwner { key_id: key_row.id, user_id: user.id, username: user.username, scope };
+    touch(key_row, db).await;
+    Ok(Some(owner))
+}
+
+/// Met à jour last_used_at (best effort, au plus une fois par LAST_USED_RESOLUTION_SECS)
+async fn touch(key: api_keys::Model, db: &DatabaseConnection) {
+    let now = Utc::now().naive_utc();
+    if key.last_used_at.is_some_and(|last| now - last < Duration::seconds(LAST_USED_RESOLUTION_SECS)) {
+        return;
+    }
+
+    let key_id = key.id;
+    let mut active = key.into_active_model();
+    active.last_used_at = Set(Some(now));
+    if let Err(e) = active.update(db).await {
+        warn!("Failed to update last_used_at of API key {}: {}", key_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema};
+
+    /// SQLite en mémoire avec users_rust + api_keys_rust, et les comptes alice (1) et bob (2)
+    async fn api_key_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table abonnement ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [schema.create_table_from_entity(users::Entity), schema.create_table_from_entity(api_keys::Entity)] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        for (id, username) in [(1, "alice"), (2, "bob")] {
+            users::ActiveModel {
+                id: Set(id),
+                username: Set(username.to_string()),
+                email: Set(format!("{}@example.com", username)),
+                email_verified: Set(true),
+                auto_post_realized_pnl: Set(false),
+                block_duplicate_trades: Set(false),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn scope_limits_methods() {
+        assert_eq!(ApiKeyScope::parse("read"), Some(ApiKeyScope::Read));
+        assert_eq!(ApiKeyScope::parse("admin"), None);
+        assert!(ApiKeyScope::Read.allows(&Method::GET, "/api/trades"));
+        assert!(!ApiKeyScope::Read.allows(&Method::POST, "/api/trades"));
+        assert!(!ApiKeyScope::Read.allows(&Method::DELETE, "/api/trades/1"));
+        assert!(ApiKeyScope::ReadWrite.allows(&Method::DELETE, "/api/trades/1"));
+    }
+
+    #[test]
+    fn read_scope_allows_read_only_posts() {
+        for path in READ_ONLY_POSTS {
+            assert!(ApiKeyScope::Read.allows(&Method::POST, path), "{}", path);
+        }
+        assert!(ApiKeyScope::Read.allows(&Method::POST, "/api/trades/validate/"));
+        assert!(!ApiKeyScope::Read.allows(&Method::PUT, "/api/trades/validate"));
+        assert!(!ApiKeyScope::Read.allows(&Method::POST, "/api/strategies"));
+    }
+
+    #[tokio::test]
+    async fn create_authenticate_and_revoke() {
+        let db = api_key_db().await;
+
+        let (info, key) = create(1, "script", ApiKeyScope::Read, &db).await.unwrap();
+        assert!(key.starts_with(KEY_PREFIX) && key.len() == KEY_PREFIX.len() + 2 * KEY_BYTES);
+        assert!(key.starts_with(&info.prefix));
+
+        // Seul le hash est stocké
+        let stored = api_keys::Entity::find_by_id(info.id).one(&db).await.unwrap().unwrap();
+        assert_eq!(stored.key_hash, hash_key(&key));
+        assert_ne!(stored.key_hash, key);
+
+        let owner = authenticate(&key, &db).await.unwrap().unwrap();
+        assert_eq!((owner.user_id, owner.username.as_str(), owner.scope), (1, "alice", ApiKeyScope::Read));
+        assert!(list(1, &db).await.unwrap()[0].last_used_at.is_some());
+        assert_eq!(authenticate("tk_unknown", &db).await.unwrap(), None);
+
+        // Un autre utilisateur ne peut pas la révoquer
+        assert_eq!(revoke(2, info.id, &db).await, Ok(false));
+        assert_eq!(revoke(1, info.id, &db).await, Ok(true));
+        assert_eq!(authenticate(&key, &db).await.unwrap(), None);
+        assert!(list(1, &db).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn keys_are_capped_per_user() {
+        let db = api_key_db().await;
+        for i in 0..MAX_API_KEYS_PER_USER {
+            create(1, &format!("key {}", i), ApiKeyScope::ReadWrite, &db).await.unwrap();
+        }
+
+        assert_eq!(create(1, "one more", ApiKeyScope::Read, &db).await.unwrap_err(), ApiKeyError::LimitReached);
+        assert!(create(2, "bob's", ApiKeyScope::Read, &db).await.is_ok());
+    }
+}
@@ -0,0 +1,40 @@
+// ============================================================================
+// SERVICE : AUDIT LOG
+// ============================================================================
+//
+// Description:
+//   Enregistre les actions sensibles dans audit_log_rust.
+//   Générique sur la connexion pour être appelé dans la même transaction
+//   que l'action auditée (pas d'action sans trace, pas de trace sans action).
+//
+// ============================================================================
+
+use sea_orm::{ConnectionTrait, DbErr, Set, ActiveModelTrait};
+use chrono::Utc;
+
+use crate::models::audit_log;
+
+pub struct AuditService;
+
+impl AuditService {
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        action: &str,
+        entity: &str,
+        entity_id: &str,
+        details: Option<serde_json::Value>,
+    ) -> Result<audit_log::Model, DbErr> {
+        audit_log::ActiveModel {
+            user_id: Set(user_id),
+            action: Set(action.to_string()),
+            entity: Set(entity.to_string()),
+            entity_id: Set(entity_id.to_string()),
+            details: Set(details),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+}
@@ -0,0 +1,115 @@
+// ============================================================================
+// SERVICE : AUDIT DE L'AUTHENTIFICATION
+// ============================================================================
+//
+// Description:
+//   Enregistre chaque événement d'authentification dans auth_audit_rust
+//   (qui, quoi, succès ou échec, depuis quelle IP / quel navigateur) et
+//   relit l'activité récente : la sienne pour un utilisateur, tout le monde
+//   pour l'admin.
+//
+// Points d'attention:
+//   - Best effort côté routes : un échec d'écriture est loggé, jamais bloquant
+//   - ip = adresse de la connexion TCP : derrière un reverse proxy, c'est celle du proxy
+//
+// ============================================================================
+
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use chrono::Utc;
+
+use crate::models::auth_audit::{self, Entity as AuthAudit};
+
+/// Longueur maximale stockée pour le User-Agent
+const MAX_USER_AGENT_LEN: usize = 512;
+/// Nombre de lignes renvoyées par défaut / au maximum par les routes de consultation
+pub const DEFAULT_AUDIT_LIMIT: u64 = 50;
+pub const MAX_AUDIT_LIMIT: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthEventType {
+    Register,
+    Login,
+    PasswordChange,
+    PasswordResetRequest,
+    PasswordReset,
+    EmailVerification,
+    GoogleSignIn,
+}
+
+impl AuthEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthEventType::Register => "register",
+            AuthEventType::Login => "login",
+            AuthEventType::PasswordChange => "password_change",
+            AuthEventType::PasswordResetRequest => "password_reset_request",
+            AuthEventType::PasswordReset => "password_reset",
+            AuthEventType::EmailVerification => "email_verification",
+            AuthEventType::GoogleSignIn => "google_sign_in",
+        }
+    }
+}
+
+/// Origine de la requête (extraite par la route)
+#[derive(Debug, Clone, Default)]
+pub struct AuthRequestInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuthRequestInfo {
+    pub fn new(ip: Option<String>, user_agent: Option<&str>) -> Self {
+        Self {
+            ip,
+            user_agent: user_agent.map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect()),
+        }
+    }
+}
+
+/// Limite de lignes demandée, bornée à [1, MAX_AUDIT_LIMIT]
+pub fn clamp_limit(limit: Option<u64>) -> u64 {
+    limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT)
+}
+
+pub struct AuthAuditService;
+
+impl AuthAuditService {
+    pub async fn record(
+        db: &DatabaseConnection,
+        event: AuthEventType,
+        user_id: Option<i32>,
+        success: bool,
+        info: &AuthRequestInfo,
+    ) -> Result<auth_audit::Model, DbErr> {
+        auth_audit::ActiveModel {
+            user_id: Set(user_id),
+            event_type: Set(event.as_str().to_string()),
+            success: Set(success),
+            ip: Set(info.ip.clone()),
+            user_agent: Set(info.user_agent.clone()),
+            created_at: Set(Utc::now().naive_utc()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    /// Derniers événements, du plus récent au plus ancien (user_id = None → tous les utilisateurs)
+    pub async fn recent(
+        db: &DatabaseConnection,
+        user_id: Option<i32>,
+        limit: u64,
+    ) -> Result<Vec<auth_audit::Model>, DbErr> {
+        let mut query = AuthAudit::find();
+        if let Some(user_id) = user_id {
+            query = query.filter(auth_audit::Column::UserId.eq(user_id));
+        }
+
+        query
+            .order_by_desc(auth_audit::Column::CreatedAt)
+            .order_by_desc(auth_audit::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+}
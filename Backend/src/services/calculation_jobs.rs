@@ -0,0 +1,189 @@
+// ============================================================================
+// SERVICE : SUIVI / ANNULATION DES CALCULS (JOBS)
+// ============================================================================
+//
+// Description:
+//   Chaque calcul lancé par POST /api/admin/strategies/calculate (ou le calcul
+//   initial des symboles ajoutés par POST /api/admin/stocks) reçoit un id de
+//   job. POST /api/admin/jobs/{id}/cancel lève un drapeau d'annulation consulté
+//   aux mêmes points de contrôle que l'arrêt propre (services::shutdown) : entre
+//   deux symboles (IndicatorService, sauvegarde des résultats) et entre deux
+//   stratégies. Le job finit alors en statut "cancelled".
+//
+// Points d'attention:
+//   - Registre en mémoire, propre à l'instance : un job lancé sur une autre
+//     instance n'est pas visible ici (les ids repartent de 1 au redémarrage)
+//   - Pas de rollback : les transactions déjà commitées (par symbole) restent
+//   - Un seul calcul à la fois (calculation_lock) : le drapeau vise le job courant
+//
+// ============================================================================
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use crate::services::shutdown;
+
+/// Jobs terminés conservés pour consultation (les plus anciens sont oubliés)
+const MAX_FINISHED_JOBS: usize = 50;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CURRENT_JOB: AtomicU64 = AtomicU64::new(0); // 0 = aucun calcul en cours
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: u64,
+    pub status: JobStatus,
+    pub cancel_requested: bool,
+    pub processed: usize, // Recommandations (ou lignes d'indicateurs, ajout de symboles) sauvegardées jusqu'ici
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CancelError {
+    NotFound,
+    NotRunning(JobStatus),
+}
+
+fn registry() -> MutexGuard<'static, BTreeMap<u64, JobInfo>> {
+    static JOBS: OnceLock<Mutex<BTreeMap<u64, JobInfo>>> = OnceLock::new();
+    JOBS.get_or_init(|| Mutex::new(BTreeMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Enregistre un nouveau calcul et en fait le job courant
+pub fn start() -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let mut jobs = registry();
+
+    // Oublier les plus vieux jobs terminés (ids croissants → ordre chronologique)
+    let finished: Vec<u64> = jobs.values().filter(|j| j.status != JobStatus::Running).map(|j| j.id).collect();
+    for old in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+        jobs.remove(old);
+    }
+
+    jobs.insert(id, JobInfo {
+        id,
+        status: JobStatus::Running,
+        cancel_requested: false,
+        processed: 0,
+        started_at: Utc::now().naive_utc(),
+        finished_at: None,
+        error: None,
+    });
+    CURRENT_JOB.store(id, Ordering::SeqCst);
+    id
+}
+
+/// Id du calcul en cours sur cette instance
+pub fn current() -> Option<u64> {
+    match CURRENT_JOB.load(Ordering::SeqCst) {
+        0 => None,
+        id => Some(id),
+    }
+}
+
+pub fn get(id: u64) -> Option<JobInfo> {
+    registry().get(&id).cloned()
+}
+
+/// Demande l'annulation : le job s'arrêtera au prochain point de contrôle
+pub fn cancel(id: u64) -> Result<JobInfo, CancelError> {
+    let mut jobs = registry();
+    let job = jobs.get_mut(&id).ok_or(CancelError::NotFound)?;
+
+    if job.status != JobStatus::Running {
+        return Err(CancelError::NotRunning(job.status));
+    }
+
+    job.cancel_requested = true;
+    Ok(job.clone())
+}
+
+/// Raison de s'arrêter au point de contrôle : arrêt du serveur ou annulation du job courant
+pub fn stop_reason() -> Option<&'static str> {
+    if shutdown::is_requested() {
+        return Some("Shutdown requested");
+    }
+
+    let cancelled = current().is_some_and(|id| registry().get(&id).is_some_and(|j| j.cancel_requested));
+    cancelled.then_some("Cancelled")
+}
+
+/// Met à jour le nombre de recommandations sauvegardées du job courant
+pub fn record_progress(processed: usize) {
+    let Some(id) = current() else { return };
+    if let Some(job) = registry().get_mut(&id) {
+        job.processed = processed;
+    }
+}
+
+/// Termine un job : "cancelled" si une annulation a été demandée, sinon "completed" / "failed"
+/// `outcome` = nombre de recommandations sauvegardées ou erreur
+pub fn finish(id: u64, outcome: Result<usize, String>) -> Option<JobInfo> {
+    let _ = CURRENT_JOB.compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst);
+
+    let mut jobs = registry();
+    let job = jobs.get_mut(&id)?;
+
+    job.finished_at = Some(Utc::now().naive_utc());
+    job.status = match (&outcome, job.cancel_requested) {
+        (_, true) => JobStatus::Cancelled,
+        (Ok(_), false) => JobStatus::Completed,
+        (Err(_), false) => JobStatus::Failed,
+    };
+    match outcome {
+        Ok(processed) => job.processed = processed,
+        Err(e) => job.error = Some(e),
+    }
+
+    Some(job.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelled_job_stops_early_and_keeps_processed_count() {
+        let id = start();
+        assert_eq!(current(), Some(id));
+
+        // Boucle "entre deux symboles" : annulation demandée après le 3e
+        let mut processed = 0;
+        for symbol_idx in 0..10 {
+            if stop_reason().is_some() {
+                break;
+            }
+            processed += 1;
+            record_progress(processed);
+            if symbol_idx == 2 {
+                cancel(id).unwrap();
+            }
+        }
+
+        let job = finish(id, Err("Cancelled: stopped after indicators".to_string())).unwrap();
+        assert_eq!(processed, 3);
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert_eq!(job.processed, 3);
+        assert_eq!(current(), None);
+
+        // Un job terminé ne peut plus être annulé
+        assert_eq!(cancel(id).unwrap_err(), CancelError::NotRunning(JobStatus::Cancelled));
+        assert_eq!(cancel(u64::MAX).unwrap_err(), CancelError::NotFound);
+    }
+}
@@ -0,0 +1,142 @@
+// ============================================================================
+// SERVICE : VERROU DU CALCUL DES INDICATEURS / STRATÉGIES
+// ============================================================================
+//
+// Description:
+//   Empêche deux calculs simultanés (POST /api/admin/strategies/calculate) de
+//   se marcher dessus sur les UPSERT de indicators_rust / strategy_results_rust.
+//
+// Fonctionnement:
+//   1. Drapeau en mémoire : refuse immédiatement un second appel sur cette instance
+//   2. pg_try_advisory_xact_lock dans une transaction dédiée : refuse un calcul
+//      lancé par une autre instance du backend sur la même BD
+//
+// Points d'attention:
+//   - Le verrou Postgres est lié à la transaction : il est libéré au commit /
+//     rollback, y compris quand la transaction est abandonnée (panic, requête
+//     annulée) et rendue au pool
+//   - La transaction reste ouverte (inactive) pendant tout le calcul : ne pas
+//     activer idle_in_transaction_session_timeout sur ce rôle
+//
+// ============================================================================
+
+use sea_orm::DatabaseConnection;
+use sqlx::{Postgres, Transaction};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Clé du verrou consultatif Postgres (arbitraire, propre à ce calcul)
+pub const CALCULATION_LOCK_KEY: i64 = 0x7472_6164_6563_616c;
+
+static LOCAL_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Calcul en cours sur cette instance ; libéré au drop (fin normale ou panic)
+#[derive(Debug)]
+pub struct LocalGuard(());
+
+impl Drop for LocalGuard {
+    fn drop(&mut self) {
+        LOCAL_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// None si un calcul est déjà en cours sur cette instance
+pub fn try_acquire_local() -> Option<LocalGuard> {
+    LOCAL_RUNNING
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .ok()
+        .map(|_| LocalGuard(()))
+}
+
+/// Verrou détenu pendant le calcul (mémoire + Postgres)
+pub struct CalculationLock {
+    tx: Transaction<'static, Postgres>,
+    _local: LocalGuard,
+}
+
+impl CalculationLock {
+    /// Libère explicitement le verrou Postgres (sinon libéré quand la transaction est abandonnée)
+    pub async fn release(self) {
+        let _ = self.tx.rollback().await;
+    }
+}
+
+/// Tente de prendre le verrou sans attendre : Ok(None) si un calcul est déjà en cours
+pub async fn try_acquire(db: &DatabaseConnection) -> Result<Option<CalculationLock>, String> {
+    let Some(local) = try_acquire_local() else {
+        return Ok(None);
+    };
+
+    let pool = db.get_postgres_connection_pool();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Transaction begin error: {}", e))?;
+
+    let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(CALCULATION_LOCK_KEY)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to acquire calculation lock: {}", e))?;
+
+    if !acquired {
+        // Autre instance en cours : la transaction et le drapeau local sont libérés au drop
+        return Ok(None);
+    }
+
+    Ok(Some(CalculationLock { tx, _local: local }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::Mutex;
+
+    // Les deux tests partagent LOCAL_RUNNING : ils ne doivent pas tourner en même temps
+    static SERIAL: Mutex<()> = Mutex::const_new(());
+
+    #[test]
+    fn second_acquire_is_rejected_until_release_even_after_panic() {
+        let _serial = SERIAL.blocking_lock();
+
+        let guard = try_acquire_local().expect("first acquire");
+        assert!(try_acquire_local().is_none());
+        drop(guard);
+
+        // Un calcul qui panique libère aussi le verrou
+        let result = std::panic::catch_unwind(|| {
+            let _guard = try_acquire_local().expect("acquire after release");
+            panic!("calculation failed");
+        });
+        assert!(result.is_err());
+
+        assert!(try_acquire_local().is_some());
+    }
+
+    /// Nécessite un Postgres : TEST_DATABASE_URL=postgresql://... cargo test (ignoré sinon)
+    #[tokio::test]
+    async fn acquire_is_rejected_while_another_connection_holds_the_advisory_lock() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL not set, skipping advisory lock test");
+            return;
+        };
+        let db = sea_orm::Database::connect(&url).await.unwrap();
+
+        // Autre instance : verrou pris dans une transaction sur une autre connexion du pool
+        let mut holder = db.get_postgres_connection_pool().begin().await.unwrap();
+        let held: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(CALCULATION_LOCK_KEY)
+            .fetch_one(&mut *holder)
+            .await
+            .unwrap();
+        assert!(held);
+
+        let _serial = SERIAL.lock().await;
+        assert!(try_acquire(&db).await.unwrap().is_none());
+        // Le refus vient bien de Postgres : le drapeau local a été rendu
+        assert!(try_acquire_local().is_some());
+
+        holder.rollback().await.unwrap();
+        let lock = try_acquire(&db).await.unwrap().expect("lock after the other transaction ended");
+        lock.release().await;
+    }
+}
@@ -0,0 +1,261 @@
+// ============================================================================
+// SERVICE : RÉIMPORT D'HISTORIQUE (historicdata)
+// ============================================================================
+//
+// Description:
+//   Remplace toute la série historicdata d'un symbole en une seule transaction :
+//   suppression de l'ancienne série puis insertion de la nouvelle. Un lecteur
+//   voit soit l'ancienne série, soit la nouvelle, jamais un mélange à moitié
+//   importé qui empoisonnerait silencieusement les indicateurs.
+//
+// Points d'attention:
+//   - Toutes les barres sont validées AVANT d'ouvrir la transaction (mêmes règles
+//     que historic_bars : une ligne que les lecteurs écarteraient est refusée)
+//   - Les indicateurs du symbole sont supprimés dans la même transaction : le
+//     prochain calcul le traite comme un nouveau symbole (FLUX B, calcul complet)
+//     au lieu de prolonger des valeurs calculées sur l'ancienne série
+//   - Erreur en cours d'insertion → la transaction est abandonnée (rollback au drop)
+//
+// ============================================================================
+
+use chrono::NaiveDate;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::info;
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+use crate::models::indicator::{Column as IndicatorColumn, Entity as Indicator};
+use crate::services::historic_bars::HistoricBar;
+
+/// Lignes par INSERT multi-lignes (8 paramètres par ligne, limite Postgres = 65535)
+const IMPORT_CHUNK_SIZE: usize = 1000;
+
+/// Nombre maximal de barres par import (~80 ans de cotations journalières)
+pub const MAX_IMPORT_BARS: usize = 20_000;
+
+/// Une barre journalière à importer
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportBar {
+    pub date: String, // YYYY-MM-DD
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub adjusted_close: Option<f64>,
+    pub volume: Option<f64>,
+}
+
+/// Résultat d'un remplacement de série
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplaceSummary {
+    pub symbol: String,
+    pub deleted: u64,
+    pub inserted: u64,
+    pub indicators_cleared: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    Invalid(String),  // Rien n'a été écrit
+    Database(String), // Transaction abandonnée : l'ancienne série est intacte
+}
+
+/// Valide toutes les barres et prépare les lignes historicdata (aucun accès BD)
+pub fn stage_bars(symbol: &str, bars: &[ImportBar]) -> Result<Vec<historic_data::Model>, ImportError> {
+    if symbol.trim().is_empty() {
+        return Err(ImportError::Invalid("Symbol is required".to_string()));
+    }
+    if bars.is_empty() {
+        return Err(ImportError::Invalid("At least one bar is required".to_string()));
+    }
+    if bars.len() > MAX_IMPORT_BARS {
+        return Err(ImportError::Invalid(format!("Too many bars: {} (max {})", bars.len(), MAX_IMPORT_BARS)));
+    }
+
+    let mut seen_dates = HashSet::new();
+    let mut rows = Vec::with_capacity(bars.len());
+
+    for bar in bars {
+        if NaiveDate::parse_from_str(&bar.date, "%Y-%m-%d").is_err() {
+            return Err(ImportError::Invalid(format!("Invalid date (expected YYYY-MM-DD): {:?}", bar.date)));
+        }
+        if !seen_dates.insert(bar.date.as_str()) {
+            return Err(ImportError::Invalid(format!("Duplicate date: {}", bar.date)));
+        }
+
+        let row = historic_data::Model {
+            symbol: symbol.to_string(),
+            date: bar.date.clone(),
+            open: Some(bar.open.to_string()),
+            high: Some(bar.high.to_string()),
+            low: Some(bar.low.to_string()),
+            close: Some(bar.close.to_string()),
+            adjusted_close: bar.adjusted_close.map(|v| v.to_string()),
+            volume: bar.volume.map(|v| v.to_string()),
+        };
+
+        // Mêmes règles que les lecteurs, plus la cohérence high/low
+        let parsed = HistoricBar::try_from(&row).map_err(|e| ImportError::Invalid(format!("{} on {}", e, bar.date)))?;
+        if parsed.adjusted_close.is_none() && bar.adjusted_close.is_some() {
+            return Err(ImportError::Invalid(format!("adjusted_close is not a valid price on {}", bar.date)));
+        }
+        if parsed.high < parsed.low {
+            return Err(ImportError::Invalid(format!("high < low on {}", bar.date)));
+        }
+
+        rows.push(row);
+    }
+
+    rows.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(rows)
+}
+
+/// Remplace la série historicdata d'un symbole : delete + insert (+ purge des indicateurs)
+/// dans une seule transaction
+pub async fn replace_symbol_history(
+    symbol: &str,
+    bars: &[ImportBar],
+    db: &DatabaseConnection,
+) -> Result<ReplaceSummary, ImportError> {
+    let rows = stage_bars(symbol, bars)?;
+    let db_error = |context: &str, e: sea_orm::DbErr| ImportError::Database(format!("{}: {}", context, e));
+
+    let txn = db.begin().await.map_err(|e| db_error("Transaction begin error", e))?;
+
+    let indicators_cleared = Indicator::delete_many()
+        .filter(IndicatorColumn::Symbol.eq(symbol))
+        .exec(&txn)
+        .await
+        .map_err(|e| db_error("Failed to clear indicators", e))?
+        .rows_affected;
+
+    let deleted = HistoricData::delete_many()
+        .filter(historic_data::Column::Symbol.eq(symbol))
+        .exec(&txn)
+        .await
+        .map_err(|e| db_error("Failed to delete previous history", e))?
+        .rows_affected;
+
+    let inserted = insert_rows(&txn, rows).await?;
+
+    txn.commit().await.map_err(|e| db_error("Transaction commit error", e))?;
+
+    info!(
+        "Replaced history for {}: {} rows deleted, {} inserted, {} indicator rows cleared",
+        symbol, deleted, inserted, indicators_cleared
+    );
+
+    Ok(ReplaceSummary {
+        symbol: symbol.to_string(),
+        deleted,
+        inserted,
+        indicators_cleared,
+    })
+}
+
+async fn insert_rows<C: ConnectionTrait>(conn: &C, rows: Vec<historic_data::Model>) -> Result<u64, ImportError> {
+    let mut inserted = 0;
+
+    for chunk in rows.chunks(IMPORT_CHUNK_SIZE) {
+        let models = chunk.iter().cloned().map(|m| historic_data::ActiveModel {
+            symbol: Set(m.symbol),
+            date: Set(m.date),
+            open: Set(m.open),
+            high: Set(m.high),
+            low: Set(m.low),
+            close: Set(m.close),
+            adjusted_close: Set(m.adjusted_close),
+            volume: Set(m.volume),
+        });
+
+        inserted += HistoricData::insert_many(models)
+            .exec_without_returning(conn)
+            .await
+            .map_err(|e| ImportError::Database(format!("Failed to insert history: {}", e)))?;
+    }
+
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{Database, DatabaseBackend, QueryOrder, Schema};
+
+    fn bars(count: usize) -> Vec<ImportBar> {
+        let start = NaiveDate::from_ymd_opt(2015, 1, 1).unwrap();
+        (0..count)
+            .map(|i| ImportBar {
+                date: (start + chrono::Duration::days(i as i64)).format("%Y-%m-%d").to_string(),
+                open: 10.0,
+                high: 11.0,
+                low: 9.5,
+                close: 10.5,
+                adjusted_close: None,
+                volume: Some(1000.0),
+            })
+            .collect()
+    }
+
+    /// SQLite en mémoire ; la contrainte CHECK sur close = 999 simule une erreur BD en cours d'import
+    async fn test_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared(
+            "CREATE TABLE historicdata (
+                symbol TEXT NOT NULL, date TEXT NOT NULL,
+                open TEXT, high TEXT, low TEXT, close TEXT, adjusted_close TEXT, volume TEXT,
+                PRIMARY KEY (symbol, date), CHECK (close <> '999')
+            )",
+        )
+        .await
+        .unwrap();
+        let indicators = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(Indicator);
+        db.execute(DatabaseBackend::Sqlite.build(&indicators)).await.unwrap();
+        db
+    }
+
+    #[test]
+    fn test_invalid_bar_rejects_whole_import() {
+        let mut input = bars(3);
+        input[1].close = 0.0;
+        assert!(matches!(stage_bars("AAPL", &input), Err(ImportError::Invalid(e)) if e.contains("2015-01-02")));
+
+        let mut input = bars(3);
+        input[2].date = input[0].date.clone();
+        assert_eq!(stage_bars("AAPL", &input), Err(ImportError::Invalid("Duplicate date: 2015-01-01".to_string())));
+
+        let mut input = bars(2);
+        input[0].high = 9.0;
+        assert!(stage_bars("AAPL", &input).is_err());
+        assert!(stage_bars("AAPL", &[]).is_err());
+        assert_eq!(stage_bars("AAPL", &bars(2)).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_mid_import_keeps_original_series() {
+        let db = test_db().await;
+        let original = replace_symbol_history("AAPL", &bars(3), &db).await.unwrap();
+        assert_eq!((original.deleted, original.inserted), (0, 3));
+
+        // Erreur BD dans le 2e chunk : le 1er chunk et la suppression doivent être annulés
+        let mut reimport = bars(IMPORT_CHUNK_SIZE + 500);
+        reimport[IMPORT_CHUNK_SIZE + 10].close = 999.0;
+        reimport[IMPORT_CHUNK_SIZE + 10].high = 999.0;
+        let err = replace_symbol_history("AAPL", &reimport, &db).await.unwrap_err();
+        assert!(matches!(err, ImportError::Database(_)), "{:?}", err);
+
+        let rows = HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq("AAPL"))
+            .order_by_asc(historic_data::Column::Date)
+            .all(&db)
+            .await
+            .unwrap();
+        let dates: Vec<&str> = rows.iter().map(|r| r.date.as_str()).collect();
+        assert_eq!(dates, vec!["2015-01-01", "2015-01-02", "2015-01-03"]);
+
+        // Un réimport valide remplace bien la série
+        let replaced = replace_symbol_history("AAPL", &bars(5), &db).await.unwrap();
+        assert_eq!((replaced.deleted, replaced.inserted), (3, 5));
+    }
+}
@@ -0,0 +1,495 @@
+// ============================================================================
+// SERVICE : QUALITÉ DES DONNÉES (historicdata)
+// ============================================================================
+//
+// Description:
+//   Détecte les trous (gaps) dans les séries historicdata avant le calcul
+//   des indicateurs. Un trou de plusieurs semaines fait silencieusement
+//   chevaucher les fenêtres RSI/EMA et produit des valeurs trompeuses.
+//   Détecte aussi les symboles sans indicateurs (ou aux indicateurs périmés),
+//   qui disparaissent silencieusement des résultats de stratégies.
+//   Audite enfin les devises : un stock à la devise NULL / non standard, ou un
+//   symbole tradé absent de la table stock, fausse tout un bucket investi/trésorerie.
+//
+// Points d'attention:
+//   - Les week-ends et jours fériés créent des écarts normaux de 3-4 jours,
+//     d'où le seuil par défaut de 10 jours (DATA_GAP_MAX_DAYS)
+//   - Les dates non parsables (format != YYYY-MM-DD) sont ignorées
+//
+// ============================================================================
+
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+use crate::models::indicator::{Column as IndicatorColumn, Entity as Indicator};
+use crate::models::stock;
+use crate::models::trade::{self, Entity as Trade};
+use crate::utils::currency;
+
+/// Écart maximal (en jours calendaires) toléré entre deux cotations consécutives
+pub const DEFAULT_MAX_GAP_DAYS: i64 = 10;
+
+/// Un trou dans les données d'un symbole
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataGap {
+    pub symbol: String,
+    pub start_date: String, // Dernière date avant le trou
+    pub end_date: String,   // Première date après le trou
+    pub gap_days: i64,
+}
+
+/// Résumé des trous pour un symbole
+#[derive(Debug, Serialize)]
+pub struct SymbolDataQuality {
+    pub symbol: String,
+    pub largest_gap_days: i64,
+    pub gaps: Vec<DataGap>,
+}
+
+impl SymbolDataQuality {
+    /// None si le symbole n'a aucun trou
+    pub fn from_gaps(symbol: String, gaps: Vec<DataGap>) -> Option<Self> {
+        let largest_gap_days = gaps.iter().map(|g| g.gap_days).max()?;
+        Some(Self { symbol, largest_gap_days, gaps })
+    }
+}
+
+/// Âge maximal (en jours calendaires) de la dernière ligne d'indicateurs avant d'être "périmée"
+pub const DEFAULT_STALE_INDICATOR_DAYS: i64 = 7;
+
+/// Âge maximal (en jours calendaires) d'un close ou d'une recommandation affichés avant d'être
+/// signalés "stale" (4 : un close du jeudi reste frais le lundi qui suit un vendredi férié)
+pub const DEFAULT_STALE_PRICE_DAYS: i64 = 4;
+
+/// Symbole dont les indicateurs existent mais ne sont plus à jour
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StaleIndicators {
+    pub symbol: String,
+    pub latest_date: String,
+    pub days_behind: i64,
+}
+
+/// Couverture des indicateurs : symboles sans aucune ligne / avec des lignes périmées
+#[derive(Debug, PartialEq, Serialize)]
+pub struct IndicatorCoverage {
+    pub missing: Vec<String>,
+    pub stale: Vec<StaleIndicators>,
+}
+
+/// Stock dont la devise est absente ou hors de SUPPORTED_CURRENCIES
+#[derive(Debug, Clone, Serialize)]
+pub struct StockCurrencyIssue {
+    pub symbol: Option<String>,
+    pub compagny_name: String,
+    pub currency: Option<String>,
+    pub issue: &'static str,          // "missing" | "unsupported"
+    pub normalized: Option<String>,   // Correction évidente (ex: " usd" → "USD"), si elle existe
+    pub trade_count: usize,           // Trades impactés : sert à prioriser la correction
+}
+
+/// Symbole présent dans trade mais absent de stock (warning "Stock not found" du wallet)
+#[derive(Debug, Clone, Serialize)]
+pub struct UnknownTradeSymbol {
+    pub symbol: String,
+    pub trade_count: usize,
+    pub defaulted_trade_count: usize, // Sans devise saisie sur le trade → comptés en DEFAULT_CURRENCY
+}
+
+/// Rapport GET /api/admin/stocks/currency-audit
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrencyAudit {
+    pub stocks_checked: usize,
+    pub trades_checked: usize,
+    pub missing_currency_count: usize,
+    pub unsupported_currency_count: usize,
+    pub unknown_trade_symbol_count: usize,
+    pub stocks: Vec<StockCurrencyIssue>,
+    pub unknown_trade_symbols: Vec<UnknownTradeSymbol>,
+}
+
+/// Seuil configuré via DATA_GAP_MAX_DAYS (défaut: 10 jours)
+pub fn max_gap_days_from_env() -> i64 {
+    std::env::var("DATA_GAP_MAX_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_MAX_GAP_DAYS)
+}
+
+/// Si INDICATOR_SKIP_GAPPED_SYMBOLS=true, les symboles avec trous sont ignorés
+/// par IndicatorService (sinon simple warning)
+pub fn skip_gapped_symbols_from_env() -> bool {
+    std::env::var("INDICATOR_SKIP_GAPPED_SYMBOLS")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Seuil configuré via STALE_PRICE_MAX_DAYS (défaut: 4 jours)
+pub fn stale_price_days_from_env() -> i64 {
+    std::env::var("STALE_PRICE_MAX_DAYS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|v| *v >= 0)
+        .unwrap_or(DEFAULT_STALE_PRICE_DAYS)
+}
+
+/// Âge en jours calendaires d'une date YYYY-MM-DD à `today`, et si elle dépasse `max_age_days`.
+/// Date absente ou illisible → (None, true) : rien ne prouve que la donnée est fraîche
+pub fn freshness(date: Option<&str>, today: NaiveDate, max_age_days: i64) -> (Option<i64>, bool) {
+    match date.and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()) {
+        Some(d) => {
+            let age = (today - d).num_days();
+            (Some(age), age > max_age_days)
+        }
+        None => (None, true),
+    }
+}
+
+/// Détecte les trous > max_gap_days dans une série de dates triée (YYYY-MM-DD)
+pub fn detect_gaps(symbol: &str, dates: &[String], max_gap_days: i64) -> Vec<DataGap> {
+    let parsed: Vec<(NaiveDate, &String)> = dates
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok().map(|p| (p, d)))
+        .collect();
+
+    parsed
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, prev_str) = pair[0];
+            let (next, next_str) = pair[1];
+            let gap_days = (next - prev).num_days();
+
+            if gap_days > max_gap_days {
+                Some(DataGap {
+                    symbol: symbol.to_string(),
+                    start_date: prev_str.clone(),
+                    end_date: next_str.clone(),
+                    gap_days,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Groupe des lignes (symbol, date) par symbole et retourne les symboles avec trous
+pub fn detect_gaps_by_symbol(
+    rows: impl IntoIterator<Item = (String, String)>,
+    max_gap_days: i64,
+) -> Vec<SymbolDataQuality> {
+    let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (symbol, date) in rows {
+        grouped.entry(symbol).or_default().push(date);
+    }
+
+    grouped
+        .into_iter()
+        .filter_map(|(symbol, mut dates)| {
+            dates.sort();
+            let gaps = detect_gaps(&symbol, &dates, max_gap_days);
+            SymbolDataQuality::from_gaps(symbol, gaps)
+        })
+        .collect()
+}
+
+/// Classe chaque symbole selon sa dernière date d'indicateurs (symbol → YYYY-MM-DD)
+/// - absent de `latest` → missing
+/// - dernière date plus vieille que `stale_days` avant `today` → stale
+///   une date non parsable est considérée comme périmée
+pub fn classify_indicator_coverage(
+    symbols: &[String],
+    latest: &HashMap<String, String>,
+    today: NaiveDate,
+    stale_days: i64,
+) -> IndicatorCoverage {
+    let mut missing = Vec::new();
+    let mut stale = Vec::new();
+
+    for symbol in symbols {
+        let Some(latest_date) = latest.get(symbol) else {
+            missing.push(symbol.clone());
+            continue;
+        };
+
+        let days_behind = NaiveDate::parse_from_str(latest_date, "%Y-%m-%d")
+            .map(|d| (today - d).num_days())
+            .unwrap_or(i64::MAX);
+
+        if days_behind > stale_days {
+            stale.push(StaleIndicators {
+                symbol: symbol.clone(),
+                latest_date: latest_date.clone(),
+                days_behind,
+            });
+        }
+    }
+
+    missing.sort();
+    stale.sort_by(|a, b| b.days_behind.cmp(&a.days_behind).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    IndicatorCoverage { missing, stale }
+}
+
+/// Croise les stocks et les trades (symbol, devise saisie sur le trade)
+/// Les deux listes sont triées par nombre de trades impactés décroissant
+pub fn audit_currencies(
+    stocks: &[stock::Model],
+    trades: impl IntoIterator<Item = (String, Option<String>)>,
+) -> CurrencyAudit {
+    // symbole → (nombre de trades, trades sans devise saisie)
+    let mut trade_counts: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut trades_checked = 0;
+    for (symbol, trade_currency) in trades {
+        trades_checked += 1;
+        let counts = trade_counts.entry(symbol).or_default();
+        counts.0 += 1;
+        if trade_currency.is_none() {
+            counts.1 += 1;
+        }
+    }
+
+    let mut issues: Vec<StockCurrencyIssue> = stocks
+        .iter()
+        .filter_map(|s| {
+            let value = s.currency.as_deref().map(str::trim).filter(|c| !c.is_empty());
+            let issue = match value {
+                None => "missing",
+                Some(_) if s.currency.as_deref().is_some_and(currency::is_supported) => return None,
+                Some(_) => "unsupported",
+            };
+            let normalized = value.map(str::to_uppercase).filter(|c| currency::is_supported(c));
+            let trade_count = s
+                .symbol_alphavantage
+                .as_ref()
+                .and_then(|symbol| trade_counts.get(symbol))
+                .map_or(0, |(count, _)| *count);
+
+            Some(StockCurrencyIssue {
+                symbol: s.symbol_alphavantage.clone(),
+                compagny_name: s.compagny_name.clone(),
+                currency: s.currency.clone(),
+                issue,
+                normalized,
+                trade_count,
+            })
+        })
+        .collect();
+    issues.sort_by(|a, b| b.trade_count.cmp(&a.trade_count).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    let known: HashSet<&str> = stocks.iter().filter_map(|s| s.symbol_alphavantage.as_deref()).collect();
+    let mut unknown: Vec<UnknownTradeSymbol> = trade_counts
+        .iter()
+        .filter(|(symbol, _)| !known.contains(symbol.as_str()))
+        .map(|(symbol, (trade_count, defaulted_trade_count))| UnknownTradeSymbol {
+            symbol: symbol.clone(),
+            trade_count: *trade_count,
+            defaulted_trade_count: *defaulted_trade_count,
+        })
+        .collect();
+    unknown.sort_by(|a, b| b.trade_count.cmp(&a.trade_count).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    CurrencyAudit {
+        stocks_checked: stocks.len(),
+        trades_checked,
+        missing_currency_count: issues.iter().filter(|i| i.issue == "missing").count(),
+        unsupported_currency_count: issues.iter().filter(|i| i.issue == "unsupported").count(),
+        unknown_trade_symbol_count: unknown.len(),
+        stocks: issues,
+        unknown_trade_symbols: unknown,
+    }
+}
+
+pub struct DataQualityService;
+
+impl DataQualityService {
+    /// Trous > max_gap_days pour un symbole (toute l'historique) - GET /api/admin/data/gaps?symbol=
+    pub async fn find_gaps(
+        symbol: &str,
+        max_gap_days: i64,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<DataGap>, String> {
+        let dates = HistoricData::find()
+            .select_only()
+            .column(historic_data::Column::Date)
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .order_by_asc(historic_data::Column::Date)
+            .into_tuple::<String>()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch dates for {}: {}", symbol, e))?;
+
+        Ok(detect_gaps(symbol, &dates, max_gap_days))
+    }
+
+    /// Trous > max_gap_days pour tous les symboles donnés, depuis une date (une seule query)
+    pub async fn find_gaps_for_symbols(
+        symbols: &[String],
+        since: &str,
+        max_gap_days: i64,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<SymbolDataQuality>, String> {
+        let rows = HistoricData::find()
+            .select_only()
+            .column(historic_data::Column::Symbol)
+            .column(historic_data::Column::Date)
+            .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+            .filter(historic_data::Column::Date.gte(since))
+            .order_by_asc(historic_data::Column::Symbol)
+            .order_by_asc(historic_data::Column::Date)
+            .into_tuple::<(String, String)>()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch historical dates: {}", e))?;
+
+        Ok(detect_gaps_by_symbol(rows, max_gap_days))
+    }
+
+    /// Dernière date d'indicateurs par symbole (une seule query GROUP BY)
+    pub async fn latest_indicator_dates(db: &DatabaseConnection) -> Result<HashMap<String, String>, String> {
+        let rows = Indicator::find()
+            .select_only()
+            .column(IndicatorColumn::Symbol)
+            .column_as(IndicatorColumn::Date.max(), "latest_date")
+            .group_by(IndicatorColumn::Symbol)
+            .into_tuple::<(String, Option<String>)>()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch latest indicator dates: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(symbol, date)| date.map(|d| (symbol, d)))
+            .collect())
+    }
+
+    /// Audit des devises : tous les stocks et les (symbol, devise) de tous les trades
+    pub async fn currency_audit(db: &DatabaseConnection) -> Result<CurrencyAudit, String> {
+        let stocks = stock::Entity::find()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch stocks: {}", e))?;
+
+        let trades = Trade::find()
+            .select_only()
+            .column(trade::Column::Symbol)
+            .column(trade::Column::Currency)
+            .filter(trade::Column::Symbol.is_not_null())
+            .into_tuple::<(Option<String>, Option<String>)>()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch trade symbols: {}", e))?;
+
+        Ok(audit_currencies(
+            &stocks,
+            trades.into_iter().filter_map(|(symbol, c)| symbol.map(|s| (s, c))),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_weekend_is_not_a_gap() {
+        // Vendredi → Lundi
+        let series = dates(&["2025-01-02", "2025-01-03", "2025-01-06", "2025-01-07"]);
+        assert!(detect_gaps("AAPL", &series, DEFAULT_MAX_GAP_DAYS).is_empty());
+    }
+
+    #[test]
+    fn test_injected_gap_is_detected() {
+        let series = dates(&["2025-01-02", "2025-01-03", "2025-02-10", "2025-02-11"]);
+        let gaps = detect_gaps("AAPL", &series, DEFAULT_MAX_GAP_DAYS);
+
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].start_date, "2025-01-03");
+        assert_eq!(gaps[0].end_date, "2025-02-10");
+        assert_eq!(gaps[0].gap_days, 38);
+    }
+
+    #[test]
+    fn test_gaps_by_symbol_only_reports_gapped_symbols() {
+        let rows = vec![
+            ("AAPL".to_string(), "2025-01-02".to_string()),
+            ("AAPL".to_string(), "2025-01-03".to_string()),
+            ("MSFT".to_string(), "2025-03-01".to_string()),
+            ("MSFT".to_string(), "2025-01-02".to_string()),
+        ];
+
+        let report = detect_gaps_by_symbol(rows, DEFAULT_MAX_GAP_DAYS);
+
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].symbol, "MSFT");
+        assert_eq!(report[0].largest_gap_days, 58);
+    }
+
+    #[test]
+    fn test_indicator_coverage_reports_missing_and_stale() {
+        let symbols: Vec<String> = ["AAPL", "MSFT", "NEWCO", "SHOP.TO"].iter().map(|s| s.to_string()).collect();
+        let latest: HashMap<String, String> = [
+            ("AAPL", "2025-06-27"),    // vendredi, today = lundi : à jour
+            ("MSFT", "2025-05-01"),    // périmé
+            ("SHOP.TO", "2025-06-10"), // périmé
+            ("DELISTED", "2020-01-01"), // pas dans la liste de symboles : ignoré
+        ]
+        .into_iter()
+        .map(|(s, d)| (s.to_string(), d.to_string()))
+        .collect();
+
+        let today = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+        let coverage = classify_indicator_coverage(&symbols, &latest, today, DEFAULT_STALE_INDICATOR_DAYS);
+
+        assert_eq!(coverage.missing, vec!["NEWCO".to_string()]);
+        assert_eq!(coverage.stale.iter().map(|s| s.symbol.as_str()).collect::<Vec<_>>(), vec!["MSFT", "SHOP.TO"]);
+        assert_eq!(coverage.stale[1].days_behind, 20);
+    }
+
+    #[test]
+    fn test_currency_audit_flags_bad_stocks_and_unknown_trade_symbols() {
+        let stock = |symbol: &str, currency: Option<&str>| stock::Model {
+            compagny_name: format!("{} Inc", symbol),
+            is_alive: None,
+            low_data: None,
+            symbol_alphavantage: Some(symbol.to_string()),
+            currency: currency.map(|c| c.to_string()),
+            lot_size: None,
+            min_lot: None,
+        };
+        let stocks = vec![
+            stock("AAPL", Some("USD")),
+            stock("SHOP.TO", None),
+            stock("RY.TO", Some("cad ")),
+            stock("XYZ", Some("US$")),
+        ];
+        let trades = vec![
+            ("RY.TO".to_string(), None),
+            ("RY.TO".to_string(), None),
+            ("AAPL".to_string(), None),
+            ("GHOST".to_string(), None),
+            ("GHOST".to_string(), Some("USD".to_string())),
+        ];
+
+        let audit = audit_currencies(&stocks, trades);
+
+        assert_eq!((audit.stocks_checked, audit.trades_checked), (4, 5));
+        assert_eq!((audit.missing_currency_count, audit.unsupported_currency_count), (1, 2));
+        // RY.TO en premier : c'est le plus tradé
+        let flagged: Vec<_> = audit.stocks.iter().map(|i| (i.symbol.as_deref().unwrap(), i.issue, i.normalized.as_deref())).collect();
+        assert_eq!(flagged, vec![("RY.TO", "unsupported", Some("CAD")), ("SHOP.TO", "missing", None), ("XYZ", "unsupported", None)]);
+        assert_eq!(audit.stocks[0].trade_count, 2);
+
+        assert_eq!(audit.unknown_trade_symbol_count, 1);
+        assert_eq!(audit.unknown_trade_symbols[0].symbol, "GHOST");
+        assert_eq!(audit.unknown_trade_symbols[0].trade_count, 2);
+        assert_eq!(audit.unknown_trade_symbols[0].defaulted_trade_count, 1);
+    }
+}
@@ -0,0 +1,325 @@
+// ============================================================================
+// SERVICE : DIGEST QUOTIDIEN
+// ============================================================================
+//
+// Description:
+//   Assemble le digest du matin d'un utilisateur (GET /api/me/digest) : pour chaque
+//   symbole détenu (lots d'achat ouverts) ou suivi (watchlist_rust), la dernière
+//   recommandation de chaque stratégie et, pour les positions, le P&L latent.
+//   C'est la lecture sur laquelle s'appuieront les futurs emails d'alerte.
+//
+// Points d'attention:
+//   - Requêtes groupées : lots, watchlist, stocks, dernières barres, résultats et
+//     noms de stratégies sont lus en une passe chacun, quel que soit le nombre de symboles
+//   - Prix moyen = coût des lots encore ouverts (FIFO), pas la moyenne de tous les achats
+//   - Sans cours connu, le prix moyen sert de prix courant (P&L nul, price_date absent)
+//
+// ============================================================================
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use serde::Serialize;
+
+use crate::models::dto::StrategyWithResult;
+use crate::models::{stock, strategy, strategy_result, trade, watchlist};
+use crate::services::data_quality;
+use crate::services::historic_bars::{self, HistoricBar};
+use crate::utils::currency;
+use crate::utils::dates::market_today;
+use crate::utils::money;
+
+/// Position ouverte d'un symbole du digest
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DigestPosition {
+    pub quantity: Decimal,
+    pub avg_price: Decimal,
+    pub current_price: Decimal,
+    pub currency: String,
+    pub pnl_dollars: Decimal,
+    pub pnl_percentage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestEntry {
+    pub symbol: String,
+    pub held: bool,
+    pub watchlist: bool,
+    pub price_date: Option<String>,
+    pub stale: bool,
+    pub position: Option<DigestPosition>,
+    pub strategies: Vec<StrategyWithResult>,
+}
+
+/// Dernière barre et derniers résultats de stratégies d'un symbole (partagé avec GET /api/watchlist)
+#[derive(Debug, Default)]
+pub struct SymbolSnapshot {
+    pub latest_bar: Option<HistoricBar>,
+    pub strategies: Vec<StrategyWithResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Digest {
+    pub date: NaiveDate,
+    pub pnl_by_currency: BTreeMap<String, Decimal>,
+    pub symbols: Vec<DigestEntry>,
+}
+
+/// Position agrégée depuis les lots d'achat ouverts d'un symbole
+pub fn position_from_lots(lots: &[&trade::Model], latest_close: Option<Decimal>, currency: String) -> Option<DigestPosition> {
+    let quantity: Decimal = lots.iter().map(|t| t.quantite_restante).sum();
+    if quantity <= Decimal::ZERO {
+        return None;
+    }
+
+    let cost: Decimal = lots.iter().map(|t| t.quantite_restante * t.prix_unitaire.unwrap_or_default()).sum();
+    let avg_price = cost / quantity;
+    let current_price = latest_close.unwrap_or(avg_price);
+
+    Some(DigestPosition {
+        quantity,
+        avg_price: money::round_money(avg_price),
+        current_price: money::round_money(current_price),
+        currency,
+        pnl_dollars: money::round_money((current_price - avg_price) * quantity),
+        pnl_percentage: money::percent_f64(money::percentage_change(avg_price, current_price)),
+    })
+}
+
+/// Dernière barre et résultats de stratégies (avec fraîcheur) de chaque symbole, en trois requêtes.
+/// Un symbole sans barre ni résultat est absent de la map.
+pub async fn snapshots(
+    symbols: &[String],
+    today: NaiveDate,
+    stale_days: i64,
+    db: &DatabaseConnection,
+) -> Result<HashMap<String, SymbolSnapshot>, String> {
+    let mut snapshots: HashMap<String, SymbolSnapshot> = HashMap::new();
+    if symbols.is_empty() {
+        return Ok(snapshots);
+    }
+
+    for (symbol, bar) in historic_bars::fetch_latest_bars(symbols, db).await? {
+        snapshots.entry(symbol).or_default().latest_bar = Some(bar);
+    }
+
+    let strategy_names: HashMap<i32, String> = strategy::Entity::find()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch strategies: {}", e))?
+        .into_iter()
+        .filter_map(|s| Some((s.id, s.name?)))
+        .collect();
+
+    for result in strategy_result::Entity::find()
+        .filter(strategy_result::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .order_by_asc(strategy_result::Column::StrategyId)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch strategy results: {}", e))?
+    {
+        let Some(symbol) = result.symbol.clone() else { continue };
+        let name = strategy_names.get(&result.strategy_id).cloned();
+        snapshots
+            .entry(symbol)
+            .or_default()
+            .strategies
+            .push(StrategyWithResult::from_result(result, name).with_freshness(today, stale_days));
+    }
+
+    Ok(snapshots)
+}
+
+/// Digest d'un utilisateur : symboles détenus ou suivis, par ordre alphabétique
+pub async fn digest(user_id: i32, db: &DatabaseConnection) -> Result<Digest, String> {
+    let lots = trade::Entity::find_active()
+        .filter(trade::Column::UserId.eq(user_id))
+        .filter(trade::Column::TradeType.eq("achat"))
+        .filter(trade::Column::QuantiteRestante.gt(Decimal::ZERO))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch open lots: {}", e))?;
+
+    let watched: BTreeSet<String> = watchlist::Entity::find()
+        .filter(watchlist::Column::UserId.eq(user_id))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch watchlist: {}", e))?
+        .into_iter()
+        .map(|w| w.symbol)
+        .collect();
+
+    let mut lots_by_symbol: BTreeMap<String, Vec<&trade::Model>> = BTreeMap::new();
+    for lot in &lots {
+        if let Some(symbol) = &lot.symbol {
+            lots_by_symbol.entry(symbol.clone()).or_default().push(lot);
+        }
+    }
+
+    let symbols: Vec<String> = lots_by_symbol.keys().chain(&watched).cloned().collect::<BTreeSet<_>>().into_iter().collect();
+    let today = market_today();
+    if symbols.is_empty() {
+        return Ok(Digest { date: today, pnl_by_currency: BTreeMap::new(), symbols: Vec::new() });
+    }
+
+    let stock_currencies: HashMap<String, Option<String>> = stock::Entity::find()
+        .filter(stock::Column::SymbolAlphavantage.is_in(symbols.iter().map(|s| s.as_str())))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch stocks: {}", e))?
+        .into_iter()
+        .filter_map(|s| Some((s.symbol_alphavantage?, s.currency)))
+        .collect();
+
+    let stale_days = data_quality::stale_price_days_from_env();
+    let mut snapshots = snapshots(&symbols, today, stale_days, db).await?;
+
+    let mut pnl_by_currency: BTreeMap<String, Decimal> = BTreeMap::new();
+    let entries = symbols
+        .into_iter()
+        .map(|symbol| {
+            let snapshot = snapshots.remove(&symbol).unwrap_or_default();
+            let bar = snapshot.latest_bar.as_ref();
+            let price_date = bar.map(|b| b.date.clone());
+            let (_, stale) = data_quality::freshness(price_date.as_deref(), today, stale_days);
+
+            let position = lots_by_symbol.get(&symbol).and_then(|lots| {
+                let currency = currency::resolve(lots[0].currency.as_deref(), stock_currencies.get(&symbol).cloned().flatten());
+                position_from_lots(lots, bar.and_then(|b| Decimal::from_f64_retain(b.close)), currency)
+            });
+            if let Some(p) = &position {
+                *pnl_by_currency.entry(p.currency.clone()).or_default() += p.pnl_dollars;
+            }
+
+            DigestEntry {
+                held: position.is_some(),
+                watchlist: watched.contains(&symbol),
+                price_date,
+                stale,
+                position,
+                strategies: snapshot.strategies,
+                symbol,
+            }
+        })
+        .collect();
+
+    Ok(Digest { date: today, pnl_by_currency, symbols: entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::strategies::strategy_trait::Signal;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema, Set};
+
+    async fn digest_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table users ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(trade::Entity),
+            schema.create_table_from_entity(watchlist::Entity),
+            schema.create_table_from_entity(stock::Entity),
+            schema.create_table_from_entity(crate::models::historic_data::Entity),
+            schema.create_table_from_entity(strategy::Entity),
+            schema.create_table_from_entity(strategy_result::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        db
+    }
+
+    async fn result(db: &DatabaseConnection, strategy_id: i32, name: &str, symbol: &str, signal: &str) {
+        strategy::ActiveModel {
+            id: Set(strategy_id),
+            name: Set(Some(name.to_string())),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        strategy_result::ActiveModel {
+            strategy_id: Set(strategy_id),
+            symbol: Set(Some(symbol.to_string())),
+            date: Set(Some(market_today().format("%Y-%m-%d").to_string())),
+            recommendation: Set(Some(Signal::new(signal, 0.75).to_value())),
+            metadata: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn digest_lists_held_and_watchlist_symbols() {
+        let db = digest_db().await;
+
+        for (quantity, price, remaining) in [(10, 100, 4), (5, 130, 5)] {
+            trade::ActiveModel {
+                user_id: Set(1),
+                symbol: Set(Some("AAPL".to_string())),
+                trade_type: Set(Some("achat".to_string())),
+                date: Set(Some("2025-01-02".to_string())),
+                quantite: Set(Some(Decimal::from(quantity))),
+                prix_unitaire: Set(Some(Decimal::from(price))),
+                quantite_restante: Set(Decimal::from(remaining)),
+                currency: Set(Some("USD".to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        for (user_id, symbol) in [(1, "MSFT"), (2, "TSLA")] {
+            watchlist::ActiveModel {
+                user_id: Set(user_id),
+                symbol: Set(symbol.to_string()),
+                added_at: Set(chrono::Utc::now().naive_utc()),
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        for (symbol, date, close) in [("AAPL", "2025-06-01", "110"), ("AAPL", "2025-06-02", "126"), ("MSFT", "2025-06-02", "400")] {
+            crate::models::historic_data::ActiveModel {
+                symbol: Set(symbol.to_string()),
+                date: Set(date.to_string()),
+                open: Set(Some(close.to_string())),
+                high: Set(Some(close.to_string())),
+                low: Set(Some(close.to_string())),
+                close: Set(Some(close.to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        result(&db, 3, "RSI", "AAPL", "SELL").await;
+        result(&db, 2, "EMA", "MSFT", "BUY").await;
+
+        let digest = digest(1, &db).await.unwrap();
+        let symbols: Vec<&str> = digest.symbols.iter().map(|e| e.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["AAPL", "MSFT"]);
+
+        // 4 × 100 + 5 × 130 = 1050 pour 9 actions → prix moyen 116.67, close 126
+        let held = &digest.symbols[0];
+        assert!(held.held && !held.watchlist);
+        let position = held.position.as_ref().unwrap();
+        assert_eq!(position.quantity, Decimal::from(9));
+        assert_eq!(position.avg_price, Decimal::new(11667, 2));
+        assert_eq!(position.pnl_dollars, Decimal::from(84));
+        assert_eq!(position.currency, "USD");
+        assert_eq!(held.price_date.as_deref(), Some("2025-06-02"));
+        assert_eq!(held.strategies[0].strategy_name.as_deref(), Some("RSI"));
+        assert_eq!(held.strategies[0].recommendation.as_deref(), Some("SELL"));
+
+        let watched = &digest.symbols[1];
+        assert!(!watched.held && watched.watchlist);
+        assert!(watched.position.is_none());
+        assert_eq!(watched.strategies[0].recommendation.as_deref(), Some("BUY"));
+
+        assert_eq!(digest.pnl_by_currency.get("USD"), Some(&Decimal::from(84)));
+    }
+}
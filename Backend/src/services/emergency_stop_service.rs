@@ -0,0 +1,276 @@
+// ============================================================================
+// SERVICE : ARRÊT D'URGENCE
+// ============================================================================
+//
+// Description:
+//   Gère l'interrupteur d'arrêt d'urgence par utilisateur (emergency_stop_rust).
+//   Le basculer exige un PIN dédié, hashé en Argon2id, distinct du mot de passe.
+//   is_armed() est la lecture à utiliser par tout chemin qui passe des ordres
+//   (trades manuels, routes broker, futur auto-trading).
+//
+// Points d'attention:
+//   - Pas de ligne = désarmé (fail-open uniquement tant qu'aucun PIN n'est défini)
+//   - Changer le PIN exige le PIN courant s'il en existe déjà un
+//   - PIN_MAX_ATTEMPTS PIN faux consécutifs bloquent le PIN PIN_LOCKOUT_MINUTES
+//     (un PIN à 4 chiffres se devine sinon en 10 000 essais)
+//
+// ============================================================================
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, Set, ActiveModelTrait};
+use chrono::{Duration, NaiveDateTime, Utc};
+
+use crate::models::emergency_stop::{self, Entity as EmergencyStop};
+use crate::models::users;
+use crate::utils::password::{self, PasswordScheme};
+
+#[derive(Debug)]
+pub enum EmergencyStopError {
+    PinNotSet,
+    InvalidPin,
+    PinIsPassword,
+    /// Trop de PIN faux : le PIN est refusé jusqu'à cette date
+    Locked(NaiveDateTime),
+    Hash(String),
+    Db(DbErr),
+}
+
+impl From<DbErr> for EmergencyStopError {
+    fn from(e: DbErr) -> Self {
+        EmergencyStopError::Db(e)
+    }
+}
+
+/// PIN faux consécutifs avant blocage
+pub const PIN_MAX_ATTEMPTS: i32 = 5;
+/// Durée du blocage du PIN (en minutes)
+pub const PIN_LOCKOUT_MINUTES: i64 = 15;
+
+/// Hash Argon2id du PIN (indépendant de PASSWORD_SCHEME)
+pub fn hash_pin(pin: &str) -> Result<String, EmergencyStopError> {
+    password::hash_password_with(pin, PasswordScheme::Argon2, password::pbkdf2_iterations())
+        .map_err(EmergencyStopError::Hash)
+}
+
+/// Vérifie le PIN contre l'enregistrement existant
+pub fn verify_pin(record: Option<&emergency_stop::Model>, pin: &str) -> Result<(), EmergencyStopError> {
+    let record = record.ok_or(EmergencyStopError::PinNotSet)?;
+
+    match password::verify_password(pin, &record.pin_hash) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(EmergencyStopError::InvalidPin),
+        Err(e) => Err(EmergencyStopError::Hash(e)),
+    }
+}
+
+/// Fin du blocage si le PIN est bloqué à `now`
+pub fn pin_locked_until(record: &emergency_stop::Model, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    record.pin_locked_until.filter(|until| *until > now)
+}
+
+/// Compteur et blocage après un PIN faux (le compteur repart de 1 après un blocage expiré)
+pub fn next_pin_failure(record: &emergency_stop::Model, now: NaiveDateTime) -> (i32, Option<NaiveDateTime>) {
+    let previous_failures = if record.pin_locked_until.is_some() { 0 } else { record.failed_pin_attempts };
+    let failed_attempts = previous_failures + 1;
+    let locked_until = (failed_attempts >= PIN_MAX_ATTEMPTS).then(|| now + Duration::minutes(PIN_LOCKOUT_MINUTES));
+
+    (failed_attempts, locked_until)
+}
+
+pub struct EmergencyStopService;
+
+impl EmergencyStopService {
+    pub async fn find(db: &DatabaseConnection, user_id: i32) -> Result<Option<emergency_stop::Model>, DbErr> {
+        EmergencyStop::find_by_id(user_id).one(db).await
+    }
+
+    /// true si l'utilisateur a armé l'arrêt d'urgence (aucun ordre ne doit passer)
+    pub async fn is_armed(db: &DatabaseConnection, user_id: i32) -> Result<bool, DbErr> {
+        Ok(Self::find(db, user_id).await?.map(|r| r.armed).unwrap_or(false))
+    }
+
+    /// Définit (ou change) le PIN. Le PIN courant est exigé s'il en existe déjà un,
+    /// et le PIN ne peut pas être le mot de passe de connexion
+    pub async fn set_pin(
+        db: &DatabaseConnection,
+        user_id: i32,
+        new_pin: &str,
+        current_pin: Option<&str>,
+    ) -> Result<emergency_stop::Model, EmergencyStopError> {
+        let user = users::Entity::find_by_id(user_id).one(db).await?;
+        if let Some(password_hash) = user.and_then(|u| u.password_hash)
+            && password::verify_password(new_pin, &password_hash).unwrap_or(false)
+        {
+            return Err(EmergencyStopError::PinIsPassword);
+        }
+
+        let pin_hash = hash_pin(new_pin)?;
+        let now = Utc::now().naive_utc();
+
+        match Self::find(db, user_id).await? {
+            Some(existing) => {
+                let mut active = Self::check_pin_attempt(db, existing, current_pin.unwrap_or_default()).await?;
+                active.pin_hash = Set(pin_hash);
+                active.updated_at = Set(now);
+                Ok(active.update(db).await?)
+            }
+            None => {
+                let active = emergency_stop::ActiveModel {
+                    user_id: Set(user_id),
+                    pin_hash: Set(pin_hash),
+                    armed: Set(false),
+                    updated_at: Set(now),
+                    failed_pin_attempts: Set(0),
+                    pin_locked_until: Set(None),
+                };
+                Ok(active.insert(db).await?)
+            }
+        }
+    }
+
+    /// Arme ou désarme l'arrêt d'urgence après vérification du PIN
+    pub async fn set_armed(
+        db: &DatabaseConnection,
+        user_id: i32,
+        pin: &str,
+        armed: bool,
+    ) -> Result<emergency_stop::Model, EmergencyStopError> {
+        let existing = Self::find(db, user_id).await?.ok_or(EmergencyStopError::PinNotSet)?;
+
+        let mut active = Self::check_pin_attempt(db, existing, pin).await?;
+        active.armed = Set(armed);
+        active.updated_at = Set(Utc::now().naive_utc());
+        Ok(active.update(db).await?)
+    }
+
+    /// Vérifie un PIN en tenant compte du blocage. Un PIN faux est compté en BD ;
+    /// un PIN correct renvoie l'enregistrement avec le compteur remis à zéro (à sauvegarder)
+    async fn check_pin_attempt(
+        db: &DatabaseConnection,
+        existing: emergency_stop::Model,
+        pin: &str,
+    ) -> Result<emergency_stop::ActiveModel, EmergencyStopError> {
+        let now = Utc::now().naive_utc();
+        if let Some(until) = pin_locked_until(&existing, now) {
+            return Err(EmergencyStopError::Locked(until));
+        }
+
+        match verify_pin(Some(&existing), pin) {
+            Ok(()) => {
+                let mut active: emergency_stop::ActiveModel = existing.into();
+                active.failed_pin_attempts = Set(0);
+                active.pin_locked_until = Set(None);
+                Ok(active)
+            }
+            Err(EmergencyStopError::InvalidPin) => {
+                let (failed_attempts, locked_until) = next_pin_failure(&existing, now);
+
+                let mut active: emergency_stop::ActiveModel = existing.into();
+                active.failed_pin_attempts = Set(failed_attempts);
+                active.pin_locked_until = Set(locked_until);
+                active.update(db).await?;
+
+                Err(locked_until.map_or(EmergencyStopError::InvalidPin, EmergencyStopError::Locked))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(pin: &str, armed: bool) -> emergency_stop::Model {
+        emergency_stop::Model {
+            user_id: 1,
+            pin_hash: hash_pin(pin).unwrap(),
+            armed,
+            updated_at: Utc::now().naive_utc(),
+            failed_pin_attempts: 0,
+            pin_locked_until: None,
+        }
+    }
+
+    #[test]
+    fn test_pin_is_hashed_and_verified() {
+        let stop = record("4821", false);
+
+        assert!(stop.pin_hash.starts_with("$argon2"));
+        assert!(!stop.pin_hash.contains("4821"));
+        assert!(verify_pin(Some(&stop), "4821").is_ok());
+        assert!(matches!(verify_pin(Some(&stop), "0000"), Err(EmergencyStopError::InvalidPin)));
+    }
+
+    #[test]
+    fn test_cannot_arm_without_pin() {
+        assert!(matches!(verify_pin(None, "4821"), Err(EmergencyStopError::PinNotSet)));
+    }
+
+    #[test]
+    fn test_pin_locks_after_max_attempts() {
+        let now = Utc::now().naive_utc();
+        let mut stop = record("4821", false);
+
+        for attempt in 1..PIN_MAX_ATTEMPTS {
+            let (failed, locked_until) = next_pin_failure(&stop, now);
+            assert_eq!(failed, attempt);
+            assert!(locked_until.is_none());
+            stop.failed_pin_attempts = failed;
+        }
+
+        let (_, locked_until) = next_pin_failure(&stop, now);
+        stop.pin_locked_until = locked_until;
+        assert_eq!(pin_locked_until(&stop, now), Some(now + Duration::minutes(PIN_LOCKOUT_MINUTES)));
+        assert!(pin_locked_until(&stop, now + Duration::minutes(PIN_LOCKOUT_MINUTES + 1)).is_none());
+
+        // Après un blocage expiré, le compteur repart de 1
+        assert_eq!(next_pin_failure(&stop, now + Duration::minutes(PIN_LOCKOUT_MINUTES + 1)), (1, None));
+    }
+
+    #[tokio::test]
+    async fn test_locked_pin_is_refused_even_when_correct() {
+        use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let table = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(EmergencyStop);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        emergency_stop::ActiveModel::from(record("4821", false)).insert(&db).await.unwrap();
+
+        for _ in 1..PIN_MAX_ATTEMPTS {
+            let result = EmergencyStopService::set_armed(&db, 1, "0000", true).await;
+            assert!(matches!(result, Err(EmergencyStopError::InvalidPin)));
+        }
+        let result = EmergencyStopService::set_armed(&db, 1, "0000", true).await;
+        assert!(matches!(result, Err(EmergencyStopError::Locked(_))));
+
+        let result = EmergencyStopService::set_armed(&db, 1, "4821", true).await;
+        assert!(matches!(result, Err(EmergencyStopError::Locked(_))));
+        assert!(!EmergencyStopService::is_armed(&db, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_arm_and_disarm_round_trip() {
+        use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let table = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(EmergencyStop);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        let mut stop = record("4821", false);
+        stop.failed_pin_attempts = 2;
+        emergency_stop::ActiveModel::from(stop).insert(&db).await.unwrap();
+
+        let armed = EmergencyStopService::set_armed(&db, 1, "4821", true).await.unwrap();
+        assert!(armed.armed);
+        assert_eq!(armed.failed_pin_attempts, 0, "a correct PIN resets the counter");
+        assert!(EmergencyStopService::is_armed(&db, 1).await.unwrap());
+
+        // Un PIN faux ne désarme pas
+        let result = EmergencyStopService::set_armed(&db, 1, "0000", false).await;
+        assert!(matches!(result, Err(EmergencyStopError::InvalidPin)));
+        assert!(EmergencyStopService::is_armed(&db, 1).await.unwrap());
+
+        let disarmed = EmergencyStopService::set_armed(&db, 1, "4821", false).await.unwrap();
+        assert!(!disarmed.armed);
+        assert!(!EmergencyStopService::is_armed(&db, 1).await.unwrap());
+    }
+}
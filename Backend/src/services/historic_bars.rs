@@ -0,0 +1,429 @@
+// Lecture typée de historicdata : OHLCV est stocké en texte, on le parse une seule fois ici.
+// Une ligne dont open/high/low/close est absent, non numérique ou ≤ 0 est écartée
+// (un warning par symbole) ; le volume et adjusted_close restent optionnels.
+//
+// Source du prix (INDICATOR_PRICE_SOURCE=close|adjusted, défaut close) : en mode adjusted,
+// les indicateurs et les stratégies qui comparent un close aux indicateurs lisent des barres
+// ajustées (splits/dividendes). Le P&L et les trades restent sur le close brut.
+
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Select};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use tracing::warn;
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoricBar {
+    pub symbol: String,
+    pub date: String, // YYYY-MM-DD
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub adjusted_close: Option<f64>,
+    pub volume: Option<f64>,
+}
+
+/// Close lu par le pipeline d'indicateurs et les stratégies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    Close,         // Close brut (défaut, comportement historique)
+    AdjustedClose, // Close ajusté splits/dividendes
+}
+
+impl PriceSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "close" | "raw" => Some(PriceSource::Close),
+            "adjusted" | "adjusted_close" => Some(PriceSource::AdjustedClose),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        std::env::var("INDICATOR_PRICE_SOURCE")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(PriceSource::Close)
+    }
+}
+
+impl HistoricBar {
+    /// Barre vue selon la source de prix. En mode adjusted, open/high/low sont mis à l'échelle
+    /// du même facteur que le close (adjusted_close / close) pour que Stochastic, Keltner,
+    /// Donchian et les pivots restent cohérents avec le close ajusté.
+    /// Sans adjusted_close sur la ligne, la barre brute est conservée.
+    pub fn priced(self, source: PriceSource) -> Self {
+        match (source, self.adjusted_close) {
+            (PriceSource::AdjustedClose, Some(adjusted)) => {
+                let factor = adjusted / self.close;
+                Self {
+                    open: self.open * factor,
+                    high: self.high * factor,
+                    low: self.low * factor,
+                    close: adjusted,
+                    ..self
+                }
+            }
+            _ => self,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BarError {
+    Missing(&'static str),
+    Invalid { field: &'static str, value: String },
+}
+
+impl fmt::Display for BarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BarError::Missing(field) => write!(f, "{} is missing", field),
+            BarError::Invalid { field, value } => write!(f, "{} is not a valid price: {:?}", field, value),
+        }
+    }
+}
+
+fn parse_price(field: &'static str, value: Option<&str>) -> Result<f64, BarError> {
+    let value = value.ok_or(BarError::Missing(field))?;
+    match value.trim().parse::<f64>() {
+        Ok(price) if price.is_finite() && price > 0.0 => Ok(price),
+        _ => Err(BarError::Invalid { field, value: value.to_string() }),
+    }
+}
+
+impl TryFrom<&historic_data::Model> for HistoricBar {
+    type Error = BarError;
+
+    fn try_from(row: &historic_data::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            symbol: row.symbol.clone(),
+            date: row.date.clone(),
+            open: parse_price("open", row.open.as_deref())?,
+            high: parse_price("high", row.high.as_deref())?,
+            low: parse_price("low", row.low.as_deref())?,
+            close: parse_price("close", row.close.as_deref())?,
+            adjusted_close: row
+                .adjusted_close
+                .as_deref()
+                .and_then(|v| parse_price("adjusted_close", Some(v)).ok()),
+            volume: row
+                .volume
+                .as_deref()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite() && *v >= 0.0),
+        })
+    }
+}
+
+/// Convertit les lignes brutes en barres, dans le même ordre, en écartant les lignes invalides
+pub fn parse_bars(rows: Vec<historic_data::Model>) -> Vec<HistoricBar> {
+    // symbole -> (lignes écartées, première erreur)
+    let mut rejected: BTreeMap<String, (usize, String)> = BTreeMap::new();
+
+    let bars = rows
+        .iter()
+        .filter_map(|row| match HistoricBar::try_from(row) {
+            Ok(bar) => Some(bar),
+            Err(e) => {
+                rejected
+                    .entry(row.symbol.clone())
+                    .or_insert_with(|| (0, format!("{} on {}", e, row.date)))
+                    .0 += 1;
+                None
+            }
+        })
+        .collect();
+
+    for (symbol, (count, first_error)) in rejected {
+        warn!("Skipped {} invalid historicdata row(s) for {} (first: {})", count, symbol, first_error);
+    }
+
+    bars
+}
+
+async fn load(query: Select<HistoricData>, scope: &str, db: &DatabaseConnection) -> Result<Vec<HistoricBar>, String> {
+    let rows = query
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch historical data for {}: {}", scope, e))?;
+
+    Ok(parse_bars(rows))
+}
+
+/// Barres de plusieurs symboles (triées par symbole puis date), strictement après `after` si fourni
+pub async fn fetch_bars_for_symbols(
+    symbols: &[String],
+    after: Option<&str>,
+    db: &DatabaseConnection,
+) -> Result<Vec<HistoricBar>, String> {
+    let mut query = HistoricData::find()
+        .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())));
+    if let Some(after) = after {
+        query = query.filter(historic_data::Column::Date.gt(after));
+    }
+
+    load(
+        query
+            .order_by_asc(historic_data::Column::Symbol)
+            .order_by_asc(historic_data::Column::Date),
+        "symbols",
+        db,
+    )
+    .await
+}
+
+/// Barres de plusieurs symboles depuis `from_date` (inclus)
+pub async fn fetch_bars_since(
+    symbols: &[String],
+    from_date: &str,
+    db: &DatabaseConnection,
+) -> Result<Vec<HistoricBar>, String> {
+    load(
+        HistoricData::find()
+            .filter(historic_data::Column::Date.gte(from_date))
+            .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str()))),
+        "symbols",
+        db,
+    )
+    .await
+}
+
+/// Barres d'un symbole entre `from` et `to` (inclus), par date croissante
+pub async fn fetch_bars_between(
+    symbol: &str,
+    from: &str,
+    to: &str,
+    db: &DatabaseConnection,
+) -> Result<Vec<HistoricBar>, String> {
+    load(
+        HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .filter(historic_data::Column::Date.between(from, to))
+            .order_by_asc(historic_data::Column::Date),
+        symbol,
+        db,
+    )
+    .await
+}
+
+/// Barre d'un symbole à une date précise (None si absente ou invalide)
+pub async fn fetch_bar_on(symbol: &str, date: &str, db: &DatabaseConnection) -> Result<Option<HistoricBar>, String> {
+    let bars = load(
+        HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .filter(historic_data::Column::Date.eq(date)),
+        symbol,
+        db,
+    )
+    .await?;
+
+    Ok(bars.into_iter().next())
+}
+
+/// Les `limit` dernières lignes jusqu'à `date` (incluse), renvoyées par date croissante.
+/// Les lignes invalides sont écartées après la limite : le résultat peut être plus court.
+pub async fn fetch_bars_up_to(
+    symbol: &str,
+    date: &str,
+    limit: u64,
+    db: &DatabaseConnection,
+) -> Result<Vec<HistoricBar>, String> {
+    let mut bars = load(
+        HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .filter(historic_data::Column::Date.lte(date))
+            .order_by_desc(historic_data::Column::Date)
+            .limit(limit),
+        symbol,
+        db,
+    )
+    .await?;
+
+    bars.reverse();
+    Ok(bars)
+}
+
+/// Lignes lues par page en remontant depuis la plus récente (fetch_latest_bar)
+const LATEST_SCAN_PAGE: u64 = 20;
+
+/// Dernière barre valide d'un symbole : les lignes récentes invalides sont sautées
+/// en remontant l'historique par pages de LATEST_SCAN_PAGE lignes
+pub async fn fetch_latest_bar(symbol: &str, db: &DatabaseConnection) -> Result<Option<HistoricBar>, String> {
+    let mut offset = 0;
+
+    loop {
+        let rows = HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .order_by_desc(historic_data::Column::Date)
+            .offset(offset)
+            .limit(LATEST_SCAN_PAGE)
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch historical data for {}: {}", symbol, e))?;
+
+        let exhausted = (rows.len() as u64) < LATEST_SCAN_PAGE;
+        if let Some(bar) = parse_bars(rows).into_iter().next() {
+            return Ok(Some(bar));
+        }
+        if exhausted {
+            return Ok(None);
+        }
+        offset += LATEST_SCAN_PAGE;
+    }
+}
+
+/// Dernière barre de plusieurs symboles en deux requêtes (date max par symbole, puis ces lignes).
+/// Un symbole sans historique, ou dont la dernière ligne est invalide, est absent de la map.
+pub async fn fetch_latest_bars(symbols: &[String], db: &DatabaseConnection) -> Result<HashMap<String, HistoricBar>, String> {
+    if symbols.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let latest: Vec<(String, String)> = HistoricData::find()
+        .select_only()
+        .column(historic_data::Column::Symbol)
+        .column_as(historic_data::Column::Date.max(), "date")
+        .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .group_by(historic_data::Column::Symbol)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch latest dates: {}", e))?;
+
+    if latest.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = latest.into_iter().fold(Condition::any(), |cond, (symbol, date)| {
+        cond.add(
+            Condition::all()
+                .add(historic_data::Column::Symbol.eq(symbol))
+                .add(historic_data::Column::Date.eq(date)),
+        )
+    });
+    let bars = load(HistoricData::find().filter(rows), "symbols", db).await?;
+
+    Ok(bars.into_iter().map(|bar| (bar.symbol.clone(), bar)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(date: &str, ohlc: [Option<&str>; 4], volume: Option<&str>) -> historic_data::Model {
+        historic_data::Model {
+            symbol: "AAPL".to_string(),
+            date: date.to_string(),
+            open: ohlc[0].map(str::to_string),
+            high: ohlc[1].map(str::to_string),
+            low: ohlc[2].map(str::to_string),
+            close: ohlc[3].map(str::to_string),
+            adjusted_close: None,
+            volume: volume.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn parses_numeric_fields_once() {
+        let bar = HistoricBar::try_from(&row(
+            "2025-01-02",
+            [Some("10.5"), Some(" 11.25 "), Some("10"), Some("11")],
+            Some("120000"),
+        ))
+        .unwrap();
+
+        assert_eq!(
+            bar,
+            HistoricBar {
+                symbol: "AAPL".into(),
+                date: "2025-01-02".into(),
+                open: 10.5,
+                high: 11.25,
+                low: 10.0,
+                close: 11.0,
+                adjusted_close: None,
+                volume: Some(120000.0),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_missing_or_invalid_prices_but_tolerates_bad_volume() {
+        let ok = [Some("1"), Some("2"), Some("1"), Some("2")];
+
+        assert_eq!(
+            HistoricBar::try_from(&row("d", [Some("1"), Some("2"), Some("1"), None], None)),
+            Err(BarError::Missing("close"))
+        );
+        assert_eq!(
+            HistoricBar::try_from(&row("d", [Some("n/a"), Some("2"), Some("1"), Some("2")], None)),
+            Err(BarError::Invalid { field: "open", value: "n/a".into() })
+        );
+        assert!(HistoricBar::try_from(&row("d", [Some("1"), Some("2"), Some("0"), Some("2")], None)).is_err());
+        assert!(HistoricBar::try_from(&row("d", [Some("1"), Some("NaN"), Some("1"), Some("2")], None)).is_err());
+        assert_eq!(HistoricBar::try_from(&row("d", ok, Some("-"))).unwrap().volume, None);
+    }
+
+    #[test]
+    fn parse_bars_keeps_order_and_drops_invalid_rows() {
+        let ok = [Some("1"), Some("2"), Some("1"), Some("2")];
+        let rows = vec![
+            row("2025-01-02", ok, None),
+            row("2025-01-03", [None, None, None, Some("2")], None),
+            row("2025-01-06", ok, None),
+        ];
+
+        let dates: Vec<String> = parse_bars(rows).into_iter().map(|b| b.date).collect();
+        assert_eq!(dates, vec!["2025-01-02", "2025-01-06"]);
+    }
+
+    #[test]
+    fn adjusted_source_rescales_the_whole_bar() {
+        let mut raw = row("2025-01-02", [Some("100"), Some("110"), Some("90"), Some("100")], None);
+        raw.adjusted_close = Some("50".to_string());
+        let bar = HistoricBar::try_from(&raw).unwrap();
+
+        assert_eq!(bar.clone().priced(PriceSource::Close), bar);
+        let adjusted = bar.clone().priced(PriceSource::AdjustedClose);
+        assert_eq!((adjusted.open, adjusted.high, adjusted.low, adjusted.close), (50.0, 55.0, 45.0, 50.0));
+
+        // Pas d'adjusted_close sur la ligne : barre brute
+        let unadjusted = HistoricBar { adjusted_close: None, ..bar };
+        assert_eq!(unadjusted.clone().priced(PriceSource::AdjustedClose), unadjusted);
+        assert_eq!(PriceSource::parse("Adjusted"), Some(PriceSource::AdjustedClose));
+        assert_eq!(PriceSource::parse("vwap"), None);
+    }
+
+    #[tokio::test]
+    async fn latest_bar_skips_invalid_newest_rows() {
+        use sea_orm::{ConnectionTrait, Database};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared(
+            "CREATE TABLE historicdata (
+                symbol TEXT NOT NULL, date TEXT NOT NULL,
+                open TEXT, high TEXT, low TEXT, close TEXT, adjusted_close TEXT, volume TEXT,
+                PRIMARY KEY (symbol, date)
+            )",
+        )
+        .await
+        .unwrap();
+
+        // Une ligne valide, puis plus d'une page de lignes invalides plus récentes
+        let mut rows = vec![row("2025-01-02", [Some("1"), Some("2"), Some("1"), Some("2")], None)];
+        for day in 1..=LATEST_SCAN_PAGE as u32 + 5 {
+            rows.push(row(&format!("2025-02-{:02}", day), [None, None, None, Some("n/a")], None));
+        }
+        HistoricData::insert_many(rows.into_iter().map(historic_data::ActiveModel::from))
+            .exec(&db)
+            .await
+            .unwrap();
+
+        let latest = fetch_latest_bar("AAPL", &db).await.unwrap().unwrap();
+        assert_eq!(latest.date, "2025-01-02");
+        assert!(fetch_latest_bar("MSFT", &db).await.unwrap().is_none());
+    }
+}
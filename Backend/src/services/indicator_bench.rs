@@ -0,0 +1,237 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use std::time::{Duration as StdDuration, Instant};
+
+use crate::models::{historic_data, indicator};
+use crate::services::indicator_service::{IndicatorService, PersistenceMode};
+use tracing::info;
+
+/// Préfixe des symboles synthétiques (nettoyés avant et après le benchmark)
+pub const BENCH_SYMBOL_PREFIX: &str = "BENCH_";
+const DEFAULT_BENCH_SYMBOLS: usize = 50;
+const DEFAULT_BENCH_DAYS: usize = 500;
+const SEED_CHUNK_SIZE: usize = 1000;
+
+/// Paramètres du benchmark : `--bench-indicators [--symbols N] [--days M]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BenchConfig {
+    pub symbols: usize,
+    pub days: usize,
+}
+
+impl BenchConfig {
+    /// None si le flag --bench-indicators est absent
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        if !args.iter().any(|a| a == "--bench-indicators") {
+            return None;
+        }
+
+        let value_after = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .and_then(|i| args.get(i + 1))
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|v| *v > 0)
+        };
+
+        Some(Self {
+            symbols: value_after("--symbols").unwrap_or(DEFAULT_BENCH_SYMBOLS),
+            days: value_after("--days").unwrap_or(DEFAULT_BENCH_DAYS),
+        })
+    }
+
+    pub fn symbol_names(&self) -> Vec<String> {
+        (0..self.symbols).map(|i| format!("{}{:04}", BENCH_SYMBOL_PREFIX, i)).collect()
+    }
+}
+
+/// Résultat d'un chemin de persistance
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub mode: PersistenceMode,
+    pub rows: u64,
+    pub elapsed: StdDuration,
+}
+
+impl BenchReport {
+    pub fn rows_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.rows as f64 / secs } else { 0.0 }
+    }
+}
+
+/// Génère `days` jours ouvrés de OHLC déterministes se terminant à `end`.
+/// Dates volontairement anciennes pour ne jamais devenir la "dernière date" des vraies données.
+pub fn synthetic_history(symbol: &str, seed: usize, days: usize, end: NaiveDate) -> Vec<historic_data::Model> {
+    let mut dates = Vec::with_capacity(days);
+    let mut date = end;
+    while dates.len() < days {
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            dates.push(date);
+        }
+        date -= Duration::days(1);
+    }
+    dates.reverse();
+
+    let base = 20.0 + (seed % 200) as f64;
+
+    dates
+        .into_iter()
+        .enumerate()
+        .map(|(i, date)| {
+            let t = i as f64;
+            let close = base * (1.0 + 0.15 * (t / 20.0 + seed as f64).sin() + 0.0005 * t);
+            let spread = close * 0.01;
+            historic_data::Model {
+                symbol: symbol.to_string(),
+                date: date.format("%Y-%m-%d").to_string(),
+                open: Some(format!("{:.2}", close - spread / 2.0)),
+                high: Some(format!("{:.2}", close + spread)),
+                low: Some(format!("{:.2}", close - spread)),
+                close: Some(format!("{:.2}", close)),
+                adjusted_close: None,
+                volume: Some(format!("{}", 100_000 + (i * 37 + seed * 101) % 50_000)),
+            }
+        })
+        .collect()
+}
+
+async fn clear_indicators(symbols: &[String], db: &DatabaseConnection) -> Result<(), String> {
+    indicator::Entity::delete_many()
+        .filter(indicator::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to clear bench indicators: {}", e))?;
+    Ok(())
+}
+
+async fn clear_history(symbols: &[String], db: &DatabaseConnection) -> Result<(), String> {
+    historic_data::Entity::delete_many()
+        .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to clear bench historicdata: {}", e))?;
+    Ok(())
+}
+
+async fn seed_history(config: &BenchConfig, symbols: &[String], db: &DatabaseConnection) -> Result<(), String> {
+    let end = NaiveDate::from_ymd_opt(2019, 12, 31).expect("valid date");
+
+    let rows: Vec<historic_data::ActiveModel> = symbols
+        .iter()
+        .enumerate()
+        .flat_map(|(i, symbol)| synthetic_history(symbol, i, config.days, end))
+        .map(|m| historic_data::ActiveModel {
+            symbol: Set(m.symbol),
+            date: Set(m.date),
+            open: Set(m.open),
+            high: Set(m.high),
+            low: Set(m.low),
+            close: Set(m.close),
+            adjusted_close: Set(m.adjusted_close),
+            volume: Set(m.volume),
+        })
+        .collect();
+
+    for chunk in rows.chunks(SEED_CHUNK_SIZE) {
+        historic_data::Entity::insert_many(chunk.to_vec())
+            .exec(db)
+            .await
+            .map_err(|e| format!("Failed to seed bench historicdata: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Mesure un chemin de persistance sur des symboles déjà seedés (indicateurs vidés au préalable)
+async fn bench_mode(mode: PersistenceMode, symbols: &[String], db: &DatabaseConnection) -> Result<BenchReport, String> {
+    clear_indicators(symbols, db).await?;
+
+    let service = IndicatorService::with_persistence(mode);
+    let start = Instant::now();
+    service.calculate_all_indicators(symbols.to_vec(), None, db).await?;
+    let elapsed = start.elapsed();
+
+    let rows = indicator::Entity::find()
+        .filter(indicator::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .count(db)
+        .await
+        .map_err(|e| format!("Failed to count bench indicators: {}", e))?;
+
+    Ok(BenchReport { mode, rows, elapsed })
+}
+
+/// Seed N symboles × M jours, lance calculate_all_indicators pour chaque chemin de persistance
+/// (FLUX B, symboles neufs) puis nettoie les données synthétiques.
+pub async fn run(config: &BenchConfig, db: &DatabaseConnection) -> Result<Vec<BenchReport>, String> {
+    let symbols = config.symbol_names();
+
+    info!("Indicator benchmark: seeding {} symbols x {} days", config.symbols, config.days);
+    clear_indicators(&symbols, db).await?;
+    clear_history(&symbols, db).await?;
+    seed_history(config, &symbols, db).await?;
+
+    let mut reports = Vec::new();
+    let mut outcome = Ok(());
+
+    for mode in PersistenceMode::ALL {
+        match bench_mode(mode, &symbols, db).await {
+            Ok(report) => {
+                info!(
+                    "Indicator benchmark [{}]: {} rows in {:.2}s ({:.0} rows/sec)",
+                    report.mode.label(),
+                    report.rows,
+                    report.elapsed.as_secs_f64(),
+                    report.rows_per_sec()
+                );
+                reports.push(report);
+            }
+            Err(e) => {
+                outcome = Err(e);
+                break;
+            }
+        }
+    }
+
+    // Nettoyage même en cas d'échec d'un chemin
+    clear_indicators(&symbols, db).await?;
+    clear_history(&symbols, db).await?;
+
+    outcome.map(|_| reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn config_requires_flag_and_reads_sizes() {
+        assert_eq!(BenchConfig::from_args(&args(&["backend"])), None);
+        assert_eq!(
+            BenchConfig::from_args(&args(&["backend", "--bench-indicators"])),
+            Some(BenchConfig { symbols: DEFAULT_BENCH_SYMBOLS, days: DEFAULT_BENCH_DAYS })
+        );
+        assert_eq!(
+            BenchConfig::from_args(&args(&["backend", "--bench-indicators", "--symbols", "10", "--days", "0"])),
+            Some(BenchConfig { symbols: 10, days: DEFAULT_BENCH_DAYS })
+        );
+    }
+
+    #[test]
+    fn synthetic_history_only_has_weekdays_in_order() {
+        let end = NaiveDate::from_ymd_opt(2019, 12, 31).unwrap();
+        let rows = synthetic_history("BENCH_0000", 0, 30, end);
+
+        assert_eq!(rows.len(), 30);
+        assert_eq!(rows.last().unwrap().date, "2019-12-31");
+        assert!(rows.windows(2).all(|w| w[0].date < w[1].date));
+        for row in &rows {
+            let date = NaiveDate::parse_from_str(&row.date, "%Y-%m-%d").unwrap();
+            assert!(!matches!(date.weekday(), Weekday::Sat | Weekday::Sun));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// symbole -> Vec<(date, high, low)>
+type BarsBySymbol = HashMap<String, Vec<(String, f64, f64)>>;
+
+pub struct DonchianCalculator {
+    period: usize, // 20 par défaut
+}
+
+impl DonchianCalculator {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating Donchian({}) for {} rows", self.period, df_new.height());
+
+        // 1. Grouper df_full par symbole
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("Donchian: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Calculer le canal pour chaque symbole
+        let mut donchian_results: HashMap<(String, String), (f64, f64)> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, bars) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("Donchian: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            let highs: Vec<f64> = bars.iter().map(|(_, high, _)| *high).collect();
+            let lows: Vec<f64> = bars.iter().map(|(_, _, low)| *low).collect();
+            let channels = compute_donchian_values(&highs, &lows, self.period);
+
+            for (i, channel) in channels.iter().enumerate() {
+                if let Some(channel) = channel {
+                    let date = &bars[i].0;
+                    donchian_results.insert((symbol.clone(), date.clone()), *channel);
+                }
+            }
+        }
+
+        info!("Donchian: Calculated {} values", donchian_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut uppers = Vec::new();
+        let mut lowers = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let channel = donchian_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            uppers.push(channel.map(|(upper, _)| upper));
+            lowers.push(channel.map(|(_, lower)| lower));
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new("donchian_upper".into(), uppers)),
+            Column::Series(Series::new("donchian_lower".into(), lowers)),
+        ])?;
+
+        info!("Donchian: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<BarsBySymbol, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let high_col = df.column("high")?;
+        let low_col = df.column("low")?;
+
+        let mut grouped: BarsBySymbol = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let high = if let AnyValue::Float64(v) = high_col.get(i)? { v } else { continue };
+            let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
+
+            grouped.entry(symbol).or_default().push((date, high, low));
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// Canal de Donchian : (plus haut des highs, plus bas des lows) sur les 'period' dernières barres,
+/// barre courante incluse. None pendant la période de chauffe.
+pub(crate) fn compute_donchian_values(highs: &[f64], lows: &[f64], period: usize) -> Vec<Option<(f64, f64)>> {
+    (0..highs.len().min(lows.len()))
+        .map(|i| {
+            if period == 0 || i + 1 < period {
+                return None;
+            }
+            let window = i + 1 - period..=i;
+            let upper = highs[window.clone()].iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let lower = lows[window].iter().copied().fold(f64::INFINITY, f64::min);
+            Some((upper, lower))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_donchian_warm_up_and_rolling_window() {
+        let highs = [10.0, 12.0, 11.0, 9.0];
+        let lows = [8.0, 9.0, 7.0, 8.5];
+
+        let channels = compute_donchian_values(&highs, &lows, 3);
+
+        assert_eq!(channels[0], None);
+        assert_eq!(channels[1], None);
+        assert_eq!(channels[2], Some((12.0, 7.0)));
+        // Le high 10 et le low 8 de la première barre sortent de la fenêtre
+        assert_eq!(channels[3], Some((12.0, 7.0)));
+    }
+
+    #[test]
+    fn test_donchian_dataframe_columns() {
+        let df = df!(
+            "date" => ["2025-01-02", "2025-01-03", "2025-01-06"],
+            "symbol" => ["AAPL", "AAPL", "AAPL"],
+            "high" => [10.0, 11.0, 15.0],
+            "low" => [9.0, 8.0, 12.0],
+            "close" => [9.5, 10.0, 14.0],
+        ).unwrap();
+
+        let result = DonchianCalculator::new(2).calculate(df.clone(), &df).unwrap();
+
+        let upper: Vec<Option<f64>> = result.column("donchian_upper").unwrap().f64().unwrap().into_iter().collect();
+        let lower: Vec<Option<f64>> = result.column("donchian_lower").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(upper, vec![None, Some(11.0), Some(15.0)]);
+        assert_eq!(lower, vec![None, Some(8.0), Some(8.0)]);
+    }
+}
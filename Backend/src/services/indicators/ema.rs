@@ -1,5 +1,6 @@
 use polars::prelude::*;
 use std::collections::HashMap;
+use tracing::{debug, info};
 
 pub struct EMACalculator {
     periods: Vec<usize>, // [20, 50, 200]
@@ -15,12 +16,12 @@ impl EMACalculator {
         df_new: DataFrame,
         df_full: &DataFrame,
     ) -> Result<DataFrame, PolarsError> {
-        println!("🔄 Calculating EMA for {} rows", df_new.height());
+        info!("Calculating EMA for {} rows", df_new.height());
 
         // 1. Grouper df_full par symbole
         let grouped_full = self.group_by_symbol(df_full)?;
 
-        println!("📊 EMA: Grouped {} unique symbols", grouped_full.len());
+        info!("EMA: Grouped {} unique symbols", grouped_full.len());
 
         // 2. Calculer EMA pour chaque période et chaque symbole
         let mut ema_results: HashMap<(String, String, usize), f64> = HashMap::new();
@@ -30,11 +31,11 @@ impl EMACalculator {
 
         for (symbol, closes_with_dates) in grouped_full.iter() {
             symbol_idx += 1;
-            println!("📊 EMA: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+            debug!("EMA: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
 
             // Calculer EMA pour chaque période
             for &period in &self.periods {
-                let ema_values = self.compute_ema(&closes_with_dates, period);
+                let ema_values = self.compute_ema(closes_with_dates, period);
 
                 for (i, ema) in ema_values.iter().enumerate() {
                     if let Some(ema_val) = ema {
@@ -45,7 +46,7 @@ impl EMACalculator {
             }
         }
 
-        println!("✅ EMA: Calculated {} values", ema_results.len());
+        info!("EMA: Calculated {} values", ema_results.len());
 
         // 3. Construire le DataFrame résultat avec seulement df_new
         let date_col = df_new.column("date")?;
@@ -80,7 +81,7 @@ impl EMACalculator {
             Column::Series(Series::new("ema200".into(), ema200s)),
         ])?;
 
-        println!("✅ EMA: Result DataFrame has {} rows", result.height());
+        info!("EMA: Result DataFrame has {} rows", result.height());
         Ok(result)
     }
 
@@ -97,7 +98,7 @@ impl EMACalculator {
             let symbol = symbol_col.get(i)?.to_string();
             let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
 
-            grouped.entry(symbol).or_insert_with(Vec::new).push((date, close));
+            grouped.entry(symbol).or_default().push((date, close));
         }
 
         Ok(grouped)
@@ -106,38 +107,41 @@ impl EMACalculator {
     /// Calcule l'EMA pour une période donnée
     /// Retourne Vec<Option<f64>> de même longueur que closes_with_dates
     fn compute_ema(&self, closes_with_dates: &[(String, f64)], period: usize) -> Vec<Option<f64>> {
-        let mut ema_values = Vec::new();
+        let closes: Vec<f64> = closes_with_dates.iter().map(|(_, close)| *close).collect();
+        compute_ema_values(&closes, period)
+    }
+}
 
-        if closes_with_dates.len() < period {
-            // Pas assez de données
-            return vec![None; closes_with_dates.len()];
-        }
+/// Calcule l'EMA d'une série de valeurs (réutilisé par Keltner pour la ligne médiane)
+/// Retourne Vec<Option<f64>> de même longueur que values
+pub(crate) fn compute_ema_values(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut ema_values = Vec::new();
 
-        let multiplier = 2.0 / (period as f64 + 1.0);
+    if period == 0 || values.len() < period {
+        // Pas assez de données
+        return vec![None; values.len()];
+    }
 
-        // Calculer la SMA initiale (Simple Moving Average) pour les 'period' premières valeurs
-        let initial_sma: f64 = closes_with_dates[0..period]
-            .iter()
-            .map(|(_, close)| close)
-            .sum::<f64>() / period as f64;
+    let multiplier = 2.0 / (period as f64 + 1.0);
 
-        // Remplir les None pour les valeurs avant la période
-        for _ in 0..(period - 1) {
-            ema_values.push(None);
-        }
+    // Calculer la SMA initiale (Simple Moving Average) pour les 'period' premières valeurs
+    let initial_sma: f64 = values[0..period].iter().sum::<f64>() / period as f64;
 
-        // La première EMA est la SMA
-        ema_values.push(Some(initial_sma));
-        let mut previous_ema = initial_sma;
+    // Remplir les None pour les valeurs avant la période
+    for _ in 0..(period - 1) {
+        ema_values.push(None);
+    }
 
-        // Calculer les EMA suivantes
-        for i in period..closes_with_dates.len() {
-            let close = closes_with_dates[i].1;
-            let ema = (close * multiplier) + (previous_ema * (1.0 - multiplier));
-            ema_values.push(Some(ema));
-            previous_ema = ema;
-        }
+    // La première EMA est la SMA
+    ema_values.push(Some(initial_sma));
+    let mut previous_ema = initial_sma;
 
-        ema_values
+    // Calculer les EMA suivantes
+    for &value in &values[period..] {
+        let ema = (value * multiplier) + (previous_ema * (1.0 - multiplier));
+        ema_values.push(Some(ema));
+        previous_ema = ema;
     }
-}
\ No newline at end of file
+
+    ema_values
+}
@@ -0,0 +1,211 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::services::indicators::ema::compute_ema_values;
+
+/// (date, high, low, close)
+pub(crate) type HlcBar = (String, f64, f64, f64);
+
+pub struct KeltnerCalculator {
+    ema_period: usize,  // 20 pour la ligne médiane (EMA du close)
+    atr_period: usize,  // 10 pour l'ATR
+    multiplier: f64,    // 2.0 x ATR pour les bandes
+}
+
+impl KeltnerCalculator {
+    pub fn new(ema_period: usize, atr_period: usize, multiplier: f64) -> Self {
+        Self {
+            ema_period,
+            atr_period,
+            multiplier,
+        }
+    }
+
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating Keltner Channels for {} rows", df_new.height());
+
+        // 1. Grouper df_full par symbole
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("KELTNER: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Calculer les bandes pour chaque symbole : (upper, middle, lower)
+        let mut kc_results: HashMap<(String, String), (f64, f64, f64)> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, data) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("KELTNER: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            for (date, bands) in self.compute_channels(data) {
+                kc_results.insert((symbol.clone(), date), bands);
+            }
+        }
+
+        info!("KELTNER: Calculated {} values", kc_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut uppers = Vec::new();
+        let mut middles = Vec::new();
+        let mut lowers = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let bands = kc_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            uppers.push(bands.map(|(upper, _, _)| upper));
+            middles.push(bands.map(|(_, middle, _)| middle));
+            lowers.push(bands.map(|(_, _, lower)| lower));
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new("kc_upper".into(), uppers)),
+            Column::Series(Series::new("kc_middle".into(), middles)),
+            Column::Series(Series::new("kc_lower".into(), lowers)),
+        ])?;
+
+        info!("KELTNER: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole et retourne HashMap<symbol, Vec<(date, high, low, close)>>
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<HlcBar>>, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let high_col = df.column("high")?;
+        let low_col = df.column("low")?;
+        let close_col = df.column("close")?;
+
+        let mut grouped: HashMap<String, Vec<HlcBar>> = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let high = if let AnyValue::Float64(v) = high_col.get(i)? { v } else { continue };
+            let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
+            let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
+
+            grouped.entry(symbol).or_default().push((date, high, low, close));
+        }
+
+        Ok(grouped)
+    }
+
+    /// Calcule (date, (upper, middle, lower)) pour chaque jour où EMA et ATR sont disponibles
+    /// middle = EMA(close, ema_period), bandes = middle ± multiplier × ATR(atr_period)
+    fn compute_channels(&self, data: &[HlcBar]) -> Vec<(String, (f64, f64, f64))> {
+        let closes: Vec<f64> = data.iter().map(|(_, _, _, close)| *close).collect();
+        let emas = compute_ema_values(&closes, self.ema_period);
+        let atrs = compute_atr_values(data, self.atr_period);
+
+        data.iter()
+            .zip(emas.iter().zip(atrs.iter()))
+            .filter_map(|((date, _, _, _), (ema, atr))| {
+                let (middle, atr) = (ema.as_ref()?, atr.as_ref()?);
+                let offset = self.multiplier * atr;
+                Some((date.clone(), (middle + offset, *middle, middle - offset)))
+            })
+            .collect()
+    }
+}
+
+/// Calcule l'ATR (lissage de Wilder) sur des données (date, high, low, close) triées par date
+/// Retourne Vec<Option<f64>> de même longueur que data
+pub(crate) fn compute_atr_values(data: &[HlcBar], period: usize) -> Vec<Option<f64>> {
+    if period == 0 || data.len() < period {
+        // Pas assez de données
+        return vec![None; data.len()];
+    }
+
+    // True Range = max(high - low, |high - close précédent|, |low - close précédent|)
+    let true_ranges: Vec<f64> = data
+        .iter()
+        .enumerate()
+        .map(|(i, (_, high, low, _))| {
+            let range = high - low;
+            if i == 0 {
+                return range;
+            }
+            let previous_close = data[i - 1].3;
+            range
+                .max((high - previous_close).abs())
+                .max((low - previous_close).abs())
+        })
+        .collect();
+
+    let mut atr_values = vec![None; period - 1];
+
+    // Le premier ATR est la moyenne simple des 'period' premiers True Range
+    let mut previous_atr = true_ranges[..period].iter().sum::<f64>() / period as f64;
+    atr_values.push(Some(previous_atr));
+
+    for tr in &true_ranges[period..] {
+        previous_atr = (previous_atr * (period as f64 - 1.0) + tr) / period as f64;
+        atr_values.push(Some(previous_atr));
+    }
+
+    atr_values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bars(values: &[(f64, f64, f64)]) -> Vec<HlcBar> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, (high, low, close))| (format!("2025-01-{:02}", i + 1), *high, *low, *close))
+            .collect()
+    }
+
+    #[test]
+    fn test_atr_uses_previous_close_gaps() {
+        // Jour 3 : gap haussier, le True Range vaut high - close précédent = 14 - 10 = 4
+        let data = bars(&[(11.0, 9.0, 10.0), (11.0, 9.0, 10.0), (14.0, 13.0, 13.5)]);
+
+        let atr = compute_atr_values(&data, 2);
+
+        assert_eq!(atr, vec![None, Some(2.0), Some(3.0)]);
+    }
+
+    #[test]
+    fn test_channels_are_symmetric_around_ema() {
+        let data = bars(&[
+            (11.0, 9.0, 10.0),
+            (12.0, 10.0, 11.0),
+            (13.0, 11.0, 12.0),
+            (14.0, 12.0, 13.0),
+        ]);
+
+        let calculator = KeltnerCalculator::new(3, 2, 2.0);
+        let channels = calculator.compute_channels(&data);
+
+        // L'EMA(3) n'existe qu'à partir du 3e jour
+        assert_eq!(channels.len(), 2);
+
+        let (date, (upper, middle, lower)) = &channels[0];
+        assert_eq!(date, "2025-01-03");
+        assert!((middle - 11.0).abs() < 1e-9);
+        assert!(((upper - middle) - (middle - lower)).abs() < 1e-9);
+        assert!(upper > middle && lower < middle);
+    }
+}
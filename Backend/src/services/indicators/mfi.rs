@@ -0,0 +1,219 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// Une barre pour le MFI : (date, high, low, close, volume)
+type MfiBar = (String, f64, f64, f64, f64);
+
+/// symbole -> Vec<MfiBar>
+type BarsBySymbol = HashMap<String, Vec<MfiBar>>;
+
+pub struct MFICalculator {
+    period: usize, // 14 par défaut
+}
+
+impl MFICalculator {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating MFI({}) for {} rows", self.period, df_new.height());
+
+        // 1. Grouper df_full par symbole
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("MFI: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Calculer le MFI pour chaque symbole
+        let mut mfi_results: HashMap<(String, String), f64> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, bars) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("MFI: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            let highs: Vec<f64> = bars.iter().map(|(_, high, _, _, _)| *high).collect();
+            let lows: Vec<f64> = bars.iter().map(|(_, _, low, _, _)| *low).collect();
+            let closes: Vec<f64> = bars.iter().map(|(_, _, _, close, _)| *close).collect();
+            let volumes: Vec<f64> = bars.iter().map(|(_, _, _, _, volume)| *volume).collect();
+            let mfi_values = compute_mfi_values(&highs, &lows, &closes, &volumes, self.period);
+
+            for (i, mfi) in mfi_values.iter().enumerate() {
+                if let Some(mfi_val) = mfi {
+                    let date = &bars[i].0;
+                    mfi_results.insert((symbol.clone(), date.clone()), *mfi_val);
+                }
+            }
+        }
+
+        info!("MFI: Calculated {} values", mfi_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut mfis = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let mfi = mfi_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            mfis.push(mfi);
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new(format!("mfi{}", self.period).into(), mfis)),
+        ])?;
+
+        info!("MFI: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole ; un volume absent (null) compte comme un volume nul
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<BarsBySymbol, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let high_col = df.column("high")?;
+        let low_col = df.column("low")?;
+        let close_col = df.column("close")?;
+        let volume_col = df.column("volume")?;
+
+        let mut grouped: BarsBySymbol = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let high = if let AnyValue::Float64(v) = high_col.get(i)? { v } else { continue };
+            let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
+            let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
+            let volume = if let AnyValue::Float64(v) = volume_col.get(i)? { v } else { 0.0 };
+
+            grouped.entry(symbol).or_default().push((date, high, low, close, volume));
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// MFI = 100 - 100 / (1 + flux positif / flux négatif) sur les 'period' dernières variations.
+/// Flux = prix typique (high + low + close) / 3 × volume, positif si le prix typique monte,
+/// négatif s'il baisse, ignoré s'il est inchangé.
+/// Une barre à volume nul n'apporte aucun flux ; flux négatif nul sur la fenêtre → 100 (comme le RSI).
+/// None pendant la période de chauffe (period + 1 barres nécessaires).
+pub(crate) fn compute_mfi_values(
+    highs: &[f64],
+    lows: &[f64],
+    closes: &[f64],
+    volumes: &[f64],
+    period: usize,
+) -> Vec<Option<f64>> {
+    let len = highs.len().min(lows.len()).min(closes.len()).min(volumes.len());
+    let typical: Vec<f64> = (0..len).map(|i| (highs[i] + lows[i] + closes[i]) / 3.0).collect();
+
+    // flows[i] = (flux positif, flux négatif) de la barre i par rapport à la barre i - 1
+    let flows: Vec<(f64, f64)> = (0..len)
+        .map(|i| {
+            if i == 0 {
+                return (0.0, 0.0);
+            }
+            let money_flow = typical[i] * volumes[i];
+            if typical[i] > typical[i - 1] {
+                (money_flow, 0.0)
+            } else if typical[i] < typical[i - 1] {
+                (0.0, money_flow)
+            } else {
+                (0.0, 0.0)
+            }
+        })
+        .collect();
+
+    (0..len)
+        .map(|i| {
+            if period == 0 || i < period {
+                return None;
+            }
+            let window = &flows[i + 1 - period..=i];
+            let positive: f64 = window.iter().map(|(positive, _)| positive).sum();
+            let negative: f64 = window.iter().map(|(_, negative)| negative).sum();
+
+            if negative == 0.0 {
+                return Some(100.0);
+            }
+            Some(100.0 - 100.0 / (1.0 + positive / negative))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mfi_known_series() {
+        // Prix typiques : 10, 11, 10.5, 12 ; volumes 100, 200, 100, 50
+        let highs = [11.0, 12.0, 11.5, 13.0];
+        let lows = [9.0, 10.0, 9.5, 11.0];
+        let closes = [10.0, 11.0, 10.5, 12.0];
+        let volumes = [100.0, 200.0, 100.0, 50.0];
+
+        let mfi = compute_mfi_values(&highs, &lows, &closes, &volumes, 2);
+
+        assert_eq!(mfi[0], None);
+        assert_eq!(mfi[1], None);
+        // Fenêtre [+2200, -1050] → 100 - 100 / (1 + 2200/1050)
+        assert!((mfi[2].unwrap() - 67.692307).abs() < 1e-5);
+        // Fenêtre [-1050, +600] → 100 - 100 / (1 + 600/1050)
+        assert!((mfi[3].unwrap() - 36.363636).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mfi_zero_volume_and_zero_negative_flow() {
+        let highs = [11.0, 12.0, 11.5, 13.0];
+        let lows = [9.0, 10.0, 9.5, 11.0];
+        let closes = [10.0, 11.0, 10.5, 12.0];
+
+        // La baisse du prix typique se fait sans volume : aucun flux négatif → 100
+        let mfi = compute_mfi_values(&highs, &lows, &closes, &[100.0, 200.0, 0.0, 50.0], 2);
+        assert_eq!(mfi[2], Some(100.0));
+        assert_eq!(mfi[3], Some(100.0));
+
+        // Aucun volume du tout : pas de NaN
+        let mfi = compute_mfi_values(&highs, &lows, &closes, &[0.0; 4], 2);
+        assert_eq!(mfi, vec![None, None, Some(100.0), Some(100.0)]);
+    }
+
+    #[test]
+    fn test_mfi_dataframe_column_and_missing_volume() {
+        let df = df!(
+            "date" => ["2025-01-02", "2025-01-03", "2025-01-06", "2025-01-07"],
+            "symbol" => ["AAPL", "AAPL", "AAPL", "AAPL"],
+            "high" => [11.0, 12.0, 11.5, 13.0],
+            "low" => [9.0, 10.0, 9.5, 11.0],
+            "close" => [10.0, 11.0, 10.5, 12.0],
+            "volume" => [Some(100.0), Some(200.0), Some(100.0), None],
+        ).unwrap();
+
+        let result = MFICalculator::new(2).calculate(df.clone(), &df).unwrap();
+
+        let mfi: Vec<Option<f64>> = result.column("mfi2").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(mfi[..2], [None, None]);
+        assert!((mfi[2].unwrap() - 67.692307).abs() < 1e-5);
+        // Volume absent sur la dernière barre → flux nul, seule la baisse précédente reste
+        assert_eq!(mfi[3], Some(0.0));
+    }
+}
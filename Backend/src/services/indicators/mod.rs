@@ -1,4 +1,12 @@
 pub mod rsi;
 pub mod stochastic;
 pub mod ema;
-pub mod point_pivot;
\ No newline at end of file
+pub mod point_pivot;
+pub mod keltner;
+pub mod roc;
+pub mod donchian;
+pub mod mfi;
+pub mod psar;
+pub mod registry;
+pub mod trix;
+pub mod precision;
\ No newline at end of file
@@ -2,6 +2,12 @@ use polars::prelude::*;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use serde_json;
+use tracing::{debug, info};
+
+use crate::services::indicators::precision::{Precision, DEFAULT_INDICATOR_DECIMALS};
+
+/// (date, open, high, low, close)
+type OhlcBar = (String, f64, f64, f64, f64);
 
 #[derive(Debug, Serialize, Deserialize)]
 struct CamarillaPivot {
@@ -12,6 +18,8 @@ struct CamarillaPivot {
     s1: f64,
     s2: f64,
     s3: f64,
+    #[serde(default)]
+    bars: usize, // Nombre de barres réellement utilisées pour la période
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,11 +32,31 @@ struct AllPivots {
     year: Option<CamarillaPivot>,
 }
 
-pub struct PointPivotCalculator;
+/// Nombre minimum de barres par période (2 / 5 / 30 par défaut pour week / month / year).
+/// Compromis : un minimum bas donne des pivots dès les premiers jours de cotation (symboles
+/// peu échangés ou récemment listés), mais calculés sur peu de barres donc peu fiables ;
+/// un minimum haut ne publie que des pivots représentatifs de la période.
+/// Le nombre de barres utilisées est stocké dans le JSON ("bars") pour pondérer côté stratégie.
+pub struct PointPivotCalculator {
+    min_week_points: usize,
+    min_month_points: usize,
+    min_year_points: usize,
+    precision: Precision, // Arrondi des niveaux (prix)
+}
 
 impl PointPivotCalculator {
-    pub fn new() -> Self {
-        Self
+    pub fn new(min_week_points: usize, min_month_points: usize, min_year_points: usize) -> Self {
+        Self {
+            min_week_points,
+            min_month_points,
+            min_year_points,
+            precision: Precision::Price(DEFAULT_INDICATOR_DECIMALS),
+        }
+    }
+
+    /// Décimales minimum des niveaux (INDICATOR_DECIMALS côté IndicatorService)
+    pub fn with_decimals(self, decimals: usize) -> Self {
+        Self { precision: Precision::Price(decimals), ..self }
     }
 
     pub fn calculate(
@@ -36,12 +64,12 @@ impl PointPivotCalculator {
         df_new: DataFrame,
         df_full: &DataFrame,
     ) -> Result<DataFrame, PolarsError> {
-        println!("🔄 Calculating Point Pivot for {} rows", df_new.height());
+        info!("Calculating Point Pivot for {} rows", df_new.height());
 
         // 1. Grouper df_full par symbole
         let grouped_full = self.group_by_symbol(df_full)?;
 
-        println!("📊 POINT PIVOT: Grouped {} unique symbols", grouped_full.len());
+        info!("POINT PIVOT: Grouped {} unique symbols", grouped_full.len());
 
         // 2. Calculer les points pivots pour chaque symbole
         let mut pivot_results: HashMap<(String, String), String> = HashMap::new();
@@ -51,20 +79,20 @@ impl PointPivotCalculator {
 
         for (symbol, data) in grouped_full.iter() {
             symbol_idx += 1;
-            println!("📊 POINT PIVOT: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+            debug!("POINT PIVOT: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
 
             // Pour chaque date dans les données du symbole
             for i in 0..data.len() {
                 let current_date = &data[i].0;
 
                 // Calculer week pivots (7 derniers jours)
-                let week_pivots = self.calculate_period_pivots(data, i, 7, 2);
+                let week_pivots = self.calculate_period_pivots(data, i, 7, self.min_week_points);
 
                 // Calculer month pivots (30 derniers jours)
-                let month_pivots = self.calculate_period_pivots(data, i, 30, 5);
+                let month_pivots = self.calculate_period_pivots(data, i, 30, self.min_month_points);
 
                 // Calculer year pivots (365 derniers jours)
-                let year_pivots = self.calculate_period_pivots(data, i, 365, 30);
+                let year_pivots = self.calculate_period_pivots(data, i, 365, self.min_year_points);
 
                 // Si au moins un pivot existe, créer le JSON
                 if week_pivots.is_some() || month_pivots.is_some() || year_pivots.is_some() {
@@ -81,7 +109,7 @@ impl PointPivotCalculator {
             }
         }
 
-        println!("✅ POINT PIVOT: Calculated {} values", pivot_results.len());
+        info!("POINT PIVOT: Calculated {} values", pivot_results.len());
 
         // 3. Construire le DataFrame résultat avec seulement df_new
         let date_col = df_new.column("date")?;
@@ -108,12 +136,12 @@ impl PointPivotCalculator {
             Column::Series(Series::new("point_pivot".into(), pivots)),
         ])?;
 
-        println!("✅ POINT PIVOT: Result DataFrame has {} rows", result.height());
+        info!("POINT PIVOT: Result DataFrame has {} rows", result.height());
         Ok(result)
     }
 
     /// Groupe df par symbole et retourne HashMap<symbol, Vec<(date, open, high, low, close)>>
-    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<(String, f64, f64, f64, f64)>>, PolarsError> {
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<OhlcBar>>, PolarsError> {
         let date_col = df.column("date")?;
         let symbol_col = df.column("symbol")?;
         let open_col = df.column("open")?;
@@ -121,7 +149,7 @@ impl PointPivotCalculator {
         let low_col = df.column("low")?;
         let close_col = df.column("close")?;
 
-        let mut grouped: HashMap<String, Vec<(String, f64, f64, f64, f64)>> = HashMap::new();
+        let mut grouped: HashMap<String, Vec<OhlcBar>> = HashMap::new();
 
         for i in 0..df.height() {
             let date = date_col.get(i)?.to_string();
@@ -131,7 +159,7 @@ impl PointPivotCalculator {
             let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
             let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
 
-            grouped.entry(symbol).or_insert_with(Vec::new).push((date, open, high, low, close));
+            grouped.entry(symbol).or_default().push((date, open, high, low, close));
         }
 
         Ok(grouped)
@@ -144,7 +172,7 @@ impl PointPivotCalculator {
     /// min_data_points: nombre minimum de points de données requis
     fn calculate_period_pivots(
         &self,
-        data: &[(String, f64, f64, f64, f64)],
+        data: &[OhlcBar],
         current_idx: usize,
         period_days: usize,
         min_data_points: usize,
@@ -181,29 +209,98 @@ impl PointPivotCalculator {
         let close_last = window.last()?.4;   // close du dernier élément
 
         // Calculer les pivots Camarilla
-        self.calculate_camarilla_pivots(high_max, low_min, close_last, open_first)
+        let mut pivots = self.calculate_camarilla_pivots(high_max, low_min, close_last, open_first)?;
+        pivots.bars = window.len();
+        Some(pivots)
     }
 
     /// Calcule les points pivots Camarilla
     fn calculate_camarilla_pivots(&self, h: f64, l: f64, c: f64, o: f64) -> Option<CamarillaPivot> {
-        if h.is_nan() || l.is_nan() || c.is_nan() || o.is_nan() {
+        // NaN ou ±Infinity (fenêtre vide, données corrompues) → pas de pivot
+        if ![h, l, c, o].iter().all(|v| v.is_finite()) {
             return None;
         }
 
         let pivot = (h + l + c + o) / 4.0;
 
-        Some(CamarillaPivot {
-            pivot: self.round_to_2_decimals(pivot),
-            r1: self.round_to_2_decimals((2.0 * pivot) - l),
-            r2: self.round_to_2_decimals(pivot + (h - l)),
-            r3: self.round_to_2_decimals(h + 2.0 * (pivot - l)),
-            s1: self.round_to_2_decimals((2.0 * pivot) - h),
-            s2: self.round_to_2_decimals(pivot - (h - l)),
-            s3: self.round_to_2_decimals(l - 2.0 * (h - pivot)),
-        })
+        let pivots = CamarillaPivot {
+            pivot: self.round_level(pivot),
+            r1: self.round_level((2.0 * pivot) - l),
+            r2: self.round_level(pivot + (h - l)),
+            r3: self.round_level(h + 2.0 * (pivot - l)),
+            s1: self.round_level((2.0 * pivot) - h),
+            s2: self.round_level(pivot - (h - l)),
+            s3: self.round_level(l - 2.0 * (h - pivot)),
+            bars: 0,
+        };
+
+        // Débordement possible sur des valeurs extrêmes
+        let values = [pivots.pivot, pivots.r1, pivots.r2, pivots.r3, pivots.s1, pivots.s2, pivots.s3];
+        values.iter().all(|v| v.is_finite()).then_some(pivots)
+    }
+
+    fn round_level(&self, value: f64) -> f64 {
+        self.precision.round(value)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camarilla_rejects_non_finite_inputs() {
+        let calculator = PointPivotCalculator::new(2, 5, 30);
 
-    fn round_to_2_decimals(&self, value: f64) -> f64 {
-        (value * 100.0).round() / 100.0
+        assert!(calculator.calculate_camarilla_pivots(f64::NEG_INFINITY, f64::INFINITY, 10.0, 10.0).is_none());
+        assert!(calculator.calculate_camarilla_pivots(f64::NAN, 9.0, 10.0, 10.0).is_none());
+        assert!(calculator.calculate_camarilla_pivots(f64::MAX, -f64::MAX, 0.0, 0.0).is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_camarilla_constant_price_is_flat() {
+        let pivots = PointPivotCalculator::new(2, 5, 30).calculate_camarilla_pivots(10.0, 10.0, 10.0, 10.0).unwrap();
+        assert_eq!((pivots.pivot, pivots.r3, pivots.s3), (10.0, 10.0, 10.0));
+    }
+
+    fn bars(n: usize) -> Vec<OhlcBar> {
+        (0..n).map(|i| (format!("d{}", i), 10.0, 11.0, 9.0, 10.0)).collect()
+    }
+
+    #[test]
+    fn test_min_data_points_per_period() {
+        let calculator = PointPivotCalculator::new(3, 10, 100);
+
+        // (période, minimum) : minimum - 1 barres → pas de pivot, minimum → pivot avec le compte de barres
+        for (period_days, min_points) in [(7, 3), (30, 10), (365, 100)] {
+            let too_few = bars(min_points - 1);
+            assert!(calculator.calculate_period_pivots(&too_few, too_few.len() - 1, period_days, min_points).is_none());
+
+            let just_enough = bars(min_points);
+            let pivots = calculator.calculate_period_pivots(&just_enough, just_enough.len() - 1, period_days, min_points).unwrap();
+            assert_eq!(pivots.bars, min_points);
+        }
+    }
+
+    #[test]
+    fn test_configured_minimums_drive_stored_json() {
+        let data = bars(5);
+        let df = df!(
+            "date" => data.iter().map(|b| b.0.clone()).collect::<Vec<_>>(),
+            "symbol" => vec!["NEW"; 5],
+            "open" => vec![10.0; 5],
+            "high" => vec![11.0; 5],
+            "low" => vec![9.0; 5],
+            "close" => vec![10.0; 5],
+        ).unwrap();
+
+        // Symbole récent : 5 barres suffisent pour week et month avec des minimums de 2 / 5, jamais pour year
+        let result = PointPivotCalculator::new(2, 5, 30).calculate(df.clone(), &df).unwrap();
+        let last = result.column("point_pivot").unwrap().get(4).unwrap().to_string();
+        let json: serde_json::Value = serde_json::from_str(last.trim_matches('"')).unwrap();
+
+        assert_eq!(json["week"]["bars"], 5);
+        assert_eq!(json["month"]["bars"], 5);
+        assert!(json.get("year").is_none());
+    }
+}
@@ -0,0 +1,64 @@
+// Précision des indicateurs stockés dans indicators_rust (colonnes texte).
+// INDICATOR_DECIMALS fixe le nombre de décimales (défaut 2). Les indicateurs exprimés en prix
+// (EMA, Keltner, Donchian, PSAR, pivots) gardent en plus au moins PRICE_SIGNIFICANT_DIGITS
+// chiffres significatifs : l'EMA d'une action à 0.0123 $ ne s'arrondit plus à 0.01.
+
+pub const DEFAULT_INDICATOR_DECIMALS: usize = 2;
+pub const MAX_INDICATOR_DECIMALS: usize = 10;
+pub const PRICE_SIGNIFICANT_DIGITS: i32 = 4;
+
+/// Décimales via INDICATOR_DECIMALS (0 à MAX_INDICATOR_DECIMALS, défaut 2)
+pub fn indicator_decimals_from_env() -> usize {
+    std::env::var("INDICATOR_DECIMALS")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|v| *v <= MAX_INDICATOR_DECIMALS)
+        .unwrap_or(DEFAULT_INDICATOR_DECIMALS)
+}
+
+/// Règle d'arrondi d'un indicateur
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Fixed(usize), // Oscillateurs, pourcentages : décimales fixes
+    Price(usize), // Prix : décimales minimum, étendues pour garder PRICE_SIGNIFICANT_DIGITS chiffres
+}
+
+impl Precision {
+    /// Décimales à utiliser pour `value`
+    pub fn decimals(self, value: f64) -> usize {
+        match self {
+            Precision::Fixed(decimals) => decimals,
+            Precision::Price(decimals) => {
+                if value == 0.0 || !value.is_finite() {
+                    return decimals;
+                }
+                let magnitude = value.abs().log10().floor() as i32;
+                let needed = (PRICE_SIGNIFICANT_DIGITS - 1 - magnitude).clamp(0, MAX_INDICATOR_DECIMALS as i32) as usize;
+                decimals.max(needed)
+            }
+        }
+    }
+
+    pub fn format(self, value: f64) -> String {
+        format!("{:.*}", self.decimals(value), value)
+    }
+
+    pub fn round(self, value: f64) -> f64 {
+        let factor = 10f64.powi(self.decimals(value) as i32);
+        (value * factor).round() / factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_keeps_significant_digits_below_one_dollar() {
+        assert_eq!(Precision::Price(2).format(152.4567), "152.46");
+        assert_eq!(Precision::Price(2).format(0.012345), "0.01235");
+        assert_eq!(Precision::Price(6).format(0.5), "0.500000");
+        assert_eq!(Precision::Fixed(2).format(0.012345), "0.01");
+        assert_eq!(Precision::Price(2).round(0.0004567), 0.0004567);
+    }
+}
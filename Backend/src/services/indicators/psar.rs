@@ -0,0 +1,249 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+/// symbole -> Vec<(date, high, low)>
+type BarsBySymbol = HashMap<String, Vec<(String, f64, f64)>>;
+
+/// Sens de la tendance Parabolic SAR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsarTrend {
+    Up,   // SAR sous les prix (stop d'une position longue)
+    Down, // SAR au-dessus des prix
+}
+
+impl PsarTrend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PsarTrend::Up => "up",
+            PsarTrend::Down => "down",
+        }
+    }
+}
+
+pub struct PSARCalculator {
+    step: f64,     // 0.02 par défaut (pas et facteur d'accélération initial)
+    max_step: f64, // 0.2 par défaut
+}
+
+impl PSARCalculator {
+    pub fn new(step: f64, max_step: f64) -> Self {
+        Self { step, max_step }
+    }
+
+    /// La récursion repart du début de df_full : en FLUX A (fenêtre de 365 jours), le SAR
+    /// rejoint celui d'un calcul complet dès les premiers retournements de tendance
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating PSAR({}, {}) for {} rows", self.step, self.max_step, df_new.height());
+
+        // 1. Grouper df_full par symbole (ordre chronologique)
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("PSAR: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Dérouler la récursion pour chaque symbole
+        let mut psar_results: HashMap<(String, String), (f64, PsarTrend)> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, bars) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("PSAR: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            let highs: Vec<f64> = bars.iter().map(|(_, high, _)| *high).collect();
+            let lows: Vec<f64> = bars.iter().map(|(_, _, low)| *low).collect();
+            let values = compute_psar_values(&highs, &lows, self.step, self.max_step);
+
+            for (i, value) in values.iter().enumerate() {
+                if let Some(value) = value {
+                    let date = &bars[i].0;
+                    psar_results.insert((symbol.clone(), date.clone()), *value);
+                }
+            }
+        }
+
+        info!("PSAR: Calculated {} values", psar_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut psars = Vec::new();
+        let mut trends = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let value = psar_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            psars.push(value.map(|(sar, _)| sar));
+            trends.push(value.map(|(_, trend)| trend.as_str()));
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new("psar".into(), psars)),
+            Column::Series(Series::new("psar_trend".into(), trends)),
+        ])?;
+
+        info!("PSAR: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole en conservant l'ordre des lignes (dates croissantes)
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<BarsBySymbol, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let high_col = df.column("high")?;
+        let low_col = df.column("low")?;
+
+        let mut grouped: BarsBySymbol = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let high = if let AnyValue::Float64(v) = high_col.get(i)? { v } else { continue };
+            let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
+
+            grouped.entry(symbol).or_default().push((date, high, low));
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// Parabolic SAR de Wilder, barre par barre : (SAR, tendance) ; None pour la première barre (chauffe).
+/// - Barre 1 : tendance haussière si le milieu du range monte ; SAR = extrême opposé de la barre 0,
+///   point extrême (EP) = extrême des deux premières barres, facteur d'accélération (AF) = step
+/// - Ensuite SAR += AF × (EP - SAR), sans dépasser les extrêmes opposés des deux barres précédentes ;
+///   chaque nouvel EP augmente l'AF de step (plafonné à max_step)
+/// - Le prix traverse le SAR → retournement : SAR = ancien EP, EP = extrême de la barre, AF = step
+pub(crate) fn compute_psar_values(highs: &[f64], lows: &[f64], step: f64, max_step: f64) -> Vec<Option<(f64, PsarTrend)>> {
+    let len = highs.len().min(lows.len());
+    let mut values = vec![None; len];
+    if len < 2 {
+        return values;
+    }
+
+    let rising = highs[1] + lows[1] >= highs[0] + lows[0];
+    let (mut trend, mut sar, mut extreme) = if rising {
+        (PsarTrend::Up, lows[0], highs[0].max(highs[1]))
+    } else {
+        (PsarTrend::Down, highs[0], lows[0].min(lows[1]))
+    };
+    let mut af = step;
+    values[1] = Some((sar, trend));
+
+    for i in 2..len {
+        let mut next = sar + af * (extreme - sar);
+
+        match trend {
+            PsarTrend::Up => {
+                next = next.min(lows[i - 1]).min(lows[i - 2]);
+                if lows[i] < next {
+                    trend = PsarTrend::Down;
+                    next = extreme;
+                    extreme = lows[i];
+                    af = step;
+                } else if highs[i] > extreme {
+                    extreme = highs[i];
+                    af = (af + step).min(max_step);
+                }
+            }
+            PsarTrend::Down => {
+                next = next.max(highs[i - 1]).max(highs[i - 2]);
+                if highs[i] > next {
+                    trend = PsarTrend::Up;
+                    next = extreme;
+                    extreme = highs[i];
+                    af = step;
+                } else if lows[i] < extreme {
+                    extreme = lows[i];
+                    af = (af + step).min(max_step);
+                }
+            }
+        }
+
+        sar = next;
+        values[i] = Some((sar, trend));
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psar_uptrend_then_reversal_flips_sar() {
+        // Hausse régulière puis chute sous le SAR à la dernière barre
+        let highs = [10.0, 11.0, 12.0, 13.0, 14.0, 9.0];
+        let lows = [9.0, 10.0, 11.0, 12.0, 13.0, 8.0];
+
+        let values = compute_psar_values(&highs, &lows, 0.02, 0.2);
+
+        assert_eq!(values[0], None);
+        assert_eq!(values[1], Some((9.0, PsarTrend::Up)));
+        // 9 + 0.02 × (11 - 9) = 9.04, plafonné au low de la barre 0 ; nouvel EP 12 → AF 0.04
+        assert_eq!(values[2], Some((9.0, PsarTrend::Up)));
+        // 9 + 0.04 × (12 - 9)
+        assert!((values[3].unwrap().0 - 9.12).abs() < 1e-9);
+
+        // Toutes les barres haussières gardent le SAR sous les lows
+        for i in 1..5 {
+            let (sar, trend) = values[i].unwrap();
+            assert_eq!(trend, PsarTrend::Up);
+            assert!(sar <= lows[i], "bar {}: sar {} > low {}", i, sar, lows[i]);
+        }
+
+        // Retournement : le SAR passe au-dessus, à l'ancien point extrême (plus haut 14)
+        assert_eq!(values[5], Some((14.0, PsarTrend::Down)));
+    }
+
+    #[test]
+    fn test_psar_downtrend_reversal_and_acceleration_cap() {
+        // Baisse régulière sur 20 barres puis envolée
+        let mut highs: Vec<f64> = (0..20).map(|i| 100.0 - i as f64).collect();
+        let mut lows: Vec<f64> = highs.iter().map(|h| h - 1.0).collect();
+        highs.push(120.0);
+        lows.push(110.0);
+
+        let values = compute_psar_values(&highs, &lows, 0.02, 0.2);
+
+        assert!(values[1..20].iter().all(|v| v.unwrap().1 == PsarTrend::Down));
+        assert!((1..20).all(|i| values[i].unwrap().0 >= highs[i]));
+
+        // Retournement : SAR = plus bas atteint pendant la baisse
+        assert_eq!(values[20], Some((lows[19], PsarTrend::Up)));
+    }
+
+    #[test]
+    fn test_psar_dataframe_columns() {
+        let df = df!(
+            "date" => ["2025-01-02", "2025-01-03", "2025-01-06"],
+            "symbol" => ["AAPL", "AAPL", "AAPL"],
+            "high" => [10.0, 11.0, 12.0],
+            "low" => [9.0, 10.0, 11.0],
+            "close" => [9.5, 10.5, 11.5],
+        ).unwrap();
+
+        let result = PSARCalculator::new(0.02, 0.2).calculate(df.clone(), &df).unwrap();
+
+        let psar: Vec<Option<f64>> = result.column("psar").unwrap().f64().unwrap().into_iter().collect();
+        let trend: Vec<Option<&str>> = result.column("psar_trend").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(psar[..2], [None, Some(9.0)]);
+        assert_eq!(trend, vec![None, Some("up"), Some("up")]);
+    }
+}
@@ -0,0 +1,166 @@
+use serde::Serialize;
+
+/// Plage de valeurs d'une colonne d'indicateur (les valeurs sont stockées en texte dans indicators_rust)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValueRange {
+    Bounded { min: f64, max: f64 },         // Oscillateur borné (ex: RSI 0-100)
+    Price,                                  // Même unité que le close
+    Percent,                                // Variation en %, non bornée
+    Categories { values: &'static [&'static str] },
+    Json,                                   // Objet JSON (point_pivot)
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndicatorColumnSpec {
+    pub name: &'static str, // Nom de la colonne dans indicators_rust
+    pub range: ValueRange,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndicatorParam {
+    pub name: &'static str,
+    pub default: f64,
+}
+
+/// Description d'un indicateur calculé par indicator_service
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct IndicatorSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub columns: &'static [IndicatorColumnSpec],
+    pub params: &'static [IndicatorParam],
+}
+
+const fn column(name: &'static str, range: ValueRange) -> IndicatorColumnSpec {
+    IndicatorColumnSpec { name, range }
+}
+
+const fn param(name: &'static str, default: f64) -> IndicatorParam {
+    IndicatorParam { name, default }
+}
+
+const OSCILLATOR: ValueRange = ValueRange::Bounded { min: 0.0, max: 100.0 };
+
+/// Indicateurs calculés et leurs paramètres par défaut (ceux passés aux calculateurs dans
+/// indicator_service). Toute colonne ajoutée à indicators_rust doit être déclarée ici :
+/// le test de synchronisation avec le modèle échoue sinon.
+pub const INDICATOR_CATALOG: [IndicatorSpec; 10] = [
+    IndicatorSpec {
+        name: "RSI",
+        description: "Relative Strength Index : survente sous 30, surachat au-dessus de 70",
+        columns: &[column("rsi25", OSCILLATOR)],
+        params: &[param("period", 25.0)],
+    },
+    IndicatorSpec {
+        name: "Stochastic",
+        description: "Oscillateur stochastique %K lissé : position du close dans le range high/low",
+        columns: &[column("stochastic14_7_7", OSCILLATOR)],
+        params: &[param("k_period", 14.0), param("k_slowing", 7.0), param("d_period", 7.0)],
+    },
+    IndicatorSpec {
+        name: "EMA",
+        description: "Moyennes mobiles exponentielles du close",
+        columns: &[
+            column("ema20", ValueRange::Price),
+            column("ema50", ValueRange::Price),
+            column("ema200", ValueRange::Price),
+        ],
+        params: &[param("period", 20.0), param("period", 50.0), param("period", 200.0)],
+    },
+    IndicatorSpec {
+        name: "Point Pivot",
+        description: "Pivots Camarilla week / month / year : {\"year\": {\"pivot\", \"s1\"..\"s3\", \"r1\"..\"r3\", \"bars\"}, ...}",
+        columns: &[column("point_pivot", ValueRange::Json)],
+        params: &[param("min_week_points", 2.0), param("min_month_points", 5.0), param("min_year_points", 30.0)],
+    },
+    IndicatorSpec {
+        name: "Keltner Channels",
+        description: "EMA du close ± multiplicateur × ATR",
+        columns: &[
+            column("kc_upper", ValueRange::Price),
+            column("kc_middle", ValueRange::Price),
+            column("kc_lower", ValueRange::Price),
+        ],
+        params: &[param("ema_period", 20.0), param("atr_period", 10.0), param("multiplier", 2.0)],
+    },
+    IndicatorSpec {
+        name: "ROC",
+        description: "Rate of Change : variation du close en % sur la période",
+        columns: &[column("roc12", ValueRange::Percent)],
+        params: &[param("period", 12.0)],
+    },
+    IndicatorSpec {
+        name: "Donchian Channels",
+        description: "Plus haut et plus bas des N dernières barres (barre courante incluse)",
+        columns: &[
+            column("donchian_upper", ValueRange::Price),
+            column("donchian_lower", ValueRange::Price),
+        ],
+        params: &[param("period", 20.0)],
+    },
+    IndicatorSpec {
+        name: "MFI",
+        description: "Money Flow Index : RSI pondéré par le volume",
+        columns: &[column("mfi14", OSCILLATOR)],
+        params: &[param("period", 14.0)],
+    },
+    IndicatorSpec {
+        name: "Parabolic SAR",
+        description: "Stop and Reverse de Wilder et sens de la tendance",
+        columns: &[
+            column("psar", ValueRange::Price),
+            column("psar_trend", ValueRange::Categories { values: &["up", "down"] }),
+        ],
+        params: &[param("step", 0.02), param("max_step", 0.2)],
+    },
+    IndicatorSpec {
+        name: "TRIX",
+        description: "Variation en % d'une EMA appliquée trois fois au close (momentum lissé)",
+        columns: &[column("trix15", ValueRange::Percent)],
+        params: &[param("period", 15.0)],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::indicator::Column as IndicatorColumn;
+    use sea_orm::{Iden, Iterable};
+    use std::collections::BTreeSet;
+
+    fn spec(name: &str) -> &'static IndicatorSpec {
+        INDICATOR_CATALOG.iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_rsi_and_ema_metadata() {
+        let rsi = spec("RSI");
+        assert_eq!(rsi.columns[0].name, "rsi25");
+        assert_eq!(rsi.columns[0].range, ValueRange::Bounded { min: 0.0, max: 100.0 });
+        assert_eq!((rsi.params[0].name, rsi.params[0].default), ("period", 25.0));
+
+        let ema = spec("EMA");
+        let columns: Vec<&str> = ema.columns.iter().map(|c| c.name).collect();
+        assert_eq!(columns, vec!["ema20", "ema50", "ema200"]);
+        assert!(ema.columns.iter().all(|c| c.range == ValueRange::Price));
+        let periods: Vec<f64> = ema.params.iter().map(|p| p.default).collect();
+        assert_eq!(periods, vec![20.0, 50.0, 200.0]);
+
+        assert_eq!(
+            serde_json::to_value(rsi.columns[0]).unwrap(),
+            serde_json::json!({"name": "rsi25", "range": {"kind": "bounded", "min": 0.0, "max": 100.0}})
+        );
+    }
+
+    #[test]
+    fn test_catalog_covers_every_indicator_column() {
+        let catalog: BTreeSet<&str> = INDICATOR_CATALOG.iter().flat_map(|s| s.columns.iter().map(|c| c.name)).collect();
+        let table: BTreeSet<String> = IndicatorColumn::iter()
+            .map(|c| c.to_string())
+            .filter(|c| c != "date" && c != "symbol")
+            .collect();
+
+        assert_eq!(catalog.into_iter().map(String::from).collect::<BTreeSet<String>>(), table);
+    }
+}
@@ -0,0 +1,137 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+pub struct ROCCalculator {
+    period: usize, // 12 par défaut
+}
+
+impl ROCCalculator {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating ROC({}) for {} rows", self.period, df_new.height());
+
+        // 1. Grouper df_full par symbole
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("ROC: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Calculer le ROC pour chaque symbole
+        let mut roc_results: HashMap<(String, String), f64> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, closes_with_dates) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("ROC: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            let closes: Vec<f64> = closes_with_dates.iter().map(|(_, close)| *close).collect();
+            let roc_values = compute_roc_values(&closes, self.period);
+
+            for (i, roc) in roc_values.iter().enumerate() {
+                if let Some(roc_val) = roc {
+                    let date = &closes_with_dates[i].0;
+                    roc_results.insert((symbol.clone(), date.clone()), *roc_val);
+                }
+            }
+        }
+
+        info!("ROC: Calculated {} values", roc_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut rocs = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let roc = roc_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            rocs.push(roc);
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new(format!("roc{}", self.period).into(), rocs)),
+        ])?;
+
+        info!("ROC: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole et retourne HashMap<symbol, Vec<(date, close)>>
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<(String, f64)>>, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let close_col = df.column("close")?;
+
+        let mut grouped: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
+
+            grouped.entry(symbol).or_default().push((date, close));
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// ROC = 100 × (close - close il y a 'period' jours) / close il y a 'period' jours
+/// None pendant la période de chauffe et si le close de référence est nul
+pub(crate) fn compute_roc_values(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    closes
+        .iter()
+        .enumerate()
+        .map(|(i, close)| {
+            if period == 0 || i < period {
+                return None;
+            }
+            let previous = closes[i - period];
+            if previous == 0.0 {
+                return None;
+            }
+            Some(100.0 * (close - previous) / previous)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roc_warm_up_and_values() {
+        let roc = compute_roc_values(&[10.0, 11.0, 12.0, 9.0], 2);
+
+        assert_eq!(roc[0], None);
+        assert_eq!(roc[1], None);
+        assert!((roc[2].unwrap() - 20.0).abs() < 1e-9); // 10 → 12
+        assert!((roc[3].unwrap() + 18.181818).abs() < 1e-5); // 11 → 9
+    }
+
+    #[test]
+    fn test_roc_zero_prior_close_is_none() {
+        let roc = compute_roc_values(&[0.0, 5.0, 6.0], 1);
+
+        assert_eq!(roc, vec![None, None, Some(20.0)]);
+    }
+}
@@ -1,5 +1,6 @@
 use polars::prelude::*;
 use std::collections::HashMap;
+use tracing::{debug, info};
 
 pub struct RSICalculator {
     period: usize,
@@ -15,12 +16,12 @@ impl RSICalculator {
         df_new: DataFrame,
         df_full: &DataFrame,
     ) -> Result<DataFrame, PolarsError> {
-        println!("🔄 Calculating RSI for {} rows", df_new.height());
+        info!("Calculating RSI for {} rows", df_new.height());
 
         // 1. Grouper df_full par symbole (une seule fois)
         let grouped_full = self.group_by_symbol(df_full)?;
 
-        println!("📊 RSI: Grouped {} unique symbols", grouped_full.len());
+        info!("RSI: Grouped {} unique symbols", grouped_full.len());
 
         // 2. Calculer RSI pour chaque symbole
         let mut rsi_results: HashMap<(String, String), f64> = HashMap::new();
@@ -30,7 +31,7 @@ impl RSICalculator {
 
         for (symbol, closes_with_dates) in grouped_full.iter() {
             symbol_idx += 1;
-            println!("📊 RSI: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+            debug!("RSI: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
 
             // Calculer RSI pour ce symbole
             for i in 0..closes_with_dates.len() {
@@ -46,7 +47,7 @@ impl RSICalculator {
             }
         }
 
-        println!("✅ RSI: Calculated {} values", rsi_results.len());
+        info!("RSI: Calculated {} values", rsi_results.len());
 
         // 3. Construire le DataFrame résultat avec seulement df_new
         let date_col = df_new.column("date")?;
@@ -78,7 +79,7 @@ impl RSICalculator {
             Column::Series(Series::new("rsi25".into(), rsis)),
         ])?;
 
-        println!("✅ RSI: Result DataFrame has {} rows", result.height());
+        info!("RSI: Result DataFrame has {} rows", result.height());
         Ok(result)
     }
 
@@ -95,7 +96,7 @@ impl RSICalculator {
             let symbol = symbol_col.get(i)?.to_string();
             let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
 
-            grouped.entry(symbol).or_insert_with(Vec::new).push((date, close));
+            grouped.entry(symbol).or_default().push((date, close));
         }
 
         Ok(grouped)
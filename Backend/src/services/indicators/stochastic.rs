@@ -1,5 +1,8 @@
 use polars::prelude::*;
 use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::services::indicators::keltner::HlcBar;
 
 pub struct StochasticCalculator {
     k_period: usize,      // 14 pour le min/max
@@ -21,12 +24,12 @@ impl StochasticCalculator {
         df_new: DataFrame,
         df_full: &DataFrame,
     ) -> Result<DataFrame, PolarsError> {
-        println!("🔄 Calculating Stochastic for {} rows", df_new.height());
+        info!("Calculating Stochastic for {} rows", df_new.height());
 
         // 1. Grouper df_full par symbole
         let grouped_full = self.group_by_symbol(df_full)?;
 
-        println!("📊 STOCHASTIC: Grouped {} unique symbols", grouped_full.len());
+        info!("STOCHASTIC: Grouped {} unique symbols", grouped_full.len());
 
         // 2. Calculer Stochastic pour chaque symbole
         let mut stoch_results: HashMap<(String, String), f64> = HashMap::new();
@@ -36,7 +39,7 @@ impl StochasticCalculator {
 
         for (symbol, data) in grouped_full.iter() {
             symbol_idx += 1;
-            println!("📊 STOCHASTIC: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+            debug!("STOCHASTIC: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
 
             // Calculer Stochastic pour ce symbole
             for i in 0..data.len() {
@@ -70,7 +73,7 @@ impl StochasticCalculator {
             }
         }
 
-        println!("✅ STOCHASTIC: Calculated {} values", stoch_results.len());
+        info!("STOCHASTIC: Calculated {} values", stoch_results.len());
 
         // 3. Construire le DataFrame résultat avec seulement df_new
         let date_col = df_new.column("date")?;
@@ -97,19 +100,19 @@ impl StochasticCalculator {
             Column::Series(Series::new("stochastic14_7_7".into(), stochs)),
         ])?;
 
-        println!("✅ STOCHASTIC: Result DataFrame has {} rows", result.height());
+        info!("STOCHASTIC: Result DataFrame has {} rows", result.height());
         Ok(result)
     }
 
     /// Groupe df par symbole et retourne HashMap<symbol, Vec<(date, high, low, close)>>
-    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<(String, f64, f64, f64)>>, PolarsError> {
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<HlcBar>>, PolarsError> {
         let date_col = df.column("date")?;
         let symbol_col = df.column("symbol")?;
         let high_col = df.column("high")?;
         let low_col = df.column("low")?;
         let close_col = df.column("close")?;
 
-        let mut grouped: HashMap<String, Vec<(String, f64, f64, f64)>> = HashMap::new();
+        let mut grouped: HashMap<String, Vec<HlcBar>> = HashMap::new();
 
         for i in 0..df.height() {
             let date = date_col.get(i)?.to_string();
@@ -118,7 +121,7 @@ impl StochasticCalculator {
             let low = if let AnyValue::Float64(v) = low_col.get(i)? { v } else { continue };
             let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
 
-            grouped.entry(symbol).or_insert_with(Vec::new).push((date, high, low, close));
+            grouped.entry(symbol).or_default().push((date, high, low, close));
         }
 
         Ok(grouped)
@@ -126,7 +129,7 @@ impl StochasticCalculator {
 
     /// Calcule le Fast %K pour une window donnée
     /// Fast %K = 100 * (close - lowest_low) / (highest_high - lowest_low)
-    fn compute_fast_k(&self, window: &[(String, f64, f64, f64)]) -> Option<f64> {
+    fn compute_fast_k(&self, window: &[HlcBar]) -> Option<f64> {
         if window.is_empty() {
             return None;
         }
@@ -0,0 +1,170 @@
+use polars::prelude::*;
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::services::indicators::ema::compute_ema_values;
+
+pub struct TRIXCalculator {
+    period: usize, // 15 par défaut
+}
+
+impl TRIXCalculator {
+    pub fn new(period: usize) -> Self {
+        Self { period }
+    }
+
+    pub fn calculate(
+        &self,
+        df_new: DataFrame,
+        df_full: &DataFrame,
+    ) -> Result<DataFrame, PolarsError> {
+        info!("Calculating TRIX({}) for {} rows", self.period, df_new.height());
+
+        // 1. Grouper df_full par symbole
+        let grouped_full = self.group_by_symbol(df_full)?;
+
+        info!("TRIX: Grouped {} unique symbols", grouped_full.len());
+
+        // 2. Calculer le TRIX pour chaque symbole
+        let mut trix_results: HashMap<(String, String), f64> = HashMap::new();
+
+        let mut symbol_idx = 0;
+        let total_symbols = grouped_full.len();
+
+        for (symbol, closes_with_dates) in grouped_full.iter() {
+            symbol_idx += 1;
+            debug!("TRIX: Processing symbol {}/{}: {}", symbol_idx, total_symbols, symbol);
+
+            let closes: Vec<f64> = closes_with_dates.iter().map(|(_, close)| *close).collect();
+            let trix_values = compute_trix_values(&closes, self.period);
+
+            for (i, trix) in trix_values.iter().enumerate() {
+                if let Some(trix_val) = trix {
+                    let date = &closes_with_dates[i].0;
+                    trix_results.insert((symbol.clone(), date.clone()), *trix_val);
+                }
+            }
+        }
+
+        info!("TRIX: Calculated {} values", trix_results.len());
+
+        // 3. Construire le DataFrame résultat avec seulement df_new
+        let date_col = df_new.column("date")?;
+        let symbol_col = df_new.column("symbol")?;
+
+        let mut dates = Vec::new();
+        let mut symbols = Vec::new();
+        let mut trixs = Vec::new();
+
+        for i in 0..df_new.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+
+            let trix = trix_results.get(&(symbol.clone(), date.clone())).copied();
+
+            dates.push(date);
+            symbols.push(symbol);
+            trixs.push(trix);
+        }
+
+        let result = DataFrame::new(vec![
+            Column::Series(Series::new("date".into(), dates)),
+            Column::Series(Series::new("symbol".into(), symbols)),
+            Column::Series(Series::new(format!("trix{}", self.period).into(), trixs)),
+        ])?;
+
+        info!("TRIX: Result DataFrame has {} rows", result.height());
+        Ok(result)
+    }
+
+    /// Groupe df par symbole et retourne HashMap<symbol, Vec<(date, close)>>
+    fn group_by_symbol(&self, df: &DataFrame) -> Result<HashMap<String, Vec<(String, f64)>>, PolarsError> {
+        let date_col = df.column("date")?;
+        let symbol_col = df.column("symbol")?;
+        let close_col = df.column("close")?;
+
+        let mut grouped: HashMap<String, Vec<(String, f64)>> = HashMap::new();
+
+        for i in 0..df.height() {
+            let date = date_col.get(i)?.to_string();
+            let symbol = symbol_col.get(i)?.to_string();
+            let close = if let AnyValue::Float64(v) = close_col.get(i)? { v } else { continue };
+
+            grouped.entry(symbol).or_default().push((date, close));
+        }
+
+        Ok(grouped)
+    }
+}
+
+/// TRIX = 100 × (EMA3 - EMA3 de la veille) / EMA3 de la veille, où EMA3 = EMA(EMA(EMA(close)))
+/// Chaque passe d'EMA démarre à la première valeur définie de la précédente : None pendant
+/// les 3 × (period - 1) + 1 premières barres, et si l'EMA3 de la veille est nulle
+pub(crate) fn compute_trix_values(closes: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut trix = vec![None; closes.len()];
+    if period == 0 {
+        return trix;
+    }
+
+    let mut offset = 0; // Index dans closes de la première valeur de `smoothed`
+    let mut smoothed = closes.to_vec();
+    for _ in 0..3 {
+        let ema = compute_ema_values(&smoothed, period);
+        let Some(first) = ema.iter().position(Option::is_some) else {
+            return trix;
+        };
+        offset += first;
+        smoothed = ema[first..].iter().flatten().copied().collect();
+    }
+
+    for (i, pair) in smoothed.windows(2).enumerate() {
+        if pair[0] != 0.0 {
+            trix[offset + i + 1] = Some(100.0 * (pair[1] - pair[0]) / pair[0]);
+        }
+    }
+
+    trix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trix_of_period_one_is_one_day_rate_of_change() {
+        // EMA(1) = close : TRIX(1) = ROC(1)
+        let trix = compute_trix_values(&[10.0, 11.0, 12.0], 1);
+
+        assert_eq!(trix[0], None);
+        assert!((trix[1].unwrap() - 10.0).abs() < 1e-9);
+        assert!((trix[2].unwrap() - 9.090909).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_trix_crosses_zero_when_trend_reverses() {
+        // 60 barres de hausse (100 → 159) puis 60 barres de baisse
+        let mut closes: Vec<f64> = (0..60).map(|i| 100.0 + i as f64).collect();
+        closes.extend((1..=60).map(|i| 159.0 - i as f64));
+
+        let trix = compute_trix_values(&closes, 15);
+
+        // Chauffe : 3 × 14 barres pour la triple EMA, + 1 pour la variation
+        assert!(trix[..43].iter().all(Option::is_none));
+        assert!(trix[43..].iter().all(Option::is_some));
+
+        let values: Vec<f64> = trix[43..].iter().flatten().copied().collect();
+        assert!(values[..60 - 43].iter().all(|v| *v > 0.0)); // Hausse : TRIX positif
+        assert!(*values.last().unwrap() < 0.0);              // Baisse : TRIX négatif
+
+        // Un seul passage sous zéro, après le sommet (la triple EMA retarde le signal)
+        let crossings: Vec<usize> = values.windows(2).enumerate().filter(|(_, w)| w[0] > 0.0 && w[1] <= 0.0).map(|(i, _)| i + 1 + 43).collect();
+        assert_eq!(crossings.len(), 1);
+        assert!(crossings[0] > 59, "crossed at {}", crossings[0]);
+        assert!(values.windows(2).all(|w| !(w[0] < 0.0 && w[1] > 0.0)));
+    }
+
+    #[test]
+    fn test_short_series_stays_in_warm_up() {
+        assert!(compute_trix_values(&[100.0; 40], 15).iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,202 @@
+// ============================================================================
+// SERVICE : VERROUILLAGE DES COMPTES APRÈS ÉCHECS DE LOGIN
+// ============================================================================
+//
+// Description:
+//   Compte les échecs de login par username dans login_attempts_rust et
+//   verrouille le compte après LOGIN_MAX_ATTEMPTS échecs consécutifs pendant
+//   LOGIN_LOCKOUT_MINUTES. L'état est en BD : un redémarrage ne le réinitialise pas.
+//
+// Points d'attention:
+//   - Le compteur repart de zéro si le dernier échec date de plus d'une fenêtre
+//   - Un login réussi supprime la ligne
+//   - purge_stale() supprime les lignes expirées (appelé périodiquement par main)
+//
+// ============================================================================
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, ColumnTrait, Set, ActiveModelTrait, Condition};
+use chrono::{Duration, NaiveDateTime, Utc};
+
+use crate::models::login_attempts::{self, Entity as LoginAttempt};
+
+/// Nombre d'échecs consécutifs avant verrouillage (défaut)
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+/// Durée du verrouillage et de la fenêtre de comptage (défaut, en minutes)
+pub const DEFAULT_LOCKOUT_MINUTES: i64 = 15;
+
+pub struct LoginAttemptService {
+    max_attempts: i32,
+    lockout: Duration,
+}
+
+impl LoginAttemptService {
+    pub fn new(max_attempts: i32, lockout_minutes: i64) -> Self {
+        Self {
+            max_attempts,
+            lockout: Duration::minutes(lockout_minutes),
+        }
+    }
+
+    /// Configuration via LOGIN_MAX_ATTEMPTS et LOGIN_LOCKOUT_MINUTES
+    pub fn from_env() -> Self {
+        let env_or = |key: &str, default: i64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+
+        Self::new(
+            env_or("LOGIN_MAX_ATTEMPTS", DEFAULT_MAX_ATTEMPTS as i64) as i32,
+            env_or("LOGIN_LOCKOUT_MINUTES", DEFAULT_LOCKOUT_MINUTES),
+        )
+    }
+
+    pub fn lockout_window(&self) -> Duration {
+        self.lockout
+    }
+
+    /// Fin du verrouillage si le compte est verrouillé à `now`
+    pub fn locked_until(&self, attempt: Option<&login_attempts::Model>, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        attempt
+            .and_then(|a| a.locked_until)
+            .filter(|until| *until > now)
+    }
+
+    /// Nouvel état après un échec : incrémente (ou repart de 1 si la fenêtre est passée)
+    /// et verrouille dès que max_attempts est atteint
+    pub fn next_failure(
+        &self,
+        username: &str,
+        attempt: Option<&login_attempts::Model>,
+        now: NaiveDateTime,
+    ) -> login_attempts::Model {
+        let previous_failures = attempt
+            .filter(|a| now - a.last_failed_at < self.lockout)
+            .filter(|a| a.locked_until.is_none_or(|until| until > now))
+            .map_or(0, |a| a.failed_attempts);
+
+        let failed_attempts = previous_failures + 1;
+        let locked_until = (failed_attempts >= self.max_attempts).then(|| now + self.lockout);
+
+        login_attempts::Model {
+            username: username.to_string(),
+            failed_attempts,
+            locked_until,
+            last_failed_at: now,
+        }
+    }
+
+    pub async fn find(db: &DatabaseConnection, username: &str) -> Result<Option<login_attempts::Model>, DbErr> {
+        LoginAttempt::find_by_id(username.to_string()).one(db).await
+    }
+
+    /// Vérifie en BD si le username est verrouillé
+    pub async fn check_locked(&self, db: &DatabaseConnection, username: &str) -> Result<Option<NaiveDateTime>, DbErr> {
+        let attempt = Self::find(db, username).await?;
+        Ok(self.locked_until(attempt.as_ref(), Utc::now().naive_utc()))
+    }
+
+    /// Enregistre un échec de login, retourne l'état mis à jour
+    pub async fn record_failure(&self, db: &DatabaseConnection, username: &str) -> Result<login_attempts::Model, DbErr> {
+        let existing = Self::find(db, username).await?;
+        let next = self.next_failure(username, existing.as_ref(), Utc::now().naive_utc());
+
+        match existing {
+            Some(model) => {
+                let mut active: login_attempts::ActiveModel = model.into();
+                active.failed_attempts = Set(next.failed_attempts);
+                active.locked_until = Set(next.locked_until);
+                active.last_failed_at = Set(next.last_failed_at);
+                active.update(db).await
+            }
+            None => {
+                let active = login_attempts::ActiveModel {
+                    username: Set(next.username),
+                    failed_attempts: Set(next.failed_attempts),
+                    locked_until: Set(next.locked_until),
+                    last_failed_at: Set(next.last_failed_at),
+                };
+                active.insert(db).await
+            }
+        }
+    }
+
+    /// Login réussi : efface le compteur
+    pub async fn record_success(db: &DatabaseConnection, username: &str) -> Result<(), DbErr> {
+        LoginAttempt::delete_by_id(username.to_string()).exec(db).await?;
+        Ok(())
+    }
+
+    /// Supprime les lignes dont le dernier échec et le verrouillage sont plus vieux que la fenêtre
+    pub async fn purge_stale(&self, db: &DatabaseConnection) -> Result<u64, DbErr> {
+        let now = Utc::now().naive_utc();
+        let cutoff = now - self.lockout;
+
+        let result = LoginAttempt::delete_many()
+            .filter(login_attempts::Column::LastFailedAt.lt(cutoff))
+            .filter(
+                Condition::any()
+                    .add(login_attempts::Column::LockedUntil.is_null())
+                    .add(login_attempts::Column::LockedUntil.lt(now)),
+            )
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(12, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_locks_after_max_attempts() {
+        let service = LoginAttemptService::new(3, 15);
+
+        let mut row = None;
+        for minute in 0..3 {
+            row = Some(service.next_failure("alice", row.as_ref(), at(minute)));
+        }
+
+        let row = row.unwrap();
+        assert_eq!(row.failed_attempts, 3);
+        assert_eq!(service.locked_until(Some(&row), at(3)), Some(at(17)));
+    }
+
+    #[test]
+    fn test_lockout_survives_restart() {
+        // Instance 1 : 5 échecs, la ligne persistée est tout ce qui reste après le "crash"
+        let persisted = {
+            let service = LoginAttemptService::new(5, 15);
+            let mut row = None;
+            for minute in 0..5 {
+                row = Some(service.next_failure("alice", row.as_ref(), at(minute)));
+            }
+            row.unwrap()
+        };
+
+        // Instance 2 (après redémarrage) : relit la même ligne
+        let restarted = LoginAttemptService::new(5, 15);
+        assert!(restarted.locked_until(Some(&persisted), at(10)).is_some());
+        assert!(restarted.locked_until(Some(&persisted), at(20)).is_none());
+    }
+
+    #[test]
+    fn test_counter_resets_after_window() {
+        let service = LoginAttemptService::new(5, 15);
+
+        let first = service.next_failure("alice", None, at(0));
+        let second = service.next_failure("alice", Some(&first), at(30));
+
+        assert_eq!(second.failed_attempts, 1);
+        assert!(second.locked_until.is_none());
+    }
+}
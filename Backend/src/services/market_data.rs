@@ -0,0 +1,286 @@
+// Source des données de marché lues par les stratégies (barres OHLCV, min / max sur une période).
+// Les stratégies dépendent du trait MarketDataSource plutôt que de historicdata ou d'une stored
+// procedure : DbMarketData est l'implémentation réelle, un test peut injecter une source en mémoire.
+//
+// Seule `bars` est obligatoire : les autres méthodes ont une implémentation par défaut construite
+// dessus (suffisante pour une source en mémoire), que DbMarketData remplace par des requêtes ciblées.
+
+use async_trait::async_trait;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+use crate::services::historic_bars::{self, HistoricBar};
+
+/// Date au-delà de toute barre (borne haute ouverte de `bars`)
+const END_OF_TIME: &str = "9999-12-31";
+
+/// Min/max/dernier close d'un symbole depuis une date
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceRange {
+    pub symbol: String,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub current_price: Option<f64>,
+    /// Date du current_price (dernier close connu)
+    pub latest_date: Option<String>,
+}
+
+#[async_trait]
+pub trait MarketDataSource: Send + Sync {
+    /// Barres valides d'un symbole entre `from` et `to` (inclus), par date croissante
+    async fn bars(&self, symbol: &str, from: &str, to: &str) -> Result<Vec<HistoricBar>, String>;
+
+    /// Dernière barre valide d'un symbole (close et date)
+    #[allow(dead_code)]
+    async fn latest_close(&self, symbol: &str) -> Result<Option<HistoricBar>, String> {
+        Ok(self.bars(symbol, "", END_OF_TIME).await?.pop())
+    }
+
+    /// Barre d'un symbole à une date précise
+    async fn bar_on(&self, symbol: &str, date: &str) -> Result<Option<HistoricBar>, String> {
+        Ok(self.bars(symbol, date, date).await?.pop())
+    }
+
+    /// Les `limit` dernières barres jusqu'à `date` (incluse), par date croissante
+    async fn bars_up_to(&self, symbol: &str, date: &str, limit: u64) -> Result<Vec<HistoricBar>, String> {
+        let mut bars = self.bars(symbol, "", date).await?;
+        let keep = bars.len().saturating_sub(limit as usize);
+        Ok(bars.split_off(keep))
+    }
+
+    /// Min / max du close depuis `from` (inclus) et dernier close, pour chaque symbole ayant des barres
+    async fn min_max(&self, symbols: &[String], from: &str) -> Result<Vec<PriceRange>, String> {
+        let mut bars = Vec::new();
+        for symbol in symbols {
+            bars.extend(self.bars(symbol, from, END_OF_TIME).await?);
+        }
+        Ok(compute_price_ranges(&bars, from))
+    }
+}
+
+/// Même sémantique que get_min_max_prices_last_year : min/max du close depuis `cutoff_date`
+/// (inclus) et current_price = close de la date la plus récente, par symbole.
+/// Les lignes invalides ont déjà été écartées par historic_bars::parse_bars.
+pub fn compute_price_ranges(historical_data: &[HistoricBar], cutoff_date: &str) -> Vec<PriceRange> {
+    // symbol -> (min, max, (date la plus récente, close))
+    let mut by_symbol: BTreeMap<&str, (f64, f64, (&str, f64))> = BTreeMap::new();
+
+    for row in historical_data {
+        if row.date.as_str() < cutoff_date {
+            continue;
+        }
+
+        let close = row.close;
+
+        by_symbol
+            .entry(row.symbol.as_str())
+            .and_modify(|(min, max, latest)| {
+                *min = min.min(close);
+                *max = max.max(close);
+                if row.date.as_str() > latest.0 {
+                    *latest = (row.date.as_str(), close);
+                }
+            })
+            .or_insert((close, close, (row.date.as_str(), close)));
+    }
+
+    by_symbol
+        .into_iter()
+        .map(|(symbol, (min_price, max_price, (latest_date, current)))| PriceRange {
+            symbol: symbol.to_string(),
+            min_price,
+            max_price,
+            current_price: Some(current),
+            latest_date: Some(latest_date.to_string()),
+        })
+        .collect()
+}
+
+/// Source réelle : historicdata via SeaORM, min / max via la stored procedure Postgres si installée
+pub struct DbMarketData<'a> {
+    db: &'a DatabaseConnection,
+}
+
+impl<'a> DbMarketData<'a> {
+    pub fn new(db: &'a DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl MarketDataSource for DbMarketData<'_> {
+    async fn bars(&self, symbol: &str, from: &str, to: &str) -> Result<Vec<HistoricBar>, String> {
+        historic_bars::fetch_bars_between(symbol, from, to, self.db).await
+    }
+
+    async fn latest_close(&self, symbol: &str) -> Result<Option<HistoricBar>, String> {
+        historic_bars::fetch_latest_bar(symbol, self.db).await
+    }
+
+    async fn bar_on(&self, symbol: &str, date: &str) -> Result<Option<HistoricBar>, String> {
+        historic_bars::fetch_bar_on(symbol, date, self.db).await
+    }
+
+    async fn bars_up_to(&self, symbol: &str, date: &str, limit: u64) -> Result<Vec<HistoricBar>, String> {
+        historic_bars::fetch_bars_up_to(symbol, date, limit, self.db).await
+    }
+
+    async fn min_max(&self, symbols: &[String], from: &str) -> Result<Vec<PriceRange>, String> {
+        // Stored procedure PostgreSQL (fallback SeaORM si elle n'est pas installée)
+        match fetch_ranges_from_procedure(from, self.db).await {
+            Ok(ranges) => {
+                // La procédure couvre tous les symboles et ne retourne pas la date du current_price
+                let latest_dates = fetch_latest_dates(symbols, self.db).await?;
+                Ok(ranges
+                    .into_iter()
+                    .filter(|range| symbols.contains(&range.symbol))
+                    .map(|range| PriceRange { latest_date: latest_dates.get(&range.symbol).cloned(), ..range })
+                    .collect())
+            }
+            Err(e) if is_undefined_function(&e) => {
+                warn!("Stored procedure get_min_max_prices_last_year not found, using SeaORM fallback");
+                let bars = historic_bars::fetch_bars_since(symbols, from, self.db).await?;
+                Ok(compute_price_ranges(&bars, from))
+            }
+            Err(e) => Err(format!("SQL stored procedure error: {}", e)),
+        }
+    }
+}
+
+async fn fetch_ranges_from_procedure(
+    cutoff_date: &str,
+    db: &DatabaseConnection,
+) -> Result<Vec<PriceRange>, sqlx::Error> {
+    let pool = db.get_postgres_connection_pool();
+    let rows = sqlx::query("SELECT * FROM get_min_max_prices_last_year($1)")
+        .bind(cutoff_date)
+        .fetch_all(pool)
+        .await?;
+
+    let mut ranges = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        ranges.push(PriceRange {
+            symbol: row.try_get("symbol")?,
+            min_price: row.try_get("min_price")?,
+            max_price: row.try_get("max_price")?,
+            current_price: row.try_get("current_price").ok(),
+            latest_date: None,
+        });
+    }
+
+    Ok(ranges)
+}
+
+/// Dernière date historicdata par symbole (fraîcheur du current_price)
+async fn fetch_latest_dates(
+    symbols: &[String],
+    db: &DatabaseConnection,
+) -> Result<HashMap<String, String>, String> {
+    let rows = HistoricData::find()
+        .select_only()
+        .column(historic_data::Column::Symbol)
+        .column_as(historic_data::Column::Date.max(), "latest_date")
+        .filter(historic_data::Column::Symbol.is_in(symbols.iter().map(|s| s.as_str())))
+        .group_by(historic_data::Column::Symbol)
+        .into_tuple::<(String, Option<String>)>()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch latest historicdata dates: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(symbol, date)| date.map(|d| (symbol, d)))
+        .collect())
+}
+
+/// Code Postgres 42883 (undefined_function) : la stored procedure n'existe pas sur cette BD
+fn is_undefined_function(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "42883")
+}
+
+/// Source en mémoire pour les tests : barres fournies telles quelles, triées par date
+#[cfg(test)]
+pub struct InMemoryMarketData {
+    pub bars: Vec<HistoricBar>,
+}
+
+#[cfg(test)]
+impl InMemoryMarketData {
+    /// Barres (symbole, date, close) avec open = high = low = close
+    pub fn from_closes(rows: &[(&str, &str, f64)]) -> Self {
+        let mut bars: Vec<HistoricBar> = rows
+            .iter()
+            .map(|(symbol, date, close)| HistoricBar {
+                symbol: symbol.to_string(),
+                date: date.to_string(),
+                open: *close,
+                high: *close,
+                low: *close,
+                close: *close,
+                adjusted_close: None,
+                volume: None,
+            })
+            .collect();
+        bars.sort_by(|a, b| a.date.cmp(&b.date));
+        Self { bars }
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl MarketDataSource for InMemoryMarketData {
+    async fn bars(&self, symbol: &str, from: &str, to: &str) -> Result<Vec<HistoricBar>, String> {
+        Ok(self
+            .bars
+            .iter()
+            .filter(|bar| bar.symbol == symbol && bar.date.as_str() >= from && bar.date.as_str() <= to)
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> InMemoryMarketData {
+        InMemoryMarketData::from_closes(&[
+            ("AAPL", "2025-06-03", 120.0),
+            ("AAPL", "2025-06-02", 110.0),
+            ("AAPL", "2025-05-01", 90.0),
+            ("AAPL", "2024-01-02", 500.0),
+            ("SHOP", "2025-06-02", 80.0),
+        ])
+    }
+
+    #[tokio::test]
+    async fn default_methods_derive_from_bars() {
+        let data = source();
+
+        assert_eq!(data.latest_close("AAPL").await.unwrap().map(|b| b.close), Some(120.0));
+        assert_eq!(data.bar_on("AAPL", "2025-06-02").await.unwrap().map(|b| b.close), Some(110.0));
+        assert_eq!(data.bar_on("AAPL", "2025-06-01").await.unwrap(), None);
+
+        let closes: Vec<f64> = data.bars_up_to("AAPL", "2025-06-02", 2).await.unwrap().iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![90.0, 110.0]);
+        assert_eq!(data.latest_close("MSFT").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn min_max_ignores_bars_before_cutoff() {
+        let symbols = vec!["AAPL".to_string(), "SHOP".to_string(), "MSFT".to_string()];
+        let ranges = source().min_max(&symbols, "2025-01-01").await.unwrap();
+
+        assert_eq!(ranges, vec![
+            PriceRange { symbol: "AAPL".into(), min_price: 90.0, max_price: 120.0, current_price: Some(120.0), latest_date: Some("2025-06-03".into()) },
+            PriceRange { symbol: "SHOP".into(), min_price: 80.0, max_price: 80.0, current_price: Some(80.0), latest_date: Some("2025-06-02".into()) },
+        ]);
+    }
+}
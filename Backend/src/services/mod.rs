@@ -1,6 +1,27 @@
 pub mod indicators;
 pub mod indicator_service;
+pub mod indicator_bench;
 pub mod strategies;
 pub mod strategy_service;
 pub mod trade_service;
-pub mod wallet_service;
\ No newline at end of file
+pub mod wallet_service;
+pub mod data_quality;
+pub mod risk_service;
+pub mod login_attempt_service;
+pub mod subscription_service;
+pub mod emergency_stop_service;
+pub mod audit_service;
+pub mod shutdown;
+pub mod analytics_service;
+pub mod historic_bars;
+pub mod token_cleanup_service;
+pub mod calculation_lock;
+pub mod strategy_run_service;
+pub mod calculation_jobs;
+pub mod data_import;
+pub mod auth_audit_service;
+pub mod stock_onboarding;
+pub mod digest_service;
+pub mod watchlist_service;
+pub mod market_data;
+pub mod api_key_service;
\ No newline at end of file
@@ -0,0 +1,207 @@
+// ============================================================================
+// SERVICE : GESTION DU RISQUE
+// ============================================================================
+//
+// Description:
+//   Calculs de protection des positions ouvertes : trailing stop basé sur le
+//   plus haut close depuis l'entrée en position, et support / résistance pivot
+//   les plus proches du dernier close (pour placer un stop ou un objectif).
+//
+// Points d'attention:
+//   - Le stop ne descend jamais sous entry_price × (1 - trail_pct) : tant que
+//     le prix n'a pas dépassé l'entrée, c'est un stop-loss fixe
+//   - Le plus haut close vient de historicdata (dates YYYY-MM-DD), entre la
+//     date d'entrée et aujourd'hui
+//   - Supports / résistances : tous les niveaux (pivot, S1-S3, R1-R3) de toutes
+//     les périodes (week, month, year) du dernier point_pivot calculé
+//
+// ============================================================================
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, ColumnTrait};
+use chrono::{Local, NaiveDate};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+use crate::models::indicator::{Column as IndicatorColumn, Entity as Indicator};
+use crate::utils::money;
+
+/// Trailing stop par défaut : 10% sous le plus haut close
+pub const DEFAULT_TRAIL_PCT: Decimal = Decimal::TEN;
+
+/// Pourcentage configuré via TRAILING_STOP_PCT (défaut: 10)
+pub fn trail_pct_from_env() -> Decimal {
+    std::env::var("TRAILING_STOP_PCT")
+        .ok()
+        .and_then(|v| Decimal::from_str(v.trim()).ok())
+        .filter(|v| *v > Decimal::ZERO && *v < Decimal::ONE_HUNDRED)
+        .unwrap_or(DEFAULT_TRAIL_PCT)
+}
+
+/// Niveau du trailing stop : trail_pct % sous le plus haut atteint depuis l'entrée
+/// (le prix d'entrée sert de plancher si aucun close n'a dépassé l'entrée)
+pub fn trailing_stop(
+    entry_price: Decimal,
+    highest_close_since_entry: Option<Decimal>,
+    trail_pct: Decimal,
+) -> Decimal {
+    let reference = highest_close_since_entry
+        .map_or(entry_price, |highest| highest.max(entry_price));
+
+    money::round_money(reference * (Decimal::ONE - trail_pct / Decimal::ONE_HUNDRED))
+}
+
+/// Le stop est déclenché si le dernier close est au niveau du stop ou en dessous
+pub fn is_stop_breached(stop: Decimal, latest_close: Decimal) -> bool {
+    latest_close <= stop
+}
+
+/// Niveau pivot le plus proche du close, d'un côté donné
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PivotLevel {
+    pub period: String,    // "week" | "month" | "year"
+    pub level: String,     // "pivot" | "S1".."S3" | "R1".."R3"
+    pub price: f64,
+    pub distance_pct: f64, // Distance au close en %, toujours positive
+}
+
+/// Support le plus proche sous le close (ou au close) et résistance la plus proche au-dessus,
+/// toutes périodes confondues. (None, None) si le JSON ne contient aucun niveau exploitable.
+pub fn nearest_pivot_levels(close: f64, point_pivot: &Value) -> (Option<PivotLevel>, Option<PivotLevel>) {
+    let mut support: Option<PivotLevel> = None;
+    let mut resistance: Option<PivotLevel> = None;
+
+    if !(close.is_finite() && close > 0.0) {
+        return (None, None);
+    }
+
+    for period in ["week", "month", "year"] {
+        for key in ["pivot", "s1", "s2", "s3", "r1", "r2", "r3"] {
+            let Some(price) = point_pivot[period][key].as_f64().filter(|p| p.is_finite()) else {
+                continue;
+            };
+
+            let level = PivotLevel {
+                period: period.to_string(),
+                level: if key == "pivot" { key.to_string() } else { key.to_uppercase() },
+                price,
+                distance_pct: ((close - price).abs() / close * 10000.0).round() / 100.0,
+            };
+
+            let nearest = if price <= close { &mut support } else { &mut resistance };
+            if nearest.as_ref().is_none_or(|current| (price - close).abs() < (current.price - close).abs()) {
+                *nearest = Some(level);
+            }
+        }
+    }
+
+    (support, resistance)
+}
+
+pub struct RiskService;
+
+impl RiskService {
+    /// Plus haut close de `symbol` entre `since` (inclus) et aujourd'hui
+    pub async fn highest_close_since(
+        db: &DatabaseConnection,
+        symbol: &str,
+        since: NaiveDate,
+    ) -> Result<Option<Decimal>, DbErr> {
+        let today = Local::now().naive_local().date();
+
+        let rows = HistoricData::find()
+            .filter(historic_data::Column::Symbol.eq(symbol))
+            .filter(historic_data::Column::Date.gte(since.format("%Y-%m-%d").to_string()))
+            .filter(historic_data::Column::Date.lte(today.format("%Y-%m-%d").to_string()))
+            .all(db)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.close.as_deref())
+            .filter_map(|close| Decimal::from_str(close.trim()).ok())
+            .max())
+    }
+
+    /// Dernier point_pivot calculé pour `symbol` (None si aucun)
+    pub async fn latest_point_pivot(db: &DatabaseConnection, symbol: &str) -> Result<Option<Value>, DbErr> {
+        Ok(Indicator::find()
+            .filter(IndicatorColumn::Symbol.eq(symbol))
+            .filter(IndicatorColumn::PointPivot.is_not_null())
+            .order_by_desc(IndicatorColumn::Date)
+            .one(db)
+            .await?
+            .and_then(|row| row.point_pivot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_stop_uses_entry_price_as_floor() {
+        // Aucun close au-dessus de l'entrée : stop-loss fixe à -10%
+        assert_eq!(trailing_stop(dec("100"), Some(dec("95")), dec("10")), dec("90"));
+        assert_eq!(trailing_stop(dec("100"), None, dec("10")), dec("90"));
+    }
+
+    #[test]
+    fn test_stop_ratchets_up_then_triggers() {
+        let entry = dec("100");
+        let trail = dec("10");
+        let closes = ["104", "112", "120", "115", "109", "107.5"];
+
+        let mut highest: Option<Decimal> = None;
+        let mut stops = Vec::new();
+        let mut breaches = Vec::new();
+
+        for close in closes {
+            let close = dec(close);
+            highest = Some(highest.map_or(close, |h| h.max(close)));
+            let stop = trailing_stop(entry, highest, trail);
+            stops.push(stop);
+            breaches.push(is_stop_breached(stop, close));
+        }
+
+        // Le stop monte avec le plus haut (120 → 108) et ne redescend jamais
+        assert_eq!(stops, vec![dec("93.6"), dec("100.8"), dec("108"), dec("108"), dec("108"), dec("108")]);
+        // 109 reste au-dessus du stop, 107.5 le déclenche
+        assert_eq!(breaches, vec![false, false, false, false, false, true]);
+    }
+
+    #[test]
+    fn test_nearest_support_and_resistance_across_periods() {
+        let point_pivot = serde_json::json!({
+            "week": {"pivot": 101.0, "s1": 98.0, "s2": 96.0, "s3": 94.0, "r1": 104.0, "r2": 106.0, "r3": 108.0, "bars": 5},
+            "month": {"pivot": 99.5, "s1": 95.0, "s2": 90.0, "s3": 85.0, "r1": 102.0, "r2": 110.0, "r3": 115.0, "bars": 21},
+            "year": {"pivot": 80.0, "s1": 70.0, "s2": 60.0, "s3": 50.0, "r1": 100.5, "r2": 120.0, "r3": 140.0, "bars": 250}
+        });
+
+        let (support, resistance) = nearest_pivot_levels(100.0, &point_pivot);
+
+        let support = support.unwrap();
+        assert_eq!((support.period.as_str(), support.level.as_str(), support.price), ("month", "pivot", 99.5));
+        assert_eq!(support.distance_pct, 0.5);
+
+        let resistance = resistance.unwrap();
+        assert_eq!((resistance.period.as_str(), resistance.level.as_str(), resistance.price), ("year", "R1", 100.5));
+        assert_eq!(resistance.distance_pct, 0.5);
+    }
+
+    #[test]
+    fn test_no_pivot_data_gives_no_levels() {
+        assert_eq!(nearest_pivot_levels(100.0, &Value::Null), (None, None));
+        // Seule la période week est disponible et tout est au-dessus du close : pas de support
+        let week_only = serde_json::json!({"week": {"pivot": 105.0, "s1": 103.0, "r1": 107.0}});
+        let (support, resistance) = nearest_pivot_levels(100.0, &week_only);
+        assert_eq!(support, None);
+        assert_eq!(resistance.unwrap().level, "S1");
+    }
+}
@@ -0,0 +1,69 @@
+// ============================================================================
+// SERVICE : ARRÊT PROPRE (GRACEFUL SHUTDOWN)
+// ============================================================================
+//
+// Description:
+//   Capte SIGTERM / SIGINT, lève un drapeau global puis laisse actix drainer
+//   les requêtes en cours (SHUTDOWN_TIMEOUT_SECS). Les traitements longs
+//   (calcul des indicateurs / stratégies) consultent is_requested() entre deux
+//   symboles ou deux stratégies et s'arrêtent proprement au point de contrôle.
+//
+// Points d'attention:
+//   - Chaque symbole est commité dans sa propre transaction : un arrêt entre deux
+//     symboles ne laisse jamais de transaction à moitié appliquée
+//   - Le calcul suivant reprend naturellement (FLUX A incrémental, UPSERT des résultats)
+//
+// ============================================================================
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+/// Délai de drainage par défaut (un calcul de stratégies peut être long)
+pub const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 60;
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Délai accordé aux requêtes en cours via SHUTDOWN_TIMEOUT_SECS
+pub fn timeout_from_env() -> u64 {
+    std::env::var("SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS)
+}
+
+pub fn request() {
+    SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// true une fois SIGTERM / SIGINT reçu : les jobs longs doivent s'arrêter au prochain point de contrôle
+pub fn is_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Attend SIGINT (Ctrl+C) ou SIGTERM (arrêt du conteneur / déploiement)
+pub async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => info!("SIGINT received"),
+                    _ = sigterm.recv() => info!("SIGTERM received"),
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+                info!("SIGINT received");
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("SIGINT received");
+    }
+}
@@ -0,0 +1,253 @@
+// ============================================================================
+// SERVICE : AJOUT DE SYMBOLES (stock) + CALCUL INITIAL DES INDICATEURS
+// ============================================================================
+//
+// Description:
+//   POST /api/admin/stocks ajoute une liste de symboles dans stock en une seule
+//   transaction, puis lance en arrière-plan le calcul complet de leurs
+//   indicateurs (FLUX B de IndicatorService) sous un id de job consultable via
+//   GET /api/admin/jobs/{id}.
+//
+// Points d'attention:
+//   - Tout ou rien : un doublon (dans la requête ou déjà en base, symbole ou
+//     compagny_name qui est la clé primaire) refuse toute la liste
+//   - Le backend ne télécharge pas historicdata : le calcul utilise les barres
+//     déjà importées (source externe ou POST /api/admin/data/{symbol}/replace).
+//     Sans historique, le job se termine avec 0 ligne et le symbole reste
+//     "nouveau" : le prochain calcul quotidien le reprendra en FLUX B
+//   - Même verrou que le calcul quotidien (calculation_lock) : si un calcul tourne
+//     déjà, les symboles sont ajoutés sans job, le calcul en cours ou le suivant
+//     les traitera
+//
+// ============================================================================
+
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+use crate::models::stock::{self, Entity as Stock};
+use crate::services::calculation_jobs::{self, JobInfo};
+use crate::services::calculation_lock;
+use crate::services::indicator_service::IndicatorService;
+use crate::utils::currency;
+
+/// Symboles par requête
+pub const MAX_NEW_STOCKS: usize = 200;
+
+/// Un symbole à ajouter
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewStock {
+    pub compagny_name: String,
+    pub symbol_alphavantage: String,
+    pub currency: Option<String>, // défaut: devise par défaut (CAD)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OnboardingError {
+    Invalid(Vec<String>), // Rien n'a été écrit
+    Database(String),
+}
+
+/// Résultat de l'ajout : symboles insérés et job de calcul initial (None si un calcul tournait déjà)
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingSummary {
+    pub added: Vec<String>,
+    pub job_id: Option<u64>,
+}
+
+/// Valide la liste (aucun accès BD hors `existing_*`) et prépare les lignes stock
+pub fn stage_stocks(
+    new_stocks: &[NewStock],
+    existing_symbols: &HashSet<String>,
+    existing_names: &HashSet<String>,
+) -> Result<Vec<stock::ActiveModel>, OnboardingError> {
+    if new_stocks.is_empty() {
+        return Err(OnboardingError::Invalid(vec!["At least one stock is required".to_string()]));
+    }
+    if new_stocks.len() > MAX_NEW_STOCKS {
+        return Err(OnboardingError::Invalid(vec![format!(
+            "Too many stocks: {} (max {})",
+            new_stocks.len(),
+            MAX_NEW_STOCKS
+        )]));
+    }
+
+    let mut errors = Vec::new();
+    let mut seen_symbols = HashSet::new();
+    let mut seen_names = HashSet::new();
+    let mut rows = Vec::with_capacity(new_stocks.len());
+
+    for (i, new_stock) in new_stocks.iter().enumerate() {
+        let name = new_stock.compagny_name.trim().to_string();
+        let symbol = new_stock.symbol_alphavantage.trim().to_uppercase();
+        let code = currency::or_default(new_stock.currency.as_deref().map(|c| c.trim().to_uppercase()));
+
+        if name.is_empty() || symbol.is_empty() {
+            errors.push(format!("stocks[{}]: compagny_name and symbol_alphavantage are required", i));
+            continue;
+        }
+        if !currency::is_supported(&code) {
+            errors.push(format!("stocks[{}]: invalid currency. Must be one of: {}", i, currency::supported_list()));
+        }
+        if existing_symbols.contains(&symbol) {
+            errors.push(format!("stocks[{}]: symbol already exists: {}", i, symbol));
+        } else if !seen_symbols.insert(symbol.clone()) {
+            errors.push(format!("stocks[{}]: duplicate symbol in request: {}", i, symbol));
+        }
+        if existing_names.contains(&name) {
+            errors.push(format!("stocks[{}]: compagny_name already exists: {}", i, name));
+        } else if !seen_names.insert(name.clone()) {
+            errors.push(format!("stocks[{}]: duplicate compagny_name in request: {}", i, name));
+        }
+
+        rows.push(stock::ActiveModel {
+            compagny_name: Set(name),
+            is_alive: Set(Some("true".to_string())),
+            symbol_alphavantage: Set(Some(symbol)),
+            currency: Set(Some(code)),
+            ..Default::default()
+        });
+    }
+
+    if errors.is_empty() { Ok(rows) } else { Err(OnboardingError::Invalid(errors)) }
+}
+
+/// Insère les symboles dans stock (une transaction) et retourne les symboles ajoutés
+pub async fn add_stocks(new_stocks: &[NewStock], db: &DatabaseConnection) -> Result<Vec<String>, OnboardingError> {
+    let symbols: Vec<String> = new_stocks.iter().map(|s| s.symbol_alphavantage.trim().to_uppercase()).collect();
+    let names: Vec<String> = new_stocks.iter().map(|s| s.compagny_name.trim().to_string()).collect();
+
+    let existing = Stock::find()
+        .filter(stock::Column::SymbolAlphavantage.is_in(symbols.clone()).or(stock::Column::CompagnyName.is_in(names)))
+        .all(db)
+        .await
+        .map_err(|e| OnboardingError::Database(format!("Failed to fetch stocks: {}", e)))?;
+    let existing_symbols: HashSet<String> = existing.iter().filter_map(|s| s.symbol_alphavantage.clone()).collect();
+    let existing_names: HashSet<String> = existing.into_iter().map(|s| s.compagny_name).collect();
+
+    let rows = stage_stocks(new_stocks, &existing_symbols, &existing_names)?;
+
+    let txn = db.begin().await.map_err(|e| OnboardingError::Database(format!("Transaction begin error: {}", e)))?;
+    Stock::insert_many(rows)
+        .exec(&txn)
+        .await
+        .map_err(|e| OnboardingError::Database(format!("Failed to insert stocks: {}", e)))?;
+    txn.commit().await.map_err(|e| OnboardingError::Database(format!("Commit error: {}", e)))?;
+
+    info!("Added {} stocks: {:?}", symbols.len(), symbols);
+    Ok(symbols)
+}
+
+/// Calcul initial (FLUX B) des indicateurs des symboles ajoutés, puis fin du job `job_id`
+pub async fn run_initial_indicators(job_id: u64, symbols: Vec<String>, db: &DatabaseConnection) -> Option<JobInfo> {
+    let outcome = IndicatorService::new().calculate_indicators(symbols, None, db).await;
+    if let Err(e) = &outcome {
+        warn!("Initial indicator calculation failed (job {}): {}", job_id, e);
+    }
+    calculation_jobs::finish(job_id, outcome)
+}
+
+/// Ajoute les symboles puis planifie leur calcul initial en arrière-plan
+pub async fn onboard(new_stocks: &[NewStock], db: &DatabaseConnection) -> Result<OnboardingSummary, OnboardingError> {
+    let added = add_stocks(new_stocks, db).await?;
+
+    let lock = match calculation_lock::try_acquire(db).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            info!("Calculation already in progress: {} new stocks left for the next run", added.len());
+            return Ok(OnboardingSummary { added, job_id: None });
+        }
+        Err(e) => {
+            warn!("Failed to schedule initial indicators: {}", e);
+            return Ok(OnboardingSummary { added, job_id: None });
+        }
+    };
+
+    let job_id = calculation_jobs::start();
+    let symbols = added.clone();
+    let db = db.clone();
+    actix_web::rt::spawn(async move {
+        run_initial_indicators(job_id, symbols, &db).await;
+        lock.release().await;
+    });
+
+    Ok(OnboardingSummary { added, job_id: Some(job_id) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema};
+
+    use crate::models::{historic_data, indicator};
+
+    fn new_stock(name: &str, symbol: &str) -> NewStock {
+        NewStock { compagny_name: name.to_string(), symbol_alphavantage: symbol.to_string(), currency: Some("USD".to_string()) }
+    }
+
+    #[test]
+    fn duplicates_reject_the_whole_list() {
+        let existing_symbols = HashSet::from(["AAPL".to_string()]);
+        let existing_names = HashSet::from(["Microsoft".to_string()]);
+
+        let err = stage_stocks(
+            &[new_stock("Apple", "aapl"), new_stock("Microsoft", "MSFT"), new_stock("Nvidia", "NVDA"), new_stock("Nvidia 2", "nvda")],
+            &existing_symbols,
+            &existing_names,
+        )
+        .unwrap_err();
+
+        assert_eq!(err, OnboardingError::Invalid(vec![
+            "stocks[0]: symbol already exists: AAPL".to_string(),
+            "stocks[1]: compagny_name already exists: Microsoft".to_string(),
+            "stocks[3]: duplicate symbol in request: NVDA".to_string(),
+        ]));
+        assert!(stage_stocks(&[new_stock("Nvidia", "NVDA")], &existing_symbols, &existing_names).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_added_symbols_appear_and_get_initial_indicators() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(Stock),
+            schema.create_table_from_entity(historic_data::Entity),
+            schema.create_table_from_entity(indicator::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        for i in 0..40 {
+            let close = format!("{:.2}", 50.0 + (i as f64 * 0.5).sin());
+            historic_data::ActiveModel {
+                symbol: Set("NEWCO".to_string()),
+                date: Set(format!("2025-{:02}-{:02}", 1 + i / 28, 1 + i % 28)),
+                open: Set(Some(close.clone())),
+                high: Set(Some(close.clone())),
+                low: Set(Some(close.clone())),
+                close: Set(Some(close)),
+                volume: Set(Some("1000".to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let added = add_stocks(&[new_stock("New Co", " newco ")], &db).await.unwrap();
+        assert_eq!(added, vec!["NEWCO".to_string()]);
+        let stored = Stock::find().all(&db).await.unwrap();
+        assert_eq!(stock::alive_symbols(stored), vec!["NEWCO".to_string()]);
+
+        // Second ajout du même symbole refusé, rien n'est écrit
+        assert!(matches!(add_stocks(&[new_stock("New Co bis", "NEWCO")], &db).await, Err(OnboardingError::Invalid(_))));
+        assert_eq!(Stock::find().all(&db).await.unwrap().len(), 1);
+
+        // Le job planifié calcule les indicateurs du nouveau symbole (FLUX B)
+        let saved = IndicatorService::new().calculate_indicators(added, None, &db).await.unwrap();
+        assert!(saved > 0);
+        let rows = indicator::Entity::find().filter(indicator::Column::Symbol.eq("NEWCO")).all(&db).await.unwrap();
+        assert_eq!(rows.len(), saved);
+    }
+}
@@ -0,0 +1,130 @@
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
+use serde_json::json;
+
+use crate::services::historic_bars::PriceSource;
+use crate::services::market_data::{DbMarketData, MarketDataSource};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal};
+use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
+use tracing::info;
+
+/// Cassure du canal de Donchian (style "turtle") : BUY quand le close du jour dépasse le plus haut
+/// du canal de la veille, SELL quand il passe sous le plus bas de la veille, HOLD sinon.
+/// Le canal de la veille est utilisé car celui du jour inclut déjà la barre courante (pas de lookahead).
+pub struct DonchianBreakoutStrategy;
+
+/// Signal de cassure : confidence = 1 sur une cassure ;
+/// sans cassure, confidence = distance du close au bord le plus proche rapportée à la demi-largeur du canal
+pub(crate) fn breakout_signal(close: f64, previous_upper: f64, previous_lower: f64) -> Signal {
+    if close > previous_upper {
+        Signal::new("BUY", 1.0)
+    } else if close < previous_lower {
+        Signal::new("SELL", 1.0)
+    } else {
+        let half_width = (previous_upper - previous_lower) / 2.0;
+        let distance = (close - previous_lower).min(previous_upper - close);
+        Signal::new("HOLD", if half_width > 0.0 { distance / half_width } else { 0.0 })
+    }
+}
+
+impl DonchianBreakoutStrategy {
+    /// Calcul sur une source de barres quelconque ; les indicateurs restent lus en BD
+    pub async fn calculate_with(
+        &self,
+        symbols: &[String],
+        data: &dyn MarketDataSource,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        info!("Donchian Breakout Strategy: Processing {} symbols", symbols.len());
+        let price_source = PriceSource::from_env(); // Même close que les indicateurs
+
+        let mut recommendations = Vec::new();
+
+        for symbol in symbols {
+            // Les deux dernières lignes d'indicateurs : jour courant puis veille
+            let indicators = Indicator::find()
+                .filter(IndicatorColumn::Symbol.eq(symbol))
+                .order_by_desc(IndicatorColumn::Date)
+                .limit(2)
+                .all(db)
+                .await
+                .map_err(|e| format!("Failed to fetch indicators for {}: {}", symbol, e))?;
+
+            let [current_indicator, previous_indicator] = indicators.as_slice() else {
+                continue;
+            };
+
+            let (Some(previous_upper), Some(previous_lower)) = (
+                previous_indicator.donchian_upper.as_ref().and_then(|v| v.parse::<f64>().ok()),
+                previous_indicator.donchian_lower.as_ref().and_then(|v| v.parse::<f64>().ok()),
+            ) else {
+                continue;
+            };
+
+            let Some(bar) = data.bar_on(symbol, &current_indicator.date)
+                .await?
+                .map(|bar| bar.priced(price_source))
+            else {
+                continue;
+            };
+
+            recommendations.push(Recommendation {
+                symbol: symbol.clone(),
+                recommendation: breakout_signal(bar.close, previous_upper, previous_lower).to_value(),
+                metadata: json!({
+                    "close": bar.close,
+                    "previous_donchian_upper": previous_upper,
+                    "previous_donchian_lower": previous_lower,
+                    "previous_date": previous_indicator.date,
+                    "date": current_indicator.date,
+                }),
+            });
+        }
+
+        info!("Donchian Breakout Strategy: Generated {} recommendations", recommendations.len());
+        Ok(recommendations)
+    }
+}
+
+#[async_trait]
+impl StrategyCalculator for DonchianBreakoutStrategy {
+    async fn calculate_batch(
+        &self,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        self.calculate_with(symbols, &DbMarketData::new(db), db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::indicators::donchian::compute_donchian_values;
+
+    #[test]
+    fn test_breakout_bar_uses_previous_channel() {
+        // 20 barres dans un range 95-105, puis une barre qui clôture à 108 (high 109)
+        let mut highs = vec![105.0; 20];
+        let mut lows = vec![95.0; 20];
+        highs.push(109.0);
+        lows.push(104.0);
+
+        let channels = compute_donchian_values(&highs, &lows, 20);
+        let (previous_upper, previous_lower) = channels[19].unwrap();
+        let (current_upper, _) = channels[20].unwrap();
+
+        assert_eq!(breakout_signal(108.0, previous_upper, previous_lower), Signal::new("BUY", 1.0));
+        // Le canal du jour inclut la barre de cassure : le close ne peut jamais le dépasser
+        assert_eq!(current_upper, 109.0);
+        assert_ne!(breakout_signal(108.0, current_upper, previous_lower).signal, "BUY");
+    }
+
+    #[test]
+    fn test_breakdown_and_inside_channel() {
+        assert_eq!(breakout_signal(94.0, 105.0, 95.0), Signal::new("SELL", 1.0));
+        // Touche le bord sans le franchir : pas de cassure
+        assert_eq!(breakout_signal(105.0, 105.0, 95.0), Signal::new("HOLD", 0.0));
+        assert_eq!(breakout_signal(100.0, 105.0, 95.0), Signal::new("HOLD", 1.0));
+    }
+}
@@ -1,99 +1,178 @@
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
-use serde_json::json;
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
 
-use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation};
-use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
-use crate::models::historic_data::{Entity as HistoricData, Column as HistoricDataColumn};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal, majority_signal};
+use crate::services::strategies::latest_indicators::fetch_latest_indicators;
+use crate::services::historic_bars::PriceSource;
+use crate::services::market_data::{DbMarketData, MarketDataSource};
+use tracing::info;
 
-pub struct EMAStrategy;
+/// Largeur max de la zone neutre autour de chaque EMA, en % de l'EMA
+pub const MAX_HOLD_BAND_PCT: f64 = 10.0;
 
-#[async_trait]
-impl StrategyCalculator for EMAStrategy {
-    async fn calculate_batch(
+/// Zone neutre paramétrable via strategy_config {"hold_band_pct": 0.5} (défaut 0 = aucune) :
+/// un close à moins de 0.5 % d'une EMA donne HOLD pour cette EMA au lieu de BUY / SELL
+#[derive(Default)]
+pub struct EMAStrategy {
+    hold_band_pct: f64,
+}
+
+impl EMAStrategy {
+    pub fn new(hold_band_pct: f64) -> Self {
+        Self { hold_band_pct }
+    }
+
+    pub fn from_config(config: &Value) -> Self {
+        let hold_band_pct = config
+            .get("hold_band_pct")
+            .and_then(Value::as_f64)
+            .filter(|band| (0.0..=MAX_HOLD_BAND_PCT).contains(band))
+            .unwrap_or(0.0);
+        Self::new(hold_band_pct)
+    }
+}
+
+/// Close vs EMA20/50/200 : un sous-signal par EMA ("N/A" si absente, "HOLD" si le close est
+/// dans la zone neutre de ±hold_band_pct % autour de l'EMA), puis vote majoritaire.
+/// Les HOLD votent aussi : ils l'emportent à égalité, et diluent la confidence d'un BUY / SELL.
+pub(crate) fn ema_signal(close: f64, emas: [Option<f64>; 3], hold_band_pct: f64) -> (Vec<&'static str>, Signal) {
+    let signals: Vec<&'static str> = emas
+        .iter()
+        .map(|ema| match ema {
+            Some(value) if (close - value).abs() <= value.abs() * hold_band_pct / 100.0 => "HOLD",
+            Some(value) if close > *value => "BUY",
+            Some(_) => "SELL",
+            None => "N/A",
+        })
+        .collect();
+
+    let count = |vote: &str| signals.iter().filter(|s| **s == vote).count();
+    let (buys, sells, holds) = (count("BUY"), count("SELL"), count("HOLD"));
+    let majority = majority_signal(&signals);
+    if holds == 0 {
+        return (signals, majority);
+    }
+
+    let total = (buys + sells + holds) as f64;
+    let signal = if holds >= buys.max(sells) {
+        Signal::new("HOLD", holds as f64 / total)
+    } else {
+        Signal::new(&majority.signal, buys.max(sells) as f64 / total)
+    };
+    (signals, signal)
+}
+
+impl EMAStrategy {
+    /// Calcul sur une source de barres quelconque ; les indicateurs restent lus en BD
+    pub async fn calculate_with(
         &self,
         symbols: &[String],
+        data: &dyn MarketDataSource,
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        println!("🔄 EMA Strategy: Processing {} symbols", symbols.len());
+        info!("EMA Strategy: Processing {} symbols", symbols.len());
+        let price_source = PriceSource::from_env(); // Même close que les indicateurs
 
         let mut recommendations = Vec::new();
 
-        // Récupérer les derniers indicateurs pour chaque symbole
+        // Dernière ligne d'indicateurs de chaque symbole, en une passe
+        let latest_indicators = fetch_latest_indicators(symbols, db).await?;
+
         for symbol in symbols {
-            // Récupérer la dernière ligne d'indicateurs pour ce symbole
-            let latest_indicator = Indicator::find()
-                .filter(IndicatorColumn::Symbol.eq(symbol))
-                .order_by_desc(IndicatorColumn::Date)
-                .one(db)
-                .await
-                .map_err(|e| format!("Failed to fetch indicator for {}: {}", symbol, e))?;
-
-            if let Some(indicator) = latest_indicator {
+            if let Some(indicator) = latest_indicators.get(symbol) {
                 let date = &indicator.date;
 
                 // Récupérer le close du même jour depuis historicdata
-                let historic = HistoricData::find()
-                    .filter(HistoricDataColumn::Symbol.eq(symbol))
-                    .filter(HistoricDataColumn::Date.eq(date))
-                    .one(db)
-                    .await
-                    .map_err(|e| format!("Failed to fetch historic data for {}: {}", symbol, e))?;
-
-                if let Some(historic_data) = historic {
-                    if let Some(close_str) = &historic_data.close {
-                        if let Ok(close) = close_str.parse::<f64>() {
-                            // Parser les 3 EMAs
-                            let ema20 = indicator.ema20.as_ref().and_then(|s| s.parse::<f64>().ok());
-                            let ema50 = indicator.ema50.as_ref().and_then(|s| s.parse::<f64>().ok());
-                            let ema200 = indicator.ema200.as_ref().and_then(|s| s.parse::<f64>().ok());
-
-                            // Calculer les 3 signaux
-                            let mut signals = Vec::new();
-
-                            // Signal 1 : Close vs EMA20
-                            if let Some(ema20_val) = ema20 {
-                                signals.push(if close > ema20_val { "BUY" } else { "SELL" });
-                            } else {
-                                signals.push("N/A");
-                            }
-
-                            // Signal 2 : Close vs EMA50
-                            if let Some(ema50_val) = ema50 {
-                                signals.push(if close > ema50_val { "BUY" } else { "SELL" });
-                            } else {
-                                signals.push("N/A");
-                            }
-
-                            // Signal 3 : Close vs EMA200
-                            if let Some(ema200_val) = ema200 {
-                                signals.push(if close > ema200_val { "BUY" } else { "SELL" });
-                            } else {
-                                signals.push("N/A");
-                            }
-
-                            // Créer la recommandation avec Vec<String>
-                            let recommendation = Recommendation {
-                                symbol: symbol.clone(),
-                                recommendation: json!(signals), // ["BUY", "SELL", "BUY"]
-                                metadata: json!({
-                                    "close": close,
-                                    "ema20": ema20,
-                                    "ema50": ema50,
-                                    "ema200": ema200,
-                                    "date": date,
-                                    "signals": signals,
-                                }),
-                            };
-
-                            recommendations.push(recommendation);
-                        }
-                    }
+                if let Some(bar) = data.bar_on(symbol, date).await?.map(|bar| bar.priced(price_source)) {
+                    let close = bar.close;
+
+                    // Parser les 3 EMAs
+                    let ema20 = indicator.ema20.as_ref().and_then(|s| s.parse::<f64>().ok());
+                    let ema50 = indicator.ema50.as_ref().and_then(|s| s.parse::<f64>().ok());
+                    let ema200 = indicator.ema200.as_ref().and_then(|s| s.parse::<f64>().ok());
+
+                    // Calculer les 3 signaux (Close vs EMA20, EMA50, EMA200)
+                    let (signals, signal) = ema_signal(close, [ema20, ema50, ema200], self.hold_band_pct);
+
+                    let recommendation = Recommendation {
+                        symbol: symbol.clone(),
+                        recommendation: signal.to_value(),
+                        metadata: json!({
+                            "close": close,
+                            "ema20": ema20,
+                            "ema50": ema50,
+                            "ema200": ema200,
+                            "date": date,
+                            "signals": signals, // ["BUY", "SELL", "HOLD"]
+                            "hold_band_pct": self.hold_band_pct,
+                        }),
+                    };
+
+                    recommendations.push(recommendation);
                 }
             }
         }
 
-        println!("✅ EMA Strategy: Generated {} recommendations", recommendations.len());
+        info!("EMA Strategy: Generated {} recommendations", recommendations.len());
         Ok(recommendations)
     }
+}
+
+#[async_trait]
+impl StrategyCalculator for EMAStrategy {
+    async fn calculate_batch(
+        &self,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        self.calculate_with(symbols, &DbMarketData::new(db), db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_recommendation_shape() {
+        let (signals, signal) = ema_signal(100.0, [Some(90.0), Some(95.0), Some(110.0)], 0.0);
+
+        assert_eq!(signals, vec!["BUY", "BUY", "SELL"]);
+        assert_eq!(signal.to_value(), json!({"signal": "BUY", "confidence": 0.67}));
+
+        let (_, missing) = ema_signal(100.0, [None, None, None], 0.0);
+        assert_eq!(missing.to_value(), json!({"signal": "HOLD", "confidence": 0.0}));
+    }
+
+    #[test]
+    fn close_inside_the_band_holds() {
+        // Bande de 1 % : 100.9 est à 0.9 % de chaque EMA à 100
+        let (signals, signal) = ema_signal(100.9, [Some(100.0), Some(100.0), Some(100.0)], 1.0);
+        assert_eq!(signals, vec!["HOLD", "HOLD", "HOLD"]);
+        assert_eq!(signal.to_value(), json!({"signal": "HOLD", "confidence": 1.0}));
+
+        // Une seule EMA hors bande ne suffit pas contre deux HOLD
+        let (signals, signal) = ema_signal(100.9, [Some(100.0), Some(100.5), Some(95.0)], 1.0);
+        assert_eq!(signals, vec!["HOLD", "HOLD", "BUY"]);
+        assert_eq!(signal.signal, "HOLD");
+    }
+
+    #[test]
+    fn close_just_outside_the_band_signals() {
+        let (signals, signal) = ema_signal(101.1, [Some(100.0), Some(100.0), Some(100.0)], 1.0);
+        assert_eq!(signals, vec!["BUY", "BUY", "BUY"]);
+        assert_eq!(signal.to_value(), json!({"signal": "BUY", "confidence": 1.0}));
+
+        let (signals, signal) = ema_signal(98.9, [Some(100.0), Some(99.5), Some(110.0)], 1.0);
+        assert_eq!(signals, vec!["SELL", "HOLD", "SELL"]);
+        assert_eq!(signal.to_value(), json!({"signal": "SELL", "confidence": 0.67}));
+    }
+
+    #[test]
+    fn hold_band_is_read_from_config() {
+        assert_eq!(EMAStrategy::from_config(&json!({"hold_band_pct": 0.5})).hold_band_pct, 0.5);
+        assert_eq!(EMAStrategy::from_config(&json!({"hold_band_pct": 50})).hold_band_pct, 0.0);
+        assert_eq!(EMAStrategy::from_config(&Value::Null).hold_band_pct, 0.0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,155 @@
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
+use serde_json::json;
+
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal};
+use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
+use tracing::info;
+
+/// Croisement EMA50 / EMA200 : Golden Cross (BUY) quand l'EMA50 passe au-dessus de l'EMA200,
+/// Death Cross (SELL) quand elle passe en dessous. HOLD les jours sans croisement.
+pub struct EMACrossStrategy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Cross {
+    Golden,
+    Death,
+    None,
+}
+
+impl Cross {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Cross::Golden => "golden_cross",
+            Cross::Death => "death_cross",
+            Cross::None => "no_cross",
+        }
+    }
+
+    fn signal(&self) -> &'static str {
+        match self {
+            Cross::Golden => "BUY",
+            Cross::Death => "SELL",
+            Cross::None => "HOLD",
+        }
+    }
+}
+
+/// Écart EMA50/EMA200 (relatif) à partir duquel un HOLD est jugé sans ambiguïté
+const HOLD_FULL_CONFIDENCE_GAP: f64 = 0.05;
+
+/// Signal standard : un croisement est un événement net (confidence = 1) ;
+/// sans croisement, confidence = écart relatif EMA50/EMA200 rapporté à HOLD_FULL_CONFIDENCE_GAP
+pub(crate) fn cross_signal(cross: Cross, current: (f64, f64)) -> Signal {
+    match cross {
+        Cross::None => {
+            let (fast, slow) = current;
+            let gap = if slow != 0.0 { ((fast - slow) / slow).abs() } else { 0.0 };
+            Signal::new(cross.signal(), gap / HOLD_FULL_CONFIDENCE_GAP)
+        }
+        _ => Signal::new(cross.signal(), 1.0),
+    }
+}
+
+/// Compare (ema50, ema200) de la veille et du jour pour détecter un croisement
+pub(crate) fn detect_cross(previous: (f64, f64), current: (f64, f64)) -> Cross {
+    let (prev_fast, prev_slow) = previous;
+    let (fast, slow) = current;
+
+    if prev_fast <= prev_slow && fast > slow {
+        Cross::Golden
+    } else if prev_fast >= prev_slow && fast < slow {
+        Cross::Death
+    } else {
+        Cross::None
+    }
+}
+
+#[async_trait]
+impl StrategyCalculator for EMACrossStrategy {
+    async fn calculate_batch(
+        &self,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        info!("EMA Cross Strategy: Processing {} symbols", symbols.len());
+
+        let mut recommendations = Vec::new();
+
+        for symbol in symbols {
+            // Les deux dernières lignes d'indicateurs : jour courant puis veille
+            let indicators = Indicator::find()
+                .filter(IndicatorColumn::Symbol.eq(symbol))
+                .order_by_desc(IndicatorColumn::Date)
+                .limit(2)
+                .all(db)
+                .await
+                .map_err(|e| format!("Failed to fetch indicators for {}: {}", symbol, e))?;
+
+            let [current_indicator, previous_indicator] = indicators.as_slice() else {
+                continue;
+            };
+
+            let emas = |indicator: &crate::models::indicator::Model| -> Option<(f64, f64)> {
+                Some((
+                    indicator.ema50.as_ref()?.parse().ok()?,
+                    indicator.ema200.as_ref()?.parse().ok()?,
+                ))
+            };
+
+            let (Some(current), Some(previous)) = (emas(current_indicator), emas(previous_indicator)) else {
+                continue;
+            };
+
+            let cross = detect_cross(previous, current);
+
+            recommendations.push(Recommendation {
+                symbol: symbol.clone(),
+                recommendation: cross_signal(cross, current).to_value(),
+                metadata: json!({
+                    "cross": cross.as_str(),
+                    "ema50": current.0,
+                    "ema200": current.1,
+                    "previous_ema50": previous.0,
+                    "previous_ema200": previous.1,
+                    "previous_date": previous_indicator.date,
+                    "date": current_indicator.date,
+                }),
+            });
+        }
+
+        info!("EMA Cross Strategy: Generated {} recommendations", recommendations.len());
+        Ok(recommendations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_and_death_cross_days() {
+        assert_eq!(detect_cross((99.0, 100.0), (101.0, 100.0)), Cross::Golden);
+        assert_eq!(detect_cross((101.0, 100.0), (99.5, 100.0)), Cross::Death);
+        // EMA50 touchait l'EMA200 la veille : le passage au-dessus compte comme un croisement
+        assert_eq!(detect_cross((100.0, 100.0), (100.5, 100.0)), Cross::Golden);
+    }
+
+    #[test]
+    fn test_no_cross_when_trend_continues() {
+        // EMA50 déjà au-dessus : tendance haussière mais pas de croisement ce jour-là
+        let cross = detect_cross((105.0, 100.0), (106.0, 100.5));
+
+        assert_eq!(cross, Cross::None);
+        assert_eq!(cross.signal(), "HOLD");
+        assert_eq!(detect_cross((95.0, 100.0), (94.0, 100.0)), Cross::None);
+    }
+
+    #[test]
+    fn test_ema_cross_recommendation_shape() {
+        assert_eq!(cross_signal(Cross::Golden, (101.0, 100.0)).to_value(), json!({"signal": "BUY", "confidence": 1.0}));
+        assert_eq!(cross_signal(Cross::Death, (99.5, 100.0)).to_value(), json!({"signal": "SELL", "confidence": 1.0}));
+        // EMA50 à 2.5% de l'EMA200 : HOLD à mi-confiance
+        assert_eq!(cross_signal(Cross::None, (102.5, 100.0)).to_value(), json!({"signal": "HOLD", "confidence": 0.5}));
+    }
+}
\ No newline at end of file
@@ -1,17 +1,125 @@
-use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal, zone_signal};
+use crate::services::market_data::{DbMarketData, MarketDataSource, PriceRange};
 use sea_orm::DatabaseConnection;
 use serde_json::{Value, json};
-use chrono::{Local, Duration};
+use chrono::{Duration, NaiveDate};
 use async_trait::async_trait;
-use sqlx::Row;
+use crate::utils::dates::{market_today, trading_days_between};
+use tracing::warn;
 
-// ========== CONSTANTES ==========
+// ========== VALEURS PAR DÉFAUT ==========
 const CALCULATION_PERIOD_DAYS: i64 = 365;
 const BUY_THRESHOLD: f64 = 20.0;   // En dessous de 20% = BUY
 const SELL_THRESHOLD: f64 = 80.0;  // Au-dessus de 80% = SELL
-// ================================
+const MAX_STALE_TRADING_DAYS: i64 = 3; // Dernier close plus vieux = prix périmé
+const STALE_TO_HOLD: bool = true;      // Recommandation périmée rétrogradée en HOLD
+// ========================================
 
-pub struct MinMaxLastYear;
+/// Paramètres lus depuis strategy_config (strategies_rust, id = 1)
+/// Ex: {"lookback_days": 180, "buy_threshold": 15, "sell_threshold": 85,
+///      "max_stale_days": 3, "stale_to_hold": true}
+/// Clé absente ou invalide → valeur par défaut
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinMaxConfig {
+    pub lookback_days: i64,
+    pub buy_threshold: f64,
+    pub sell_threshold: f64,
+    pub max_stale_days: i64,
+    pub stale_to_hold: bool,
+}
+
+impl Default for MinMaxConfig {
+    fn default() -> Self {
+        Self {
+            lookback_days: CALCULATION_PERIOD_DAYS,
+            buy_threshold: BUY_THRESHOLD,
+            sell_threshold: SELL_THRESHOLD,
+            max_stale_days: MAX_STALE_TRADING_DAYS,
+            stale_to_hold: STALE_TO_HOLD,
+        }
+    }
+}
+
+impl MinMaxConfig {
+    pub fn from_config(config: &Value) -> Self {
+        let defaults = Self::default();
+
+        let lookback_days = config
+            .get("lookback_days")
+            .and_then(Value::as_i64)
+            .filter(|days| *days > 0)
+            .unwrap_or(defaults.lookback_days);
+
+        let threshold = |key: &str| config.get(key).and_then(Value::as_f64).filter(|t| (0.0..=100.0).contains(t));
+        let mut buy_threshold = threshold("buy_threshold").unwrap_or(defaults.buy_threshold);
+        let mut sell_threshold = threshold("sell_threshold").unwrap_or(defaults.sell_threshold);
+
+        if buy_threshold >= sell_threshold {
+            warn!("MinMaxLastYear: buy_threshold must be below sell_threshold, using defaults");
+            buy_threshold = defaults.buy_threshold;
+            sell_threshold = defaults.sell_threshold;
+        }
+
+        let max_stale_days = config
+            .get("max_stale_days")
+            .and_then(Value::as_i64)
+            .filter(|days| *days >= 0)
+            .unwrap_or(defaults.max_stale_days);
+
+        let stale_to_hold = config
+            .get("stale_to_hold")
+            .and_then(Value::as_bool)
+            .unwrap_or(defaults.stale_to_hold);
+
+        Self { lookback_days, buy_threshold, sell_threshold, max_stale_days, stale_to_hold }
+    }
+
+    /// Le dernier close date de plus de `max_stale_days` jours ouvrés avant `today`
+    /// Date inconnue ou illisible → non périmé (rien pour en juger)
+    pub fn is_stale(&self, latest_date: Option<&str>, today: NaiveDate) -> bool {
+        latest_date
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .is_some_and(|latest| trading_days_between(latest, today) > self.max_stale_days)
+    }
+
+    /// Date de début de la période de calcul (incluse)
+    pub fn cutoff_date(&self, today: NaiveDate) -> String {
+        (today - Duration::days(self.lookback_days)).format("%Y-%m-%d").to_string()
+    }
+}
+
+#[derive(Default)]
+pub struct MinMaxLastYear {
+    config: MinMaxConfig,
+}
+
+impl MinMaxLastYear {
+    pub fn new(config: MinMaxConfig) -> Self {
+        Self { config }
+    }
+
+    /// Calcul sur une source de données quelconque (BD en production, mémoire en test)
+    pub async fn calculate_with(
+        &self,
+        symbols: &[String],
+        data: &dyn MarketDataSource,
+    ) -> Result<Vec<Recommendation>, String> {
+        // Date de bourse (MARKET_TZ) : la même pour le cutoff et pour la détection de prix périmé
+        let today = market_today();
+
+        // Calculer la date de cutoff (lookback_days configurable)
+        let cutoff_date = self.config.cutoff_date(today);
+        let ranges = data.min_max(symbols, &cutoff_date).await?;
+
+        // Transformer les résultats en Recommendations
+        let results = ranges
+            .into_iter()
+            .filter_map(|range| to_recommendation(range, &self.config, today))
+            .collect();
+
+        Ok(results)
+    }
+}
 
 #[async_trait]
 impl StrategyCalculator for MinMaxLastYear {
@@ -27,77 +135,203 @@ impl StrategyCalculator for MinMaxLastYear {
 
     async fn calculate_batch(
         &self,
-        _symbols: &[String],
+        symbols: &[String],
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        // Calculer la date de cutoff
-        let one_year_ago = Local::now().naive_local().date() - Duration::days(CALCULATION_PERIOD_DAYS);
-        let cutoff_date = one_year_ago.format("%Y-%m-%d").to_string();
-
-        // Appeler la stored procedure PostgreSQL
-        let pool = db.get_postgres_connection_pool();
-        let rows = sqlx::query("SELECT * FROM get_min_max_prices_last_year($1)")
-            .bind(&cutoff_date)
-            .fetch_all(pool)
-            .await
-            .map_err(|e| format!("SQL stored procedure error: {}", e))?;
+        self.calculate_with(symbols, &DbMarketData::new(db)).await
+    }
+}
 
-        // Transformer les résultats en Recommendations
-        let mut results = Vec::new();
-
-        for row in rows {
-            let symbol: String = row.try_get("symbol")
-                .map_err(|e| format!("Failed to get symbol: {}", e))?;
-
-            let min_price: f64 = row.try_get("min_price")
-                .map_err(|e| format!("Failed to get min_price for {}: {}", symbol, e))?;
-
-            let max_price: f64 = row.try_get("max_price")
-                .map_err(|e| format!("Failed to get max_price for {}: {}", symbol, e))?;
-
-            let current_price: Option<f64> = row.try_get("current_price").ok();
-
-            // Validation des données
-            let current_price = match current_price {
-                Some(price) if price > 0.0 => price,
-                _ => {
-                    println!("⚠️ Skipping {} - no current price", symbol);
-                    continue;
-                }
-            };
-
-            if max_price == min_price {
-                println!("⚠️ Skipping {} - no price variation (min=max)", symbol);
-                continue;
-            }
-
-            // Calculer le pourcentage (côté Rust)
-            let percentage = ((current_price - min_price) / (max_price - min_price)) * 100.0;
-
-            // Déterminer la recommandation avec les constantes
-            let recommendation = if percentage <= BUY_THRESHOLD {
-                "BUY"
-            } else if percentage >= SELL_THRESHOLD {
-                "SELL"
-            } else {
-                "HOLD"
-            };
-
-            results.push(Recommendation {
-                symbol: symbol.clone(),
-                recommendation: json!(recommendation),
-                metadata: json!({
-                    "percentage": format!("{:.2}", percentage),
-                    "min_price": format!("{:.2}", min_price),
-                    "max_price": format!("{:.2}", max_price),
-                    "current_price": format!("{:.2}", current_price),
-                    "calculation_period_days": CALCULATION_PERIOD_DAYS,
-                    "buy_threshold": BUY_THRESHOLD,
-                    "sell_threshold": SELL_THRESHOLD
-                }),
-            });
+fn to_recommendation(range: PriceRange, config: &MinMaxConfig, today: NaiveDate) -> Option<Recommendation> {
+    let PriceRange { symbol, min_price, max_price, current_price, latest_date } = range;
+
+    // Validation des données
+    let current_price = match current_price {
+        Some(price) if price > 0.0 => price,
+        _ => {
+            warn!("Skipping {} - no current price", symbol);
+            return None;
         }
+    };
 
-        Ok(results)
+    if max_price == min_price {
+        warn!("Skipping {} - no price variation (min=max)", symbol);
+        return None;
+    }
+
+    // Calculer le pourcentage (côté Rust)
+    let percentage = ((current_price - min_price) / (max_price - min_price)) * 100.0;
+
+    // Déterminer la recommandation avec les seuils configurés (confidence = profondeur dans la zone)
+    let mut signal = zone_signal(percentage, config.buy_threshold, config.sell_threshold, 0.0, 100.0);
+
+    // Prix périmé : on n'agit pas sur un current_price trop ancien
+    let stale = config.is_stale(latest_date.as_deref(), today);
+    if stale {
+        warn!("{} - current price is stale (last close {:?})", symbol, latest_date);
+        if config.stale_to_hold {
+            signal = Signal::new("HOLD", 0.0);
+        }
+    }
+
+    Some(Recommendation {
+        symbol,
+        recommendation: signal.to_value(),
+        metadata: json!({
+            "percentage": format!("{:.2}", percentage),
+            "min_price": format!("{:.2}", min_price),
+            "max_price": format!("{:.2}", max_price),
+            "current_price": format!("{:.2}", current_price),
+            "calculation_period_days": config.lookback_days,
+            "buy_threshold": config.buy_threshold,
+            "sell_threshold": config.sell_threshold,
+            "latest_date": latest_date,
+            "stale": stale
+        }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::historic_data;
+    use crate::services::historic_bars;
+    use crate::services::market_data::{compute_price_ranges, InMemoryMarketData};
+
+    fn bar(symbol: &str, date: &str, close: Option<&str>) -> historic_data::Model {
+        historic_data::Model {
+            symbol: symbol.to_string(),
+            date: date.to_string(),
+            open: close.map(|c| c.to_string()),
+            high: close.map(|c| c.to_string()),
+            low: close.map(|c| c.to_string()),
+            close: close.map(|c| c.to_string()),
+            adjusted_close: None,
+            volume: None,
+        }
+    }
+
+    #[test]
+    fn test_fallback_matches_procedure_semantics() {
+        let fixture = vec![
+            bar("AAPL", "2024-01-10", Some("500.00")), // avant le cutoff : ignoré
+            bar("AAPL", "2024-06-01", Some("100.00")), // cutoff inclus
+            bar("AAPL", "2024-09-01", Some("150.00")),
+            bar("AAPL", "2025-03-01", Some("90.00")),
+            bar("AAPL", "2025-05-30", Some("120.00")), // dernière date = current_price
+            bar("AAPL", "2025-05-15", Some("n/a")),    // non numérique : ignoré
+            bar("SHOP", "2025-01-02", Some("80.0")),
+            bar("SHOP", "2025-02-02", None),
+        ];
+
+        let ranges = compute_price_ranges(&historic_bars::parse_bars(fixture), "2024-06-01");
+
+        assert_eq!(ranges, vec![
+            PriceRange { symbol: "AAPL".into(), min_price: 90.0, max_price: 150.0, current_price: Some(120.0), latest_date: Some("2025-05-30".into()) },
+            PriceRange { symbol: "SHOP".into(), min_price: 80.0, max_price: 80.0, current_price: Some(80.0), latest_date: Some("2025-01-02".into()) },
+        ]);
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 30).unwrap()
+    }
+
+    #[test]
+    fn test_recommendation_from_range() {
+        let buy = to_recommendation(PriceRange {
+            symbol: "AAPL".into(), min_price: 90.0, max_price: 150.0, current_price: Some(95.0), latest_date: None,
+        }, &MinMaxConfig::default(), today()).unwrap();
+        // 95 dans [90, 150] = 8.33% : à 58% de la profondeur de la zone BUY (0-20%)
+        assert_eq!(buy.recommendation, json!({"signal": "BUY", "confidence": 0.58}));
+
+        // min = max : pas de recommandation (comme avec la stored procedure)
+        assert!(to_recommendation(PriceRange {
+            symbol: "SHOP".into(), min_price: 80.0, max_price: 80.0, current_price: Some(80.0), latest_date: None,
+        }, &MinMaxConfig::default(), today()).is_none());
+    }
+
+    #[test]
+    fn test_stale_price_is_flagged_and_downgraded() {
+        // Lundi 2025-06-30 : dernier close le mardi 2025-06-24 → 4 jours ouvrés
+        let range = PriceRange {
+            symbol: "AAPL".into(), min_price: 90.0, max_price: 150.0, current_price: Some(95.0),
+            latest_date: Some("2025-06-24".into()),
+        };
+
+        let rec = to_recommendation(range.clone(), &MinMaxConfig::default(), today()).unwrap();
+        assert_eq!(rec.recommendation, json!({"signal": "HOLD", "confidence": 0.0}));
+        assert_eq!(rec.metadata["stale"], json!(true));
+
+        // Sans rétrogradation : BUY conservé mais marqué stale
+        let keep = MinMaxConfig::from_config(&json!({"stale_to_hold": false}));
+        let rec = to_recommendation(range.clone(), &keep, today()).unwrap();
+        assert_eq!(rec.recommendation["signal"], json!("BUY"));
+        assert_eq!(rec.metadata["stale"], json!(true));
+
+        // Vendredi précédent : 1 jour ouvré → frais
+        let fresh = PriceRange { latest_date: Some("2025-06-27".into()), ..range };
+        let rec = to_recommendation(fresh, &MinMaxConfig::default(), today()).unwrap();
+        assert_eq!(rec.recommendation["signal"], json!("BUY"));
+        assert_eq!(rec.metadata["stale"], json!(false));
+    }
+
+    #[test]
+    fn test_custom_180_day_lookback() {
+        let config = MinMaxConfig::from_config(&json!({
+            "lookback_days": 180, "buy_threshold": 15, "sell_threshold": 85
+        }));
+        assert_eq!(config, MinMaxConfig {
+            lookback_days: 180, buy_threshold: 15.0, sell_threshold: 85.0, ..MinMaxConfig::default()
+        });
+
+        let cutoff = config.cutoff_date(today());
+        assert_eq!(cutoff, "2025-01-01");
+
+        // Le pic de 500 date d'avant la fenêtre de 180 jours : exclu du range
+        let fixture = vec![
+            bar("AAPL", "2024-12-15", Some("500.00")),
+            bar("AAPL", "2025-01-01", Some("100.00")),
+            bar("AAPL", "2025-06-30", Some("118.00")),
+            bar("AAPL", "2025-03-01", Some("200.00")),
+        ];
+        let ranges = compute_price_ranges(&historic_bars::parse_bars(fixture), &cutoff);
+        assert_eq!(ranges[0].max_price, 200.0);
+
+        // 18% : BUY avec les seuils par défaut (20), HOLD avec buy_threshold = 15
+        let range = ranges[0].clone();
+        assert_eq!(to_recommendation(range.clone(), &MinMaxConfig::default(), today()).unwrap().recommendation["signal"], json!("BUY"));
+        let rec = to_recommendation(range, &config, today()).unwrap();
+        assert_eq!(rec.recommendation["signal"], json!("HOLD"));
+        assert_eq!(rec.metadata["calculation_period_days"], json!(180));
+    }
+
+    #[tokio::test]
+    async fn test_calculate_with_in_memory_source() {
+        let day = |offset: i64| (market_today() - Duration::days(offset)).format("%Y-%m-%d").to_string();
+        let (old, low, high, last) = (day(400), day(10), day(5), day(0));
+        let data = InMemoryMarketData::from_closes(&[
+            ("AAPL", old.as_str(), 10.0), // hors de la fenêtre d'un an
+            ("AAPL", low.as_str(), 100.0),
+            ("AAPL", high.as_str(), 200.0),
+            ("AAPL", last.as_str(), 105.0),
+        ]);
+
+        let recs = MinMaxLastYear::default()
+            .calculate_with(&["AAPL".to_string(), "MSFT".to_string()], &data)
+            .await
+            .unwrap();
+
+        assert_eq!(recs.len(), 1);
+        assert_eq!(recs[0].recommendation["signal"], json!("BUY"));
+        assert_eq!(recs[0].metadata["percentage"], json!("5.00"));
+        assert_eq!(recs[0].metadata["latest_date"], json!(last));
+    }
+
+    #[test]
+    fn test_invalid_config_falls_back_to_defaults() {
+        assert_eq!(MinMaxConfig::from_config(&Value::Null), MinMaxConfig::default());
+
+        let inverted = MinMaxConfig::from_config(&json!({"buy_threshold": 90, "sell_threshold": 10}));
+        assert_eq!((inverted.buy_threshold, inverted.sell_threshold), (BUY_THRESHOLD, SELL_THRESHOLD));
     }
 }
\ No newline at end of file
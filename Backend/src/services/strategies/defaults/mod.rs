@@ -2,4 +2,7 @@ pub mod min_max_last_year;
 pub mod rsi;
 pub mod stochastic;
 pub mod ema;
-pub mod point_pivot;
\ No newline at end of file
+pub mod point_pivot;
+pub mod squeeze;
+pub mod ema_cross;
+pub mod donchian_breakout;
\ No newline at end of file
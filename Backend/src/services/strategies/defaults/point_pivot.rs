@@ -1,10 +1,12 @@
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
+use sea_orm::DatabaseConnection;
 use serde_json::{json, Value};
 
-use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation};
-use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
-use crate::models::historic_data::{Entity as HistoricData, Column as HistoricDataColumn};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal};
+use crate::services::strategies::latest_indicators::fetch_latest_indicators;
+use crate::services::historic_bars::PriceSource;
+use crate::services::market_data::{DbMarketData, MarketDataSource};
+use tracing::info;
 
 /*
 ========================================
@@ -44,6 +46,8 @@ LOGIQUE DE LA STRATÉGIE POINT PIVOT
    - Score > 0  → BUY  (plus de supports proches que de résistances)
    - Score < 0  → SELL (plus de résistances proches que de supports)
    - Score = 0  → HOLD (équilibre ou aucun niveau proche)
+   - Confidence = |score| / 9 (plafonnée à 1) : 9 = prix sur le S3/R3 annuel
+     HOLD (score = 0) → confidence = 1
 
 EXEMPLE:
   Prix = 150.50$
@@ -53,8 +57,24 @@ EXEMPLE:
 ========================================
 */
 
+/// Score à partir duquel la confidence vaut 1 (year × S3/R3 = 3 × 3)
+const FULL_CONFIDENCE_SCORE: f64 = 9.0;
+
 pub struct PointPivotStrategy;
 
+/// Décision finale à partir du score pondéré des niveaux proches
+pub(crate) fn pivot_signal(total_score: i32) -> Signal {
+    let confidence = (total_score.abs() as f64 / FULL_CONFIDENCE_SCORE).min(1.0);
+
+    if total_score > 0 {
+        Signal::new("BUY", confidence)
+    } else if total_score < 0 {
+        Signal::new("SELL", confidence)
+    } else {
+        Signal::new("HOLD", 1.0)
+    }
+}
+
 impl PointPivotStrategy {
     /// Vérifie si le prix est "proche" d'un niveau (dans un rayon de 1%)
     fn is_close_to_level(&self, price: f64, level: f64) -> bool {
@@ -69,144 +89,125 @@ impl PointPivotStrategy {
         period_pivots: &Value,
         period_weight: i32,
     ) -> i32 {
-        let mut score = 0;
+        // (niveau, poids_niveau × direction) : +1 support → BUY, -1 résistance → SELL
+        const LEVELS: [(&str, i32); 6] = [("s3", 3), ("s2", 2), ("s1", 1), ("r1", -1), ("r2", -2), ("r3", -3)];
 
-        // Extraire les niveaux de cette période
-        let s3 = period_pivots["s3"].as_f64();
-        let s2 = period_pivots["s2"].as_f64();
-        let s1 = period_pivots["s1"].as_f64();
-        let r1 = period_pivots["r1"].as_f64();
-        let r2 = period_pivots["r2"].as_f64();
-        let r3 = period_pivots["r3"].as_f64();
-
-        // Vérifier chaque niveau de support (direction = +1 pour BUY)
-        if let Some(s3_val) = s3 {
-            if self.is_close_to_level(close, s3_val) {
-                score += period_weight * 3 * 1; // poids_période × poids_niveau × direction
-            }
-        }
-        if let Some(s2_val) = s2 {
-            if self.is_close_to_level(close, s2_val) {
-                score += period_weight * 2 * 1;
-            }
-        }
-        if let Some(s1_val) = s1 {
-            if self.is_close_to_level(close, s1_val) {
-                score += period_weight * 1 * 1;
+        let mut score = 0;
+        for (level, weight) in LEVELS {
+            if let Some(level_val) = period_pivots[level].as_f64()
+                && self.is_close_to_level(close, level_val)
+            {
+                score += period_weight * weight; // poids_période × poids_niveau × direction
             }
         }
 
-        // Vérifier chaque niveau de résistance (direction = -1 pour SELL)
-        if let Some(r1_val) = r1 {
-            if self.is_close_to_level(close, r1_val) {
-                score += period_weight * 1 * (-1); // poids_période × poids_niveau × direction
-            }
-        }
-        if let Some(r2_val) = r2 {
-            if self.is_close_to_level(close, r2_val) {
-                score += period_weight * 2 * (-1);
-            }
-        }
-        if let Some(r3_val) = r3 {
-            if self.is_close_to_level(close, r3_val) {
-                score += period_weight * 3 * (-1);
+        score
+    }
+}
+
+/// Niveaux dans le rayon de 1% du close, du plus fort au plus faible par période
+/// Ex: ["year S1 150.00", "month R1 151.00"] (utilisé par l'explication des recommandations)
+pub(crate) fn near_levels(close: f64, point_pivot: &Value) -> Vec<String> {
+    let strategy = PointPivotStrategy;
+    let mut levels = Vec::new();
+
+    for period in ["year", "month", "week"] {
+        for level in ["s3", "s2", "s1", "r1", "r2", "r3"] {
+            if let Some(value) = point_pivot[period][level].as_f64()
+                && strategy.is_close_to_level(close, value)
+            {
+                levels.push(format!("{} {} {:.2}", period, level.to_uppercase(), value));
             }
         }
-
-        score
     }
+
+    levels
 }
 
-#[async_trait]
-impl StrategyCalculator for PointPivotStrategy {
-    async fn calculate_batch(
+impl PointPivotStrategy {
+    /// Calcul sur une source de barres quelconque ; les indicateurs restent lus en BD
+    pub async fn calculate_with(
         &self,
         symbols: &[String],
+        data: &dyn MarketDataSource,
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        println!("🔄 Point Pivot Strategy: Processing {} symbols", symbols.len());
+        info!("Point Pivot Strategy: Processing {} symbols", symbols.len());
+        let price_source = PriceSource::from_env(); // Même close que les indicateurs
 
         let mut recommendations = Vec::new();
 
+        // Dernière ligne d'indicateurs de chaque symbole, en une passe
+        let latest_indicators = fetch_latest_indicators(symbols, db).await?;
+
         for symbol in symbols {
-            // Récupérer le dernier indicateur pour ce symbole
-            let latest_indicator = Indicator::find()
-                .filter(IndicatorColumn::Symbol.eq(symbol))
-                .order_by_desc(IndicatorColumn::Date)
-                .one(db)
-                .await
-                .map_err(|e| format!("Failed to fetch indicator for {}: {}", symbol, e))?;
-
-            if let Some(indicator) = latest_indicator {
+            if let Some(indicator) = latest_indicators.get(symbol) {
                 let date = &indicator.date;
 
                 // Récupérer le close du même jour
-                let historic = HistoricData::find()
-                    .filter(HistoricDataColumn::Symbol.eq(symbol))
-                    .filter(HistoricDataColumn::Date.eq(date))
-                    .one(db)
-                    .await
-                    .map_err(|e| format!("Failed to fetch historic data for {}: {}", symbol, e))?;
-
-                if let Some(historic_data) = historic {
-                    if let Some(close_str) = &historic_data.close {
-                        if let Ok(close) = close_str.parse::<f64>() {
-                            // Récupérer les point pivots (JSON)
-                            if let Some(point_pivot) = &indicator.point_pivot {
-                                let mut total_score = 0;
-
-                                // Calculer score pour year (poids = 3)
-                                if let Some(year_pivots) = point_pivot.get("year") {
-                                    if !year_pivots.is_null() && year_pivots.as_object().is_some() {
-                                        total_score += self.calculate_period_score(close, year_pivots, 3);
-                                    }
-                                }
-
-                                // Calculer score pour month (poids = 2)
-                                if let Some(month_pivots) = point_pivot.get("month") {
-                                    if !month_pivots.is_null() && month_pivots.as_object().is_some() {
-                                        total_score += self.calculate_period_score(close, month_pivots, 2);
-                                    }
-                                }
-
-                                // Calculer score pour week (poids = 1)
-                                if let Some(week_pivots) = point_pivot.get("week") {
-                                    if !week_pivots.is_null() && week_pivots.as_object().is_some() {
-                                        total_score += self.calculate_period_score(close, week_pivots, 1);
-                                    }
-                                }
-
-                                // Décision finale basée sur le score
-                                let signal = if total_score > 0 {
-                                    "BUY"
-                                } else if total_score < 0 {
-                                    "SELL"
-                                } else {
-                                    "HOLD"
-                                };
-
-                                // Créer la recommandation
-                                let recommendation = Recommendation {
-                                    symbol: symbol.clone(),
-                                    recommendation: json!(signal),
-                                    metadata: json!({
-                                        "close": close,
-                                        "total_score": total_score,
-                                        "signal_type": signal,
-                                        "date": date,
-                                        "point_pivot": point_pivot,
-                                    }),
-                                };
-
-                                recommendations.push(recommendation);
+                if let Some(bar) = data.bar_on(symbol, date).await?.map(|bar| bar.priced(price_source)) {
+                    let close = bar.close;
+
+                    // Récupérer les point pivots (JSON)
+                    if let Some(point_pivot) = &indicator.point_pivot {
+                        let mut total_score = 0;
+
+                        // Score par période (poids : year = 3, month = 2, week = 1)
+                        for (period, weight) in [("year", 3), ("month", 2), ("week", 1)] {
+                            if let Some(period_pivots) = point_pivot.get(period)
+                                && period_pivots.is_object()
+                            {
+                                total_score += self.calculate_period_score(close, period_pivots, weight);
                             }
                         }
+
+                        // Décision finale basée sur le score
+                        let signal = pivot_signal(total_score);
+
+                        // Créer la recommandation
+                        let recommendation = Recommendation {
+                            symbol: symbol.clone(),
+                            recommendation: signal.to_value(),
+                            metadata: json!({
+                                "close": close,
+                                "total_score": total_score,
+                                "signal_type": signal.signal,
+                                "date": date,
+                                "point_pivot": point_pivot,
+                            }),
+                        };
+
+                        recommendations.push(recommendation);
                     }
                 }
             }
         }
 
-        println!("✅ Point Pivot Strategy: Generated {} recommendations", recommendations.len());
+        info!("Point Pivot Strategy: Generated {} recommendations", recommendations.len());
         Ok(recommendations)
     }
+}
+
+#[async_trait]
+impl StrategyCalculator for PointPivotStrategy {
+    async fn calculate_batch(
+        &self,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        self.calculate_with(symbols, &DbMarketData::new(db), db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_pivot_recommendation_shape() {
+        // Exemple de la doc : score +1 → BUY faible
+        assert_eq!(pivot_signal(1).to_value(), json!({"signal": "BUY", "confidence": 0.11}));
+        assert_eq!(pivot_signal(-12).to_value(), json!({"signal": "SELL", "confidence": 1.0}));
+        assert_eq!(pivot_signal(0).to_value(), json!({"signal": "HOLD", "confidence": 1.0}));
+    }
 }
\ No newline at end of file
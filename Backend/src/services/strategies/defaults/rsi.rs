@@ -1,11 +1,35 @@
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
+use sea_orm::DatabaseConnection;
 use serde_json::json;
 
-use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation};
-use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, ZoneThresholds};
+use crate::services::strategies::latest_indicators::fetch_latest_indicators;
+use tracing::info;
 
-pub struct RSIStrategy;
+const RSI_BUY_THRESHOLD: f64 = 30.0;   // Zone de survente
+const RSI_SELL_THRESHOLD: f64 = 70.0;  // Zone de surachat
+
+pub const RSI_DEFAULT_THRESHOLDS: ZoneThresholds = ZoneThresholds {
+    buy_below: RSI_BUY_THRESHOLD,
+    sell_above: RSI_SELL_THRESHOLD,
+};
+
+/// Seuils paramétrables via strategy_config (voir ZoneThresholds::from_config)
+pub struct RSIStrategy {
+    thresholds: ZoneThresholds,
+}
+
+impl RSIStrategy {
+    pub fn new(thresholds: ZoneThresholds) -> Self {
+        Self { thresholds }
+    }
+}
+
+impl Default for RSIStrategy {
+    fn default() -> Self {
+        Self::new(RSI_DEFAULT_THRESHOLDS)
+    }
+}
 
 #[async_trait]
 impl StrategyCalculator for RSIStrategy {
@@ -14,42 +38,32 @@ impl StrategyCalculator for RSIStrategy {
         symbols: &[String],
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        println!("🔄 RSI Strategy: Processing {} symbols", symbols.len());
+        info!("RSI Strategy: Processing {} symbols", symbols.len());
 
         let mut recommendations = Vec::new();
 
-        // Récupérer les derniers indicateurs pour chaque symbole
+        // Dernière ligne d'indicateurs de chaque symbole, en une passe
+        let latest_indicators = fetch_latest_indicators(symbols, db).await?;
+
         for symbol in symbols {
-            // Récupérer la dernière ligne pour ce symbole
-            let latest_indicator = Indicator::find()
-                .filter(IndicatorColumn::Symbol.eq(symbol))
-                .order_by_desc(IndicatorColumn::Date)
-                .one(db)
-                .await
-                .map_err(|e| format!("Failed to fetch indicator for {}: {}", symbol, e))?;
-
-            if let Some(indicator) = latest_indicator {
+            if let Some(indicator) = latest_indicators.get(symbol) {
                 // Vérifier si RSI existe
                 if let Some(rsi_str) = &indicator.rsi25 {
                     // Parser RSI
                     if let Ok(rsi_value) = rsi_str.parse::<f64>() {
                         // Appliquer la logique de stratégie
-                        let signal = if rsi_value <= 30.0 {
-                            "BUY"
-                        } else if rsi_value >= 70.0 {
-                            "SELL"
-                        } else {
-                            "HOLD"
-                        };
+                        let signal = self.thresholds.signal(rsi_value);
 
                         // Créer la recommandation
                         let recommendation = Recommendation {
                             symbol: symbol.clone(),
-                            recommendation: json!(signal),
+                            recommendation: signal.to_value(),
                             metadata: json!({
                                 "rsi25": rsi_value,
                                 "date": indicator.date,
-                                "signal_type": signal,
+                                "signal_type": signal.signal,
+                                "buy_below": self.thresholds.buy_below,
+                                "sell_above": self.thresholds.sell_above,
                             }),
                         };
 
@@ -59,7 +73,32 @@ impl StrategyCalculator for RSIStrategy {
             }
         }
 
-        println!("✅ RSI Strategy: Generated {} recommendations", recommendations.len());
+        info!("RSI Strategy: Generated {} recommendations", recommendations.len());
         Ok(recommendations)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rsi_recommendation_shape() {
+        let rsi = RSI_DEFAULT_THRESHOLDS;
+        assert_eq!(rsi.signal(0.0).to_value(), json!({"signal": "BUY", "confidence": 1.0}));
+        assert_eq!(rsi.signal(50.0).signal, "HOLD");
+        assert_eq!(rsi.signal(100.0).to_value(), json!({"signal": "SELL", "confidence": 1.0}));
+    }
+
+    #[test]
+    fn test_custom_thresholds_change_the_signal() {
+        let tuned = ZoneThresholds::from_config(&json!({"buy_below": 45, "sell_above": 60}), RSI_DEFAULT_THRESHOLDS);
+
+        // RSI 42 : neutre avec 30/70, survente avec 45/60
+        assert_eq!(RSI_DEFAULT_THRESHOLDS.signal(42.0).signal, "HOLD");
+        assert_eq!(tuned.signal(42.0).signal, "BUY");
+        // RSI 65 : neutre avec 30/70, surachat avec 45/60
+        assert_eq!(RSI_DEFAULT_THRESHOLDS.signal(65.0).signal, "HOLD");
+        assert_eq!(tuned.signal(65.0).signal, "SELL");
+    }
 }
\ No newline at end of file
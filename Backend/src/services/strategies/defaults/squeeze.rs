@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect};
+use serde_json::json;
+
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, Signal};
+use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
+use crate::services::historic_bars::PriceSource;
+use crate::services::market_data::{DbMarketData, MarketDataSource};
+use tracing::info;
+
+// ========== CONSTANTES ==========
+const BOLLINGER_PERIOD: usize = 20;
+const BOLLINGER_STD_DEV: f64 = 2.0;
+// ================================
+
+/// Squeeze : les Bollinger Bands (20, 2σ) se contractent à l'intérieur des Keltner Channels.
+/// HOLD pendant le squeeze, BUY/SELL à la sortie selon le close vs la ligne médiane Keltner.
+pub struct SqueezeStrategy;
+
+/// Bandes d'un jour donné, utilisées pour détecter le squeeze
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SqueezeBands {
+    pub close: f64,
+    pub bb_upper: f64,
+    pub bb_lower: f64,
+    pub kc_upper: f64,
+    pub kc_middle: f64,
+    pub kc_lower: f64,
+}
+
+impl SqueezeBands {
+    /// Squeeze actif si les Bollinger sont entièrement à l'intérieur des Keltner
+    pub fn is_squeeze(&self) -> bool {
+        self.bb_upper < self.kc_upper && self.bb_lower > self.kc_lower
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SqueezeState {
+    On,       // Squeeze en cours : volatilité comprimée
+    Released, // Le squeeze vient de se relâcher : breakout
+    Off,      // Pas de squeeze
+}
+
+impl SqueezeState {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SqueezeState::On => "squeeze_on",
+            SqueezeState::Released => "released",
+            SqueezeState::Off => "no_squeeze",
+        }
+    }
+}
+
+/// Compare la veille et le jour courant pour obtenir l'état et le signal
+pub(crate) fn squeeze_signal(previous: &SqueezeBands, current: &SqueezeBands) -> (SqueezeState, &'static str) {
+    if current.is_squeeze() {
+        return (SqueezeState::On, "HOLD");
+    }
+
+    if previous.is_squeeze() {
+        // Direction du breakout : momentum du close par rapport à la ligne médiane
+        let signal = if current.close > current.kc_middle { "BUY" } else { "SELL" };
+        return (SqueezeState::Released, signal);
+    }
+
+    (SqueezeState::Off, "HOLD")
+}
+
+/// Signal standard : à la sortie du squeeze, confidence = distance du close à la médiane Keltner
+/// rapportée à la demi-largeur du canal ; HOLD pendant le squeeze = 1, HOLD hors setup = 0.5
+pub(crate) fn squeeze_recommendation(previous: &SqueezeBands, current: &SqueezeBands) -> (SqueezeState, Signal) {
+    let (state, signal) = squeeze_signal(previous, current);
+
+    let confidence = match state {
+        SqueezeState::Released => {
+            let half_width = current.kc_upper - current.kc_middle;
+            if half_width > 0.0 { (current.close - current.kc_middle).abs() / half_width } else { 1.0 }
+        }
+        SqueezeState::On => 1.0,
+        SqueezeState::Off => 0.5,
+    };
+
+    (state, Signal::new(signal, confidence))
+}
+
+/// Bollinger Bands (upper, middle, lower) sur les `period` derniers closes (ordre chronologique)
+pub(crate) fn compute_bollinger(closes: &[f64], period: usize, std_dev: f64) -> Option<(f64, f64, f64)> {
+    if period == 0 || closes.len() < period {
+        return None;
+    }
+
+    let window = &closes[closes.len() - period..];
+    let mean = window.iter().sum::<f64>() / period as f64;
+    let variance = window.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / period as f64;
+    let offset = std_dev * variance.sqrt();
+
+    Some((mean + offset, mean, mean - offset))
+}
+
+impl SqueezeStrategy {
+    /// Calcul sur une source de barres quelconque ; les indicateurs restent lus en BD
+    pub async fn calculate_with(
+        &self,
+        symbols: &[String],
+        data: &dyn MarketDataSource,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        info!("Squeeze Strategy: Processing {} symbols", symbols.len());
+        let price_source = PriceSource::from_env(); // Même close que les indicateurs
+
+        let mut recommendations = Vec::new();
+
+        for symbol in symbols {
+            // Les 2 dernières lignes d'indicateurs (jour courant + veille) pour détecter la sortie du squeeze
+            let indicators = Indicator::find()
+                .filter(IndicatorColumn::Symbol.eq(symbol))
+                .order_by_desc(IndicatorColumn::Date)
+                .limit(2)
+                .all(db)
+                .await
+                .map_err(|e| format!("Failed to fetch indicators for {}: {}", symbol, e))?;
+
+            let [current_indicator, previous_indicator] = indicators.as_slice() else {
+                continue;
+            };
+
+            // BOLLINGER_PERIOD + 1 closes jusqu'à la date de l'indicateur (Bollinger du jour et de la veille)
+            let closes: Vec<f64> = data.bars_up_to(
+                symbol,
+                current_indicator.date.as_str(),
+                (BOLLINGER_PERIOD + 1) as u64,
+            )
+            .await?
+            .into_iter()
+            .map(|bar| bar.priced(price_source).close)
+            .collect();
+
+            if closes.len() < BOLLINGER_PERIOD + 1 {
+                continue;
+            }
+
+            let bands = |closes: &[f64], indicator: &crate::models::indicator::Model| -> Option<SqueezeBands> {
+                let (bb_upper, _, bb_lower) = compute_bollinger(closes, BOLLINGER_PERIOD, BOLLINGER_STD_DEV)?;
+                Some(SqueezeBands {
+                    close: *closes.last()?,
+                    bb_upper,
+                    bb_lower,
+                    kc_upper: indicator.kc_upper.as_ref()?.parse().ok()?,
+                    kc_middle: indicator.kc_middle.as_ref()?.parse().ok()?,
+                    kc_lower: indicator.kc_lower.as_ref()?.parse().ok()?,
+                })
+            };
+
+            let (Some(current), Some(previous)) = (
+                bands(&closes, current_indicator),
+                bands(&closes[..closes.len() - 1], previous_indicator),
+            ) else {
+                continue;
+            };
+
+            let (state, signal) = squeeze_recommendation(&previous, &current);
+
+            recommendations.push(Recommendation {
+                symbol: symbol.clone(),
+                recommendation: signal.to_value(),
+                metadata: json!({
+                    "squeeze_state": state.as_str(),
+                    "squeeze_on": current.is_squeeze(),
+                    "previous_squeeze_on": previous.is_squeeze(),
+                    "close": current.close,
+                    "bb_upper": format!("{:.2}", current.bb_upper),
+                    "bb_lower": format!("{:.2}", current.bb_lower),
+                    "kc_upper": format!("{:.2}", current.kc_upper),
+                    "kc_middle": format!("{:.2}", current.kc_middle),
+                    "kc_lower": format!("{:.2}", current.kc_lower),
+                    "date": current_indicator.date,
+                }),
+            });
+        }
+
+        info!("Squeeze Strategy: Generated {} recommendations", recommendations.len());
+        Ok(recommendations)
+    }
+}
+
+#[async_trait]
+impl StrategyCalculator for SqueezeStrategy {
+    async fn calculate_batch(
+        &self,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        self.calculate_with(symbols, &DbMarketData::new(db), db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::indicators::ema::compute_ema_values;
+    use crate::services::indicators::keltner::compute_atr_values;
+
+    /// Rejoue la stratégie jour par jour sur une série (high, low, close) avec Keltner (20, 10, 2.0)
+    fn replay(series: &[(f64, f64, f64)]) -> Vec<(SqueezeState, &'static str)> {
+        let data: Vec<(String, f64, f64, f64)> = series
+            .iter()
+            .enumerate()
+            .map(|(i, (h, l, c))| (format!("day{}", i), *h, *l, *c))
+            .collect();
+        let closes: Vec<f64> = series.iter().map(|(_, _, c)| *c).collect();
+        let emas = compute_ema_values(&closes, 20);
+        let atrs = compute_atr_values(&data, 10);
+
+        let bands_at = |i: usize| -> Option<SqueezeBands> {
+            let (bb_upper, _, bb_lower) = compute_bollinger(&closes[..=i], BOLLINGER_PERIOD, BOLLINGER_STD_DEV)?;
+            let (kc_middle, atr) = (emas[i]?, atrs[i]?);
+            Some(SqueezeBands {
+                close: closes[i],
+                bb_upper,
+                bb_lower,
+                kc_upper: kc_middle + 2.0 * atr,
+                kc_middle,
+                kc_lower: kc_middle - 2.0 * atr,
+            })
+        };
+
+        (1..series.len())
+            .filter_map(|i| Some(squeeze_signal(&bands_at(i - 1)?, &bands_at(i)?)))
+            .collect()
+    }
+
+    /// 30 jours de range serré (closes quasi plats, mèches larges), puis breakout
+    fn contraction_then_expansion(direction: f64) -> Vec<(f64, f64, f64)> {
+        let mut series: Vec<(f64, f64, f64)> = (0..30)
+            .map(|i| {
+                let close = 100.0 + if i % 2 == 0 { 0.2 } else { -0.2 };
+                (close + 1.5, close - 1.5, close)
+            })
+            .collect();
+
+        let mut close = 100.0;
+        for _ in 0..5 {
+            close += direction * 4.0;
+            series.push((close + 1.0, close - 1.0, close));
+        }
+
+        series
+    }
+
+    #[test]
+    fn test_squeeze_holds_then_buys_on_upside_release() {
+        let states = replay(&contraction_then_expansion(1.0));
+
+        // Pendant la contraction : squeeze actif et HOLD
+        let (last_contraction_state, last_contraction_signal) = states[states.len() - 6];
+        assert_eq!(last_contraction_state, SqueezeState::On);
+        assert_eq!(last_contraction_signal, "HOLD");
+
+        // Expansion : le squeeze se relâche avec un signal BUY
+        let release = states.iter().find(|(state, _)| *state == SqueezeState::Released);
+        assert_eq!(release, Some(&(SqueezeState::Released, "BUY")));
+    }
+
+    #[test]
+    fn test_squeeze_sells_on_downside_release() {
+        let states = replay(&contraction_then_expansion(-1.0));
+
+        let release = states.iter().find(|(state, _)| *state == SqueezeState::Released);
+        assert_eq!(release, Some(&(SqueezeState::Released, "SELL")));
+    }
+
+    #[test]
+    fn test_squeeze_recommendation_shape() {
+        let bands = |close: f64, width: f64| SqueezeBands {
+            close,
+            bb_upper: 100.0 + width,
+            bb_lower: 100.0 - width,
+            kc_upper: 104.0,
+            kc_middle: 100.0,
+            kc_lower: 96.0,
+        };
+
+        // Squeeze actif → HOLD net
+        let (_, hold) = squeeze_recommendation(&bands(100.0, 1.0), &bands(100.0, 1.0));
+        assert_eq!(hold.to_value(), json!({"signal": "HOLD", "confidence": 1.0}));
+
+        // Sortie du squeeze, close à mi-chemin de la bande haute Keltner
+        let (state, release) = squeeze_recommendation(&bands(100.0, 1.0), &bands(102.0, 6.0));
+        assert_eq!(state, SqueezeState::Released);
+        assert_eq!(release.to_value(), json!({"signal": "BUY", "confidence": 0.5}));
+    }
+
+    #[test]
+    fn test_bollinger_on_flat_series_has_zero_width() {
+        let (upper, middle, lower) = compute_bollinger(&[10.0; 20], 20, 2.0).unwrap();
+        assert_eq!((upper, middle, lower), (10.0, 10.0, 10.0));
+        assert!(compute_bollinger(&[10.0; 5], 20, 2.0).is_none());
+    }
+}
@@ -1,11 +1,35 @@
 use async_trait::async_trait;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder};
+use sea_orm::DatabaseConnection;
 use serde_json::json;
 
-use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation};
-use crate::models::indicator::{Entity as Indicator, Column as IndicatorColumn};
+use crate::services::strategies::strategy_trait::{StrategyCalculator, Recommendation, ZoneThresholds};
+use crate::services::strategies::latest_indicators::fetch_latest_indicators;
+use tracing::info;
 
-pub struct StochasticStrategy;
+const STOCHASTIC_BUY_THRESHOLD: f64 = 20.0;   // Zone de survente
+const STOCHASTIC_SELL_THRESHOLD: f64 = 80.0;  // Zone de surachat
+
+pub const STOCHASTIC_DEFAULT_THRESHOLDS: ZoneThresholds = ZoneThresholds {
+    buy_below: STOCHASTIC_BUY_THRESHOLD,
+    sell_above: STOCHASTIC_SELL_THRESHOLD,
+};
+
+/// Seuils paramétrables via strategy_config (voir ZoneThresholds::from_config)
+pub struct StochasticStrategy {
+    thresholds: ZoneThresholds,
+}
+
+impl StochasticStrategy {
+    pub fn new(thresholds: ZoneThresholds) -> Self {
+        Self { thresholds }
+    }
+}
+
+impl Default for StochasticStrategy {
+    fn default() -> Self {
+        Self::new(STOCHASTIC_DEFAULT_THRESHOLDS)
+    }
+}
 
 #[async_trait]
 impl StrategyCalculator for StochasticStrategy {
@@ -14,42 +38,32 @@ impl StrategyCalculator for StochasticStrategy {
         symbols: &[String],
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        println!("🔄 Stochastic Strategy: Processing {} symbols", symbols.len());
+        info!("Stochastic Strategy: Processing {} symbols", symbols.len());
 
         let mut recommendations = Vec::new();
 
-        // Récupérer les derniers indicateurs pour chaque symbole
-        for symbol in symbols {
-            // Récupérer la dernière ligne pour ce symbole
-            let latest_indicator = Indicator::find()
-                .filter(IndicatorColumn::Symbol.eq(symbol))
-                .order_by_desc(IndicatorColumn::Date)
-                .one(db)
-                .await
-                .map_err(|e| format!("Failed to fetch indicator for {}: {}", symbol, e))?;
+        // Dernière ligne d'indicateurs de chaque symbole, en une passe
+        let latest_indicators = fetch_latest_indicators(symbols, db).await?;
 
-            if let Some(indicator) = latest_indicator {
+        for symbol in symbols {
+            if let Some(indicator) = latest_indicators.get(symbol) {
                 // Vérifier si Stochastic existe
                 if let Some(stoch_str) = &indicator.stochastic14_7_7 {
                     // Parser Stochastic
                     if let Ok(stoch_value) = stoch_str.parse::<f64>() {
                         // Appliquer la logique de stratégie
-                        let signal = if stoch_value <= 20.0 {
-                            "BUY"
-                        } else if stoch_value >= 80.0 {
-                            "SELL"
-                        } else {
-                            "HOLD"
-                        };
+                        let signal = self.thresholds.signal(stoch_value);
 
                         // Créer la recommandation
                         let recommendation = Recommendation {
                             symbol: symbol.clone(),
-                            recommendation: json!(signal),
+                            recommendation: signal.to_value(),
                             metadata: json!({
                                 "stochastic14_7_7": stoch_value,
                                 "date": indicator.date,
-                                "signal_type": signal,
+                                "signal_type": signal.signal,
+                                "buy_below": self.thresholds.buy_below,
+                                "sell_above": self.thresholds.sell_above,
                             }),
                         };
 
@@ -59,7 +73,20 @@ impl StrategyCalculator for StochasticStrategy {
             }
         }
 
-        println!("✅ Stochastic Strategy: Generated {} recommendations", recommendations.len());
+        info!("Stochastic Strategy: Generated {} recommendations", recommendations.len());
         Ok(recommendations)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stochastic_recommendation_shape() {
+        let stochastic = STOCHASTIC_DEFAULT_THRESHOLDS;
+        assert_eq!(stochastic.signal(0.0).to_value(), json!({"signal": "BUY", "confidence": 1.0}));
+        assert_eq!(stochastic.signal(50.0).signal, "HOLD");
+        assert_eq!(stochastic.signal(100.0).to_value(), json!({"signal": "SELL", "confidence": 1.0}));
+    }
 }
\ No newline at end of file
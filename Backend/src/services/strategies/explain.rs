@@ -0,0 +1,221 @@
+use serde_json::Value;
+
+use crate::services::strategies::defaults::point_pivot::near_levels;
+use crate::services::strategies::history_guard::insufficient_history;
+use crate::services::strategies::smoothing::suppressed_raw;
+
+/// Explication lisible d'une recommandation stockée, reconstruite depuis son metadata
+/// (ex: "RSI 18.4 ≤ 30 → BUY"). `signal` est le signal stocké dans recommendation.
+/// Si le lissage a retenu le signal précédent, l'explication porte sur le signal brut du jour.
+/// None pour une stratégie sans formateur (stratégies personnalisées) ou un metadata incomplet.
+pub fn explain(strategy_id: i32, signal: &str, metadata: &Value) -> Option<String> {
+    if let Some((bars, required)) = insufficient_history(metadata) {
+        return Some(format!("Only {} days of history ({} required) → {}", bars, required, signal));
+    }
+
+    match suppressed_raw(metadata) {
+        Some((raw, streak, days)) => Some(format!(
+            "{} (not confirmed yet, {}/{} days: {} kept)",
+            explain_signal(strategy_id, &raw, metadata)?,
+            streak,
+            days,
+            signal
+        )),
+        None => explain_signal(strategy_id, signal, metadata),
+    }
+}
+
+fn explain_signal(strategy_id: i32, signal: &str, metadata: &Value) -> Option<String> {
+    match strategy_id {
+        1 => explain_min_max(signal, metadata),
+        2 => explain_ema(signal, metadata),
+        3 => explain_zone("RSI", "rsi25", signal, metadata),
+        4 => explain_zone("Stochastic", "stochastic14_7_7", signal, metadata),
+        5 => explain_point_pivot(signal, metadata),
+        6 => explain_squeeze(signal, metadata),
+        7 => explain_ema_cross(signal, metadata),
+        8 => explain_donchian(signal, metadata),
+        _ => None,
+    }
+}
+
+/// Valeur numérique du metadata (certaines stratégies stockent des nombres formatés en texte)
+fn number(metadata: &Value, key: &str) -> Option<f64> {
+    match &metadata[key] {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+/// RSI / Stochastic : valeur comparée aux seuils de zone
+fn explain_zone(label: &str, key: &str, signal: &str, metadata: &Value) -> Option<String> {
+    let value = number(metadata, key)?;
+    let buy_below = number(metadata, "buy_below")?;
+    let sell_above = number(metadata, "sell_above")?;
+
+    Some(match signal {
+        "BUY" => format!("{} {:.1} ≤ {} → BUY", label, value, buy_below),
+        "SELL" => format!("{} {:.1} ≥ {} → SELL", label, value, sell_above),
+        _ => format!("{} {:.1} between {} and {} → {}", label, value, buy_below, sell_above, signal),
+    })
+}
+
+fn explain_min_max(signal: &str, metadata: &Value) -> Option<String> {
+    let mut explanation = format!(
+        "Price {:.2} is at {:.2}% of its range [{:.2}, {:.2}] over {} days (buy ≤ {}, sell ≥ {}) → {}",
+        number(metadata, "current_price")?,
+        number(metadata, "percentage")?,
+        number(metadata, "min_price")?,
+        number(metadata, "max_price")?,
+        metadata["calculation_period_days"].as_i64()?,
+        number(metadata, "buy_threshold")?,
+        number(metadata, "sell_threshold")?,
+        signal,
+    );
+
+    if metadata["stale"].as_bool() == Some(true) {
+        let latest = metadata["latest_date"].as_str().unwrap_or("unknown");
+        explanation.push_str(&format!(" (stale price, last close {})", latest));
+    }
+
+    Some(explanation)
+}
+
+fn explain_ema(signal: &str, metadata: &Value) -> Option<String> {
+    let close = number(metadata, "close")?;
+    let votes = metadata["signals"].as_array()?;
+
+    let parts: Vec<String> = ["ema20", "ema50", "ema200"]
+        .iter()
+        .zip(votes)
+        .map(|(key, vote)| {
+            let vote = vote.as_str().unwrap_or("N/A");
+            match number(metadata, key) {
+                Some(ema) => format!("{} {:.2} ({})", key.to_uppercase(), ema, vote),
+                None => format!("{} N/A", key.to_uppercase()),
+            }
+        })
+        .collect();
+
+    let band = number(metadata, "hold_band_pct").filter(|band| *band > 0.0);
+    Some(match band {
+        Some(band) => format!("Close {:.2} vs {} (HOLD within ±{}%) → majority {}", close, parts.join(", "), band, signal),
+        None => format!("Close {:.2} vs {} → majority {}", close, parts.join(", "), signal),
+    })
+}
+
+fn explain_point_pivot(signal: &str, metadata: &Value) -> Option<String> {
+    let close = number(metadata, "close")?;
+    let score = metadata["total_score"].as_i64()?;
+    let levels = near_levels(close, &metadata["point_pivot"]);
+
+    Some(if levels.is_empty() {
+        format!("Close {:.2} is not within 1% of any pivot level, weighted score {} → {}", close, score, signal)
+    } else {
+        format!("Close {:.2} near {}, weighted score {:+} → {}", close, levels.join(", "), score, signal)
+    })
+}
+
+fn explain_squeeze(signal: &str, metadata: &Value) -> Option<String> {
+    Some(match metadata["squeeze_state"].as_str()? {
+        "squeeze_on" => format!("Bollinger Bands inside Keltner Channels (squeeze on) → {}", signal),
+        "released" => {
+            let close = number(metadata, "close")?;
+            let middle = number(metadata, "kc_middle")?;
+            let side = if close > middle { "above" } else { "below" };
+            format!("Squeeze released, close {:.2} {} Keltner middle {:.2} → {}", close, side, middle, signal)
+        }
+        _ => format!("No squeeze → {}", signal),
+    })
+}
+
+fn explain_ema_cross(signal: &str, metadata: &Value) -> Option<String> {
+    let ema50 = number(metadata, "ema50")?;
+    let ema200 = number(metadata, "ema200")?;
+
+    Some(match metadata["cross"].as_str()? {
+        "golden_cross" => format!("Golden cross: EMA50 {:.2} crossed above EMA200 {:.2} → {}", ema50, ema200, signal),
+        "death_cross" => format!("Death cross: EMA50 {:.2} crossed below EMA200 {:.2} → {}", ema50, ema200, signal),
+        _ => {
+            let side = if ema50 > ema200 { "above" } else { "below" };
+            format!("No cross: EMA50 {:.2} stays {} EMA200 {:.2} → {}", ema50, side, ema200, signal)
+        }
+    })
+}
+
+fn explain_donchian(signal: &str, metadata: &Value) -> Option<String> {
+    let close = number(metadata, "close")?;
+    let upper = number(metadata, "previous_donchian_upper")?;
+    let lower = number(metadata, "previous_donchian_lower")?;
+
+    Some(if close > upper {
+        format!("Close {:.2} > previous Donchian upper {:.2} → {}", close, upper, signal)
+    } else if close < lower {
+        format!("Close {:.2} < previous Donchian lower {:.2} → {}", close, lower, signal)
+    } else {
+        format!("Close {:.2} inside previous Donchian channel [{:.2}, {:.2}] → {}", close, lower, upper, signal)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rsi_explanation() {
+        let metadata = json!({"rsi25": 18.42, "date": "2025-06-30", "signal_type": "BUY", "buy_below": 30.0, "sell_above": 70.0});
+        assert_eq!(explain(3, "BUY", &metadata).as_deref(), Some("RSI 18.4 ≤ 30 → BUY"));
+
+        let metadata = json!({"rsi25": 55.0, "buy_below": 30.0, "sell_above": 70.0});
+        assert_eq!(explain(3, "HOLD", &metadata).as_deref(), Some("RSI 55.0 between 30 and 70 → HOLD"));
+
+        // Metadata incomplet : pas d'explication plutôt qu'une phrase fausse
+        assert_eq!(explain(3, "BUY", &json!({"rsi25": 18.4})), None);
+    }
+
+    #[test]
+    fn test_smoothed_signal_explains_the_raw_signal() {
+        let metadata = json!({
+            "rsi25": 75.0, "buy_below": 30.0, "sell_above": 70.0,
+            "smoothing": {"confirmation_days": 2, "raw": {"signal": "SELL", "confidence": 0.17}, "raw_streak": 1, "suppressed": true}
+        });
+        assert_eq!(
+            explain(3, "BUY", &metadata).as_deref(),
+            Some("RSI 75.0 ≥ 70 → SELL (not confirmed yet, 1/2 days: BUY kept)")
+        );
+    }
+
+    #[test]
+    fn test_short_history_explanation() {
+        let metadata = json!({"reason": "insufficient_history", "history_bars": 12, "min_history_days": 200});
+        assert_eq!(explain(2, "HOLD", &metadata).as_deref(), Some("Only 12 days of history (200 required) → HOLD"));
+    }
+
+    #[test]
+    fn test_point_pivot_explanation() {
+        let point_pivot = json!({
+            "year": {"pivot": 140.0, "s1": 150.0, "s2": 130.0, "s3": 120.0, "r1": 160.0, "r2": 170.0, "r3": 180.0},
+            "month": {"pivot": 148.0, "s1": 145.0, "s2": 140.0, "s3": 135.0, "r1": 151.0, "r2": 155.0, "r3": 160.0},
+            "week": null
+        });
+        let metadata = json!({"close": 150.5, "total_score": 1, "signal_type": "BUY", "point_pivot": point_pivot});
+
+        assert_eq!(
+            explain(5, "BUY", &metadata).as_deref(),
+            Some("Close 150.50 near year S1 150.00, month R1 151.00, weighted score +1 → BUY")
+        );
+
+        let metadata = json!({"close": 100.0, "total_score": 0, "point_pivot": point_pivot});
+        assert_eq!(
+            explain(5, "HOLD", &metadata).as_deref(),
+            Some("Close 100.00 is not within 1% of any pivot level, weighted score 0 → HOLD")
+        );
+    }
+
+    #[test]
+    fn test_unknown_strategy_has_no_explanation() {
+        assert_eq!(explain(42, "BUY", &json!({"rsi25": 10.0})), None);
+    }
+}
@@ -0,0 +1,174 @@
+// Historique minimum avant d'émettre une recommandation : un symbole récemment ajouté n'a
+// que quelques barres, et une EMA200 ou un min/max sur un an calculés sur 10 jours donnent
+// des signaux BUY/SELL à forte confiance qui ne veulent rien dire.
+//
+// En dessous du seuil, la recommandation est remplacée par HOLD (confiance 0) et le metadata
+// porte reason = "insufficient_history", le nombre de barres disponibles et le seuil.
+//
+// strategy_config : {"min_history_days": 120} (barres de historicdata, 0 = pas de garde,
+// absent = défaut de la stratégie, max MAX_MIN_HISTORY_DAYS)
+
+use std::collections::HashMap;
+
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use serde_json::{json, Value};
+
+use crate::models::historic_data::{self, Entity as HistoricData};
+use crate::services::strategies::strategy_trait::{Recommendation, Signal};
+
+pub const MIN_HISTORY_DAYS_KEY: &str = "min_history_days";
+pub const MAX_MIN_HISTORY_DAYS: u32 = 2000;
+pub const INSUFFICIENT_HISTORY: &str = "insufficient_history";
+
+/// Barres nécessaires par défaut selon la plus longue fenêtre lue par la stratégie
+pub fn default_min_history_days(strategy_id: i32) -> u32 {
+    match strategy_id {
+        1 => 252, // MinMaxLastYear : une année de bourse
+        2 => 200, // EMA : jusqu'à l'EMA200
+        3 => 26,  // RSI 25
+        4 => 28,  // Stochastic 14/7/7
+        5 => 30,  // Point Pivot : pivots mensuels
+        6 => 20,  // Squeeze : Bollinger / Keltner 20
+        7 => 200, // EMA Cross : EMA50 / EMA200
+        8 => 21,  // Donchian 20 + la barre du jour
+        _ => 0,
+    }
+}
+
+/// Seuil lu dans strategy_config, sinon le défaut de la stratégie
+pub fn min_history_days(config: &Value, strategy_id: i32) -> u32 {
+    config
+        .get(MIN_HISTORY_DAYS_KEY)
+        .and_then(Value::as_u64)
+        .map(|days| days.min(MAX_MIN_HISTORY_DAYS as u64) as u32)
+        .unwrap_or_else(|| default_min_history_days(strategy_id))
+}
+
+/// Remplace la recommandation par HOLD si le symbole a moins de `min_days` barres
+pub fn hold_if_short(rec: &mut Recommendation, bars: u64, min_days: u32) {
+    if bars >= min_days as u64 {
+        return;
+    }
+
+    rec.recommendation = Signal::new("HOLD", 0.0).to_value();
+    if !rec.metadata.is_object() {
+        rec.metadata = json!({});
+    }
+    if let Value::Object(metadata) = &mut rec.metadata {
+        metadata.insert("reason".to_string(), json!(INSUFFICIENT_HISTORY));
+        metadata.insert("history_bars".to_string(), json!(bars));
+        metadata.insert(MIN_HISTORY_DAYS_KEY.to_string(), json!(min_days));
+    }
+}
+
+/// (barres disponibles, seuil) si la recommandation a été neutralisée par la garde
+pub fn insufficient_history(metadata: &Value) -> Option<(u64, u64)> {
+    if metadata["reason"].as_str() != Some(INSUFFICIENT_HISTORY) {
+        return None;
+    }
+    Some((metadata["history_bars"].as_u64()?, metadata[MIN_HISTORY_DAYS_KEY].as_u64()?))
+}
+
+/// Nombre de barres de historicdata par symbole, en une requête groupée
+pub async fn history_bars(symbols: &[String], db: &DatabaseConnection) -> Result<HashMap<String, u64>, String> {
+    let counts: Vec<(String, i64)> = HistoricData::find()
+        .select_only()
+        .column(historic_data::Column::Symbol)
+        .column_as(historic_data::Column::Date.count(), "bars")
+        .filter(historic_data::Column::Symbol.is_in(symbols.iter().cloned()))
+        .group_by(historic_data::Column::Symbol)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to count history bars: {}", e))?;
+
+    Ok(counts.into_iter().map(|(symbol, bars)| (symbol, bars.max(0) as u64)).collect())
+}
+
+/// Applique la garde à un lot de recommandations (0 = garde désactivée, aucune requête)
+pub async fn guard(recs: &mut [Recommendation], min_days: u32, db: &DatabaseConnection) -> Result<(), String> {
+    if min_days == 0 || recs.is_empty() {
+        return Ok(());
+    }
+
+    let symbols: Vec<String> = recs.iter().map(|r| r.symbol.clone()).collect();
+    let bars = history_bars(&symbols, db).await?;
+    for rec in recs.iter_mut() {
+        hold_if_short(rec, bars.get(&rec.symbol).copied().unwrap_or(0), min_days);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::strategies::strategy_trait::read_signal;
+    use sea_orm::{ConnectionTrait, Database, DatabaseBackend, Schema, Set, ActiveModelTrait};
+
+    fn rec(symbol: &str, signal: &str) -> Recommendation {
+        Recommendation {
+            symbol: symbol.to_string(),
+            recommendation: Signal::new(signal, 0.9).to_value(),
+            metadata: json!({"ema200": "12.50"}),
+        }
+    }
+
+    #[test]
+    fn min_history_days_defaults_per_strategy() {
+        assert_eq!(min_history_days(&Value::Null, 2), 200);
+        assert_eq!(min_history_days(&Value::Null, 1), 252);
+        assert_eq!(min_history_days(&json!({"min_history_days": 60}), 2), 60);
+        assert_eq!(min_history_days(&json!({"min_history_days": 0}), 1), 0);
+        assert_eq!(min_history_days(&json!({"min_history_days": 99999}), 3), MAX_MIN_HISTORY_DAYS);
+        assert_eq!(min_history_days(&Value::Null, 42), 0);
+    }
+
+    #[test]
+    fn short_history_is_held() {
+        let mut young = rec("NEWCO", "BUY");
+        hold_if_short(&mut young, 10, 200);
+        assert_eq!(read_signal(&young.recommendation).unwrap().0, "HOLD");
+        assert_eq!(young.metadata["reason"], INSUFFICIENT_HISTORY);
+        assert_eq!(insufficient_history(&young.metadata), Some((10, 200)));
+        assert_eq!(young.metadata["ema200"], "12.50");
+
+        let mut seasoned = rec("AAPL", "BUY");
+        hold_if_short(&mut seasoned, 200, 200);
+        assert_eq!(read_signal(&seasoned.recommendation).unwrap().0, "BUY");
+        assert_eq!(insufficient_history(&seasoned.metadata), None);
+    }
+
+    #[tokio::test]
+    async fn guard_counts_bars_per_symbol() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        let table = schema.create_table_from_entity(HistoricData);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+
+        for (symbol, days) in [("AAPL", 30), ("NEWCO", 5)] {
+            for day in 0..days {
+                historic_data::ActiveModel {
+                    symbol: Set(symbol.to_string()),
+                    date: Set(format!("2025-{:02}-{:02}", 1 + day / 28, 1 + day % 28)),
+                    open: Set(None),
+                    high: Set(None),
+                    low: Set(None),
+                    close: Set(Some("10".to_string())),
+                    adjusted_close: Set(None),
+                    volume: Set(None),
+                }
+                .insert(&db)
+                .await
+                .unwrap();
+            }
+        }
+
+        let mut recs = vec![rec("AAPL", "SELL"), rec("NEWCO", "BUY"), rec("GHOST", "BUY")];
+        guard(&mut recs, 20, &db).await.unwrap();
+
+        let signals: Vec<String> = recs.iter().map(|r| read_signal(&r.recommendation).unwrap().0).collect();
+        assert_eq!(signals, vec!["SELL", "HOLD", "HOLD"]);
+        assert_eq!(insufficient_history(&recs[1].metadata), Some((5, 20)));
+        assert_eq!(insufficient_history(&recs[2].metadata), Some((0, 20)));
+    }
+}
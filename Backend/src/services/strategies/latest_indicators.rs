@@ -0,0 +1,100 @@
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QuerySelect};
+use std::collections::HashMap;
+
+use crate::models::indicator::{self, Column as IndicatorColumn, Entity as Indicator};
+
+/// Symboles par chunk (2 paramètres par couple (symbol, date) dans la 2e requête)
+const LATEST_INDICATORS_CHUNK_SIZE: usize = 1000;
+
+/// Dernière ligne d'indicateurs de chaque symbole, en 2 requêtes par chunk de symboles
+/// (MAX(date) groupé par symbole, puis les lignes (symbol, date) correspondantes)
+/// au lieu d'une requête par symbole. Un symbole sans indicateurs est absent de la map.
+pub async fn fetch_latest_indicators(
+    symbols: &[String],
+    db: &DatabaseConnection,
+) -> Result<HashMap<String, indicator::Model>, String> {
+    let mut latest = HashMap::with_capacity(symbols.len());
+
+    for chunk in symbols.chunks(LATEST_INDICATORS_CHUNK_SIZE) {
+        let last_dates: Vec<(String, Option<String>)> = Indicator::find()
+            .select_only()
+            .column(IndicatorColumn::Symbol)
+            .column_as(Expr::col(IndicatorColumn::Date).max(), "max_date")
+            .filter(IndicatorColumn::Symbol.is_in(chunk.iter().cloned()))
+            .group_by(IndicatorColumn::Symbol)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch latest indicator dates: {}", e))?;
+
+        let keys: Vec<(String, String)> = last_dates
+            .into_iter()
+            .filter_map(|(symbol, date)| date.map(|date| (symbol, date)))
+            .collect();
+        if keys.is_empty() {
+            continue;
+        }
+
+        let rows = Indicator::find()
+            .filter(
+                Expr::tuple([Expr::col(IndicatorColumn::Symbol).into(), Expr::col(IndicatorColumn::Date).into()])
+                    .in_tuples(keys),
+            )
+            .all(db)
+            .await
+            .map_err(|e| format!("Failed to fetch latest indicators: {}", e))?;
+
+        latest.extend(rows.into_iter().map(|row| (row.symbol.clone(), row)));
+    }
+
+    Ok(latest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, QueryOrder, Schema, Set};
+
+    #[tokio::test]
+    async fn test_batch_matches_per_symbol_latest_rows() {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        let table = Schema::new(DatabaseBackend::Sqlite).create_table_from_entity(Indicator);
+        db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+
+        let rows = [
+            ("AAPL", "2025-01-02", "40"),
+            ("AAPL", "2025-01-03", "45"),
+            ("MSFT", "2025-01-02", "60"),
+            // Dernière date différente d'un symbole à l'autre
+            ("NVDA", "2025-01-06", "70"),
+            ("NVDA", "2024-12-31", "20"),
+        ];
+        for (symbol, date, rsi) in rows {
+            indicator::ActiveModel {
+                symbol: Set(symbol.to_string()),
+                date: Set(date.to_string()),
+                rsi25: Set(Some(rsi.to_string())),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+
+        let symbols: Vec<String> = ["AAPL", "MSFT", "NVDA", "TSLA"].iter().map(|s| String::from(*s)).collect();
+        let batch = fetch_latest_indicators(&symbols, &db).await.unwrap();
+
+        for symbol in &symbols {
+            let per_symbol = Indicator::find()
+                .filter(IndicatorColumn::Symbol.eq(symbol))
+                .order_by_desc(IndicatorColumn::Date)
+                .one(&db)
+                .await
+                .unwrap();
+            assert_eq!(batch.get(symbol), per_symbol.as_ref(), "{}", symbol);
+        }
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch["NVDA"].rsi25.as_deref(), Some("70"));
+    }
+}
@@ -1,3 +1,7 @@
 pub mod strategy_trait;
 pub mod defaults;
-// pub mod custom;  // Pour plus tard
\ No newline at end of file
+pub mod latest_indicators;
+pub mod explain;
+// pub mod custom;  // Pour plus tard
+pub mod smoothing;
+pub mod history_guard;
\ No newline at end of file
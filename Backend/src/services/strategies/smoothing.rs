@@ -0,0 +1,198 @@
+// Lissage des signaux (anti-whipsaw) : un changement de signal n'est émis que si le même
+// signal brut persiste `confirmation_days` jours de suite, sinon le signal précédent est conservé.
+//
+// strategy_results_rust ne garde qu'une ligne par stratégie / symbole : l'historique nécessaire
+// (signal brut et nombre de jours consécutifs) est donc porté par metadata.smoothing, et l'état
+// de la veille y est recopié ("previous") pour qu'un recalcul le même jour ne compte pas deux fois.
+//
+// strategy_config : {"confirmation_days": 2} (1 ou absent = pas de lissage, max MAX_CONFIRMATION_DAYS)
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::strategy_result;
+use crate::services::strategies::strategy_trait::{read_signal, Recommendation};
+
+pub const CONFIRMATION_DAYS_KEY: &str = "confirmation_days";
+pub const MAX_CONFIRMATION_DAYS: u32 = 30;
+
+/// Nombre de jours de confirmation lu dans strategy_config (1 = signal brut émis tel quel)
+pub fn confirmation_days(config: &Value) -> u32 {
+    config
+        .get(CONFIRMATION_DAYS_KEY)
+        .and_then(Value::as_u64)
+        .map(|days| days.clamp(1, MAX_CONFIRMATION_DAYS as u64) as u32)
+        .unwrap_or(1)
+}
+
+/// Dernier état émis avant aujourd'hui pour une stratégie / un symbole
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorSignal {
+    pub date: String,
+    pub recommendation: Value, // Recommandation émise ce jour-là
+    pub raw_signal: String,    // Signal brut calculé ce jour-là
+    pub raw_streak: u32,       // Jours consécutifs avec ce signal brut
+}
+
+/// État de la veille depuis la ligne stockée : la ligne elle-même si elle date d'avant `today`,
+/// sinon (recalcul le même jour) l'état qu'elle avait recopié dans metadata.smoothing.previous
+pub fn prior_signal(existing: &strategy_result::Model, today: &str) -> Option<PriorSignal> {
+    let smoothing = existing.metadata.as_ref().and_then(|m| m.get("smoothing"));
+    let date = existing.date.clone()?;
+
+    if date.as_str() >= today {
+        return smoothing
+            .and_then(|s| s.get("previous"))
+            .and_then(|previous| serde_json::from_value(previous.clone()).ok());
+    }
+
+    let recommendation = existing.recommendation.clone()?;
+    let (signal, _) = read_signal(&recommendation)?;
+    let raw_signal = smoothing
+        .and_then(|s| s["raw"]["signal"].as_str())
+        .map(str::to_string)
+        .unwrap_or(signal);
+    let raw_streak = smoothing
+        .and_then(|s| s["raw_streak"].as_u64())
+        .unwrap_or(1) as u32;
+
+    Some(PriorSignal { date, recommendation, raw_signal, raw_streak })
+}
+
+/// Applique la règle de confirmation à une recommandation fraîchement calculée :
+/// le signal brut remplace le précédent s'il est identique, s'il n'y a pas d'historique
+/// ou s'il persiste depuis `days` jours ; sinon la recommandation précédente est conservée.
+/// Le signal brut et le décompte sont ajoutés dans metadata.smoothing.
+pub fn smooth(rec: &mut Recommendation, prior: Option<PriorSignal>, days: u32) {
+    if days <= 1 {
+        return;
+    }
+    let Some((raw_signal, _)) = read_signal(&rec.recommendation) else {
+        return;
+    };
+
+    let raw_streak = match &prior {
+        Some(p) if p.raw_signal == raw_signal => p.raw_streak + 1,
+        _ => 1,
+    };
+
+    let held = prior.as_ref().filter(|p| {
+        let emitted = read_signal(&p.recommendation).map(|(signal, _)| signal);
+        emitted.as_deref() != Some(raw_signal.as_str()) && raw_streak < days
+    });
+
+    let raw = rec.recommendation.clone();
+    if let Some(p) = held {
+        rec.recommendation = p.recommendation.clone();
+    }
+
+    if let Value::Object(metadata) = &mut rec.metadata {
+        metadata.insert(
+            "smoothing".to_string(),
+            json!({
+                "confirmation_days": days,
+                "raw": raw,
+                "raw_streak": raw_streak,
+                "suppressed": held.is_some(),
+                "previous": prior,
+            }),
+        );
+    }
+}
+
+/// Signal brut retenu par le lissage, et décompte (jours consécutifs, jours requis)
+pub fn suppressed_raw(metadata: &Value) -> Option<(String, u64, u64)> {
+    let smoothing = metadata.get("smoothing")?;
+    if smoothing["suppressed"].as_bool() != Some(true) {
+        return None;
+    }
+
+    let (raw, _) = read_signal(&smoothing["raw"])?;
+    Some((raw, smoothing["raw_streak"].as_u64()?, smoothing["confirmation_days"].as_u64()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::strategies::strategy_trait::Signal;
+
+    fn rec(signal: &str) -> Recommendation {
+        Recommendation {
+            symbol: "AAPL".to_string(),
+            recommendation: Signal::new(signal, 0.5).to_value(),
+            metadata: json!({"rsi25": 50.0}),
+        }
+    }
+
+    fn emitted(rec: &Recommendation) -> String {
+        read_signal(&rec.recommendation).unwrap().0
+    }
+
+    /// Rejoue une suite de signaux bruts jour après jour, comme save_result le ferait
+    fn replay(raw_signals: &[&str], days: u32) -> Vec<String> {
+        let mut stored: Option<strategy_result::Model> = None;
+        let mut out = Vec::new();
+
+        for (i, raw) in raw_signals.iter().enumerate() {
+            let today = format!("2025-06-{:02}", i + 2);
+            let prior = stored.as_ref().and_then(|row| prior_signal(row, &today));
+            let mut r = rec(raw);
+            smooth(&mut r, prior, days);
+            out.push(emitted(&r));
+            stored = Some(strategy_result::Model {
+                strategy_id: 3,
+                symbol: Some("AAPL".to_string()),
+                date: Some(today),
+                recommendation: Some(r.recommendation),
+                metadata: Some(r.metadata),
+            });
+        }
+        out
+    }
+
+    #[test]
+    fn one_day_blip_is_suppressed() {
+        assert_eq!(replay(&["BUY", "BUY", "SELL", "BUY"], 2), vec!["BUY", "BUY", "BUY", "BUY"]);
+    }
+
+    #[test]
+    fn two_day_change_is_confirmed() {
+        assert_eq!(replay(&["BUY", "SELL", "SELL", "SELL"], 2), vec!["BUY", "BUY", "SELL", "SELL"]);
+        // Sans lissage, le signal brut est émis tel quel
+        assert_eq!(replay(&["BUY", "SELL", "BUY"], 1), vec!["BUY", "SELL", "BUY"]);
+    }
+
+    #[test]
+    fn same_day_rerun_does_not_count_twice() {
+        let yesterday = PriorSignal {
+            date: "2025-06-02".to_string(),
+            recommendation: Signal::new("BUY", 0.8).to_value(),
+            raw_signal: "BUY".to_string(),
+            raw_streak: 4,
+        };
+        let mut first = rec("SELL");
+        smooth(&mut first, Some(yesterday), 2);
+        assert_eq!(emitted(&first), "BUY");
+        assert_eq!(suppressed_raw(&first.metadata), Some(("SELL".to_string(), 1, 2)));
+
+        // Deuxième calcul le même jour : repart de l'état de la veille, le blip reste retenu
+        let row = strategy_result::Model {
+            strategy_id: 3,
+            symbol: Some("AAPL".to_string()),
+            date: Some("2025-06-03".to_string()),
+            recommendation: Some(first.recommendation),
+            metadata: Some(first.metadata),
+        };
+        let mut rerun = rec("SELL");
+        smooth(&mut rerun, prior_signal(&row, "2025-06-03"), 2);
+        assert_eq!(emitted(&rerun), "BUY");
+    }
+
+    #[test]
+    fn confirmation_days_is_bounded() {
+        assert_eq!(confirmation_days(&Value::Null), 1);
+        assert_eq!(confirmation_days(&json!({"confirmation_days": 3})), 3);
+        assert_eq!(confirmation_days(&json!({"confirmation_days": 0})), 1);
+        assert_eq!(confirmation_days(&json!({"confirmation_days": 365})), MAX_CONFIRMATION_DAYS);
+    }
+}
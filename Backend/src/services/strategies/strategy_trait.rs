@@ -1,15 +1,119 @@
 use sea_orm::DatabaseConnection;
 use serde::{Serialize, Deserialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 use async_trait::async_trait;
+use tracing::warn;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Recommendation {
     pub symbol: String,
-    pub recommendation: Value,  // JSON flexible : "BUY" ou ["BUY", "SELL", "BUY"]
+    pub recommendation: Value,  // Forme standard : {"signal": "BUY", "confidence": 0.0-1.0} (voir Signal)
     pub metadata: Value,         // JSON flexible pour les métriques spécifiques
 }
 
+/// Recommandation standard de toutes les stratégies par défaut
+/// confidence ∈ [0, 1] : profondeur dans la zone d'achat/vente (ou netteté du HOLD)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signal {
+    pub signal: String,
+    pub confidence: f64,
+}
+
+impl Signal {
+    /// confidence bornée à [0, 1] et arrondie à 2 décimales
+    pub fn new(signal: &str, confidence: f64) -> Self {
+        let confidence = if confidence.is_finite() { confidence.clamp(0.0, 1.0) } else { 0.0 };
+        Self {
+            signal: signal.to_string(),
+            confidence: (confidence * 100.0).round() / 100.0,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!(self)
+    }
+}
+
+/// Signal d'une valeur bornée [lower, upper] : BUY si <= buy, SELL si >= sell, HOLD entre les deux.
+/// confidence = profondeur dans la zone (0 au seuil, 1 à la borne) ;
+/// en HOLD, distance au seuil le plus proche rapportée à la demi-bande.
+pub fn zone_signal(value: f64, buy: f64, sell: f64, lower: f64, upper: f64) -> Signal {
+    let ratio = |distance: f64, width: f64| if width > 0.0 { distance / width } else { 1.0 };
+
+    if value <= buy {
+        Signal::new("BUY", ratio(buy - value, buy - lower))
+    } else if value >= sell {
+        Signal::new("SELL", ratio(value - sell, upper - sell))
+    } else {
+        Signal::new("HOLD", ratio((value - buy).min(sell - value), (sell - buy) / 2.0))
+    }
+}
+
+/// Seuils BUY / SELL d'un oscillateur 0-100, lus dans strategy_config : {"buy_below": 30, "sell_above": 70}.
+/// Valeur absente ou hors [0, 100] → défaut ; buy_below ≥ sell_above → les deux défauts.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ZoneThresholds {
+    pub buy_below: f64,
+    pub sell_above: f64,
+}
+
+impl ZoneThresholds {
+    pub fn from_config(config: &Value, defaults: Self) -> Self {
+        let threshold = |key: &str| config.get(key).and_then(Value::as_f64).filter(|t| (0.0..=100.0).contains(t));
+        let buy_below = threshold("buy_below").unwrap_or(defaults.buy_below);
+        let sell_above = threshold("sell_above").unwrap_or(defaults.sell_above);
+
+        if buy_below >= sell_above {
+            warn!("buy_below ({}) must be below sell_above ({}), using defaults", buy_below, sell_above);
+            return defaults;
+        }
+
+        Self { buy_below, sell_above }
+    }
+
+    /// Signal sur l'échelle 0-100 : confidence = profondeur dans la zone de survente/surachat
+    pub fn signal(&self, value: f64) -> Signal {
+        zone_signal(value, self.buy_below, self.sell_above, 0.0, 100.0)
+    }
+}
+
+/// Vote majoritaire de sous-signaux ("BUY"/"SELL", les autres valeurs sont ignorées)
+/// confidence = part des sous-signaux disponibles qui vont dans le sens retenu
+pub fn majority_signal(signals: &[&str]) -> Signal {
+    let buys = signals.iter().filter(|s| **s == "BUY").count();
+    let sells = signals.iter().filter(|s| **s == "SELL").count();
+    let available = buys + sells;
+
+    if available == 0 {
+        return Signal::new("HOLD", 0.0);
+    }
+
+    let signal = match buys.cmp(&sells) {
+        std::cmp::Ordering::Greater => "BUY",
+        std::cmp::Ordering::Less => "SELL",
+        std::cmp::Ordering::Equal => "HOLD",
+    };
+    Signal::new(signal, buys.max(sells) as f64 / available as f64)
+}
+
+/// Lit une recommandation stockée en BD : (signal, confidence)
+/// Gère aussi les anciennes formes : "BUY" (confidence inconnue) et ["BUY", "SELL", "BUY"] (vote majoritaire)
+pub fn read_signal(value: &Value) -> Option<(String, Option<f64>)> {
+    match value {
+        Value::Object(obj) => {
+            let signal = obj.get("signal")?.as_str()?.to_string();
+            Some((signal, obj.get("confidence").and_then(Value::as_f64)))
+        }
+        Value::String(s) => Some((s.clone(), None)),
+        Value::Array(items) => {
+            let signals: Vec<&str> = items.iter().filter_map(Value::as_str).collect();
+            let majority = majority_signal(&signals);
+            Some((majority.signal, Some(majority.confidence)))
+        }
+        _ => None,
+    }
+}
+
 //trait = Interface
 #[async_trait]
 pub trait StrategyCalculator {
@@ -38,4 +142,44 @@ pub trait StrategyCalculator {
         }
         Ok(results)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zone_signal_confidence_grows_into_the_zone() {
+        assert_eq!(zone_signal(30.0, 30.0, 70.0, 0.0, 100.0), Signal::new("BUY", 0.0));
+        assert_eq!(zone_signal(15.0, 30.0, 70.0, 0.0, 100.0), Signal::new("BUY", 0.5));
+        assert_eq!(zone_signal(100.0, 30.0, 70.0, 0.0, 100.0), Signal::new("SELL", 1.0));
+        assert_eq!(zone_signal(50.0, 30.0, 70.0, 0.0, 100.0), Signal::new("HOLD", 1.0));
+        assert_eq!(zone_signal(60.0, 30.0, 70.0, 0.0, 100.0), Signal::new("HOLD", 0.5));
+    }
+
+    #[test]
+    fn test_zone_thresholds_from_config_falls_back_on_invalid_values() {
+        let defaults = ZoneThresholds { buy_below: 30.0, sell_above: 70.0 };
+
+        assert_eq!(ZoneThresholds::from_config(&Value::Null, defaults), defaults);
+        assert_eq!(
+            ZoneThresholds::from_config(&json!({"buy_below": 40, "sell_above": 150}), defaults),
+            ZoneThresholds { buy_below: 40.0, sell_above: 70.0 }
+        );
+        assert_eq!(ZoneThresholds::from_config(&json!({"buy_below": 80, "sell_above": 60}), defaults), defaults);
+    }
+
+    #[test]
+    fn test_signal_shape_is_an_object() {
+        let value = Signal::new("SELL", 1.7).to_value();
+        assert_eq!(value, json!({"signal": "SELL", "confidence": 1.0}));
+    }
+
+    #[test]
+    fn test_read_signal_handles_new_and_legacy_shapes() {
+        assert_eq!(read_signal(&json!({"signal": "BUY", "confidence": 0.42})), Some(("BUY".to_string(), Some(0.42))));
+        assert_eq!(read_signal(&json!("HOLD")), Some(("HOLD".to_string(), None)));
+        assert_eq!(read_signal(&json!(["BUY", "SELL", "BUY"])), Some(("BUY".to_string(), Some(0.67))));
+        assert_eq!(read_signal(&json!(42)), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+// ============================================================================
+// SERVICE : SUIVI DES EXÉCUTIONS DE STRATÉGIES
+// ============================================================================
+//
+// Description:
+//   Écrit une ligne strategy_runs_rust autour de chaque stratégie exécutée par
+//   execute_default_strategies : "running" au début, puis "success", "empty"
+//   (aucune recommandation) ou "failed" (avec le message d'erreur) à la fin.
+//   "cancelled" = arrêt entre deux symboles (annulation du job, SIGTERM) :
+//   symbols_processed devient le nombre de symboles traités avant l'arrêt.
+//
+// Points d'attention:
+//   - Un échec d'écriture du suivi est loggé mais ne bloque jamais le calcul
+//
+// ============================================================================
+
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, Set};
+use tracing::warn;
+
+use crate::models::strategy_run::{self, Entity as StrategyRun};
+
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_SUCCESS: &str = "success";
+pub const STATUS_EMPTY: &str = "empty";
+pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_CANCELLED: &str = "cancelled";
+
+/// Statut et erreur d'une exécution terminée (`outcome` = nombre de recommandations ou erreur)
+pub fn run_status(outcome: Result<usize, &str>) -> (&'static str, Option<String>) {
+    match outcome {
+        Ok(0) => (STATUS_EMPTY, None),
+        Ok(_) => (STATUS_SUCCESS, None),
+        Err(e) => (STATUS_FAILED, Some(e.to_string())),
+    }
+}
+
+/// Ligne "running" complétée avec le résultat de l'exécution
+pub fn finished_run(run: strategy_run::Model, outcome: Result<usize, &str>, finished_at: NaiveDateTime) -> strategy_run::ActiveModel {
+    let (status, error) = run_status(outcome);
+    let mut active = run.into_active_model();
+    active.finished_at = Set(Some(finished_at));
+    active.recommendations_generated = Set(outcome.ok().map(|count| count as i32));
+    active.status = Set(status.to_string());
+    active.error = Set(error);
+    active
+}
+
+/// Ligne "running" complétée après un arrêt entre deux symboles (`saved` symboles traités)
+pub fn stopped_run(run: strategy_run::Model, saved: usize, reason: &str, finished_at: NaiveDateTime) -> strategy_run::ActiveModel {
+    let mut active = run.into_active_model();
+    active.finished_at = Set(Some(finished_at));
+    active.symbols_processed = Set(saved as i32);
+    active.recommendations_generated = Set(Some(saved as i32));
+    active.status = Set(STATUS_CANCELLED.to_string());
+    active.error = Set(Some(reason.to_string()));
+    active
+}
+
+/// Enregistre le début d'une exécution (None si l'écriture échoue)
+pub async fn start(strategy_id: i32, symbols: usize, db: &DatabaseConnection) -> Option<strategy_run::Model> {
+    let run = strategy_run::ActiveModel {
+        strategy_id: Set(strategy_id),
+        started_at: Set(Utc::now().naive_utc()),
+        symbols_processed: Set(symbols as i32),
+        status: Set(STATUS_RUNNING.to_string()),
+        ..Default::default()
+    };
+
+    match run.insert(db).await {
+        Ok(model) => Some(model),
+        Err(e) => {
+            warn!("Failed to record start of strategy {} run: {}", strategy_id, e);
+            None
+        }
+    }
+}
+
+/// Enregistre la fin d'une exécution démarrée par start()
+pub async fn finish(run: Option<strategy_run::Model>, outcome: Result<usize, &str>, db: &DatabaseConnection) {
+    let Some(run) = run else { return };
+    let strategy_id = run.strategy_id;
+    save_end(strategy_id, finished_run(run, outcome, Utc::now().naive_utc()), db).await;
+}
+
+/// Enregistre l'arrêt d'une exécution démarrée par start() après `saved` symboles
+pub async fn finish_stopped(run: Option<strategy_run::Model>, saved: usize, reason: &str, db: &DatabaseConnection) {
+    let Some(run) = run else { return };
+    let strategy_id = run.strategy_id;
+    save_end(strategy_id, stopped_run(run, saved, reason, Utc::now().naive_utc()), db).await;
+}
+
+async fn save_end(strategy_id: i32, row: strategy_run::ActiveModel, db: &DatabaseConnection) {
+    if let Err(e) = row.update(db).await {
+        warn!("Failed to record end of strategy {} run: {}", strategy_id, e);
+    }
+}
+
+/// Dernière exécution d'une stratégie
+pub async fn latest_run(strategy_id: i32, db: &DatabaseConnection) -> Result<Option<strategy_run::Model>, DbErr> {
+    StrategyRun::find()
+        .filter(strategy_run::Column::StrategyId.eq(strategy_id))
+        .order_by_desc(strategy_run::Column::StartedAt)
+        .one(db)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, 2).unwrap().and_hms_opt(22, minute, 0).unwrap()
+    }
+
+    fn running(strategy_id: i32) -> strategy_run::Model {
+        strategy_run::Model {
+            id: 1,
+            strategy_id,
+            started_at: at(0),
+            finished_at: None,
+            symbols_processed: 2000,
+            recommendations_generated: None,
+            status: STATUS_RUNNING.to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_failing_strategy_records_error_row() {
+        let row = finished_run(running(3), Err("Failed to fetch indicator for AAPL: timeout"), at(4));
+
+        assert_eq!(row.status, Set(STATUS_FAILED.to_string()));
+        assert_eq!(row.error, Set(Some("Failed to fetch indicator for AAPL: timeout".to_string())));
+        assert_eq!(row.finished_at, Set(Some(at(4))));
+        assert_eq!(row.recommendations_generated, Set(None));
+    }
+
+    #[test]
+    fn test_zero_recommendations_is_flagged_empty() {
+        assert_eq!(run_status(Ok(0)), (STATUS_EMPTY, None));
+        assert_eq!(run_status(Ok(1850)), (STATUS_SUCCESS, None));
+
+        let row = finished_run(running(6), Ok(0), at(2));
+        assert_eq!(row.recommendations_generated, Set(Some(0)));
+    }
+}
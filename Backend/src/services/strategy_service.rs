@@ -1,41 +1,63 @@
 /*
 services/
 ├─ strategy_service.rs
-│  ├─ execute_default_strategies()     ← ADMIN, 5 stratégies hardcodées
+│  ├─ execute_default_strategies()     ← ADMIN, 8 stratégies hardcodées
 │  └─ execute_custom_strategy()        ← USER, parse JSON DSL (futur)
 │
+├─ market_data.rs                     ← MarketDataSource : barres et min / max lus par les stratégies
+│
 └─ strategies/
    ├─ strategy_trait.rs                ← Interface commune
+   ├─ latest_indicators.rs             ← Dernière ligne d'indicateurs par symbole, en une passe
+   ├─ explain.rs                       ← Explication lisible d'une recommandation stockée
+   ├─ smoothing.rs                     ← Confirmation d'un changement de signal sur N jours (anti-whipsaw)
+   ├─ history_guard.rs                 ← HOLD tant qu'un symbole n'a pas assez d'historique
    ├─ defaults/                        ← Stratégies ADMIN hardcodées
    │  ├─ mod.rs
    │  ├─ min_max_last_year.rs
    │  ├─ rsi.rs
    │  ├─ stochastic.rs
    │  ├─ ema.rs
-   │  └─ point_pivot.rs
+   │  ├─ point_pivot.rs
+   │  ├─ squeeze.rs
+   │  ├─ ema_cross.rs
+   │  └─ donchian_breakout.rs
    │
    └─ custom/                           ← Interpréteur JSON DSL (futur)
       ├─ mod.rs
       └─ dsl_executor.rs                ← Parse strategy_config
 */
-use sea_orm::{DatabaseConnection, Set, ActiveModelTrait, EntityTrait, QueryFilter, ColumnTrait, IntoActiveModel};
-use chrono::Local;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{DatabaseConnection, Set, ActiveModelTrait, EntityTrait, QueryFilter, ColumnTrait, IntoActiveModel, ConnectionTrait, Statement};
+use serde::Serialize;
+use crate::utils::dates::market_today;
 
 use crate::services::strategies::{
-    strategy_trait::{StrategyCalculator, Recommendation},
+    strategy_trait::{StrategyCalculator, Recommendation, ZoneThresholds},
+    smoothing,
+    history_guard,
     defaults::{
-        min_max_last_year::MinMaxLastYear,
-        rsi::RSIStrategy,
-        stochastic::StochasticStrategy,
+        min_max_last_year::{MinMaxConfig, MinMaxLastYear},
+        rsi::{RSIStrategy, RSI_DEFAULT_THRESHOLDS},
+        stochastic::{StochasticStrategy, STOCHASTIC_DEFAULT_THRESHOLDS},
         ema::EMAStrategy,
         point_pivot::PointPivotStrategy,
+        squeeze::SqueezeStrategy,
+        ema_cross::EMACrossStrategy,
+        donchian_breakout::DonchianBreakoutStrategy,
     },
 };
 use crate::services::indicator_service::IndicatorService;
+use crate::services::calculation_jobs;
+use crate::services::strategy_run_service;
+use crate::services::subscription_service::PlanLimits;
 use crate::models::{
     strategy_result::{self, Entity as StrategyResult},
-    stock::Entity as Stock,
+    strategy_result_history::{self, Entity as StrategyResultHistory},
+    stock::{self, Entity as Stock},
+    strategy::{self, Entity as Strategy},
 };
+use tracing::{info, warn};
 
 pub struct StrategyService;
 
@@ -47,102 +69,85 @@ impl StrategyService {
     }
 
     // FLOW 1: ADMIN - Stratégies par défaut hardcodées
+    // `plan` : plafond de symboles du plan de l'appelant (None = run admin, sans plafond) ;
+    //          seul POST /api/admin/strategies/calculate appelle ce flux, toujours avec None (réservé à un futur appelant utilisateur)
+    // `selection` : stratégies à exécuter et dans quel ordre, recalcul des indicateurs ou non
     pub async fn execute_default_strategies(
         &self,
+        plan: Option<&PlanLimits>,
+        selection: &StrategySelection,
         db: &DatabaseConnection,
     ) -> Result<Vec<Recommendation>, String> {
-        println!("🚀 Starting strategy execution");
+        info!("Starting strategy execution: {:?}", selection.strategies);
 
-        // 1. Récupérer tous les symboles
+        // 1. Récupérer tous les symboles (sauf les tickers délistés, is_alive = false)
         let stocks = Stock::find()
             .all(db)
             .await
             .map_err(|e| format!("Failed to fetch stocks: {}", e))?;
 
-        let symbols: Vec<String> = stocks
-            .into_iter()
-            .filter_map(|s| s.symbol_alphavantage)
-            .collect();
-
-        println!("📊 Found {} symbols", symbols.len());
-
-        // 2. Calculer les indicateurs (RSI, EMA, Stochastic, point_pivot)
-        let indicator_service = IndicatorService::new();
-        indicator_service.calculate_all_indicators(symbols.clone(), db).await?;
+        let symbols: Vec<String> = stock::alive_symbols(stocks);
 
-        println!("✅ Indicators calculated");
+        info!("Found {} symbols", symbols.len());
 
-        // 3. Exécuter les stratégies
-        let mut all_results = Vec::new();
-
-        // ============================================================================
-        // STRATÉGIE 1 : MinMaxLastYear (strategy_id = 1)
-        // ============================================================================
-        println!("📊 Executing MinMaxLastYear strategy...");
-        let min_max_calc = MinMaxLastYear;
-        let min_max_recs = min_max_calc.calculate_batch(&symbols, db).await?;
-        println!("✅ Calculated {} recommendations for MinMaxLastYear", min_max_recs.len());
-
-        for rec in min_max_recs {
-            save_result(1, &rec.symbol, &rec, db).await?;
-            all_results.push(rec);
+        if let Some(plan) = plan {
+            plan.check_symbol_cap(symbols.len())?;
         }
 
-        // ============================================================================
-        // STRATÉGIE 2 : EMA (strategy_id = 2) ← CORRECTION ICI
-        // ============================================================================
-        println!("📊 Executing EMA strategy...");
-        let ema_calc = EMAStrategy;
-        let ema_recs = ema_calc.calculate_batch(&symbols, db).await?;
-        println!("✅ Calculated {} recommendations for EMA", ema_recs.len());
+        // 2. Calculer les indicateurs (RSI, EMA, Stochastic, point_pivot), sauf s'ils sont déjà à jour
+        if selection.skip_indicators {
+            info!("Indicator recompute skipped");
+        } else {
+            let indicator_service = IndicatorService::new();
+            indicator_service.calculate_all_indicators(symbols.clone(), plan, db).await?;
 
-        for rec in ema_recs {
-            save_result(2, &rec.symbol, &rec, db).await?;  // ← CHANGÉ DE 4 À 2
-            all_results.push(rec);
+            info!("Indicators calculated");
+            checkpoint("indicators", 0)?;
         }
 
-        // ============================================================================
-        // STRATÉGIE 3 : RSI (strategy_id = 3) ← CORRECTION ICI
-        // ============================================================================
-        println!("📊 Executing RSI strategy...");
-        let rsi_calc = RSIStrategy;
-        let rsi_recs = rsi_calc.calculate_batch(&symbols, db).await?;
-        println!("✅ Calculated {} recommendations for RSI", rsi_recs.len());
+        // 3. Exécuter les stratégies, dans l'ordre demandé
+        let mut all_results = Vec::new();
 
-        for rec in rsi_recs {
-            save_result(3, &rec.symbol, &rec, db).await?;  // ← CHANGÉ DE 2 À 3
-            all_results.push(rec);
+        for (i, strategy_type) in selection.strategies.iter().enumerate() {
+            let (name, recs) = run_default_strategy(strategy_type, &symbols, db).await?;
+            all_results.extend(recs);
+            if i + 1 < selection.strategies.len() {
+                checkpoint(name, all_results.len())?;
+            }
         }
 
-        // ============================================================================
-        // STRATÉGIE 4 : Stochastic (strategy_id = 4) ← CORRECTION ICI
-        // ============================================================================
-        println!("📊 Executing Stochastic strategy...");
-        let stoch_calc = StochasticStrategy;
-        let stoch_recs = stoch_calc.calculate_batch(&symbols, db).await?;
-        println!("✅ Calculated {} recommendations for Stochastic", stoch_recs.len());
+        info!("Strategy execution completed: {} total recommendations", all_results.len());
 
-        for rec in stoch_recs {
-            save_result(4, &rec.symbol, &rec, db).await?;  // ← CHANGÉ DE 3 À 4
-            all_results.push(rec);
-        }
-
-        // ============================================================================
-        // STRATÉGIE 5 : Point Pivot (strategy_id = 5)
-        // ============================================================================
-        println!("📊 Executing Point Pivot strategy...");
-        let pivot_calc = PointPivotStrategy;
-        let pivot_recs = pivot_calc.calculate_batch(&symbols, db).await?;
-        println!("✅ Calculated {} recommendations for Point Pivot", pivot_recs.len());
+        Ok(all_results)
+    }
 
-        for rec in pivot_recs {
-            save_result(5, &rec.symbol, &rec, db).await?;
-            all_results.push(rec);
-        }
+    // SIMULATION : une stratégie par défaut avec un config fourni, calculée en mémoire
+    // Rien n'est écrit (ni strategy_results_rust, ni strategy_runs_rust)
+    pub async fn simulate_default_strategy(
+        &self,
+        strategy_type: &str,
+        config: &serde_json::Value,
+        symbols: &[String],
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Recommendation>, String> {
+        let (strategy_id, _) = default_strategy(strategy_type)?;
+        info!("Simulating {} strategy on {} symbols", strategy_type, symbols.len());
 
-        println!("✅ Strategy execution completed: {} total recommendations", all_results.len());
+        let mut recs = match strategy_type {
+            "min_max_last_year" => MinMaxLastYear::new(MinMaxConfig::from_config(config)).calculate_batch(symbols, db).await,
+            "ema" => EMAStrategy::from_config(config).calculate_batch(symbols, db).await,
+            "rsi" => RSIStrategy::new(ZoneThresholds::from_config(config, RSI_DEFAULT_THRESHOLDS)).calculate_batch(symbols, db).await,
+            "stochastic" => StochasticStrategy::new(ZoneThresholds::from_config(config, STOCHASTIC_DEFAULT_THRESHOLDS)).calculate_batch(symbols, db).await,
+            "point_pivot" => PointPivotStrategy.calculate_batch(symbols, db).await,
+            "squeeze" => SqueezeStrategy.calculate_batch(symbols, db).await,
+            "ema_cross" => EMACrossStrategy.calculate_batch(symbols, db).await,
+            "donchian_breakout" => DonchianBreakoutStrategy.calculate_batch(symbols, db).await,
+            other => Err(format!("Unknown strategy type: {}", other)),
+        }?;
 
-        Ok(all_results)
+        // Même garde d'historique que le run réel
+        history_guard::guard(&mut recs, history_guard::min_history_days(config, strategy_id), db).await?;
+        Ok(recs)
     }
 
     // FLOW 2: USER - Stratégies custom via JSON DSL (futur)
@@ -158,14 +163,330 @@ impl StrategyService {
     }
 }
 
-// Fonction helper pour sauvegarder un résultat dans strategy_results_test
+/// Stratégies par défaut : (id utilisé par save_result(), type accepté par
+/// POST /api/strategies/simulate et POST /api/admin/strategies/calculate, nom dans strategies_rust)
+pub const DEFAULT_STRATEGIES: [(i32, &str, &str); 8] = [
+    (1, "min_max_last_year", "MinMaxLastYear"),
+    (2, "ema", "EMA"),
+    (3, "rsi", "RSI"),
+    (4, "stochastic", "Stochastic"),
+    (5, "point_pivot", "Point Pivot"),
+    (6, "squeeze", "Squeeze"),
+    (7, "ema_cross", "EMA Cross"),
+    (8, "donchian_breakout", "Donchian Breakout"),
+];
+
+const SEED_OWNER: &str = "system";
+
+/// Types de DEFAULT_STRATEGIES, dans l'ordre des ids (seuils paramétrables : min_max_last_year, rsi, stochastic)
+pub const SIMULATION_TYPES: [&str; 8] = {
+    let mut types = [""; 8];
+    let mut i = 0;
+    while i < types.len() {
+        types[i] = DEFAULT_STRATEGIES[i].1;
+        i += 1;
+    }
+    types
+};
+
+/// (id, nom) de la stratégie par défaut d'un type ; erreur si le type est inconnu
+pub fn default_strategy(strategy_type: &str) -> Result<(i32, &'static str), String> {
+    DEFAULT_STRATEGIES
+        .iter()
+        .find(|(_, t, _)| *t == strategy_type)
+        .map(|&(id, _, name)| (id, name))
+        .ok_or_else(|| format!("Unknown strategy type: {}", strategy_type))
+}
+
+/// Stratégies exécutées par execute_default_strategies : types de SIMULATION_TYPES, dans l'ordre d'exécution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategySelection {
+    pub strategies: Vec<&'static str>,
+    /// true = indicateurs déjà à jour, pas de recalcul avant les stratégies
+    pub skip_indicators: bool,
+}
+
+impl StrategySelection {
+    /// Les 8 stratégies dans l'ordre des ids, avec recalcul des indicateurs
+    pub fn all() -> Self {
+        Self { strategies: SIMULATION_TYPES.to_vec(), skip_indicators: false }
+    }
+
+    /// Valide les types demandés (None = toutes) ; l'ordre est conservé, les doublons ignorés
+    pub fn parse(names: Option<&[String]>, skip_indicators: bool) -> Result<Self, String> {
+        let Some(names) = names else {
+            return Ok(Self { skip_indicators, ..Self::all() });
+        };
+        if names.is_empty() {
+            return Err("strategies must not be empty".to_string());
+        }
+
+        let mut strategies = Vec::with_capacity(names.len());
+        let mut unknown = Vec::new();
+        for name in names {
+            match SIMULATION_TYPES.iter().find(|t| **t == name.trim()) {
+                Some(t) if !strategies.contains(t) => strategies.push(*t),
+                Some(_) => {}
+                None => unknown.push(name.as_str()),
+            }
+        }
+
+        if !unknown.is_empty() {
+            return Err(format!(
+                "Unknown strategies: {} (expected one of: {})",
+                unknown.join(", "),
+                SIMULATION_TYPES.join(", ")
+            ));
+        }
+        Ok(Self { strategies, skip_indicators })
+    }
+}
+
+/// Action à effectuer sur une ligne par défaut de strategies_rust
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeedAction {
+    Insert { id: i32, name: &'static str },
+    Rename { id: i32, name: &'static str },
+    Unchanged { id: i32 },
+    /// L'id est occupé par une ligne qui n'appartient pas à "system" (created_by NULL compris) : on ne l'écrase pas
+    Conflict { id: i32, existing_name: Option<String> },
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SeedReport {
+    pub inserted: Vec<i32>,
+    pub updated: Vec<i32>,
+    pub unchanged: Vec<i32>,
+    pub conflicts: Vec<i32>,
+}
+
+/// Compare les lignes existantes aux stratégies par défaut (aucun accès DB)
+pub fn plan_seed(existing: &[strategy::Model]) -> Vec<SeedAction> {
+    DEFAULT_STRATEGIES
+        .iter()
+        .map(|&(id, _, name)| match existing.iter().find(|s| s.id == id) {
+            None => SeedAction::Insert { id, name },
+            Some(row) if row.name.as_deref() == Some(name) => SeedAction::Unchanged { id },
+            Some(row) if row.created_by.as_deref() == Some(SEED_OWNER) => SeedAction::Rename { id, name },
+            Some(row) => SeedAction::Conflict { id, existing_name: row.name.clone() },
+        })
+        .collect()
+}
+
+/// Insère / renomme les stratégies par défaut (idempotent).
+/// strategy_config existant est conservé ; la séquence d'id est réalignée après les insertions explicites.
+pub async fn seed_default_strategies(db: &DatabaseConnection) -> Result<SeedReport, String> {
+    let ids: Vec<i32> = DEFAULT_STRATEGIES.iter().map(|(id, _, _)| *id).collect();
+    let existing = Strategy::find()
+        .filter(strategy::Column::Id.is_in(ids))
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch default strategies: {}", e))?;
+
+    let mut report = SeedReport::default();
+
+    for action in plan_seed(&existing) {
+        match action {
+            SeedAction::Insert { id, name } => {
+                strategy::ActiveModel {
+                    id: Set(id),
+                    name: Set(Some(name.to_string())),
+                    created_by: Set(Some(SEED_OWNER.to_string())),
+                    is_public: Set(Some(true)),
+                    created_at: Set(Some(chrono::Utc::now().naive_utc())),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+                .map_err(|e| format!("Failed to insert strategy {}: {}", id, e))?;
+                report.inserted.push(id);
+            }
+            SeedAction::Rename { id, name } => {
+                strategy::ActiveModel {
+                    id: Set(id),
+                    name: Set(Some(name.to_string())),
+                    created_by: Set(Some(SEED_OWNER.to_string())),
+                    is_public: Set(Some(true)),
+                    ..Default::default()
+                }
+                .update(db)
+                .await
+                .map_err(|e| format!("Failed to update strategy {}: {}", id, e))?;
+                report.updated.push(id);
+            }
+            SeedAction::Unchanged { id } => report.unchanged.push(id),
+            SeedAction::Conflict { id, existing_name } => {
+                warn!("Strategy id {} is used by a custom strategy ({:?}), not seeded", id, existing_name);
+                report.conflicts.push(id);
+            }
+        }
+    }
+
+    if !report.inserted.is_empty() {
+        db.execute(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT setval(pg_get_serial_sequence('strategies_rust', 'id'), \
+             GREATEST((SELECT MAX(id) FROM strategies_rust), 1))",
+        ))
+        .await
+        .map_err(|e| format!("Failed to realign strategies_rust id sequence: {}", e))?;
+    }
+
+    info!(
+        "Default strategies seeded: {} inserted, {} updated, {} unchanged, {} conflicts",
+        report.inserted.len(),
+        report.updated.len(),
+        report.unchanged.len(),
+        report.conflicts.len()
+    );
+
+    Ok(report)
+}
+
+// Point de contrôle entre deux étapes : si un arrêt (SIGTERM) ou l'annulation du job est demandé,
+// on s'arrête ici. Les résultats déjà sauvegardés restent valides ; le prochain calcul reprend tout (UPSERT).
+fn checkpoint(completed_step: &str, saved_results: usize) -> Result<(), String> {
+    calculation_jobs::record_progress(saved_results);
+
+    if let Some(reason) = calculation_jobs::stop_reason() {
+        return Err(format!(
+            "{}: stopped after {} ({} recommendations saved, partial run)",
+            reason, completed_step, saved_results
+        ));
+    }
+    Ok(())
+}
+
+// Fonction helper pour lire strategy_config d'une stratégie par défaut (Null si absente)
+async fn load_strategy_config(strategy_id: i32, db: &DatabaseConnection) -> Result<serde_json::Value, String> {
+    let strategy = Strategy::find_by_id(strategy_id)
+        .one(db)
+        .await
+        .map_err(|e| format!("Failed to fetch strategy {}: {}", strategy_id, e))?;
+
+    Ok(strategy
+        .and_then(|s| s.strategy_config)
+        .unwrap_or(serde_json::Value::Null))
+}
+
+// Exécute une stratégie par défaut à partir de son type (id et nom lus dans DEFAULT_STRATEGIES)
+// Retourne le nom de la stratégie (pour le checkpoint) et ses recommandations sauvegardées
+async fn run_default_strategy(
+    strategy_type: &str,
+    symbols: &[String],
+    db: &DatabaseConnection,
+) -> Result<(&'static str, Vec<Recommendation>), String> {
+    let (strategy_id, name) = default_strategy(strategy_type)?;
+    info!("Executing {} strategy...", name);
+
+    let recs = match strategy_type {
+        // Seuils paramétrables via strategy_config
+        "min_max_last_year" => {
+            let config = load_strategy_config(strategy_id, db).await?;
+            let calc = MinMaxLastYear::new(MinMaxConfig::from_config(&config));
+            run_strategy(strategy_id, name, &calc, symbols, db).await
+        }
+        "ema" => {
+            let config = load_strategy_config(strategy_id, db).await?;
+            run_strategy(strategy_id, name, &EMAStrategy::from_config(&config), symbols, db).await
+        }
+        "rsi" => {
+            let config = load_strategy_config(strategy_id, db).await?;
+            let calc = RSIStrategy::new(ZoneThresholds::from_config(&config, RSI_DEFAULT_THRESHOLDS));
+            run_strategy(strategy_id, name, &calc, symbols, db).await
+        }
+        "stochastic" => {
+            let config = load_strategy_config(strategy_id, db).await?;
+            let calc = StochasticStrategy::new(ZoneThresholds::from_config(&config, STOCHASTIC_DEFAULT_THRESHOLDS));
+            run_strategy(strategy_id, name, &calc, symbols, db).await
+        }
+        // Sans paramètres
+        "point_pivot" => run_strategy(strategy_id, name, &PointPivotStrategy, symbols, db).await,
+        "squeeze" => run_strategy(strategy_id, name, &SqueezeStrategy, symbols, db).await,
+        "ema_cross" => run_strategy(strategy_id, name, &EMACrossStrategy, symbols, db).await,
+        "donchian_breakout" => run_strategy(strategy_id, name, &DonchianBreakoutStrategy, symbols, db).await,
+        other => Err(format!("Unknown strategy type: {}", other)),
+    }?;
+
+    Ok((name, recs))
+}
+
+// Exécute une stratégie, sauvegarde ses résultats et trace l'exécution dans strategy_runs_rust
+// Les signaux sont lissés selon strategy_config.confirmation_days (voir strategies::smoothing)
+async fn run_strategy<S: StrategyCalculator + Sync>(
+    strategy_id: i32,
+    name: &str,
+    calculator: &S,
+    symbols: &[String],
+    db: &DatabaseConnection,
+) -> Result<Vec<Recommendation>, String> {
+    run_strategy_until(strategy_id, name, calculator, symbols, calculation_jobs::stop_reason, db).await
+}
+
+// Corps de run_strategy ; `stop_reason` est consulté avant chaque symbole (arrêt du serveur, annulation du job)
+async fn run_strategy_until<S: StrategyCalculator + Sync>(
+    strategy_id: i32,
+    name: &str,
+    calculator: &S,
+    symbols: &[String],
+    mut stop_reason: impl FnMut() -> Option<&'static str>,
+    db: &DatabaseConnection,
+) -> Result<Vec<Recommendation>, String> {
+    let run = strategy_run_service::start(strategy_id, symbols.len(), db).await;
+
+    let outcome = async {
+        let config = load_strategy_config(strategy_id, db).await?;
+        let confirmation_days = smoothing::confirmation_days(&config);
+        let mut recs = calculator.calculate_batch(symbols, db).await?;
+        history_guard::guard(&mut recs, history_guard::min_history_days(&config, strategy_id), db).await?;
+        info!("Calculated {} recommendations for {}", recs.len(), name);
+
+        let mut stopped = None;
+        for (saved, rec) in recs.iter_mut().enumerate() {
+            // Arrêt ou annulation entre deux symboles : on garde ce qui est déjà sauvegardé
+            if let Some(reason) = stop_reason() {
+                warn!("{}: {} results saved for {}/{} symbols", reason, name, saved, recs.len());
+                stopped = Some((saved, reason));
+                break;
+            }
+            save_result(strategy_id, rec, confirmation_days, db).await?;
+        }
+
+        if let Some((saved, _)) = stopped {
+            recs.truncate(saved);
+        }
+        Ok::<_, String>((recs, stopped.map(|(_, reason)| reason)))
+    }
+    .await;
+
+    match outcome {
+        Ok((recs, Some(reason))) => {
+            strategy_run_service::finish_stopped(run, recs.len(), reason, db).await;
+            Ok(recs)
+        }
+        Ok((recs, None)) => {
+            strategy_run_service::finish(run, Ok(recs.len()), db).await;
+            Ok(recs)
+        }
+        Err(e) => {
+            strategy_run_service::finish(run, Err(&e), db).await;
+            Err(e)
+        }
+    }
+}
+
+// Fonction helper pour sauvegarder un résultat dans strategy_results_rust
+// `rec` est lissé avant l'écriture (signal précédent conservé tant que le changement n'est pas confirmé)
 async fn save_result(
     strategy_id: i32,
-    symbol: &str,
-    rec: &Recommendation,
+    rec: &mut Recommendation,
+    confirmation_days: u32,
     db: &DatabaseConnection,
 ) -> Result<(), String> {
-    let today = Local::now().naive_local().date().format("%Y-%m-%d").to_string();
+    let symbol = rec.symbol.clone();
+    let symbol = symbol.as_str();
+    // Date de bourse (MARKET_TZ), pas la date locale du serveur
+    let today = market_today().format("%Y-%m-%d").to_string();
 
     // 1. Chercher si un enregistrement existe déjà
     let existing = StrategyResult::find()
@@ -175,10 +496,14 @@ async fn save_result(
         .await
         .map_err(|e| format!("Failed to query existing result: {}", e))?;
 
+    let prior = existing.as_ref().and_then(|row| smoothing::prior_signal(row, &today));
+    smoothing::smooth(rec, prior, confirmation_days);
+
     match existing {
         // 2a. Si existe → UPDATE
         Some(existing_model) => {
             let mut active_model: strategy_result::ActiveModel = existing_model.into_active_model();
+            active_model.date = Set(Some(today.clone()));
             active_model.recommendation = Set(Some(rec.recommendation.clone()));
             active_model.metadata = Set(Some(rec.metadata.clone()));
 
@@ -192,10 +517,9 @@ async fn save_result(
             let new_model = strategy_result::ActiveModel {
                 strategy_id: Set(strategy_id),
                 symbol: Set(Some(symbol.to_string())),
-                date: Set(Some(today)),
+                date: Set(Some(today.clone())),
                 recommendation: Set(Some(rec.recommendation.clone())),
                 metadata: Set(Some(rec.metadata.clone())),
-                ..Default::default()
             };
 
             new_model.insert(db)
@@ -204,5 +528,249 @@ async fn save_result(
         }
     }
 
+    // 3. Historique : une ligne par jour de bourse (un recalcul le même jour la remplace)
+    let history_row = strategy_result_history::ActiveModel {
+        strategy_id: Set(strategy_id),
+        symbol: Set(symbol.to_string()),
+        date: Set(today),
+        recommendation: Set(Some(rec.recommendation.clone())),
+        metadata: Set(Some(rec.metadata.clone())),
+    };
+    StrategyResultHistory::insert(history_row)
+        .on_conflict(
+            OnConflict::columns([
+                strategy_result_history::Column::StrategyId,
+                strategy_result_history::Column::Symbol,
+                strategy_result_history::Column::Date,
+            ])
+            .update_columns([
+                strategy_result_history::Column::Recommendation,
+                strategy_result_history::Column::Metadata,
+            ])
+            .to_owned(),
+        )
+        .exec(db)
+        .await
+        .map_err(|e| format!("Failed to save result history: {}", e))?;
+
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn row(id: i32, name: Option<&str>, created_by: Option<&str>) -> strategy::Model {
+        strategy::Model {
+            id,
+            name: name.map(str::to_string),
+            created_by: created_by.map(str::to_string),
+            shared_with: None,
+            is_public: None,
+            strategy_config: None,
+            created_at: None,
+        }
+    }
+
+    // Applique le plan comme le ferait seed_default_strategies, en mémoire
+    fn apply(existing: Vec<strategy::Model>) -> Vec<strategy::Model> {
+        let mut rows = existing;
+        for action in plan_seed(&rows.clone()) {
+            match action {
+                SeedAction::Insert { id, name } => rows.push(row(id, Some(name), Some(SEED_OWNER))),
+                SeedAction::Rename { id, name } => {
+                    let target = rows.iter_mut().find(|r| r.id == id).unwrap();
+                    target.name = Some(name.to_string());
+                }
+                SeedAction::Unchanged { .. } | SeedAction::Conflict { .. } => {}
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_names_resolve_after_seeding() {
+        let rows = apply(vec![row(3, None, Some("system")), row(5, Some("Old pivot"), Some("system"))]);
+
+        // Même résolution que get_stocks_with_strategies
+        let names: HashMap<i32, String> = rows
+            .into_iter()
+            .filter_map(|s| s.name.map(|name| (s.id, name)))
+            .collect();
+
+        for (id, _, name) in DEFAULT_STRATEGIES {
+            assert_eq!(names.get(&id).map(String::as_str), Some(name));
+        }
+    }
+
+    #[test]
+    fn test_seed_is_idempotent_and_keeps_user_strategies() {
+        let seeded = apply(vec![]);
+        assert!(plan_seed(&seeded).iter().all(|a| matches!(a, SeedAction::Unchanged { .. })));
+
+        let actions = plan_seed(&[row(2, Some("My strategy"), Some("alice"))]);
+        assert_eq!(actions[1], SeedAction::Conflict { id: 2, existing_name: Some("My strategy".to_string()) });
+        assert_eq!(actions[0], SeedAction::Insert { id: 1, name: "MinMaxLastYear" });
+
+        // Sans propriétaire : rien ne prouve que la ligne vient du seed, elle n'est pas renommée
+        let actions = plan_seed(&[row(4, Some("Legacy"), None), row(5, Some("Old pivot"), Some("system"))]);
+        assert_eq!(actions[3], SeedAction::Conflict { id: 4, existing_name: Some("Legacy".to_string()) });
+        assert_eq!(actions[4], SeedAction::Rename { id: 5, name: "Point Pivot" });
+    }
+
+    #[test]
+    fn test_selection_validates_names_and_keeps_order() {
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(StrategySelection::parse(None, false), Ok(StrategySelection::all()));
+        let selection = StrategySelection::parse(Some(&names(&["rsi", "ema", "rsi"])), true).unwrap();
+        assert_eq!(selection, StrategySelection { strategies: vec!["rsi", "ema"], skip_indicators: true });
+
+        assert!(StrategySelection::parse(Some(&names(&["rsi", "macd"])), false).unwrap_err().contains("macd"));
+        assert!(StrategySelection::parse(Some(&[]), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulation_resolves_ids_from_the_default_table() {
+        assert_eq!(default_strategy("min_max_last_year"), Ok((1, "MinMaxLastYear")));
+        assert_eq!(default_strategy("donchian_breakout"), Ok((8, "Donchian Breakout")));
+        assert_eq!(SIMULATION_TYPES[2], "rsi");
+
+        // Type inconnu : erreur avant tout accès BD (plus d'id 0 par défaut)
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+        let err = StrategyService::new()
+            .simulate_default_strategy("macd", &serde_json::Value::Null, &["AAPL".to_string()], &db)
+            .await
+            .unwrap_err();
+        assert_eq!(err, "Unknown strategy type: macd");
+    }
+
+    #[tokio::test]
+    async fn test_run_rejects_unknown_types_before_writing() {
+        let db = sea_orm::Database::connect("sqlite::memory:").await.unwrap();
+
+        let err = run_default_strategy("macd", &["AAPL".to_string()], &db).await.unwrap_err();
+        assert_eq!(err, "Unknown strategy type: macd");
+    }
+
+    #[tokio::test]
+    async fn test_only_selected_strategies_write_results() {
+        use crate::models::{historic_data, indicator, strategy_run};
+        use sea_orm::{Database, DatabaseBackend, Schema};
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(Stock),
+            schema.create_table_from_entity(Strategy),
+            schema.create_table_from_entity(StrategyResult),
+            schema.create_table_from_entity(StrategyResultHistory),
+            schema.create_table_from_entity(strategy_run::Entity),
+            schema.create_table_from_entity(indicator::Entity),
+            schema.create_table_from_entity(historic_data::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        stock::ActiveModel {
+            compagny_name: Set("Apple".to_string()),
+            symbol_alphavantage: Set(Some("AAPL".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        // De quoi produire un signal RSI, Stochastic et EMA
+        indicator::ActiveModel {
+            symbol: Set("AAPL".to_string()),
+            date: Set("2025-01-02".to_string()),
+            rsi25: Set(Some("25".to_string())),
+            stochastic14_7_7: Set(Some("90".to_string())),
+            ema20: Set(Some("10".to_string())),
+            ema50: Set(Some("11".to_string())),
+            ema200: Set(Some("12".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        // RSI seul, sans recalcul des indicateurs
+        let selection = StrategySelection::parse(Some(&["rsi".to_string()]), true).unwrap();
+        let recs = StrategyService::new().execute_default_strategies(None, &selection, &db).await.unwrap();
+        assert_eq!(recs.len(), 1);
+
+        let results = StrategyResult::find().all(&db).await.unwrap();
+        assert_eq!(results.iter().map(|r| r.strategy_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(results[0].symbol.as_deref(), Some("AAPL"));
+        let history = StrategyResultHistory::find().all(&db).await.unwrap();
+        assert_eq!(history.iter().map(|r| r.strategy_id).collect::<Vec<_>>(), vec![3]);
+        let runs = strategy_run::Entity::find().all(&db).await.unwrap();
+        assert_eq!(runs.iter().map(|r| r.strategy_id).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_stops_between_symbols() {
+        use crate::models::strategy_run;
+        use crate::services::strategies::strategy_trait::Signal;
+        use sea_orm::{Database, DatabaseBackend, Schema};
+
+        // Un HOLD par symbole, sans lire la BD
+        struct HoldAll;
+
+        #[async_trait::async_trait]
+        impl StrategyCalculator for HoldAll {
+            async fn calculate_batch(&self, symbols: &[String], _db: &DatabaseConnection) -> Result<Vec<Recommendation>, String> {
+                Ok(symbols
+                    .iter()
+                    .map(|symbol| Recommendation {
+                        symbol: symbol.clone(),
+                        recommendation: Signal::new("HOLD", 0.5).to_value(),
+                        metadata: serde_json::json!({}),
+                    })
+                    .collect())
+            }
+        }
+
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(Strategy),
+            schema.create_table_from_entity(StrategyResultHistory),
+            schema.create_table_from_entity(strategy_run::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        // L'entité ne déclare que strategy_id comme clé : une ligne par (stratégie, symbole) comme en production
+        db.execute_unprepared(&format!(
+            "CREATE TABLE {} (strategy_id INTEGER NOT NULL, symbol TEXT, date TEXT, recommendation TEXT, metadata TEXT,
+             PRIMARY KEY (strategy_id, symbol))",
+            crate::models::tables::STRATEGY_RESULTS
+        ))
+        .await
+        .unwrap();
+
+        // Annulation demandée après le 2e symbole sur 5 (id 42 : pas de garde d'historique)
+        let symbols: Vec<String> = ["A", "B", "C", "D", "E"].iter().map(|s| s.to_string()).collect();
+        let mut checks = 0;
+        let stop = || {
+            checks += 1;
+            (checks > 2).then_some("Cancelled")
+        };
+        let recs = run_strategy_until(42, "Hold all", &HoldAll, &symbols, stop, &db).await.unwrap();
+        assert_eq!(recs.iter().map(|r| r.symbol.as_str()).collect::<Vec<_>>(), vec!["A", "B"]);
+
+        let results = StrategyResult::find().all(&db).await.unwrap();
+        assert_eq!(results.len(), 2);
+
+        let run = strategy_run_service::latest_run(42, &db).await.unwrap().unwrap();
+        assert_eq!(run.status, strategy_run_service::STATUS_CANCELLED);
+        assert_eq!(run.symbols_processed, 2);
+        assert_eq!(run.recommendations_generated, Some(2));
+        assert_eq!(run.error.as_deref(), Some("Cancelled"));
+        assert!(run.finished_at.is_some());
+    }
+}
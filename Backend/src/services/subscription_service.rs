@@ -0,0 +1,236 @@
+// ============================================================================
+// SERVICE : ABONNEMENTS ET LIMITES PAR PLAN
+// ============================================================================
+//
+// Description:
+//   Lit les limites du plan de l'utilisateur (abonnements_rust) et les
+//   applique à la création de stratégies personnalisées et aux calculs
+//   d'indicateurs / stratégies lancés pour un utilisateur.
+//
+// Points d'attention:
+//   - max_strategies / max_symbols à NULL = illimité
+//   - User sans abonnement (ou plan introuvable) → plan par défaut
+//     (DEFAULT_ABONNEMENT_ID, 1 = Free)
+//
+// ============================================================================
+
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryFilter, ColumnTrait};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use crate::models::{
+    abonnement::{self, Entity as Abonnement},
+    strategy::{self, Entity as Strategy},
+    users::Entity as User,
+};
+
+/// Plan attribué aux nouveaux users (register, Google OAuth)
+pub const DEFAULT_ABONNEMENT_ID: i32 = 1;
+
+/// Plan par défaut configuré via DEFAULT_ABONNEMENT_ID
+pub fn default_abonnement_id() -> i32 {
+    std::env::var("DEFAULT_ABONNEMENT_ID")
+        .ok()
+        .and_then(|v| v.parse::<i32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_ABONNEMENT_ID)
+}
+
+/// Limites numériques d'un plan (None = illimité)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanLimits {
+    pub plan_name: String,
+    pub max_strategies: Option<i32>,
+    pub max_symbols: Option<i32>,
+}
+
+impl From<abonnement::Model> for PlanLimits {
+    fn from(plan: abonnement::Model) -> Self {
+        Self {
+            plan_name: plan.name,
+            max_strategies: plan.max_strategies,
+            max_symbols: plan.max_symbols,
+        }
+    }
+}
+
+impl PlanLimits {
+    /// Vérifie qu'une nouvelle stratégie avec `symbol_count` symboles respecte le plan
+    pub fn check_new_strategy(&self, existing_strategies: u64, symbol_count: usize) -> Result<(), String> {
+        if let Some(max) = self.max_strategies
+            && existing_strategies >= max.max(0) as u64
+        {
+            return Err(format!(
+                "Strategy limit reached for plan {} ({} max)",
+                self.plan_name, max
+            ));
+        }
+
+        if let Some(max) = self.max_symbols
+            && symbol_count > max.max(0) as usize
+        {
+            return Err(format!(
+                "Too many symbols for plan {} ({} max per strategy, got {})",
+                self.plan_name, max, symbol_count
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Nombre total de symboles calculables pour ce plan (max_strategies × max_symbols,
+    /// 150 pour Free), None si l'une des deux limites est illimitée
+    pub fn symbol_cap(&self) -> Option<usize> {
+        match (self.max_strategies, self.max_symbols) {
+            (Some(strategies), Some(symbols)) => Some(strategies.max(0) as usize * symbols.max(0) as usize),
+            _ => None,
+        }
+    }
+
+    /// Vérifie qu'un calcul sur `requested` symboles respecte le plan
+    pub fn check_symbol_cap(&self, requested: usize) -> Result<(), String> {
+        match self.symbol_cap() {
+            Some(cap) if requested > cap => Err(format!(
+                "Too many symbols for plan {}: {} requested, {} allowed",
+                self.plan_name, requested, cap
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Consommation d'un quota (limit / remaining à null = illimité)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuotaUsage {
+    pub used: u64,
+    pub limit: Option<i32>,
+    pub remaining: Option<u64>,
+}
+
+/// Plan + quotas de l'utilisateur, tels qu'affichés par le frontend (/me)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlanUsage {
+    pub plan: String,
+    pub strategies: QuotaUsage,
+    // used = symboles distincts sur toutes les stratégies, limit = max par stratégie
+    pub symbols_per_strategy: QuotaUsage,
+}
+
+impl PlanLimits {
+    /// `largest_strategy` = nombre de symboles de la plus grosse stratégie de l'utilisateur
+    pub fn usage(&self, strategies_used: u64, distinct_symbols: u64, largest_strategy: u64) -> PlanUsage {
+        let remaining = |limit: Option<i32>, used: u64| limit.map(|max| (max.max(0) as u64).saturating_sub(used));
+
+        PlanUsage {
+            plan: self.plan_name.clone(),
+            strategies: QuotaUsage {
+                used: strategies_used,
+                limit: self.max_strategies,
+                remaining: remaining(self.max_strategies, strategies_used),
+            },
+            symbols_per_strategy: QuotaUsage {
+                used: distinct_symbols,
+                limit: self.max_symbols,
+                remaining: remaining(self.max_symbols, largest_strategy),
+            },
+        }
+    }
+}
+
+/// Symboles d'une stratégie personnalisée (stockés dans strategy_config.symbols)
+fn strategy_symbols(config: Option<&serde_json::Value>) -> Vec<String> {
+    config
+        .and_then(|c| c.get("symbols"))
+        .and_then(|s| s.as_array())
+        .map(|symbols| symbols.iter().filter_map(|s| s.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+pub struct SubscriptionService;
+
+impl SubscriptionService {
+    /// Limites du plan de l'utilisateur (plan par défaut si aucun)
+    pub async fn limits_for_user(db: &DatabaseConnection, user_id: i32) -> Result<PlanLimits, DbErr> {
+        let abonnement_id = User::find_by_id(user_id)
+            .one(db)
+            .await?
+            .and_then(|user| user.abonnement_id)
+            .unwrap_or_else(default_abonnement_id);
+
+        let plan = match Abonnement::find_by_id(abonnement_id).one(db).await? {
+            Some(plan) => Some(plan),
+            None => Abonnement::find_by_id(default_abonnement_id()).one(db).await?,
+        };
+
+        plan.map(PlanLimits::from)
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Subscription plan {} not found", abonnement_id)))
+    }
+
+    /// Plan + consommation des quotas (stratégies créées par `username`)
+    pub async fn usage_for_user(db: &DatabaseConnection, user_id: i32, username: &str) -> Result<PlanUsage, DbErr> {
+        let limits = Self::limits_for_user(db, user_id).await?;
+
+        let strategies = Strategy::find()
+            .filter(strategy::Column::CreatedBy.eq(username))
+            .all(db)
+            .await?;
+
+        let per_strategy: Vec<Vec<String>> = strategies
+            .iter()
+            .map(|s| strategy_symbols(s.strategy_config.as_ref()))
+            .collect();
+
+        let distinct: BTreeSet<&String> = per_strategy.iter().flatten().collect();
+        let largest = per_strategy.iter().map(Vec::len).max().unwrap_or(0);
+
+        Ok(limits.usage(strategies.len() as u64, distinct.len() as u64, largest as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free() -> PlanLimits {
+        PlanLimits { plan_name: "Free".to_string(), max_strategies: Some(10), max_symbols: Some(15) }
+    }
+
+    fn pro() -> PlanLimits {
+        PlanLimits { plan_name: "Pro".to_string(), max_strategies: None, max_symbols: Some(100) }
+    }
+
+    #[test]
+    fn test_free_user_hits_strategy_limit() {
+        assert!(free().check_new_strategy(9, 5).is_ok());
+        assert!(free().check_new_strategy(10, 5).is_err());
+        assert!(free().check_new_strategy(0, 16).is_err());
+    }
+
+    #[test]
+    fn test_usage_remaining_quotas() {
+        let usage = free().usage(3, 20, 12);
+
+        assert_eq!(usage.plan, "Free");
+        assert_eq!(usage.strategies, QuotaUsage { used: 3, limit: Some(10), remaining: Some(7) });
+        assert_eq!(usage.symbols_per_strategy.remaining, Some(3));
+        assert_eq!(pro().usage(50, 0, 0).strategies.remaining, None);
+    }
+
+    #[test]
+    fn test_symbol_cap_rejects_above_and_accepts_at_cap() {
+        assert_eq!(free().symbol_cap(), Some(150));
+        assert!(free().check_symbol_cap(150).is_ok());
+        assert_eq!(
+            free().check_symbol_cap(151).unwrap_err(),
+            "Too many symbols for plan Free: 151 requested, 150 allowed"
+        );
+        assert_eq!(pro().symbol_cap(), None);
+        assert!(pro().check_symbol_cap(5000).is_ok());
+    }
+
+    #[test]
+    fn test_pro_user_does_not_hit_free_limits() {
+        assert!(pro().check_new_strategy(10, 16).is_ok());
+        assert!(pro().check_new_strategy(500, 100).is_ok());
+    }
+}
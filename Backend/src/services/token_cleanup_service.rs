@@ -0,0 +1,112 @@
+// ============================================================================
+// SERVICE : PURGE DES TOKENS EXPIRÉS
+// ============================================================================
+//
+// Description:
+//   Supprime les tokens devenus inutiles dans password_reset_tokens_rust et
+//   email_verification_tokens_rust (rien d'autre ne les supprime).
+//
+// Règles:
+//   - Reset password : token expiré (expires_at < now)
+//   - Vérification email : token expiré, ou utilisé et créé il y a plus de
+//     USED_TOKEN_RETENTION_DAYS jours
+//
+// Points d'attention:
+//   - Appelé par POST /api/admin/maintenance/purge-tokens et périodiquement
+//     par main (TOKEN_PURGE_INTERVAL_HOURS, 0 = désactivé)
+//
+// ============================================================================
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, DbErr, DeleteMany, EntityTrait, QueryFilter};
+use serde::Serialize;
+
+use crate::models::{email_verification_tokens, password_reset_tokens};
+
+/// Les tokens de vérification utilisés sont conservés 30 jours (traçabilité)
+pub const USED_TOKEN_RETENTION_DAYS: i64 = 30;
+/// Intervalle par défaut de la purge automatique (heures)
+pub const DEFAULT_PURGE_INTERVAL_HOURS: u64 = 24;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PurgeReport {
+    pub password_reset_tokens: u64,
+    pub email_verification_tokens: u64,
+}
+
+/// Intervalle de la purge automatique via TOKEN_PURGE_INTERVAL_HOURS (None si 0)
+pub fn purge_interval_from_env() -> Option<std::time::Duration> {
+    let hours = std::env::var("TOKEN_PURGE_INTERVAL_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_PURGE_INTERVAL_HOURS);
+
+    (hours > 0).then(|| std::time::Duration::from_secs(hours * 3600))
+}
+
+fn used_cutoff(now: NaiveDateTime) -> NaiveDateTime {
+    now - Duration::days(USED_TOKEN_RETENTION_DAYS)
+}
+
+/// DELETE des tokens de reset expirés
+fn reset_purge_query(now: NaiveDateTime) -> DeleteMany<password_reset_tokens::Entity> {
+    password_reset_tokens::Entity::delete_many()
+        .filter(password_reset_tokens::Column::ExpiresAt.lt(now))
+}
+
+/// DELETE des tokens de vérification expirés, ou utilisés et plus vieux que la rétention
+fn verification_purge_query(now: NaiveDateTime) -> DeleteMany<email_verification_tokens::Entity> {
+    email_verification_tokens::Entity::delete_many().filter(
+        Condition::any()
+            .add(email_verification_tokens::Column::ExpiresAt.lt(now))
+            .add(
+                Condition::all()
+                    .add(email_verification_tokens::Column::Used.eq(true))
+                    .add(email_verification_tokens::Column::CreatedAt.lt(used_cutoff(now))),
+            ),
+    )
+}
+
+/// Supprime les tokens expirés des deux tables et renvoie le nombre de lignes supprimées par table
+pub async fn purge_tokens(db: &DatabaseConnection) -> Result<PurgeReport, DbErr> {
+    let now = Utc::now().naive_utc();
+
+    let reset = reset_purge_query(now).exec(db).await?;
+    let verification = verification_purge_query(now).exec(db).await?;
+
+    Ok(PurgeReport {
+        password_reset_tokens: reset.rows_affected,
+        email_verification_tokens: verification.rows_affected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn reset_tokens_purged_only_when_expired() {
+        let sql = reset_purge_query(now()).build(DbBackend::Postgres).to_string();
+
+        assert_eq!(
+            sql,
+            r#"DELETE FROM "password_reset_tokens_rust" WHERE "password_reset_tokens_rust"."expires_at" < '2025-06-01 12:00:00.000000'"#
+        );
+    }
+
+    #[test]
+    fn verification_tokens_purged_when_expired_or_used_long_ago() {
+        let sql = verification_purge_query(now()).build(DbBackend::Postgres).to_string();
+
+        assert_eq!(
+            sql,
+            r#"DELETE FROM "email_verification_tokens_rust" WHERE "email_verification_tokens_rust"."expires_at" < '2025-06-01 12:00:00.000000' OR ("email_verification_tokens_rust"."used" = TRUE AND "email_verification_tokens_rust"."created_at" < '2025-05-02 12:00:00.000000')"#
+        );
+    }
+}
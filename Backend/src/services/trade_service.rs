@@ -1,37 +1,507 @@
 use sea_orm::*;
 use rust_decimal::Decimal;
-use chrono::NaiveDate;
-use crate::models::{trade, trades_fermes, stock};
-use crate::models::dto::CreateTradeRequest;
+use crate::models::{trade, trades_fermes, stock, users, wallet};
+use crate::models::dto::{CreateTradeRequest, FifoRecalculationResponse, FifoStateSummary, PreviewSaleRequest, SalePreviewLot, SalePreviewResponse, TradeValidationResponse, TradingPreferences};
+use crate::services::analytics_service::realized_pnl;
 use crate::services::wallet_service::WalletService;
+use crate::services::audit_service::AuditService;
+use crate::services::emergency_stop_service::EmergencyStopService;
+use crate::utils::currency;
+use crate::utils::money;
+use crate::utils::dates::parse_trade_date;
+use crate::utils::trading_calendar::{self, Exchange};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+
+/// Résultat d'un rejeu FIFO complet (sans effet en BD)
+#[derive(Debug, PartialEq)]
+pub struct FifoReplay {
+    /// buy_id → quantite_restante attendue
+    pub remaining: HashMap<i32, Decimal>,
+    /// (buy_id, sale_id, quantité fermée), dans l'ordre chronologique
+    pub closures: Vec<(i32, i32, Decimal)>,
+}
+
+/// Rejoue tous les trades d'un utilisateur en FIFO, par symbole et par ordre chronologique
+/// (date parsée puis id). Erreur si une vente dépasse la quantité détenue à ce moment.
+pub fn replay_fifo(trades: &[trade::Model]) -> Result<FifoReplay, String> {
+    // Une date illisible serait triée en tête et fausserait l'ordre FIFO : refusée
+    let mut ordered = Vec::with_capacity(trades.len());
+    for t in trades {
+        match t.date.as_deref().and_then(parse_trade_date) {
+            Some(date) => ordered.push((date, t)),
+            None => return Err(format!("Trade {} has an unparseable date '{}'", t.id, t.date.as_deref().unwrap_or_default())),
+        }
+    }
+    ordered.sort_by_key(|(date, t)| (*date, t.id));
+
+    let mut remaining: HashMap<i32, Decimal> = HashMap::new();
+    let mut open_lots: HashMap<String, Vec<i32>> = HashMap::new(); // symbol → buy ids (FIFO)
+    let mut closures = Vec::new();
+
+    for (_, t) in ordered {
+        let symbol = t.symbol.clone().unwrap_or_default();
+        let quantite = t.quantite.unwrap_or_default();
+
+        match t.trade_type.as_deref() {
+            Some("achat") => {
+                remaining.insert(t.id, quantite);
+                open_lots.entry(symbol).or_default().push(t.id);
+            }
+            Some("vente") => {
+                let mut to_close = quantite;
+
+                for buy_id in open_lots.entry(symbol.clone()).or_default().iter() {
+                    if to_close <= Decimal::ZERO {
+                        break;
+                    }
+                    let available = remaining[buy_id];
+                    if available <= Decimal::ZERO {
+                        continue;
+                    }
+                    let closed = to_close.min(available);
+                    remaining.insert(*buy_id, available - closed);
+                    closures.push((*buy_id, t.id, closed));
+                    to_close -= closed;
+                }
+
+                if to_close > Decimal::ZERO {
+                    return Err(format!(
+                        "Sale {} of {} {} exceeds the quantity held at that date ({} missing)",
+                        t.id, quantite, symbol, to_close
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(FifoReplay { remaining, closures })
+}
+
+/// Décimales conservées pour pourcentage_gain des trades fermés
+pub const GAIN_PERCENTAGE_DECIMALS: u32 = 2;
+
+/// Gain en % entre le prix d'achat et le prix de vente, arrondi à GAIN_PERCENTAGE_DECIMALS
+/// selon money::ROUNDING_MODE (0 si le prix d'achat est nul)
+pub fn gain_percentage(buy_price: Decimal, sale_price: Decimal) -> Decimal {
+    money::round(money::percentage_change(buy_price, sale_price), GAIN_PERCENTAGE_DECIMALS)
+}
+
+/// Entrée wallet d'un P&L réalisé : ("gain", gain) si positif ou nul, sinon ("perte", |gain|)
+pub fn realized_pnl_action(gain: Decimal) -> (&'static str, Decimal) {
+    if gain < Decimal::ZERO {
+        ("perte", -gain)
+    } else {
+        ("gain", gain)
+    }
+}
+
+/// Durée de détention en séances de bourse de `exchange` entre un achat et une vente (week-ends et
+/// jours fériés exclus), jamais négative (0 si une des dates est illisible ou si la vente précède l'achat)
+pub fn holding_days(date_achat: &str, date_vente: &str, exchange: Exchange) -> i32 {
+    match (parse_trade_date(date_achat), parse_trade_date(date_vente)) {
+        (Some(achat), Some(vente)) => trading_calendar::trading_days_between(achat, vente, exchange),
+        _ => 0,
+    }
+}
+
+/// Vérifie qu'une vente n'est pas datée avant le plus ancien lot d'achat ouvert du symbole
+pub fn validate_sale_date(sale_date: &str, open_buy_lots: &[trade::Model]) -> Result<(), String> {
+    let sale = parse_trade_date(sale_date)
+        .ok_or_else(|| format!("Invalid sale date: {}", sale_date))?;
+
+    let earliest_buy = open_buy_lots
+        .iter()
+        .filter_map(|t| t.date.as_deref().and_then(parse_trade_date))
+        .min();
+
+    match earliest_buy {
+        Some(buy) if sale < buy => Err(format!(
+            "Sale date {} is before the earliest open buy lot ({})",
+            sale_date,
+            buy.format("%Y-%m-%d")
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Raison pour laquelle un trade ne peut pas être supprimé sans casser l'historique FIFO
+/// `closed_trades` = nombre de trades fermés qui référencent ce trade
+pub fn deletion_blocker(t: &trade::Model, closed_trades: usize) -> Option<String> {
+    match t.trade_type.as_deref() {
+        Some("achat") if closed_trades > 0 || t.quantite_restante < t.quantite.unwrap_or_default() => {
+            Some(format!("Buy lot {} has already been consumed by a sale", t.id))
+        }
+        Some("vente") if closed_trades > 0 => {
+            Some(format!("Sale {} has already closed buy lots", t.id))
+        }
+        _ => None,
+    }
+}
+
+/// Vérifie les contraintes de lot du stock : quantité multiple de `lot_size` et montant
+/// (quantité × prix unitaire) d'au moins `min_lot`. Contrainte absente (ou lot_size ≤ 0) = ignorée
+pub fn check_lot_constraints(
+    symbol: &str,
+    quantity: Decimal,
+    unit_price: Decimal,
+    lot_size: Option<Decimal>,
+    min_lot: Option<Decimal>,
+) -> Result<(), String> {
+    if let Some(lot_size) = lot_size.filter(|l| *l > Decimal::ZERO)
+        && !(quantity % lot_size).is_zero()
+    {
+        return Err(format!(
+            "Quantity {} of {} is not a multiple of the lot size {} (nearest valid quantities: {} or {})",
+            quantity,
+            symbol,
+            lot_size.normalize(),
+            ((quantity / lot_size).floor() * lot_size).normalize(),
+            ((quantity / lot_size).ceil() * lot_size).normalize()
+        ));
+    }
+
+    let notional = quantity * unit_price;
+    if let Some(min_lot) = min_lot
+        && notional < min_lot
+    {
+        return Err(format!(
+            "Trade amount {} for {} is below the minimum of {}",
+            notional.normalize(),
+            symbol,
+            min_lot.normalize()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Refuse un achat qui ouvrirait un nouveau symbole au-delà de `max_open_positions`
+/// `open_symbols` = symboles avec une quantite_restante > 0 ; renforcer une position existante est toujours permis
+pub fn check_open_positions_limit(
+    open_symbols: &HashSet<String>,
+    symbol: &str,
+    max_open_positions: Option<i32>,
+) -> Result<(), String> {
+    let Some(max) = max_open_positions else {
+        return Ok(());
+    };
+
+    if open_symbols.contains(&symbol.trim().to_uppercase()) || open_symbols.len() < max.max(0) as usize {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Open positions limit reached: {} symbols held ({} max), cannot open a position in {}",
+        open_symbols.len(),
+        max,
+        symbol
+    ))
+}
+
+/// Frais estimés d'un trade : aucun modèle de frais n'est appliqué (prix_total = quantité × prix)
+pub const ESTIMATED_FEES: Decimal = Decimal::ZERO;
+
+/// Données lues en BD pour valider un trade sans l'écrire
+#[derive(Debug, Default)]
+pub struct TradeContext {
+    pub currency: Option<String>,         // None = aucune devise saisie et stock introuvable
+    pub treasury: Decimal,                // Trésorerie actuelle de la devise
+    pub open_buy_lots: Vec<trade::Model>, // Lots d'achat ouverts du symbole, du plus ancien au plus récent
+    pub emergency_stop_armed: bool,
+    pub open_symbols: HashSet<String>,    // Symboles détenus (achats seulement)
+    pub max_open_positions: Option<i32>,
+    pub lot_size: Option<Decimal>,        // Contraintes de lot du stock (None = aucune)
+    pub min_lot: Option<Decimal>,
+}
+
+/// Quantité détenue et capital investi libéré si `quantity` est vendue en FIFO sur ces lots
+fn fifo_release(open_buy_lots: &[trade::Model], quantity: Decimal) -> (Decimal, Decimal) {
+    let held = open_buy_lots.iter().map(|lot| lot.quantite_restante).sum();
+    let (matches, _) = match_sale_fifo(open_buy_lots, quantity);
+    let released = matches
+        .iter()
+        .map(|(lot, closed)| *closed * lot.prix_unitaire.unwrap_or_default())
+        .sum();
+
+    (held, released)
+}
+
+/// Lots consommés par la vente de `quantity` en FIFO : (lot, quantité fermée) du plus ancien au plus
+/// récent, et quantité que les lots ouverts ne couvrent pas (0 si la vente est couverte)
+pub fn match_sale_fifo(open_buy_lots: &[trade::Model], quantity: Decimal) -> (Vec<(&trade::Model, Decimal)>, Decimal) {
+    let mut remaining = quantity;
+    let mut matches = Vec::new();
+
+    for lot in open_buy_lots {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let closed = remaining.min(lot.quantite_restante);
+        if closed > Decimal::ZERO {
+            matches.push((lot, closed));
+            remaining -= closed;
+        }
+    }
+
+    (matches, remaining.max(Decimal::ZERO))
+}
+
+/// Simule la vente de `quantity` à `sale_price` le `sale_date` sur les lots ouverts d'un symbole :
+/// mêmes gains, pourcentages et temps_jours que les trades fermés qu'écrirait create_trade.
+/// `exchanges[i]` est la place de cotation de `open_buy_lots[i]`. Aucune écriture.
+pub fn preview_sale(
+    symbol: &str,
+    open_buy_lots: &[trade::Model],
+    exchanges: &[Exchange],
+    quantity: Decimal,
+    sale_price: Decimal,
+    sale_date: &str,
+) -> Result<SalePreviewResponse, String> {
+    validate_sale_date(sale_date, open_buy_lots)?;
+
+    let (matches, uncovered) = match_sale_fifo(open_buy_lots, quantity);
+    if uncovered > Decimal::ZERO {
+        return Err(format!(
+            "Attempted to sell {} units of {} but only {} units are held. \
+             Short selling is not currently supported.",
+            quantity, symbol, quantity - uncovered
+        ));
+    }
+
+    let exchange_of: HashMap<i32, Exchange> = open_buy_lots
+        .iter()
+        .zip(exchanges)
+        .map(|(lot, exchange)| (lot.id, *exchange))
+        .collect();
+
+    let lots: Vec<SalePreviewLot> = matches
+        .into_iter()
+        .map(|(lot, closed)| {
+            let buy_price = lot.prix_unitaire.unwrap_or_default();
+            let date_achat = lot.date.clone().unwrap_or_default();
+            let exchange = exchange_of.get(&lot.id).copied().unwrap_or(Exchange::WeekdaysOnly);
+            SalePreviewLot {
+                trade_achat_id: lot.id,
+                temps_jours: holding_days(&date_achat, sale_date, exchange),
+                date_achat,
+                quantite: closed,
+                prix_achat: buy_price,
+                gain_dollars: (sale_price - buy_price) * closed,
+                pourcentage_gain: gain_percentage(buy_price, sale_price),
+            }
+        })
+        .collect();
+
+    let cost_basis: Decimal = lots.iter().map(|lot| lot.prix_achat * lot.quantite).sum();
+    let gain_dollars: Decimal = lots.iter().map(|lot| lot.gain_dollars).sum();
+    let pourcentage_gain = money::round(money::percentage_change(cost_basis, cost_basis + gain_dollars), GAIN_PERCENTAGE_DECIMALS);
+
+    Ok(SalePreviewResponse {
+        symbol: symbol.to_string(),
+        quantite: quantity,
+        prix_vente: sale_price,
+        date_vente: sale_date.to_string(),
+        lots,
+        cost_basis,
+        gain_dollars,
+        pourcentage_gain,
+    })
+}
+
+/// Rejoue les préconditions de create_trade (arrêt d'urgence, fonds, date de vente, quantité vendable)
+/// et estime la trésorerie après le trade. Aucune écriture.
+pub fn evaluate_trade(request: &CreateTradeRequest, ctx: &TradeContext) -> TradeValidationResponse {
+    let prix_total = request.quantite * request.prix_unitaire;
+    let mut reasons = Vec::new();
+
+    if ctx.emergency_stop_armed {
+        reasons.push("Emergency stop is armed, trading is halted".to_string());
+    }
+
+    if let Err(e) = check_lot_constraints(&request.symbol, request.quantite, request.prix_unitaire, ctx.lot_size, ctx.min_lot) {
+        reasons.push(e);
+    }
+
+    let treasury_change = match request.trade_type.as_str() {
+        "achat" => {
+            if let Err(e) = check_open_positions_limit(&ctx.open_symbols, &request.symbol, ctx.max_open_positions) {
+                reasons.push(e);
+            }
+            match &ctx.currency {
+                Some(currency) if ctx.treasury < prix_total => {
+                    reasons.push(WalletService::insufficient_funds_message(ctx.treasury, currency, prix_total));
+                }
+                Some(_) => {}
+                None => reasons.push(format!("Stock not found: {}", request.symbol)),
+            }
+            -prix_total
+        }
+        "vente" => {
+            if let Err(e) = validate_sale_date(&request.date, &ctx.open_buy_lots) {
+                reasons.push(e);
+            }
+
+            // Une vente libère le capital investi des lots consommés (les gains sont saisis à part dans le wallet)
+            let (held, released) = fifo_release(&ctx.open_buy_lots, request.quantite);
+            if request.quantite > held {
+                reasons.push(format!(
+                    "Attempted to sell {} units of {} but only {} units are held. \
+                     Short selling is not currently supported.",
+                    request.quantite, request.symbol, held
+                ));
+            }
+            released
+        }
+        other => {
+            reasons.push(format!("Invalid trade type: {}", other));
+            Decimal::ZERO
+        }
+    };
+
+    TradeValidationResponse {
+        valid: reasons.is_empty(),
+        reasons,
+        estimated_fees: ESTIMATED_FEES,
+        resulting_treasury: ctx.currency.as_ref().map(|_| ctx.treasury + treasury_change - ESTIMATED_FEES),
+        currency: ctx.currency.clone(),
+    }
+}
+
+#[derive(Debug)]
+pub enum DeleteTradeError {
+    NotFound,
+    Consumed(String),
+    Db(DbErr),
+}
+
+impl From<DbErr> for DeleteTradeError {
+    fn from(e: DbErr) -> Self {
+        DeleteTradeError::Db(e)
+    }
+}
+
+/// Fenêtre de détection des doublons par défaut (secondes)
+pub const DEFAULT_DUPLICATE_TRADE_WINDOW_SECS: i64 = 10;
+pub const MAX_DUPLICATE_TRADE_WINDOW_SECS: i64 = 3600;
+
+/// Fenêtre configurée via DUPLICATE_TRADE_WINDOW_SECS (0 = détection désactivée, défaut: 10)
+pub fn duplicate_trade_window_from_env() -> i64 {
+    std::env::var("DUPLICATE_TRADE_WINDOW_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|secs| (0..=MAX_DUPLICATE_TRADE_WINDOW_SECS).contains(secs))
+        .unwrap_or(DEFAULT_DUPLICATE_TRADE_WINDOW_SECS)
+}
+
+/// Trade identique (symbole, type, quantité, prix, date) créé il y a moins de `window_secs` secondes.
+/// Les trades sans created_at (antérieurs à la colonne) ne sont jamais des doublons.
+pub fn find_duplicate(
+    request: &CreateTradeRequest,
+    recent: &[trade::Model],
+    now: chrono::NaiveDateTime,
+    window_secs: i64,
+) -> Option<i32> {
+    if window_secs <= 0 {
+        return None;
+    }
+
+    recent
+        .iter()
+        .filter(|t| t.created_at.is_some_and(|created| (now - created).num_seconds() < window_secs))
+        .filter(|t| {
+            t.symbol.as_deref() == Some(request.symbol.as_str())
+                && t.trade_type.as_deref() == Some(request.trade_type.as_str())
+                && t.quantite == Some(request.quantite)
+                && t.prix_unitaire == Some(request.prix_unitaire)
+                && t.date.as_deref() == Some(request.date.as_str())
+        })
+        .max_by_key(|t| t.created_at)
+        .map(|t| t.id)
+}
+
+/// Trade créé ; `duplicate_of` = trade identique récent (préférence block_duplicate_trades désactivée)
+#[derive(Debug)]
+pub struct CreatedTrade {
+    pub trade: trade::Model,
+    pub duplicate_of: Option<i32>,
+}
+
+/// Refus de create_trade : Rejected → 400, OpenPositionsLimit → 403, Duplicate → 409
+#[derive(Debug)]
+pub enum CreateTradeError {
+    Rejected(String),
+    OpenPositionsLimit(String),
+    Duplicate { existing_trade_id: i32 },
+    Db(DbErr),
+}
+
+impl From<DbErr> for CreateTradeError {
+    fn from(e: DbErr) -> Self {
+        match e {
+            DbErr::Custom(message) => CreateTradeError::Rejected(message),
+            e => CreateTradeError::Db(e),
+        }
+    }
+}
+
+impl From<CreateTradeError> for DbErr {
+    fn from(e: CreateTradeError) -> Self {
+        match e {
+            CreateTradeError::Rejected(message) | CreateTradeError::OpenPositionsLimit(message) => DbErr::Custom(message),
+            CreateTradeError::Duplicate { existing_trade_id } => {
+                DbErr::Custom(format!("Duplicate of trade {} submitted moments ago", existing_trade_id))
+            }
+            CreateTradeError::Db(e) => e,
+        }
+    }
+}
 
 pub struct TradeService;
 
 impl TradeService {
     /// Crée un nouveau trade (achat ou vente)
-    /// Pour les achats, vérifie d'abord que l'utilisateur a assez de fonds
+    /// Pour les achats, vérifie d'abord la limite de positions ouvertes puis que l'utilisateur a assez de fonds
     /// Pour les ventes, déclenche automatiquement la logique FIFO
+    /// Un trade identique créé dans la fenêtre DUPLICATE_TRADE_WINDOW_SECS est refusé si l'utilisateur
+    /// a activé block_duplicate_trades, sinon le trade est créé et signalé dans `duplicate_of`
     pub async fn create_trade(
         db: &DatabaseConnection,
         user_id: i32,
         request: CreateTradeRequest,
-    ) -> Result<trade::Model, DbErr> {
+    ) -> Result<CreatedTrade, CreateTradeError> {
         let prix_total = request.quantite * request.prix_unitaire;
+        let now = chrono::Utc::now().naive_utc();
+
+        let duplicate_of = Self::recent_duplicate(db, user_id, &request, now, duplicate_trade_window_from_env()).await?;
+        if let Some(existing_trade_id) = duplicate_of {
+            if Self::trading_preferences(db, user_id).await?.block_duplicate_trades {
+                return Err(CreateTradeError::Duplicate { existing_trade_id });
+            }
+            warn!("User {} submitted a duplicate of trade {}", user_id, existing_trade_id);
+        }
+
+        let (lot_size, min_lot) = Self::lot_constraints(db, &request.symbol).await?;
+        check_lot_constraints(&request.symbol, request.quantite, request.prix_unitaire, lot_size, min_lot)
+            .map_err(CreateTradeError::Rejected)?;
 
-        // CORRECTION CRITIQUE #3: Vérifier la balance avant un achat
         if request.trade_type == "achat" {
-            // 1. Récupérer la devise du stock
-            let stock_option = stock::Entity::find()
-                .filter(stock::Column::SymbolAlphavantage.eq(&request.symbol))
-                .one(db)
-                .await?;
+            let max_open_positions = Self::max_open_positions(db, user_id).await?;
+            if max_open_positions.is_some() {
+                let open_symbols = Self::open_symbols(db, user_id).await?;
+                check_open_positions_limit(&open_symbols, &request.symbol, max_open_positions)
+                    .map_err(CreateTradeError::OpenPositionsLimit)?;
+            }
+        }
 
-            let stock = stock_option.ok_or_else(|| {
+        // CORRECTION CRITIQUE #3: Vérifier la balance avant un achat
+        if request.trade_type == "achat" {
+            // 1. Devise du trade : celle saisie, sinon celle du stock
+            let currency = Self::request_currency(db, &request).await?.ok_or_else(|| {
                 DbErr::Custom(format!("Stock not found: {}", request.symbol))
             })?;
 
-            let currency = stock.currency.unwrap_or_else(|| "CAD".to_string());
-
             // 2. Vérifier si l'utilisateur a assez de trésorerie
             let has_funds = WalletService::has_sufficient_funds(
                 db,
@@ -48,10 +518,16 @@ impl TradeService {
                     prix_total,
                 ).await?;
 
-                return Err(DbErr::Custom(error_msg));
+                return Err(CreateTradeError::Rejected(error_msg));
             }
         }
 
+        // Une vente ne peut pas précéder le plus ancien lot d'achat ouvert (temps_jours négatif)
+        if request.trade_type == "vente" {
+            let open_buy_lots = Self::open_buy_lots(db, user_id, &request.symbol).await?;
+            validate_sale_date(&request.date, &open_buy_lots).map_err(DbErr::Custom)?;
+        }
+
         // Initialiser quantite_restante selon le type de trade
         let quantite_restante = if request.trade_type == "achat" {
             request.quantite
@@ -68,99 +544,356 @@ impl TradeService {
             prix_total: Set(Some(prix_total)),
             date: Set(Some(request.date.clone())),
             quantite_restante: Set(quantite_restante),
+            currency: Set(request.currency.as_deref().map(|c| currency::resolve(Some(c), None))),
+            created_at: Set(Some(now)),
             ..Default::default()
         };
 
-        let trade_result = new_trade.insert(db).await?;
+        // Vente, FIFO et P&L reporté dans le wallet : tout ou rien
+        let txn = db.begin().await?;
+
+        let trade_result = new_trade.insert(&txn).await?;
 
         // Si c'est une vente, traiter le FIFO
         if request.trade_type == "vente" {
-            Self::process_sale_fifo(db, user_id, &trade_result).await?;
+            Self::process_sale_fifo(&txn, user_id, &trade_result).await?;
         }
 
-        Ok(trade_result)
+        txn.commit().await?;
+
+        Ok(CreatedTrade { trade: trade_result, duplicate_of })
     }
 
-    /// Traite une vente selon la méthode FIFO (First In, First Out)
-    /// Ferme les trades d'achat les plus anciens en premier
-    async fn process_sale_fifo(
+    /// Trade identique créé par l'utilisateur dans les `window_secs` dernières secondes (voir find_duplicate)
+    async fn recent_duplicate(
         db: &DatabaseConnection,
         user_id: i32,
-        sale_trade: &trade::Model,
+        request: &CreateTradeRequest,
+        now: chrono::NaiveDateTime,
+        window_secs: i64,
+    ) -> Result<Option<i32>, DbErr> {
+        if window_secs <= 0 {
+            return Ok(None);
+        }
+
+        let recent = trade::Entity::find_active()
+            .filter(trade::Column::UserId.eq(user_id))
+            .filter(trade::Column::Symbol.eq(request.symbol.as_str()))
+            .filter(trade::Column::Date.eq(request.date.as_str()))
+            .filter(trade::Column::CreatedAt.gte(now - chrono::Duration::seconds(window_secs)))
+            .all(db)
+            .await?;
+
+        Ok(find_duplicate(request, &recent, now, window_secs))
+    }
+
+    /// Dry-run de create_trade : mêmes vérifications, sans rien insérer
+    pub async fn validate_trade(
+        db: &DatabaseConnection,
+        user_id: i32,
+        request: &CreateTradeRequest,
+    ) -> Result<TradeValidationResponse, DbErr> {
+        let mut currency = Self::request_currency(db, request).await?;
+        let mut open_buy_lots = Vec::new();
+        let mut open_symbols = HashSet::new();
+        let mut max_open_positions = None;
+
+        if request.trade_type == "achat" {
+            max_open_positions = Self::max_open_positions(db, user_id).await?;
+            if max_open_positions.is_some() {
+                open_symbols = Self::open_symbols(db, user_id).await?;
+            }
+        }
+
+        if request.trade_type == "vente" {
+            // Même repli que WalletService::trade_currency pour un trade déjà en BD
+            currency = currency.or_else(|| Some(currency::DEFAULT_CURRENCY.to_string()));
+            open_buy_lots = Self::open_buy_lots(db, user_id, &request.symbol).await?;
+        }
+
+        let treasury = match &currency {
+            Some(c) => WalletService::get_treasury_for_currency(db, user_id, c).await?,
+            None => Decimal::ZERO,
+        };
+
+        let (lot_size, min_lot) = Self::lot_constraints(db, &request.symbol).await?;
+
+        let ctx = TradeContext {
+            currency,
+            treasury,
+            open_buy_lots,
+            emergency_stop_armed: EmergencyStopService::is_armed(db, user_id).await?,
+            open_symbols,
+            max_open_positions,
+            lot_size,
+            min_lot,
+        };
+
+        Ok(evaluate_trade(request, &ctx))
+    }
+
+    /// Contraintes de lot (lot_size, min_lot) du stock, (None, None) si le stock est introuvable
+    async fn lot_constraints(db: &DatabaseConnection, symbol: &str) -> Result<(Option<Decimal>, Option<Decimal>), DbErr> {
+        let stock_option = stock::Entity::find()
+            .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+            .one(db)
+            .await?;
+
+        Ok(stock_option.map(|s| (s.lot_size, s.min_lot)).unwrap_or((None, None)))
+    }
+
+    /// Devise d'un trade à créer : celle saisie, sinon celle du stock (None si le stock est introuvable)
+    async fn request_currency(
+        db: &DatabaseConnection,
+        request: &CreateTradeRequest,
+    ) -> Result<Option<String>, DbErr> {
+        if let Some(c) = &request.currency {
+            return Ok(Some(currency::resolve(Some(c), None)));
+        }
+
+        let stock_option = stock::Entity::find()
+            .filter(stock::Column::SymbolAlphavantage.eq(&request.symbol))
+            .one(db)
+            .await?;
+
+        Ok(stock_option.map(|s| currency::or_default(s.currency)))
+    }
+
+    /// Préférence max_open_positions de l'utilisateur (None = pas de limite)
+    pub async fn max_open_positions(db: &DatabaseConnection, user_id: i32) -> Result<Option<i32>, DbErr> {
+        Ok(users::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .and_then(|user| user.max_open_positions))
+    }
+
+    /// Préférence auto_post_realized_pnl de l'utilisateur (false si inconnu)
+    async fn auto_post_realized_pnl<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<bool, DbErr> {
+        Ok(users::Entity::find_by_id(user_id)
+            .one(db)
+            .await?
+            .is_some_and(|user| user.auto_post_realized_pnl))
+    }
+
+    /// Préférences de trading de l'utilisateur (valeurs par défaut si inconnu)
+    pub async fn trading_preferences(db: &DatabaseConnection, user_id: i32) -> Result<TradingPreferences, DbErr> {
+        let user = users::Entity::find_by_id(user_id).one(db).await?;
+
+        Ok(TradingPreferences {
+            max_open_positions: user.as_ref().and_then(|u| u.max_open_positions),
+            auto_post_realized_pnl: user.as_ref().is_some_and(|u| u.auto_post_realized_pnl),
+            block_duplicate_trades: user.is_some_and(|u| u.block_duplicate_trades),
+        })
+    }
+
+    /// Enregistre les préférences de trading (max_open_positions None = pas de limite)
+    pub async fn set_trading_preferences(
+        db: &DatabaseConnection,
+        user_id: i32,
+        preferences: &TradingPreferences,
     ) -> Result<(), DbErr> {
-        let symbol = sale_trade.symbol.as_ref().unwrap();
-        let mut remaining_quantity = sale_trade.quantite.unwrap();
+        users::Entity::update_many()
+            .col_expr(users::Column::MaxOpenPositions, sea_query::Expr::value(preferences.max_open_positions))
+            .col_expr(users::Column::AutoPostRealizedPnl, sea_query::Expr::value(preferences.auto_post_realized_pnl))
+            .col_expr(users::Column::BlockDuplicateTrades, sea_query::Expr::value(preferences.block_duplicate_trades))
+            .filter(users::Column::Id.eq(user_id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Symboles (en majuscules) ayant au moins un lot d'achat ouvert
+    async fn open_symbols(db: &DatabaseConnection, user_id: i32) -> Result<HashSet<String>, DbErr> {
+        let lots = trade::Entity::find_active()
+            .filter(trade::Column::UserId.eq(user_id))
+            .filter(trade::Column::TradeType.eq("achat"))
+            .filter(trade::Column::QuantiteRestante.gt(Decimal::ZERO))
+            .all(db)
+            .await?;
 
+        Ok(lots
+            .into_iter()
+            .filter_map(|t| t.symbol)
+            .map(|symbol| symbol.trim().to_uppercase())
+            .collect())
+    }
+
+    /// Lots d'achat encore ouverts d'un symbole, du plus ancien au plus récent
+    async fn open_buy_lots<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        symbol: &str,
+    ) -> Result<Vec<trade::Model>, DbErr> {
         // CORRECTION CRITIQUE #2: Filtrer sur quantite_restante > 0
-        let buy_trades = trade::Entity::find()
+        trade::Entity::find_active()
             .filter(trade::Column::UserId.eq(user_id))
             .filter(trade::Column::Symbol.eq(symbol))
             .filter(trade::Column::TradeType.eq("achat"))
             .filter(trade::Column::QuantiteRestante.gt(Decimal::ZERO))
             .order_by_asc(trade::Column::Date)
             .all(db)
-            .await?;
+            .await
+    }
 
-        for buy_trade in buy_trades {
-            if remaining_quantity <= Decimal::ZERO {
-                break;
-            }
+    /// Traite une vente selon la méthode FIFO (First In, First Out)
+    /// Ferme les trades d'achat les plus anciens en premier
+    async fn process_sale_fifo<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        sale_trade: &trade::Model,
+    ) -> Result<(), DbErr> {
+        let symbol = sale_trade.symbol.as_ref().unwrap();
+        let quantity = sale_trade.quantite.unwrap();
 
-            let available_quantity = buy_trade.quantite_restante;
-            let quantity_to_close = remaining_quantity.min(available_quantity);
+        let buy_trades = Self::open_buy_lots(db, user_id, symbol).await?;
+        let (matches, uncovered) = match_sale_fifo(&buy_trades, quantity);
+
+        // Vérification: impossible de vendre plus qu'on ne possède
+        if uncovered > Decimal::ZERO {
+            return Err(DbErr::Custom(format!(
+                "Attempted to sell {} units of {} but only had enough buy positions to cover {} units. \
+                 Short selling is not currently supported.",
+                quantity,
+                symbol,
+                quantity - uncovered
+            )));
+        }
 
-            Self::create_closed_trade(
+        let mut realized_gain = Decimal::ZERO;
+        for (buy_trade, quantity_to_close) in matches {
+            realized_gain += Self::create_closed_trade(
                 db,
                 user_id,
-                &buy_trade,
+                buy_trade,
                 sale_trade,
                 quantity_to_close,
             ).await?;
 
             // Mettre à jour quantite_restante du trade d'achat
-            let mut active_buy: trade::ActiveModel = buy_trade.into();
-            active_buy.quantite_restante = Set(available_quantity - quantity_to_close);
+            let mut active_buy: trade::ActiveModel = buy_trade.clone().into();
+            active_buy.quantite_restante = Set(buy_trade.quantite_restante - quantity_to_close);
             active_buy.update(db).await?;
+        }
 
-            remaining_quantity -= quantity_to_close;
+        if Self::auto_post_realized_pnl(db, user_id).await? {
+            Self::post_realized_pnl(db, user_id, sale_trade, realized_gain).await?;
         }
 
-        // Vérification: impossible de vendre plus qu'on ne possède
-        if remaining_quantity > Decimal::ZERO {
-            return Err(DbErr::Custom(format!(
-                "Attempted to sell {} units of {} but only had enough buy positions to cover {} units. \
-                 Short selling is not currently supported.",
-                sale_trade.quantite.unwrap(),
-                symbol,
-                sale_trade.quantite.unwrap() - remaining_quantity
-            )));
+        Ok(())
+    }
+
+    /// Reporte le P&L réalisé d'une vente dans le wallet ("gain" / "perte", devise du trade sinon du stock).
+    /// Une seule entrée par vente (trade_vente_id) : mise à jour si elle existe déjà, pour qu'un
+    /// recalcul FIFO ne poste jamais deux fois la même vente
+    async fn post_realized_pnl<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        sale_trade: &trade::Model,
+        realized_gain: Decimal,
+    ) -> Result<(), DbErr> {
+        let (action, amount) = realized_pnl_action(realized_gain);
+
+        let existing = wallet::Entity::find()
+            .filter(wallet::Column::UserId.eq(user_id))
+            .filter(wallet::Column::TradeVenteId.eq(sale_trade.id))
+            .one(db)
+            .await?;
+
+        if let Some(entry) = existing {
+            let mut active: wallet::ActiveModel = entry.into();
+            active.action = Set(action.to_string());
+            active.amount = Set(amount);
+            active.update(db).await?;
+            return Ok(());
+        }
+
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        let currency = WalletService::trade_currency(db, sale_trade)
+            .await?
+            .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+
+        wallet::ActiveModel {
+            user_id: Set(user_id),
+            date: Set(sale_trade.date.clone().unwrap_or_default()),
+            action: Set(action.to_string()),
+            symbol: Set(sale_trade.symbol.clone()),
+            amount: Set(amount),
+            currency: Set(currency),
+            trade_vente_id: Set(Some(sale_trade.id)),
+            account_type: Set(wallet::REAL_ACCOUNT.to_string()),
+            ..Default::default()
         }
+        .insert(db)
+        .await?;
 
         Ok(())
     }
 
-    /// Crée un enregistrement de trade fermé avec calcul des gains/pertes
-    async fn create_closed_trade(
+    /// Aperçu FIFO d'une vente hypothétique (POST /api/trades/preview-sale) : Err(message) si la date
+    /// précède le plus ancien lot ouvert ou si la quantité dépasse la position. Aucune écriture.
+    pub async fn preview_sale(
         db: &DatabaseConnection,
         user_id: i32,
+        request: &PreviewSaleRequest,
+    ) -> Result<Result<SalePreviewResponse, String>, DbErr> {
+        let open_buy_lots = Self::open_buy_lots(db, user_id, &request.symbol).await?;
+
+        let mut exchanges = Vec::with_capacity(open_buy_lots.len());
+        for lot in &open_buy_lots {
+            exchanges.push(Self::trade_exchange(db, lot).await?);
+        }
+
+        Ok(preview_sale(
+            &request.symbol,
+            &open_buy_lots,
+            &exchanges,
+            request.quantite,
+            request.prix_unitaire,
+            &request.date,
+        ))
+    }
+
+    /// Place de cotation d'un trade : sa devise, sinon celle du stock (calendrier de temps_jours)
+    async fn trade_exchange<C: ConnectionTrait>(db: &C, trade: &trade::Model) -> Result<Exchange, DbErr> {
+        if trade.currency.is_some() {
+            return Ok(Exchange::from_currency(trade.currency.as_deref()));
+        }
+
+        let stock_currency = match &trade.symbol {
+            Some(symbol) => stock::Entity::find()
+                .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+                .one(db)
+                .await?
+                .and_then(|s| s.currency),
+            None => None,
+        };
+
+        Ok(Exchange::from_currency(stock_currency.as_deref()))
+    }
+
+    /// Crée un enregistrement de trade fermé avec calcul des gains/pertes, et retourne le gain en dollars
+    async fn create_closed_trade<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
         buy_trade: &trade::Model,
         sale_trade: &trade::Model,
         quantity: Decimal,
-    ) -> Result<(), DbErr> {
+    ) -> Result<Decimal, DbErr> {
         let buy_price = buy_trade.prix_unitaire.unwrap();
         let sale_price = sale_trade.prix_unitaire.unwrap();
 
         let gain = (sale_price - buy_price) * quantity;
-        let pourcentage = ((sale_price - buy_price) / buy_price * Decimal::from(100)).round();
+        let pourcentage = gain_percentage(buy_price, sale_price);
 
-        let date_achat = NaiveDate::parse_from_str(&buy_trade.date.as_ref().unwrap(), "%Y-%m-%d").ok();
-        let date_vente = NaiveDate::parse_from_str(&sale_trade.date.as_ref().unwrap(), "%Y-%m-%d").ok();
-
-        let temps_jours = if let (Some(achat), Some(vente)) = (date_achat, date_vente) {
-            (vente - achat).num_days() as i32
-        } else {
-            0
-        };
+        let exchange = Self::trade_exchange(db, buy_trade).await?;
+        let temps_jours = holding_days(
+            buy_trade.date.as_ref().unwrap(),
+            sale_trade.date.as_ref().unwrap(),
+            exchange,
+        );
 
         let unique_id = format!("{}_{}_{}_{}",
                                 user_id,
@@ -177,7 +910,7 @@ impl TradeService {
             prix_achat: Set(Some(buy_price.to_string())),
             date_vente: Set(Some(sale_trade.date.clone().unwrap())),
             prix_vente: Set(Some(sale_price.to_string())),
-            pourcentage_gain: Set(Some(pourcentage.to_string().parse().unwrap_or(0))),
+            pourcentage_gain: Set(Some(pourcentage)),
             gain_dollars: Set(Some(gain)),
             temps_jours: Set(Some(temps_jours)),
             trade_achat_id: Set(Some(buy_trade.id)),
@@ -185,17 +918,186 @@ impl TradeService {
         };
 
         closed_trade.insert(db).await?;
-        Ok(())
+        Ok(gain)
+    }
+
+    /// Vend toute la position ouverte d'un symbole (quantite_restante totale)
+    /// Retourne le trade de vente et les trades fermés générés par le FIFO
+    pub async fn close_position(
+        db: &DatabaseConnection,
+        user_id: i32,
+        symbol: &str,
+        quantite: Decimal,
+        prix_unitaire: Decimal,
+        date: String,
+    ) -> Result<(trade::Model, Vec<trades_fermes::Model>), DbErr> {
+        let request = CreateTradeRequest {
+            symbol: symbol.to_string(),
+            trade_type: "vente".to_string(),
+            quantite,
+            prix_unitaire,
+            date,
+            currency: None,
+        };
+
+        let sale_trade = Self::create_trade(db, user_id, request).await?.trade;
+
+        let closed_trades = trades_fermes::Entity::find()
+            .filter(trades_fermes::Column::UserId.eq(user_id))
+            .filter(trades_fermes::Column::TradeVenteId.eq(sale_trade.id))
+            .order_by_asc(trades_fermes::Column::DateAchat)
+            .all(db)
+            .await?;
+
+        Ok((sale_trade, closed_trades))
+    }
+
+    /// Reconstruit l'état FIFO d'un utilisateur à partir de ses trades (réparation de cohérence)
+    /// Dans une transaction: remet les quantite_restante des achats, supprime les trades fermés,
+    /// rejoue chaque vente dans l'ordre chronologique et retire le P&L reporté des ventes disparues
+    pub async fn recalculate_fifo(
+        db: &DatabaseConnection,
+        user_id: i32,
+    ) -> Result<FifoRecalculationResponse, DbErr> {
+        let txn = db.begin().await?;
+
+        let trades = trade::Entity::find_active()
+            .filter(trade::Column::UserId.eq(user_id))
+            .all(&txn)
+            .await?;
+
+        let before = Self::fifo_summary(&txn, user_id, &trades).await?;
+
+        let replay = replay_fifo(&trades).map_err(DbErr::Custom)?;
+
+        // 1. Remettre chaque achat à sa quantite_restante attendue
+        let mut buy_lots_updated = 0;
+        for t in &trades {
+            if let Some(expected) = replay.remaining.get(&t.id)
+                && t.quantite_restante != *expected
+            {
+                let mut active: trade::ActiveModel = t.clone().into();
+                active.quantite_restante = Set(*expected);
+                active.update(&txn).await?;
+                buy_lots_updated += 1;
+            }
+        }
+
+        // 2. Supprimer puis recréer les trades fermés
+        trades_fermes::Entity::delete_many()
+            .filter(trades_fermes::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+
+        let by_id: HashMap<i32, &trade::Model> = trades.iter().map(|t| (t.id, t)).collect();
+        let mut realized_by_sale: HashMap<i32, Decimal> = HashMap::new();
+        for (buy_id, sale_id, quantity) in &replay.closures {
+            let gain = Self::create_closed_trade(&txn, user_id, by_id[buy_id], by_id[sale_id], *quantity).await?;
+            *realized_by_sale.entry(*sale_id).or_insert(Decimal::ZERO) += gain;
+        }
+
+        // 3. Mettre à jour (sans dupliquer) les gains / pertes reportés dans le wallet
+        if Self::auto_post_realized_pnl(&txn, user_id).await? {
+            for (sale_id, gain) in &realized_by_sale {
+                Self::post_realized_pnl(&txn, user_id, by_id[sale_id], *gain).await?;
+            }
+        }
+
+        // 4. Supprimer le P&L reporté des ventes qui ne ferment plus aucun lot (vente supprimée)
+        wallet::Entity::delete_many()
+            .filter(wallet::Column::UserId.eq(user_id))
+            .filter(wallet::Column::TradeVenteId.is_not_null())
+            .filter(wallet::Column::TradeVenteId.is_not_in(realized_by_sale.keys().copied()))
+            .exec(&txn)
+            .await?;
+
+        let trades_after = trade::Entity::find_active()
+            .filter(trade::Column::UserId.eq(user_id))
+            .all(&txn)
+            .await?;
+        let after = Self::fifo_summary(&txn, user_id, &trades_after).await?;
+
+        txn.commit().await?;
+
+        Ok(FifoRecalculationResponse {
+            before,
+            after,
+            buy_lots_updated,
+        })
+    }
+
+    async fn fifo_summary<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        trades: &[trade::Model],
+    ) -> Result<FifoStateSummary, DbErr> {
+        let closed = trades_fermes::Entity::find()
+            .filter(trades_fermes::Column::UserId.eq(user_id))
+            .all(db)
+            .await?;
+
+        let realized = realized_pnl(&closed, None);
+
+        Ok(FifoStateSummary {
+            closed_trades: realized.closed_trades,
+            total_gain: realized.realized_gain,
+            open_quantity: trades
+                .iter()
+                .filter(|t| t.trade_type.as_deref() == Some("achat"))
+                .map(|t| t.quantite_restante)
+                .sum(),
+        })
+    }
+
+    /// Soft-delete d'un trade (deleted_at) avec une entrée dans audit_log_rust
+    /// Refusé si le trade fait déjà partie de l'historique FIFO (lot consommé / vente fermée)
+    pub async fn soft_delete_trade(
+        db: &DatabaseConnection,
+        user_id: i32,
+        trade_id: i32,
+    ) -> Result<trade::Model, DeleteTradeError> {
+        let txn = db.begin().await?;
+
+        let t = trade::Entity::find_active()
+            .filter(trade::Column::Id.eq(trade_id))
+            .filter(trade::Column::UserId.eq(user_id))
+            .one(&txn)
+            .await?
+            .ok_or(DeleteTradeError::NotFound)?;
+
+        let closed_trades = trades_fermes::Entity::find()
+            .filter(trades_fermes::Column::UserId.eq(user_id))
+            .filter(
+                Condition::any()
+                    .add(trades_fermes::Column::TradeAchatId.eq(trade_id))
+                    .add(trades_fermes::Column::TradeVenteId.eq(trade_id)),
+            )
+            .count(&txn)
+            .await?;
+
+        if let Some(reason) = deletion_blocker(&t, closed_trades as usize) {
+            return Err(DeleteTradeError::Consumed(reason));
+        }
+
+        let snapshot = serde_json::to_value(&t).ok();
+
+        let mut active: trade::ActiveModel = t.into();
+        active.deleted_at = Set(Some(chrono::Utc::now().naive_utc()));
+        let deleted = active.update(&txn).await?;
+
+        AuditService::record(&txn, user_id, "trade.delete", "trade", &trade_id.to_string(), snapshot).await?;
+
+        txn.commit().await?;
+        Ok(deleted)
     }
 
     /// Vérifie si l'utilisateur possède assez de quantité d'un symbole pour vendre
-    #[allow(dead_code)]
     pub async fn get_available_quantity(
         db: &DatabaseConnection,
         user_id: i32,
         symbol: &str,
     ) -> Result<Decimal, DbErr> {
-        let buy_trades = trade::Entity::find()
+        let buy_trades = trade::Entity::find_active()
             .filter(trade::Column::UserId.eq(user_id))
             .filter(trade::Column::Symbol.eq(symbol))
             .filter(trade::Column::TradeType.eq("achat"))
@@ -210,4 +1112,523 @@ impl TradeService {
 
         Ok(total_available)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{Database, DatabaseBackend, Schema};
+
+    fn trade(id: i32, trade_type: &str, date: &str, quantite: i64, quantite_restante: i64) -> trade::Model {
+        trade::Model {
+            id,
+            user_id: 1,
+            date: Some(date.to_string()),
+            symbol: Some("AAPL".to_string()),
+            trade_type: Some(trade_type.to_string()),
+            quantite: Some(Decimal::from(quantite)),
+            prix_unitaire: Some(Decimal::from(100)),
+            prix_total: Some(Decimal::from(quantite * 100)),
+            quantite_restante: Decimal::from(quantite_restante),
+            currency: None,
+            deleted_at: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_replay_repairs_corrupted_lots() {
+        // État corrompu : quantite_restante incohérentes, vente saisie avant un achat dans la table (id)
+        let trades = vec![
+            trade(3, "vente", "2025-03-01", 120, 0),
+            trade(1, "achat", "2025-01-10", 100, 100), // devrait être 0
+            trade(2, "achat", "10/02/2025", 50, 7),    // ancien format, devrait être 30
+        ];
+
+        let replay = replay_fifo(&trades).unwrap();
+
+        assert_eq!(replay.remaining[&1], Decimal::ZERO);
+        assert_eq!(replay.remaining[&2], Decimal::from(30));
+        assert_eq!(replay.closures, vec![
+            (1, 3, Decimal::from(100)),
+            (2, 3, Decimal::from(20)),
+        ]);
+    }
+
+    #[test]
+    fn test_replay_rejects_oversell() {
+        let trades = vec![
+            trade(1, "achat", "2025-01-10", 10, 10),
+            trade(2, "vente", "2025-01-05", 5, 0), // vente avant l'achat
+        ];
+
+        assert!(replay_fifo(&trades).is_err());
+    }
+
+    #[test]
+    fn test_replay_rejects_unparseable_dates() {
+        let trades = vec![
+            trade(1, "achat", "2025-01-10", 10, 10),
+            trade(2, "vente", "not a date", 5, 0),
+        ];
+
+        assert_eq!(replay_fifo(&trades).unwrap_err(), "Trade 2 has an unparseable date 'not a date'");
+    }
+
+    #[test]
+    fn test_consumed_buy_lot_cannot_be_deleted() {
+        // Lot partiellement vendu
+        let partially_sold = trade(1, "achat", "2025-01-10", 100, 40);
+        assert!(deletion_blocker(&partially_sold, 1).is_some());
+
+        // Lot entièrement vendu
+        let sold_out = trade(2, "achat", "2025-01-10", 100, 0);
+        assert!(deletion_blocker(&sold_out, 1).is_some());
+
+        // Vente ayant fermé des lots
+        let sale = trade(3, "vente", "2025-02-10", 60, 0);
+        assert!(deletion_blocker(&sale, 1).is_some());
+    }
+
+    #[test]
+    fn test_fresh_buy_lot_can_be_deleted() {
+        let fresh = trade(1, "achat", "2025-01-10", 100, 100);
+
+        assert_eq!(deletion_blocker(&fresh, 0), None);
+    }
+
+    #[test]
+    fn test_same_day_sale_is_accepted_with_zero_days() {
+        let lots = vec![trade(1, "achat", "2025-01-10", 10, 10)];
+
+        assert!(validate_sale_date("2025-01-10", &lots).is_ok());
+        assert_eq!(holding_days("2025-01-10", "2025-01-10", Exchange::Tsx), 0);
+    }
+
+    #[test]
+    fn test_sale_before_earliest_buy_is_rejected() {
+        let lots = vec![
+            trade(2, "achat", "2025-02-01", 10, 10),
+            trade(1, "achat", "15/01/2025", 10, 4), // ancien format, lot le plus ancien
+        ];
+
+        assert!(validate_sale_date("2025-01-14", &lots).is_err());
+        assert!(validate_sale_date("2025-01-15", &lots).is_ok());
+    }
+
+    #[test]
+    fn test_holding_days_is_never_negative() {
+        assert_eq!(holding_days("2025-03-01", "2025-02-01", Exchange::Tsx), 0);
+        // Janvier 2025 en séances TSX (1er janvier férié) : 22, pas 30 jours calendaires
+        assert_eq!(holding_days("2025-01-01", "2025-01-31", Exchange::Tsx), 22);
+        // Vendredi → lundi : une séance
+        assert_eq!(holding_days("2025-06-13", "2025-06-16", Exchange::Nyse), 1);
+    }
+
+    #[test]
+    fn test_gain_percentage_keeps_two_decimals() {
+        // 100 → 106.40 : 6.4 % conservé, pas arrondi à 6
+        let gain = gain_percentage(Decimal::from(100), Decimal::new(10640, 2));
+        assert_eq!(gain, Decimal::new(640, 2));
+        assert_eq!(gain.to_string(), "6.40");
+
+        assert_eq!(gain_percentage(Decimal::new(15050, 2), Decimal::from(160)), Decimal::new(631, 2));
+        assert_eq!(gain_percentage(Decimal::from(3), Decimal::from(2)), Decimal::new(-3333, 2));
+        assert_eq!(gain_percentage(Decimal::ZERO, Decimal::from(10)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_gain_percentage_rounds_half_away_from_zero() {
+        // 200 → 200.25 : +0.125 %, 200 → 199.75 : -0.125 % (l'arrondi au pair donnerait ±0.12)
+        assert_eq!(gain_percentage(Decimal::from(200), Decimal::new(20025, 2)), Decimal::new(13, 2));
+        assert_eq!(gain_percentage(Decimal::from(200), Decimal::new(19975, 2)), Decimal::new(-13, 2));
+    }
+
+    fn request(trade_type: &str, quantite: i64, prix_unitaire: i64) -> CreateTradeRequest {
+        CreateTradeRequest {
+            symbol: "AAPL".to_string(),
+            trade_type: trade_type.to_string(),
+            quantite: Decimal::from(quantite),
+            prix_unitaire: Decimal::from(prix_unitaire),
+            date: "2025-03-01".to_string(),
+            currency: None,
+        }
+    }
+
+    fn context(treasury: i64, open_buy_lots: Vec<trade::Model>) -> TradeContext {
+        TradeContext {
+            currency: Some("USD".to_string()),
+            treasury: Decimal::from(treasury),
+            open_buy_lots,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_underfunded_buy_is_rejected_with_shortage() {
+        let result = evaluate_trade(&request("achat", 10, 150), &context(1000, vec![]));
+
+        assert!(!result.valid);
+        assert_eq!(
+            result.reasons,
+            vec!["Insufficient funds: 1000 USD available, 1500 USD required (shortage: 500 USD)".to_string()]
+        );
+        assert_eq!(result.estimated_fees, Decimal::ZERO);
+        assert_eq!(result.resulting_treasury, Some(Decimal::from(-500)));
+
+        let funded = evaluate_trade(&request("achat", 10, 150), &context(2000, vec![]));
+        assert!(funded.valid && funded.reasons.is_empty());
+        assert_eq!(funded.resulting_treasury, Some(Decimal::from(500)));
+    }
+
+    #[test]
+    fn test_quantity_must_be_a_multiple_of_lot_size() {
+        let lot = Some(Decimal::from(100));
+
+        let err = check_lot_constraints("RY.TO", Decimal::from(150), Decimal::from(10), lot, None).unwrap_err();
+        assert_eq!(err, "Quantity 150 of RY.TO is not a multiple of the lot size 100 (nearest valid quantities: 100 or 200)");
+        // Actions entières uniquement
+        assert!(check_lot_constraints("AAPL", Decimal::new(25, 1), Decimal::from(10), Some(Decimal::ONE), None).is_err());
+
+        // Lot valide, montant minimal atteint
+        assert!(check_lot_constraints("RY.TO", Decimal::from(200), Decimal::from(10), lot, Some(Decimal::from(2000))).is_ok());
+        // Sans contrainte configurée : tout passe
+        assert!(check_lot_constraints("AAPL", Decimal::new(25, 1), Decimal::from(10), None, None).is_ok());
+    }
+
+    #[test]
+    fn test_trade_below_minimum_amount_is_rejected() {
+        let ctx = TradeContext { min_lot: Some(Decimal::from(1000)), ..context(5000, vec![]) };
+
+        let result = evaluate_trade(&request("achat", 5, 150), &ctx);
+        assert_eq!(result.reasons, vec!["Trade amount 750 for AAPL is below the minimum of 1000".to_string()]);
+
+        assert!(evaluate_trade(&request("achat", 10, 150), &ctx).valid);
+    }
+
+    #[test]
+    fn test_oversell_is_rejected() {
+        // 5 + 3 actions détenues (achetées à 100), vente de 10
+        let lots = vec![trade(1, "achat", "2025-01-10", 5, 5), trade(2, "achat", "2025-02-10", 4, 3)];
+
+        let result = evaluate_trade(&request("vente", 10, 120), &context(200, lots.clone()));
+        assert!(!result.valid);
+        assert_eq!(result.reasons.len(), 1);
+        assert!(result.reasons[0].contains("only 8 units are held"), "{:?}", result.reasons);
+
+        // Vente couverte : libère le capital investi des lots consommés (6 × 100)
+        let covered = evaluate_trade(&request("vente", 6, 120), &context(200, lots));
+        assert!(covered.valid);
+        assert_eq!(covered.resulting_treasury, Some(Decimal::from(800)));
+    }
+
+    #[test]
+    fn test_sale_preview_matches_across_lots() {
+        // 5 actions à 100 puis 3 restantes à 120, vente de 7 à 130
+        let mut second = trade(2, "achat", "2025-02-10", 4, 3);
+        second.prix_unitaire = Some(Decimal::from(120));
+        let lots = vec![trade(1, "achat", "2025-01-10", 5, 5), second];
+        let exchanges = [Exchange::WeekdaysOnly; 2];
+
+        let preview = preview_sale("AAPL", &lots, &exchanges, Decimal::from(7), Decimal::from(130), "2025-02-14").unwrap();
+
+        let closed: Vec<(i32, Decimal, Decimal, i32)> = preview
+            .lots
+            .iter()
+            .map(|lot| (lot.trade_achat_id, lot.quantite, lot.gain_dollars, lot.temps_jours))
+            .collect();
+        assert_eq!(closed, vec![
+            (1, Decimal::from(5), Decimal::from(150), 25),
+            (2, Decimal::from(2), Decimal::from(20), 4),
+        ]);
+        assert_eq!(preview.lots[1].pourcentage_gain, Decimal::new(833, 2));
+        assert_eq!(preview.cost_basis, Decimal::from(740));
+        assert_eq!(preview.gain_dollars, Decimal::from(170));
+        assert_eq!(preview.pourcentage_gain, Decimal::new(2297, 2));
+
+        // Les lots ne sont pas modifiés
+        assert_eq!(lots[0].quantite_restante, Decimal::from(5));
+    }
+
+    #[test]
+    fn test_sale_preview_rejects_oversell_and_early_date() {
+        let lots = vec![trade(1, "achat", "2025-01-10", 5, 5), trade(2, "achat", "2025-02-10", 4, 3)];
+        let exchanges = [Exchange::WeekdaysOnly; 2];
+
+        let oversell = preview_sale("AAPL", &lots, &exchanges, Decimal::from(9), Decimal::from(130), "2025-02-14");
+        assert!(oversell.unwrap_err().contains("only 8 units are held"));
+
+        let early = preview_sale("AAPL", &lots, &exchanges, Decimal::from(2), Decimal::from(130), "2025-01-09");
+        assert!(early.unwrap_err().contains("before the earliest open buy lot"));
+    }
+
+    #[test]
+    fn test_armed_emergency_stop_and_unknown_stock_are_reported() {
+        let ctx = TradeContext { emergency_stop_armed: true, ..Default::default() };
+        let result = evaluate_trade(&request("achat", 1, 10), &ctx);
+
+        assert_eq!(
+            result.reasons,
+            vec!["Emergency stop is armed, trading is halted".to_string(), "Stock not found: AAPL".to_string()]
+        );
+        assert_eq!(result.resulting_treasury, None);
+    }
+
+    async fn pnl_db(users: &[(i32, bool)]) -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(users::Entity),
+            schema.create_table_from_entity(trade::Entity),
+            schema.create_table_from_entity(trades_fermes::Entity),
+            schema.create_table_from_entity(wallet::Entity),
+            schema.create_table_from_entity(stock::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        stock::ActiveModel {
+            compagny_name: Set("Royal Bank".to_string()),
+            symbol_alphavantage: Set(Some("RY.TO".to_string())),
+            currency: Set(Some("CAD".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        for (id, auto_post) in users {
+            users::ActiveModel {
+                id: Set(*id),
+                username: Set(format!("user{}", id)),
+                email: Set(format!("user{}@example.com", id)),
+                email_verified: Set(true),
+                auto_post_realized_pnl: Set(*auto_post),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+
+            wallet::ActiveModel {
+                user_id: Set(*id),
+                date: Set("2025-01-02".to_string()),
+                action: Set("ajout".to_string()),
+                amount: Set(Decimal::from(5000)),
+                currency: Set("CAD".to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    async fn place(db: &DatabaseConnection, user_id: i32, trade_type: &str, quantite: i64, prix: i64, date: &str) {
+        let request = CreateTradeRequest {
+            symbol: "RY.TO".to_string(),
+            trade_type: trade_type.to_string(),
+            quantite: Decimal::from(quantite),
+            prix_unitaire: Decimal::from(prix),
+            date: date.to_string(),
+            currency: None,
+        };
+        TradeService::create_trade(db, user_id, request).await.unwrap();
+    }
+
+    async fn posted_pnl(db: &DatabaseConnection, user_id: i32) -> Vec<(String, Decimal, String)> {
+        wallet::Entity::find()
+            .filter(wallet::Column::UserId.eq(user_id))
+            .filter(wallet::Column::TradeVenteId.is_not_null())
+            .order_by_asc(wallet::Column::Id)
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.action, entry.amount, entry.currency))
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicate_is_detected_only_within_window() {
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(15, 0, 0).unwrap();
+        let submitted = |id: i32, seconds_ago: i64, quantite: i64| trade::Model {
+            prix_unitaire: Some(Decimal::from(10)),
+            created_at: Some(now - chrono::Duration::seconds(seconds_ago)),
+            ..trade(id, "achat", "2025-03-01", quantite, quantite)
+        };
+        let req = request("achat", 5, 10);
+
+        assert_eq!(find_duplicate(&req, &[submitted(1, 3, 5)], now, 10), Some(1));
+        assert_eq!(find_duplicate(&req, &[submitted(1, 30, 5)], now, 10), None);
+        // Quantité différente, fenêtre désactivée, trade antérieur à created_at : pas un doublon
+        assert_eq!(find_duplicate(&req, &[submitted(1, 3, 6)], now, 10), None);
+        assert_eq!(find_duplicate(&req, &[submitted(1, 3, 5)], now, 0), None);
+        assert_eq!(find_duplicate(&req, &[trade(1, "achat", "2025-03-01", 5, 5)], now, 10), None);
+        // Le plus récent des doublons est signalé
+        assert_eq!(find_duplicate(&req, &[submitted(1, 8, 5), submitted(2, 2, 5)], now, 10), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_trade_warns_or_blocks_per_preference() {
+        let db = pnl_db(&[(1, false), (2, false)]).await;
+        let preferences = TradingPreferences { max_open_positions: None, auto_post_realized_pnl: false, block_duplicate_trades: true };
+        TradeService::set_trading_preferences(&db, 2, &preferences).await.unwrap();
+
+        let buy = || CreateTradeRequest {
+            symbol: "RY.TO".to_string(),
+            trade_type: "achat".to_string(),
+            quantite: Decimal::from(10),
+            prix_unitaire: Decimal::from(100),
+            date: "2025-01-10".to_string(),
+            currency: None,
+        };
+
+        // Avertissement : le second trade est créé et pointe vers le premier
+        let first = TradeService::create_trade(&db, 1, buy()).await.unwrap();
+        assert_eq!(first.duplicate_of, None);
+        let second = TradeService::create_trade(&db, 1, buy()).await.unwrap();
+        assert_eq!(second.duplicate_of, Some(first.trade.id));
+
+        // Blocage : 409 avec l'id du trade existant, rien n'est inséré
+        let kept = TradeService::create_trade(&db, 2, buy()).await.unwrap();
+        match TradeService::create_trade(&db, 2, buy()).await {
+            Err(CreateTradeError::Duplicate { existing_trade_id }) => assert_eq!(existing_trade_id, kept.trade.id),
+            other => panic!("expected a duplicate rejection, got {:?}", other),
+        }
+        let user2_trades = trade::Entity::find().filter(trade::Column::UserId.eq(2)).count(&db).await.unwrap();
+        assert_eq!(user2_trades, 1);
+
+        // Hors fenêtre (created_at vieilli), le même trade est accepté
+        trade::Entity::update_many()
+            .col_expr(trade::Column::CreatedAt, sea_query::Expr::value(chrono::Utc::now().naive_utc() - chrono::Duration::minutes(5)))
+            .filter(trade::Column::UserId.eq(2))
+            .exec(&db)
+            .await
+            .unwrap();
+        let later = TradeService::create_trade(&db, 2, buy()).await.unwrap();
+        assert_eq!(later.duplicate_of, None);
+    }
+
+    #[tokio::test]
+    async fn test_closing_sell_posts_realized_pnl_to_wallet() {
+        let db = pnl_db(&[(1, true), (2, false)]).await;
+
+        for user_id in [1, 2] {
+            place(&db, user_id, "achat", 10, 100, "2025-01-10").await;
+            place(&db, user_id, "achat", 5, 110, "2025-01-20").await;
+            // 10 × (+5) + 2 × (-5) = +40, puis 3 × (-10) = -30
+            place(&db, user_id, "vente", 12, 105, "2025-02-03").await;
+            place(&db, user_id, "vente", 3, 100, "2025-02-10").await;
+        }
+
+        let expected = vec![
+            ("gain".to_string(), Decimal::from(40), "CAD".to_string()),
+            ("perte".to_string(), Decimal::from(30), "CAD".to_string()),
+        ];
+        assert_eq!(posted_pnl(&db, 1).await, expected);
+        assert!(posted_pnl(&db, 2).await.is_empty(), "opt-in only");
+
+        // Le recalcul FIFO met à jour les entrées existantes sans les dupliquer
+        TradeService::recalculate_fifo(&db, 1).await.unwrap();
+        assert_eq!(posted_pnl(&db, 1).await, expected);
+
+        // Solde : 5000 + 40 - 30, plus rien d'investi
+        let balances = WalletService::calculate_balances(&db, 1).await.unwrap();
+        assert_eq!(balances[0].total, Decimal::from(5010));
+        assert_eq!(balances[0].treasury, Decimal::from(5010));
+    }
+
+    #[tokio::test]
+    async fn test_failed_pnl_post_rolls_back_the_sale() {
+        let db = pnl_db(&[(1, true)]).await;
+        place(&db, 1, "achat", 10, 100, "2025-01-10").await;
+
+        // Wallet indisponible : le report du P&L échoue après l'insertion de la vente et le FIFO
+        db.execute_unprepared(&format!("DROP TABLE {}", wallet::Entity.table_name())).await.unwrap();
+        let request = CreateTradeRequest {
+            symbol: "RY.TO".to_string(),
+            trade_type: "vente".to_string(),
+            quantite: Decimal::from(4),
+            prix_unitaire: Decimal::from(110),
+            date: "2025-02-03".to_string(),
+            currency: None,
+        };
+        assert!(TradeService::create_trade(&db, 1, request).await.is_err());
+
+        let trades = trade::Entity::find().all(&db).await.unwrap();
+        assert_eq!(trades.len(), 1, "sale must be rolled back");
+        assert_eq!(trades[0].quantite_restante, Decimal::from(10));
+        assert_eq!(trades_fermes::Entity::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_recalculation_drops_pnl_of_deleted_sales() {
+        let db = pnl_db(&[(1, true)]).await;
+        place(&db, 1, "achat", 10, 100, "2025-01-10").await;
+        place(&db, 1, "vente", 4, 110, "2025-02-03").await;
+        place(&db, 1, "vente", 2, 90, "2025-02-10").await;
+        assert_eq!(posted_pnl(&db, 1).await.len(), 2);
+
+        // La première vente est supprimée (soft delete) : son gain ne doit plus figurer au wallet
+        trade::Entity::update_many()
+            .col_expr(trade::Column::DeletedAt, sea_query::Expr::value(chrono::Utc::now().naive_utc()))
+            .filter(trade::Column::TradeType.eq("vente"))
+            .filter(trade::Column::Date.eq("2025-02-03"))
+            .exec(&db)
+            .await
+            .unwrap();
+        TradeService::recalculate_fifo(&db, 1).await.unwrap();
+
+        assert_eq!(posted_pnl(&db, 1).await, vec![("perte".to_string(), Decimal::from(20), "CAD".to_string())]);
+    }
+
+    #[test]
+    fn test_realized_pnl_action_sign() {
+        assert_eq!(realized_pnl_action(Decimal::from(40)), ("gain", Decimal::from(40)));
+        assert_eq!(realized_pnl_action(Decimal::from(-30)), ("perte", Decimal::from(30)));
+        assert_eq!(realized_pnl_action(Decimal::ZERO), ("gain", Decimal::ZERO));
+    }
+
+    fn symbols(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| String::from(*s)).collect()
+    }
+
+    #[test]
+    fn test_open_positions_limit_at_and_over_the_limit() {
+        let held = symbols(&["AAPL", "MSFT"]);
+
+        // 1 symbole de marge : le 2e nouveau symbole atteint la limite, pas au-delà
+        assert!(check_open_positions_limit(&symbols(&["AAPL"]), "MSFT", Some(2)).is_ok());
+        // À la limite : un nouveau symbole la dépasserait
+        let err = check_open_positions_limit(&held, "NVDA", Some(2)).unwrap_err();
+        assert!(err.contains("2 symbols held (2 max)"), "{}", err);
+        // Au-delà (limite abaissée après coup) : toujours refusé
+        assert!(check_open_positions_limit(&held, "NVDA", Some(1)).is_err());
+
+        // Renforcer une position existante reste permis, même au-delà de la limite
+        assert!(check_open_positions_limit(&held, "aapl", Some(2)).is_ok());
+        assert!(check_open_positions_limit(&held, "MSFT", Some(1)).is_ok());
+        assert!(check_open_positions_limit(&held, "NVDA", None).is_ok());
+    }
+
+    #[test]
+    fn test_dry_run_reports_open_positions_limit() {
+        let ctx = TradeContext {
+            open_symbols: symbols(&["MSFT"]),
+            max_open_positions: Some(1),
+            ..context(5000, vec![])
+        };
+
+        let result = evaluate_trade(&request("achat", 10, 150), &ctx);
+        assert!(!result.valid);
+        assert!(result.reasons[0].starts_with("Open positions limit reached"), "{:?}", result.reasons);
+
+        // Une vente n'est jamais concernée
+        let lots = vec![trade(1, "achat", "2025-01-10", 5, 5)];
+        let sale = evaluate_trade(&request("vente", 5, 150), &TradeContext { open_buy_lots: lots, ..ctx });
+        assert!(sale.valid, "{:?}", sale.reasons);
+    }
+}
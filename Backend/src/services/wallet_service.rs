@@ -1,13 +1,49 @@
 use sea_orm::*;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
+use std::str::FromStr;
 use crate::models::{wallet, trade, stock};
+use crate::utils::currency;
+use crate::utils::dates::market_today;
+use tracing::{info, warn};
 
 pub struct WalletService;
 
+/// Capital virtuel d'un nouveau compte papier, par devise (PAPER_STARTING_CASH="CAD:10000,USD:10000")
+pub const DEFAULT_PAPER_STARTING_CASH: &str = "CAD:10000,USD:10000";
+
+/// Lit "DEVISE:MONTANT,..." : devises non supportées, montants ≤ 0 ou trop précis ignorés
+/// (avertissement). Une chaîne vide désactive l'amorçage ; une devise répétée garde la dernière valeur.
+pub fn parse_paper_starting_cash(raw: &str) -> Vec<(String, Decimal)> {
+    let mut amounts: Vec<(String, Decimal)> = Vec::new();
+
+    for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(code, amount)| {
+            let code = code.trim().to_uppercase();
+            let amount = Decimal::from_str(amount.trim()).ok()?;
+            (amount > Decimal::ZERO && currency::validate_amount(&code, amount).is_ok()).then_some((code, amount))
+        });
+
+        match parsed {
+            Some((code, amount)) => {
+                amounts.retain(|(c, _)| *c != code);
+                amounts.push((code, amount));
+            }
+            None => warn!("Ignoring invalid PAPER_STARTING_CASH entry: {}", entry),
+        }
+    }
+
+    amounts
+}
+
+/// PAPER_STARTING_CASH, sinon DEFAULT_PAPER_STARTING_CASH
+pub fn paper_starting_cash_from_env() -> Vec<(String, Decimal)> {
+    let raw = std::env::var("PAPER_STARTING_CASH").unwrap_or_else(|_| DEFAULT_PAPER_STARTING_CASH.to_string());
+    parse_paper_starting_cash(&raw)
+}
+
 /// Représente la balance pour une devise spécifique
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct CurrencyBalance {
     pub currency: String,
     pub total: Decimal,        // Total du wallet (ajouts + gains - pertes - retraits)
@@ -92,7 +128,12 @@ impl WalletService {
     ) -> Result<String, DbErr> {
         let treasury = Self::get_treasury_for_currency(db, user_id, currency).await?;
 
-        Ok(format!(
+        Ok(Self::insufficient_funds_message(treasury, currency, required_amount))
+    }
+
+    /// Message d'erreur de fonds insuffisants pour une trésorerie déjà connue
+    pub fn insufficient_funds_message(treasury: Decimal, currency: &str, required_amount: Decimal) -> String {
+        format!(
             "Insufficient funds: {} {} available, {} {} required (shortage: {} {})",
             treasury,
             currency,
@@ -100,16 +141,87 @@ impl WalletService {
             currency,
             required_amount - treasury,
             currency
-        ))
+        )
     }
 
-    /// Calcule le total du wallet par devise (ajouts + gains - pertes - retraits)
+    /// Balances du compte papier : total du wallet papier par devise.
+    /// Les trades sont tous réels pour l'instant, rien n'y est donc investi.
+    pub async fn calculate_paper_balances<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> Result<Vec<CurrencyBalance>, DbErr> {
+        let totals = Self::wallet_totals(db, user_id, wallet::PAPER_ACCOUNT).await?;
+
+        let mut balances: Vec<CurrencyBalance> = totals
+            .into_iter()
+            .map(|(currency, total)| CurrencyBalance { currency, total, invested: Decimal::ZERO, treasury: total })
+            .collect();
+        balances.sort_by(|a, b| a.currency.cmp(&b.currency));
+
+        Ok(balances)
+    }
+
+    /// Amorce le compte papier avec PAPER_STARTING_CASH (un ajout par devise), une seule fois :
+    /// rien n'est fait si l'utilisateur a déjà une entrée papier. Le compte réel n'est jamais touché.
+    /// Retourne le nombre d'ajouts créés.
+    pub async fn seed_paper_wallet<C: ConnectionTrait>(db: &C, user_id: i32) -> Result<usize, DbErr> {
+        Self::seed_paper_wallet_with(db, user_id, &paper_starting_cash_from_env()).await
+    }
+
+    pub async fn seed_paper_wallet_with<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        starting_cash: &[(String, Decimal)],
+    ) -> Result<usize, DbErr> {
+        let already_seeded = wallet::Entity::find()
+            .filter(wallet::Column::UserId.eq(user_id))
+            .filter(wallet::Column::AccountType.eq(wallet::PAPER_ACCOUNT))
+            .count(db)
+            .await?
+            > 0;
+        if already_seeded {
+            return Ok(0);
+        }
+
+        let today = market_today().format("%Y-%m-%d").to_string();
+        for (code, amount) in starting_cash {
+            wallet::ActiveModel {
+                user_id: Set(user_id),
+                date: Set(today.clone()),
+                action: Set("ajout".to_string()),
+                symbol: Set(None),
+                amount: Set(*amount),
+                currency: Set(code.clone()),
+                account_type: Set(wallet::PAPER_ACCOUNT.to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await?;
+        }
+
+        if !starting_cash.is_empty() {
+            info!("Paper wallet seeded for user {}: {} currencies", user_id, starting_cash.len());
+        }
+        Ok(starting_cash.len())
+    }
+
+    /// Calcule le total du wallet réel par devise (ajouts + gains - pertes - retraits)
     async fn calculate_wallet_totals(
         db: &DatabaseConnection,
         user_id: i32,
+    ) -> Result<HashMap<String, Decimal>, DbErr> {
+        Self::wallet_totals(db, user_id, wallet::REAL_ACCOUNT).await
+    }
+
+    /// Total par devise des entrées d'un type de compte (REAL_ACCOUNT / PAPER_ACCOUNT)
+    async fn wallet_totals<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        account_type: &str,
     ) -> Result<HashMap<String, Decimal>, DbErr> {
         let transactions = wallet::Entity::find()
             .filter(wallet::Column::UserId.eq(user_id))
+            .filter(wallet::Column::AccountType.eq(account_type))
             .all(db)
             .await?;
 
@@ -128,12 +240,41 @@ impl WalletService {
         Ok(totals)
     }
 
+    /// Devise effective d'un trade : devise saisie sur le trade en priorité, sinon devise du stock
+    /// None si le trade n'a pas de symbole
+    pub async fn trade_currency<C: ConnectionTrait>(
+        db: &C,
+        t: &trade::Model,
+    ) -> Result<Option<String>, DbErr> {
+        let symbol = match &t.symbol {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+
+        if let Some(trade_currency) = t.currency.as_deref() {
+            return Ok(Some(currency::resolve(Some(trade_currency), None)));
+        }
+
+        let stock_option = stock::Entity::find()
+            .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+            .one(db)
+            .await?;
+
+        Ok(Some(match stock_option {
+            Some(s) => currency::or_default(s.currency),
+            None => {
+                warn!("Stock not found for symbol: {}, defaulting to {}", symbol, currency::DEFAULT_CURRENCY);
+                currency::DEFAULT_CURRENCY.to_string()
+            }
+        }))
+    }
+
     /// Calcule les montants investis par devise (positions ouvertes)
     async fn calculate_invested_amounts(
         db: &DatabaseConnection,
         user_id: i32,
     ) -> Result<HashMap<String, Decimal>, DbErr> {
-        let trades = trade::Entity::find()
+        let trades = trade::Entity::find_active()
             .filter(trade::Column::UserId.eq(user_id))
             .all(db)
             .await?;
@@ -141,25 +282,11 @@ impl WalletService {
         let mut invested: HashMap<String, Decimal> = HashMap::new();
 
         for t in trades {
-            let symbol = match &t.symbol {
-                Some(s) => s,
+            let currency = match Self::trade_currency(db, &t).await? {
+                Some(c) => c,
                 None => continue,
             };
 
-            // Récupérer la devise du stock
-            let stock_option = stock::Entity::find()
-                .filter(stock::Column::SymbolAlphavantage.eq(symbol))
-                .one(db)
-                .await?;
-
-            let currency = match stock_option {
-                Some(s) => s.currency.unwrap_or_else(|| "CAD".to_string()),
-                None => {
-                    eprintln!("⚠️  Stock not found for symbol: {}, defaulting to CAD", symbol);
-                    "CAD".to_string()
-                }
-            };
-
             let inv = invested.entry(currency).or_insert(Decimal::ZERO);
 
             // Calculer le montant selon le type de trade
@@ -183,4 +310,50 @@ impl WalletService {
 
         Ok(invested)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn wallet_db() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(wallet::Entity),
+            schema.create_table_from_entity(trade::Entity),
+            schema.create_table_from_entity(stock::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+        db
+    }
+
+    #[test]
+    fn paper_starting_cash_skips_invalid_entries() {
+        let cash = parse_paper_starting_cash("cad:25000, XYZ:100, USD:-5, JPY:10.5, USD:5000");
+        assert_eq!(cash, vec![
+            ("CAD".to_string(), Decimal::from(25000)),
+            ("USD".to_string(), Decimal::from(5000)),
+        ]);
+        assert!(parse_paper_starting_cash("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_new_user_paper_wallet_is_seeded_and_real_stays_empty() {
+        let db = wallet_db().await;
+        let cash = parse_paper_starting_cash("CAD:10000,USD:2500");
+
+        assert_eq!(WalletService::seed_paper_wallet_with(&db, 7, &cash).await.unwrap(), 2);
+        // Deuxième accès : déjà amorcé, rien n'est ajouté
+        assert_eq!(WalletService::seed_paper_wallet_with(&db, 7, &cash).await.unwrap(), 0);
+
+        let paper = WalletService::calculate_paper_balances(&db, 7).await.unwrap();
+        let treasury: Vec<(String, Decimal)> = paper.iter().map(|b| (b.currency.clone(), b.treasury)).collect();
+        assert_eq!(treasury, cash);
+
+        let real = WalletService::calculate_balances(&db, 7).await.unwrap();
+        assert!(real.iter().all(|b| b.treasury == Decimal::ZERO));
+        assert_eq!(WalletService::get_treasury_for_currency(&db, 7, "CAD").await.unwrap(), Decimal::ZERO);
+    }
+}
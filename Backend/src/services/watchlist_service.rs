@@ -0,0 +1,219 @@
+// ============================================================================
+// SERVICE : WATCHLIST
+// ============================================================================
+//
+// Description:
+//   Symboles suivis par un utilisateur (watchlist_rust), pour voir leurs
+//   recommandations sans les détenir. GET /api/watchlist renvoie pour chaque
+//   symbole le dernier cours et la dernière recommandation de chaque stratégie ;
+//   le digest quotidien (digest_service) lit la même table.
+//
+// Points d'attention:
+//   - Seuls les symboles présents dans la table stock peuvent être suivis
+//   - Plafond de MAX_WATCHLIST_SYMBOLS symboles par utilisateur
+//   - Ajouter un symbole déjà suivi ne fait rien (ni erreur, ni doublon)
+//
+// ============================================================================
+
+use chrono::{NaiveDateTime, Utc};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set};
+use serde::Serialize;
+
+use crate::models::dto::StrategyWithResult;
+use crate::models::{stock, watchlist};
+use crate::services::data_quality;
+use crate::services::digest_service;
+use crate::utils::dates::market_today;
+
+pub const MAX_WATCHLIST_SYMBOLS: u64 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchlistError {
+    UnknownSymbol(String),
+    LimitReached,
+    Database(String),
+}
+
+/// Symbole suivi, avec son dernier cours et ses dernières recommandations
+#[derive(Debug, Serialize)]
+pub struct WatchlistItem {
+    pub symbol: String,
+    pub added_at: NaiveDateTime,
+    pub price: Option<f64>,
+    pub price_date: Option<String>,
+    pub stale: bool,
+    pub strategies: Vec<StrategyWithResult>,
+}
+
+/// Ajoute un symbole (doit exister dans la table stock) ; false s'il était déjà suivi
+pub async fn add(user_id: i32, symbol: &str, db: &DatabaseConnection) -> Result<bool, WatchlistError> {
+    let db_err = |e: sea_orm::DbErr| WatchlistError::Database(e.to_string());
+
+    let known = stock::Entity::find()
+        .filter(stock::Column::SymbolAlphavantage.eq(symbol))
+        .count(db)
+        .await
+        .map_err(db_err)?;
+    if known == 0 {
+        return Err(WatchlistError::UnknownSymbol(symbol.to_string()));
+    }
+
+    let already = watchlist::Entity::find_by_id((user_id, symbol.to_string()))
+        .one(db)
+        .await
+        .map_err(db_err)?;
+    if already.is_some() {
+        return Ok(false);
+    }
+
+    let watched = watchlist::Entity::find()
+        .filter(watchlist::Column::UserId.eq(user_id))
+        .count(db)
+        .await
+        .map_err(db_err)?;
+    if watched >= MAX_WATCHLIST_SYMBOLS {
+        return Err(WatchlistError::LimitReached);
+    }
+
+    let row = watchlist::ActiveModel {
+        user_id: Set(user_id),
+        symbol: Set(symbol.to_string()),
+        added_at: Set(Utc::now().naive_utc()),
+    };
+    // Deux ajouts simultanés du même symbole : le second ne fait rien
+    watchlist::Entity::insert(row)
+        .on_conflict(
+            OnConflict::columns([watchlist::Column::UserId, watchlist::Column::Symbol])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(db)
+        .await
+        .map_err(db_err)?;
+
+    Ok(true)
+}
+
+/// Retire un symbole ; false s'il n'était pas suivi
+pub async fn remove(user_id: i32, symbol: &str, db: &DatabaseConnection) -> Result<bool, String> {
+    watchlist::Entity::delete_by_id((user_id, symbol.to_string()))
+        .exec(db)
+        .await
+        .map(|res| res.rows_affected > 0)
+        .map_err(|e| format!("Failed to remove from watchlist: {}", e))
+}
+
+/// Watchlist d'un utilisateur par ordre alphabétique, avec cours et recommandations
+pub async fn list(user_id: i32, db: &DatabaseConnection) -> Result<Vec<WatchlistItem>, String> {
+    let rows = watchlist::Entity::find()
+        .filter(watchlist::Column::UserId.eq(user_id))
+        .order_by_asc(watchlist::Column::Symbol)
+        .all(db)
+        .await
+        .map_err(|e| format!("Failed to fetch watchlist: {}", e))?;
+
+    let symbols: Vec<String> = rows.iter().map(|w| w.symbol.clone()).collect();
+    let (today, stale_days) = (market_today(), data_quality::stale_price_days_from_env());
+    let mut snapshots = digest_service::snapshots(&symbols, today, stale_days, db).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let snapshot = snapshots.remove(&row.symbol).unwrap_or_default();
+            let price_date = snapshot.latest_bar.as_ref().map(|b| b.date.clone());
+            let (_, stale) = data_quality::freshness(price_date.as_deref(), today, stale_days);
+
+            WatchlistItem {
+                symbol: row.symbol,
+                added_at: row.added_at,
+                price: snapshot.latest_bar.map(|b| b.close),
+                price_date,
+                stale,
+                strategies: snapshot.strategies,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{historic_data, strategy, strategy_result};
+    use sea_orm::{ActiveModelTrait, ConnectionTrait, Database, DatabaseBackend, Schema};
+
+    async fn watchlist_db(stocks: usize) -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap(); // Pas de table users ici
+        let schema = Schema::new(DatabaseBackend::Sqlite);
+        for table in [
+            schema.create_table_from_entity(watchlist::Entity),
+            schema.create_table_from_entity(stock::Entity),
+            schema.create_table_from_entity(historic_data::Entity),
+            schema.create_table_from_entity(strategy::Entity),
+            schema.create_table_from_entity(strategy_result::Entity),
+        ] {
+            db.execute(DatabaseBackend::Sqlite.build(&table)).await.unwrap();
+        }
+
+        for i in 0..stocks {
+            stock::ActiveModel {
+                compagny_name: Set(format!("Company {}", i)),
+                symbol_alphavantage: Set(Some(format!("S{}", i))),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    #[tokio::test]
+    async fn add_list_and_remove() {
+        let db = watchlist_db(2).await;
+        historic_data::ActiveModel {
+            symbol: Set("S0".to_string()),
+            date: Set("2025-06-02".to_string()),
+            open: Set(Some("10".to_string())),
+            high: Set(Some("11".to_string())),
+            low: Set(Some("9".to_string())),
+            close: Set(Some("10.5".to_string())),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        assert_eq!(add(1, "S0", &db).await, Ok(true));
+        assert_eq!(add(1, "S0", &db).await, Ok(false));
+        assert_eq!(add(1, "S1", &db).await, Ok(true));
+        assert_eq!(add(1, "NOPE", &db).await, Err(WatchlistError::UnknownSymbol("NOPE".to_string())));
+
+        let items = list(1, &db).await.unwrap();
+        let symbols: Vec<&str> = items.iter().map(|i| i.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["S0", "S1"]);
+        assert_eq!((items[0].price, items[0].price_date.as_deref()), (Some(10.5), Some("2025-06-02")));
+        assert_eq!(items[1].price, None);
+        assert!(list(2, &db).await.unwrap().is_empty());
+
+        assert_eq!(remove(1, "S0", &db).await, Ok(true));
+        assert_eq!(remove(1, "S0", &db).await, Ok(false));
+        assert_eq!(list(1, &db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn watchlist_is_capped() {
+        let db = watchlist_db(MAX_WATCHLIST_SYMBOLS as usize + 1).await;
+        for i in 0..MAX_WATCHLIST_SYMBOLS {
+            assert_eq!(add(1, &format!("S{}", i), &db).await, Ok(true));
+        }
+
+        let last = format!("S{}", MAX_WATCHLIST_SYMBOLS);
+        assert_eq!(add(1, &last, &db).await, Err(WatchlistError::LimitReached));
+        // Un symbole déjà suivi reste accepté au plafond, et un autre utilisateur n'est pas concerné
+        assert_eq!(add(1, "S0", &db).await, Ok(false));
+        assert_eq!(add(2, &last, &db).await, Ok(true));
+    }
+}
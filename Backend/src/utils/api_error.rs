@@ -0,0 +1,90 @@
+// Enveloppe d'erreur commune des API : {"error": "...", "code": "...", "validation": [...]}
+// Les erreurs du crate validator (map imbriquée champ → erreurs) sont aplaties en une liste
+// {field, code, message} triée par champ, pour que le frontend n'ait qu'une forme à lire.
+
+use actix_web::HttpResponse;
+use serde::Serialize;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+pub const VALIDATION_FAILED: &str = "validation_failed";
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ApiError {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub validation: Vec<FieldError>,
+}
+
+/// Une erreur de validation : champ (chemin pointé pour les structures imbriquées, ex: "lots[0].quantite")
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub code: String,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(error: impl Into<String>) -> Self {
+        Self { error: error.into(), code: None, validation: Vec::new() }
+    }
+
+    /// Erreurs de validator → {"error": "Validation failed", "code": "validation_failed", "validation": [...]}
+    pub fn validation(errors: &ValidationErrors) -> Self {
+        let mut validation = Vec::new();
+        flatten(errors, "", &mut validation);
+        validation.sort_by(|a, b| a.field.cmp(&b.field).then_with(|| a.code.cmp(&b.code)));
+
+        Self {
+            error: "Validation failed".to_string(),
+            code: Some(VALIDATION_FAILED.to_string()),
+            validation,
+        }
+    }
+}
+
+/// 400 avec l'enveloppe ApiError pour des erreurs de validator
+pub fn validation_error_response(errors: &ValidationErrors) -> HttpResponse {
+    HttpResponse::BadRequest().json(ApiError::validation(errors))
+}
+
+fn flatten(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() { field.to_string() } else { format!("{}.{}", prefix, field) };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                out.extend(field_errors.iter().map(|e| FieldError {
+                    field: path.clone(),
+                    code: e.code.to_string(),
+                    message: message(e),
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => flatten(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    flatten(nested, &format!("{}[{}]", path, index), out);
+                }
+            }
+        }
+    }
+}
+
+/// Message du validateur s'il en a un, sinon déduit des paramètres min / max, sinon du code
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |key: &str| error.params.get(key).map(|v| v.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("length must be between {} and {}", min, max),
+        ("length", Some(min), None) => format!("length must be at least {}", min),
+        ("length", None, Some(max)) => format!("length must be at most {}", max),
+        ("range", Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+        ("range", Some(min), None) => format!("must be at least {}", min),
+        ("range", None, Some(max)) => format!("must be at most {}", max),
+        (code, _, _) => code.replace('_', " "),
+    }
+}
@@ -0,0 +1,102 @@
+use rust_decimal::Decimal;
+
+/// Devise par défaut quand un stock n'a pas de currency renseignée
+pub const DEFAULT_CURRENCY: &str = "CAD";
+
+/// Devises acceptées (code ISO 4217, nombre de décimales)
+/// Ajouter une ligne ici suffit pour supporter une nouvelle devise partout
+pub const SUPPORTED_CURRENCIES: &[(&str, u32)] = &[
+    ("CAD", 2),
+    ("USD", 2),
+    ("EUR", 2),
+    ("GBP", 2),
+    ("JPY", 0),
+    ("CHF", 2),
+    ("AUD", 2),
+    ("NZD", 2),
+    ("HKD", 2),
+];
+
+/// Nombre de décimales autorisées pour une devise (None si devise inconnue)
+pub fn decimal_places(currency: &str) -> Option<u32> {
+    SUPPORTED_CURRENCIES
+        .iter()
+        .find(|(code, _)| *code == currency)
+        .map(|(_, places)| *places)
+}
+
+pub fn is_supported(currency: &str) -> bool {
+    decimal_places(currency).is_some()
+}
+
+/// Liste lisible pour les messages d'erreur ("CAD, USD, EUR, ...")
+pub fn supported_list() -> String {
+    SUPPORTED_CURRENCIES
+        .iter()
+        .map(|(code, _)| *code)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Devise d'un stock, avec fallback sur la devise par défaut
+pub fn or_default(currency: Option<String>) -> String {
+    currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_string())
+}
+
+/// Devise effective d'un trade : la devise saisie sur le trade l'emporte sur celle du stock
+pub fn resolve(trade_currency: Option<&str>, stock_currency: Option<String>) -> String {
+    match trade_currency {
+        Some(c) => c.trim().to_uppercase(),
+        None => or_default(stock_currency),
+    }
+}
+
+/// Vérifie que la devise est supportée et que le montant respecte ses décimales
+/// (ex: 100.5 JPY est refusé, JPY n'a pas de décimales)
+pub fn validate_amount(currency: &str, amount: Decimal) -> Result<(), String> {
+    let places = decimal_places(currency).ok_or_else(|| {
+        format!("Invalid currency. Must be one of: {}", supported_list())
+    })?;
+
+    if amount.normalize().scale() > places {
+        return Err(format!(
+            "Invalid amount for {}: at most {} decimal place(s) allowed",
+            currency, places
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_new_currency_is_allowed() {
+        assert!(is_supported("GBP"));
+        assert!(validate_amount("GBP", Decimal::from_str("125.50").unwrap()).is_ok());
+        assert!(validate_amount("JPY", Decimal::from_str("15000").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_currency_is_rejected() {
+        assert!(!is_supported("XYZ"));
+        assert!(validate_amount("XYZ", Decimal::from(100)).is_err());
+    }
+
+    #[test]
+    fn test_trade_currency_overrides_stock() {
+        assert_eq!(resolve(Some("usd"), Some("CAD".to_string())), "USD");
+        assert_eq!(resolve(None, Some("EUR".to_string())), "EUR");
+        assert_eq!(resolve(None, None), DEFAULT_CURRENCY);
+    }
+
+    #[test]
+    fn test_decimal_places_per_currency() {
+        assert!(validate_amount("JPY", Decimal::from_str("100.5").unwrap()).is_err());
+        assert!(validate_amount("USD", Decimal::from_str("100.505").unwrap()).is_err());
+        assert!(validate_amount("USD", Decimal::from_str("100.500").unwrap()).is_ok());
+    }
+}
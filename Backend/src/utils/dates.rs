@@ -0,0 +1,143 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Parse une date de trade dans l'un des formats rencontrés en BD
+/// - "YYYY-MM-DD" (format API actuel)
+/// - "DD/MM/YYYY" (ancien format importé depuis Python)
+pub fn parse_trade_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%d/%m/%Y"))
+        .ok()
+}
+
+/// Date moyenne pondérée par la quantité de chaque lot
+/// Retourne None si aucun lot ou quantité totale nulle
+pub fn weighted_average_date(lots: &[(NaiveDate, Decimal)]) -> Option<NaiveDate> {
+    let total_quantity: Decimal = lots.iter().map(|(_, q)| *q).sum();
+    if total_quantity <= Decimal::ZERO {
+        return None;
+    }
+
+    let weighted_days: Decimal = lots
+        .iter()
+        .map(|(date, q)| Decimal::from(date.num_days_from_ce()) * *q)
+        .sum();
+
+    let avg_days = (weighted_days / total_quantity).round().to_i32()?;
+    NaiveDate::from_num_days_from_ce_opt(avg_days)
+}
+
+/// Fuseau horaire par défaut du marché (NYSE/NASDAQ)
+pub const DEFAULT_MARKET_TZ: Tz = chrono_tz::America::New_York;
+
+/// Fuseau horaire du marché, configurable via MARKET_TZ (ex: "America/Toronto")
+/// Valeur invalide → fuseau par défaut
+pub fn market_tz() -> Tz {
+    parse_market_tz(std::env::var("MARKET_TZ").ok().as_deref())
+}
+
+/// Fuseau lu depuis une valeur de MARKET_TZ (absente ou invalide → fuseau par défaut)
+pub fn parse_market_tz(value: Option<&str>) -> Tz {
+    value
+        .and_then(|v| v.trim().parse::<Tz>().ok())
+        .unwrap_or(DEFAULT_MARKET_TZ)
+}
+
+/// Date de bourse correspondant à un instant UTC dans le fuseau du marché
+pub fn trading_date_at(now: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    now.with_timezone(&tz).date_naive()
+}
+
+/// "Aujourd'hui" dans le fuseau du marché, indépendamment du fuseau du serveur
+pub fn market_today() -> NaiveDate {
+    market_today_in(market_tz())
+}
+
+/// "Aujourd'hui" dans un fuseau donné
+pub fn market_today_in(tz: Tz) -> NaiveDate {
+    trading_date_at(Utc::now(), tz)
+}
+
+/// Nombre de jours ouvrés (lun-ven) écoulés après `from` jusqu'à `to` inclus
+/// 0 si `to` <= `from`. Les jours fériés ne sont pas pris en compte.
+pub fn trading_days_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|d| *d <= to)
+        .filter(|d| !matches!(d.weekday(), chrono::Weekday::Sat | chrono::Weekday::Sun))
+        .count() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trading_days_skip_weekends() {
+        let friday = NaiveDate::from_ymd_opt(2025, 6, 6).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2025, 6, 9).unwrap();
+        let next_friday = NaiveDate::from_ymd_opt(2025, 6, 13).unwrap();
+
+        assert_eq!(trading_days_between(friday, friday), 0);
+        assert_eq!(trading_days_between(friday, monday), 1);
+        assert_eq!(trading_days_between(friday, next_friday), 5);
+        assert_eq!(trading_days_between(monday, friday), 0);
+    }
+
+    #[test]
+    fn test_parse_both_formats() {
+        let expected = NaiveDate::from_ymd_opt(2025, 6, 1);
+        assert_eq!(parse_trade_date("2025-06-01"), expected);
+        assert_eq!(parse_trade_date("01/06/2025"), expected);
+        assert_eq!(parse_trade_date("not a date"), None);
+    }
+
+    #[test]
+    fn test_weighted_date_between_two_buys() {
+        let first = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let second = NaiveDate::from_ymd_opt(2025, 1, 29).unwrap();
+
+        // 10 unités le 1er, 30 unités le 29 → 3/4 du chemin (21 jours)
+        let avg = weighted_average_date(&[
+            (first, Decimal::from(10)),
+            (second, Decimal::from(30)),
+        ])
+        .unwrap();
+
+        assert!(avg > first && avg < second);
+        assert_eq!(avg, NaiveDate::from_ymd_opt(2025, 1, 22).unwrap());
+    }
+
+    #[test]
+    fn test_trading_date_uses_market_timezone() {
+        // 16 janvier 03:30 UTC = 15 janvier 22:30 à New York
+        let now = DateTime::parse_from_rfc3339("2025-01-16T03:30:00Z").unwrap().with_timezone(&Utc);
+
+        assert_eq!(trading_date_at(now, DEFAULT_MARKET_TZ), NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+        assert_eq!(trading_date_at(now, chrono_tz::UTC), NaiveDate::from_ymd_opt(2025, 1, 16).unwrap());
+        assert_eq!(trading_date_at(now, chrono_tz::Asia::Tokyo), NaiveDate::from_ymd_opt(2025, 1, 16).unwrap());
+    }
+
+    #[test]
+    fn test_market_tz_parsing() {
+        assert_eq!(parse_market_tz(Some(" Europe/Paris ")), chrono_tz::Europe::Paris);
+        assert_eq!(parse_market_tz(Some("Not/AZone")), DEFAULT_MARKET_TZ);
+        assert_eq!(parse_market_tz(None), DEFAULT_MARKET_TZ);
+    }
+
+    #[test]
+    fn test_market_today_in_explicit_timezone() {
+        // Kiritimati (UTC+14) et Pago Pago (UTC-11) : jamais le même jour calendaire
+        let (ahead, behind) = (chrono_tz::Pacific::Kiritimati, chrono_tz::Pacific::Pago_Pago);
+        let before = Utc::now();
+        let (today_ahead, today_behind) = (market_today_in(ahead), market_today_in(behind));
+        let after = Utc::now();
+
+        assert!([trading_date_at(before, ahead), trading_date_at(after, ahead)].contains(&today_ahead));
+        assert!([trading_date_at(before, behind), trading_date_at(after, behind)].contains(&today_behind));
+        assert!(today_ahead > today_behind);
+    }
+}
@@ -0,0 +1,270 @@
+// Vérification des id_token Google (endpoint tokeninfo) avec retry/backoff.
+// Seules les erreurs transitoires (connexion, timeout, 5xx) sont retentées ;
+// une réponse 4xx signifie que le token est invalide.
+// Le claim `aud` doit correspondre à GOOGLE_CLIENT_ID : un token émis pour une autre
+// application est refusé même si Google le considère valide.
+
+use serde::Deserialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::warn;
+
+pub const DEFAULT_TOKENINFO_URL: &str = "https://oauth2.googleapis.com/tokeninfo";
+const DEFAULT_TIMEOUT_SECS: u64 = 5;
+const MAX_ATTEMPTS: u32 = 3;
+const BASE_BACKOFF_MS: u64 = 200;
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+pub struct GoogleTokenInfo {
+    pub sub: String,        // Google ID unique
+    pub email: String,
+    pub name: Option<String>,
+    pub email_verified: Option<String>,
+    pub aud: Option<String>, // Client id OAuth pour lequel le token a été émis
+}
+
+impl GoogleTokenInfo {
+    /// tokeninfo renvoie email_verified en chaîne ("true" / "false")
+    pub fn is_email_verified(&self) -> bool {
+        self.email_verified.as_deref() == Some("true")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GoogleVerifyError {
+    /// Google a rejeté le token (4xx) ou la réponse est inexploitable → 401
+    InvalidToken,
+    /// Token valide mais émis pour une autre application (aud ≠ GOOGLE_CLIENT_ID) → 401
+    WrongAudience,
+    /// Google injoignable après toutes les tentatives → 503
+    Unavailable(String),
+}
+
+/// Nombre de tentatives et délai de base (doublé à chaque nouvel essai)
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(BASE_BACKOFF_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Délai avant la tentative `attempt` (1-indexée) : base, 2×base, 4×base...
+    fn delay_before(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.pow(attempt.saturating_sub(2))
+    }
+}
+
+/// URL tokeninfo (GOOGLE_TOKENINFO_URL pour pointer vers un mock)
+pub fn tokeninfo_url() -> String {
+    std::env::var("GOOGLE_TOKENINFO_URL").unwrap_or_else(|_| DEFAULT_TOKENINFO_URL.to_string())
+}
+
+/// Client id OAuth de l'application (GOOGLE_CLIENT_ID), None si absent ou vide
+pub fn client_id() -> Option<String> {
+    std::env::var("GOOGLE_CLIENT_ID")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Client HTTP partagé avec timeout explicite (GOOGLE_HTTP_TIMEOUT_SECS, défaut 5s)
+pub fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let timeout = std::env::var("GOOGLE_HTTP_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+        reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Vérifie un id_token auprès de Google avec la politique de retry par défaut
+/// Sans GOOGLE_CLIENT_ID configuré, aucun token n'est accepté (Unavailable → 503)
+pub async fn verify_id_token(id_token: &str) -> Result<GoogleTokenInfo, GoogleVerifyError> {
+    let Some(audience) = client_id() else {
+        return Err(GoogleVerifyError::Unavailable("GOOGLE_CLIENT_ID is not configured".to_string()));
+    };
+
+    verify_id_token_with(client(), &tokeninfo_url(), id_token, &audience, RetryPolicy::default()).await
+}
+
+/// Refuse un token dont le claim `aud` ne correspond pas au client id attendu
+fn check_audience(info: GoogleTokenInfo, audience: &str) -> Result<GoogleTokenInfo, GoogleVerifyError> {
+    if info.aud.as_deref() == Some(audience) {
+        Ok(info)
+    } else {
+        warn!("Google token rejected: audience {:?} does not match the configured client id", info.aud);
+        Err(GoogleVerifyError::WrongAudience)
+    }
+}
+
+pub async fn verify_id_token_with(
+    client: &reqwest::Client,
+    url: &str,
+    id_token: &str,
+    audience: &str,
+    policy: RetryPolicy,
+) -> Result<GoogleTokenInfo, GoogleVerifyError> {
+    let mut last_error = String::new();
+
+    for attempt in 1..=policy.max_attempts {
+        if attempt > 1 {
+            tokio::time::sleep(policy.delay_before(attempt)).await;
+        }
+
+        match client.get(url).query(&[("id_token", id_token)]).send().await {
+            Ok(resp) if resp.status().is_server_error() => {
+                last_error = format!("Google returned {}", resp.status());
+            }
+            Ok(resp) if !resp.status().is_success() => {
+                return Err(GoogleVerifyError::InvalidToken);
+            }
+            Ok(resp) => {
+                let info = resp.json::<GoogleTokenInfo>().await.map_err(|e| {
+                    warn!("Failed to parse Google tokeninfo response: {}", e);
+                    GoogleVerifyError::InvalidToken
+                })?;
+                return check_audience(info, audience);
+            }
+            Err(e) if e.is_connect() || e.is_timeout() => {
+                last_error = e.to_string();
+            }
+            Err(e) => return Err(GoogleVerifyError::Unavailable(e.to_string())),
+        }
+
+        warn!("Google token verification attempt {}/{} failed: {}", attempt, policy.max_attempts, last_error);
+    }
+
+    Err(GoogleVerifyError::Unavailable(last_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const CLIENT_ID: &str = "client-123.apps.googleusercontent.com";
+    const TOKEN_INFO: &str = r#"{"sub":"google-123","email":"jane@example.com","name":"Jane","email_verified":"true","aud":"client-123.apps.googleusercontent.com"}"#;
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(5) }
+    }
+
+    /// Mini serveur HTTP : répond aux requêtes successives avec les (status, body) donnés
+    /// (le dernier est répété) et compte les requêtes reçues.
+    async fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/tokeninfo", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else { return };
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = responses[index.min(responses.len() - 1)];
+
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        (url, hits)
+    }
+
+    #[actix_web::test]
+    async fn retries_transient_failure_then_succeeds() {
+        let (url, hits) = mock_server(vec![(503, "{}"), (200, TOKEN_INFO)]).await;
+
+        let info = verify_id_token_with(&reqwest::Client::new(), &url, "tok", CLIENT_ID, fast_policy()).await.unwrap();
+
+        assert_eq!(info.sub, "google-123");
+        assert!(info.is_email_verified());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn token_for_another_client_is_rejected() {
+        let other_app = r#"{"sub":"google-123","email":"jane@example.com","email_verified":"true","aud":"other-app.apps.googleusercontent.com"}"#;
+        let (url, hits) = mock_server(vec![(200, other_app)]).await;
+
+        let result = verify_id_token_with(&reqwest::Client::new(), &url, "tok", CLIENT_ID, fast_policy()).await;
+
+        assert_eq!(result.unwrap_err(), GoogleVerifyError::WrongAudience);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Sans claim aud : refusé aussi
+        let (url, _) = mock_server(vec![(200, r#"{"sub":"google-123","email":"jane@example.com"}"#)]).await;
+        let missing = verify_id_token_with(&reqwest::Client::new(), &url, "tok", CLIENT_ID, fast_policy()).await;
+        assert_eq!(missing.unwrap_err(), GoogleVerifyError::WrongAudience);
+    }
+
+    #[test]
+    fn email_verified_requires_true() {
+        let info = |verified: Option<&str>| GoogleTokenInfo {
+            sub: "google-123".to_string(),
+            email: "jane@example.com".to_string(),
+            name: None,
+            email_verified: verified.map(str::to_string),
+            aud: Some(CLIENT_ID.to_string()),
+        };
+
+        assert!(info(Some("true")).is_email_verified());
+        assert!(!info(Some("false")).is_email_verified());
+        assert!(!info(None).is_email_verified());
+    }
+
+    #[actix_web::test]
+    async fn rejected_token_is_not_retried() {
+        let (url, hits) = mock_server(vec![(400, r#"{"error":"invalid_token"}"#)]).await;
+
+        let result = verify_id_token_with(&reqwest::Client::new(), &url, "tok", CLIENT_ID, fast_policy()).await;
+
+        assert_eq!(result.unwrap_err(), GoogleVerifyError::InvalidToken);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn persistent_server_error_is_unavailable_after_max_attempts() {
+        let (url, hits) = mock_server(vec![(500, "{}")]).await;
+
+        let result = verify_id_token_with(&reqwest::Client::new(), &url, "tok", CLIENT_ID, fast_policy()).await;
+
+        assert!(matches!(result, Err(GoogleVerifyError::Unavailable(_))));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn backoff_doubles_between_attempts() {
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(200) };
+        assert_eq!(policy.delay_before(2), Duration::from_millis(200));
+        assert_eq!(policy.delay_before(3), Duration::from_millis(400));
+    }
+}
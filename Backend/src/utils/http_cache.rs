@@ -0,0 +1,85 @@
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+
+/// Durée de cache client par défaut (les données changent une fois par jour)
+pub const DEFAULT_MAX_AGE_SECONDS: u64 = 300;
+
+/// max-age configuré via HTTP_CACHE_MAX_AGE (secondes)
+pub fn max_age_from_env() -> u64 {
+    std::env::var("HTTP_CACHE_MAX_AGE")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_AGE_SECONDS)
+}
+
+/// ETag faible construit à partir des éléments qui versionnent la réponse
+/// Ex: weak_etag(&["stocks", "2025-06-30", "512"]) → W/"stocks-2025-06-30-512"
+pub fn weak_etag(parts: &[&str]) -> String {
+    let tag: String = parts
+        .join("-")
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && *c != '"')
+        .collect();
+    format!("W/\"{}\"", tag)
+}
+
+/// true si If-None-Match contient l'ETag (comparaison faible) ou "*"
+pub fn matches_if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+
+    req.headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+/// 304 Not Modified si le client a déjà cette version, sinon None
+pub fn not_modified(req: &HttpRequest, etag: &str) -> Option<HttpResponse> {
+    if !matches_if_none_match(req, etag) {
+        return None;
+    }
+
+    let mut builder = HttpResponse::NotModified();
+    Some(with_cache_headers(&mut builder, etag).finish())
+}
+
+/// Ajoute ETag + Cache-Control (private : réponses authentifiées)
+pub fn with_cache_headers<'a>(builder: &'a mut HttpResponseBuilder, etag: &str) -> &'a mut HttpResponseBuilder {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        builder.insert_header((header::ETAG, value));
+    }
+    builder.insert_header((header::CACHE_CONTROL, format!("private, max-age={}", max_age_from_env())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_matching_etag_returns_304() {
+        let etag = weak_etag(&["stocks", "2025-06-30", "512"]);
+        assert_eq!(etag, "W/\"stocks-2025-06-30-512\"");
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "\"other\", W/\"stocks-2025-06-30-512\""))
+            .to_http_request();
+
+        let response = not_modified(&req, &etag).unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap(), etag.as_str());
+        assert!(response.headers().get(header::CACHE_CONTROL).is_some());
+    }
+
+    #[test]
+    fn test_changed_data_is_served_again() {
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, "W/\"stocks-2025-06-27-512\""))
+            .to_http_request();
+
+        assert!(not_modified(&req, &weak_etag(&["stocks", "2025-06-30", "512"])).is_none());
+        assert!(not_modified(&TestRequest::default().to_http_request(), "W/\"x\"").is_none());
+    }
+}
@@ -13,7 +13,11 @@ pub struct Claims {
 /// Récupère la clé secrète JWT depuis les variables d'environnement
 /// PANIC si JWT_SECRET n'est pas défini (sécurité critique)
 fn get_jwt_secret() -> String {
-    env::var("JWT_SECRET").expect(
+    require_jwt_secret(env::var("JWT_SECRET").ok())
+}
+
+fn require_jwt_secret(secret: Option<String>) -> String {
+    secret.expect(
         "FATAL ERROR: JWT_SECRET must be set in .env file.\n\
          \n\
          The server cannot start without a secure JWT secret.\n\
@@ -32,6 +36,10 @@ fn get_jwt_secret() -> String {
 /// Génère un JWT token pour un utilisateur
 /// Expiration: 24 heures par défaut
 pub fn generate_token(user_id: i32, username: &str) -> Result<String, String> {
+    generate_token_with_secret(user_id, username, &get_jwt_secret())
+}
+
+fn generate_token_with_secret(user_id: i32, username: &str, secret: &str) -> Result<String, String> {
     let expiration = Utc::now()
         .checked_add_signed(Duration::hours(24))
         .ok_or("Failed to calculate expiration")?
@@ -43,8 +51,6 @@ pub fn generate_token(user_id: i32, username: &str) -> Result<String, String> {
         exp: expiration,
     };
 
-    let secret = get_jwt_secret();
-
     encode(
         &Header::default(),
         &claims,
@@ -55,8 +61,10 @@ pub fn generate_token(user_id: i32, username: &str) -> Result<String, String> {
 
 /// Vérifie et décode un JWT token
 pub fn verify_token(token: &str) -> Result<Claims, String> {
-    let secret = get_jwt_secret();
+    verify_token_with_secret(token, &get_jwt_secret())
+}
 
+fn verify_token_with_secret(token: &str, secret: &str) -> Result<Claims, String> {
     decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_ref()),
@@ -70,36 +78,30 @@ pub fn verify_token(token: &str) -> Result<Claims, String> {
 mod tests {
     use super::*;
 
+    const SECRET: &str = "test-secret-key-for-unit-tests-minimum-32-chars";
+
     #[test]
     fn test_generate_and_verify_token() {
-        std::env::set_var("JWT_SECRET", "test-secret-key-for-unit-tests-minimum-32-chars");
-
         let user_id = 123;
         let username = "testuser";
 
-        let token = generate_token(user_id, username).unwrap();
-        let claims = verify_token(&token).unwrap();
+        let token = generate_token_with_secret(user_id, username, SECRET).unwrap();
+        let claims = verify_token_with_secret(&token, SECRET).unwrap();
 
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.username, username);
-
-        std::env::remove_var("JWT_SECRET");
+        assert!(verify_token_with_secret(&token, "another-secret-key-minimum-32-characters").is_err());
     }
 
     #[test]
     fn test_invalid_token() {
-        std::env::set_var("JWT_SECRET", "test-secret-key-for-unit-tests-minimum-32-chars");
-
-        let result = verify_token("invalid.token.here");
+        let result = verify_token_with_secret("invalid.token.here", SECRET);
         assert!(result.is_err());
-
-        std::env::remove_var("JWT_SECRET");
     }
 
     #[test]
     #[should_panic(expected = "JWT_SECRET must be set")]
     fn test_missing_jwt_secret_panics() {
-        std::env::remove_var("JWT_SECRET");
-        get_jwt_secret();
+        require_jwt_secret(None);
     }
-}
\ No newline at end of file
+}
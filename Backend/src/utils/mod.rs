@@ -1,2 +1,12 @@
 pub mod password;
-pub mod jwt;
\ No newline at end of file
+pub mod jwt;
+pub mod dates;
+pub mod currency;
+pub mod http_cache;
+pub mod google;
+pub mod tls;
+pub mod webhook;
+pub mod trading_calendar;
+pub mod api_error;
+pub mod polars_convert;
+pub mod money;
\ No newline at end of file
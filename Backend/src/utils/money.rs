@@ -0,0 +1,66 @@
+// Arrondi des montants et des pourcentages : une seule règle, explicite.
+// round_dp de rust_decimal arrondit au pair (banker's : 0.125 → 0.12, 0.135 → 0.14), ce qui donne
+// des centimes différents selon la valeur ; toute la monnaie passe ici en arrondi commercial
+// (demi s'éloignant de zéro : 0.125 → 0.13, -0.125 → -0.13).
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Règle d'arrondi de toute la monnaie (P&L, prix moyens, pourcentages, stops)
+pub const ROUNDING_MODE: RoundingStrategy = RoundingStrategy::MidpointAwayFromZero;
+
+/// Décimales des montants et des pourcentages renvoyés par l'API
+pub const MONEY_DECIMALS: u32 = 2;
+pub const PERCENT_DECIMALS: u32 = 2;
+
+/// `value` arrondi à `decimals` selon ROUNDING_MODE
+pub fn round(value: Decimal, decimals: u32) -> Decimal {
+    value.round_dp_with_strategy(decimals, ROUNDING_MODE)
+}
+
+/// Montant arrondi au centime
+pub fn round_money(amount: Decimal) -> Decimal {
+    round(amount, MONEY_DECIMALS)
+}
+
+/// Variation en % de `base` à `value`, non arrondie (0 si `base` est nul ou négatif)
+pub fn percentage_change(base: Decimal, value: Decimal) -> Decimal {
+    if base <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    (value - base) * Decimal::ONE_HUNDRED / base
+}
+
+/// Pourcentage arrondi à PERCENT_DECIMALS, en f64 pour les réponses JSON
+pub fn percent_f64(percentage: Decimal) -> f64 {
+    round(percentage, PERCENT_DECIMALS).to_f64().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn half_cent_rounds_away_from_zero() {
+        // round_dp donnerait 0.12 / 2.34 / -0.12 (arrondi au pair)
+        assert_eq!(round_money(dec("0.125")), dec("0.13"));
+        assert_eq!(round_money(dec("2.345")), dec("2.35"));
+        assert_eq!(round_money(dec("-0.125")), dec("-0.13"));
+        assert_eq!(round_money(dec("0.135")), dec("0.14"));
+        assert_eq!(round_money(dec("0.1249")), dec("0.12"));
+        assert_eq!(round_money(dec("0.005")), dec("0.01"));
+    }
+
+    #[test]
+    fn percentages_use_the_same_rule() {
+        // 100 → 100.125 : +0.125 %
+        assert_eq!(percent_f64(percentage_change(dec("100"), dec("100.125"))), 0.13);
+        assert_eq!(percent_f64(percentage_change(dec("100"), dec("99.875"))), -0.13);
+        assert_eq!(percentage_change(Decimal::ZERO, dec("10")), Decimal::ZERO);
+    }
+}
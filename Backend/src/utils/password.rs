@@ -3,22 +3,64 @@ use pbkdf2::pbkdf2;
 use sha2::Sha256;
 use rand::Rng;
 use base64::{Engine, engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD}};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::{PasswordHash, SaltString};
+use std::env;
+use std::sync::OnceLock;
 
 type HmacSha256 = Hmac<Sha256>;
 
-const ITERATIONS: u32 = 260000;
+const DEFAULT_ITERATIONS: u32 = 260000;
 const KEY_LENGTH: usize = 32;
 
-/// Hash un mot de passe au format Werkzeug (compatible Python)
-/// Utilise PBKDF2-HMAC-SHA256 avec 260000 itérations et un salt de 16 bytes
+/// Schéma utilisé pour hasher les NOUVEAUX mots de passe
+/// Les anciens hashs restent vérifiables quel que soit le schéma courant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasswordScheme {
+    Pbkdf2,  // Format Werkzeug (compatible Python)
+    Argon2,  // Argon2id, format PHC ($argon2id$...)
+}
+
+/// Nombre d'itérations PBKDF2 via PBKDF2_ITERATIONS (défaut: 260000, comme Werkzeug)
+pub fn pbkdf2_iterations() -> u32 {
+    env::var("PBKDF2_ITERATIONS")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_ITERATIONS)
+}
+
+/// Schéma courant via PASSWORD_SCHEME ("pbkdf2" par défaut, "argon2" pour Argon2id)
+pub fn current_scheme() -> PasswordScheme {
+    match env::var("PASSWORD_SCHEME").unwrap_or_default().to_lowercase().as_str() {
+        "argon2" | "argon2id" => PasswordScheme::Argon2,
+        _ => PasswordScheme::Pbkdf2,
+    }
+}
+
+/// Hash un mot de passe avec le schéma configuré (PASSWORD_SCHEME / PBKDF2_ITERATIONS)
 pub fn hash_password(password: &str) -> Result<String, String> {
+    hash_password_with(password, current_scheme(), pbkdf2_iterations())
+}
+
+/// Hash un mot de passe avec un schéma et un nombre d'itérations explicites
+pub fn hash_password_with(password: &str, scheme: PasswordScheme, iterations: u32) -> Result<String, String> {
+    match scheme {
+        PasswordScheme::Pbkdf2 => hash_pbkdf2(password, iterations),
+        PasswordScheme::Argon2 => hash_argon2(password),
+    }
+}
+
+/// Hash un mot de passe au format Werkzeug (compatible Python)
+/// Utilise PBKDF2-HMAC-SHA256 avec un salt de 16 bytes
+fn hash_pbkdf2(password: &str, iterations: u32) -> Result<String, String> {
     // Générer un salt aléatoire de 16 bytes
     let mut salt = [0u8; 16];
     rand::thread_rng().fill(&mut salt);
 
     // Calculer le hash PBKDF2
     let mut key = [0u8; KEY_LENGTH];
-    pbkdf2::<HmacSha256>(password.as_bytes(), &salt, ITERATIONS, &mut key)
+    pbkdf2::<HmacSha256>(password.as_bytes(), &salt, iterations, &mut key)
         .expect("PBKDF2 hash generation failed");
 
     // Encoder en base64 URL-safe sans padding (format Werkzeug moderne)
@@ -26,12 +68,76 @@ pub fn hash_password(password: &str) -> Result<String, String> {
     let hash_b64 = URL_SAFE_NO_PAD.encode(key);
 
     // Format: pbkdf2:sha256:iterations$salt$hash
-    Ok(format!("pbkdf2:sha256:{}${}${}", ITERATIONS, salt_b64, hash_b64))
+    Ok(format!("pbkdf2:sha256:{}${}${}", iterations, salt_b64, hash_b64))
 }
 
-/// Vérifie un mot de passe contre un hash Werkzeug
-/// Supporte les formats: base64 (nouveau) et hex (ancien Python)
+/// Hash un mot de passe en Argon2id (paramètres par défaut de la crate argon2)
+fn hash_argon2(password: &str) -> Result<String, String> {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill(&mut salt);
+
+    let salt_string = SaltString::encode_b64(&salt)
+        .map_err(|e| format!("Argon2 salt error: {}", e))?;
+
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt_string)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Argon2 hash generation failed: {}", e))
+}
+
+/// Vérifie un mot de passe contre un hash stocké
+/// Supporte: Argon2id ($argon2id$...) et Werkzeug PBKDF2 (base64 nouveau / hex ancien Python)
 pub fn verify_password(password: &str, stored_hash: &str) -> Result<bool, String> {
+    if stored_hash.starts_with("$argon2") {
+        return verify_argon2(password, stored_hash);
+    }
+
+    verify_pbkdf2(password, stored_hash)
+}
+
+/// Indique si un hash valide doit être recalculé avec la configuration courante
+/// (à appeler après un login réussi pour migrer transparentement les anciens hashs)
+pub fn needs_rehash(stored_hash: &str) -> bool {
+    needs_rehash_with(stored_hash, current_scheme(), pbkdf2_iterations())
+}
+
+/// needs_rehash avec un schéma et un nombre d'itérations explicites
+/// - schéma différent du schéma courant → rehash
+/// - PBKDF2 avec moins d'itérations que configuré → rehash
+pub fn needs_rehash_with(stored_hash: &str, scheme: PasswordScheme, iterations: u32) -> bool {
+    let is_argon2 = stored_hash.starts_with("$argon2");
+
+    match scheme {
+        PasswordScheme::Argon2 => !is_argon2,
+        PasswordScheme::Pbkdf2 => {
+            if is_argon2 {
+                return true;
+            }
+            match parse_pbkdf2_iterations(stored_hash) {
+                Some(stored_iterations) => stored_iterations < iterations,
+                None => false,
+            }
+        }
+    }
+}
+
+fn verify_argon2(password: &str, stored_hash: &str) -> Result<bool, String> {
+    let parsed = PasswordHash::new(stored_hash)
+        .map_err(|e| format!("Invalid Argon2 hash: {}", e))?;
+
+    Ok(Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+/// Extrait les itérations du header "pbkdf2:sha256:iterations$..."
+fn parse_pbkdf2_iterations(stored_hash: &str) -> Option<u32> {
+    let header = stored_hash.split('$').next()?;
+    header.split(':').nth(2)?.parse::<u32>().ok()
+}
+
+/// Vérifie un mot de passe contre un hash Werkzeug
+fn verify_pbkdf2(password: &str, stored_hash: &str) -> Result<bool, String> {
     // Parser le format: pbkdf2:sha256:iterations$salt$hash
     let parts: Vec<&str> = stored_hash.split('$').collect();
     if parts.len() != 3 {
@@ -62,7 +168,27 @@ pub fn verify_password(password: &str, stored_hash: &str) -> Result<bool, String
         .expect("PBKDF2 hash verification failed");
 
     // Comparer les hashs (constant-time pour éviter timing attacks)
-    Ok(computed == expected_hash)
+    Ok(constant_time_eq(&computed, &expected_hash))
+}
+
+/// Comparaison en temps constant (ne s'arrête pas au premier octet différent)
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Exécute une vérification factice quand l'utilisateur n'existe pas
+/// Égalise le temps de réponse du login pour ne pas révéler quels comptes existent
+pub fn dummy_verify(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+
+    let dummy_hash = DUMMY_HASH.get_or_init(|| {
+        hash_password("dummy-password-for-timing").unwrap_or_default()
+    });
+
+    let _ = verify_password(password, dummy_hash);
 }
 
 /// Décode une chaîne encodée en base64 ou hexadécimal
@@ -100,4 +226,50 @@ fn decode_flexible(input: &str) -> Result<Vec<u8>, String> {
 fn add_base64_padding(input: &str) -> String {
     let padding_needed = (4 - (input.len() % 4)) % 4;
     format!("{}{}", input, "=".repeat(padding_needed))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Peu d'itérations pour garder les tests rapides
+    const TEST_ITERATIONS: u32 = 1000;
+
+    #[test]
+    fn test_verify_pbkdf2_hash() {
+        let hash = hash_password_with("secret", PasswordScheme::Pbkdf2, TEST_ITERATIONS).unwrap();
+
+        assert!(hash.starts_with("pbkdf2:sha256:1000$"));
+        assert!(verify_password("secret", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_verify_argon2_hash() {
+        let hash = hash_password_with("secret", PasswordScheme::Argon2, TEST_ITERATIONS).unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("secret", &hash).unwrap());
+        assert!(!verify_password("wrong", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_upgrade_path_from_pbkdf2_to_argon2() {
+        let old_hash = hash_password_with("secret", PasswordScheme::Pbkdf2, TEST_ITERATIONS).unwrap();
+
+        // Ancien hash PBKDF2 → doit être migré quand le schéma courant est Argon2
+        assert!(needs_rehash_with(&old_hash, PasswordScheme::Argon2, TEST_ITERATIONS));
+
+        let new_hash = hash_password_with("secret", PasswordScheme::Argon2, TEST_ITERATIONS).unwrap();
+        assert!(verify_password("secret", &new_hash).unwrap());
+        assert!(!needs_rehash_with(&new_hash, PasswordScheme::Argon2, TEST_ITERATIONS));
+    }
+
+    #[test]
+    fn test_upgrade_path_for_pbkdf2_iterations() {
+        let old_hash = hash_password_with("secret", PasswordScheme::Pbkdf2, TEST_ITERATIONS).unwrap();
+
+        assert!(needs_rehash_with(&old_hash, PasswordScheme::Pbkdf2, TEST_ITERATIONS * 2));
+        assert!(!needs_rehash_with(&old_hash, PasswordScheme::Pbkdf2, TEST_ITERATIONS));
+    }
+}
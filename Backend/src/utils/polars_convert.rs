@@ -0,0 +1,56 @@
+// Conversions typées des cellules polars (AnyValue) : chaque variante est traitée
+// explicitement au lieu de passer par Display (qui entoure les chaînes de guillemets
+// et affiche "null" pour une cellule vide).
+
+use polars::prelude::AnyValue;
+
+/// Texte d'une cellule, None si null.
+/// Chaînes (y compris catégorielles) telles quelles, nombres / booléens / dates via leur forme usuelle ;
+/// Err pour les types sans forme texte fiable (listes, structs, binaire…)
+pub fn anyvalue_to_string(value: &AnyValue) -> Result<Option<String>, String> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    if let Some(s) = value.get_str() {
+        return Ok(Some(s.to_string()));
+    }
+
+    match value {
+        AnyValue::Boolean(_) | AnyValue::Date(_) => Ok(Some(value.to_string())),
+        v if v.is_numeric() => Ok(Some(v.to_string())),
+        other => Err(format!("Unsupported value type: {}", other.dtype())),
+    }
+}
+
+/// Nombre fini d'une cellule numérique (float ou entier), None si null, NaN / ±Infinity ou non numérique
+pub fn anyvalue_to_f64_opt(value: &AnyValue) -> Option<f64> {
+    if !value.is_numeric() {
+        return None;
+    }
+    value.extract::<f64>().filter(|v| v.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_are_not_quoted() {
+        assert_eq!(anyvalue_to_string(&AnyValue::String("AAPL")), Ok(Some("AAPL".to_string())));
+        assert_eq!(anyvalue_to_string(&AnyValue::StringOwned("2025-01-02".into())), Ok(Some("2025-01-02".to_string())));
+        assert_eq!(anyvalue_to_string(&AnyValue::Null), Ok(None));
+        assert_eq!(anyvalue_to_string(&AnyValue::Int64(42)), Ok(Some("42".to_string())));
+        assert_eq!(anyvalue_to_string(&AnyValue::Float64(1.5)), Ok(Some("1.5".to_string())));
+        assert!(anyvalue_to_string(&AnyValue::Binary(b"x")).is_err());
+    }
+
+    #[test]
+    fn numbers_are_finite_floats() {
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::Float64(42.5)), Some(42.5));
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::Int32(7)), Some(7.0));
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::UInt64(3)), Some(3.0));
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::Float64(f64::NAN)), None);
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::Null), None);
+        assert_eq!(anyvalue_to_f64_opt(&AnyValue::String("12.5")), None);
+    }
+}
@@ -0,0 +1,95 @@
+// TLS optionnel du serveur HTTP (rustls).
+// TLS_CERT_PATH + TLS_KEY_PATH définis → HTTPS ; aucun des deux → HTTP en clair (dev local).
+// Un seul des deux défini est une erreur de configuration : le serveur refuse de démarrer
+// plutôt que de retomber silencieusement en HTTP.
+
+use rustls::ServerConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::sync::Arc;
+
+/// Chemins du certificat (chaîne PEM) et de la clé privée (PEM)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsPaths {
+    pub cert_path: String,
+    pub key_path: String,
+}
+
+/// Some(paths) si les deux variables sont renseignées, None si aucune, erreur si une seule
+pub fn tls_paths(cert_path: Option<String>, key_path: Option<String>) -> Result<Option<TlsPaths>, String> {
+    let non_empty = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+
+    match (non_empty(cert_path), non_empty(key_path)) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsPaths { cert_path, key_path })),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err("TLS_CERT_PATH is set but TLS_KEY_PATH is missing".to_string()),
+        (None, Some(_)) => Err("TLS_KEY_PATH is set but TLS_CERT_PATH is missing".to_string()),
+    }
+}
+
+/// Lit TLS_CERT_PATH / TLS_KEY_PATH
+pub fn tls_paths_from_env() -> Result<Option<TlsPaths>, String> {
+    tls_paths(std::env::var("TLS_CERT_PATH").ok(), std::env::var("TLS_KEY_PATH").ok())
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Charge le certificat et la clé et construit la configuration rustls du serveur
+pub fn load_server_config(paths: &TlsPaths) -> io::Result<ServerConfig> {
+    let open = |path: &str| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to open {}: {}", path, e)))
+    };
+
+    let certs: Vec<CertificateDer<'static>> = rustls_pemfile::certs(&mut open(&paths.cert_path)?)
+        .collect::<Result<_, _>>()
+        .map_err(|e| invalid_data(format!("Invalid certificate in {}: {}", paths.cert_path, e)))?;
+    if certs.is_empty() {
+        return Err(invalid_data(format!("No certificate found in {}", paths.cert_path)));
+    }
+
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut open(&paths.key_path)?)
+        .map_err(|e| invalid_data(format!("Invalid private key in {}: {}", paths.key_path, e)))?
+        .ok_or_else(|| invalid_data(format!("No private key found in {}", paths.key_path)))?;
+
+    // Fournisseur crypto explicite : évite le panic si plusieurs fournisseurs sont compilés
+    ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| invalid_data(format!("TLS configuration error: {}", e)))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| invalid_data(format!("Certificate / key mismatch: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_requires_both_paths() {
+        assert_eq!(tls_paths(None, None), Ok(None));
+        assert_eq!(tls_paths(Some(" ".into()), None), Ok(None));
+        assert_eq!(
+            tls_paths(Some("cert.pem".into()), Some("key.pem".into())),
+            Ok(Some(TlsPaths { cert_path: "cert.pem".into(), key_path: "key.pem".into() }))
+        );
+        assert!(tls_paths(Some("cert.pem".into()), None).is_err());
+        assert!(tls_paths(None, Some("key.pem".into())).is_err());
+    }
+
+    #[test]
+    fn test_missing_or_empty_pem_files_are_rejected() {
+        let missing = TlsPaths { cert_path: "/nonexistent/cert.pem".into(), key_path: "/nonexistent/key.pem".into() };
+        assert_eq!(load_server_config(&missing).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        let empty = std::env::temp_dir().join("tls_test_empty.pem");
+        std::fs::write(&empty, "").unwrap();
+        let path = empty.to_string_lossy().to_string();
+        let err = load_server_config(&TlsPaths { cert_path: path.clone(), key_path: path }).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,239 @@
+// ============================================================================
+// UTILS : CALENDRIER DE BOURSE
+// ============================================================================
+//
+// Description:
+//   Jours de bourse par place de cotation : week-ends et jours fériés exclus.
+//   Sert à compter une durée de détention en séances plutôt qu'en jours
+//   calendaires (un achat le vendredi revendu le lundi = 1 séance, pas 3).
+//
+// Jours fériés embarqués (calculés par règle pour chaque année):
+//   - NYSE : New Year's Day, Martin Luther King Jr. Day, Washington's Birthday,
+//     Good Friday, Memorial Day, Juneteenth (depuis 2022), Independence Day,
+//     Labor Day, Thanksgiving, Christmas + fermetures exceptionnelles
+//     (NYSE_SPECIAL_CLOSURES). Férié un samedi → vendredi, un dimanche → lundi
+//     (sauf le 1er janvier tombant un samedi : pas de report)
+//   - TSX : Jour de l'An, Family Day, Vendredi saint, fête de la Reine
+//     (Victoria Day), fête du Canada, Civic Holiday, fête du Travail,
+//     Action de grâce, Noël, Boxing Day. Férié un week-end → jour ouvré suivant
+//
+// Points d'attention:
+//   - La place se déduit de la devise : USD → NYSE, CAD → TSX, autres devises
+//     → week-ends seulement (pas de jours fériés connus)
+//   - Les fermetures exceptionnelles futures (deuil national, intempéries) sont
+//     à ajouter à la main dans NYSE_SPECIAL_CLOSURES
+//
+// ============================================================================
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::{HashMap, HashSet};
+
+use crate::utils::currency;
+
+/// Place de cotation dont on suit le calendrier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exchange {
+    Nyse,
+    Tsx,
+    WeekdaysOnly, // Place inconnue : seuls les week-ends sont exclus
+}
+
+impl Exchange {
+    /// Place déduite de la devise du trade / du stock (devise absente → devise par défaut, CAD)
+    pub fn from_currency(currency: Option<&str>) -> Self {
+        let currency = currency
+            .map(|c| c.trim().to_uppercase())
+            .unwrap_or_else(|| currency::DEFAULT_CURRENCY.to_string());
+
+        match currency.as_str() {
+            "USD" => Exchange::Nyse,
+            "CAD" => Exchange::Tsx,
+            _ => Exchange::WeekdaysOnly,
+        }
+    }
+}
+
+/// Fermetures exceptionnelles du NYSE (hors jours fériés annuels)
+const NYSE_SPECIAL_CLOSURES: &[(i32, u32, u32)] = &[
+    (2012, 10, 29), // Ouragan Sandy
+    (2012, 10, 30),
+    (2018, 12, 5),  // Funérailles de George H. W. Bush
+    (2025, 1, 9),   // Funérailles de Jimmy Carter
+];
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("valid calendar date")
+}
+
+fn is_weekend(day: NaiveDate) -> bool {
+    matches!(day.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// n-ième `weekday` du mois (n = 1 pour le premier)
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid nth weekday")
+}
+
+/// Dernier `weekday` tombant au plus tard le `day` du mois
+fn last_weekday_on_or_before(year: i32, month: u32, day: u32, weekday: Weekday) -> NaiveDate {
+    let limit = date(year, month, day);
+    let back = (7 + limit.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    limit - Duration::days(back as i64)
+}
+
+/// Dimanche de Pâques (algorithme grégorien anonyme)
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    date(year, month as u32, day as u32)
+}
+
+/// Report NYSE : samedi → vendredi, dimanche → lundi
+fn nyse_observed(day: NaiveDate) -> NaiveDate {
+    match day.weekday() {
+        Weekday::Sat => day - Duration::days(1),
+        Weekday::Sun => day + Duration::days(1),
+        _ => day,
+    }
+}
+
+/// Report TSX : premier jour de semaine à partir de `day`
+fn next_weekday_from(mut day: NaiveDate) -> NaiveDate {
+    while is_weekend(day) {
+        day += Duration::days(1);
+    }
+    day
+}
+
+/// Jours fériés (dates de fermeture effectives) d'une place pour une année
+pub fn holidays(exchange: Exchange, year: i32) -> HashSet<NaiveDate> {
+    let good_friday = easter_sunday(year) - Duration::days(2);
+
+    match exchange {
+        Exchange::Nyse => {
+            let mut days = vec![
+                nth_weekday(year, 1, Weekday::Mon, 3),  // Martin Luther King Jr. Day
+                nth_weekday(year, 2, Weekday::Mon, 3),  // Washington's Birthday
+                good_friday,
+                last_weekday_on_or_before(year, 5, 31, Weekday::Mon), // Memorial Day
+                nyse_observed(date(year, 7, 4)),
+                nth_weekday(year, 9, Weekday::Mon, 1),  // Labor Day
+                nth_weekday(year, 11, Weekday::Thu, 4), // Thanksgiving
+                nyse_observed(date(year, 12, 25)),
+            ];
+            // 1er janvier un samedi : pas de report au vendredi 31 décembre
+            let new_year = date(year, 1, 1);
+            if new_year.weekday() != Weekday::Sat {
+                days.push(nyse_observed(new_year));
+            }
+            if year >= 2022 {
+                days.push(nyse_observed(date(year, 6, 19))); // Juneteenth
+            }
+            days.extend(
+                NYSE_SPECIAL_CLOSURES
+                    .iter()
+                    .filter(|(y, _, _)| *y == year)
+                    .map(|(y, m, d)| date(*y, *m, *d)),
+            );
+            days.into_iter().collect()
+        }
+        Exchange::Tsx => {
+            let christmas = next_weekday_from(date(year, 12, 25));
+            let boxing_day = next_weekday_from((christmas + Duration::days(1)).max(date(year, 12, 26)));
+            [
+                next_weekday_from(date(year, 1, 1)),
+                nth_weekday(year, 2, Weekday::Mon, 3), // Family Day
+                good_friday,
+                last_weekday_on_or_before(year, 5, 24, Weekday::Mon), // Victoria Day
+                next_weekday_from(date(year, 7, 1)),                 // Fête du Canada
+                nth_weekday(year, 8, Weekday::Mon, 1),               // Civic Holiday
+                nth_weekday(year, 9, Weekday::Mon, 1),               // Fête du Travail
+                nth_weekday(year, 10, Weekday::Mon, 2),              // Action de grâce
+                christmas,
+                boxing_day,
+            ]
+            .into_iter()
+            .collect()
+        }
+        Exchange::WeekdaysOnly => HashSet::new(),
+    }
+}
+
+/// Nombre de séances de bourse dans ]start, end] : 0 le jour même, 1 du vendredi au lundi suivant,
+/// 0 si end précède start
+pub fn trading_days_between(start: NaiveDate, end: NaiveDate, exchange: Exchange) -> i32 {
+    let mut holidays_by_year: HashMap<i32, HashSet<NaiveDate>> = HashMap::new();
+    let mut count = 0;
+    let mut day = start;
+
+    while day < end {
+        day += Duration::days(1);
+        if is_weekend(day) {
+            continue;
+        }
+        let closed = holidays_by_year
+            .entry(day.year())
+            .or_insert_with(|| holidays(exchange, day.year()))
+            .contains(&day);
+        if !closed {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekend_is_skipped() {
+        // Vendredi 13 → lundi 16 juin 2025 : une seule séance
+        assert_eq!(trading_days_between(date(2025, 6, 13), date(2025, 6, 16), Exchange::Nyse), 1);
+        assert_eq!(trading_days_between(date(2025, 6, 13), date(2025, 6, 13), Exchange::Tsx), 0);
+        assert_eq!(trading_days_between(date(2025, 6, 16), date(2025, 6, 13), Exchange::Tsx), 0);
+        // Deux semaines complètes, sans férié
+        assert_eq!(trading_days_between(date(2025, 6, 2), date(2025, 6, 16), Exchange::WeekdaysOnly), 10);
+    }
+
+    #[test]
+    fn test_known_holidays_per_exchange() {
+        // Independence Day (vendredi 4 juillet 2025) : NYSE fermé, TSX ouvert
+        assert_eq!(trading_days_between(date(2025, 7, 3), date(2025, 7, 7), Exchange::Nyse), 1);
+        assert_eq!(trading_days_between(date(2025, 7, 3), date(2025, 7, 7), Exchange::Tsx), 2);
+
+        // Vendredi saint 2025 (18 avril) : fermé des deux côtés
+        assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+        assert_eq!(trading_days_between(date(2025, 4, 17), date(2025, 4, 21), Exchange::Nyse), 1);
+        assert_eq!(trading_days_between(date(2025, 4, 17), date(2025, 4, 21), Exchange::Tsx), 1);
+
+        // Noël 2021 un samedi : NYSE ferme le vendredi 24, TSX les lundi 27 et mardi 28
+        assert!(holidays(Exchange::Nyse, 2021).contains(&date(2021, 12, 24)));
+        let tsx = holidays(Exchange::Tsx, 2021);
+        assert!(tsx.contains(&date(2021, 12, 27)) && tsx.contains(&date(2021, 12, 28)));
+
+        // Victoria Day 2025 : lundi 19 mai
+        assert!(holidays(Exchange::Tsx, 2025).contains(&date(2025, 5, 19)));
+    }
+
+    #[test]
+    fn test_exchange_from_currency() {
+        assert_eq!(Exchange::from_currency(Some("usd")), Exchange::Nyse);
+        assert_eq!(Exchange::from_currency(Some("CAD")), Exchange::Tsx);
+        assert_eq!(Exchange::from_currency(None), Exchange::Tsx);
+        assert_eq!(Exchange::from_currency(Some("EUR")), Exchange::WeekdaysOnly);
+    }
+}
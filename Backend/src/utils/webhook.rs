@@ -0,0 +1,166 @@
+// ============================================================================
+// UTILS : SIGNATURE DES WEBHOOKS SORTANTS
+// ============================================================================
+//
+// Description:
+//   Signature HMAC-SHA256 des alertes POSTées vers les webhooks des abonnés.
+//   Chaque abonnement a son propre secret ; le destinataire recalcule la
+//   signature pour vérifier que l'alerte vient bien de nous et n'a pas été
+//   modifiée ni rejouée.
+//
+// En-têtes envoyés avec chaque POST:
+//   X-Signature-Timestamp: 1735689600          (secondes Unix à l'envoi)
+//   X-Signature: sha256=<hex>                  (HMAC-SHA256 de "<timestamp>.<body>")
+//
+// Vérification côté destinataire:
+//   1. Lire X-Signature-Timestamp et le corps BRUT de la requête (avant parsing JSON)
+//   2. Calculer HMAC-SHA256(secret, "<timestamp>.<body>") en hexadécimal
+//   3. Comparer à X-Signature (sans le préfixe "sha256=") en temps constant
+//   4. Refuser si le timestamp est à plus de 5 minutes de l'heure courante :
+//      le timestamp est signé, une alerte capturée ne peut donc pas être
+//      rejouée plus tard avec un nouvel horodatage
+//
+// Points d'attention:
+//   - Une config de livraison sans secret est refusée (WebhookConfig::new)
+//   - Le chemin d'envoi des alertes (Version 3) n'existe pas encore : ce module
+//     fournit la signature et la vérification qu'il utilisera
+//
+// ============================================================================
+
+// Pas encore appelé hors des tests tant que l'envoi des alertes n'existe pas
+#![allow(dead_code)]
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// Préfixe de la valeur de X-Signature (algorithme)
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Écart maximal accepté entre le timestamp signé et l'heure de réception
+pub const DEFAULT_TOLERANCE_SECONDS: i64 = 300;
+
+/// Longueur minimale d'un secret d'abonnement
+pub const MIN_SECRET_LENGTH: usize = 16;
+
+/// Config de livraison d'un abonnement webhook
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    secret: String,
+}
+
+impl WebhookConfig {
+    /// Refuse une URL non HTTP(S) et un secret absent ou trop court
+    pub fn new(url: &str, secret: Option<&str>) -> Result<Self, String> {
+        let url = url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err("Webhook URL must start with http:// or https://".to_string());
+        }
+
+        let secret = secret.map(str::trim).unwrap_or_default();
+        if secret.is_empty() {
+            return Err("Webhook secret is required".to_string());
+        }
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(format!("Webhook secret must be at least {} characters", MIN_SECRET_LENGTH));
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            secret: secret.to_string(),
+        })
+    }
+
+    /// En-têtes (nom, valeur) à joindre au POST de `body` envoyé à `timestamp`
+    pub fn signed_headers(&self, body: &[u8], timestamp: i64) -> [(&'static str, String); 2] {
+        [
+            (TIMESTAMP_HEADER, timestamp.to_string()),
+            (SIGNATURE_HEADER, format!("{}{}", SIGNATURE_PREFIX, sign(&self.secret, timestamp, body))),
+        ]
+    }
+}
+
+fn mac_for(secret: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    // HMAC accepte une clé de n'importe quelle longueur
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// HMAC-SHA256 hexadécimal de "<timestamp>.<body>"
+pub fn sign(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    hex::encode(mac_for(secret, timestamp, body).finalize().into_bytes())
+}
+
+/// Vérifie une signature reçue (avec ou sans préfixe "sha256=") en temps constant,
+/// et refuse un timestamp hors de la fenêtre de tolérance
+pub fn verify(
+    secret: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+    tolerance_seconds: i64,
+) -> Result<(), String> {
+    if (now - timestamp).abs() > tolerance_seconds {
+        return Err("Signature timestamp outside tolerance".to_string());
+    }
+
+    let signature = signature.trim();
+    let expected = hex::decode(signature.strip_prefix(SIGNATURE_PREFIX).unwrap_or(signature))
+        .map_err(|_| "Malformed signature".to_string())?;
+
+    mac_for(secret, timestamp, body)
+        .verify_slice(&expected)
+        .map_err(|_| "Invalid signature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_0123456789abcdef";
+    const BODY: &[u8] = br#"{"symbol":"AAPL","signal":"BUY"}"#;
+    const TS: i64 = 1_735_689_600;
+
+    #[test]
+    fn test_signature_is_deterministic_and_bound_to_inputs() {
+        let signature = sign(SECRET, TS, BODY);
+        assert_eq!(signature, sign(SECRET, TS, BODY));
+        assert_eq!(signature.len(), 64);
+
+        assert_ne!(signature, sign(SECRET, TS, br#"{"symbol":"AAPL","signal":"SELL"}"#));
+        assert_ne!(signature, sign("whsec_fedcba9876543210", TS, BODY));
+        // Timestamp signé : on ne peut pas rejouer le corps avec un nouvel horodatage
+        assert_ne!(signature, sign(SECRET, TS + 1, BODY));
+    }
+
+    #[test]
+    fn test_verify_round_trip_and_replay_window() {
+        let config = WebhookConfig::new("https://example.com/hook", Some(SECRET)).unwrap();
+        let [(ts_name, ts_value), (sig_name, sig_value)] = config.signed_headers(BODY, TS);
+        assert_eq!((ts_name, sig_name), (TIMESTAMP_HEADER, SIGNATURE_HEADER));
+        assert_eq!(ts_value, TS.to_string());
+        assert!(sig_value.starts_with("sha256="));
+
+        assert_eq!(verify(SECRET, TS, BODY, &sig_value, TS + 10, DEFAULT_TOLERANCE_SECONDS), Ok(()));
+        assert!(verify(SECRET, TS, b"tampered", &sig_value, TS, DEFAULT_TOLERANCE_SECONDS).is_err());
+        assert!(verify(SECRET, TS, BODY, &sig_value, TS + 301, DEFAULT_TOLERANCE_SECONDS).is_err());
+        assert!(verify(SECRET, TS, BODY, "sha256=not-hex", TS, DEFAULT_TOLERANCE_SECONDS).is_err());
+    }
+
+    #[test]
+    fn test_config_requires_secret() {
+        assert!(WebhookConfig::new("https://example.com/hook", None).is_err());
+        assert!(WebhookConfig::new("https://example.com/hook", Some("   ")).is_err());
+        assert!(WebhookConfig::new("https://example.com/hook", Some("short")).is_err());
+        assert!(WebhookConfig::new("ftp://example.com/hook", Some(SECRET)).is_err());
+    }
+}